@@ -0,0 +1,117 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use sn_api::{
+    files::{FileInfo, FilesMap, SharedFilesMap},
+    Keypair,
+};
+
+// Numbers of files of the FilesMaps benched
+const FILES_COUNTS: [usize; 3] = [10, 1_000, 10_000];
+
+// A FilesMap of the given number of files, with the metadata a FilesContainer stores for them
+fn files_map(files_count: usize) -> FilesMap {
+    (0..files_count)
+        .map(|i| {
+            let file_info: FileInfo = [
+                ("type", "text/plain".to_string()),
+                ("size", format!("{}", i * 1024)),
+                ("created", "2022-05-20T10:39:24Z".to_string()),
+                ("modified", "2022-05-20T10:39:24Z".to_string()),
+                (
+                    "link",
+                    format!(
+                        "safe://hbyyyydx{}nrc4ttqmq1g1nq7e8eu9yy4a3p8bh5p4kn8zbm8n9jjn9j",
+                        i
+                    ),
+                ),
+            ]
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+            (format!("/dir-{}/file-{}.txt", i % 10, i), file_info)
+        })
+        .collect()
+}
+
+fn bench_files_map_serialisation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("files_map");
+
+    for files_count in FILES_COUNTS {
+        let files_map = files_map(files_count);
+        let serialised = serde_json::to_vec(&files_map).expect("failed to serialise FilesMap");
+        group.throughput(Throughput::Elements(files_count as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("serialise", files_count),
+            &files_map,
+            |b, files_map| {
+                b.iter(|| serde_json::to_vec(files_map).expect("failed to serialise FilesMap"))
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("deserialise", files_count),
+            &serialised,
+            |b, serialised| {
+                b.iter(|| {
+                    serde_json::from_slice::<FilesMap>(serialised)
+                        .expect("failed to deserialise FilesMap")
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn bench_shared_files_map_merge(c: &mut Criterion) {
+    let mut group = c.benchmark_group("shared_files_map");
+    let writer = Keypair::new_ed25519().public_key();
+    let other_writer = Keypair::new_ed25519().public_key();
+
+    for files_count in FILES_COUNTS {
+        let files_map = files_map(files_count);
+        let ours = SharedFilesMap::from_files_map(&files_map, writer, 1);
+
+        // the other replica updated every other file, and removed a tenth of them
+        let mut theirs = ours.clone();
+        for (i, (path, file_info)) in files_map.iter().enumerate() {
+            if i % 10 == 0 {
+                theirs.remove(path, other_writer, 2);
+            } else if i % 2 == 0 {
+                theirs.insert(path, file_info.clone(), other_writer, 2);
+            }
+        }
+        group.throughput(Throughput::Elements(files_count as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("merge", files_count),
+            &theirs,
+            |b, theirs| {
+                b.iter_batched(
+                    || ours.clone(),
+                    |mut ours| {
+                        ours.merge(theirs);
+                        ours
+                    },
+                    BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_files_map_serialisation,
+    bench_shared_files_map_merge
+);
+criterion_main!(benches);
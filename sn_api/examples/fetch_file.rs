@@ -8,7 +8,7 @@
 
 use bytes::Buf;
 use color_eyre::{eyre::eyre, Result};
-use sn_api::{resolver::SafeData, PublicKey, Safe};
+use sn_api::{resolver::SafeData, NodeConfig, PublicKey, Safe};
 use std::{collections::BTreeSet, env::args, net::SocketAddr};
 
 // To be executed passing Safe network contact address and file Safe URL, e.g.:
@@ -43,10 +43,10 @@ async fn main() -> Result<()> {
         .ok_or_else(|| eyre!("Unexpectedly failed to obtain (BLS) genesis key."))?;
     let mut nodes: BTreeSet<SocketAddr> = BTreeSet::new();
     nodes.insert(network_addr);
-    let node_config = (genesis_key, nodes);
+    let node_config = NodeConfig::new(genesis_key, nodes);
 
     // The Safe instance is what will give us access to the network API.
-    let safe = Safe::connected(node_config, None, None, None, None, None).await?;
+    let safe = Safe::connected(node_config, None, None, None, None, None, None).await?;
 
     println!("Connected to Safe!");
 
@@ -8,7 +8,7 @@
 
 use bytes::Buf;
 use color_eyre::{eyre::eyre, Result};
-use sn_api::{resolver::SafeData, PublicKey, Safe, SafeUrl};
+use sn_api::{files::SymlinkPolicy, resolver::SafeData, NodeConfig, PublicKey, Safe, SafeUrl};
 use std::{
     collections::BTreeSet, env::temp_dir, fs::File, io::Write, net::SocketAddr, path::PathBuf,
 };
@@ -34,19 +34,19 @@ async fn main() -> Result<()> {
     // Let's build the bootstrap config
     let mut nodes: BTreeSet<SocketAddr> = BTreeSet::new();
     nodes.insert("127.0.0.1:12000".parse()?);
-    let bootstrap_config = (genesis_key, nodes);
+    let bootstrap_config = NodeConfig::new(genesis_key, nodes);
 
     // The Safe instance is what will give us access to the network API.
-    let safe = Safe::connected(bootstrap_config, None, None, None, None, None).await?;
+    let safe = Safe::connected(bootstrap_config, None, None, None, None, None, None).await?;
 
     // We can now upload the file to the network, using the following information
     let dst = None; // root path at destination container
     let recursive = false; // do not do a recursive look up of files on local path
-    let follow_links = false; // do not attempt to follow local links
+    let symlink_policy = SymlinkPolicy::StoreAsLink; // do not attempt to follow local links
 
     println!("Uploading '{}' to Safe ...", file_path.display());
     let (xorurl, _, _) = safe
-        .files_container_create_from(&file_path, dst, recursive, follow_links)
+        .files_container_create_from(&file_path, dst, recursive, symlink_policy)
         .await?;
 
     // The 'files_container_create_from' API returns (among other information) the
@@ -12,7 +12,7 @@ use super::{
     Safe,
 };
 use crate::{
-    ipc::{IpcMsg, IpcResp},
+    ipc::{BootstrapInfo, IpcError, IpcMsg, IpcResp},
     Error, Result,
 };
 use log::{debug, info};
@@ -55,8 +55,21 @@ impl Safe {
         match IpcMsg::from_string(&auth_res) {
             Ok(IpcMsg::Resp(IpcResp::Auth(Ok(auth_granted)))) => {
                 info!("Application '{}' was authorised!", app_id);
+                if let Some(expiry) = auth_granted.expiry {
+                    debug!(
+                        "Authorisation of application '{}' lapses: {:?}",
+                        app_id, expiry
+                    );
+                }
                 Ok(auth_granted.app_keypair)
             }
+            Ok(IpcMsg::Resp(IpcResp::Auth(Err(IpcError::AuthExpired)))) => {
+                info!("Authorisation of application '{}' expired", app_id);
+                Err(Error::AuthExpired(format!(
+                    "Authorisation of application '{}' expired",
+                    app_id
+                )))
+            }
             Ok(other) => {
                 info!("Unexpected messages received: {:?}", other);
                 Err(Error::AuthError(format!(
@@ -73,10 +86,49 @@ impl Safe {
             }
         }
     }
+
+    /// Fetch up to date network bootstrap information from a SAFE Authenticator.
+    /// Apps are expected to call it whenever the bootstrap information they hold
+    /// is past its validity time, or they fail to connect to the network with it.
+    pub async fn fetch_bootstrap_info(
+        endpoint: Option<&str>,
+        authd_cert_path: impl AsRef<Path>,
+    ) -> Result<BootstrapInfo> {
+        info!("Sending bootstrap refresh request to SAFE Authenticator...");
+
+        let request = IpcMsg::new_bootstrap_refresh_req();
+        let refresh_req_str = request.to_string()?;
+
+        let refresh_res = send_app_auth_req(
+            &refresh_req_str,
+            endpoint,
+            &PathBuf::from(authd_cert_path.as_ref()),
+        )
+        .await?;
+
+        match IpcMsg::from_string(&refresh_res) {
+            Ok(IpcMsg::Resp(IpcResp::BootstrapRefresh(Ok(bootstrap_info)))) => {
+                debug!("Bootstrap information received: {:?}", bootstrap_info);
+                Ok(bootstrap_info)
+            }
+            Ok(IpcMsg::Resp(IpcResp::BootstrapRefresh(Err(err)))) => Err(Error::AuthError(
+                format!("Failed to obtain bootstrap information: {:?}", err),
+            )),
+            Ok(other) => Err(Error::AuthError(format!(
+                "Unexpected response received when requesting bootstrap information: {:?}",
+                other
+            ))),
+            Err(e) => Err(Error::AuthError(format!(
+                "Failed to decode bootstrap information response: {:?}",
+                e
+            ))),
+        }
+    }
 }
 
-// Sends an authorisation request string to the SAFE Authenticator daemon endpoint.
-// It returns the credentials necessary to connect to the network, encoded in a single string.
+// Sends an IPC request string, e.g. an authorisation request, to the SAFE Authenticator daemon
+// endpoint. It returns the response, e.g. the credentials necessary to connect to the network,
+// encoded in a single string.
 async fn send_app_auth_req(
     auth_req_str: &str,
     endpoint: Option<&str>,
@@ -0,0 +1,373 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::helpers::{bls_decrypt, bls_encrypt};
+use crate::{Error, Result};
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sn_interface::types::{
+    utils::{deserialise, passphrase_mac, passphrase_secret_key, serialise, verify_passphrase_mac},
+    Keypair,
+};
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+use tiny_keccak::{Hasher, Sha3};
+use tracing::{debug, info, warn};
+
+/// Version of the credentials file format written by [`Credentials::write`].
+///
+/// Version 1 files, i.e. a bare serialised keypair, are still read, and rewritten in the current
+/// format when loaded with [`Credentials::load`].
+pub const CREDENTIALS_FORMAT_VERSION: u8 = 2;
+
+const SALT_LEN: usize = 32;
+
+/// Reasons a credentials file can't be read.
+#[derive(Debug, Error)]
+pub enum CredentialsError {
+    /// The file was written in a format version we don't know of
+    #[error("unsupported credentials format version {0}")]
+    UnsupportedVersion(u8),
+    /// The checksum in the file doesn't match its content
+    #[error("checksum mismatch, the credentials file is corrupt")]
+    ChecksumMismatch,
+    /// The credentials are encrypted, but no passphrase was given
+    #[error("the credentials are encrypted, a passphrase is needed to read them")]
+    PassphraseRequired,
+    /// The credentials couldn't be decrypted with the passphrase given
+    #[error("the credentials couldn't be decrypted with the passphrase given")]
+    WrongPassphrase,
+    /// The file isn't a credentials file of any known version
+    #[error("the credentials file is corrupt: {0}")]
+    Corrupt(String),
+}
+
+/// A keypair persisted to a credentials file, along with some details about it.
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    /// The keypair the credentials give access to
+    pub keypair: Keypair,
+    /// Optional label, to tell credentials apart
+    pub label: Option<String>,
+    /// When the credentials were created, or migrated from a version 1 file
+    pub created: SystemTime,
+    /// When the credentials were last loaded
+    pub last_used: SystemTime,
+}
+
+// Just enough of the file to find out its format version
+#[derive(Deserialize)]
+struct VersionHeader {
+    version: u8,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CredentialsFile {
+    version: u8,
+    body: CredentialsBody,
+    // Hex encoded SHA3-256 of the serialised body
+    checksum: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CredentialsBody {
+    created: u64,
+    last_used: u64,
+    label: Option<String>,
+    // Hex encoded salt of the passphrase, only set when the keypair is encrypted
+    salt: Option<String>,
+    // Hex encoded MAC of the encrypted keypair, set along with the salt
+    mac: Option<String>,
+    // Hex encoded serialised keypair, encrypted when a salt is set
+    keypair: String,
+}
+
+impl Credentials {
+    /// Credentials for the given keypair, created and last used now.
+    pub fn new(keypair: Keypair, label: Option<String>) -> Self {
+        let now = SystemTime::now();
+        Self {
+            keypair,
+            label,
+            created: now,
+            last_used: now,
+        }
+    }
+
+    /// Write the credentials to a file at the given path, in the current format version.
+    ///
+    /// When a passphrase is given, the keypair is encrypted with a key derived from it, and the
+    /// same passphrase is needed to load the credentials back.
+    pub fn write(&self, path: impl AsRef<Path>, passphrase: Option<&str>) -> Result<()> {
+        let path = path.as_ref();
+        let serialised_keypair = serialise(&self.keypair)?;
+        let (salt, mac, keypair) = match passphrase {
+            Some(passphrase) => {
+                let mut salt = [0; SALT_LEN];
+                rand::thread_rng().fill_bytes(&mut salt);
+                let secret_key = passphrase_secret_key(passphrase, &salt)?;
+                let encrypted = bls_encrypt(&secret_key, &serialised_keypair)?;
+                let mac = passphrase_mac(&secret_key, &encrypted)?;
+                (
+                    Some(hex::encode(salt)),
+                    Some(hex::encode(mac)),
+                    hex::encode(encrypted),
+                )
+            }
+            None => (None, None, hex::encode(serialised_keypair)),
+        };
+
+        let body = CredentialsBody {
+            created: secs_since_epoch(self.created),
+            last_used: secs_since_epoch(self.last_used),
+            label: self.label.clone(),
+            salt,
+            mac,
+            keypair,
+        };
+        let file = CredentialsFile {
+            version: CREDENTIALS_FORMAT_VERSION,
+            checksum: hex::encode(checksum(&body)?),
+            body,
+        };
+
+        let serialised = serde_json::to_vec_pretty(&file).map_err(|err| {
+            Error::Serialisation(format!("Couldn't serialise credentials: {:?}", err))
+        })?;
+        // written to a temporary file first, for a crash in between not to lose the only copy
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serialised)
+            .and_then(|()| fs::rename(&tmp_path, path))
+            .map_err(|err| {
+                Error::FileSystemError(format!(
+                    "Couldn't write credentials to {}: {}",
+                    path.display(),
+                    err
+                ))
+            })
+    }
+
+    /// Load the credentials from a file at the given path, recording they were used now.
+    ///
+    /// A version 1 file is migrated to the current format version, which leaves it unencrypted
+    /// unless a passphrase is given. Failing to rewrite the file doesn't fail the loading, the
+    /// credentials are then only left as they were.
+    pub fn load(path: impl AsRef<Path>, passphrase: Option<&str>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut credentials = Self::read(path, passphrase)?;
+
+        credentials.last_used = SystemTime::now();
+        if let Err(err) = credentials.write(path, passphrase) {
+            warn!("Couldn't record the credentials were used: {}", err);
+        }
+        debug!("Credentials loaded from {}", path.display());
+
+        Ok(credentials)
+    }
+
+    /// Read the credentials from a file at the given path, of any format version, leaving the
+    /// file as it is.
+    pub fn read(path: impl AsRef<Path>, passphrase: Option<&str>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|err| {
+            Error::FileSystemError(format!(
+                "Couldn't read credentials from {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+
+        match serde_json::from_slice::<VersionHeader>(&bytes) {
+            Ok(header) => Self::from_current_version(header.version, &bytes, passphrase),
+            Err(_) => {
+                let keypair = keypair_from_v1(&bytes)?;
+                info!(
+                    "Read credentials in format version 1 at {}, they're migrated to version {} \
+                    once loaded",
+                    path.display(),
+                    CREDENTIALS_FORMAT_VERSION
+                );
+                Ok(Self::new(keypair, None))
+            }
+        }
+    }
+
+    fn from_current_version(version: u8, bytes: &[u8], passphrase: Option<&str>) -> Result<Self> {
+        if version != CREDENTIALS_FORMAT_VERSION {
+            return Err(CredentialsError::UnsupportedVersion(version).into());
+        }
+        let file: CredentialsFile = serde_json::from_slice(bytes)
+            .map_err(|err| CredentialsError::Corrupt(err.to_string()))?;
+
+        let expected = hex::decode(&file.checksum)
+            .map_err(|err| CredentialsError::Corrupt(format!("invalid checksum: {}", err)))?;
+        if expected != checksum(&file.body)? {
+            return Err(CredentialsError::ChecksumMismatch.into());
+        }
+
+        let body = file.body;
+        let keypair = hex::decode(&body.keypair)
+            .map_err(|err| CredentialsError::Corrupt(format!("invalid keypair: {}", err)))?;
+        let serialised_keypair = match body.salt {
+            Some(salt) => {
+                let salt = hex::decode(salt)
+                    .map_err(|err| CredentialsError::Corrupt(format!("invalid salt: {}", err)))?;
+                let mac = body
+                    .mac
+                    .ok_or_else(|| CredentialsError::Corrupt("missing MAC".to_string()))
+                    .and_then(|mac| {
+                        hex::decode(mac).map_err(|err| {
+                            CredentialsError::Corrupt(format!("invalid MAC: {}", err))
+                        })
+                    })?;
+                let passphrase = passphrase.ok_or(CredentialsError::PassphraseRequired)?;
+                let secret_key = passphrase_secret_key(passphrase, &salt)?;
+                // decrypting with the key of a wrong passphrase would give garbage, not fail
+                if !verify_passphrase_mac(&secret_key, &keypair, &mac) {
+                    return Err(CredentialsError::WrongPassphrase.into());
+                }
+                bls_decrypt(&secret_key, &keypair)
+                    .map_err(|err| CredentialsError::Corrupt(err.to_string()))?
+            }
+            None => keypair,
+        };
+        let keypair = deserialise(&serialised_keypair)
+            .map_err(|err| CredentialsError::Corrupt(format!("invalid keypair: {}", err)))?;
+
+        Ok(Self {
+            keypair,
+            label: body.label,
+            created: UNIX_EPOCH + Duration::from_secs(body.created),
+            last_used: UNIX_EPOCH + Duration::from_secs(body.last_used),
+        })
+    }
+}
+
+// Version 1 files are a bare keypair, serialised with bincode by `Safe::serialize_keypair` before
+// it wrote credentials, or with JSON when the CLI was authorised through authd.
+fn keypair_from_v1(bytes: &[u8]) -> Result<Keypair> {
+    deserialise(bytes)
+        .ok()
+        .or_else(|| serde_json::from_slice(bytes).ok())
+        .ok_or_else(|| {
+            CredentialsError::Corrupt("not a credentials file of any known version".to_string())
+                .into()
+        })
+}
+
+fn checksum(body: &CredentialsBody) -> Result<[u8; 32]> {
+    let serialised = serde_json::to_vec(body).map_err(|err| {
+        Error::Serialisation(format!("Couldn't serialise credentials: {:?}", err))
+    })?;
+    let mut hasher = Sha3::v256();
+    let mut checksum = [0; 32];
+    hasher.update(&serialised);
+    hasher.finalize(&mut checksum);
+    Ok(checksum)
+}
+
+fn secs_since_epoch(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Credentials, CredentialsError, CREDENTIALS_FORMAT_VERSION};
+    use crate::Error;
+    use anyhow::{anyhow, Result};
+    use assert_fs::TempDir;
+    use sn_interface::types::{utils::serialise, Keypair};
+    use std::fs;
+
+    #[test]
+    fn encrypted_credentials_are_only_loaded_with_their_passphrase() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let path = tmp_dir.path().join("credentials");
+        let credentials = Credentials::new(Keypair::new_ed25519(), Some("cli".to_string()));
+        credentials.write(&path, Some("correct horse"))?;
+
+        assert!(matches!(
+            Credentials::load(&path, None),
+            Err(Error::Credentials(CredentialsError::PassphraseRequired))
+        ));
+        assert!(matches!(
+            Credentials::load(&path, Some("battery staple")),
+            Err(Error::Credentials(CredentialsError::WrongPassphrase))
+        ));
+
+        let loaded = Credentials::load(&path, Some("correct horse"))?;
+        assert_eq!(
+            loaded.keypair.public_key(),
+            credentials.keypair.public_key()
+        );
+        assert_eq!(loaded.label, credentials.label);
+        assert!(loaded.last_used >= loaded.created);
+
+        Ok(())
+    }
+
+    #[test]
+    fn v1_credentials_are_migrated_on_first_load() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let path = tmp_dir.path().join("credentials");
+        let keypair = Keypair::new_ed25519();
+        fs::write(&path, serialise(&keypair)?)?;
+
+        let loaded = Credentials::load(&path, None)?;
+        assert_eq!(loaded.keypair.public_key(), keypair.public_key());
+
+        let migrated: serde_json::Value = serde_json::from_slice(&fs::read(&path)?)?;
+        assert_eq!(
+            migrated["version"].as_u64(),
+            Some(CREDENTIALS_FORMAT_VERSION as u64)
+        );
+        assert_eq!(
+            Credentials::load(&path, None)?.keypair.public_key(),
+            keypair.public_key()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn tampered_credentials_fail_the_checksum() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let path = tmp_dir.path().join("credentials");
+        Credentials::new(Keypair::new_ed25519(), None).write(&path, None)?;
+
+        let mut file: serde_json::Value = serde_json::from_slice(&fs::read(&path)?)?;
+        file["body"]["label"] = serde_json::Value::String("tampered".to_string());
+        fs::write(&path, serde_json::to_vec(&file)?)?;
+
+        match Credentials::load(&path, None) {
+            Err(Error::Credentials(CredentialsError::ChecksumMismatch)) => Ok(()),
+            other => Err(anyhow!("Unexpected result: {:?}", other)),
+        }
+    }
+
+    #[test]
+    fn garbage_is_reported_as_corrupt() -> Result<()> {
+        let tmp_dir = TempDir::new()?;
+        let path = tmp_dir.path().join("credentials");
+        fs::write(&path, b"not credentials")?;
+
+        assert!(matches!(
+            Credentials::load(&path, None),
+            Err(Error::Credentials(CredentialsError::Corrupt(_)))
+        ));
+
+        Ok(())
+    }
+}
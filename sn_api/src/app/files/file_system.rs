@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{metadata::get_metadata, FilesMapChange, ProcessedFiles};
+use super::{metadata::get_metadata, FilesMapChange, ProcessedFiles, SymlinkPolicy};
 use crate::{Error, Result, Safe, XorUrl};
 use bytes::Bytes;
 use log::info;
@@ -64,18 +64,19 @@ pub(crate) fn normalise_path_separator(from: &str) -> String {
 
 // Walk the local filesystem starting from `location`, creating a list of files paths,
 // and if not requested as a `dry_run` upload the files to the network filling up
-// the list of files with their corresponding XOR-URLs
+// the list of files with their corresponding XOR-URLs. Symlinks, to files or to directories,
+// are handled as per the given policy, which is reported for each of them.
 pub(crate) async fn file_system_dir_walk(
     safe: &Safe,
     location: &Path,
     recursive: bool,
-    follow_links: bool,
+    symlink_policy: SymlinkPolicy,
 ) -> Result<ProcessedFiles> {
     info!("Reading files from {}", location.display());
 
+    let follow_links = symlink_policy.follows_links();
     let (metadata, _) = get_metadata(location, follow_links)?;
     if metadata.is_dir() || !recursive {
-        // TODO: option to enable following symlinks?
         // We now compare both FilesMaps to upload the missing files
         let max_depth = if recursive { MAX_RECURSIVE_DEPTH } else { 1 };
         let mut processed_files = ProcessedFiles::default();
@@ -91,6 +92,19 @@ pub(crate) async fn file_system_dir_walk(
             info!("Processing {}...", current_path_str);
             let normalised_path = PathBuf::from(normalise_path_separator(&current_path_str));
 
+            let link_policy = child.path_is_symlink().then(|| symlink_policy);
+            if link_policy == Some(SymlinkPolicy::Skip) {
+                info!("Skipping symlink \"{}\"", normalised_path.display());
+                processed_files.insert(
+                    normalised_path,
+                    FilesMapChange::Symlink {
+                        policy: SymlinkPolicy::Skip,
+                        change: None,
+                    },
+                );
+                continue;
+            }
+
             let result = get_metadata(current_file_path, follow_links);
             match result {
                 Ok((metadata, _)) => {
@@ -114,39 +128,39 @@ pub(crate) async fn file_system_dir_walk(
                         // Callers can inspect the file's metadata.
                         processed_files.insert(
                             normalised_path.clone(),
-                            FilesMapChange::Added(String::default()),
+                            FilesMapChange::Added(String::default())
+                                .with_symlink_policy(link_policy),
                         );
                     }
 
                     if metadata.file_type().is_symlink() {
                         processed_files.insert(
                             normalised_path.clone(),
-                            FilesMapChange::Added(String::default()),
+                            FilesMapChange::Added(String::default())
+                                .with_symlink_policy(link_policy),
                         );
                     }
 
                     if metadata.file_type().is_file() {
-                        match upload_file_to_net(safe, current_file_path).await {
-                            Ok(xorurl) => {
-                                processed_files
-                                    .insert(normalised_path, FilesMapChange::Added(xorurl));
-                            }
+                        let change = match upload_file_to_net(safe, current_file_path).await {
+                            Ok(xorurl) => FilesMapChange::Added(xorurl),
                             Err(err) => {
                                 info!("Skipping file \"{}\". {}", normalised_path.display(), err);
-                                processed_files.insert(
-                                    normalised_path,
-                                    FilesMapChange::Failed(format!("{}", err)),
-                                );
+                                FilesMapChange::Failed(format!("{}", err))
                             }
-                        }
+                        };
+                        processed_files
+                            .insert(normalised_path, change.with_symlink_policy(link_policy));
                     }
                 }
                 Err(err) => {
                     info!(
                         "Skipping file \"{}\" since no metadata could be read from local location: {:?}",
                         normalised_path.display(), err);
-                    processed_files
-                        .insert(normalised_path, FilesMapChange::Failed(format!("{}", err)));
+                    processed_files.insert(
+                        normalised_path,
+                        FilesMapChange::Failed(format!("{}", err)).with_symlink_policy(link_policy),
+                    );
                 }
             }
         }
@@ -174,32 +188,59 @@ fn valid_depth(entry: &DirEntry, max_depth: usize) -> bool {
 
 // Read the local filesystem at `location`, creating a list of one single file's path,
 // and if not as a `dry_run` upload the file to the network and putting
-// the obtained XOR-URL in the single file list returned
+// the obtained XOR-URL in the single file list returned. If `location` is a symlink,
+// it's handled as per the given policy, i.e. only a symlink to a directory can't be followed.
 pub(crate) async fn file_system_single_file(
     safe: &Safe,
     location: &Path,
+    symlink_policy: SymlinkPolicy,
 ) -> Result<ProcessedFiles> {
     info!("Reading file {}", location.display());
-    let (metadata, _) = get_metadata(location, true)?; // follows symlinks.
+    let (metadata, _) = get_metadata(location, symlink_policy.follows_links())?;
+    let is_symlink = fs::symlink_metadata(location)
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false);
+    let link_policy = is_symlink.then(|| symlink_policy);
 
     // We now compare both FilesMaps to upload the missing files
     let mut processed_files = ProcessedFiles::default();
     let normalised_path = PathBuf::from(normalise_path_separator(&location.display().to_string()));
+    match link_policy {
+        Some(SymlinkPolicy::Skip) => {
+            info!("Skipping symlink \"{}\"", normalised_path.display());
+            processed_files.insert(
+                normalised_path,
+                FilesMapChange::Symlink {
+                    policy: SymlinkPolicy::Skip,
+                    change: None,
+                },
+            );
+            return Ok(processed_files);
+        }
+        Some(SymlinkPolicy::StoreAsLink) => {
+            processed_files.insert(
+                normalised_path,
+                FilesMapChange::Added(String::default()).with_symlink_policy(link_policy),
+            );
+            return Ok(processed_files);
+        }
+        Some(SymlinkPolicy::FollowTarget) | None => {}
+    }
+
     if metadata.is_dir() {
         Err(Error::InvalidInput(format!(
             "'{}' is a directory, only individual files can be added. Use files sync operation for uploading folders",
             location.display()
         )))
     } else {
-        match upload_file_to_net(safe, location).await {
-            Ok(xorurl) => {
-                processed_files.insert(normalised_path, FilesMapChange::Added(xorurl));
-            }
+        let change = match upload_file_to_net(safe, location).await {
+            Ok(xorurl) => FilesMapChange::Added(xorurl),
             Err(err) => {
                 info!("Skipping file \"{}\". {}", normalised_path.display(), err);
-                processed_files.insert(normalised_path, FilesMapChange::Failed(format!("{}", err)));
+                FilesMapChange::Failed(format!("{}", err))
             }
         };
+        processed_files.insert(normalised_path, change.with_symlink_policy(link_policy));
         Ok(processed_files)
     }
 }
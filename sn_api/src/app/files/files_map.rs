@@ -12,9 +12,14 @@ use super::{
     ProcessedFiles, RealPath,
 };
 use crate::{app::consts::*, Error, Result, Safe, XorUrl};
-use log::{debug, info};
+use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, fs, path::Path};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs,
+    path::Path,
+    str::FromStr,
+};
 
 // To use for mapping files names (with path in a flattened hierarchy) to FileInfos
 pub type FilesMap = BTreeMap<String, FileInfo>;
@@ -22,6 +27,113 @@ pub type FilesMap = BTreeMap<String, FileInfo>;
 // Each FileInfo contains file metadata and the link to the file's XOR-URL
 pub type FileInfo = BTreeMap<String, String>;
 
+// How the symlinks found in the local files uploaded to a FilesContainer are handled
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SymlinkPolicy {
+    // Store the symlink itself, i.e. the path of its target
+    StoreAsLink,
+    // Store the file or directory the symlink points to, under the name of the symlink
+    FollowTarget,
+    // Leave the symlink out
+    Skip,
+}
+
+impl Default for SymlinkPolicy {
+    fn default() -> Self {
+        Self::StoreAsLink
+    }
+}
+
+impl SymlinkPolicy {
+    // Whether symlinks are followed when reading local files
+    pub(crate) fn follows_links(&self) -> bool {
+        *self == Self::FollowTarget
+    }
+}
+
+impl FromStr for SymlinkPolicy {
+    type Err = Error;
+    fn from_str(str: &str) -> Result<Self> {
+        match str {
+            "store-as-link" => Ok(Self::StoreAsLink),
+            "follow-target" => Ok(Self::FollowTarget),
+            "skip" => Ok(Self::Skip),
+            other => Err(Error::InvalidInput(format!(
+                "Invalid symlink policy: {}. Supported values are store-as-link, follow-target, and skip",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for SymlinkPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let policy = match self {
+            Self::StoreAsLink => "store-as-link",
+            Self::FollowTarget => "follow-target",
+            Self::Skip => "skip",
+        };
+        write!(f, "{}", policy)
+    }
+}
+
+// Caching hints of a file, set by its publisher when uploading it, for gateways and clients
+// to cache it as intended. They are held in its FileInfo in the form of a Cache-Control header.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheControl {
+    // How long, in seconds, the file can be cached for
+    pub max_age: Option<u64>,
+    // Whether the content at the file's link never changes, so it can be cached for good
+    pub immutable: bool,
+}
+
+impl CacheControl {
+    // The caching hints held in the given FileInfo, if any
+    pub fn from_file_info(file_info: &FileInfo) -> Result<Option<Self>> {
+        file_info
+            .get(PREDICATE_CACHE_CONTROL)
+            .map(|cache_control| cache_control.parse())
+            .transpose()
+    }
+}
+
+impl FromStr for CacheControl {
+    type Err = Error;
+    fn from_str(str: &str) -> Result<Self> {
+        let mut cache_control = Self::default();
+        for directive in str.split(',').map(str::trim) {
+            let lowercase = directive.to_ascii_lowercase();
+            if lowercase == "immutable" {
+                cache_control.immutable = true;
+            } else if let Some(secs) = lowercase.strip_prefix("max-age=") {
+                let secs = secs.parse().map_err(|_| {
+                    Error::InvalidInput(format!("Invalid max-age in cache control: {}", directive))
+                })?;
+                cache_control.max_age = Some(secs);
+            } else {
+                return Err(Error::InvalidInput(format!(
+                    "Invalid cache control directive: '{}'. Supported directives are max-age=<seconds>, and immutable",
+                    directive
+                )));
+            }
+        }
+        Ok(cache_control)
+    }
+}
+
+impl fmt::Display for CacheControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut directives = vec![];
+        if let Some(secs) = self.max_age {
+            directives.push(format!("max-age={}", secs));
+        }
+        if self.immutable {
+            directives.push("immutable".to_string());
+        }
+        write!(f, "{}", directives.join(", "))
+    }
+}
+
 // Type of changes made to each item of a FilesMap
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Serialize, Deserialize)]
@@ -30,6 +142,12 @@ pub enum FilesMapChange {
     Updated(XorUrl),
     Removed(XorUrl),
     Failed(String),
+    // A symlink, with the policy applied to it, and the change made as per that policy,
+    // if it wasn't skipped
+    Symlink {
+        policy: SymlinkPolicy,
+        change: Option<Box<FilesMapChange>>,
+    },
 }
 
 impl FilesMapChange {
@@ -37,6 +155,7 @@ impl FilesMapChange {
         match self {
             Self::Added(_) | Self::Updated(_) | Self::Removed(_) => true,
             Self::Failed(_) => false,
+            Self::Symlink { change, .. } => change.as_ref().map_or(true, |c| c.is_success()),
         }
     }
 
@@ -44,6 +163,7 @@ impl FilesMapChange {
         match self {
             Self::Added(link) | Self::Updated(link) | Self::Removed(link) => Some(link),
             Self::Failed(_) => None,
+            Self::Symlink { change, .. } => change.as_ref().and_then(|c| c.link()),
         }
     }
 
@@ -51,6 +171,7 @@ impl FilesMapChange {
         match self {
             Self::Added(_) => true,
             Self::Updated(_) | Self::Removed(_) | Self::Failed(_) => false,
+            Self::Symlink { change, .. } => change.as_ref().map_or(false, |c| c.is_added()),
         }
     }
 
@@ -58,6 +179,7 @@ impl FilesMapChange {
         match self {
             Self::Updated(_) => true,
             Self::Added(_) | Self::Removed(_) | Self::Failed(_) => false,
+            Self::Symlink { change, .. } => change.as_ref().map_or(false, |c| c.is_updated()),
         }
     }
 
@@ -65,6 +187,252 @@ impl FilesMapChange {
         match self {
             Self::Removed(_) => true,
             Self::Added(_) | Self::Updated(_) | Self::Failed(_) => false,
+            Self::Symlink { change, .. } => change.as_ref().map_or(false, |c| c.is_removed()),
+        }
+    }
+
+    // The symlink policy applied to the item, if it's a symlink
+    pub fn symlink_policy(&self) -> Option<SymlinkPolicy> {
+        match self {
+            Self::Symlink { policy, .. } => Some(*policy),
+            _ => None,
+        }
+    }
+
+    // Whether the item is a symlink which was left out as per the symlink policy
+    pub fn is_skipped(&self) -> bool {
+        matches!(self, Self::Symlink { change: None, .. })
+    }
+
+    // The change made to the item, recorded as a symlink's if the given policy was applied to it
+    pub(crate) fn with_symlink_policy(self, policy: Option<SymlinkPolicy>) -> Self {
+        match policy {
+            Some(policy) => Self::Symlink {
+                policy,
+                change: Some(Box::new(self)),
+            },
+            None => self,
+        }
+    }
+}
+
+// How an entry of a FilesMap changed from one version of it to another: its size, its
+// modification time, its content, which its link is the XOR-URL of, and which of the fields of
+// its FileInfo changed. Fields the entry doesn't have, e.g. as it was added or removed, are None.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileInfoDiff {
+    pub old_size: Option<String>,
+    pub new_size: Option<String>,
+    pub old_modified: Option<String>,
+    pub new_modified: Option<String>,
+    pub old_link: Option<XorUrl>,
+    pub new_link: Option<XorUrl>,
+    // Fields added to, removed from, or set to another value in the FileInfo
+    pub changed_fields: BTreeSet<String>,
+}
+
+impl FileInfoDiff {
+    // The changes from the FileInfo of the entry in the old version of the FilesMap, if it
+    // was in it, to the one in the new version, if it's still in it
+    pub fn new(old: Option<&FileInfo>, new: Option<&FileInfo>) -> Self {
+        let field = |file_info: Option<&FileInfo>, key: &str| {
+            file_info.and_then(|file_info| file_info.get(key)).cloned()
+        };
+        let changed_fields = old
+            .into_iter()
+            .chain(new)
+            .flat_map(|file_info| file_info.keys())
+            .filter(|key| field(old, key) != field(new, key))
+            .cloned()
+            .collect();
+
+        Self {
+            old_size: field(old, PREDICATE_SIZE),
+            new_size: field(new, PREDICATE_SIZE),
+            old_modified: field(old, PREDICATE_MODIFIED),
+            new_modified: field(new, PREDICATE_MODIFIED),
+            old_link: field(old, PREDICATE_LINK),
+            new_link: field(new, PREDICATE_LINK),
+            changed_fields,
+        }
+    }
+
+    pub fn is_unchanged(&self) -> bool {
+        self.changed_fields.is_empty()
+    }
+
+    pub fn content_changed(&self) -> bool {
+        self.changed_fields.contains(PREDICATE_LINK)
+    }
+}
+
+impl fmt::Display for FileInfoDiff {
+    // A summary of the changes, e.g. "size: 12 -> 23; metadata: mode_bits"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let value = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+        let mut changes = vec![];
+        for (name, key, old, new) in [
+            ("content", PREDICATE_LINK, &self.old_link, &self.new_link),
+            ("size", PREDICATE_SIZE, &self.old_size, &self.new_size),
+            (
+                "modified",
+                PREDICATE_MODIFIED,
+                &self.old_modified,
+                &self.new_modified,
+            ),
+        ] {
+            if self.changed_fields.contains(key) {
+                changes.push(format!("{}: {} -> {}", name, value(old), value(new)));
+            }
+        }
+
+        let other_fields: Vec<_> = self
+            .changed_fields
+            .iter()
+            .map(String::as_str)
+            .filter(|key| ![PREDICATE_LINK, PREDICATE_SIZE, PREDICATE_MODIFIED].contains(key))
+            .collect();
+        if !other_fields.is_empty() {
+            changes.push(format!("metadata: {}", other_fields.join(", ")));
+        }
+
+        if changes.is_empty() {
+            write!(f, "unchanged")
+        } else {
+            write!(f, "{}", changes.join("; "))
+        }
+    }
+}
+
+// How each entry added to, removed from, or changed in a FilesMap changed from its old version
+// to the new one. Entries left as they were are not included.
+pub fn diff_files_maps(old: &FilesMap, new: &FilesMap) -> BTreeMap<String, FileInfoDiff> {
+    old.keys()
+        .chain(new.keys())
+        .filter_map(|path| {
+            let diff = FileInfoDiff::new(old.get(path), new.get(path));
+            (!diff.is_unchanged()).then(|| (path.clone(), diff))
+        })
+        .collect()
+}
+
+// How an entry of a FilesMap is corrupt
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FilesMapViolationKind {
+    // The entry has no "type" property, so it can't be told whether it's a file, a directory
+    // or a symlink
+    MissingType,
+    // The entry is a file with no "link" property to its content
+    MissingLink,
+    // The entry is a symlink with no "symlink_target" property
+    MissingSymlinkTarget,
+    // The entry lacks one of the metadata properties all entries are stored with
+    MissingMetadata(String),
+}
+
+// An entry of a FilesMap found to be corrupt, and how
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilesMapViolation {
+    pub path: String,
+    pub kind: FilesMapViolationKind,
+}
+
+impl fmt::Display for FilesMapViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            FilesMapViolationKind::MissingType => {
+                write!(
+                    f,
+                    "missing a \"{}\" property at: {}",
+                    PREDICATE_TYPE, self.path
+                )
+            }
+            FilesMapViolationKind::MissingLink => {
+                write!(
+                    f,
+                    "missing a \"{}\" property at: {}",
+                    PREDICATE_LINK, self.path
+                )
+            }
+            FilesMapViolationKind::MissingSymlinkTarget => {
+                write!(f, "missing a \"symlink_target\" property at: {}", self.path)
+            }
+            FilesMapViolationKind::MissingMetadata(key) => {
+                write!(f, "missing a \"{}\" property at: {}", key, self.path)
+            }
+        }
+    }
+}
+
+// Checks all the entries of a FilesMap, returning all the violations found, rather than
+// failing on the first corrupt entry as resolving paths in it does
+pub fn validate_files_map(files_map: &FilesMap) -> Vec<FilesMapViolation> {
+    let mut violations = vec![];
+    for (path, file_info) in files_map {
+        let mut violation = |kind| {
+            violations.push(FilesMapViolation {
+                path: path.clone(),
+                kind,
+            })
+        };
+
+        for key in [PREDICATE_CREATED, PREDICATE_MODIFIED, PREDICATE_SIZE] {
+            if !file_info.contains_key(key) {
+                violation(FilesMapViolationKind::MissingMetadata(key.to_string()));
+            }
+        }
+
+        match file_info.get(PREDICATE_TYPE) {
+            None => violation(FilesMapViolationKind::MissingType),
+            Some(file_type) if FileMeta::filetype_is_symlink(file_type) => {
+                if !file_info.contains_key("symlink_target") {
+                    violation(FilesMapViolationKind::MissingSymlinkTarget);
+                }
+            }
+            Some(file_type) if FileMeta::filetype_is_file(file_type) => {
+                if !file_info.contains_key(PREDICATE_LINK) {
+                    violation(FilesMapViolationKind::MissingLink);
+                }
+            }
+            Some(_) => {}
+        }
+    }
+    violations
+}
+
+// How the FilesMaps of FilesContainers are checked when read
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FilesMapReadMode {
+    // Fail with all the violations found if any entry is corrupt
+    Strict,
+    // Leave the corrupt entries out, returning the violations found along with the
+    // entries which are valid, e.g. for them to be repaired
+    Lenient,
+}
+
+impl FilesMapReadMode {
+    // Checks the FilesMap as per the mode, returning its valid entries and the violations found
+    pub(crate) fn check(
+        &self,
+        mut files_map: FilesMap,
+    ) -> Result<(FilesMap, Vec<FilesMapViolation>)> {
+        let violations = validate_files_map(&files_map);
+        if violations.is_empty() {
+            return Ok((files_map, violations));
+        }
+
+        match self {
+            Self::Strict => Err(Error::CorruptFilesMap(
+                format!("{} corrupt entries found in FilesMap", violations.len()),
+                violations,
+            )),
+            Self::Lenient => {
+                for violation in &violations {
+                    warn!("Leaving corrupt FilesMap entry out: {}", violation);
+                    files_map.remove(&violation.path);
+                }
+                Ok((files_map, violations))
+            }
         }
     }
 }
@@ -95,6 +463,7 @@ pub(crate) async fn add_or_update_file_item(
     file_meta: &FileMeta,
     file_link: Option<&str>,
     name_exists: bool,
+    symlink_policy: Option<SymlinkPolicy>,
     files_map: &mut FilesMap,
     processed_files: &mut ProcessedFiles,
 ) -> bool {
@@ -117,7 +486,10 @@ pub(crate) async fn add_or_update_file_item(
             debug!("New FileInfo item inserted as {:?}", file_name);
             files_map.insert(file_name_for_map.to_string(), new_file_item);
 
-            processed_files.insert(file_name.to_path_buf(), file_item_change);
+            processed_files.insert(
+                file_name.to_path_buf(),
+                file_item_change.with_symlink_policy(symlink_policy),
+            );
 
             true
         }
@@ -133,6 +505,26 @@ pub(crate) async fn add_or_update_file_item(
     }
 }
 
+// Sets the caching hints of the files added to, or updated in, the FilesMap since the previous
+// version of it. The hints of files not uploaded anew are left as they were.
+pub(crate) fn set_cache_control(
+    files_map: &mut FilesMap,
+    previous_files_map: &FilesMap,
+    cache_control: CacheControl,
+) {
+    for (path, file_info) in files_map.iter_mut() {
+        let is_file = file_info
+            .get(PREDICATE_TYPE)
+            .map_or(false, |file_type| FileMeta::filetype_is_file(file_type));
+        if is_file && previous_files_map.get(path) != Some(file_info) {
+            file_info.insert(
+                PREDICATE_CACHE_CONTROL.to_string(),
+                cache_control.to_string(),
+            );
+        }
+    }
+}
+
 // Generate a FileInfo for a file which can then be added to a FilesMap
 async fn gen_new_file_item(
     safe: &Safe,
@@ -252,6 +644,21 @@ pub(crate) fn get_file_link_and_metadata(
                 }
             }
 
+            // caching hints are only passed on in a form gateways and clients can rely on
+            match CacheControl::from_file_info(file_info) {
+                Ok(Some(cache_control)) => {
+                    enriched_file_info.insert(
+                        PREDICATE_CACHE_CONTROL.to_string(),
+                        cache_control.to_string(),
+                    );
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    warn!("Ignoring caching hints of file at {}: {}", path, err);
+                    enriched_file_info.remove(PREDICATE_CACHE_CONTROL);
+                }
+            }
+
             return Ok((Some(link.to_owned()), Some(enriched_file_info)));
         }
     }
@@ -282,3 +689,229 @@ fn filesmap_chroot(urlpath: &str, files_map: &FilesMap) -> Result<FilesMap> {
         Ok(filtered_filesmap)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        diff_files_maps, get_file_link_and_metadata, set_cache_control, validate_files_map,
+        CacheControl, FileInfo, FilesMap, FilesMapReadMode, FilesMapViolation,
+        FilesMapViolationKind,
+    };
+    use crate::app::consts::*;
+    use crate::Error;
+
+    use anyhow::{anyhow, Result};
+
+    fn file_info(link: &str) -> FileInfo {
+        FileInfo::from([
+            (PREDICATE_TYPE.to_string(), "text/plain".to_string()),
+            (PREDICATE_LINK.to_string(), link.to_string()),
+        ])
+    }
+
+    #[test]
+    fn cache_control_is_parsed_from_its_header_form() -> Result<()> {
+        let cache_control: CacheControl = "Max-Age=60 ,immutable".parse()?;
+        assert_eq!(
+            cache_control,
+            CacheControl {
+                max_age: Some(60),
+                immutable: true,
+            }
+        );
+        assert_eq!(cache_control.to_string(), "max-age=60, immutable");
+        assert_eq!(
+            cache_control.to_string().parse::<CacheControl>()?,
+            cache_control
+        );
+
+        for invalid in ["", "max-age=soon", "no-store", "immutable,"] {
+            assert!(invalid.parse::<CacheControl>().is_err(), "{}", invalid);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn cache_control_is_only_set_on_files_uploaded_anew() {
+        let previous_files_map = FilesMap::from([
+            ("/kept.md".to_string(), file_info("safe://kept")),
+            ("/updated.md".to_string(), file_info("safe://updated")),
+        ]);
+        let mut files_map = previous_files_map.clone();
+        files_map.insert("/updated.md".to_string(), file_info("safe://updated-again"));
+        files_map.insert("/added.md".to_string(), file_info("safe://added"));
+        files_map.insert(
+            "/folder".to_string(),
+            FileInfo::from([(
+                PREDICATE_TYPE.to_string(),
+                MIMETYPE_FILESYSTEM_DIR.to_string(),
+            )]),
+        );
+
+        let cache_control = CacheControl {
+            max_age: None,
+            immutable: true,
+        };
+        set_cache_control(&mut files_map, &previous_files_map, cache_control);
+
+        let with_cache_control: Vec<_> = files_map
+            .iter()
+            .filter(|(_, file_info)| file_info.contains_key(PREDICATE_CACHE_CONTROL))
+            .map(|(path, _)| path.as_str())
+            .collect();
+        assert_eq!(with_cache_control, vec!["/added.md", "/updated.md"]);
+    }
+
+    #[test]
+    fn invalid_cache_control_is_left_out_of_the_metadata() -> Result<()> {
+        let mut valid = file_info("safe://valid");
+        valid.insert(
+            PREDICATE_CACHE_CONTROL.to_string(),
+            "immutable, max-age=10".to_string(),
+        );
+        let mut invalid = file_info("safe://invalid");
+        invalid.insert(PREDICATE_CACHE_CONTROL.to_string(), "forever".to_string());
+        let files_map = FilesMap::from([
+            ("/valid.md".to_string(), valid),
+            ("/invalid.md".to_string(), invalid),
+        ]);
+
+        let (_, metadata) = get_file_link_and_metadata(&files_map, "/valid.md")?;
+        let metadata = metadata.ok_or_else(|| anyhow!("no metadata for /valid.md"))?;
+        // the hints are passed on in their canonical form
+        assert_eq!(metadata[PREDICATE_CACHE_CONTROL], "max-age=10, immutable");
+
+        let (link, metadata) = get_file_link_and_metadata(&files_map, "/invalid.md")?;
+        assert_eq!(link.as_deref(), Some("safe://invalid"));
+        let metadata = metadata.ok_or_else(|| anyhow!("no metadata for /invalid.md"))?;
+        assert!(!metadata.contains_key(PREDICATE_CACHE_CONTROL));
+
+        Ok(())
+    }
+
+    #[test]
+    fn files_maps_diff_tells_how_each_entry_changed() -> Result<()> {
+        let mut kept = file_info("safe://kept");
+        kept.insert(PREDICATE_SIZE.to_string(), "5".to_string());
+        let mut updated = file_info("safe://updated");
+        updated.insert(PREDICATE_SIZE.to_string(), "12".to_string());
+        updated.insert(
+            PREDICATE_MODIFIED.to_string(),
+            "2022-06-01T10:00:00Z".to_string(),
+        );
+        let old_files_map = FilesMap::from([
+            ("/kept.md".to_string(), kept.clone()),
+            ("/updated.md".to_string(), updated.clone()),
+            ("/removed.md".to_string(), file_info("safe://removed")),
+        ]);
+
+        let mut chmoded = kept;
+        chmoded.insert(PREDICATE_MODE_BITS.to_string(), "644".to_string());
+        updated.insert(
+            PREDICATE_LINK.to_string(),
+            "safe://updated-again".to_string(),
+        );
+        updated.insert(PREDICATE_SIZE.to_string(), "23".to_string());
+        updated.insert(
+            PREDICATE_MODIFIED.to_string(),
+            "2022-06-02T10:00:00Z".to_string(),
+        );
+        let new_files_map = FilesMap::from([
+            ("/kept.md".to_string(), chmoded),
+            ("/updated.md".to_string(), updated),
+            ("/added.md".to_string(), file_info("safe://added")),
+        ]);
+
+        let diffs = diff_files_maps(&old_files_map, &new_files_map);
+        assert_eq!(
+            diffs.keys().map(String::as_str).collect::<Vec<_>>(),
+            vec!["/added.md", "/kept.md", "/removed.md", "/updated.md"]
+        );
+
+        let updated = diffs
+            .get("/updated.md")
+            .ok_or_else(|| anyhow!("no diff for /updated.md"))?;
+        assert!(updated.content_changed());
+        assert_eq!(updated.old_size.as_deref(), Some("12"));
+        assert_eq!(updated.new_size.as_deref(), Some("23"));
+        assert_eq!(
+            updated.to_string(),
+            "content: safe://updated -> safe://updated-again; size: 12 -> 23; \
+            modified: 2022-06-01T10:00:00Z -> 2022-06-02T10:00:00Z"
+        );
+
+        let kept = diffs
+            .get("/kept.md")
+            .ok_or_else(|| anyhow!("no diff for /kept.md"))?;
+        assert!(!kept.content_changed());
+        assert_eq!(kept.to_string(), "metadata: mode_bits");
+
+        let removed = diffs
+            .get("/removed.md")
+            .ok_or_else(|| anyhow!("no diff for /removed.md"))?;
+        assert_eq!(removed.new_link, None);
+        assert_eq!(
+            removed.to_string(),
+            "content: safe://removed -> -; metadata: type"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn corrupt_entries_are_all_reported_and_left_out_in_lenient_mode() -> Result<()> {
+        let valid = |file_type: &str| {
+            FileInfo::from([
+                (PREDICATE_TYPE.to_string(), file_type.to_string()),
+                (PREDICATE_CREATED.to_string(), "1".to_string()),
+                (PREDICATE_MODIFIED.to_string(), "1".to_string()),
+                (PREDICATE_SIZE.to_string(), "0".to_string()),
+            ])
+        };
+        let mut file = valid("text/plain");
+        file.insert(PREDICATE_LINK.to_string(), "safe://file".to_string());
+        let mut no_type = file.clone();
+        no_type.remove(PREDICATE_TYPE);
+        let mut no_size = file.clone();
+        no_size.remove(PREDICATE_SIZE);
+
+        let files_map = FilesMap::from([
+            ("/dir".to_string(), valid(MIMETYPE_FILESYSTEM_DIR)),
+            ("/dir/file.md".to_string(), file),
+            ("/no-link.md".to_string(), valid("text/plain")),
+            ("/no-size.md".to_string(), no_size),
+            ("/no-target".to_string(), valid(MIMETYPE_FILESYSTEM_SYMLINK)),
+            ("/no-type.md".to_string(), no_type),
+        ]);
+
+        let violation = |path: &str, kind| FilesMapViolation {
+            path: path.to_string(),
+            kind,
+        };
+        let violations = vec![
+            violation("/no-link.md", FilesMapViolationKind::MissingLink),
+            violation(
+                "/no-size.md",
+                FilesMapViolationKind::MissingMetadata(PREDICATE_SIZE.to_string()),
+            ),
+            violation("/no-target", FilesMapViolationKind::MissingSymlinkTarget),
+            violation("/no-type.md", FilesMapViolationKind::MissingType),
+        ];
+        assert_eq!(validate_files_map(&files_map), violations);
+
+        match FilesMapReadMode::Strict.check(files_map.clone()) {
+            Err(Error::CorruptFilesMap(_, reported)) => assert_eq!(reported, violations),
+            other => return Err(anyhow!("unexpected result in strict mode: {:?}", other)),
+        }
+
+        let (valid_files_map, reported) = FilesMapReadMode::Lenient.check(files_map)?;
+        assert_eq!(reported, violations);
+        assert_eq!(
+            valid_files_map.keys().collect::<Vec<_>>(),
+            vec!["/dir", "/dir/file.md"]
+        );
+
+        Ok(())
+    }
+}
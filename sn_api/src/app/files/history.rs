@@ -0,0 +1,221 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::{app::nrs::VersionHash, Error, Result, Safe, SafeUrl};
+
+use log::{debug, info};
+use regex::Regex;
+use sn_interface::types::register::Entry;
+use std::{collections::BTreeMap, ops::RangeBounds, str};
+
+/// Pattern to match the paths of the files of a FilesContainer against
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathPattern {
+    /// Glob pattern, where `*` and `?` match within a single path segment, and `**` across them,
+    /// e.g. `/images/*.png` or `/**.md`
+    Glob(String),
+    /// Regular expression, e.g. `^/images/.*\.(png|jpg)$`
+    Regex(String),
+}
+
+impl PathPattern {
+    fn to_regex(&self) -> Result<Regex> {
+        let expr = match self {
+            Self::Glob(glob) => glob_to_regex(glob),
+            Self::Regex(expr) => expr.clone(),
+        };
+        Regex::new(&expr)
+            .map_err(|err| Error::InvalidInput(format!("Invalid path pattern {:?}: {}", self, err)))
+    }
+}
+
+/// A file path matching the pattern searched for, along with the versions of the FilesContainer
+/// it appeared and disappeared in.
+///
+/// A path removed and added back later on is reported once for each time it was in the container.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileMatch {
+    /// Path of the file
+    pub path: String,
+    /// Version the file appeared in, or the first version searched if it was already there
+    pub appeared_in: VersionHash,
+    /// Version the file disappeared in, if it was removed within the versions searched
+    pub disappeared_in: Option<VersionHash>,
+}
+
+impl Safe {
+    /// # Search for files across the versions of a FilesContainer
+    ///
+    /// Walks the history of the FilesContainer at the given URL, looking for file paths matching
+    /// the pattern, and reports in which version each match appeared and disappeared, e.g. to
+    /// find out when a file got deleted.
+    ///
+    /// The versions searched are given by their position in the history of the container,
+    /// from `0` for its first version. Any version in the URL is ignored.
+    pub async fn files_container_find(
+        &self,
+        url: &str,
+        pattern: &PathPattern,
+        version_range: impl RangeBounds<usize>,
+    ) -> Result<Vec<FileMatch>> {
+        info!("Searching FilesContainer at {} for {:?}", url, pattern);
+        let regex = pattern.to_regex()?;
+        let mut safe_url = self.parse_and_resolve_url(url).await?;
+        safe_url.set_content_version(None);
+
+        let mut matches = Vec::new();
+        // matching paths in the previous version searched, with the version they appeared in
+        let mut present = BTreeMap::<String, VersionHash>::new();
+        let history = self.files_container_history(&safe_url).await?;
+        for (position, (version, entry)) in history.into_iter().enumerate() {
+            if !version_range.contains(&position) {
+                continue;
+            }
+            let files_map = self.fetch_files_map(str::from_utf8(&entry)?).await?;
+
+            let (kept, removed) = present
+                .into_iter()
+                .partition::<BTreeMap<_, _>, _>(|(path, _)| files_map.contains_key(path));
+            matches.extend(removed.into_iter().map(|(path, appeared_in)| FileMatch {
+                path,
+                appeared_in,
+                disappeared_in: Some(version),
+            }));
+
+            present = kept;
+            for path in files_map.keys().filter(|path| regex.is_match(path)) {
+                let _ = present.entry(path.clone()).or_insert(version);
+            }
+        }
+
+        matches.extend(present.into_iter().map(|(path, appeared_in)| FileMatch {
+            path,
+            appeared_in,
+            disappeared_in: None,
+        }));
+        debug!("Found {} matches for {:?}", matches.len(), pattern);
+
+        Ok(matches)
+    }
+
+    // Versions of a FilesContainer, from its first to its latest one, along with their entries
+    async fn files_container_history(
+        &self,
+        safe_url: &SafeUrl,
+    ) -> Result<Vec<(VersionHash, Entry)>> {
+        let address = self.get_register_address(safe_url)?;
+        let register = self.get_safe_client()?.get_register(address).await?;
+
+        let heads = register.read();
+        if heads.len() > 1 {
+            return Err(Error::NotImplementedError("Multiple file container entries not managed, this happends when 2 clients write concurrently to a file container".to_string()));
+        }
+
+        let mut history = Vec::new();
+        let mut next = heads.into_iter().next().map(|(hash, _)| hash);
+        while let Some(hash) = next {
+            let entry = register.get(hash)?.clone();
+            let mut children = register.children(hash)?.into_iter();
+            next = children.next();
+            if children.next().is_some() {
+                return Err(Error::NotImplementedError(format!(
+                    "Version {} of the file container at \"{}\" merges concurrent versions, which is not managed",
+                    VersionHash::from(&hash),
+                    safe_url
+                )));
+            }
+            history.push((VersionHash::from(&hash), entry));
+        }
+        history.reverse();
+
+        Ok(history)
+    }
+}
+
+// Regular expression matching the same paths as a glob pattern
+fn glob_to_regex(glob: &str) -> String {
+    let mut expr = "^".to_string();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                let _ = chars.next();
+                expr.push_str(".*");
+            }
+            '*' => expr.push_str("[^/]*"),
+            '?' => expr.push_str("[^/]"),
+            c => expr.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    expr.push('$');
+    expr
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::{files::SymlinkPolicy, test_helpers::new_safe_instance},
+        retry_loop,
+    };
+    use anyhow::{anyhow, Result};
+
+    #[test]
+    fn glob_patterns_match_within_or_across_segments() -> Result<()> {
+        let pattern = PathPattern::Glob("/*.md".to_string()).to_regex()?;
+        assert!(pattern.is_match("/test.md"));
+        assert!(!pattern.is_match("/subfolder/subexists.md"));
+        assert!(!pattern.is_match("/test.mdx"));
+
+        let pattern = PathPattern::Glob("/**.md".to_string()).to_regex()?;
+        assert!(pattern.is_match("/test.md"));
+        assert!(pattern.is_match("/subfolder/subexists.md"));
+
+        let pattern = PathPattern::Glob("/test.?d".to_string()).to_regex()?;
+        assert!(pattern.is_match("/test.md"));
+        assert!(!pattern.is_match("/testamd"));
+
+        assert!(PathPattern::Regex("(".to_string()).to_regex().is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_find() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let (xorurl, _, _) = retry_loop!(safe.files_container_create_from(
+            "./testdata/",
+            None,
+            true,
+            SymlinkPolicy::StoreAsLink
+        ));
+        let (version0, _) = retry_loop!(safe.files_container_get(&xorurl))
+            .ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+
+        let mut url_with_path = SafeUrl::from_xorurl(&xorurl)?;
+        url_with_path.set_path("/test.md");
+        let (version1, _, _) =
+            retry_loop!(safe.files_container_remove_path(&url_with_path.to_string(), false, false));
+
+        let pattern = PathPattern::Glob("/test.md".to_string());
+        let matches = safe.files_container_find(&xorurl, &pattern, ..).await?;
+        assert_eq!(
+            matches,
+            vec![FileMatch {
+                path: "/test.md".to_string(),
+                appeared_in: version0,
+                disappeared_in: Some(version1),
+            }]
+        );
+
+        // the file isn't in the latest version anymore
+        let matches = safe.files_container_find(&xorurl, &pattern, 1..).await?;
+        assert!(matches.is_empty());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,312 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    shared_files_map::StoredFilesMap, FilesMap, FilesMapFormat, SharedFilesMap,
+    SHARED_FILES_CONTAINER_TYPE_TAG,
+};
+use crate::{safeurl::VersionHash, Error, Result, Safe};
+
+use log::{debug, info};
+use sn_interface::types::PublicKey;
+use std::{collections::BTreeSet, str};
+
+/// Outcome of the migration of a FilesContainer to another format, as returned by
+/// [`Safe::migrate_container`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContainerMigration {
+    /// Version written in the target format, `None` if the latest versions were all in it already
+    pub version: Option<VersionHash>,
+    /// Latest versions which were in another format. The new version replaces them, yet they
+    /// are still part of the history of the container, and can be read as they were.
+    pub migrated_versions: BTreeSet<VersionHash>,
+    /// What couldn't be carried over to the target format
+    pub incompatibilities: Vec<MigrationIncompatibility>,
+}
+
+/// Something a FilesMap held which the format it's migrated to has no room for
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MigrationIncompatibility {
+    /// Removals of paths, which are forgotten rather than recorded, so a concurrent write of one
+    /// of these paths would no longer be overridden by its removal
+    RemovalsDropped(Vec<String>),
+    /// Writers of the files, which are no longer recorded, along with when they wrote them
+    WritersDropped(BTreeSet<PublicKey>),
+}
+
+impl Safe {
+    /// # Migrate a FilesContainer to another format
+    ///
+    /// Reads the latest versions of the FilesContainer at the given URL and, if any of them is
+    /// stored in another format than `target_format`, writes them as a new version in the target
+    /// format. The new version replaces the latest ones, which remain part of its history.
+    ///
+    /// This gets FilesContainers whose versions were written by apps predating their current
+    /// format, e.g. single-writer versions of a shared container, to be updated again. The format
+    /// is to be the one of the kind of container, so shared containers hold the shared format.
+    /// Whatever wasn't carried over to the new version is reported along with it.
+    pub async fn migrate_container(
+        &self,
+        url: &str,
+        target_format: FilesMapFormat,
+    ) -> Result<ContainerMigration> {
+        info!(
+            "Migrating FilesContainer at {} to {} format",
+            url, target_format
+        );
+        let mut safe_url = self.parse_and_resolve_url(url).await?;
+        let container_format = if safe_url.type_tag() == SHARED_FILES_CONTAINER_TYPE_TAG {
+            FilesMapFormat::Shared
+        } else {
+            FilesMapFormat::Single
+        };
+        if target_format != container_format {
+            return Err(Error::InvalidInput(format!(
+                "A {} FilesContainer can't hold versions in the {} format",
+                container_format, target_format
+            )));
+        }
+
+        // All the latest versions are migrated, not just the one the URL refers to
+        safe_url.set_content_version(None);
+        let entries = self.register_fetch_entries(&safe_url).await?;
+        if entries.len() > 1 && target_format == FilesMapFormat::Single {
+            return Err(Error::NotImplementedError(
+                "Migrating a single-writer FilesContainer with concurrent versions".to_string(),
+            ));
+        }
+
+        let mut stored_files_maps = vec![];
+        for (hash, entry) in &entries {
+            let stored = self.fetch_stored_files_map(str::from_utf8(entry)?).await?;
+            stored_files_maps.push((VersionHash::from(hash), stored));
+        }
+        let migrated_versions: BTreeSet<_> = stored_files_maps
+            .iter()
+            .filter(|(_, stored)| stored.format() != target_format)
+            .map(|(version, _)| *version)
+            .collect();
+        if migrated_versions.is_empty() {
+            debug!(
+                "FilesContainer at {} is in {} format already",
+                url, target_format
+            );
+            return Ok(ContainerMigration {
+                version: None,
+                migrated_versions,
+                incompatibilities: vec![],
+            });
+        }
+
+        let (files_map_xorurl, incompatibilities) = match target_format {
+            FilesMapFormat::Shared => {
+                let writer = self.get_safe_client()?.public_key();
+                let shared_files_map =
+                    to_shared_format(stored_files_maps.iter().map(|(_, stored)| stored), writer);
+                (self.store_files_map(&shared_files_map).await?, vec![])
+            }
+            FilesMapFormat::Single => {
+                // there's a single latest version, as checked above, in the shared format
+                let mut files_map = FilesMap::default();
+                let mut incompatibilities = vec![];
+                if let Some((_, StoredFilesMap::Shared(shared_files_map))) =
+                    stored_files_maps.first()
+                {
+                    files_map = shared_files_map.files_map();
+                    incompatibilities = single_format_incompatibilities(shared_files_map);
+                }
+                (self.store_files_map(&files_map).await?, incompatibilities)
+            }
+        };
+
+        let parents = entries.iter().map(|(hash, _)| *hash).collect();
+        let entry_hash = self
+            .register_write(
+                &safe_url.to_string(),
+                files_map_xorurl.into_bytes(),
+                parents,
+            )
+            .await?;
+        let version = VersionHash::from(&entry_hash);
+        info!(
+            "FilesContainer at {} migrated to {} format in version {}, replacing {:?}",
+            url, target_format, version, migrated_versions
+        );
+
+        Ok(ContainerMigration {
+            version: Some(version),
+            migrated_versions,
+            incompatibilities,
+        })
+    }
+}
+
+// Merges the FilesMaps stored in the latest versions of a shared FilesContainer, converting the
+// single-writer ones as if they were written by the migrating writer before any other write,
+// as they hold their files only, not when they were written.
+fn to_shared_format<'a>(
+    stored_files_maps: impl IntoIterator<Item = &'a StoredFilesMap>,
+    writer: PublicKey,
+) -> SharedFilesMap {
+    let mut merged = SharedFilesMap::default();
+    for stored in stored_files_maps {
+        match stored {
+            StoredFilesMap::Shared(shared_files_map) => merged.merge(shared_files_map),
+            StoredFilesMap::Single(files_map) => {
+                merged.merge(&SharedFilesMap::from_files_map(files_map, writer, 0))
+            }
+        }
+    }
+    merged
+}
+
+// What's lost when a shared FilesMap is converted to the single-writer format
+fn single_format_incompatibilities(
+    shared_files_map: &SharedFilesMap,
+) -> Vec<MigrationIncompatibility> {
+    let mut incompatibilities = vec![];
+
+    let removed_paths: Vec<_> = shared_files_map.removed_paths().cloned().collect();
+    if !removed_paths.is_empty() {
+        incompatibilities.push(MigrationIncompatibility::RemovalsDropped(removed_paths));
+    }
+    let writers = shared_files_map.writers();
+    if !writers.is_empty() {
+        incompatibilities.push(MigrationIncompatibility::WritersDropped(writers));
+    }
+
+    incompatibilities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{single_format_incompatibilities, to_shared_format, MigrationIncompatibility};
+    use crate::{
+        app::{
+            files::{
+                shared_files_map::StoredFilesMap, FileInfo, FilesMap, FilesMapFormat,
+                SharedFilesMap,
+            },
+            test_helpers::new_safe_instance,
+        },
+        retry_loop, Error, SafeUrl, VersionHash,
+    };
+
+    use anyhow::{anyhow, Result};
+    use sn_interface::types::Keypair;
+    use std::collections::{BTreeMap, BTreeSet};
+
+    fn file_info(link: &str) -> FileInfo {
+        BTreeMap::from([
+            ("type".to_string(), "text/plain".to_string()),
+            ("link".to_string(), link.to_string()),
+        ])
+    }
+
+    #[test]
+    fn single_writer_versions_merge_below_shared_writes() {
+        let (alice, migrator) = (
+            Keypair::new_ed25519().public_key(),
+            Keypair::new_ed25519().public_key(),
+        );
+        let mut shared = SharedFilesMap::default();
+        shared.insert("/a.md", file_info("safe://a-shared"), alice, 1);
+        shared.remove("/b.md", alice, 1);
+        let single = FilesMap::from([
+            ("/a.md".to_string(), file_info("safe://a-single")),
+            ("/b.md".to_string(), file_info("safe://b-single")),
+            ("/c.md".to_string(), file_info("safe://c-single")),
+        ]);
+
+        let merged = to_shared_format(
+            &[
+                StoredFilesMap::Shared(shared),
+                StoredFilesMap::Single(single),
+            ],
+            migrator,
+        );
+        assert_eq!(
+            merged.files_map(),
+            FilesMap::from([
+                ("/a.md".to_string(), file_info("safe://a-shared")),
+                ("/c.md".to_string(), file_info("safe://c-single")),
+            ])
+        );
+    }
+
+    #[test]
+    fn removals_and_writers_dropped_by_the_single_format_are_reported() {
+        let writer = Keypair::new_ed25519().public_key();
+        let mut shared = SharedFilesMap::default();
+        assert!(single_format_incompatibilities(&shared).is_empty());
+
+        shared.insert("/kept.md", file_info("safe://kept"), writer, 1);
+        shared.insert("/removed.md", file_info("safe://removed"), writer, 1);
+        shared.remove("/removed.md", writer, 2);
+        assert_eq!(
+            single_format_incompatibilities(&shared),
+            vec![
+                MigrationIncompatibility::RemovalsDropped(vec!["/removed.md".to_string()]),
+                MigrationIncompatibility::WritersDropped(BTreeSet::from([writer])),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_migrate_container_to_shared_format() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let xorurl = safe.files_container_create_shared(BTreeSet::new()).await?;
+
+        // a version written in the single-writer format, as apps predating shared
+        // containers did, which prevents the container from being updated
+        let files_map = FilesMap::from([("/legacy.md".to_string(), file_info("safe://legacy"))]);
+        let files_map_xorurl = safe.store_files_map(&files_map).await?;
+        let legacy_hash = safe
+            .register_write(&xorurl, files_map_xorurl.into_bytes(), BTreeSet::new())
+            .await?;
+        let legacy_version = VersionHash::from(&legacy_hash);
+
+        let migration = retry_loop!(safe.migrate_container(&xorurl, FilesMapFormat::Shared));
+        assert_eq!(
+            migration.migrated_versions,
+            BTreeSet::from([legacy_version])
+        );
+        assert!(migration.incompatibilities.is_empty());
+        let version = migration
+            .version
+            .ok_or_else(|| anyhow!("no version was written"))?;
+
+        let (latest, migrated_files_map) = retry_loop!(safe.files_container_get(&xorurl))
+            .ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+        assert_eq!(latest, version);
+        assert_eq!(migrated_files_map, files_map);
+
+        // the legacy version is still part of the history
+        let mut legacy_url = SafeUrl::from_url(&xorurl)?;
+        legacy_url.set_content_version(Some(legacy_version));
+        let (_, legacy_files_map) = retry_loop!(safe.files_container_get(&legacy_url.to_string()))
+            .ok_or_else(|| anyhow!("legacy version was unexpectedly empty"))?;
+        assert_eq!(legacy_files_map, files_map);
+
+        // there's nothing left to migrate
+        let migration = safe
+            .migrate_container(&xorurl, FilesMapFormat::Shared)
+            .await?;
+        assert_eq!(migration.version, None);
+        assert!(migration.migrated_versions.is_empty());
+
+        // a shared container can't be migrated to the single-writer format
+        assert!(matches!(
+            safe.migrate_container(&xorurl, FilesMapFormat::Single)
+                .await,
+            Err(Error::InvalidInput(_))
+        ));
+
+        Ok(())
+    }
+}
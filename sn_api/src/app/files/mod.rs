@@ -8,41 +8,72 @@
 
 mod file_system;
 mod files_map;
+mod history;
 mod metadata;
+mod migration;
 mod realpath;
+mod shared_files_map;
+mod snapshot;
+mod upload_index;
 
 use crate::{
-    app::consts::*, app::nrs::VersionHash, resolver::Range, ContentType, DataType, Error, Result,
-    Safe, SafeUrl, Scope, XorUrl,
+    app::consts::*,
+    app::nrs::VersionHash,
+    register::{Entry, EntryHash, WriteAccess},
+    resolver::Range,
+    ContentType, DataMapInfo, DataType, Error, Result, Safe, SafeUrl, Scope, XorUrl,
 };
 use bytes::{Buf, Bytes};
 use file_system::{
     file_system_dir_walk, file_system_single_file, normalise_path_separator, upload_file_to_net,
 };
-use files_map::add_or_update_file_item;
+use files_map::{add_or_update_file_item, diff_files_maps};
 use log::{debug, info, warn};
 use relative_path::RelativePath;
+use serde::Serialize;
+use shared_files_map::StoredFilesMap;
 use sn_client::Client;
-use sn_interface::types::BytesAddress;
+use sn_interface::messaging::data::StorageReceipt;
+use sn_interface::types::{BytesAddress, PublicKey};
 use std::{
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashSet},
     iter::FromIterator,
     path::{Path, PathBuf},
     str,
+    time::Duration,
 };
 
-pub(crate) use files_map::{file_map_for_path, get_file_link_and_metadata};
+pub(crate) use files_map::{file_map_for_path, get_file_link_and_metadata, set_cache_control};
 pub(crate) use metadata::FileMeta;
 pub(crate) use realpath::RealPath;
 
-pub use files_map::{FileInfo, FilesMap, FilesMapChange, GetAttr};
+pub use files_map::{
+    validate_files_map, CacheControl, FileInfo, FileInfoDiff, FilesMap, FilesMapChange,
+    FilesMapReadMode, FilesMapViolation, FilesMapViolationKind, GetAttr, SymlinkPolicy,
+};
+pub use history::{FileMatch, PathPattern};
+pub use migration::{ContainerMigration, MigrationIncompatibility};
+pub use shared_files_map::{FilesMapFormat, SharedFilesMap};
+pub use snapshot::SnapshotBundle;
+pub use upload_index::UploadIndex;
 
 // List of files uploaded with details if they were added, updated or removed from FilesContainer
 pub type ProcessedFiles = BTreeMap<PathBuf, FilesMapChange>;
 
+// Outcome of syncing up a local folder with a FilesContainer: the version of the container, if
+// it's not empty, the local files processed, and how each entry of its FilesMap changed
+#[derive(Debug)]
+pub struct SyncReport {
+    pub version: Option<(VersionHash, FilesMap)>,
+    pub processed_files: ProcessedFiles,
+    pub entry_changes: BTreeMap<String, FileInfoDiff>,
+}
+
 const ERROR_MSG_NO_FILES_CONTAINER_FOUND: &str = "No FilesContainer found at this address";
 // Type tag to use for the FilesContainer stored on Register
 pub(crate) const FILES_CONTAINER_TYPE_TAG: u64 = 1_100;
+// Type tag to use for the FilesContainer stored on Register, when several writers can update it
+pub(crate) const SHARED_FILES_CONTAINER_TYPE_TAG: u64 = 1_101;
 
 impl Safe {
     /// # Create an empty FilesContainer.
@@ -67,23 +98,82 @@ impl Safe {
                 FILES_CONTAINER_TYPE_TAG,
                 false,
                 ContentType::FilesContainer,
+                &WriteAccess::OwnerOnly,
             )
             .await?;
 
         Ok(xorurl)
     }
 
+    /// # Create an empty FilesContainer which several writers can update concurrently.
+    ///
+    /// Besides this instance's identity, the given writers can add, update and remove files in
+    /// it. Its FilesMap is stored as a [`SharedFilesMap`], so the versions the writers append
+    /// concurrently are merged when the container is read, rather than conflicting, and the
+    /// next version appended replaces all of them.
+    pub async fn files_container_create_shared(
+        &self,
+        writers: BTreeSet<PublicKey>,
+    ) -> Result<XorUrl> {
+        let xorurl = self
+            .register_create(
+                None,
+                SHARED_FILES_CONTAINER_TYPE_TAG,
+                false,
+                ContentType::FilesContainer,
+                &WriteAccess::Writers(writers),
+            )
+            .await?;
+
+        Ok(xorurl)
+    }
+
+    /// # Share the files of a FilesContainer with several writers.
+    ///
+    /// Creates a FilesContainer as [`Safe::files_container_create_shared`] does, holding the
+    /// files of the latest version of the FilesContainer at the given URL, which is left as is.
+    pub async fn files_container_share(
+        &self,
+        url: &str,
+        writers: BTreeSet<PublicKey>,
+    ) -> Result<XorUrl> {
+        let files_map = match self.files_container_get(url).await? {
+            Some((_, files_map)) => files_map,
+            None => FilesMap::default(),
+        };
+
+        let xorurl = self.files_container_create_shared(writers).await?;
+        if !files_map.is_empty() {
+            let safe_url = SafeUrl::from_url(&xorurl)?;
+            let _ = self
+                .append_version_to_files_container(
+                    HashSet::new(),
+                    &FilesMap::default(),
+                    &files_map,
+                    &xorurl,
+                    safe_url,
+                    false,
+                )
+                .await?;
+        }
+
+        Ok(xorurl)
+    }
+
     /// # Create a FilesContainer containing files uploaded from a local folder.
     ///
+    /// Symlinks found in the folder, to files or to directories, are handled as per the given
+    /// `symlink_policy`, which is reported for each of them in the returned `ProcessedFiles`.
+    ///
     /// ## Example
     ///
     /// ```no_run
-    /// # use sn_api::Safe;
+    /// # use sn_api::{files::SymlinkPolicy, Safe};
     /// # let mut safe = Safe::default();
     /// # let rt = tokio::runtime::Runtime::new().unwrap();
     /// # rt.block_on(async {
     ///     safe.connect(None, None, None).await.unwrap();
-    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from("./testdata", None, true, true).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from("./testdata", None, true, SymlinkPolicy::FollowTarget).await.unwrap();
     ///     assert!(xorurl.contains("safe://"))
     /// # });
     /// ```
@@ -92,11 +182,11 @@ impl Safe {
         location: P,
         dst: Option<&Path>,
         recursive: bool,
-        follow_links: bool,
+        symlink_policy: SymlinkPolicy,
     ) -> Result<(XorUrl, ProcessedFiles, FilesMap)> {
         // Let's upload the files (if not dry_run) and generate the list of local files paths
         let mut processed_files =
-            file_system_dir_walk(self, location.as_ref(), recursive, follow_links).await?;
+            file_system_dir_walk(self, location.as_ref(), recursive, symlink_policy).await?;
 
         // The FilesContainer is stored on a Register
         // and the link to the serialised FilesMap as the entry's value
@@ -105,7 +195,7 @@ impl Safe {
             &mut processed_files,
             location.as_ref(),
             dst,
-            follow_links,
+            symlink_policy,
         )
         .await?;
 
@@ -128,7 +218,9 @@ impl Safe {
                 .write_to_register(reg_address, entry, Default::default())
                 .await?;
 
-            client.publish_register_ops(reg_op).await?;
+            client
+                .publish_register_ops(reg_op, self.ordered_writes)
+                .await?;
 
             // We return versioned xorurl
             reg_url.set_content_version(Some(VersionHash::from(&entry_hash)));
@@ -142,12 +234,12 @@ impl Safe {
     /// ## Example
     ///
     /// ```no_run
-    /// # use sn_api::Safe;
+    /// # use sn_api::{files::SymlinkPolicy, Safe};
     /// # let mut safe = Safe::default();
     /// # let rt = tokio::runtime::Runtime::new().unwrap();
     /// # rt.block_on(async {
     /// #   safe.connect(None, None, None).await.unwrap();
-    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from("./testdata", None, true, true).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from("./testdata", None, true, SymlinkPolicy::FollowTarget).await.unwrap();
     ///     let (version, files_map) = safe.files_container_get(&xorurl).await.unwrap().unwrap();
     ///     println!("FilesContainer fetched is at version: {}", version);
     ///     println!("FilesMap of fetched version is: {:?}", files_map);
@@ -160,6 +252,26 @@ impl Safe {
         self.fetch_files_container(&safe_url).await
     }
 
+    /// # Fetch an existing FilesContainer, checking its FilesMap.
+    ///
+    /// All the entries of the FilesMap are validated. In `FilesMapReadMode::Strict` mode an
+    /// `Error::CorruptFilesMap` listing all the violations is returned if any entry is corrupt,
+    /// whereas in `FilesMapReadMode::Lenient` mode the corrupt entries are left out of the
+    /// FilesMap returned, and the violations found are returned along with it.
+    pub async fn files_container_get_checked(
+        &self,
+        url: &str,
+        mode: FilesMapReadMode,
+    ) -> Result<Option<(VersionHash, FilesMap, Vec<FilesMapViolation>)>> {
+        match self.files_container_get(url).await? {
+            Some((version, files_map)) => {
+                let (files_map, violations) = mode.check(files_map)?;
+                Ok(Some((version, files_map, violations)))
+            }
+            None => Ok(None),
+        }
+    }
+
     /// Fetch a FilesContainer from a SafeUrl without performing any type of URL resolution
     pub(crate) async fn fetch_files_container(
         &self,
@@ -197,7 +309,18 @@ impl Safe {
             safe_url.to_string()
         );
         if entries.len() > 1 {
-            return Err(Error::NotImplementedError("Multiple file container entries not managed, this happends when 2 clients write concurrently to a file container".to_string()));
+            if safe_url.type_tag() != SHARED_FILES_CONTAINER_TYPE_TAG {
+                return Err(Error::NotImplementedError("Multiple file container entries not managed, this happends when 2 clients write concurrently to a file container".to_string()));
+            }
+            // The versions written concurrently to a shared FilesContainer are merged,
+            // and reported along with the version of one of them
+            let shared_files_map = self.merge_shared_files_maps(&entries).await?;
+            let version = entries
+                .iter()
+                .map(|(hash, _)| VersionHash::from(hash))
+                .max()
+                .ok_or_else(|| Error::EmptyContent(safe_url.to_string()))?;
+            return Ok(Some((version, shared_files_map.files_map())));
         }
         let first_entry = entries.iter().next();
         let (version, files_map_xorurl) = if let Some((v, m)) = first_entry {
@@ -207,18 +330,66 @@ impl Safe {
             return Ok(None);
         };
 
-        // Using the FilesMap XOR-URL we can now fetch the FilesMap and deserialise it
+        let files_map = self.fetch_files_map(files_map_xorurl).await?;
+        debug!("Files map retrieved.... {:?}", &version);
+
+        Ok(Some((version, files_map)))
+    }
+
+    // Using the FilesMap XOR-URL stored in a FilesContainer entry, fetch the FilesMap and deserialise it
+    async fn fetch_files_map(&self, files_map_xorurl: &str) -> Result<FilesMap> {
+        let stored_files_map = self.fetch_stored_files_map(files_map_xorurl).await?;
+        Ok(stored_files_map.files_map())
+    }
+
+    // Same as `fetch_files_map`, keeping the format the FilesMap was stored in
+    async fn fetch_stored_files_map(&self, files_map_xorurl: &str) -> Result<StoredFilesMap> {
         let files_map_url = SafeUrl::from_xorurl(files_map_xorurl)?;
         let serialised_files_map = self.fetch_data(&files_map_url, None).await?;
-        let files_map = serde_json::from_slice(serialised_files_map.chunk()).map_err(|err| {
+        serde_json::from_slice(serialised_files_map.chunk()).map_err(|err| {
             Error::ContentError(format!(
                 "Couldn't deserialise the FilesMap stored in the FilesContainer: {:?}",
                 err
             ))
-        })?;
-        debug!("Files map retrieved.... {:?}", &version);
+        })
+    }
 
-        Ok(Some((version, files_map)))
+    // Fetch the latest versions of a shared FilesContainer, along with their merged FilesMap
+    async fn fetch_shared_files_map(
+        &self,
+        safe_url: &SafeUrl,
+    ) -> Result<(BTreeSet<EntryHash>, SharedFilesMap)> {
+        let mut safe_url = safe_url.clone();
+        safe_url.set_content_version(None);
+        let entries = match self.register_fetch_entries(&safe_url).await {
+            Ok(entries) => entries,
+            Err(Error::EmptyContent(_)) => BTreeSet::new(),
+            Err(err) => return Err(err),
+        };
+
+        let shared_files_map = self.merge_shared_files_maps(&entries).await?;
+        let heads = entries.into_iter().map(|(hash, _)| hash).collect();
+        Ok((heads, shared_files_map))
+    }
+
+    // Merge the FilesMaps stored in entries of a shared FilesContainer
+    async fn merge_shared_files_maps(
+        &self,
+        entries: &BTreeSet<(EntryHash, Entry)>,
+    ) -> Result<SharedFilesMap> {
+        let mut merged = SharedFilesMap::default();
+        for (hash, entry) in entries {
+            match self.fetch_stored_files_map(str::from_utf8(entry)?).await? {
+                StoredFilesMap::Shared(shared_files_map) => merged.merge(&shared_files_map),
+                StoredFilesMap::Single(_) => {
+                    return Err(Error::ContentError(format!(
+                        "Version {} of the shared FilesContainer holds a single-writer FilesMap",
+                        VersionHash::from(hash)
+                    )))
+                }
+            }
+        }
+        Ok(merged)
     }
 
     /// # Sync up local folder with the content on a FilesContainer.
@@ -226,13 +397,13 @@ impl Safe {
     /// ## Example
     ///
     /// ```no_run
-    /// # use sn_api::Safe;
+    /// # use sn_api::{files::SymlinkPolicy, Safe};
     /// # let mut safe = Safe::default();
     /// # let rt = tokio::runtime::Runtime::new().unwrap();
     /// # rt.block_on(async {
     /// #   safe.connect(None, None, None).await.unwrap();
-    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from("./testdata", None, true, false).await.unwrap();
-    ///     let (version, new_processed_files, new_files_map) = safe.files_container_sync("./testdata", &xorurl, true, true, false, false).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from("./testdata", None, true, SymlinkPolicy::StoreAsLink).await.unwrap();
+    ///     let (version, new_processed_files, new_files_map) = safe.files_container_sync("./testdata", &xorurl, true, SymlinkPolicy::FollowTarget, false, false).await.unwrap();
     ///     println!("FilesContainer synced up is at version: {}", version);
     ///     println!("The local files that were synced up are: {:?}", new_processed_files);
     ///     println!("The FilesMap of the updated FilesContainer now is: {:?}", new_files_map);
@@ -244,10 +415,54 @@ impl Safe {
         location: P,
         url: &str,
         recursive: bool,
-        follow_links: bool,
+        symlink_policy: SymlinkPolicy,
         delete: bool,
         update_nrs: bool,
     ) -> Result<(Option<(VersionHash, FilesMap)>, ProcessedFiles)> {
+        let report = self
+            .files_container_sync_report(
+                location,
+                url,
+                recursive,
+                symlink_policy,
+                delete,
+                update_nrs,
+            )
+            .await?;
+        Ok((report.version, report.processed_files))
+    }
+
+    /// # Sync up local folder with the content on a FilesContainer, reporting how each entry changed.
+    ///
+    /// Same as `files_container_sync`, with the report also telling, for each entry of the
+    /// FilesMap added, removed or updated, its old and new size, modification time and link,
+    /// and which of its metadata fields changed.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{files::SymlinkPolicy, Safe};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from("./testdata", None, true, SymlinkPolicy::StoreAsLink).await.unwrap();
+    ///     let report = safe.files_container_sync_report("./testdata", &xorurl, true, SymlinkPolicy::FollowTarget, false, false).await.unwrap();
+    ///     for (path, diff) in report.entry_changes {
+    ///         println!("{}: {}", path, diff);
+    ///     }
+    /// # });
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub async fn files_container_sync_report<P: AsRef<Path>>(
+        &self,
+        location: P,
+        url: &str,
+        recursive: bool,
+        symlink_policy: SymlinkPolicy,
+        delete: bool,
+        update_nrs: bool,
+    ) -> Result<SyncReport> {
         if delete && !recursive {
             return Err(Error::InvalidInput(
                 "'delete' is not allowed if 'recursive' is not set".to_string(),
@@ -279,48 +494,64 @@ impl Safe {
         // Use a dry runner only for this next operation
         let dry_runner = Safe::dry_runner(Some(self.xorurl_base));
         let processed_files =
-            file_system_dir_walk(&dry_runner, location.as_ref(), recursive, follow_links).await?;
+            file_system_dir_walk(&dry_runner, location.as_ref(), recursive, symlink_policy).await?;
 
         let dst_path = Path::new(safe_url.path());
 
         let (processed_files, new_files_map, success_count) = files_map_sync(
             self,
-            current_files_map,
+            current_files_map.clone(),
             location.as_ref(),
             processed_files,
             Some(dst_path),
             delete,
             false,
             true,
-            follow_links,
+            symlink_policy,
         )
         .await?;
 
-        self.update_files_container(
-            success_count,
-            current_version,
-            new_files_map,
+        let entry_changes = diff_files_maps(&current_files_map, &new_files_map);
+        let (version, processed_files) = self
+            .update_files_container(
+                success_count,
+                current_version,
+                &current_files_map,
+                new_files_map,
+                processed_files,
+                url,
+                safe_url,
+                update_nrs,
+            )
+            .await?;
+
+        Ok(SyncReport {
+            version,
             processed_files,
-            url,
-            safe_url,
-            update_nrs,
-        )
-        .await
+            entry_changes,
+        })
     }
 
     /// # Add a file, either a local path or an already uploaded file, on an existing FilesContainer.
     ///
+    /// If the local path is a symlink, it's handled as per the given `symlink_policy`, a symlink
+    /// to a directory being stored as a link or skipped, as only files can be added.
+    ///
+    /// The given caching hints, if any, are stored in the FileInfo of the file added, for gateways
+    /// and clients to cache it as intended. A file updated without hints doesn't keep its previous
+    /// ones, as they may not suit its new content.
+    ///
     /// ## Example
     ///
     /// ```no_run
-    /// # use sn_api::Safe;
+    /// # use sn_api::{files::SymlinkPolicy, Safe};
     /// # let mut safe = Safe::default();
     /// # let rt = tokio::runtime::Runtime::new().unwrap();
     /// # rt.block_on(async {
     /// #   safe.connect(None, None, None).await.unwrap();
-    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from("./testdata", None, true, true).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from("./testdata", None, true, SymlinkPolicy::FollowTarget).await.unwrap();
     ///     let new_file_name = format!("{}/new_name_test.md", xorurl);
-    ///     let (version, new_processed_files, new_files_map) = safe.files_container_add("./testdata/test.md", &new_file_name, false, false, true).await.unwrap();
+    ///     let (version, new_processed_files, new_files_map) = safe.files_container_add("./testdata/test.md", &new_file_name, false, false, SymlinkPolicy::FollowTarget, None).await.unwrap();
     ///     println!("FilesContainer is now at version: {}", version);
     ///     println!("The local files that were synced up are: {:?}", new_processed_files);
     ///     println!("The FilesMap of the updated FilesContainer now is: {:?}", new_files_map);
@@ -332,7 +563,8 @@ impl Safe {
         url: &str,
         force: bool,
         update_nrs: bool,
-        follow_links: bool,
+        symlink_policy: SymlinkPolicy,
+        cache_control: Option<CacheControl>,
     ) -> Result<(Option<(VersionHash, FilesMap)>, ProcessedFiles)> {
         debug!("Adding file to FilesContainer at {}", url);
         let (safe_url, current_version, current_files_map) =
@@ -341,35 +573,47 @@ impl Safe {
         let dst_path = Path::new(safe_url.path());
 
         // Let's act according to if it's a local file path or a safe:// location
-        let (processed_files, new_files_map, success_count) = if source_file.starts_with("safe://")
-        {
-            files_map_add_link(self, current_files_map, source_file, dst_path, force).await?
-        } else {
-            // We then assume source is a local path
-            let source_path = Path::new(source_file);
-
-            // Let's generate the list of local files paths, without uploading any new file yet.
-            // Use dry runner only for this next operation
-            let dry_runner = Safe::dry_runner(Some(self.xorurl_base));
-            let processed_files = file_system_single_file(&dry_runner, source_path).await?;
-
-            files_map_sync(
-                self,
-                current_files_map,
-                source_path,
-                processed_files,
-                Some(dst_path),
-                false,
-                force,
-                false,
-                follow_links,
-            )
-            .await?
-        };
+        let (processed_files, mut new_files_map, success_count) =
+            if source_file.starts_with("safe://") {
+                files_map_add_link(
+                    self,
+                    current_files_map.clone(),
+                    source_file,
+                    dst_path,
+                    force,
+                )
+                .await?
+            } else {
+                // We then assume source is a local path
+                let source_path = Path::new(source_file);
+
+                // Let's generate the list of local files paths, without uploading any new file yet.
+                // Use dry runner only for this next operation
+                let dry_runner = Safe::dry_runner(Some(self.xorurl_base));
+                let processed_files =
+                    file_system_single_file(&dry_runner, source_path, symlink_policy).await?;
+
+                files_map_sync(
+                    self,
+                    current_files_map.clone(),
+                    source_path,
+                    processed_files,
+                    Some(dst_path),
+                    false,
+                    force,
+                    false,
+                    symlink_policy,
+                )
+                .await?
+            };
+        if let Some(cache_control) = cache_control {
+            set_cache_control(&mut new_files_map, &current_files_map, cache_control);
+        }
 
         self.update_files_container(
             success_count,
             current_version,
+            &current_files_map,
             new_files_map,
             processed_files,
             url,
@@ -381,17 +625,19 @@ impl Safe {
 
     /// # Add a file, from raw bytes, on an existing FilesContainer.
     ///
+    /// The given caching hints, if any, are stored in the FileInfo of the file added.
+    ///
     /// ## Example
     ///
     /// ```no_run
-    /// # use sn_api::Safe;
+    /// # use sn_api::{files::SymlinkPolicy, Safe};
     /// # let mut safe = Safe::default();
     /// # let rt = tokio::runtime::Runtime::new().unwrap();
     /// # rt.block_on(async {
     /// #   safe.connect(None, None, None).await.unwrap();
-    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from("./testdata", None, true, true).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from("./testdata", None, true, SymlinkPolicy::FollowTarget).await.unwrap();
     ///     let new_file_name = format!("{}/new_name_test.md", xorurl);
-    ///     let (version, new_processed_files, new_files_map) = safe.files_container_add_from_raw(b"0123456789", &new_file_name, false, false).await.unwrap();
+    ///     let (version, new_processed_files, new_files_map) = safe.files_container_add_from_raw(b"0123456789", &new_file_name, false, false, None).await.unwrap();
     ///     println!("FilesContainer is now at version: {}", version);
     ///     println!("The local files that were synced up are: {:?}", new_processed_files);
     ///     println!("The FilesMap of the updated FilesContainer now is: {:?}", new_files_map);
@@ -403,6 +649,7 @@ impl Safe {
         url: &str,
         force: bool,
         update_nrs: bool,
+        cache_control: Option<CacheControl>,
     ) -> Result<(Option<(VersionHash, FilesMap)>, ProcessedFiles)> {
         let (safe_url, current_version, current_files_map) =
             validate_files_add_params(self, "", url, update_nrs).await?;
@@ -410,12 +657,22 @@ impl Safe {
         let new_file_xorurl = self.store_public_bytes(data, None).await?;
 
         let dst_path = Path::new(safe_url.path());
-        let (processed_files, new_files_map, success_count) =
-            files_map_add_link(self, current_files_map, &new_file_xorurl, dst_path, force).await?;
+        let (processed_files, mut new_files_map, success_count) = files_map_add_link(
+            self,
+            current_files_map.clone(),
+            &new_file_xorurl,
+            dst_path,
+            force,
+        )
+        .await?;
+        if let Some(cache_control) = cache_control {
+            set_cache_control(&mut new_files_map, &current_files_map, cache_control);
+        }
 
         self.update_files_container(
             success_count,
             current_version,
+            &current_files_map,
             new_files_map,
             processed_files,
             url,
@@ -430,12 +687,12 @@ impl Safe {
     /// ## Example
     ///
     /// ```no_run
-    /// # use sn_api::Safe;
+    /// # use sn_api::{files::SymlinkPolicy, Safe};
     /// # let mut safe = Safe::default();
     /// # let rt = tokio::runtime::Runtime::new().unwrap();
     /// # rt.block_on(async {
     /// #   safe.connect(None, None, None).await.unwrap();
-    ///     let (xorurl, processed_files, files_map) = safe.files_container_create_from("./testdata/", None, true, true).await.unwrap();
+    ///     let (xorurl, processed_files, files_map) = safe.files_container_create_from("./testdata/", None, true, SymlinkPolicy::FollowTarget).await.unwrap();
     ///     let remote_file_path = format!("{}/test.md", xorurl);
     ///     let (version, new_processed_files, new_files_map) = safe.files_container_remove_path(&remote_file_path, false, false).await.unwrap();
     ///     println!("FilesContainer is now at version: {}", version);
@@ -481,13 +738,14 @@ impl Safe {
         };
 
         let (processed_files, new_files_map, success_count) =
-            files_map_remove_path(Path::new(dst_path), files_map, recursive)?;
+            files_map_remove_path(Path::new(dst_path), files_map.clone(), recursive)?;
 
         let version = if success_count == 0 {
             current_version
         } else {
             self.append_version_to_files_container(
                 HashSet::from_iter([current_version]),
+                &files_map,
                 &new_files_map,
                 url,
                 safe_url,
@@ -506,6 +764,7 @@ impl Safe {
         &self,
         files_map_changes_count: u64,
         current_version: Option<VersionHash>,
+        current_files_map: &FilesMap,
         new_files_map: FilesMap,
         processed_files: ProcessedFiles,
         url: &str,
@@ -535,6 +794,7 @@ impl Safe {
             let new_version = self
                 .append_version_to_files_container(
                     parent_versions,
+                    current_files_map,
                     &new_files_map,
                     url,
                     safe_url,
@@ -548,26 +808,37 @@ impl Safe {
 
     // Private helper function to append new version of the FilesMap to the Files Container
     // It flagged with `update_nrs`, it will also update the link in the corresponding NRS Map Container
+    // The current FilesMap is the one the new one was made from, i.e. the one of the current version.
     #[allow(clippy::too_many_arguments)]
     async fn append_version_to_files_container(
         &self,
         current_version: HashSet<VersionHash>,
+        current_files_map: &FilesMap,
         new_files_map: &FilesMap,
         url: &str,
         mut safe_url: SafeUrl,
         update_nrs: bool,
     ) -> Result<VersionHash> {
+        let mut replace = current_version.iter().map(|e| e.entry_hash()).collect();
+
         // The FilesContainer is updated by adding an entry containing the link to
         // the file with the serialised new version of the FilesMap.
-        let files_map_xorurl = if !self.dry_run_mode {
-            self.store_files_map(new_files_map).await?
-        } else {
+        let files_map_xorurl = if self.dry_run_mode {
             "".to_string()
+        } else if safe_url.type_tag() == SHARED_FILES_CONTAINER_TYPE_TAG {
+            // Our changes are applied on top of the latest versions of all the writers,
+            // which the new version then replaces
+            let (heads, mut shared_files_map) = self.fetch_shared_files_map(&safe_url).await?;
+            let writer = self.get_safe_client()?.public_key();
+            shared_files_map.record_changes(current_files_map, new_files_map, writer);
+            replace = heads;
+            self.store_files_map(&shared_files_map).await?
+        } else {
+            self.store_files_map(new_files_map).await?
         };
 
         // append entry to register
         let entry = files_map_xorurl.as_bytes().to_vec();
-        let replace = current_version.iter().map(|e| e.entry_hash()).collect();
         let entry_hash = &self
             .register_write(&safe_url.to_string(), entry, replace)
             .await?;
@@ -609,7 +880,84 @@ impl Safe {
         bytes: Bytes,
         media_type: Option<&str>,
     ) -> Result<XorUrl> {
-        self.store_bytes(bytes, media_type, Scope::Public).await
+        let upload_index = match &self.upload_index {
+            Some(upload_index) if !self.dry_run_mode => upload_index.clone(),
+            _ => return self.store_bytes(bytes, media_type, Scope::Public).await,
+        };
+
+        if let Some(xorurl) = self
+            .find_uploaded(&upload_index, &bytes, media_type)
+            .await?
+        {
+            return Ok(xorurl);
+        }
+
+        let xorurl = self
+            .store_bytes(bytes.clone(), media_type, Scope::Public)
+            .await?;
+        // the content was uploaded regardless, it'll just be uploaded again next time
+        if let Err(err) = upload_index.insert(&bytes, xorurl.clone()) {
+            warn!("Failed to record upload of {}: {}", xorurl, err);
+        }
+
+        Ok(xorurl)
+    }
+
+    // Private helper to find the XOR-URL the given content was uploaded at, according to the
+    // upload index, as long as it can be found on the network
+    async fn find_uploaded(
+        &self,
+        upload_index: &UploadIndex,
+        bytes: &[u8],
+        media_type: Option<&str>,
+    ) -> Result<Option<XorUrl>> {
+        let content_type = media_type_to_content_type(media_type)?;
+        let indexed = match upload_index.get(bytes) {
+            Some(xorurl) => xorurl,
+            None => return Ok(None),
+        };
+
+        let address = match SafeUrl::from_xorurl(&indexed) {
+            Ok(safe_url)
+                if safe_url.data_type() == DataType::File && safe_url.scope() == Scope::Public =>
+            {
+                BytesAddress::Public(safe_url.xorname())
+            }
+            _ => {
+                warn!("Dropping invalid XOR-URL {} from upload index", indexed);
+                upload_index.remove(bytes)?;
+                return Ok(None);
+            }
+        };
+
+        // chunks are stored concurrently, so all of them are checked, not only the head chunk
+        match self
+            .get_safe_client()?
+            .verify_all_chunks_stored(address)
+            .await
+        {
+            Ok(_) => {
+                debug!(
+                    "{} bytes of data were uploaded already at {}",
+                    bytes.len(),
+                    indexed
+                );
+                // the media type may differ from the one the content was uploaded with
+                Ok(Some(SafeUrl::encode_bytes(
+                    address,
+                    content_type,
+                    self.xorurl_base,
+                )?))
+            }
+            Err(err) => {
+                debug!(
+                    "Data in upload index at {} not found on the network, uploading it again: {:?}",
+                    indexed, err
+                );
+                upload_index.remove(bytes)?;
+                Ok(None)
+            }
+        }
     }
 
     /// Store a private file
@@ -621,6 +969,57 @@ impl Safe {
         self.store_bytes(bytes, media_type, Scope::Private).await
     }
 
+    /// Store temporary data, which expires once the given `ttl` elapsed
+    ///
+    /// Unlike files, temporary data is not kept forever on the network, which suits caches and
+    /// ephemeral app state. It's stored in a single chunk, so it's limited to about 1MB, and
+    /// can be fetched like any public file until it expires.
+    pub async fn store_temporary(
+        &self,
+        bytes: Bytes,
+        media_type: Option<&str>,
+        ttl: Duration,
+    ) -> Result<XorUrl> {
+        let content_type = media_type_to_content_type(media_type)?;
+        if self.dry_run_mode {
+            return Err(Error::InvalidInput(
+                "Temporary data can't be stored in dry-run mode".to_string(),
+            ));
+        }
+
+        debug!(
+            "Storing {} bytes of temporary data for {:?}",
+            bytes.len(),
+            ttl
+        );
+        let address = self
+            .get_safe_client()?
+            .store_temporary(bytes, Scope::Public, ttl)
+            .await?;
+
+        SafeUrl::encode_bytes(address, content_type, self.xorurl_base)
+    }
+
+    /// Store a public file, returning along with its XOR-URL the storage receipts of its chunks.
+    /// The receipts can be handed to third parties as proof the network accepted the content,
+    /// who can check them with [`Safe::verify_receipt`].
+    pub async fn store_public_bytes_with_receipts(
+        &self,
+        bytes: Bytes,
+        media_type: Option<&str>,
+    ) -> Result<(XorUrl, Vec<StorageReceipt>)> {
+        self.store_bytes_with_receipts(bytes, media_type, Scope::Public)
+            .await
+    }
+
+    /// Check a storage receipt was signed by a section of the network we are connected to,
+    /// proving the network accepted the chunk it was issued for.
+    pub async fn verify_receipt(&self, receipt: &StorageReceipt) -> Result<()> {
+        let client = self.get_safe_client()?;
+        client.verify_storage_receipt(receipt).await?;
+        Ok(())
+    }
+
     // Private helper to store a public/private file
     async fn store_bytes(
         &self,
@@ -628,35 +1027,38 @@ impl Safe {
         media_type: Option<&str>,
         scope: Scope,
     ) -> Result<XorUrl> {
-        let content_type = media_type.map_or_else(
-            || Ok(ContentType::Raw),
-            |media_type_str| {
-                if SafeUrl::is_media_type_supported(media_type_str) {
-                    Ok(ContentType::MediaType(media_type_str.to_string()))
-                } else {
-                    Err(Error::InvalidMediaType(format!(
-                        "Media-type '{}' not supported. You can pass 'None' as the 'media_type' for this content to be treated as raw",
-                        media_type_str
-                    )))
-                }
-            },
-        )?;
+        let (xorurl, _receipts) = self
+            .store_bytes_with_receipts(bytes, media_type, scope)
+            .await?;
+        Ok(xorurl)
+    }
 
-        let address = if self.dry_run_mode {
+    // Private helper to store a public/private file, returning the storage receipts of its chunks
+    async fn store_bytes_with_receipts(
+        &self,
+        bytes: Bytes,
+        media_type: Option<&str>,
+        scope: Scope,
+    ) -> Result<(XorUrl, Vec<StorageReceipt>)> {
+        let content_type = media_type_to_content_type(media_type)?;
+
+        let (address, receipts) = if self.dry_run_mode {
             debug!(
                 "Calculating network address for {} bytes of data",
                 bytes.len()
             );
-            Client::calculate_address(bytes, scope)?
+            (Client::calculate_address(bytes, scope)?, vec![])
         } else {
             debug!("Storing {} bytes of data", bytes.len());
             let client = self.get_safe_client()?;
-            let (address, _) = client.upload_and_verify(bytes, scope).await?;
-            address
+            let (address, receipts) = client.upload_with_receipts(bytes, scope).await?;
+            // let's now try to retrieve it, to verify all the data was uploaded
+            let _ = client.read_bytes(address).await?;
+            (address, receipts)
         };
         let xorurl = SafeUrl::encode_bytes(address, content_type, self.xorurl_base)?;
 
-        Ok(xorurl)
+        Ok((xorurl, receipts))
     }
 
     /// # Get a file
@@ -676,21 +1078,71 @@ impl Safe {
     /// # });
     /// ```
     pub async fn files_get(&self, url: &str, range: Range) -> Result<Bytes> {
+        self.files_get_with_chunk_repair(url, range, true).await
+    }
+
+    /// # Get a file, choosing whether missing chunks are repaired
+    /// Get file from the network. When `repair_missing_chunks` is set, any chunk which
+    /// cannot be found is reported to the Elders of its section, so it's replicated again,
+    /// and its retrieval is retried. Latency-sensitive reads can opt out of it, in which case
+    /// the read fails as soon as a chunk can't be found.
+    pub async fn files_get_with_chunk_repair(
+        &self,
+        url: &str,
+        range: Range,
+        repair_missing_chunks: bool,
+    ) -> Result<Bytes> {
         // TODO: do we want ownership from other PKs yet?
         let safe_url = self.parse_and_resolve_url(url).await?;
-        self.fetch_data(&safe_url, range).await
+        self.fetch_data_with_chunk_repair(&safe_url, range, repair_missing_chunks)
+            .await
+    }
+
+    /// # Inspect the data map of a file
+    /// Get the chunks a file is stored in, along with their sizes and whether the file is
+    /// encrypted or padded, without fetching its content. The URL can be the one of a file
+    /// within a FilesContainer.
+    pub async fn inspect_datamap(&self, url: &str) -> Result<DataMapInfo> {
+        let safe_url = self.parse_and_resolve_url(url).await?;
+        let address = match (safe_url.data_type(), safe_url.scope()) {
+            (DataType::File, Scope::Public) => BytesAddress::Public(safe_url.xorname()),
+            (DataType::File, Scope::Private) => BytesAddress::Private(safe_url.xorname()),
+            (other, _) => return Err(Error::ContentError(format!("{}", other))),
+        };
+
+        let info = self.get_safe_client()?.inspect_bytes(address).await?;
+        Ok(info)
     }
 
     /// Fetch a file from a SafeUrl without performing any type of URL resolution
     pub(crate) async fn fetch_data(&self, safe_url: &SafeUrl, range: Range) -> Result<Bytes> {
+        self.fetch_data_with_chunk_repair(safe_url, range, true)
+            .await
+    }
+
+    // Private helper to fetch a file from a SafeUrl, choosing whether missing chunks are repaired
+    async fn fetch_data_with_chunk_repair(
+        &self,
+        safe_url: &SafeUrl,
+        range: Range,
+        repair_missing_chunks: bool,
+    ) -> Result<Bytes> {
         match (safe_url.data_type(), safe_url.scope()) {
             (DataType::File, Scope::Public) => {
-                self.get_bytes(BytesAddress::Public(safe_url.xorname()), range)
-                    .await
+                self.get_bytes(
+                    BytesAddress::Public(safe_url.xorname()),
+                    range,
+                    repair_missing_chunks,
+                )
+                .await
             }
             (DataType::File, Scope::Private) => {
-                self.get_bytes(BytesAddress::Private(safe_url.xorname()), range)
-                    .await
+                self.get_bytes(
+                    BytesAddress::Private(safe_url.xorname()),
+                    range,
+                    repair_missing_chunks,
+                )
+                .await
             }
             (other, _) => {
                 return Err(Error::ContentError(format!("{}", other)));
@@ -698,9 +1150,16 @@ impl Safe {
         }
     }
 
-    async fn get_bytes(&self, address: BytesAddress, range: Range) -> Result<Bytes> {
+    async fn get_bytes(
+        &self,
+        address: BytesAddress,
+        range: Range,
+        repair_missing_chunks: bool,
+    ) -> Result<Bytes> {
         debug!("Attempting to fetch data from {:?}", address.name());
-        let client = self.get_safe_client()?;
+        let client = self
+            .get_safe_client()?
+            .with_chunk_repair(repair_missing_chunks);
         let data = if let Some((start, end)) = range {
             let start = start.map(|start_index| start_index as usize).unwrap_or(0);
             let len = end
@@ -722,8 +1181,8 @@ impl Safe {
         Ok(data)
     }
 
-    // Private helper to serialise a FilesMap and store it in a file
-    async fn store_files_map(&self, files_map: &FilesMap) -> Result<String> {
+    // Private helper to serialise a FilesMap, in either format, and store it in a file
+    async fn store_files_map(&self, files_map: &impl Serialize) -> Result<String> {
         // The FilesMapContainer is a Register where each NRS Map version is
         // an entry containing the XOR-URL of the file that contains the serialised NrsMap.
         let serialised_files_map = serde_json::to_string(&files_map).map_err(|err| {
@@ -743,6 +1202,23 @@ impl Safe {
 
 // Helper functions
 
+// Turn the media type of a file being stored into its content type, Raw if none was given
+fn media_type_to_content_type(media_type: Option<&str>) -> Result<ContentType> {
+    media_type.map_or_else(
+        || Ok(ContentType::Raw),
+        |media_type_str| {
+            if SafeUrl::is_media_type_supported(media_type_str) {
+                Ok(ContentType::MediaType(media_type_str.to_string()))
+            } else {
+                Err(Error::InvalidMediaType(format!(
+                    "Media-type '{}' not supported. You can pass 'None' as the 'media_type' for this content to be treated as raw",
+                    media_type_str
+                )))
+            }
+        },
+    )
+}
+
 // Make sure the input params are valid for a files_container_add operation
 async fn validate_files_add_params(
     safe: &Safe,
@@ -845,14 +1321,22 @@ async fn files_map_sync(
     delete: bool,
     force: bool,
     compare_file_content: bool,
-    follow_links: bool,
+    symlink_policy: SymlinkPolicy,
 ) -> Result<(ProcessedFiles, FilesMap, u64)> {
     let (location_base_path, dst_base_path) = get_base_paths(location, dst_path);
     let mut updated_files_map = FilesMap::new();
     let mut processed_files = ProcessedFiles::new();
     let mut success_count = 0;
+    let follow_links = symlink_policy.follows_links();
 
-    for (local_file_name, _) in new_content.iter().filter(|(_, change)| change.is_success()) {
+    for (local_file_name, change) in new_content.into_iter().filter(|(_, c)| c.is_success()) {
+        if change.is_skipped() {
+            // symlinks left out are only reported
+            processed_files.insert(local_file_name, change);
+            continue;
+        }
+        let local_file_name = &local_file_name;
+        let link_policy = change.symlink_policy();
         let file_path = Path::new(&local_file_name);
 
         let file_name = RelativePath::new(
@@ -884,6 +1368,7 @@ async fn files_map_sync(
                     &FileMeta::from_path(local_file_name, follow_links)?,
                     None, // no xorurl link
                     false,
+                    link_policy,
                     &mut updated_files_map,
                     &mut processed_files,
                 )
@@ -923,6 +1408,7 @@ async fn files_map_sync(
                         &FileMeta::from_path(local_file_name.as_path(), follow_links)?,
                         None, // no xorurl link
                         true,
+                        link_policy,
                         &mut updated_files_map,
                         &mut processed_files,
                     )
@@ -1074,6 +1560,7 @@ async fn files_map_add_link(
                         &file_meta,
                         Some(file_link),
                         true,
+                        None,
                         &mut files_map,
                         &mut processed_files,
                     )
@@ -1111,6 +1598,7 @@ async fn files_map_add_link(
                 &FileMeta::from_type_and_size(&file_type, file_size),
                 Some(file_link),
                 false,
+                None,
                 &mut files_map,
                 &mut processed_files,
             )
@@ -1185,7 +1673,7 @@ async fn files_map_create(
     content: &mut ProcessedFiles,
     location: &Path,
     dst_path: Option<&Path>,
-    follow_links: bool,
+    symlink_policy: SymlinkPolicy,
 ) -> Result<FilesMap> {
     let mut files_map = FilesMap::default();
 
@@ -1198,11 +1686,12 @@ async fn files_map_create(
     // either, but is much less data.  Is there a more efficient way?
     let names = content.keys().cloned().collect::<Vec<_>>();
     for file_name in names {
-        let link = match &content[&file_name] {
-            FilesMapChange::Failed(_) => continue,
-            FilesMapChange::Added(link)
-            | FilesMapChange::Updated(link)
-            | FilesMapChange::Removed(link) => link.clone(),
+        // failed items, and symlinks left out, aren't added
+        let change = &content[&file_name];
+        let link_policy = change.symlink_policy();
+        let link = match change.link() {
+            Some(link) if change.is_success() => link.clone(),
+            _ => continue,
         };
 
         let new_file_name = RelativePath::new(
@@ -1226,9 +1715,10 @@ async fn files_map_create(
             &file_name,
             &final_name,
             &file_name,
-            &FileMeta::from_path(&file_name, follow_links)?,
+            &FileMeta::from_path(&file_name, symlink_policy.follows_links())?,
             if link.is_empty() { None } else { Some(&link) },
             false,
+            link_policy,
             &mut files_map,
             content,
         )
@@ -1249,6 +1739,7 @@ mod tests {
     use anyhow::{anyhow, bail, Result};
     use assert_matches::assert_matches;
     use rand::{distributions::Alphanumeric, thread_rng, Rng};
+    use std::sync::Arc;
 
     const TEST_DATA_FOLDER: &str = "./testdata/";
     const TEST_DATA_FOLDER_NO_SLASH: &str = "./testdata";
@@ -1265,8 +1756,12 @@ mod tests {
     async fn new_files_container_from_testdata(
         safe: &Safe,
     ) -> Result<(String, ProcessedFiles, FilesMap)> {
-        let (xorurl, processed_files, files_map) =
-            retry_loop!(safe.files_container_create_from(TEST_DATA_FOLDER, None, true, true,));
+        let (xorurl, processed_files, files_map) = retry_loop!(safe.files_container_create_from(
+            TEST_DATA_FOLDER,
+            None,
+            true,
+            SymlinkPolicy::FollowTarget,
+        ));
 
         assert!(xorurl.starts_with("safe://"));
         assert_eq!(processed_files.len(), TESTDATA_PUT_FILEITEM_COUNT);
@@ -1327,7 +1822,14 @@ mod tests {
 
         // let's add a file
         let (content, new_processed_files) = safe
-            .files_container_add("./testdata/test.md", &xorurl, false, false, false)
+            .files_container_add(
+                "./testdata/test.md",
+                &xorurl,
+                false,
+                false,
+                SymlinkPolicy::StoreAsLink,
+                None,
+            )
             .await?;
         let (_, new_files_map) =
             content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
@@ -1364,12 +1866,90 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_inspect_datamap() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let (xorurl, _, files_map) = retry_loop!(safe.files_container_create_from(
+            "./testdata/",
+            None,
+            true,
+            SymlinkPolicy::FollowTarget
+        ));
+
+        let mut url_with_path = SafeUrl::from_xorurl(&xorurl)?;
+        url_with_path.set_path("/test.md");
+        let info = retry_loop!(safe.inspect_datamap(&url_with_path.to_string()));
+        let file_size: usize = files_map["/test.md"][PREDICATE_SIZE].parse()?;
+        assert_eq!(info.size, file_size);
+        assert_eq!(info.self_encrypted, !info.chunks.is_empty());
+
+        // a FilesContainer is not a file
+        assert!(safe.inspect_datamap(&xorurl).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_store_public_with_receipts() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let random_content: String = thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(20)
+            .map(char::from)
+            .collect();
+
+        let (_, receipts) = safe
+            .store_public_bytes_with_receipts(Bytes::from(random_content), None)
+            .await?;
+        assert_eq!(receipts.len(), 1);
+        safe.verify_receipt(&receipts[0]).await?;
+
+        let mut tampered = receipts[0].clone();
+        tampered.content.name = xor_name::rand::random();
+        assert!(safe.verify_receipt(&tampered).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_store_temporary() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let random_content: String = thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(20)
+            .map(char::from)
+            .collect();
+
+        let xorurl = safe
+            .store_temporary(
+                Bytes::from(random_content.to_owned()),
+                None,
+                Duration::from_secs(20),
+            )
+            .await?;
+        let retrieved = retry_loop!(safe.files_get(&xorurl, None));
+        assert_eq!(retrieved, random_content.as_bytes());
+
+        tokio::time::sleep(Duration::from_secs(25)).await;
+        assert!(safe
+            .files_get_with_chunk_repair(&xorurl, None, false)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_files_container_create_from_file() -> Result<()> {
         let safe = new_safe_instance().await?;
         let filename = Path::new("./testdata/test.md");
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create_from(&filename.display().to_string(), None, false, false)
+            .files_container_create_from(
+                &filename.display().to_string(),
+                None,
+                false,
+                SymlinkPolicy::StoreAsLink,
+            )
             .await?;
 
         assert!(xorurl.starts_with("safe://"));
@@ -1384,12 +1964,53 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_store_public_bytes_with_upload_index() -> Result<()> {
+        let dir = assert_fs::TempDir::new()?;
+        let index_path = dir.path().join("upload_index.json");
+        let upload_index = Arc::new(UploadIndex::load(&index_path)?);
+        let mut safe = new_safe_instance().await?;
+        safe.use_upload_index(upload_index.clone());
+
+        // an entry for content which isn't on the network, e.g. as the index was copied from
+        // another network, is dropped and the content uploaded
+        let bytes = Bytes::from(random_nrs_name());
+        let not_uploaded = Safe::dry_runner(None)
+            .store_public_bytes(Bytes::from(random_nrs_name()), None)
+            .await?;
+        upload_index.insert(&bytes, not_uploaded.clone())?;
+        let xorurl = safe
+            .store_public_bytes(bytes.clone(), Some("text/plain"))
+            .await?;
+        assert_ne!(xorurl, not_uploaded);
+        assert_eq!(upload_index.get(&bytes), Some(xorurl.clone()));
+
+        // another session finds the content in the index, with the media type it asks for
+        let mut other_safe = new_safe_instance().await?;
+        other_safe.use_upload_index(Arc::new(UploadIndex::load(&index_path)?));
+        let other_xorurl = other_safe.store_public_bytes(bytes.clone(), None).await?;
+        assert_eq!(
+            SafeUrl::from_url(&other_xorurl)?.xorname(),
+            SafeUrl::from_url(&xorurl)?.xorname()
+        );
+        assert_eq!(
+            SafeUrl::from_url(&other_xorurl)?.content_type(),
+            ContentType::Raw
+        );
+        assert_eq!(
+            retry_loop!(other_safe.files_get(&other_xorurl, None)),
+            bytes
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_files_container_create_from_dry_run() -> Result<()> {
         let mut safe = new_safe_instance().await?;
         safe.dry_run_mode = true;
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create_from(TEST_DATA_FOLDER, None, true, false)
+            .files_container_create_from(TEST_DATA_FOLDER, None, true, SymlinkPolicy::StoreAsLink)
             .await?;
 
         assert!(xorurl.starts_with("safe://"));
@@ -1431,6 +2052,60 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_files_container_create_from_symlink_policies() -> Result<()> {
+        let tmp_dir = assert_fs::TempDir::new()?;
+        let target = tmp_dir.path().join("target.md");
+        let symlink = tmp_dir.path().join("link.md");
+        std::fs::write(&target, "symlink target")?;
+        std::os::unix::fs::symlink(&target, &symlink)?;
+        let location = format!("{}/", tmp_dir.path().display());
+
+        let safe = Safe::dry_runner(None);
+        let (_, processed_files, files_map) = safe
+            .files_container_create_from(&location, None, true, SymlinkPolicy::StoreAsLink)
+            .await?;
+        assert_eq!(
+            processed_files[&symlink].symlink_policy(),
+            Some(SymlinkPolicy::StoreAsLink)
+        );
+        assert!(processed_files[&symlink].is_added());
+        assert_eq!(processed_files[&symlink].link(), Some(&String::default()));
+        assert_eq!(processed_files[&target].symlink_policy(), None);
+        assert_eq!(
+            files_map["/link.md"][PREDICATE_TYPE],
+            MIMETYPE_FILESYSTEM_SYMLINK
+        );
+
+        let (_, processed_files, files_map) = safe
+            .files_container_create_from(&location, None, true, SymlinkPolicy::FollowTarget)
+            .await?;
+        assert_eq!(
+            processed_files[&symlink].symlink_policy(),
+            Some(SymlinkPolicy::FollowTarget)
+        );
+        assert_eq!(
+            processed_files[&symlink].link(),
+            processed_files[&target].link()
+        );
+        assert_eq!(
+            files_map["/link.md"][PREDICATE_LINK],
+            files_map["/target.md"][PREDICATE_LINK]
+        );
+
+        let (_, processed_files, files_map) = safe
+            .files_container_create_from(&location, None, true, SymlinkPolicy::Skip)
+            .await?;
+        assert!(processed_files[&symlink].is_skipped());
+        assert!(processed_files[&symlink].is_success());
+        assert_eq!(processed_files[&symlink].link(), None);
+        assert!(!files_map.contains_key("/link.md"));
+        assert!(files_map.contains_key("/target.md"));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_files_container_create_from_folder_without_trailing_slash() -> Result<()> {
         let safe = new_safe_instance().await?;
@@ -1438,7 +2113,7 @@ mod tests {
             TEST_DATA_FOLDER_NO_SLASH,
             None,
             true,
-            true,
+            SymlinkPolicy::FollowTarget,
         ));
 
         assert!(xorurl.starts_with("safe://"));
@@ -1520,7 +2195,7 @@ mod tests {
                 TEST_DATA_FOLDER_NO_SLASH,
                 Some(Path::new("/myroot")),
                 true,
-                true,
+                SymlinkPolicy::FollowTarget,
             )
             .await?;
 
@@ -1567,7 +2242,7 @@ mod tests {
                 TEST_DATA_FOLDER_NO_SLASH,
                 Some(Path::new("/myroot/")),
                 true,
-                true,
+                SymlinkPolicy::FollowTarget,
             )
             .await?;
 
@@ -1615,7 +2290,14 @@ mod tests {
             .ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
 
         let (content, new_processed_files) = safe
-            .files_container_sync("./testdata/subfolder/", &xorurl, true, true, false, false)
+            .files_container_sync(
+                "./testdata/subfolder/",
+                &xorurl,
+                true,
+                SymlinkPolicy::FollowTarget,
+                false,
+                false,
+            )
             .await?;
         let (version, new_files_map) =
             content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
@@ -1680,7 +2362,14 @@ mod tests {
         // set dry_run flag on
         safe.dry_run_mode = true;
         let (content, new_processed_files) = safe
-            .files_container_sync("./testdata/subfolder/", &xorurl, true, true, false, false)
+            .files_container_sync(
+                "./testdata/subfolder/",
+                &xorurl,
+                true,
+                SymlinkPolicy::FollowTarget,
+                false,
+                false,
+            )
             .await?;
         let (_, new_files_map) =
             content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
@@ -1742,7 +2431,12 @@ mod tests {
     async fn test_files_container_sync_same_size() -> Result<()> {
         let safe = new_safe_instance().await?;
         let (xorurl, processed_files, files_map) = safe
-            .files_container_create_from("./testdata/test.md", None, false, false)
+            .files_container_create_from(
+                "./testdata/test.md",
+                None,
+                false,
+                SymlinkPolicy::StoreAsLink,
+            )
             .await?;
 
         assert_eq!(processed_files.len(), 1);
@@ -1755,7 +2449,7 @@ mod tests {
                 "./testdata/.subhidden/test.md",
                 &xorurl,
                 false,
-                false,
+                SymlinkPolicy::StoreAsLink,
                 false,
                 false,
             )
@@ -1803,7 +2497,7 @@ mod tests {
                 "./testdata/subfolder/",
                 &xorurl,
                 false,
-                false,
+                SymlinkPolicy::StoreAsLink,
                 false,
                 // FIXME: shall we just set this to false
                 true, // this flag requests the update-nrs
@@ -1839,7 +2533,7 @@ mod tests {
                 "./testdata/subfolder/",
                 &xorurl,
                 true,
-                false,
+                SymlinkPolicy::StoreAsLink,
                 true, // this sets the delete flag
                 false,
             )
@@ -1902,8 +2596,8 @@ mod tests {
                 "./testdata/subfolder/",
                 "some-url",
                 false, // this sets the recursive flag to off
-                false, // do not follow links
-                true,  // this sets the delete flag
+                SymlinkPolicy::StoreAsLink,
+                true, // this sets the delete flag
                 false,
             )
             .await
@@ -1961,7 +2655,7 @@ mod tests {
                 "./testdata/subfolder/",
                 &xorurl,
                 false,
-                false,
+                SymlinkPolicy::StoreAsLink,
                 false,
                 true, // this flag requests the update-nrs
             )
@@ -2003,7 +2697,7 @@ mod tests {
             "./testdata/subfolder/",
             &nrsurl,
             false,
-            false,
+            SymlinkPolicy::StoreAsLink,
             false,
             true, // this flag requests the update-nrs
         ));
@@ -2034,7 +2728,7 @@ mod tests {
             "./testdata/subfolder",
             &safe_url.to_string(),
             true,
-            false,
+            SymlinkPolicy::StoreAsLink,
             false,
             false,
         ));
@@ -2100,7 +2794,7 @@ mod tests {
             "./testdata/subfolder",
             &safe_url.to_string(),
             true,
-            false,
+            SymlinkPolicy::StoreAsLink,
             false,
             false,
         ));
@@ -2188,7 +2882,7 @@ mod tests {
             "./testdata/subfolder/",
             &xorurl,
             true,
-            false,
+            SymlinkPolicy::StoreAsLink,
             true, // this sets the delete flag,
             false,
         ));
@@ -2219,7 +2913,7 @@ mod tests {
             "./testdata/subfolder/",
             &xorurl,
             true,
-            false,
+            SymlinkPolicy::StoreAsLink,
             true, // this sets the delete flag
             false,
         ));
@@ -2312,8 +3006,12 @@ mod tests {
     #[ignore = "fix unknown issue"]
     async fn test_files_container_sync_with_nrs_url() -> Result<()> {
         let safe = new_safe_instance().await?;
-        let (xorurl, _, _) =
-            retry_loop!(safe.files_container_create_from("./testdata/test.md", None, false, true,));
+        let (xorurl, _, _) = retry_loop!(safe.files_container_create_from(
+            "./testdata/test.md",
+            None,
+            false,
+            SymlinkPolicy::FollowTarget,
+        ));
         let _ = retry_loop!(safe.fetch(&xorurl, None));
         let (version0, _) = retry_loop!(safe.files_container_get(&xorurl))
             .ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
@@ -2330,7 +3028,7 @@ mod tests {
             "./testdata/subfolder/",
             &xorurl,
             false,
-            false,
+            SymlinkPolicy::StoreAsLink,
             false,
             false,
         ));
@@ -2339,7 +3037,7 @@ mod tests {
             TEST_DATA_FOLDER,
             &nrsurl,
             false,
-            false,
+            SymlinkPolicy::StoreAsLink,
             false,
             true, // this flag requests the update-nrs
         ));
@@ -2369,7 +3067,7 @@ mod tests {
             "./testdata/subfolder/",
             None,
             false,
-            true,
+            SymlinkPolicy::FollowTarget,
         ));
         assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
@@ -2385,7 +3083,8 @@ mod tests {
             &url_with_path.to_string(),
             false,
             false,
-            false,
+            SymlinkPolicy::StoreAsLink,
+            None,
         ));
         let (version1, new_files_map) =
             version1_content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
@@ -2424,7 +3123,7 @@ mod tests {
             "./testdata/subfolder/",
             None,
             false,
-            true,
+            SymlinkPolicy::FollowTarget,
         ));
         assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
@@ -2439,7 +3138,8 @@ mod tests {
             &url_with_path.to_string(),
             false,
             false,
-            false,
+            SymlinkPolicy::StoreAsLink,
+            None,
         ));
         let (_, new_files_map) =
             version1_content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
@@ -2454,7 +3154,8 @@ mod tests {
             &url_with_path.to_string(),
             false,
             false,
-            false,
+            SymlinkPolicy::StoreAsLink,
+            None,
         ));
         let (_, new_files_map2) =
             version2_content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
@@ -2484,14 +3185,21 @@ mod tests {
             "./testdata/subfolder/",
             None,
             false,
-            true,
+            SymlinkPolicy::FollowTarget,
         ));
         assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT); // root "/" + 2 files
         assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
         match safe
-            .files_container_add(TEST_DATA_FOLDER_NO_SLASH, &xorurl, false, false, false)
+            .files_container_add(
+                TEST_DATA_FOLDER_NO_SLASH,
+                &xorurl,
+                false,
+                false,
+                SymlinkPolicy::StoreAsLink,
+                None,
+            )
             .await
         {
             Ok(_) => Err(anyhow!(
@@ -2518,7 +3226,7 @@ mod tests {
             "./testdata/subfolder/",
             None,
             false,
-            true,
+            SymlinkPolicy::FollowTarget,
         ));
         assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
@@ -2538,7 +3246,8 @@ mod tests {
                 &url_with_path.to_string(),
                 false,
                 false,
-                false,
+                SymlinkPolicy::StoreAsLink,
+                None,
             )
             .await?;
         let (version1, new_files_map) =
@@ -2560,7 +3269,8 @@ mod tests {
             &url_with_path.to_string(),
             false,
             false,
-            false,
+            SymlinkPolicy::StoreAsLink,
+            None,
         ));
         let (version2, new_files_map) =
             version2_content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
@@ -2580,7 +3290,8 @@ mod tests {
             &url_with_path.to_string(),
             true, //force it
             false,
-            false,
+            SymlinkPolicy::StoreAsLink,
+            None,
         ));
         let (version3, new_files_map) =
             version3_content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
@@ -2600,14 +3311,25 @@ mod tests {
     #[tokio::test]
     async fn test_files_container_fail_add_or_sync_invalid_path() -> Result<()> {
         let safe = new_safe_instance().await?;
-        let (xorurl, processed_files, files_map) =
-            retry_loop!(safe.files_container_create_from("./testdata/test.md", None, false, true,));
+        let (xorurl, processed_files, files_map) = retry_loop!(safe.files_container_create_from(
+            "./testdata/test.md",
+            None,
+            false,
+            SymlinkPolicy::FollowTarget,
+        ));
         assert_eq!(processed_files.len(), 1);
         assert_eq!(files_map.len(), 1);
         let _ = retry_loop!(safe.fetch(&xorurl, None));
 
         match safe
-            .files_container_sync("/non-existing-path", &xorurl, false, false, false, false)
+            .files_container_sync(
+                "/non-existing-path",
+                &xorurl,
+                false,
+                SymlinkPolicy::StoreAsLink,
+                false,
+                false,
+            )
             .await
         {
             Ok(_) => {
@@ -2631,7 +3353,8 @@ mod tests {
                 &url_with_path.to_string(),
                 false,
                 false,
-                false,
+                SymlinkPolicy::StoreAsLink,
+                None,
             )
             .await
         {
@@ -2657,7 +3380,7 @@ mod tests {
             "./testdata/subfolder/",
             None,
             false,
-            true,
+            SymlinkPolicy::FollowTarget,
         ));
         assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
@@ -2677,7 +3400,8 @@ mod tests {
             &url_with_path.to_string(),
             false,
             false,
-            false,
+            SymlinkPolicy::StoreAsLink,
+            None,
         ));
         let (version1, new_files_map) =
             version1_content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
@@ -2718,7 +3442,8 @@ mod tests {
             &url_with_path.to_string(),
             true, // force to overwrite it with new link
             false,
-            false,
+            SymlinkPolicy::StoreAsLink,
+            None,
         ));
         let (version2, new_files_map) =
             version2_content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
@@ -2747,7 +3472,7 @@ mod tests {
             "./testdata/subfolder/",
             None,
             false,
-            true,
+            SymlinkPolicy::FollowTarget,
         ));
         assert_eq!(processed_files.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
         assert_eq!(files_map.len(), SUBFOLDER_PUT_FILEITEM_COUNT);
@@ -2762,7 +3487,13 @@ mod tests {
         url_with_path.set_path(&new_filename.display().to_string());
 
         let (version1_content, new_processed_files) = retry_loop!(safe
-            .files_container_add_from_raw(data.clone(), &url_with_path.to_string(), false, false,));
+            .files_container_add_from_raw(
+                data.clone(),
+                &url_with_path.to_string(),
+                false,
+                false,
+                None,
+            ));
         let (version1, new_files_map) =
             version1_content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
 
@@ -2784,6 +3515,7 @@ mod tests {
                 &url_with_path.to_string(),
                 true, // force to overwrite it with new link
                 false,
+                None,
             ));
         let (version2, new_files_map) =
             version2_content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
@@ -2800,6 +3532,62 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_files_container_add_with_cache_control() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let (xorurl, _, files_map) = new_files_container_from_testdata(&safe).await?;
+
+        let mut url_with_path = SafeUrl::from_xorurl(&xorurl)?;
+        url_with_path.set_path("/cached.md");
+        let cache_control = CacheControl {
+            max_age: Some(3600),
+            immutable: true,
+        };
+        let (content, _) = retry_loop!(safe.files_container_add_from_raw(
+            Bytes::from("cached"),
+            &url_with_path.to_string(),
+            false,
+            false,
+            Some(cache_control),
+        ));
+        let (_, new_files_map) =
+            content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+
+        // only the file added holds the hints
+        assert_eq!(
+            new_files_map["/cached.md"][PREDICATE_CACHE_CONTROL],
+            "max-age=3600, immutable"
+        );
+        for (path, file_info) in &files_map {
+            assert_eq!(&new_files_map[path], file_info);
+        }
+
+        // gateways get them along with the file's metadata
+        let (_, metadata) = get_file_link_and_metadata(&new_files_map, "/cached.md")?;
+        let metadata = metadata.ok_or_else(|| anyhow!("no metadata for the file added"))?;
+        assert_eq!(
+            CacheControl::from_file_info(&metadata)?,
+            Some(cache_control)
+        );
+
+        // the hints don't stick to a new content of the file
+        let (content, _) = retry_loop!(safe.files_container_add_from_raw(
+            Bytes::from("cached, take two"),
+            &url_with_path.to_string(),
+            true,
+            false,
+            None,
+        ));
+        let (_, new_files_map) =
+            content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+        assert_eq!(
+            CacheControl::from_file_info(&new_files_map["/cached.md"])?,
+            None
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_files_container_remove_path() -> Result<()> {
         let safe = new_safe_instance().await?;
@@ -2855,4 +3643,54 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_files_container_shared_writers() -> Result<()> {
+        let owner = new_safe_instance().await?;
+        let writer = new_safe_instance().await?;
+        let writer_key = writer.get_safe_client()?.public_key();
+        let xorurl = owner
+            .files_container_create_shared(BTreeSet::from([writer_key]))
+            .await?;
+
+        let mut url_with_path = SafeUrl::from_xorurl(&xorurl)?;
+        url_with_path.set_path("/owner.md");
+        let _ = retry_loop!(owner.files_container_add_from_raw(
+            Bytes::from("owner"),
+            &url_with_path.to_string(),
+            false,
+            false,
+            None
+        ));
+        url_with_path.set_path("/writer.md");
+        let (content, _) = retry_loop!(writer.files_container_add_from_raw(
+            Bytes::from("writer"),
+            &url_with_path.to_string(),
+            false,
+            false,
+            None
+        ));
+        let (_, files_map) =
+            content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+        assert_eq!(
+            files_map.keys().collect::<Vec<_>>(),
+            ["/owner.md", "/writer.md"]
+        );
+
+        // the files of a single-writer container can be shared
+        let (single_xorurl, _, files_map) = retry_loop!(owner.files_container_create_from(
+            "./testdata/subfolder/",
+            None,
+            false,
+            SymlinkPolicy::FollowTarget,
+        ));
+        let shared_xorurl = owner
+            .files_container_share(&single_xorurl, BTreeSet::new())
+            .await?;
+        let (_, shared_files_map) = retry_loop!(owner.files_container_get(&shared_xorurl))
+            .ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+        assert_eq!(shared_files_map, files_map);
+
+        Ok(())
+    }
 }
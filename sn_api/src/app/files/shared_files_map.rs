@@ -0,0 +1,319 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{FileInfo, FilesMap};
+
+use serde::{Deserialize, Serialize};
+use sn_interface::types::PublicKey;
+use std::{
+    collections::{btree_map::Entry, BTreeMap, BTreeSet},
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// FilesMap of a FilesContainer which several writers can update concurrently, without their
+/// versions conflicting.
+///
+/// Each path holds a last-writer-wins register, so merging the maps written concurrently keeps
+/// the latest write of each path, writes being ordered by their timestamp, then by their writer.
+/// Removed paths are kept as tombstones, for removals to be merged like any other write.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SharedFilesMap {
+    shared_entries: BTreeMap<String, PathWrite>,
+}
+
+// Latest write of a path
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct PathWrite {
+    // milliseconds since the Unix epoch
+    timestamp: u64,
+    writer: PublicKey,
+    // `None` if the path was removed
+    file_info: Option<FileInfo>,
+}
+
+impl PathWrite {
+    fn is_newer_than(&self, other: &Self) -> bool {
+        // the file infos only break the tie between writes a writer made at the same time
+        (self.timestamp, self.writer, &self.file_info)
+            > (other.timestamp, other.writer, &other.file_info)
+    }
+}
+
+impl SharedFilesMap {
+    /// Converts a single-writer FilesMap, as if all its files were written by the given writer.
+    pub fn from_files_map(files_map: &FilesMap, writer: PublicKey, timestamp: u64) -> Self {
+        let mut shared = Self::default();
+        for (path, file_info) in files_map {
+            shared.insert(path, file_info.clone(), writer, timestamp);
+        }
+        shared
+    }
+
+    /// Returns the files currently in the map, in the single-writer format.
+    pub fn files_map(&self) -> FilesMap {
+        self.shared_entries
+            .iter()
+            .filter_map(|(path, write)| Some((path.clone(), write.file_info.clone()?)))
+            .collect()
+    }
+
+    /// Adds or updates the file at the given path, unless a newer write of the path was merged.
+    pub fn insert(&mut self, path: &str, file_info: FileInfo, writer: PublicKey, timestamp: u64) {
+        self.write(
+            path,
+            PathWrite {
+                timestamp,
+                writer,
+                file_info: Some(file_info),
+            },
+        )
+    }
+
+    /// Removes the file at the given path, unless a newer write of the path was merged.
+    pub fn remove(&mut self, path: &str, writer: PublicKey, timestamp: u64) {
+        self.write(
+            path,
+            PathWrite {
+                timestamp,
+                writer,
+                file_info: None,
+            },
+        )
+    }
+
+    /// Returns the paths whose latest write is a removal.
+    pub fn removed_paths(&self) -> impl Iterator<Item = &String> {
+        self.shared_entries
+            .iter()
+            .filter(|(_, write)| write.file_info.is_none())
+            .map(|(path, _)| path)
+    }
+
+    /// Returns the writers of the latest write of each path.
+    pub fn writers(&self) -> BTreeSet<PublicKey> {
+        self.shared_entries
+            .values()
+            .map(|write| write.writer)
+            .collect()
+    }
+
+    /// Merges the writes of another replica of the map into this one.
+    ///
+    /// Merging is commutative, associative and idempotent, so all the replicas end up with the
+    /// same files once they merged each other's writes, whichever the order.
+    pub fn merge(&mut self, other: &Self) {
+        for (path, write) in &other.shared_entries {
+            self.write(path, write.clone());
+        }
+    }
+
+    /// Records the changes made by the writer from the `old` to the `new` FilesMap, e.g. the one
+    /// read from the container and the one synced with a local folder.
+    ///
+    /// Only the paths which changed are written, so the concurrent writes of other writers to
+    /// other paths are kept.
+    pub fn record_changes(&mut self, old: &FilesMap, new: &FilesMap, writer: PublicKey) {
+        let timestamp = self.next_timestamp();
+        for path in old.keys().filter(|path| !new.contains_key(*path)) {
+            self.remove(path, writer, timestamp);
+        }
+        for (path, file_info) in new {
+            if old.get(path) != Some(file_info) {
+                self.insert(path, file_info.clone(), writer, timestamp);
+            }
+        }
+    }
+
+    // Timestamp of a new write, which is after all the writes merged so far even if the clocks
+    // of their writers were ahead of ours
+    fn next_timestamp(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+        let latest = self
+            .shared_entries
+            .values()
+            .map(|write| write.timestamp)
+            .max()
+            .unwrap_or_default();
+        now.max(latest + 1)
+    }
+
+    fn write(&mut self, path: &str, write: PathWrite) {
+        match self.shared_entries.entry(path.to_string()) {
+            Entry::Vacant(entry) => {
+                let _ = entry.insert(write);
+            }
+            Entry::Occupied(mut entry) => {
+                if write.is_newer_than(entry.get()) {
+                    let _ = entry.insert(write);
+                }
+            }
+        }
+    }
+}
+
+/// Format a FilesContainer stores its FilesMap in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FilesMapFormat {
+    /// A [`FilesMap`], written by a single writer
+    Single,
+    /// A [`SharedFilesMap`], which several writers can update concurrently
+    Shared,
+}
+
+impl fmt::Display for FilesMapFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let format = match self {
+            Self::Single => "single-writer",
+            Self::Shared => "shared",
+        };
+        write!(f, "{}", format)
+    }
+}
+
+/// A FilesMap as stored by a FilesContainer, in either of the formats.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum StoredFilesMap {
+    Shared(SharedFilesMap),
+    Single(FilesMap),
+}
+
+impl StoredFilesMap {
+    /// Returns the files in the map, in the single-writer format.
+    pub(crate) fn files_map(&self) -> FilesMap {
+        match self {
+            Self::Shared(shared) => shared.files_map(),
+            Self::Single(files_map) => files_map.clone(),
+        }
+    }
+
+    /// Returns the format the map is stored in.
+    pub(crate) fn format(&self) -> FilesMapFormat {
+        match self {
+            Self::Shared(_) => FilesMapFormat::Shared,
+            Self::Single(_) => FilesMapFormat::Single,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use sn_interface::types::Keypair;
+
+    fn file_info(link: &str) -> FileInfo {
+        BTreeMap::from([
+            ("type".to_string(), "text/plain".to_string()),
+            ("link".to_string(), link.to_string()),
+        ])
+    }
+
+    fn gen_writer() -> PublicKey {
+        Keypair::new_ed25519().public_key()
+    }
+
+    #[test]
+    fn concurrent_writes_merge_the_same_in_any_order() {
+        let (alice, bob) = (gen_writer(), gen_writer());
+        let mut base = SharedFilesMap::default();
+        base.insert("/readme.md", file_info("safe://readme"), alice, 1);
+        base.insert("/old.md", file_info("safe://old"), alice, 1);
+
+        let mut alices = base.clone();
+        alices.insert("/a.md", file_info("safe://a"), alice, 2);
+        alices.insert("/readme.md", file_info("safe://readme-a"), alice, 2);
+        let mut bobs = base.clone();
+        bobs.insert("/b.md", file_info("safe://b"), bob, 3);
+        bobs.insert("/readme.md", file_info("safe://readme-b"), bob, 3);
+        bobs.remove("/old.md", bob, 3);
+
+        let mut merged = alices.clone();
+        merged.merge(&bobs);
+        let mut merged_other_way = bobs.clone();
+        merged_other_way.merge(&alices);
+        assert_eq!(merged, merged_other_way);
+
+        // merging again changes nothing
+        let mut merged_twice = merged.clone();
+        merged_twice.merge(&alices);
+        merged_twice.merge(&merged);
+        assert_eq!(merged_twice, merged);
+
+        let files_map = merged.files_map();
+        assert_eq!(
+            files_map.keys().collect::<Vec<_>>(),
+            ["/a.md", "/b.md", "/readme.md"]
+        );
+        // the latest write of a path wins
+        assert_eq!(files_map["/readme.md"], file_info("safe://readme-b"));
+    }
+
+    #[test]
+    fn removal_only_wins_over_older_writes() {
+        let (alice, bob) = (gen_writer(), gen_writer());
+        let mut alices = SharedFilesMap::default();
+        alices.remove("/a.md", alice, 5);
+        let mut bobs = SharedFilesMap::default();
+        bobs.insert("/a.md", file_info("safe://a"), bob, 4);
+        bobs.insert("/b.md", file_info("safe://b"), bob, 6);
+        alices.remove("/b.md", alice, 5);
+
+        alices.merge(&bobs);
+        assert_eq!(alices.files_map().keys().collect::<Vec<_>>(), ["/b.md"]);
+    }
+
+    #[test]
+    fn changes_are_recorded_without_undoing_concurrent_ones() {
+        let (alice, bob) = (gen_writer(), gen_writer());
+        let old = FilesMap::from([
+            ("/a.md".to_string(), file_info("safe://a")),
+            ("/b.md".to_string(), file_info("safe://b")),
+        ]);
+        let base = SharedFilesMap::from_files_map(&old, alice, 1);
+
+        // Bob adds a file Alice doesn't know about, while she updates and removes some
+        let mut bobs = base.clone();
+        bobs.insert("/c.md", file_info("safe://c"), bob, 2);
+        let mut alices = base;
+        let mut new = old.clone();
+        let _ = new.insert("/a.md".to_string(), file_info("safe://a2"));
+        let _ = new.remove("/b.md");
+        alices.record_changes(&old, &new, alice);
+
+        alices.merge(&bobs);
+        let files_map = alices.files_map();
+        assert_eq!(files_map.keys().collect::<Vec<_>>(), ["/a.md", "/c.md"]);
+        assert_eq!(files_map["/a.md"], file_info("safe://a2"));
+    }
+
+    #[test]
+    fn both_formats_are_read() -> Result<()> {
+        let files_map = FilesMap::from([("/a.md".to_string(), file_info("safe://a"))]);
+        let shared = SharedFilesMap::from_files_map(&files_map, gen_writer(), 1);
+
+        let single = serde_json::to_string(&files_map)?;
+        match serde_json::from_str(&single)? {
+            StoredFilesMap::Single(read) => assert_eq!(read, files_map),
+            other => panic!("single-writer FilesMap read as {:?}", other),
+        }
+        let stored: StoredFilesMap = serde_json::from_str(&serde_json::to_string(&shared)?)?;
+        match &stored {
+            StoredFilesMap::Shared(read) => assert_eq!(read, &shared),
+            other => panic!("shared FilesMap read as {:?}", other),
+        }
+        assert_eq!(stored.files_map(), files_map);
+
+        Ok(())
+    }
+}
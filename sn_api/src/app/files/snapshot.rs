@@ -0,0 +1,193 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::FilesMap;
+use crate::{
+    app::{
+        consts::PREDICATE_LINK,
+        helpers::{bls_decrypt, bls_encrypt},
+    },
+    safeurl::{SafeUrl, VersionHash, XorUrl},
+    Error, Result, Safe,
+};
+
+use bls::serde_impl::SerdeSecret;
+use bytes::Bytes;
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use sn_interface::types::{Chunk, DataAddress};
+use std::collections::{BTreeMap, HashSet};
+use xor_name::XorName;
+
+/// A version of a FilesContainer along with the data maps of all the files it links to,
+/// encrypted into a single blob.
+///
+/// The bundle can be stored or shared out-of-band, e.g. as a cold backup of a whole site,
+/// and published again as a new FilesContainer with [`Safe::restore_snapshot`].
+/// Only the data maps are held in the bundle, so the content of the files has to be still
+/// available on the network when it's restored.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SnapshotBundle {
+    /// The encrypted snapshot
+    pub blob: Bytes,
+    /// Key the snapshot is encrypted with, which has to be kept or shared along with the blob
+    pub key: SerdeSecret<bls::SecretKey>,
+}
+
+// Content of a snapshot, as encrypted in its bundle
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    version: VersionHash,
+    files_map: FilesMap,
+    // head chunks of the files linked to, holding their data maps
+    head_chunks: Vec<Chunk>,
+}
+
+impl Safe {
+    /// # Export a snapshot of a FilesContainer
+    ///
+    /// Reads the FilesContainer at the given URL, at the version it refers to or else the latest,
+    /// along with the data maps of all the files it links to, and bundles them into an encrypted
+    /// blob. Links to anything else than files, e.g. to other containers, are kept as they are.
+    pub async fn files_container_snapshot(&self, url: &str) -> Result<SnapshotBundle> {
+        info!("Exporting snapshot of FilesContainer at: {}", url);
+        let (version, files_map) = self.files_container_get(url).await?.ok_or_else(|| {
+            Error::EmptyContent(format!("FilesContainer found at \"{}\" is empty", url))
+        })?;
+
+        let client = self.get_safe_client()?;
+        let mut head_chunks = BTreeMap::<XorName, Chunk>::new();
+        for file_info in files_map.values() {
+            let link = match file_info.get(PREDICATE_LINK) {
+                Some(link) => SafeUrl::from_url(link)?,
+                None => continue,
+            };
+            if let DataAddress::Bytes(address) = link.address() {
+                if !head_chunks.contains_key(address.name()) {
+                    let chunk = client.read_head_chunk(address).await?;
+                    let _ = head_chunks.insert(*address.name(), chunk);
+                }
+            }
+        }
+        debug!(
+            "Snapshot of FilesContainer at {} holds {} data maps",
+            url,
+            head_chunks.len()
+        );
+
+        let snapshot = Snapshot {
+            version,
+            files_map,
+            head_chunks: head_chunks.into_values().collect(),
+        };
+        let serialised = rmp_serde::to_vec(&snapshot).map_err(|err| {
+            Error::Serialisation(format!("Couldn't serialise snapshot: {:?}", err))
+        })?;
+
+        let key = bls::SecretKey::random();
+        let blob = bls_encrypt(&key, &serialised)?;
+
+        Ok(SnapshotBundle {
+            blob,
+            key: SerdeSecret(key),
+        })
+    }
+
+    /// # Restore a snapshot of a FilesContainer
+    ///
+    /// Republishes the data maps held in the snapshot, and the FilesMap of the version it was
+    /// taken at, as a new FilesContainer. Returns the XOR-URL of the new container, along with
+    /// the version the snapshot was taken at in the original one.
+    pub async fn restore_snapshot(&self, bundle: &SnapshotBundle) -> Result<(XorUrl, VersionHash)> {
+        let serialised = bls_decrypt(&bundle.key, &bundle.blob)?;
+        let snapshot: Snapshot = rmp_serde::from_slice(&serialised).map_err(|err| {
+            Error::ContentError(format!("Couldn't deserialise snapshot: {:?}", err))
+        })?;
+        info!(
+            "Restoring snapshot of FilesContainer version {} with {} data maps",
+            snapshot.version,
+            snapshot.head_chunks.len()
+        );
+
+        let client = self.get_safe_client()?;
+        for chunk in snapshot.head_chunks {
+            client.store_chunk(chunk).await?;
+        }
+
+        let xorurl = self.files_container_create().await?;
+        let safe_url = SafeUrl::from_url(&xorurl)?;
+        let _ = self
+            .append_version_to_files_container(
+                HashSet::new(),
+                &FilesMap::default(),
+                &snapshot.files_map,
+                &xorurl,
+                safe_url,
+                false,
+            )
+            .await?;
+
+        Ok((xorurl, snapshot.version))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SnapshotBundle;
+    use crate::app::{files::SymlinkPolicy, test_helpers::new_safe_instance};
+    use anyhow::{anyhow, Result};
+    use bls::serde_impl::SerdeSecret;
+
+    #[tokio::test]
+    async fn test_files_container_snapshot_and_restore() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let (xorurl, _, files_map) = safe
+            .files_container_create_from("./testdata/", None, true, SymlinkPolicy::StoreAsLink)
+            .await?;
+
+        let bundle = safe.files_container_snapshot(&xorurl).await?;
+        let (restored_xorurl, _) = safe.restore_snapshot(&bundle).await?;
+        assert_ne!(restored_xorurl, xorurl);
+
+        let (_, restored_files_map) = safe
+            .files_container_get(&restored_xorurl)
+            .await?
+            .ok_or_else(|| anyhow!("Restored FilesContainer should not be empty"))?;
+        assert_eq!(restored_files_map, files_map);
+
+        // the snapshot can't be read without its key
+        let mut wrong_key = bundle.clone();
+        wrong_key.key = SerdeSecret(bls::SecretKey::random());
+        assert!(safe.restore_snapshot(&wrong_key).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_files_container_snapshot_export_and_import() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let (xorurl, _, files_map) = safe
+            .files_container_create_from("./testdata/", None, true, SymlinkPolicy::StoreAsLink)
+            .await?;
+
+        let bundle = safe.files_container_snapshot(&xorurl).await?;
+        let exported = rmp_serde::to_vec(&bundle)?;
+        let imported: SnapshotBundle = rmp_serde::from_slice(&exported)?;
+        assert_eq!(imported.blob, bundle.blob);
+        assert_eq!(imported.key.public_key(), bundle.key.public_key());
+
+        let (restored_xorurl, _) = safe.restore_snapshot(&imported).await?;
+        let (_, restored_files_map) = safe
+            .files_container_get(&restored_xorurl)
+            .await?
+            .ok_or_else(|| anyhow!("Restored FilesContainer should not be empty"))?;
+        assert_eq!(restored_files_map, files_map);
+
+        Ok(())
+    }
+}
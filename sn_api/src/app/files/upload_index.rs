@@ -0,0 +1,274 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::{Error, Result, XorUrl};
+
+use log::{debug, warn};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    thread,
+    time::{Duration, SystemTime},
+};
+use tiny_keccak::{Hasher, Sha3};
+
+// How long to wait for another app to be done updating the index
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+// Age beyond which a lock file is taken as left behind by an app which stopped while holding it
+const STALE_LOCK_AGE: Duration = Duration::from_secs(30);
+
+/// A local index of the public files uploaded, by the hash of their content, kept in a file so
+/// it outlives the app.
+///
+/// An app using it, with [`Safe::use_upload_index`], doesn't upload again the content it
+/// already uploaded, whichever FilesContainer it was added to, and in whichever session. As the
+/// index can be out of date, e.g. if it was copied from another network, the content is only
+/// considered uploaded once it's found on the network.
+///
+/// [`Safe::use_upload_index`]: crate::Safe::use_upload_index
+#[derive(Debug)]
+pub struct UploadIndex {
+    path: PathBuf,
+    // XOR-URLs of the files uploaded, by the hex encoded hash of their content
+    entries: Mutex<BTreeMap<String, XorUrl>>,
+}
+
+impl UploadIndex {
+    /// Load the index kept in the file at the given path, which is created on the first upload
+    /// if there's none yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let entries = read_entries(&path)?;
+        debug!(
+            "Loaded upload index at {} with {} entries",
+            path.display(),
+            entries.len()
+        );
+
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+        })
+    }
+
+    /// Number of files in the index
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether there are no files in the index
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    // XOR-URL the given content was uploaded at, according to the index
+    pub(crate) fn get(&self, content: &[u8]) -> Option<XorUrl> {
+        self.lock().get(&content_hash(content)).cloned()
+    }
+
+    // Records the given content was uploaded at the given XOR-URL
+    pub(crate) fn insert(&self, content: &[u8], xorurl: XorUrl) -> Result<()> {
+        let hash = content_hash(content);
+        if self.lock().get(&hash) == Some(&xorurl) {
+            return Ok(());
+        }
+        self.update(|entries| {
+            let _ = entries.insert(hash, xorurl);
+        })
+    }
+
+    // Forgets about the given content, e.g. as it wasn't found on the network
+    pub(crate) fn remove(&self, content: &[u8]) -> Result<()> {
+        let hash = content_hash(content);
+        if !self.lock().contains_key(&hash) {
+            return Ok(());
+        }
+        self.update(|entries| {
+            let _ = entries.remove(&hash);
+        })
+    }
+
+    // Applies the change to the index as currently kept in its file, so the entries other apps
+    // or instances recorded since we loaded it aren't lost, holding a lock file meanwhile
+    fn update(&self, change: impl FnOnce(&mut BTreeMap<String, XorUrl>)) -> Result<()> {
+        let mut entries = self.lock();
+        let _file_lock = FileLock::acquire(&self.path)?;
+        let mut latest = read_entries(&self.path)?;
+        change(&mut latest);
+        self.persist(&latest)?;
+        *entries = latest;
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, XorUrl>> {
+        // the entries are always left consistent, so a panic while holding the lock is harmless
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    // Writes the entries to a temporary file first, then moves it over the index, so an app
+    // stopping in between doesn't leave a truncated index behind
+    fn persist(&self, entries: &BTreeMap<String, XorUrl>) -> Result<()> {
+        let serialised = serde_json::to_vec(entries).map_err(|err| {
+            Error::Serialisation(format!("Couldn't serialise upload index: {:?}", err))
+        })?;
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, serialised)
+            .and_then(|()| fs::rename(&tmp_path, &self.path))
+            .map_err(|err| {
+                Error::FileSystemError(format!(
+                    "Couldn't write upload index at {}: {}",
+                    self.path.display(),
+                    err
+                ))
+            })
+    }
+}
+
+fn read_entries(path: &Path) -> Result<BTreeMap<String, XorUrl>> {
+    match fs::read(path) {
+        Ok(serialised) => serde_json::from_slice(&serialised).map_err(|err| {
+            Error::Serialisation(format!(
+                "Couldn't deserialise upload index at {}: {:?}",
+                path.display(),
+                err
+            ))
+        }),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+        Err(err) => Err(Error::FileSystemError(format!(
+            "Couldn't read upload index at {}: {}",
+            path.display(),
+            err
+        ))),
+    }
+}
+
+// Lock file held by the app updating the index, removed when dropped
+struct FileLock {
+    path: PathBuf,
+}
+
+impl FileLock {
+    fn acquire(index_path: &Path) -> Result<Self> {
+        let path = index_path.with_extension("lock");
+        let deadline = SystemTime::now() + LOCK_TIMEOUT;
+        loop {
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(_) => return Ok(Self { path }),
+                Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let is_stale = fs::metadata(&path)
+                        .and_then(|meta| meta.modified())
+                        .ok()
+                        .and_then(|modified| modified.elapsed().ok())
+                        .map_or(false, |age| age > STALE_LOCK_AGE);
+                    if is_stale {
+                        warn!("Removing stale upload index lock at {}", path.display());
+                        let _ = fs::remove_file(&path);
+                    } else if SystemTime::now() > deadline {
+                        return Err(Error::FileSystemError(format!(
+                            "Timed out waiting for the upload index lock at {}",
+                            path.display()
+                        )));
+                    } else {
+                        thread::sleep(LOCK_RETRY_INTERVAL);
+                    }
+                }
+                Err(err) => {
+                    return Err(Error::FileSystemError(format!(
+                        "Couldn't lock upload index at {}: {}",
+                        path.display(),
+                        err
+                    )))
+                }
+            }
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn content_hash(content: &[u8]) -> String {
+    let mut hasher = Sha3::v256();
+    let mut hash = [0; 32];
+    hasher.update(content);
+    hasher.finalize(&mut hash);
+    hex::encode(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UploadIndex;
+
+    use anyhow::Result;
+    use assert_fs::TempDir;
+    use std::sync::Arc;
+
+    #[test]
+    fn upload_index_is_kept_across_loads() -> Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("upload_index.json");
+
+        let index = UploadIndex::load(&path)?;
+        assert!(index.is_empty());
+        index.insert(b"site asset", "safe://asset".to_string())?;
+        index.insert(b"other asset", "safe://other".to_string())?;
+        index.remove(b"other asset")?;
+
+        let index = UploadIndex::load(&path)?;
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.get(b"site asset"), Some("safe://asset".to_string()));
+        assert_eq!(index.get(b"other asset"), None);
+
+        std::fs::write(&path, "not an index")?;
+        assert!(UploadIndex::load(&path).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn updates_from_several_instances_are_kept() -> Result<()> {
+        let dir = TempDir::new()?;
+        let path = dir.path().join("upload_index.json");
+
+        let first = Arc::new(UploadIndex::load(&path)?);
+        let second = Arc::new(UploadIndex::load(&path)?);
+        let handles: Vec<_> = (0..20)
+            .map(|i| {
+                let index = if i % 2 == 0 {
+                    first.clone()
+                } else {
+                    second.clone()
+                };
+                std::thread::spawn(move || {
+                    index.insert(format!("asset {}", i).as_bytes(), format!("safe://{}", i))
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().expect("insert panicked")?;
+        }
+
+        let index = UploadIndex::load(&path)?;
+        assert_eq!(index.len(), 20);
+        assert!(!path.with_extension("lock").exists());
+
+        Ok(())
+    }
+}
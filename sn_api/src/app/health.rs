@@ -0,0 +1,248 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::{Error, NodeConfig, Result, Safe};
+
+use bytes::Bytes;
+use rand::Rng;
+use sn_interface::messaging::data::SectionStats;
+use std::{
+    fmt,
+    future::Future,
+    time::{Duration, Instant},
+};
+use tracing::{debug, info, warn};
+use xor_name::{Prefix, XorName, XOR_NAME_LEN};
+
+// Size of the chunk stored and fetched back by a health check
+const PROBE_CHUNK_SIZE: usize = 32;
+// How long the chunk stored by a health check is kept by the network
+const PROBE_CHUNK_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// A step of a network health check, in the order they are run
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HealthCheckStep {
+    /// Contacting the bootstrap nodes to learn about the sections of the network
+    FetchContacts,
+    /// Connecting to the Elders of the section closest to us, and querying its stats
+    ConnectToElders,
+    /// Storing a small chunk, which expires shortly after
+    StoreChunk,
+    /// Fetching the chunk back from the network, and checking it's unchanged
+    FetchChunk,
+}
+
+impl fmt::Display for HealthCheckStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let step = match self {
+            Self::FetchContacts => "fetch contacts",
+            Self::ConnectToElders => "connect to Elders",
+            Self::StoreChunk => "store chunk",
+            Self::FetchChunk => "fetch chunk",
+        };
+        write!(f, "{}", step)
+    }
+}
+
+/// Outcome of a step of a network health check
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HealthCheckStepReport {
+    /// The step run
+    pub step: HealthCheckStep,
+    /// How long the step took, until it succeeded or failed
+    pub elapsed: Duration,
+    /// Why the step failed, if it did
+    pub error: Option<String>,
+}
+
+/// Report of a network health check, as returned by [`Safe::network_health_check`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HealthReport {
+    /// Steps run, in order. A failing step is the last one, the steps after it not being run.
+    pub steps: Vec<HealthCheckStepReport>,
+    /// Stats of the section closest to us, as reported by its Elders, if they were reached
+    pub section: Option<SectionStats>,
+}
+
+impl HealthReport {
+    /// Whether all the steps were run and succeeded
+    pub fn is_healthy(&self) -> bool {
+        self.steps.len() == 4 && self.steps.iter().all(|step| step.error.is_none())
+    }
+
+    /// The step which failed, if any
+    pub fn failed_step(&self) -> Option<&HealthCheckStepReport> {
+        self.steps.iter().find(|step| step.error.is_some())
+    }
+
+    /// How long all the steps run took
+    pub fn elapsed(&self) -> Duration {
+        self.steps.iter().map(|step| step.elapsed).sum()
+    }
+
+    // Runs the step, recording how it went. Returns its output if it succeeded.
+    async fn run<T>(
+        &mut self,
+        step: HealthCheckStep,
+        future: impl Future<Output = Result<T>>,
+    ) -> Option<T> {
+        debug!("Health check: {}...", step);
+        let started = Instant::now();
+        let result = future.await;
+        let elapsed = started.elapsed();
+
+        let (output, error) = match result {
+            Ok(output) => {
+                info!("Health check: {} succeeded in {:?}", step, elapsed);
+                (Some(output), None)
+            }
+            Err(error) => {
+                warn!(
+                    "Health check: {} failed after {:?}: {}",
+                    step, elapsed, error
+                );
+                (None, Some(error.to_string()))
+            }
+        };
+        self.steps.push(HealthCheckStepReport {
+            step,
+            elapsed,
+            error,
+        });
+
+        output
+    }
+}
+
+impl Safe {
+    /// # Check the health of the network
+    ///
+    /// Runs a smoke test of the network with the given bootstrap contacts, timing each of its
+    /// steps: contacting the bootstrap nodes, connecting to the Elders of the section closest
+    /// to us, then storing a small chunk and fetching it back. The chunk is temporary data,
+    /// so probing the network regularly, e.g. from monitoring, doesn't fill it up.
+    ///
+    /// This uses a connection of its own, with a fresh keypair, and doesn't fail if the network
+    /// doesn't behave: the step which failed, along with why, is in the report returned.
+    pub async fn network_health_check(
+        bootstrap_config: NodeConfig,
+        timeout: Option<Duration>,
+    ) -> HealthReport {
+        let mut report = HealthReport {
+            steps: vec![],
+            section: None,
+        };
+
+        let safe = match report
+            .run(
+                HealthCheckStep::FetchContacts,
+                Self::connected(bootstrap_config, None, None, None, timeout, None, None),
+            )
+            .await
+        {
+            Some(safe) => safe,
+            None => return report,
+        };
+
+        report.section = report
+            .run(HealthCheckStep::ConnectToElders, async {
+                let name = XorName::from(safe.get_safe_client()?.public_key());
+                // the section our name leads to is the one closest to us
+                safe.section_info(Prefix::new(XOR_NAME_LEN * 8, name)).await
+            })
+            .await;
+        if report.section.is_none() {
+            return report;
+        }
+
+        let bytes: Vec<u8> = (0..PROBE_CHUNK_SIZE)
+            .map(|_| rand::thread_rng().gen())
+            .collect();
+        let bytes = Bytes::from(bytes);
+        let xorurl = match report
+            .run(
+                HealthCheckStep::StoreChunk,
+                safe.store_temporary(bytes.clone(), None, PROBE_CHUNK_TTL),
+            )
+            .await
+        {
+            Some(xorurl) => xorurl,
+            None => return report,
+        };
+
+        let _ = report
+            .run(HealthCheckStep::FetchChunk, async {
+                let fetched = safe.files_get(&xorurl, None).await?;
+                if fetched != bytes {
+                    return Err(Error::ContentError(format!(
+                        "Chunk fetched from {} differs from the one stored",
+                        xorurl
+                    )));
+                }
+                Ok(())
+            })
+            .await;
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HealthCheckStep, HealthCheckStepReport, HealthReport};
+    use crate::{app::test_helpers::get_bootstrap_contacts, Safe};
+
+    use anyhow::Result;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_network_health_check() -> Result<()> {
+        let report = Safe::network_health_check(get_bootstrap_contacts()?, None).await;
+
+        assert!(report.is_healthy(), "{:?}", report);
+        assert_eq!(
+            report
+                .steps
+                .iter()
+                .map(|step| step.step)
+                .collect::<Vec<_>>(),
+            vec![
+                HealthCheckStep::FetchContacts,
+                HealthCheckStep::ConnectToElders,
+                HealthCheckStep::StoreChunk,
+                HealthCheckStep::FetchChunk,
+            ]
+        );
+        assert!(matches!(report.section, Some(stats) if stats.elder_count > 0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn health_report_with_steps_missing_is_not_healthy() {
+        let step = |step| HealthCheckStepReport {
+            step,
+            elapsed: Duration::from_millis(10),
+            error: None,
+        };
+        let mut report = HealthReport {
+            steps: vec![
+                step(HealthCheckStep::FetchContacts),
+                step(HealthCheckStep::ConnectToElders),
+            ],
+            section: None,
+        };
+        assert!(!report.is_healthy());
+        assert_eq!(report.failed_step(), None);
+
+        report.steps.push(step(HealthCheckStep::StoreChunk));
+        report.steps.push(step(HealthCheckStep::FetchChunk));
+        assert!(report.is_healthy());
+        assert_eq!(report.elapsed(), Duration::from_millis(40));
+    }
+}
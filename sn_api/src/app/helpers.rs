@@ -9,6 +9,7 @@
 #[cfg(feature = "app")]
 use crate::{Error, Result};
 use ::time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use bytes::Bytes;
 
 use sn_interface::types::{Error as SafeNdError, PublicKey, Token};
 use std::{
@@ -61,3 +62,23 @@ pub fn systemtime_to_rfc3339(t: time::SystemTime) -> String {
 pub fn gen_timestamp_secs() -> String {
     OffsetDateTime::now_utc().unix_timestamp().to_string()
 }
+
+/// Encrypt the bytes to the public key of the given secret key, serialising the ciphertext
+pub(crate) fn bls_encrypt(secret_key: &bls::SecretKey, bytes: &[u8]) -> Result<Bytes> {
+    let ciphertext = secret_key.public_key().encrypt(bytes);
+    let encrypted = rmp_serde::to_vec(&ciphertext)
+        .map_err(|err| Error::Serialisation(format!("Couldn't serialise ciphertext: {:?}", err)))?;
+    Ok(Bytes::from(encrypted))
+}
+
+/// Decrypt bytes encrypted with [`bls_encrypt`], failing if they aren't a valid ciphertext.
+/// Decrypting with another key than the one they were encrypted to gives garbage rather than
+/// failing, so callers needing to detect it are to authenticate the ciphertext separately.
+pub(crate) fn bls_decrypt(secret_key: &bls::SecretKey, encrypted: &[u8]) -> Result<Vec<u8>> {
+    let ciphertext: bls::Ciphertext = rmp_serde::from_slice(encrypted).map_err(|err| {
+        Error::ContentError(format!("Couldn't deserialise ciphertext: {:?}", err))
+    })?;
+    secret_key
+        .decrypt(&ciphertext)
+        .ok_or_else(|| Error::ContentError("Couldn't decrypt content with the key".to_string()))
+}
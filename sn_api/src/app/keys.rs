@@ -6,13 +6,37 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::Safe;
+use super::{
+    credentials::Credentials,
+    helpers::{bls_decrypt, bls_encrypt},
+    register::{EntryHash, WriteAccess},
+    Safe,
+};
+use crate::safeurl::{ContentType, XorUrl};
 use crate::{Error, Result, SafeUrl};
+use bytes::Bytes;
 use hex::encode;
-use sn_interface::types::{Keypair, SecretKey};
-use std::path::Path;
+use hkdf::Hkdf;
+use log::debug;
+use sha3::Sha3_256;
+use sn_client::Client;
+use sn_interface::types::{Keypair, PublicKey, Scope, SecretKey, Signature};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
 use xor_name::XorName;
 
+/// Type tag of the Registers pointing to the accounts' keys containers
+pub(crate) const KEYS_CONTAINER_TYPE_TAG: u64 = 1_700;
+
+// Context the key the keys container is encrypted with is derived for, which also names the
+// Register pointing to it
+const KEYS_CONTAINER_KEY_SEED: &[u8] = b"sn_api keys container";
+
+// Keypairs held in a keys container, by label
+type KeysContainer = BTreeMap<String, Keypair>;
+
 impl Safe {
     /// Check the XOR/NRS-URL corresponds to the public key derived from the provided client id.
     pub async fn validate_sk_for_url(&self, secret_key: &SecretKey, url: &str) -> Result<String> {
@@ -48,15 +72,13 @@ impl Safe {
         Ok((keypair, url))
     }
 
-    /// Serializes a `Keypair` to a file at a given path.
+    /// Serializes a `Keypair` to a file at a given path, as unencrypted [`Credentials`].
     ///
     /// A utility to help callers working with keypairs avoid using serde or bincode directly.
     ///
     /// If the path already exists it will be overwritten.
     pub fn serialize_keypair(&self, keypair: &Keypair, path: impl AsRef<Path>) -> Result<()> {
-        let serialized = sn_interface::types::utils::serialise(&keypair)?;
-        std::fs::write(&path, serialized)?;
-        Ok(())
+        Credentials::new(keypair.clone(), None).write(path, None)
     }
 
     /// Deserializes a `Keypair` from file at a given path.
@@ -65,29 +87,256 @@ impl Safe {
     pub fn deserialize_keypair(&self, path: impl AsRef<Path>) -> Result<Keypair> {
         deserialize_keypair(path)
     }
+
+    /// Return the URL of the keys container of the account we are connected with,
+    /// which is the same whether the container was created or not.
+    ///
+    /// The keys container holds labelled keypairs, encrypted so only the account can read them.
+    pub fn keys_container_url(&self) -> Result<XorUrl> {
+        let client = self.get_safe_client()?;
+        SafeUrl::encode_register(
+            keys_container_name(client.public_key()),
+            KEYS_CONTAINER_TYPE_TAG,
+            Scope::Private,
+            ContentType::Raw,
+            self.xorurl_base,
+        )
+    }
+
+    /// Generate a new keypair, and store it in the account's keys container with the given label.
+    /// The keys container is created along with the first key stored in it.
+    pub async fn keys_create(&self, label: &str) -> Result<PublicKey> {
+        debug!("Creating a key labelled '{}'", label);
+        let (parents, mut keys) = self.fetch_keys_container().await?;
+        if keys.contains_key(label) {
+            return Err(Error::KeyLabelAlreadyExists(format!(
+                "A key labelled '{}' already exists in the keys container",
+                label
+            )));
+        }
+
+        let keypair = Keypair::new_ed25519();
+        let public_key = keypair.public_key();
+        let _ = keys.insert(label.to_string(), keypair);
+        self.store_keys_container(parents, &keys).await?;
+
+        Ok(public_key)
+    }
+
+    /// List the labels of the keys in the account's keys container, along with their public keys
+    pub async fn keys_list(&self) -> Result<BTreeMap<String, PublicKey>> {
+        let (_, keys) = self.fetch_keys_container().await?;
+        Ok(keys
+            .into_iter()
+            .map(|(label, keypair)| (label, keypair.public_key()))
+            .collect())
+    }
+
+    /// Change the label of a key in the account's keys container
+    pub async fn keys_label(&self, label: &str, new_label: &str) -> Result<()> {
+        debug!("Relabelling key '{}' as '{}'", label, new_label);
+        let (parents, mut keys) = self.fetch_keys_container().await?;
+        if keys.contains_key(new_label) {
+            return Err(Error::KeyLabelAlreadyExists(format!(
+                "A key labelled '{}' already exists in the keys container",
+                new_label
+            )));
+        }
+
+        let keypair = keys.remove(label).ok_or_else(|| key_not_found(label))?;
+        let _ = keys.insert(new_label.to_string(), keypair);
+
+        self.store_keys_container(parents, &keys).await
+    }
+
+    /// Export the keypair with the given label from the account's keys container.
+    ///
+    /// Apps which only need to sign with a labelled key shall do it through authd instead,
+    /// with a `RemoteSigner` for that key, which never hands them its secret.
+    pub async fn keys_export(&self, label: &str) -> Result<Keypair> {
+        let (_, mut keys) = self.fetch_keys_container().await?;
+        keys.remove(label).ok_or_else(|| key_not_found(label))
+    }
+
+    /// Sign the given data with the key with the given label from the account's keys container
+    pub async fn keys_sign(&self, label: &str, data: &[u8]) -> Result<Signature> {
+        let keypair = self.keys_export(label).await?;
+        Ok(keypair.sign(data))
+    }
+
+    // Fetch the account's keys container, along with the hashes of the Register entries it was
+    // read from, or `None` if the container wasn't created yet. Versions of the container written
+    // concurrently are merged, so a key relabelled in one of them may show up under both labels.
+    async fn fetch_keys_container(&self) -> Result<(Option<BTreeSet<EntryHash>>, KeysContainer)> {
+        let safeurl = SafeUrl::from_url(&self.keys_container_url()?)?;
+        let entries = match self.register_fetch_entries(&safeurl).await {
+            Ok(entries) => entries,
+            Err(Error::ContentNotFound(_)) => return Ok((None, KeysContainer::new())),
+            Err(Error::EmptyContent(_)) => {
+                return Ok((Some(BTreeSet::new()), KeysContainer::new()))
+            }
+            Err(err) => return Err(err),
+        };
+
+        let secret_key = keys_container_secret_key(self.get_safe_client()?)?;
+        let mut keys = KeysContainer::new();
+        let mut parents = BTreeSet::new();
+        for (hash, entry) in entries {
+            let url = SafeUrl::from_xorurl(std::str::from_utf8(&entry)?)?;
+            let encrypted = self.fetch_data(&url, None).await?;
+            keys.extend(decrypt_keys(&secret_key, &encrypted)?);
+            let _ = parents.insert(hash);
+        }
+
+        Ok((Some(parents), keys))
+    }
+
+    // Store a new version of the account's keys container, superseding the ones it was read from
+    async fn store_keys_container(
+        &self,
+        parents: Option<BTreeSet<EntryHash>>,
+        keys: &KeysContainer,
+    ) -> Result<()> {
+        let client = self.get_safe_client()?;
+        let secret_key = keys_container_secret_key(client)?;
+        let keys_xorurl = self
+            .store_private_bytes(encrypt_keys(&secret_key, keys)?, None)
+            .await?;
+
+        let parents = match parents {
+            Some(parents) => parents,
+            None => {
+                let _ = self
+                    .register_create(
+                        Some(keys_container_name(client.public_key())),
+                        KEYS_CONTAINER_TYPE_TAG,
+                        true,
+                        ContentType::Raw,
+                        &WriteAccess::OwnerOnly,
+                    )
+                    .await?;
+                BTreeSet::new()
+            }
+        };
+
+        let url = self.keys_container_url()?;
+        let _ = self
+            .register_write(&url, keys_xorurl.into_bytes(), parents)
+            .await?;
+
+        Ok(())
+    }
+}
+
+// Name of the Register pointing to the keys container of the given account
+fn keys_container_name(owner: PublicKey) -> XorName {
+    XorName::from_content(&[KEYS_CONTAINER_KEY_SEED, &XorName::from(owner).0].concat())
+}
+
+// The keys container is encrypted with a BLS key derived with HKDF from the account's secret key,
+// so it can only be decrypted by whoever holds it, i.e. authd, never an app signing through it.
+fn keys_container_secret_key(client: &Client) -> Result<bls::SecretKey> {
+    let keypair = client.keypair();
+    if keypair.public_key() != client.public_key() {
+        return Err(Error::AccessDenied(
+            "The keys container can only be accessed with the account's own keypair".to_string(),
+        ));
+    }
+
+    let secret_key_bytes = match keypair.secret_key()? {
+        SecretKey::Ed25519(secret_key) => secret_key.to_bytes().to_vec(),
+        SecretKey::Bls(secret_key) => secret_key.to_bytes().to_vec(),
+        SecretKey::BlsShare(secret_key) => secret_key.to_bytes().to_vec(),
+    };
+
+    let mut key_bytes = [0; bls::SK_SIZE];
+    Hkdf::<Sha3_256>::new(None, &secret_key_bytes)
+        .expand(KEYS_CONTAINER_KEY_SEED, &mut key_bytes)
+        .map_err(|_| Error::AuthError("Couldn't derive the keys container key".to_string()))?;
+
+    // the derived bytes are the key as they are, for it not to depend on the version of any rng;
+    // clearing the two most significant bits keeps the big-endian scalar below the curve order
+    key_bytes[0] &= 0x3f;
+    bls::SecretKey::from_bytes(key_bytes)
+        .map_err(|_| Error::AuthError("Couldn't derive the keys container key".to_string()))
+}
+
+fn encrypt_keys(secret_key: &bls::SecretKey, keys: &KeysContainer) -> Result<Bytes> {
+    let serialised = rmp_serde::to_vec(keys).map_err(|err| {
+        Error::Serialisation(format!("Couldn't serialise keys container: {:?}", err))
+    })?;
+    bls_encrypt(secret_key, &serialised)
+}
+
+fn decrypt_keys(secret_key: &bls::SecretKey, encrypted: &[u8]) -> Result<KeysContainer> {
+    let serialised = bls_decrypt(secret_key, encrypted)?;
+    rmp_serde::from_slice(&serialised).map_err(|err| {
+        Error::ContentError(format!("Couldn't deserialise keys container: {:?}", err))
+    })
 }
 
-/// Deserializes a `Keypair` from file at a given path.
+fn key_not_found(label: &str) -> Error {
+    Error::EntryNotFound(format!("No key labelled '{}' in the keys container", label))
+}
+
+/// Deserializes a `Keypair` from file at a given path, written as unencrypted [`Credentials`] of
+/// any format version.
 ///
 /// A utility to help callers working with keypairs avoid using serde or bincode directly.
 ///
 /// This exists as an independent function in addition to being a function of the safe client
 /// because some deserialization needs to be performed in the CLI before it has access to a client.
 pub fn deserialize_keypair(path: impl AsRef<Path>) -> Result<Keypair> {
-    let deserialized = std::fs::read(path)?;
-    let keypair: Keypair = sn_interface::types::utils::deserialise(&deserialized)?;
-    Ok(keypair)
+    Ok(Credentials::read(path, None)?.keypair)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Safe, SafeUrl};
+    use super::{decrypt_keys, encrypt_keys, Credentials, KeysContainer, Safe, SafeUrl};
+    use crate::{app::test_helpers::new_safe_instance, retry_loop_for_pattern};
     use assert_fs::prelude::*;
     use color_eyre::{eyre::eyre, Result};
     use predicates::prelude::*;
     use sn_interface::types::Keypair;
     use xor_name::XorName;
 
+    #[test]
+    fn keys_container_is_only_decrypted_with_its_key() -> Result<()> {
+        let secret_key = bls::SecretKey::random();
+        let mut keys = KeysContainer::new();
+        let _ = keys.insert("signing".to_string(), Keypair::new_ed25519());
+
+        let encrypted = encrypt_keys(&secret_key, &keys)?;
+        assert_eq!(decrypt_keys(&secret_key, &encrypted)?, keys);
+        assert!(decrypt_keys(&bls::SecretKey::random(), &encrypted).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_keys_create_label_and_sign() -> Result<()> {
+        let safe = new_safe_instance().await?;
+
+        let public_key = safe.keys_create("first").await?;
+        assert!(safe.keys_create("first").await.is_err());
+
+        let keys = retry_loop_for_pattern!(safe.keys_list(), Ok(keys) if keys.len() == 1)?;
+        assert_eq!(keys.get("first"), Some(&public_key));
+
+        safe.keys_label("first", "renamed").await?;
+        let _ =
+            retry_loop_for_pattern!(safe.keys_list(), Ok(keys) if keys.contains_key("renamed"))?;
+        assert!(safe.keys_export("first").await.is_err());
+
+        let keypair = safe.keys_export("renamed").await?;
+        assert_eq!(keypair.public_key(), public_key);
+
+        let signature = safe.keys_sign("renamed", b"data").await?;
+        assert!(public_key.verify(&signature, b"data").is_ok());
+
+        Ok(())
+    }
+
     #[test]
     fn new_keypair_should_generate_bls_keypair() -> Result<()> {
         let safe = Safe::dry_runner(None);
@@ -118,9 +367,8 @@ mod tests {
 
         serialized_keypair_file.assert(predicate::path::is_file());
 
-        let encoded = std::fs::read(serialized_keypair_file.path())?;
-        let keypair2: Keypair = sn_interface::types::utils::deserialise(&encoded)?;
-        assert_eq!(keypair, keypair2);
+        let credentials = Credentials::read(serialized_keypair_file.path(), None)?;
+        assert_eq!(keypair, credentials.keypair);
         Ok(())
     }
 
@@ -136,4 +384,20 @@ mod tests {
         assert_eq!(keypair, keypair2);
         Ok(())
     }
+
+    #[test]
+    fn deserialize_keypair_should_read_a_v1_keypair_file() -> Result<()> {
+        let safe = Safe::dry_runner(None);
+        let tmp_dir = assert_fs::TempDir::new()?;
+        let serialized_keypair_file = tmp_dir.child("serialized_keypair");
+        let (keypair, _) = safe.new_keypair_with_pk_url()?;
+        let serialized = sn_interface::types::utils::serialise(&keypair)?;
+        serialized_keypair_file.write_binary(&serialized)?;
+
+        let keypair2 = safe.deserialize_keypair(serialized_keypair_file.path())?;
+        assert_eq!(keypair, keypair2);
+        // reading it leaves the file as it was
+        assert_eq!(std::fs::read(serialized_keypair_file.path())?, serialized);
+        Ok(())
+    }
 }
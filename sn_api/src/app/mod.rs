@@ -9,12 +9,17 @@
 // --------------------------------------------------------------------
 // ------ The following is what's meant to be the public API -------
 
+pub mod credentials;
 pub mod files;
+pub mod health;
 pub mod keys;
 pub mod multimap;
 pub mod nrs;
 pub mod register;
 pub mod resolver;
+#[cfg(feature = "search")]
+pub mod search;
+pub mod thread;
 pub mod wallet;
 
 pub use crate::safeurl::*;
@@ -27,6 +32,8 @@ pub use xor_name::{XorName, XOR_NAME_LEN};
 mod auth;
 mod consts;
 mod helpers;
+mod network_snapshot;
+mod node_attestation;
 
 #[cfg(test)]
 mod test_helpers;
@@ -34,22 +41,41 @@ mod test_helpers;
 use super::{common, constants, Error, Result};
 
 use crate::NodeConfig;
+use files::UploadIndex;
 
+pub use sn_client::{
+    BandwidthLimits, ChunkDetails, DataMapInfo, OperationKind, Outcome, Signer, Telemetry,
+    TelemetryEvent, Throughput,
+};
 use sn_client::{Client, ClientConfig, DEFAULT_OPERATION_TIMEOUT};
+#[cfg(any(test, feature = "test-utils"))]
+pub use sn_client::{Faults, MockNetwork, MockSection};
 use sn_dbc::Owner;
-use sn_interface::types::Keypair;
+use sn_interface::messaging::data::SectionStats;
+use sn_interface::types::{Keypair, PublicKey};
 use tracing::debug;
 
+use std::collections::BTreeMap;
 use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
+use xor_name::Prefix;
 
 const APP_NOT_CONNECTED: &str = "Application is not connected to the network";
 
 #[derive(Clone)]
 pub struct Safe {
     client: Option<Client>,
+    // Signers of the identities the app can act as, by label
+    identities: BTreeMap<String, Arc<dyn Signer>>,
+    // Index of the content uploaded, so it's not uploaded again
+    upload_index: Option<Arc<UploadIndex>>,
     pub xorurl_base: XorUrlBase,
     pub dry_run_mode: bool,
+    /// Whether the writes to Registers are applied in the order they're made, a write retried
+    /// late being rejected rather than applied after later ones, see
+    /// [`Client::publish_register_ops`]. Unset by default.
+    pub ordered_writes: bool,
 }
 
 impl Safe {
@@ -57,8 +83,11 @@ impl Safe {
     pub fn dry_runner(xorurl_base: Option<XorUrlBase>) -> Self {
         Self {
             client: None,
+            identities: BTreeMap::new(),
+            upload_index: None,
             xorurl_base: xorurl_base.unwrap_or(DEFAULT_XORURL_BASE),
             dry_run_mode: true,
+            ordered_writes: false,
         }
     }
 
@@ -70,20 +99,34 @@ impl Safe {
         xorurl_base: Option<XorUrlBase>,
         timeout: Option<Duration>,
         dbc_owner: Option<Owner>,
+        telemetry: Option<Arc<dyn Telemetry>>,
     ) -> Result<Self> {
         let mut safe = Self {
             client: None,
+            identities: BTreeMap::new(),
+            upload_index: None,
             xorurl_base: xorurl_base.unwrap_or(DEFAULT_XORURL_BASE),
             dry_run_mode: false,
+            ordered_writes: false,
         };
 
-        safe.connect(bootstrap_config, keypair, config_path, timeout, dbc_owner)
-            .await?;
+        safe.connect(
+            bootstrap_config,
+            keypair,
+            config_path,
+            timeout,
+            dbc_owner,
+            telemetry,
+        )
+        .await?;
 
         Ok(safe)
     }
 
     /// Connect to the SAFE Network
+    ///
+    /// An optional [`Telemetry`] can be provided, which will then receive a [`TelemetryEvent`]
+    /// for every operation sent to the network by this instance.
     pub async fn connect(
         &mut self,
         bootstrap_config: NodeConfig,
@@ -91,6 +134,7 @@ impl Safe {
         config_path: Option<&Path>,
         timeout: Option<Duration>,
         dbc_owner: Option<Owner>,
+        telemetry: Option<Arc<dyn Telemetry>>,
     ) -> Result<()> {
         debug!("Connecting to SAFE Network...");
 
@@ -99,10 +143,10 @@ impl Safe {
         debug!("Client to be instantiated with specific pk?: {:?}", keypair);
         debug!("Bootstrap contacts list set to: {:?}", bootstrap_config);
 
-        let config = ClientConfig::new(
+        let mut config = ClientConfig::new(
             None,
             None,
-            bootstrap_config.0,
+            bootstrap_config.genesis_key,
             config_path.as_deref(),
             timeout.or(Some(DEFAULT_OPERATION_TIMEOUT)),
             timeout.or(Some(DEFAULT_OPERATION_TIMEOUT)),
@@ -110,27 +154,140 @@ impl Safe {
         )
         .await;
 
-        self.client = Some(
-            Client::new(config, bootstrap_config.1, keypair, dbc_owner)
-                .await
-                .map_err(|err| {
-                    Error::ConnectionError(format!(
-                        "Failed to connect to the SAFE Network: {:?}",
-                        err
-                    ))
-                })?,
-        );
+        // the network name set in the environment, if any, takes precedence
+        if config.network_name.is_none() {
+            config.network_name = bootstrap_config.network_name;
+        }
+
+        let client = Client::new(config, bootstrap_config.contacts, keypair, dbc_owner)
+            .await
+            .map_err(|err| {
+                Error::ConnectionError(format!("Failed to connect to the SAFE Network: {:?}", err))
+            })?;
+
+        self.client = Some(match telemetry {
+            Some(telemetry) => client.with_telemetry(telemetry),
+            None => client,
+        });
 
         debug!("Successfully connected to the Network!!!");
 
         Ok(())
     }
 
+    /// Connect to an in-memory [`MockNetwork`] instead of the SAFE Network, for apps to be unit
+    /// tested without a testnet. Files, registers and NRS work as they do on the network, while
+    /// the [`Faults`] set on the mock network are injected into the msgs sent to it.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub async fn connect_mock(
+        &mut self,
+        network: MockNetwork,
+        keypair: Option<Keypair>,
+        dbc_owner: Option<Owner>,
+    ) -> Result<()> {
+        debug!("Connecting to mock network...");
+
+        let config = ClientConfig::new(
+            None,
+            None,
+            network.genesis_key(),
+            None,
+            Some(DEFAULT_OPERATION_TIMEOUT),
+            Some(DEFAULT_OPERATION_TIMEOUT),
+            None,
+        )
+        .await;
+
+        let client = Client::new_mock(config, network, keypair, dbc_owner).map_err(|err| {
+            Error::ConnectionError(format!("Failed to connect to the mock network: {:?}", err))
+        })?;
+        self.client = Some(client);
+
+        Ok(())
+    }
+
     /// Returns true if we already have a connection with the network
     pub fn is_connected(&self) -> bool {
         self.client.is_some()
     }
 
+    /// Sign everything sent to the network with the given [`Signer`] from now on,
+    /// e.g. a `RemoteSigner` delegating to authd so the account's secret keys are never
+    /// held by the app. This requires to be already connected to the network.
+    pub fn use_signer(&mut self, signer: Arc<dyn Signer>) -> Result<()> {
+        let client = self.get_safe_client()?.with_signer(signer);
+        self.client = Some(client);
+        Ok(())
+    }
+
+    /// Look up the public files this instance stores in the given [`UploadIndex`] from now on,
+    /// so content found in it isn't uploaded again, and record the ones uploaded in it. The
+    /// index can be shared by several instances, e.g. of apps publishing the same assets.
+    pub fn use_upload_index(&mut self, upload_index: Arc<UploadIndex>) {
+        self.upload_index = Some(upload_index);
+    }
+
+    /// Add an identity this instance can act as, signing with the given [`Signer`], e.g. a
+    /// `Keypair` or a `RemoteSigner`. An identity previously added with the same label is replaced.
+    pub fn add_identity(&mut self, label: &str, signer: Arc<dyn Signer>) {
+        let _ = self.identities.insert(label.to_string(), signer);
+    }
+
+    /// Remove the identity with the given label, returning whether there was one
+    pub fn remove_identity(&mut self, label: &str) -> bool {
+        self.identities.remove(label).is_some()
+    }
+
+    /// List the labels of the identities this instance can act as, along with their public keys
+    pub fn identities(&self) -> BTreeMap<String, PublicKey> {
+        self.identities
+            .iter()
+            .map(|(label, signer)| (label.clone(), signer.public_key()))
+            .collect()
+    }
+
+    /// Return a copy of this instance acting as the identity with the given label, i.e. signing
+    /// everything it sends to the network with that identity's [`Signer`]. The copy shares the
+    /// connection to the network of this instance, so switching identity doesn't reconnect.
+    pub fn with_identity(&self, label: &str) -> Result<Self> {
+        let signer = self.identities.get(label).ok_or_else(|| {
+            Error::InvalidInput(format!("No identity labelled '{}' was added", label))
+        })?;
+        let client = self.get_safe_client()?.with_signer(signer.clone());
+
+        Ok(Self {
+            client: Some(client),
+            ..self.clone()
+        })
+    }
+
+    /// Return a copy of this instance whose transfers with the network are limited by the given
+    /// [`BandwidthLimits`], e.g. for a background sync not to saturate the user's uplink.
+    /// The copy shares the connection to the network of this instance, but not its limits.
+    pub fn with_bandwidth_limits(&self, limits: BandwidthLimits) -> Result<Self> {
+        let client = self.get_safe_client()?.with_bandwidth_limits(limits);
+
+        Ok(Self {
+            client: Some(client),
+            ..self.clone()
+        })
+    }
+
+    /// Return the rate at which this instance recently sent and received data
+    pub fn throughput(&self) -> Result<Throughput> {
+        Ok(self.get_safe_client()?.throughput())
+    }
+
+    /// # Get the stats of a section
+    ///
+    /// Queries the Elders of the section with the given prefix for its stats, e.g. its number of
+    /// nodes and how much data it approximately stores.
+    pub async fn section_info(&self, prefix: Prefix) -> Result<SectionStats> {
+        debug!("Querying stats of section {:?}", prefix);
+        let stats = self.get_safe_client()?.get_section_stats(prefix).await?;
+        Ok(stats)
+    }
+
     // Private helper to obtain the Client instance
     pub(crate) fn get_safe_client(&self) -> Result<&Client> {
         match &self.client {
@@ -139,3 +296,138 @@ impl Safe {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{register::WriteAccess, test_helpers::new_safe_instance};
+    use anyhow::{anyhow, Result};
+    use bytes::Bytes;
+
+    #[test]
+    fn identities_are_listed_by_label() {
+        let mut safe = Safe::dry_runner(None);
+        let bot = Keypair::new_ed25519();
+        let persona = Keypair::new_ed25519();
+        safe.add_identity("bot", Arc::new(bot.clone()));
+        safe.add_identity("persona", Arc::new(persona.clone()));
+
+        let identities = safe.identities();
+        assert_eq!(identities.get("bot"), Some(&bot.public_key()));
+        assert_eq!(identities.get("persona"), Some(&persona.public_key()));
+
+        assert!(safe.remove_identity("bot"));
+        assert!(!safe.remove_identity("bot"));
+        assert_eq!(safe.identities().len(), 1);
+        assert!(safe.with_identity("bot").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_with_identity() -> Result<()> {
+        let mut safe = new_safe_instance().await?;
+        let persona = Keypair::new_ed25519();
+        safe.add_identity("persona", Arc::new(persona.clone()));
+
+        let as_persona = safe.with_identity("persona")?;
+        assert_eq!(
+            as_persona.get_safe_client()?.public_key(),
+            persona.public_key()
+        );
+        assert_ne!(safe.get_safe_client()?.public_key(), persona.public_key());
+
+        // data is owned by the identity it was created as
+        let xorurl = as_persona
+            .register_create(
+                None,
+                1_000,
+                false,
+                ContentType::Raw,
+                &WriteAccess::OwnerOnly,
+            )
+            .await?;
+        let safe_url = SafeUrl::from_url(&xorurl)?;
+        let address = as_persona.get_register_address(&safe_url)?;
+        let owner = as_persona
+            .get_safe_client()?
+            .get_register_owner(address)
+            .await?;
+        assert_eq!(
+            owner,
+            sn_interface::types::register::User::Key(persona.public_key())
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_connect_mock() -> Result<()> {
+        let network = MockNetwork::new(0)?;
+        let mut safe = Safe::dry_runner(None);
+        safe.dry_run_mode = false;
+        safe.connect_mock(network.clone(), None, None).await?;
+
+        let data = Bytes::from_static(b"Something super good");
+        let xorurl = safe.store_public_bytes(data.clone(), None).await?;
+        let url = SafeUrl::from_url(&xorurl)?;
+
+        let site_name = "mock-site";
+        let _ = safe.nrs_create(site_name).await?;
+        let _ = safe.nrs_associate(site_name, &url).await?;
+        let (link, _) = safe.nrs_get(site_name, None).await?;
+        assert_eq!(link, Some(url));
+
+        // data written by an app is seen by another one connected to the same mock network
+        let mut other = Safe::dry_runner(None);
+        other.dry_run_mode = false;
+        other.connect_mock(network, None, None).await?;
+        assert_eq!(other.files_get(&xorurl, None).await?, data);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_connect_mock_section_splits() -> Result<()> {
+        let network = MockNetwork::new(0)?;
+        let mut safe = Safe::dry_runner(None);
+        safe.dry_run_mode = false;
+        safe.connect_mock(network.clone(), None, None).await?;
+
+        let site_name = "split-site";
+        let data = Bytes::from_static(b"Stored before the split");
+        let xorurl = safe.store_public_bytes(data.clone(), None).await?;
+        let _ = safe.nrs_create(site_name).await?;
+        assert_eq!(network.msgs_redirected(), 0);
+
+        // the section changes under the app, which is redirected by AE to the current one
+        let children = network
+            .split_section(Prefix::default())
+            .await
+            .ok_or_else(|| anyhow!("no root section to split"))?;
+        let _ = network
+            .churn_elders(children[1].prefix)
+            .await
+            .ok_or_else(|| anyhow!("no section to change the Elders of"))?;
+
+        assert_eq!(safe.files_get(&xorurl, None).await?, data);
+        let url = SafeUrl::from_url(&xorurl)?;
+        let _ = safe.nrs_associate(site_name, &url).await?;
+        let (link, _) = safe.nrs_get(site_name, None).await?;
+        assert_eq!(link, Some(url));
+        assert!(network.msgs_redirected() > 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_section_info() -> Result<()> {
+        let safe = new_safe_instance().await?;
+
+        // the root prefix leads to a section however many times the network split
+        let stats = safe.section_info(Prefix::default()).await?;
+        assert!(stats.elder_count > 0);
+        assert!(stats.section_key_generation > 0);
+        assert!(stats.data_copy_count > 0);
+
+        Ok(())
+    }
+}
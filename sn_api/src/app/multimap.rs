@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::register::EntryHash;
+use super::register::{EntryHash, WriteAccess};
 
 use crate::safeurl::{ContentType, SafeUrl, XorUrl};
 use crate::{Error, Result, Safe};
@@ -33,8 +33,14 @@ impl Safe {
         private: bool,
     ) -> Result<XorUrl> {
         debug!("Creating a Multimap");
-        self.register_create(name, type_tag, private, ContentType::Multimap)
-            .await
+        self.register_create(
+            name,
+            type_tag,
+            private,
+            ContentType::Multimap,
+            &WriteAccess::OwnerOnly,
+        )
+        .await
     }
 
     /// Return the value of a Multimap on the network corresponding to the key provided
@@ -108,7 +114,9 @@ impl Safe {
 
         let (entry_hash, op_batch) = client.write_to_register(address, data, replace).await?;
 
-        client.publish_register_ops(op_batch).await?;
+        client
+            .publish_register_ops(op_batch, self.ordered_writes)
+            .await?;
 
         Ok(entry_hash)
     }
@@ -145,7 +153,9 @@ impl Safe {
             .write_to_register(address, MULTIMAP_REMOVED_MARK.to_vec(), to_remove)
             .await?;
 
-        client.publish_register_ops(op_batch).await?;
+        client
+            .publish_register_ops(op_batch, self.ordered_writes)
+            .await?;
 
         Ok(entry_hash)
     }
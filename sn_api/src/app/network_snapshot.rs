@@ -0,0 +1,90 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::{Error, Result, Safe};
+
+use sn_interface::network_knowledge::NetworkSnapshot;
+use std::{fs, path::Path};
+use tracing::debug;
+
+impl Safe {
+    /// Export a snapshot of what we know of the network, i.e. the sections we learnt about, with
+    /// their Elders, and our connections to them, to a JSON file at the given path.
+    ///
+    /// The snapshot carries no secret, so it can be attached to bug reports. Snapshots, e.g. the
+    /// ones exported from the admin socket of nodes, are loaded back with
+    /// [`Safe::load_network_snapshot`], and compared with [`NetworkSnapshot::diff`].
+    pub async fn export_network_snapshot(&self, path: impl AsRef<Path>) -> Result<NetworkSnapshot> {
+        let path = path.as_ref();
+        let snapshot = self.get_safe_client()?.network_snapshot().await;
+
+        let serialised = serde_json::to_vec_pretty(&snapshot).map_err(|err| {
+            Error::Serialisation(format!("Couldn't serialise network snapshot: {:?}", err))
+        })?;
+        fs::write(path, serialised).map_err(|err| {
+            Error::FileSystemError(format!(
+                "Couldn't write network snapshot to {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+        debug!(
+            "Network snapshot with {} sections exported to {}",
+            snapshot.sections.len(),
+            path.display()
+        );
+
+        Ok(snapshot)
+    }
+
+    /// Load a snapshot of the network exported to the JSON file at the given path.
+    pub fn load_network_snapshot(path: impl AsRef<Path>) -> Result<NetworkSnapshot> {
+        let path = path.as_ref();
+        let serialised = fs::read(path).map_err(|err| {
+            Error::FileSystemError(format!(
+                "Couldn't read network snapshot at {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+        serde_json::from_slice(&serialised).map_err(|err| {
+            Error::Serialisation(format!(
+                "Couldn't deserialise network snapshot at {}: {:?}",
+                path.display(),
+                err
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{app::test_helpers::new_safe_instance, Safe};
+
+    use anyhow::Result;
+    use assert_fs::TempDir;
+
+    #[tokio::test]
+    async fn test_network_snapshot_export_and_load() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let dir = TempDir::new()?;
+        let path = dir.path().join("network_snapshot.json");
+
+        let exported = safe.export_network_snapshot(&path).await?;
+        assert!(!exported.sections.is_empty());
+
+        let loaded = Safe::load_network_snapshot(&path)?;
+        assert_eq!(loaded, exported);
+        assert!(exported.diff(&loaded).is_empty());
+
+        std::fs::write(&path, "not a snapshot")?;
+        assert!(Safe::load_network_snapshot(&path).is_err());
+
+        Ok(())
+    }
+}
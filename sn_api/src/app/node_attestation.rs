@@ -0,0 +1,164 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::{Error, Result, Safe};
+
+use sn_interface::types::NodeAttestation;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::debug;
+
+impl Safe {
+    /// Verify an attestation published by the operator of a node, as exported from the admin
+    /// socket of the node, proving they run it.
+    ///
+    /// The attestation has to be signed by the node it's about, whose name has to be within the
+    /// prefix of the section it claims to be a member of, not too long ago. It has to carry the
+    /// state of the node as a member of that section, signed by the section, and the keys of the
+    /// section have to be ones we know of, i.e. ones proven by the section chain of the network
+    /// we're connected to.
+    pub async fn verify_node_attestation(&self, attestation: &NodeAttestation) -> Result<()> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        check_attestation(attestation, now)?;
+
+        let client = self.get_safe_client()?;
+        for section_pk in [
+            attestation.section_pk,
+            attestation.node_state.sig.public_key,
+        ] {
+            if !client.is_known_section_key(&section_pk).await {
+                return Err(Error::InvalidNodeAttestation(format!(
+                    "the section key {:?} isn't known to be one of the network",
+                    section_pk
+                )));
+            }
+        }
+        debug!(
+            "Attestation of node {} verified, from section {:?}",
+            attestation.name, attestation.section_prefix
+        );
+
+        Ok(())
+    }
+}
+
+// Checks of the attestation which don't need the network
+fn check_attestation(attestation: &NodeAttestation, now: u64) -> Result<()> {
+    attestation
+        .verify()
+        .and_then(|()| attestation.check_freshness(now))
+        .map_err(|err| Error::InvalidNodeAttestation(err.to_string()))?;
+    if !attestation.section_prefix.matches(&attestation.name) {
+        return Err(Error::InvalidNodeAttestation(format!(
+            "the node {} isn't within the prefix {:?} of its section",
+            attestation.name, attestation.section_prefix
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::check_attestation;
+    use crate::{app::test_helpers::new_safe_instance, Error};
+
+    use anyhow::{anyhow, Result};
+    use ed25519_dalek::Keypair;
+    use sn_interface::{
+        messaging::system::{KeyedSig, NodeState, SectionAuth},
+        network_knowledge::MIN_ADULT_AGE,
+        types::{keys::ed25519, NodeAttestation},
+    };
+    use std::{
+        net::{Ipv4Addr, SocketAddr},
+        time::{SystemTime, UNIX_EPOCH},
+    };
+    use xor_name::Prefix;
+
+    const TIMESTAMP: u64 = 1_650_000_000;
+
+    // Attestation of the node, with its state signed by the given key as if it were the one of
+    // its section
+    fn self_signed_attestation(
+        keypair: &Keypair,
+        prefix: Prefix,
+        section_sk: &bls::SecretKey,
+        timestamp: u64,
+    ) -> Result<NodeAttestation> {
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, 12345));
+        let node_state = NodeState::joined(ed25519::name(&keypair.public), addr, None);
+        let sig = KeyedSig {
+            public_key: section_sk.public_key(),
+            signature: section_sk.sign(bincode::serialize(&node_state)?),
+        };
+
+        Ok(NodeAttestation::new(
+            keypair,
+            prefix,
+            section_sk.public_key(),
+            1,
+            timestamp,
+            SectionAuth {
+                value: node_state,
+                sig,
+            },
+        ))
+    }
+
+    #[test]
+    fn attestation_of_node_outside_its_section_is_invalid() -> Result<()> {
+        let prefix = Prefix::default().pushed(true);
+        let keypair = ed25519::gen_keypair(&prefix.range_inclusive(), MIN_ADULT_AGE);
+        let section_sk = bls::SecretKey::random();
+
+        check_attestation(
+            &self_signed_attestation(&keypair, prefix, &section_sk, TIMESTAMP)?,
+            TIMESTAMP,
+        )?;
+
+        let attestation =
+            self_signed_attestation(&keypair, prefix.sibling(), &section_sk, TIMESTAMP)?;
+        match check_attestation(&attestation, TIMESTAMP) {
+            Err(Error::InvalidNodeAttestation(_)) => Ok(()),
+            other => Err(anyhow!("unexpected result: {:?}", other)),
+        }
+    }
+
+    #[test]
+    fn stale_attestation_is_invalid() -> Result<()> {
+        let prefix = Prefix::default();
+        let keypair = ed25519::gen_keypair(&prefix.range_inclusive(), MIN_ADULT_AGE);
+        let attestation =
+            self_signed_attestation(&keypair, prefix, &bls::SecretKey::random(), TIMESTAMP)?;
+
+        match check_attestation(&attestation, TIMESTAMP + 7 * 24 * 60 * 60) {
+            Err(Error::InvalidNodeAttestation(_)) => Ok(()),
+            other => Err(anyhow!("unexpected result: {:?}", other)),
+        }
+    }
+
+    #[tokio::test]
+    async fn forged_self_signed_attestation_is_rejected() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let prefix = Prefix::default();
+        let keypair = ed25519::gen_keypair(&prefix.range_inclusive(), MIN_ADULT_AGE);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+
+        // valid as far as it goes without the network, but signed by no section of it
+        let attestation =
+            self_signed_attestation(&keypair, prefix, &bls::SecretKey::random(), now)?;
+        check_attestation(&attestation, now)?;
+
+        match safe.verify_node_attestation(&attestation).await {
+            Err(Error::InvalidNodeAttestation(_)) => Ok(()),
+            other => Err(anyhow!("unexpected result: {:?}", other)),
+        }
+    }
+}
@@ -7,14 +7,16 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 mod nrs_map;
+mod subscription;
 
 pub use crate::app::multimap::Multimap;
 pub use crate::safeurl::{ContentType, DataType, VersionHash};
-pub use nrs_map::NrsMap;
+pub use nrs_map::{NrsMap, ServiceName, ServiceRecord};
+pub use subscription::NrsChangeEvent;
 
 use crate::{app::Safe, register::EntryHash, Error, Result, SafeUrl};
 
-use log::{debug, info};
+use log::{debug, info, warn};
 use std::collections::{BTreeMap, BTreeSet};
 use std::str;
 
@@ -189,6 +191,91 @@ impl Safe {
         Ok((url, nrs_map))
     }
 
+    /// # Publishes the record of a service reachable under a public name
+    /// The top name of the input public name needs to be registered first with `nrs_create`
+    ///
+    /// The service name is made of labels starting with an underscore, e.g. `_safeapi._quic`.
+    /// The record replaces any record previously published for the same service and public name.
+    /// Returns the versioned NRS SafeUrl (containing a VersionHash) of the public name:
+    /// `safe://{public_name}?v={version_hash}`
+    pub async fn nrs_add_service(
+        &self,
+        public_name: &str,
+        service: &str,
+        record: &ServiceRecord,
+    ) -> Result<SafeUrl> {
+        info!(
+            "Publishing record of service \"{}\" for public name \"{}\": {:?}",
+            service, public_name, record
+        );
+
+        let mut url = validate_nrs_public_name(public_name)?;
+        let key = service_key(service, public_name)?;
+
+        let current_versions = self
+            .fetch_multimap_values_by_key(&url, key.as_bytes())
+            .await?
+            .into_iter()
+            .map(|(hash, _)| hash)
+            .collect();
+
+        let value = serde_json::to_vec(record).map_err(|err| {
+            Error::Serialisation(format!("Couldn't serialise service record: {:?}", err))
+        })?;
+        let entry_hash = self
+            .multimap_insert(
+                &url.to_string(),
+                (key.as_bytes().to_vec(), value),
+                current_versions,
+            )
+            .await?;
+        set_nrs_url_props(&mut url, entry_hash)?;
+
+        Ok(url)
+    }
+
+    /// # Removes the record of a service published by a public name
+    /// Returns the versioned NRS SafeUrl (containing a VersionHash) of the public name, for the
+    /// version including the removal: `safe://{public_name}?v={version_hash}`
+    pub async fn nrs_remove_service(&self, public_name: &str, service: &str) -> Result<SafeUrl> {
+        info!(
+            "Removing record of service \"{}\" for public name \"{}\"",
+            service, public_name
+        );
+
+        let mut url = validate_nrs_public_name(public_name)?;
+        let key = service_key(service, public_name)?;
+
+        let current_versions = self
+            .fetch_multimap_values_by_key(&url, key.as_bytes())
+            .await?
+            .into_iter()
+            .map(|(hash, _)| hash)
+            .collect();
+
+        let entry_hash = self
+            .multimap_remove(&url.to_string(), current_versions)
+            .await?;
+        set_nrs_url_props(&mut url, entry_hash)?;
+        Ok(url)
+    }
+
+    /// # Gets the record of a service published by a public name
+    /// Returns `None` if the public name publishes no record for the service.
+    pub async fn nrs_get_service(
+        &self,
+        public_name: &str,
+        service: &str,
+    ) -> Result<Option<ServiceRecord>> {
+        validate_service_name(service)?;
+        // conflicting links don't prevent from getting the service records
+        let nrs_map = match self.nrs_get_subnames_map(public_name, None).await {
+            Ok(nrs_map) | Err(Error::ConflictingNrsEntries(_, _, nrs_map)) => nrs_map,
+            Err(e) => return Err(e),
+        };
+        Ok(nrs_map.service(public_name, service).copied())
+    }
+
     /// Get the mapping of all subNames and their associated SafeUrl for the Nrs Map Container at the given public name
     pub async fn nrs_get_subnames_map(
         &self,
@@ -221,9 +308,15 @@ impl Safe {
             }
         }
 
+        // Service records are stored alongside the links, under keys of their own
+        let (services_multimap, multimap): (Multimap, Multimap) = multimap
+            .into_iter()
+            .partition(|(_, (key, _))| key.starts_with(b"_"));
+
         // The set may have duplicate entries; the map doesn't.
         let subnames_set = convert_multimap_to_nrs_set(&multimap, public_name, version)?;
-        let nrs_map = get_nrs_map_from_set(&subnames_set)?;
+        let mut nrs_map = get_nrs_map_from_set(&subnames_set)?;
+        nrs_map.services = get_services_from_multimap(&services_multimap)?;
 
         if nrs_map.map.len() != subnames_set.len() {
             let diff_set: BTreeSet<(String, SafeUrl)> = nrs_map.map.clone().into_iter().collect();
@@ -302,10 +395,85 @@ fn get_nrs_map_from_set(set: &BTreeSet<(String, SafeUrl)>) -> Result<NrsMap> {
         .collect::<BTreeMap<String, SafeUrl>>();
     let nrs_map = NrsMap {
         map: public_names_map,
+        ..Default::default()
     };
     Ok(nrs_map)
 }
 
+/// Parses the service records stored in the Multimap, keyed by the public name publishing them.
+///
+/// Concurrent writes may leave multiple records for the same service, in which case the one
+/// with the greatest entry hash is kept, so all clients resolve the same one.
+fn get_services_from_multimap(
+    multimap: &Multimap,
+) -> Result<BTreeMap<String, BTreeMap<ServiceName, ServiceRecord>>> {
+    let mut services: BTreeMap<String, BTreeMap<ServiceName, ServiceRecord>> = BTreeMap::new();
+    for (_, (key, value)) in multimap {
+        let key = str::from_utf8(key)?;
+        let (service, public_name) = parse_service_key(key)?;
+        let record: ServiceRecord = serde_json::from_slice(value).map_err(|err| {
+            Error::ContentError(format!(
+                "Invalid record of service \"{}\" for public name \"{}\": {:?}",
+                service, public_name, err
+            ))
+        })?;
+
+        if let Some(previous) = services
+            .entry(public_name.to_string())
+            .or_default()
+            .insert(service.to_string(), record)
+        {
+            warn!(
+                "Found multiple records of service \"{}\" for public name \"{}\", ignoring {:?}",
+                service, public_name, previous
+            );
+        }
+    }
+
+    Ok(services)
+}
+
+// Key a service record is stored under in the Multimap, e.g. "_safeapi._quic.example"
+fn service_key(service: &str, public_name: &str) -> Result<String> {
+    validate_service_name(service)?;
+    Ok(format!("{}.{}", service, public_name))
+}
+
+// Splits the key of a service record into the service name and the public name
+fn parse_service_key(key: &str) -> Result<(&str, &str)> {
+    let public_name_start = key
+        .split('.')
+        .take_while(|label| label.starts_with('_'))
+        .map(|label| label.len() + 1)
+        .sum::<usize>();
+    if public_name_start == 0 || public_name_start >= key.len() {
+        return Err(Error::ContentError(format!(
+            "Invalid key of service record in NRS Map Container: \"{}\"",
+            key
+        )));
+    }
+
+    Ok((&key[..public_name_start - 1], &key[public_name_start..]))
+}
+
+fn validate_service_name(service: &str) -> Result<()> {
+    let is_valid_label = |label: &str| {
+        label.len() > 1
+            && label.starts_with('_')
+            && label[1..]
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    };
+    if !service.split('.').all(is_valid_label) {
+        return Err(Error::InvalidInput(format!(
+            "The service name \"{}\" is invalid, it must be made of labels starting with an \
+                underscore, e.g. \"_safeapi._quic\".",
+            service
+        )));
+    }
+    Ok(())
+}
+
 fn set_nrs_url_props(url: &mut SafeUrl, entry_hash: EntryHash) -> Result<()> {
     url.set_content_version(Some(VersionHash::from(&entry_hash)));
     url.set_content_type(ContentType::NrsMapContainer)?;
@@ -333,6 +501,14 @@ fn validate_nrs_public_name(public_name: &str) -> Result<SafeUrl> {
             public_name
         )));
     }
+    // keys starting with an underscore are those of service records
+    if public_name.starts_with('_') {
+        return Err(Error::InvalidInput(format!(
+            "The NRS public name \"{}\" is invalid because it starts with an underscore, \
+                which is reserved for service names.",
+            public_name
+        )));
+    }
     Ok(url)
 }
 
@@ -365,7 +541,9 @@ fn validate_nrs_url(link: &SafeUrl) -> Result<()> {
 mod tests {
     use super::*;
     use crate::{
+        app::files::SymlinkPolicy,
         app::test_helpers::{new_safe_instance, random_nrs_name, TestDataFilesContainer},
+        register::WriteAccess,
         Error, SafeUrl,
     };
     use anyhow::{anyhow, Result};
@@ -564,7 +742,13 @@ mod tests {
         let safe = new_safe_instance().await?;
 
         let register_link = safe
-            .register_create(None, NRS_MAP_TYPE_TAG, false, ContentType::Raw)
+            .register_create(
+                None,
+                NRS_MAP_TYPE_TAG,
+                false,
+                ContentType::Raw,
+                &WriteAccess::OwnerOnly,
+            )
             .await?;
         let mut register_url = SafeUrl::from_xorurl(&register_link)?;
         register_url.set_content_version(None);
@@ -701,7 +885,7 @@ mod tests {
 
         // let's create an empty files container so we have a valid to link
         let (link, _, _) = safe
-            .files_container_create_from(TEST_DATA_FILE, None, false, false)
+            .files_container_create_from(TEST_DATA_FILE, None, false, SymlinkPolicy::StoreAsLink)
             .await?;
         let (version0, _) = safe
             .files_container_get(&link)
@@ -895,4 +1079,63 @@ mod tests {
         assert!(url.is_none());
         Ok(())
     }
+
+    #[test]
+    fn test_nrs_service_key_parsing() -> Result<()> {
+        let key = service_key("_safeapi._quic", "a.example")?;
+        assert_eq!(key, "_safeapi._quic.a.example");
+        assert_eq!(parse_service_key(&key)?, ("_safeapi._quic", "a.example"));
+
+        assert!(service_key("safeapi._quic", "example").is_err());
+        assert!(service_key("_safeapi._", "example").is_err());
+        assert!(service_key("_safe/api", "example").is_err());
+        assert!(parse_service_key("_safeapi._quic").is_err());
+        assert!(parse_service_key("example").is_err());
+        assert!(validate_nrs_public_name("_safeapi.example").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_nrs_add_and_remove_service() -> Result<()> {
+        let site_name = random_nrs_name();
+        let safe = new_safe_instance().await?;
+
+        let files_container = TestDataFilesContainer::get_container(["/testdata/test.md"]).await?;
+        let public_name = &format!("api.{site_name}");
+        let _ = safe
+            .nrs_add(public_name, &files_container["/testdata/test.md"])
+            .await?;
+
+        let record = ServiceRecord {
+            addr: "127.0.0.1:12000".parse()?,
+            public_key: crate::PublicKey::Bls(bls::SecretKey::random().public_key()),
+        };
+        let url = safe
+            .nrs_add_service(public_name, "_safeapi._quic", &record)
+            .await?;
+        assert_eq!(url.public_name(), public_name);
+        assert!(url.content_version().is_some());
+
+        let found = safe.nrs_get_service(public_name, "_safeapi._quic").await?;
+        assert_eq!(found, Some(record));
+        assert_eq!(
+            safe.nrs_get_service(public_name, "_other._quic").await?,
+            None
+        );
+
+        // the link of the public name is unaffected by its service records
+        let (link, nrs_map) = safe.nrs_get(public_name, None).await?;
+        assert_eq!(link, Some(files_container["/testdata/test.md"].clone()));
+        assert_eq!(nrs_map.map.len(), 1);
+        assert_eq!(nrs_map.services_of(public_name).len(), 1);
+
+        let _ = safe
+            .nrs_remove_service(public_name, "_safeapi._quic")
+            .await?;
+        assert_eq!(
+            safe.nrs_get_service(public_name, "_safeapi._quic").await?,
+            None
+        );
+        Ok(())
+    }
 }
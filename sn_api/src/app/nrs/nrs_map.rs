@@ -6,13 +6,27 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::{Error, Result, SafeUrl};
+use crate::{Error, PublicKey, Result, SafeUrl};
 use log::debug;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, net::SocketAddr};
 
 pub(crate) type PublicName = String;
 
+/// Name of a service a public name can publish a record for, made of labels starting
+/// with an underscore, e.g. `_safeapi._quic`.
+pub type ServiceName = String;
+
+/// Record of a service reachable under a public name, so clients can find where to
+/// connect to it, and which key it is expected to prove it holds.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Copy)]
+pub struct ServiceRecord {
+    /// Address the service is reachable at
+    pub addr: SocketAddr,
+    /// Public key the service is expected to hold the secret key of
+    pub public_key: PublicKey,
+}
+
 /// An NRS map is a description of a registered topname and all subnames associated with that.
 ///
 /// Each subname will link to some content, e.g., a `FilesContainer`, and the topname can also
@@ -30,9 +44,14 @@ pub(crate) type PublicName = String;
 /// The map also has a subname version field that optionally specifies a subname at a particular
 /// version, since it's possible to have multiple entries for a given subname. If no version was
 /// requested when the map is retrieved, it will be set to `None`.
+///
+/// Alongside the content they link to, public names can publish `ServiceRecord`s, stored in the
+/// Multimap under the public name prefixed by the service name, e.g. "_safeapi._quic.example".
 #[derive(Debug, PartialEq, Default, Serialize, Deserialize, Clone)]
 pub struct NrsMap {
     pub map: BTreeMap<PublicName, SafeUrl>,
+    #[serde(default)]
+    pub services: BTreeMap<PublicName, BTreeMap<ServiceName, ServiceRecord>>,
 }
 
 impl NrsMap {
@@ -75,6 +94,18 @@ impl NrsMap {
         }
     }
 
+    /// Get the record of the given service published by the public name, if any.
+    pub fn service(&self, public_name: &str, service: &str) -> Option<&ServiceRecord> {
+        self.services
+            .get(public_name)
+            .and_then(|services| services.get(service))
+    }
+
+    /// Get the records of all the services published by the public name.
+    pub fn services_of(&self, public_name: &str) -> BTreeMap<ServiceName, ServiceRecord> {
+        self.services.get(public_name).cloned().unwrap_or_default()
+    }
+
     /// Prints a summary for the NRS map.
     ///
     /// This is used in the CLI for printing out the details of a map.
@@ -111,9 +142,7 @@ mod tests {
 
     #[test]
     fn get_should_return_link_for_subname() -> Result<()> {
-        let mut nrs_map = NrsMap {
-            map: BTreeMap::new(),
-        };
+        let mut nrs_map = NrsMap::default();
         nrs_map
             .map
             .insert("example".to_string(), SafeUrl::from_url("safe://example")?);
@@ -133,9 +162,7 @@ mod tests {
 
     #[test]
     fn get_should_return_link_for_multi_subname() -> Result<()> {
-        let mut nrs_map = NrsMap {
-            map: BTreeMap::new(),
-        };
+        let mut nrs_map = NrsMap::default();
         nrs_map
             .map
             .insert("example".to_string(), SafeUrl::from_url("safe://example")?);
@@ -159,9 +186,7 @@ mod tests {
 
     #[test]
     fn get_should_return_link_for_topname() -> Result<()> {
-        let mut nrs_map = NrsMap {
-            map: BTreeMap::new(),
-        };
+        let mut nrs_map = NrsMap::default();
         let topname_url = SafeUrl::from_url("safe://example")?;
         nrs_map
             .map
@@ -182,9 +207,7 @@ mod tests {
 
     #[test]
     fn get_should_return_error_for_non_existent_subname() -> Result<()> {
-        let mut nrs_map = NrsMap {
-            map: BTreeMap::new(),
-        };
+        let mut nrs_map = NrsMap::default();
         nrs_map
             .map
             .insert("example".to_string(), SafeUrl::from_url("safe://example")?);
@@ -209,9 +232,7 @@ mod tests {
 
     #[test]
     fn get_should_return_none_for_container_xorurl() -> Result<()> {
-        let mut nrs_map = NrsMap {
-            map: BTreeMap::new(),
-        };
+        let mut nrs_map = NrsMap::default();
         let topname_url = SafeUrl::from_url("safe://example")?;
         nrs_map
             .map
@@ -233,9 +254,7 @@ mod tests {
 
     #[test]
     fn get_should_return_none_for_topname_when_topname_has_no_link() -> Result<()> {
-        let mut nrs_map = NrsMap {
-            map: BTreeMap::new(),
-        };
+        let mut nrs_map = NrsMap::default();
         nrs_map.map.insert(
             "a.example".to_string(),
             SafeUrl::from_url("safe://a.example")?,
@@ -251,10 +270,32 @@ mod tests {
     }
 
     #[test]
-    fn get_map_summary_should_return_map_entries() -> Result<()> {
-        let mut nrs_map = NrsMap {
-            map: BTreeMap::new(),
+    fn service_should_return_record_of_public_name() -> Result<()> {
+        let mut nrs_map = NrsMap::default();
+        let record = ServiceRecord {
+            addr: "127.0.0.1:12000".parse()?,
+            public_key: PublicKey::Bls(bls::SecretKey::random().public_key()),
         };
+        let _ = nrs_map
+            .services
+            .entry("a.example".to_string())
+            .or_default()
+            .insert("_safeapi._quic".to_string(), record);
+
+        assert_eq!(
+            nrs_map.service("a.example", "_safeapi._quic"),
+            Some(&record)
+        );
+        assert_eq!(nrs_map.service("a.example", "_other._quic"), None);
+        assert_eq!(nrs_map.service("example", "_safeapi._quic"), None);
+        assert_eq!(nrs_map.services_of("a.example").len(), 1);
+        assert!(nrs_map.services_of("example").is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn get_map_summary_should_return_map_entries() -> Result<()> {
+        let mut nrs_map = NrsMap::default();
         let topname_url = SafeUrl::from_url("safe://example")?;
         let a_url = SafeUrl::from_url("safe://a.example")?;
         let a_b_url = SafeUrl::from_url("safe://a.b.example")?;
@@ -0,0 +1,261 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{validate_nrs_top_name, NrsMap};
+use crate::{app::Safe, register::EntryHash, Error, Result, SafeUrl};
+
+use futures::stream::{self, Stream};
+use log::{debug, info};
+use sn_interface::types::register::User;
+use std::{
+    collections::{BTreeSet, VecDeque},
+    time::Duration,
+};
+
+// Interval the NRS map is first polled at, and polled at again once a change was found
+const MIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+// Interval polling backs off to while no changes are found
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A change made to an NRS map, as notified to its subscribers
+#[derive(Clone, Debug, PartialEq)]
+pub enum NrsChangeEvent {
+    /// A public name was associated to a link
+    SubnameAdded { public_name: String, link: SafeUrl },
+    /// A public name was removed from the map
+    SubnameRemoved { public_name: String },
+    /// A public name was associated to a different link
+    TargetChanged {
+        public_name: String,
+        previous: SafeUrl,
+        link: SafeUrl,
+    },
+    /// The NRS map was transferred to a new owner
+    OwnershipTransferred { previous: User, owner: User },
+}
+
+impl Safe {
+    /// # Subscribe to the changes made to an NRS map
+    ///
+    /// Returns a stream of the changes made to the NRS map of the given top name from now on,
+    /// e.g. so a gateway can invalidate what it cached for a public name as soon as it changes.
+    ///
+    /// The stream is driven by polling the version of the NRS map. It's polled more often right
+    /// after a change, backing off while none are found. Errors hit while polling are returned
+    /// in the stream, which carries on afterwards.
+    pub async fn nrs_subscribe(
+        &self,
+        top_name: &str,
+    ) -> Result<impl Stream<Item = Result<NrsChangeEvent>>> {
+        info!("Subscribing to changes of NRS map for: {}", top_name);
+        let url = validate_nrs_top_name(top_name)?;
+
+        let mut poller = NrsPoller {
+            safe: self.clone(),
+            top_name: top_name.to_string(),
+            url,
+            heads: BTreeSet::new(),
+            map: NrsMap::default(),
+            owner: None,
+            interval: MIN_POLL_INTERVAL,
+            pending: VecDeque::new(),
+        };
+        // the current state is the starting point, only the changes made from now on are notified
+        let _ = poller.poll().await?;
+        poller.pending.clear();
+
+        Ok(stream::unfold(poller, |mut poller| async move {
+            let event = poller.next_event().await;
+            Some((event, poller))
+        }))
+    }
+}
+
+// State of a subscription to an NRS map, as of the last time it was polled
+struct NrsPoller {
+    safe: Safe,
+    top_name: String,
+    url: SafeUrl,
+    heads: BTreeSet<EntryHash>,
+    map: NrsMap,
+    owner: Option<User>,
+    interval: Duration,
+    pending: VecDeque<NrsChangeEvent>,
+}
+
+impl NrsPoller {
+    async fn next_event(&mut self) -> Result<NrsChangeEvent> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(event);
+            }
+
+            tokio::time::sleep(self.interval).await;
+            match self.poll().await {
+                Ok(true) => self.interval = MIN_POLL_INTERVAL,
+                Ok(false) => self.interval = (self.interval * 2).min(MAX_POLL_INTERVAL),
+                Err(err) => {
+                    self.interval = (self.interval * 2).min(MAX_POLL_INTERVAL);
+                    return Err(err);
+                }
+            }
+        }
+    }
+
+    // Queue the events for the changes made since the last poll, returning whether there were any.
+    // The NRS map is only fetched once the version of its Register changed.
+    async fn poll(&mut self) -> Result<bool> {
+        let mut changed = false;
+
+        let heads: BTreeSet<EntryHash> = match self.safe.register_fetch_entries(&self.url).await {
+            Ok(entries) => entries.into_iter().map(|(hash, _)| hash).collect(),
+            Err(Error::EmptyContent(_)) => BTreeSet::new(),
+            Err(err) => return Err(err),
+        };
+        if heads != self.heads {
+            debug!("NRS map for {} is at a new version", self.top_name);
+            let map = match self.safe.nrs_get_subnames_map(&self.top_name, None).await {
+                Ok(map) | Err(Error::ConflictingNrsEntries(_, _, map)) => map,
+                Err(err) => return Err(err),
+            };
+            let changes = nrs_map_changes(&self.map, &map);
+            changed |= !changes.is_empty();
+            self.pending.extend(changes);
+            self.map = map;
+            self.heads = heads;
+        }
+
+        let address = self.safe.get_register_address(&self.url)?;
+        let owner = self
+            .safe
+            .get_safe_client()?
+            .get_register_owner(address)
+            .await?;
+        if let Some(previous) = self.owner.replace(owner) {
+            if previous != owner {
+                self.pending
+                    .push_back(NrsChangeEvent::OwnershipTransferred { previous, owner });
+                changed = true;
+            }
+        }
+
+        Ok(changed)
+    }
+}
+
+// Changes to go from the previous version of an NRS map to the current one
+fn nrs_map_changes(previous: &NrsMap, current: &NrsMap) -> Vec<NrsChangeEvent> {
+    let mut changes = Vec::new();
+
+    for (public_name, link) in &current.map {
+        match previous.map.get(public_name) {
+            None => changes.push(NrsChangeEvent::SubnameAdded {
+                public_name: public_name.clone(),
+                link: link.clone(),
+            }),
+            Some(previous_link) if previous_link != link => {
+                changes.push(NrsChangeEvent::TargetChanged {
+                    public_name: public_name.clone(),
+                    previous: previous_link.clone(),
+                    link: link.clone(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+
+    for public_name in previous.map.keys() {
+        if !current.map.contains_key(public_name) {
+            changes.push(NrsChangeEvent::SubnameRemoved {
+                public_name: public_name.clone(),
+            });
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::test_helpers::{new_safe_instance, random_nrs_name, TestDataFilesContainer};
+    use anyhow::{anyhow, Result};
+    use futures::{pin_mut, StreamExt};
+
+    #[test]
+    fn nrs_map_changes_are_listed() -> Result<()> {
+        let link = SafeUrl::from_url("safe://link")?;
+        let other_link = SafeUrl::from_url("safe://other-link")?;
+
+        let mut previous = NrsMap::default();
+        let _ = previous.map.insert("kept.site".to_string(), link.clone());
+        let _ = previous
+            .map
+            .insert("changed.site".to_string(), link.clone());
+        let _ = previous
+            .map
+            .insert("removed.site".to_string(), link.clone());
+
+        let mut current = NrsMap::default();
+        let _ = current.map.insert("kept.site".to_string(), link.clone());
+        let _ = current
+            .map
+            .insert("changed.site".to_string(), other_link.clone());
+        let _ = current.map.insert("added.site".to_string(), link.clone());
+
+        assert_eq!(
+            nrs_map_changes(&previous, &current),
+            vec![
+                NrsChangeEvent::SubnameAdded {
+                    public_name: "added.site".to_string(),
+                    link: link.clone(),
+                },
+                NrsChangeEvent::TargetChanged {
+                    public_name: "changed.site".to_string(),
+                    previous: link,
+                    link: other_link,
+                },
+                NrsChangeEvent::SubnameRemoved {
+                    public_name: "removed.site".to_string(),
+                },
+            ]
+        );
+        assert!(nrs_map_changes(&current, &current).is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_nrs_subscribe() -> Result<()> {
+        let site_name = random_nrs_name();
+        let safe = new_safe_instance().await?;
+        let files_container = TestDataFilesContainer::get_container([]).await?;
+
+        let _ = safe.nrs_create(&site_name).await?;
+        let events = safe.nrs_subscribe(&site_name).await?;
+        pin_mut!(events);
+
+        let public_name = format!("sub.{}", site_name);
+        let _ = safe
+            .nrs_associate(&public_name, &files_container.url)
+            .await?;
+
+        let event = tokio::time::timeout(Duration::from_secs(60), events.next())
+            .await?
+            .ok_or_else(|| anyhow!("NRS subscription stream ended"))??;
+        assert_eq!(
+            event,
+            NrsChangeEvent::SubnameAdded {
+                public_name,
+                link: files_container.url,
+            }
+        );
+
+        Ok(())
+    }
+}
@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-pub use sn_interface::types::register::{Entry, EntryHash};
+pub use sn_interface::types::register::{Entry, EntryHash, WriteAccess};
 
 use crate::safeurl::{ContentType, SafeUrl, XorUrl};
 use crate::{Error, Result, Safe};
@@ -16,22 +16,27 @@ use log::debug;
 use rand::Rng;
 use sn_client::Error as ClientError;
 use sn_interface::types::{
-    register::{Policy, PrivatePermissions, PrivatePolicy, PublicPermissions, PublicPolicy, User},
+    register::{Policy, User},
     DataAddress, Error as SafeNdError, RegisterAddress, Scope,
 };
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::BTreeSet;
 use tracing::info;
 use xor_name::XorName;
 
 impl Safe {
     // === Register data operations ===
     /// Create a Register on the network
+    ///
+    /// The Register is owned by this instance's identity, which can grant others to write to it,
+    /// either explicitly listing who can, or letting anyone append entries to a public Register.
+    /// Writes of others are checked against this by the network.
     pub async fn register_create(
         &self,
         name: Option<XorName>,
         tag: u64,
         private: bool,
         content_type: ContentType,
+        write_access: &WriteAccess,
     ) -> Result<XorUrl> {
         debug!(
             "Storing {} Register data with tag type: {}, xorname: {:?}, dry_run: {}",
@@ -62,11 +67,9 @@ impl Safe {
         let owner = User::Key(client.public_key());
 
         // Store the Register on the network
-        let policy = if private {
-            private_policy(owner)
-        } else {
-            public_policy(owner)
-        };
+        let policy = Policy::with_write_access(owner, scope, write_access).map_err(|_| {
+            Error::InvalidInput("Only public Registers can be appended to by anyone".to_string())
+        })?;
 
         let (_, op_batch) = client
             .create_register(xorname, tag, policy)
@@ -78,7 +81,9 @@ impl Safe {
                 ))
             })?;
 
-        client.publish_register_ops(op_batch).await?;
+        client
+            .publish_register_ops(op_batch, self.ordered_writes)
+            .await?;
 
         Ok(xorurl)
     }
@@ -222,11 +227,66 @@ impl Safe {
             }
         };
 
-        client.publish_register_ops(op_batch).await?;
+        client
+            .publish_register_ops(op_batch, self.ordered_writes)
+            .await?;
 
         Ok(entry_hash)
     }
 
+    /// Write several values to a Register on the network, in a single cmd
+    ///
+    /// The values are written in order, each of them the child of the one before it, the first
+    /// one being the child of the given `parents`. They're all written or none of them, and their
+    /// hashes are returned in the same order as the values.
+    pub async fn register_write_batch(
+        &self,
+        url: &str,
+        entries: Vec<Entry>,
+        parents: BTreeSet<EntryHash>,
+    ) -> Result<Vec<EntryHash>> {
+        let reg_url = self.parse_and_resolve_url(url).await?;
+        let address = self.get_register_address(&reg_url)?;
+        if self.dry_run_mode {
+            return Ok(entries
+                .iter()
+                .map(|_| EntryHash(rand::thread_rng().gen::<[u8; 32]>()))
+                .collect());
+        }
+
+        let client = self.get_safe_client()?;
+        let (entry_hashes, op_batch) = match client
+            .write_batch_to_register(address, entries, parents)
+            .await
+        {
+            Ok(data) => data,
+            Err(
+                ClientError::NetworkDataError(SafeNdError::AccessDenied(_))
+                | ClientError::ErrorMsg {
+                    source: ErrorMsg::AccessDenied(_),
+                    ..
+                },
+            ) => {
+                return Err(Error::AccessDenied(format!(
+                    "Couldn't write data on Register found at \"{}\"",
+                    url
+                )));
+            }
+            Err(err) => {
+                return Err(Error::NetDataError(format!(
+                    "Failed to write data on Register: {:?}",
+                    err
+                )));
+            }
+        };
+
+        client
+            .publish_register_ops(op_batch, self.ordered_writes)
+            .await?;
+
+        Ok(entry_hashes)
+    }
+
     pub(crate) fn get_register_address(&self, url: &SafeUrl) -> Result<RegisterAddress> {
         let address = match url.address() {
             DataAddress::Register(reg_address) => reg_address,
@@ -242,32 +302,34 @@ impl Safe {
     }
 }
 
-fn private_policy(owner: User) -> Policy {
-    let mut permissions = BTreeMap::new();
-    let _ = permissions.insert(owner, PrivatePermissions::new(true, true));
-    Policy::Private(PrivatePolicy { owner, permissions })
-}
-
-fn public_policy(owner: User) -> Policy {
-    let mut permissions = BTreeMap::new();
-    let _ = permissions.insert(owner, PublicPermissions::new(true));
-    Policy::Public(PublicPolicy { owner, permissions })
-}
-
 #[cfg(test)]
 mod tests {
+    use super::WriteAccess;
     use crate::{app::test_helpers::new_safe_instance, ContentType, Error};
     use anyhow::{bail, Result};
+    use std::collections::BTreeSet;
 
     #[tokio::test]
     async fn test_register_create() -> Result<()> {
         let safe = new_safe_instance().await?;
 
         let xorurl = safe
-            .register_create(None, 25_000, false, ContentType::Raw)
+            .register_create(
+                None,
+                25_000,
+                false,
+                ContentType::Raw,
+                &WriteAccess::OwnerOnly,
+            )
             .await?;
         let xorurl_priv = safe
-            .register_create(None, 25_000, true, ContentType::Raw)
+            .register_create(
+                None,
+                25_000,
+                true,
+                ContentType::Raw,
+                &WriteAccess::OwnerOnly,
+            )
             .await?;
 
         let received_data = safe.register_read(&xorurl).await?;
@@ -293,6 +355,44 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_register_write_batch() -> Result<()> {
+        let safe = new_safe_instance().await?;
+
+        let xorurl = safe
+            .register_create(
+                None,
+                25_000,
+                false,
+                ContentType::Raw,
+                &WriteAccess::OwnerOnly,
+            )
+            .await?;
+
+        let entries: Vec<_> = ["first", "second", "third"]
+            .iter()
+            .map(|entry| entry.as_bytes().to_vec())
+            .collect();
+        let hashes = safe
+            .register_write_batch(&xorurl, entries.clone(), Default::default())
+            .await?;
+        assert_eq!(hashes.len(), entries.len());
+
+        for (hash, entry) in hashes.iter().zip(&entries) {
+            let received_entry = safe.register_read_entry(&xorurl, *hash).await?;
+            assert_eq!(&received_entry, entry);
+        }
+
+        // each entry is the child of the one before it, so the last one is the only latest
+        let received_data = safe.register_read(&xorurl).await?;
+        assert_eq!(
+            received_data,
+            BTreeSet::from([(hashes[2], entries[2].clone())])
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_register_owner_permissions() -> Result<()> {
         let safe = new_safe_instance().await?;
@@ -304,11 +404,18 @@ mod tests {
                 25_000,
                 /*private=*/ true,
                 ContentType::Raw,
+                &WriteAccess::OwnerOnly,
             )
             .await?;
 
         let xorurl = safe
-            .register_create(Some(xorname), 25_000, false, ContentType::Raw)
+            .register_create(
+                Some(xorname),
+                25_000,
+                false,
+                ContentType::Raw,
+                &WriteAccess::OwnerOnly,
+            )
             .await?;
 
         let received_data_priv = safe.register_read(&xorurl_priv).await?;
@@ -374,4 +481,58 @@ mod tests {
             Ok(_) => bail!("Reading private Register succeeded unexpectedly".to_string()),
         }
     }
+
+    #[tokio::test]
+    async fn test_register_write_access() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let writer = new_safe_instance().await?;
+        let other = new_safe_instance().await?;
+
+        let writers =
+            WriteAccess::Writers(BTreeSet::from([writer.get_safe_client()?.public_key()]));
+        let xorurl = safe
+            .register_create(None, 25_000, false, ContentType::Raw, &writers)
+            .await?;
+        let _ = writer
+            .register_write(&xorurl, b"listed-writer".to_vec(), Default::default())
+            .await?;
+        match other
+            .register_write(&xorurl, b"unlisted-writer".to_vec(), Default::default())
+            .await
+        {
+            Err(Error::AccessDenied(_)) => {}
+            other => bail!(
+                "Write by an unlisted writer unexpectedly returned {:?}",
+                other
+            ),
+        }
+
+        let xorurl = safe
+            .register_create(
+                None,
+                25_000,
+                false,
+                ContentType::Raw,
+                &WriteAccess::PublicAppend,
+            )
+            .await?;
+        let _ = other
+            .register_write(&xorurl, b"anyone".to_vec(), Default::default())
+            .await?;
+
+        // private Registers can't be appended to by anyone
+        match safe
+            .register_create(
+                None,
+                25_000,
+                true,
+                ContentType::Raw,
+                &WriteAccess::PublicAppend,
+            )
+            .await
+        {
+            Err(Error::InvalidInput(_)) => Ok(()),
+            other => bail!("Creation unexpectedly returned {:?}", other),
+        }
+    }
 }
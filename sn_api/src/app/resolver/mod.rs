@@ -26,6 +26,7 @@ impl Safe {
     pub async fn parse_and_resolve_url(&self, url: &str) -> Result<SafeUrl> {
         let safe_url = SafeUrl::from_url(url)?;
         let orig_path = safe_url.path_decoded()?;
+        let orig_url = safe_url.clone();
 
         // Obtain the resolution chain without resolving the URL's path
         let mut resolution_chain = self
@@ -39,9 +40,10 @@ impl Safe {
             .pop()
             .ok_or_else(|| Error::ContentNotFound(format!("Failed to resolve {}", url)))?;
 
-        // Set the original path so we return the SafeUrl with it
+        // Set the original path and query params so we return the SafeUrl with them
         let mut new_safe_url = SafeUrl::from_url(&safe_data.xorurl())?;
         new_safe_url.set_path(&orig_path);
+        new_safe_url.inherit_query_params(&orig_url);
 
         Ok(new_safe_url)
     }
@@ -52,13 +54,13 @@ impl Safe {
     ///
     /// ### Fetch FilesContainer relative path file
     /// ```no_run
-    /// # use sn_api::{Safe, resolver::SafeData};
+    /// # use sn_api::{files::SymlinkPolicy, resolver::SafeData, Safe};
     /// # use std::collections::BTreeMap;
     /// # let mut safe = Safe::default();
     /// # let rt = tokio::runtime::Runtime::new().unwrap();
     /// # rt.block_on(async {
     /// #   safe.connect(None, None, None).await.unwrap();
-    ///     let (xorurl, _, _) = safe.files_container_create_from("./testdata/", None, true, false).await.unwrap();
+    ///     let (xorurl, _, _) = safe.files_container_create_from("./testdata/", None, true, SymlinkPolicy::StoreAsLink).await.unwrap();
     ///
     ///     let safe_data = safe.fetch( &format!( "{}/test.md", &xorurl.replace("?v=0", "") ), None ).await.unwrap();
     ///     let data_string = match safe_data {
@@ -101,13 +103,13 @@ impl Safe {
     ///
     /// ### Inspect FilesContainer relative path file
     /// ```no_run
-    /// # use sn_api::{Safe, resolver::SafeData};
+    /// # use sn_api::{files::SymlinkPolicy, resolver::SafeData, Safe};
     /// # use std::collections::BTreeMap;
     /// # let rt = tokio::runtime::Runtime::new().unwrap();
     /// # rt.block_on(async {
     /// #   let mut safe = Safe::default();
     /// #   safe.connect(None, None, None).await.unwrap();
-    ///     let (container_xorurl, _, _) = safe.files_container_create_from("./testdata/", None, true, false).await.unwrap();
+    ///     let (container_xorurl, _, _) = safe.files_container_create_from("./testdata/", None, true, SymlinkPolicy::StoreAsLink).await.unwrap();
     ///
     ///     let inspected_content = safe.inspect( &format!( "{}/test.md", &container_xorurl.replace("?v=0", "") ) ).await.unwrap();
     ///     match &inspected_content[0] {
@@ -241,7 +243,7 @@ impl Safe {
 mod tests {
     use super::*;
     use crate::{
-        app::files,
+        app::files::{self, SymlinkPolicy},
         app::test_helpers::{new_safe_instance, random_nrs_name, TestDataFilesContainer},
         SafeUrl, Scope,
     };
@@ -254,7 +256,7 @@ mod tests {
     async fn test_fetch_files_container() -> Result<()> {
         let safe = new_safe_instance().await?;
         let (fc_xorurl, _, original_files_map) = safe
-            .files_container_create_from("./testdata/", None, true, false)
+            .files_container_create_from("./testdata/", None, true, SymlinkPolicy::StoreAsLink)
             .await?;
 
         let safe_url = SafeUrl::from_url(&fc_xorurl)?;
@@ -309,7 +311,7 @@ mod tests {
 
         // create file container
         let (xorurl, _, the_files_map) = safe
-            .files_container_create_from("./testdata/", None, true, false)
+            .files_container_create_from("./testdata/", None, true, SymlinkPolicy::StoreAsLink)
             .await?;
         let _ = safe.fetch(&xorurl, None).await?;
         let (version0, _) = safe
@@ -371,7 +373,7 @@ mod tests {
 
         // create file container
         let (xorurl, _, _the_files_map) = safe
-            .files_container_create_from("./testdata/", None, true, false)
+            .files_container_create_from("./testdata/", None, true, SymlinkPolicy::StoreAsLink)
             .await?;
         let _ = safe.fetch(&xorurl, None).await?;
         let (version0, _) = safe
@@ -557,7 +559,7 @@ mod tests {
 
         // create file container
         let (xorurl, _, _files_map) = safe
-            .files_container_create_from("./testdata/", None, true, false)
+            .files_container_create_from("./testdata/", None, true, SymlinkPolicy::StoreAsLink)
             .await?;
         let _ = safe.fetch(&xorurl, None).await?;
         let (version0, _) = safe
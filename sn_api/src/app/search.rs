@@ -0,0 +1,259 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::consts::{PREDICATE_LINK, PREDICATE_TYPE};
+use super::files::FileMeta;
+use crate::safeurl::XorUrl;
+use crate::{Error, Result, Safe};
+use bytes::Bytes;
+use log::{debug, warn};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Path of the file holding the serialised index, in the FilesContainer the index is stored on
+pub const SEARCH_INDEX_PATH: &str = "/search_index.json";
+
+// Terms shorter than this are not indexed, nor searched for
+const MIN_TERM_LEN: usize = 2;
+
+// Media types, besides the `text/*` ones, of the files which are indexed
+const TEXT_MEDIA_TYPES: [&str; 4] = [
+    "application/json",
+    "application/javascript",
+    "application/xml",
+    // files without an extension, indexed only if their content turns out to be UTF-8
+    "Raw",
+];
+
+/// Inverted index of the text files of a FilesContainer, mapping each term found in them
+/// to the paths of the files it was found in.
+pub type SearchIndex = BTreeMap<String, BTreeSet<String>>;
+
+impl Safe {
+    /// # Build a search index of the text files of a FilesContainer.
+    ///
+    /// Every text file of the latest version of the FilesContainer at the given URL is
+    /// fetched, and the terms found in it indexed. The index is then stored as a file,
+    /// at `SEARCH_INDEX_PATH`, in a FilesContainer of its own, whose XOR-URL is returned
+    /// along with the index, so it can be published alongside the site it was built for.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::{files::SymlinkPolicy, Safe};
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    ///     safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from("./testdata", None, true, SymlinkPolicy::FollowTarget).await.unwrap();
+    ///     let (index_xorurl, _index) = safe.search_index_create(&xorurl).await.unwrap();
+    ///     let paths = safe.search(&index_xorurl, "hello").await.unwrap();
+    ///     println!("Files containing 'hello': {:?}", paths);
+    /// # });
+    /// ```
+    pub async fn search_index_create(&self, url: &str) -> Result<(XorUrl, SearchIndex)> {
+        let files_map = match self.files_container_get(url).await? {
+            Some((_, files_map)) => files_map,
+            None => {
+                return Err(Error::ContentNotFound(format!(
+                    "No FilesContainer found at {}",
+                    url
+                )))
+            }
+        };
+
+        let mut index = SearchIndex::new();
+        for (path, file_info) in &files_map {
+            let (file_type, link) =
+                match (file_info.get(PREDICATE_TYPE), file_info.get(PREDICATE_LINK)) {
+                    (Some(file_type), Some(link)) if is_text_file(file_type) => (file_type, link),
+                    _ => continue,
+                };
+
+            let content = self.files_get(link, None).await?;
+            match std::str::from_utf8(&content) {
+                Ok(text) => index_text(&mut index, path, text),
+                Err(err) => warn!(
+                    "Not indexing file at {} of type {}, its content is not UTF-8: {}",
+                    path, file_type, err
+                ),
+            }
+        }
+
+        let serialised_index = serde_json::to_vec(&index).map_err(|err| {
+            Error::Serialisation(format!("Couldn't serialise the search index: {:?}", err))
+        })?;
+
+        let index_xorurl = self.files_container_create().await?;
+        let _ = self
+            .files_container_add_from_raw(
+                Bytes::from(serialised_index),
+                &format!("{}{}", index_xorurl, SEARCH_INDEX_PATH),
+                false,
+                false,
+                None,
+            )
+            .await?;
+
+        debug!(
+            "Search index of {} terms, for FilesContainer at {}, stored at: {}",
+            index.len(),
+            url,
+            index_xorurl
+        );
+
+        Ok((index_xorurl, index))
+    }
+
+    /// # Search the files indexed by a search index.
+    ///
+    /// Returns the paths of the files containing all the terms of the query,
+    /// looked up in the search index stored at the given URL by `search_index_create`.
+    pub async fn search(&self, index_url: &str, query: &str) -> Result<BTreeSet<String>> {
+        let index = self.search_index_get(index_url).await?;
+        Ok(search_index(&index, query))
+    }
+
+    /// # Fetch a search index stored by `search_index_create`.
+    pub async fn search_index_get(&self, index_url: &str) -> Result<SearchIndex> {
+        let files_map = match self.files_container_get(index_url).await? {
+            Some((_, files_map)) => files_map,
+            None => {
+                return Err(Error::ContentNotFound(format!(
+                    "No search index found at {}",
+                    index_url
+                )))
+            }
+        };
+
+        let link = files_map
+            .get(SEARCH_INDEX_PATH)
+            .and_then(|file_info| file_info.get(PREDICATE_LINK))
+            .ok_or_else(|| {
+                Error::ContentError(format!(
+                    "FilesContainer at {} is not a search index, it has no file at {}",
+                    index_url, SEARCH_INDEX_PATH
+                ))
+            })?;
+
+        let serialised_index = self.files_get(link, None).await?;
+        serde_json::from_slice(&serialised_index).map_err(|err| {
+            Error::ContentError(format!(
+                "Couldn't deserialise the search index stored at {}: {:?}",
+                index_url, err
+            ))
+        })
+    }
+}
+
+fn is_text_file(file_type: &str) -> bool {
+    FileMeta::filetype_is_file(file_type)
+        && (file_type.starts_with("text/") || TEXT_MEDIA_TYPES.contains(&file_type))
+}
+
+// Lowercased alphanumeric terms of the text, skipping the too short ones
+fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|term| term.chars().count() >= MIN_TERM_LEN)
+        .map(str::to_lowercase)
+}
+
+fn index_text(index: &mut SearchIndex, path: &str, text: &str) {
+    for term in terms(text) {
+        let _ = index.entry(term).or_default().insert(path.to_string());
+    }
+}
+
+// Paths of the files containing all the terms of the query
+fn search_index(index: &SearchIndex, query: &str) -> BTreeSet<String> {
+    let mut matches: Option<BTreeSet<String>> = None;
+    for term in terms(query) {
+        let paths = index.get(&term).cloned().unwrap_or_default();
+        matches = Some(match matches {
+            Some(matches) => matches.intersection(&paths).cloned().collect(),
+            None => paths,
+        });
+    }
+
+    matches.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::{files::SymlinkPolicy, test_helpers::new_safe_instance},
+        retry_loop,
+    };
+    use anyhow::Result;
+
+    fn paths(paths: &[&str]) -> BTreeSet<String> {
+        paths.iter().map(|path| path.to_string()).collect()
+    }
+
+    #[test]
+    fn test_search_index_matches_all_query_terms() {
+        let mut index = SearchIndex::new();
+        index_text(
+            &mut index,
+            "/index.html",
+            "<h1>Welcome to the Safe Network</h1>",
+        );
+        index_text(
+            &mut index,
+            "/about.md",
+            "The network is SAFE, a network of nodes.",
+        );
+        index_text(&mut index, "/a.txt", "a b c");
+
+        assert_eq!(
+            search_index(&index, "network"),
+            paths(&["/about.md", "/index.html"])
+        );
+        assert_eq!(
+            search_index(&index, "SAFE  welcome!"),
+            paths(&["/index.html"])
+        );
+        assert_eq!(search_index(&index, "nodes welcome"), paths(&[]));
+        // too short to be indexed
+        assert!(!index.contains_key("a"));
+        assert_eq!(search_index(&index, "a"), paths(&[]));
+        assert_eq!(search_index(&index, ""), paths(&[]));
+    }
+
+    #[test]
+    fn test_search_index_text_files_only() {
+        assert!(is_text_file("text/markdown"));
+        assert!(is_text_file("application/json"));
+        assert!(is_text_file("Raw"));
+        assert!(!is_text_file("image/png"));
+        assert!(!is_text_file("inode/directory"));
+        assert!(!is_text_file("inode/symlink"));
+    }
+
+    #[tokio::test]
+    async fn test_search_index_create_and_search() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let (xorurl, _, _) = retry_loop!(safe.files_container_create_from(
+            "./testdata/",
+            None,
+            true,
+            SymlinkPolicy::FollowTarget
+        ));
+
+        let (index_xorurl, index) = safe.search_index_create(&xorurl).await?;
+        assert!(index_xorurl.starts_with("safe://"));
+        assert_eq!(index["hello"], paths(&["/test.md"]));
+
+        let found = retry_loop!(safe.search(&index_xorurl, "Hello tests"));
+        assert_eq!(found, paths(&["/test.md"]));
+        let found = safe.search(&index_xorurl, "noextension file").await?;
+        assert_eq!(found, paths(&["/noextension"]));
+
+        Ok(())
+    }
+}
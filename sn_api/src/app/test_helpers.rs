@@ -6,20 +6,13 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::{ipc::NodeConfig, Safe, SafeUrl};
+use crate::{files::SymlinkPolicy, ipc::NodeConfig, Safe, SafeUrl};
 use anyhow::{anyhow, bail, Context, Result};
 use bls::SecretKey;
 use rand::{distributions::Alphanumeric, thread_rng, Rng};
 use sn_dbc::Owner;
-use sn_interface::types::{Keypair, PublicKey};
-use std::{
-    collections::{BTreeSet, HashMap},
-    env::var,
-    fs,
-    net::SocketAddr,
-    ops::Index,
-    sync::Once,
-};
+use sn_interface::types::Keypair;
+use std::{collections::HashMap, env::var, fs, ops::Index, sync::Once};
 use tracing_subscriber::{fmt, EnvFilter};
 
 // Environment variable which can be set with the auth credentials
@@ -60,7 +53,7 @@ impl TestDataFilesContainer {
         let mut map: HashMap<String, SafeUrl> = HashMap::new();
         let safe = new_safe_instance().await?;
         let (container_xorurl, _, files_map) = safe
-            .files_container_create_from("./testdata", None, false, false)
+            .files_container_create_from("./testdata", None, false, SymlinkPolicy::StoreAsLink)
             .await?;
         let container_url = SafeUrl::from_url(&container_xorurl)?;
         for file in files {
@@ -112,6 +105,7 @@ pub async fn new_safe_instance() -> Result<Safe> {
         None,
         None,
         None,
+        None,
     )
     .await?;
 
@@ -141,6 +135,7 @@ pub async fn new_safe_instance_with_dbc_owner(secret_key: &str) -> Result<(Safe,
         None,
         None,
         Some(dbc_owner.clone()),
+        None,
     )
     .await?;
 
@@ -151,7 +146,7 @@ pub async fn new_safe_instance_with_dbc_owner(secret_key: &str) -> Result<(Safe,
 pub async fn new_read_only_safe_instance() -> Result<Safe> {
     init_logger();
     let bootstrap_contacts = get_bootstrap_contacts()?;
-    let safe = Safe::connected(bootstrap_contacts, None, None, None, None, None).await?;
+    let safe = Safe::connected(bootstrap_contacts, None, None, None, None, None, None).await?;
 
     Ok(safe)
 }
@@ -165,7 +160,7 @@ pub fn random_nrs_name() -> String {
         .collect()
 }
 
-fn read_default_peers_from_file() -> Result<(String, BTreeSet<SocketAddr>)> {
+fn read_default_peers_from_file() -> Result<NodeConfig> {
     let default_peer_file = match dirs_next::home_dir() {
         None => bail!(
             "Failed to obtain local home directory where to read {} from",
@@ -177,45 +172,36 @@ fn read_default_peers_from_file() -> Result<(String, BTreeSet<SocketAddr>)> {
         }
     };
 
-    let raw_json = fs::read_to_string(&default_peer_file).with_context(|| {
+    let raw_json = fs::read(&default_peer_file).with_context(|| {
         format!(
             "Failed to read bootstraping contacts list from file: {:?}",
             &default_peer_file
         )
     })?;
 
-    let info: (String, BTreeSet<SocketAddr>) =
-        serde_json::from_str(&raw_json).with_context(|| {
-            format!(
-                "Failed to parse bootstraping contacts list from file: {:?}",
-                &default_peer_file
-            )
-        })?;
+    let info = NodeConfig::from_json(&raw_json).with_context(|| {
+        format!(
+            "Failed to parse bootstraping contacts list from file: {:?}",
+            &default_peer_file
+        )
+    })?;
 
     Ok(info)
 }
 
-fn get_bootstrap_contacts() -> Result<NodeConfig> {
-    let (genesis_key_hex, bootstrap_contacts) = match var(TEST_BOOTSTRAPPING_PEERS) {
-        Ok(val) => serde_json::from_str(&val).with_context(|| {
+pub(crate) fn get_bootstrap_contacts() -> Result<NodeConfig> {
+    match var(TEST_BOOTSTRAPPING_PEERS) {
+        Ok(val) => NodeConfig::from_json(val.as_bytes()).with_context(|| {
             format!(
                 "Failed to parse bootstraping contacts list from {} env var",
                 TEST_BOOTSTRAPPING_PEERS
             )
-        })?,
+        }),
         Err(_) => {
             // read default peers from the file we normally use for peers
-            read_default_peers_from_file()?
+            read_default_peers_from_file()
         }
-    };
-
-    let genesis_key = PublicKey::bls_from_hex(&genesis_key_hex)?
-        .bls()
-        .ok_or_else(|| {
-            anyhow::anyhow!("Unexpectedly failed to obtain network's (BLS) genesis key.")
-        })?;
-
-    Ok((genesis_key, bootstrap_contacts))
+    }
 }
 
 #[macro_export]
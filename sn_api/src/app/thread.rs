@@ -0,0 +1,381 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::register::EntryHash;
+
+use crate::safeurl::{ContentType, SafeUrl, XorUrl};
+use crate::{Error, Result, Safe};
+
+use log::{debug, warn};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sn_client::Client;
+use sn_interface::types::{
+    register::{Policy, PublicPermissions, PublicPolicy, User},
+    PublicKey, Scope, Signature,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use xor_name::XorName;
+
+/// Type tag of the Registers holding discussion threads
+pub(crate) const THREAD_TYPE_TAG: u64 = 1_600;
+
+// How far ahead of our clock the time a post claims to be created at can be, in seconds.
+// Posts claiming a later time are left out, for them not to be sorted before all others.
+const MAX_POST_CLOCK_SKEW_SECS: u64 = 5 * 60;
+
+/// A post appended to a discussion thread, signed by its author
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ThreadPost {
+    /// Public key of the author of the post
+    pub author: PublicKey,
+    /// Content of the post
+    pub content: Vec<u8>,
+    /// Time the post was created at, in seconds since the Unix epoch, as claimed by its author.
+    /// It's only relied on to sort concurrent posts, unless too far ahead of the reader's clock.
+    pub timestamp: u64,
+    /// Posts which were the latest in the thread when this post was appended
+    pub previous: BTreeSet<EntryHash>,
+    /// Author's signature over the thread name and all the fields above
+    pub signature: Signature,
+}
+
+impl ThreadPost {
+    async fn new(
+        client: &Client,
+        thread: XorName,
+        content: Vec<u8>,
+        previous: BTreeSet<EntryHash>,
+    ) -> Result<Self> {
+        let author = client.public_key();
+        let timestamp = now_secs();
+        let bytes = Self::signable_bytes(thread, author, &content, timestamp, &previous)?;
+        let signature = client.sign(&bytes).await?;
+
+        Ok(Self {
+            author,
+            content,
+            timestamp,
+            previous,
+            signature,
+        })
+    }
+
+    /// Verify the post was signed by its author for the given thread
+    pub fn verify(&self, thread: XorName) -> Result<()> {
+        let bytes = Self::signable_bytes(
+            thread,
+            self.author,
+            &self.content,
+            self.timestamp,
+            &self.previous,
+        )?;
+        self.author
+            .verify(&self.signature, bytes)
+            .map_err(|err| Error::InvalidInput(format!("Invalid thread post signature: {}", err)))
+    }
+
+    // Whether the time the post claims to be created at isn't too far ahead of the given time
+    fn is_timestamp_plausible(&self, now: u64) -> bool {
+        self.timestamp <= now.saturating_add(MAX_POST_CLOCK_SKEW_SECS)
+    }
+
+    // The thread name is part of what's signed so a post can't be replayed in another thread
+    fn signable_bytes(
+        thread: XorName,
+        author: PublicKey,
+        content: &[u8],
+        timestamp: u64,
+        previous: &BTreeSet<EntryHash>,
+    ) -> Result<Vec<u8>> {
+        rmp_serde::to_vec(&(thread, author, content, timestamp, previous)).map_err(|err| {
+            Error::Serialisation(format!("Couldn't serialise thread post: {:?}", err))
+        })
+    }
+}
+
+/// A page of posts read from a thread, newest first
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ThreadPage {
+    /// The posts of the page, newest first
+    pub posts: Vec<(EntryHash, ThreadPost)>,
+    /// Cursor to read the next (older) page from, `None` if there are no older posts
+    pub next: Option<BTreeSet<EntryHash>>,
+}
+
+impl Safe {
+    /// Return the URL of the discussion thread for the given topic,
+    /// which is the same for everyone, whether the thread was created or not
+    pub fn thread_url(&self, topic: &str) -> Result<XorUrl> {
+        SafeUrl::encode_register(
+            thread_name(topic),
+            THREAD_TYPE_TAG,
+            Scope::Public,
+            ContentType::Raw,
+            self.xorurl_base,
+        )
+    }
+
+    /// Create the discussion thread for the given topic on the network.
+    /// Anyone is allowed to append posts to it.
+    pub async fn thread_create(&self, topic: &str) -> Result<XorUrl> {
+        debug!("Creating a thread for topic: {}", topic);
+        let xorurl = self.thread_url(topic)?;
+
+        if self.dry_run_mode {
+            return Ok(xorurl);
+        }
+
+        let client = self.get_safe_client()?;
+        let (_, op_batch) = client
+            .create_register(
+                thread_name(topic),
+                THREAD_TYPE_TAG,
+                thread_policy(User::Key(client.public_key())),
+            )
+            .await
+            .map_err(|err| {
+                Error::NetDataError(format!("Failed to prepare thread creation: {:?}", err))
+            })?;
+
+        client
+            .publish_register_ops(op_batch, self.ordered_writes)
+            .await?;
+
+        Ok(xorurl)
+    }
+
+    /// Append a post, signed with our keypair, to the discussion thread at the given URL
+    pub async fn thread_append(&self, url: &str, content: Vec<u8>) -> Result<EntryHash> {
+        debug!("Appending a post to the thread at: {}", url);
+        let safeurl = self.parse_and_resolve_url(url).await?;
+
+        if self.dry_run_mode {
+            return Ok(EntryHash(rand::thread_rng().gen::<[u8; 32]>()));
+        }
+
+        // the new post supersedes whatever the latest posts are,
+        // so reading the thread always starts from the newest post
+        let previous = match self.register_fetch_entries(&safeurl).await {
+            Ok(entries) => entries.into_iter().map(|(hash, _)| hash).collect(),
+            Err(Error::EmptyContent(_)) => BTreeSet::new(),
+            Err(err) => return Err(err),
+        };
+
+        let client = self.get_safe_client()?;
+        let post = ThreadPost::new(client, safeurl.xorname(), content, previous.clone()).await?;
+        let entry = rmp_serde::to_vec_named(&post).map_err(|err| {
+            Error::Serialisation(format!("Couldn't serialise thread post: {:?}", err))
+        })?;
+
+        self.register_write(url, entry, previous).await
+    }
+
+    /// Read a page of at most `page_size` posts from the discussion thread at the given URL,
+    /// newest first. The first page is read when no cursor is provided, the following ones by
+    /// passing the `next` cursor of the previous page.
+    ///
+    /// Posts whose author signature doesn't verify, or claiming to be created too far ahead of
+    /// our clock, are left out, as well as the posts only reachable through them.
+    pub async fn thread_read(
+        &self,
+        url: &str,
+        cursor: Option<BTreeSet<EntryHash>>,
+        page_size: usize,
+    ) -> Result<ThreadPage> {
+        debug!("Reading a page of posts from thread at: {}", url);
+        let safeurl = self.parse_and_resolve_url(url).await?;
+        let thread = safeurl.xorname();
+
+        let start = match cursor {
+            Some(cursor) => cursor,
+            None => match self.register_fetch_entries(&safeurl).await {
+                Ok(entries) => entries.into_iter().map(|(hash, _)| hash).collect(),
+                Err(Error::EmptyContent(_)) => return Ok(ThreadPage::default()),
+                Err(err) => return Err(err),
+            },
+        };
+
+        let mut seen = BTreeSet::new();
+        let mut frontier = BTreeMap::new();
+        for hash in start {
+            self.fetch_verified_post(&safeurl, thread, hash, &mut seen, &mut frontier)
+                .await?;
+        }
+
+        let mut posts = vec![];
+        while posts.len() < page_size {
+            // concurrent posts are sorted by their timestamp
+            let newest = frontier
+                .iter()
+                .max_by_key(|(hash, post)| (post.timestamp, **hash))
+                .map(|(hash, _)| *hash);
+            let (hash, post) = match newest.and_then(|hash| frontier.remove_entry(&hash)) {
+                Some(newest) => newest,
+                None => break,
+            };
+
+            for previous in &post.previous {
+                self.fetch_verified_post(&safeurl, thread, *previous, &mut seen, &mut frontier)
+                    .await?;
+            }
+            posts.push((hash, post));
+        }
+
+        let next = if frontier.is_empty() {
+            None
+        } else {
+            Some(frontier.into_keys().collect())
+        };
+
+        Ok(ThreadPage { posts, next })
+    }
+
+    // Private helper to fetch a post from a thread, adding it to the
+    // frontier only if it hadn't been seen yet and its signature is valid
+    async fn fetch_verified_post(
+        &self,
+        safeurl: &SafeUrl,
+        thread: XorName,
+        hash: EntryHash,
+        seen: &mut BTreeSet<EntryHash>,
+        frontier: &mut BTreeMap<EntryHash, ThreadPost>,
+    ) -> Result<()> {
+        if !seen.insert(hash) {
+            return Ok(());
+        }
+
+        let entry = self.register_fetch_entry(safeurl, hash).await?;
+        let post: ThreadPost = match rmp_serde::from_slice(&entry) {
+            Ok(post) => post,
+            Err(err) => {
+                warn!("Ignoring thread entry which is not a post: {:?}", err);
+                return Ok(());
+            }
+        };
+
+        match post.verify(thread) {
+            Ok(()) if !post.is_timestamp_plausible(now_secs()) => warn!(
+                "Ignoring thread post claiming to be created in the future: {}",
+                post.timestamp
+            ),
+            Ok(()) => {
+                let _ = frontier.insert(hash, post);
+            }
+            Err(err) => warn!("Ignoring thread post: {}", err),
+        }
+
+        Ok(())
+    }
+}
+
+// Name of the Register holding the thread for a topic
+fn thread_name(topic: &str) -> XorName {
+    XorName::from_content(topic.as_bytes())
+}
+
+// Seconds since the Unix epoch, as per our clock
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+fn thread_policy(owner: User) -> Policy {
+    let mut permissions = BTreeMap::new();
+    let _ = permissions.insert(User::Anyone, PublicPermissions::new(true));
+    Policy::Public(PublicPolicy { owner, permissions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app::test_helpers::new_safe_instance, retry_loop_for_pattern};
+    use anyhow::Result;
+
+    #[test]
+    fn test_thread_post_signature() -> Result<()> {
+        let keypair = sn_interface::types::Keypair::new_ed25519();
+        let thread = thread_name("topic");
+        let author = keypair.public_key();
+        let content = b"hello".to_vec();
+        let bytes = ThreadPost::signable_bytes(thread, author, &content, 0, &BTreeSet::new())?;
+        let post = ThreadPost {
+            author,
+            content,
+            timestamp: 0,
+            previous: BTreeSet::new(),
+            signature: keypair.sign(&bytes),
+        };
+
+        assert!(post.verify(thread).is_ok());
+        // a post can't be replayed in another thread
+        assert!(post.verify(thread_name("another topic")).is_err());
+
+        let mut tampered = post;
+        tampered.content = b"goodbye".to_vec();
+        assert!(tampered.verify(thread).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_thread_post_timestamp_bound() -> Result<()> {
+        let keypair = sn_interface::types::Keypair::new_ed25519();
+        let thread = thread_name("topic");
+        let now = now_secs();
+        let post = |timestamp| -> Result<ThreadPost> {
+            let author = keypair.public_key();
+            let bytes =
+                ThreadPost::signable_bytes(thread, author, &[], timestamp, &BTreeSet::new())?;
+            Ok(ThreadPost {
+                author,
+                content: vec![],
+                timestamp,
+                previous: BTreeSet::new(),
+                signature: keypair.sign(&bytes),
+            })
+        };
+
+        assert!(post(0)?.is_timestamp_plausible(now));
+        assert!(post(now + MAX_POST_CLOCK_SKEW_SECS)?.is_timestamp_plausible(now));
+        // validly signed, but claiming to be created too far ahead of our clock
+        let from_future = post(now + MAX_POST_CLOCK_SKEW_SECS + 1)?;
+        assert!(from_future.verify(thread).is_ok());
+        assert!(!from_future.is_timestamp_plausible(now));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_thread_append_and_read() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let topic = format!("topic-{}", xor_name::rand::random::<XorName>());
+
+        let xorurl = safe.thread_create(&topic).await?;
+        assert_eq!(xorurl, safe.thread_url(&topic)?);
+
+        let first = safe.thread_append(&xorurl, b"first".to_vec()).await?;
+        let _ = retry_loop_for_pattern!(safe.thread_read(&xorurl, None, 1), Ok(page) if page.posts.len() == 1)?;
+        let second = safe.thread_append(&xorurl, b"second".to_vec()).await?;
+
+        let page = retry_loop_for_pattern!(safe.thread_read(&xorurl, None, 1), Ok(page) if page.posts[0].0 == second)?;
+        assert_eq!(page.posts[0].1.content, b"second".to_vec());
+
+        let page = safe.thread_read(&xorurl, page.next, 10).await?;
+        assert_eq!(page.posts.len(), 1);
+        assert_eq!(page.posts[0].0, first);
+        assert_eq!(page.next, None);
+
+        Ok(())
+    }
+}
@@ -13,18 +13,77 @@ use super::register::EntryHash;
 use crate::safeurl::{ContentType, SafeUrl, XorUrl};
 use crate::{Error, Result, Safe};
 use bytes::Bytes;
+use futures::future::join_all;
 use log::{debug, warn};
-use sn_dbc::{rng, Owner, OwnerOnce, TransactionBuilder};
-use sn_interface::types::Token;
-use std::collections::{BTreeMap, BTreeSet};
+use sn_client::Client;
+use sn_dbc::{
+    rng, DbcBuilder, Hash, Owner, OwnerOnce, SpentProof, SpentProofShare, TransactionBuilder,
+};
+use sn_interface::messaging::data::{store_cost, DataPayment};
+use sn_interface::types::{Chunk, Scope, Token};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
 
 // Type tag used for the Wallet
 const WALLET_TYPE_TAG: u64 = 1_000;
+// Number of times storing a paid chunk is attempted before giving up
+const PAID_STORE_ATTEMPTS: usize = 3;
 
 /// Set of spendable DBC's mapped to their friendly name
 /// as defined/chosen by the user when depositing DBC's into a Wallet.
 pub type WalletSpendableDbcs = BTreeMap<String, (Dbc, EntryHash)>;
 
+/// Outcome of the check of one of the spent proofs a DBC carries for the inputs of its transaction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpentProofStatus {
+    /// Signed by the current key of one of the sections we know of
+    SignedByCurrentSection,
+    /// Signed by a key one of the sections we know of had before
+    SignedByPastSection,
+    /// Signed by a key which is not known to be a key of any section of the network
+    UnknownSpentbook,
+    /// The signature doesn't match the content of the proof
+    InvalidSignature,
+    /// The proof is for another transaction than the one of the DBC
+    OtherTransaction,
+}
+
+impl SpentProofStatus {
+    /// Whether the proof was validly signed by a section of the network, for the DBC.
+    pub fn is_valid(&self) -> bool {
+        matches!(
+            self,
+            Self::SignedByCurrentSection | Self::SignedByPastSection
+        )
+    }
+}
+
+/// Report of the verification of a DBC by `Safe::verify_dbc`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DbcVerification {
+    /// Amount of the DBC, if it's a bearer one, i.e. its amount can be revealed
+    pub amount: Option<Token>,
+    /// Status of each of the spent proofs the DBC carries
+    pub spent_proofs: Vec<SpentProofStatus>,
+    /// Number of inputs of the DBC's transaction which no spent proof was found for
+    pub inputs_without_proof: usize,
+    /// Whether the DBC itself was spent already, if it's a bearer one, i.e. it can be told
+    pub already_spent: Option<bool>,
+}
+
+impl DbcVerification {
+    /// Whether all the inputs of the DBC were proven spent by the network, and the DBC itself
+    /// wasn't spent already, i.e. whether it can be accepted as a payment.
+    pub fn is_valid(&self) -> bool {
+        !self.spent_proofs.is_empty()
+            && self.spent_proofs.iter().all(SpentProofStatus::is_valid)
+            && self.inputs_without_proof == 0
+            && self.already_spent != Some(true)
+    }
+}
+
 impl Safe {
     /// Create an empty wallet and return its XOR-URL.
     pub async fn wallet_create(&self) -> Result<XorUrl> {
@@ -53,8 +112,8 @@ impl Safe {
         }
 
         // TODO: check the input DBCs were spent and all other sort of verifications,
-        // perhaps all optional, and we may want a separate API to also do these verifications
-        // for the user to perform them without depositing the DBC into a wallet.
+        // perhaps all optional, as `verify_dbc` does for the user to perform them without
+        // depositing the DBC into a wallet.
 
         let safeurl = self.parse_and_resolve_url(wallet_url).await?;
 
@@ -74,6 +133,53 @@ impl Safe {
         Ok(spendable_name)
     }
 
+    /// Verify a DBC received from a third party, e.g. as a payment, before accepting it.
+    ///
+    /// The spent proofs it carries are checked against the keys of the sections we know of,
+    /// their current ones as well as the ones they had before, and, for bearer DBCs, the
+    /// spentbook is checked for the DBC itself having been spent already.
+    pub async fn verify_dbc(&self, dbc: &Dbc) -> Result<DbcVerification> {
+        let client = self.get_safe_client()?;
+
+        let tx_hash = Hash::from(dbc.transaction.hash());
+        let mut spent_proofs = Vec::new();
+        let mut proven_inputs = BTreeSet::new();
+        for spent_proof in &dbc.spent_proofs {
+            let status = spent_proof_status(client, spent_proof, &tx_hash).await;
+            if status.is_valid() {
+                let _ = proven_inputs.insert(spent_proof.content.key_image.to_bytes().to_vec());
+            }
+            spent_proofs.push(status);
+        }
+        let inputs_without_proof = dbc
+            .transaction
+            .mlsags
+            .len()
+            .saturating_sub(proven_inputs.len());
+
+        let (amount, already_spent) = if dbc.is_bearer() {
+            let amount = Token::from_nano(dbc.amount_secrets_bearer()?.amount());
+            let spent_proof_shares = client.spent_proof_shares(dbc.key_image_bearer()?).await?;
+            (Some(amount), Some(!spent_proof_shares.is_empty()))
+        } else {
+            (None, None)
+        };
+
+        let verification = DbcVerification {
+            amount,
+            spent_proofs,
+            inputs_without_proof,
+            already_spent,
+        };
+        debug!(
+            "DBC {} verified: {:?}",
+            hex::encode(dbc.hash()),
+            verification
+        );
+
+        Ok(verification)
+    }
+
     /// Fetch a wallet from a Url performing all type of URL resolution required.
     /// Return the set of spendable DBCs found in the wallet.
     pub async fn wallet_get(&self, wallet_url: &str) -> Result<WalletSpendableDbcs> {
@@ -193,64 +299,254 @@ impl Safe {
         }
 
         let safeurl = self.parse_and_resolve_url(wallet_url).await?;
-        let spendable_dbcs = self.fetch_wallet(&safeurl).await?;
+        let mut output_dbcs = self
+            .reissue_from_wallet(&safeurl, vec![output_amount], owner_public_key)
+            .await?;
 
-        // We'll combine one or more input DBCs and reissue:
-        // - one output DBC for the recipient,
-        // - and a second DBC for the change, which will be stored in the source Wallet.
-        let mut input_dbcs_to_spend = Vec::<Dbc>::new();
-        let mut input_dbcs_entries_hash = BTreeSet::<EntryHash>::new();
-        let mut total_input_amount = 0;
-        let mut change_amount = output_amount;
-        for (name, (dbc, entry_hash)) in spendable_dbcs.into_iter() {
-            let dbc_balance = match dbc.amount_secrets_bearer() {
-                Ok(amount_secrets) => Token::from_nano(amount_secrets.amount()),
-                Err(err) => {
-                    warn!("Ignoring input DBC found in Wallet (entry: {}) due to error in revealing secret amount: {:?}", name, err);
-                    continue;
-                }
-            };
+        output_dbcs.pop().ok_or_else(|| {
+            Error::DbcReissueError(
+                "Unexpectedly failed to generate output DBC. No balance were spent from the wallet.".to_string(),
+            )
+        })
+    }
 
-            // Add this DBC as input to be spent
-            input_dbcs_to_spend.push(dbc);
-            input_dbcs_entries_hash.insert(entry_hash);
-            total_input_amount += dbc_balance.as_nano();
-
-            // If we've already combined input DBCs for the total output amount, then stop
-            match change_amount.checked_sub(dbc_balance) {
-                Some(pending_output) => {
-                    change_amount = pending_output;
-                    if change_amount.as_nano() == 0 {
-                        break;
+    /// Store data on the network as a public file, paying for it with DBCs
+    /// reissued from the wallet at the given URL. Return the XOR-URL of the file.
+    ///
+    /// The data is chunked beforehand so the cost of storing each chunk can be calculated,
+    /// and each chunk is then sent along with a DBC paying for it. If there is change from
+    /// the payments, the change DBC will be deposited in the wallet.
+    ///
+    /// Chunks which fail to be stored are retried with their same payment. The payments of the
+    /// chunks still not stored after all the attempts are deposited back in the wallet.
+    pub async fn pay_and_store(&self, data: Bytes, wallet_url: &str) -> Result<XorUrl> {
+        if self.dry_run_mode {
+            let address = Client::calculate_address(data, Scope::Public)?;
+            return SafeUrl::encode_bytes(address, ContentType::Raw, self.xorurl_base);
+        }
+
+        let client = self.get_safe_client()?;
+        let (address, chunks) = client.chunk_bytes(data, Scope::Public)?;
+        let xorurl = SafeUrl::encode_bytes(address, ContentType::Raw, self.xorurl_base)?;
+
+        let costs: Vec<Token> = chunks
+            .iter()
+            .map(|chunk| store_cost(chunk.value().len()))
+            .collect();
+        debug!(
+            "Paying for {} chunks from wallet at {}: {:?}",
+            chunks.len(),
+            wallet_url,
+            costs
+        );
+
+        let safeurl = self.parse_and_resolve_url(wallet_url).await?;
+        let payments = self.reissue_from_wallet(&safeurl, costs, None).await?;
+
+        // each payment is bound to the chunk it pays for
+        let mut pending: Vec<(Chunk, DataPayment)> = chunks
+            .into_iter()
+            .zip(payments)
+            .map(|(chunk, dbc)| {
+                let payment = DataPayment::new(dbc, *chunk.name(), chunk.value().len())?;
+                Ok((chunk, payment))
+            })
+            .collect::<Result<_>>()?;
+
+        for attempt in 1..=PAID_STORE_ATTEMPTS {
+            let results = join_all(
+                pending
+                    .iter()
+                    .cloned()
+                    .map(|(chunk, payment)| client.store_paid_chunk(chunk, payment)),
+            )
+            .await;
+
+            pending = pending
+                .into_iter()
+                .zip(results)
+                .filter_map(|((chunk, payment), result)| match result {
+                    Ok(_receipt) => None,
+                    Err(err) => {
+                        warn!(
+                            "Failed to store paid chunk {:?} (attempt #{}): {:?}",
+                            chunk.name(),
+                            attempt,
+                            err
+                        );
+                        Some((chunk, payment))
                     }
-                }
-                None => {
-                    change_amount =
-                        Token::from_nano(dbc_balance.as_nano() - change_amount.as_nano());
-                    break;
-                }
+                })
+                .collect();
+
+            if pending.is_empty() {
+                return Ok(xorurl);
             }
         }
 
-        // Make sure total input amount gathered with input DBCs are enough for the output amount
-        if total_input_amount < output_amount.as_nano() {
-            return Err(Error::NotEnoughBalance(
-                Token::from_nano(total_input_amount).to_string(),
+        // The DBCs paying for the chunks which couldn't be stored are not lost
+        for (chunk, payment) in &pending {
+            let spendable_name = format!("unused-payment-{}", hex::encode(chunk.name().0));
+            self.insert_dbc_into_wallet(&safeurl, &payment.dbc, spendable_name)
+                .await?;
+        }
+
+        Err(Error::NetDataError(format!(
+            "Failed to store {} of the chunks after {} attempts, their payments were deposited back in the wallet",
+            pending.len(),
+            PAID_STORE_ATTEMPTS
+        )))
+    }
+
+    /// Prepare a batch of transfers out of a wallet, one for each of the recipients, given as the
+    /// amount to transfer and the public key to own the output DBC, or `None` for a bearer one.
+    ///
+    /// The DBCs to spend are selected from the current content of the wallet, and the transaction
+    /// spending them is signed, but nothing is spent until the batch is submitted with
+    /// [`Safe::wallet_submit_transfer_batch`].
+    pub async fn wallet_prepare_transfer_batch(
+        &self,
+        wallet_url: &str,
+        recipients: &[(&str, Option<bls::PublicKey>)],
+    ) -> Result<TransferBatch> {
+        debug!(
+            "Preparing a batch of {} transfers from wallet at {}",
+            recipients.len(),
+            wallet_url
+        );
+        if recipients.is_empty() {
+            return Err(Error::InvalidInput(
+                "A transfer batch needs at least one recipient".to_string(),
             ));
         }
 
+        let mut outputs = Vec::with_capacity(recipients.len());
+        for (amount, public_key) in recipients {
+            let amount = parse_tokens_amount(amount)?;
+            if amount.as_nano() == 0 {
+                return Err(Error::InvalidAmount(
+                    "Amount to transfer needs to be larger than zero (0).".to_string(),
+                ));
+            }
+            outputs.push((amount, *public_key));
+        }
+        let amounts: Vec<Token> = outputs.iter().map(|(amount, _)| *amount).collect();
+        let output_amount = total_amount(&amounts)?;
+
+        let safeurl = self.parse_and_resolve_url(wallet_url).await?;
+        let spendable_dbcs = self.fetch_wallet(&safeurl).await?;
+        let (input_dbcs, input_entries_hash, change_amount) =
+            select_inputs(spendable_dbcs, output_amount)?;
+        let reissue = self.build_reissue(&input_dbcs, &outputs, change_amount)?;
+
+        Ok(TransferBatch {
+            wallet: safeurl,
+            recipients: outputs,
+            input_entries_hash,
+            change_amount,
+            state: TransferBatchState::Prepared(reissue),
+        })
+    }
+
+    /// Submit a batch of transfers prepared with [`Safe::wallet_prepare_transfer_batch`],
+    /// returning the output DBCs, in the same order as the recipients of the batch.
+    ///
+    /// If there is change from the transaction, the change DBC is deposited in the wallet, and
+    /// the spent DBCs are marked as removed from it.
+    ///
+    /// If the submission fails, it can be retried with the same batch. It fails without spending
+    /// anything if the DBCs the batch spends were removed from the wallet since it was prepared.
+    pub async fn wallet_submit_transfer_batch(
+        &self,
+        batch: &mut TransferBatch,
+    ) -> Result<Vec<Dbc>> {
+        if let TransferBatchState::Prepared(reissue) = &batch.state {
+            let present: BTreeSet<EntryHash> = self
+                .fetch_multimap(&batch.wallet)
+                .await?
+                .into_iter()
+                .map(|(hash, _)| hash)
+                .collect();
+            if !batch.input_entries_hash.is_subset(&present) {
+                return Err(Error::InvalidInput(format!(
+                    "The DBCs to spend are not in the wallet at {} anymore, the transfer batch needs to be prepared again",
+                    batch.wallet
+                )));
+            }
+
+            let spent_proof_shares = self.spend_reissue_inputs(reissue).await?;
+            // the batch stays prepared if the DBCs fail to be built, for it to be retried
+            let (output_dbcs, change_dbc) = reissue.clone().into_dbcs(spent_proof_shares)?;
+            batch.state = TransferBatchState::Reissued {
+                output_dbcs,
+                change_dbc,
+            };
+        }
+
+        if let TransferBatchState::Reissued {
+            output_dbcs,
+            change_dbc,
+        } = &batch.state
+        {
+            if let Some(change_dbc) = change_dbc {
+                self.insert_dbc_into_wallet(&batch.wallet, change_dbc, "change-dbc".to_string())
+                    .await?;
+            }
+            batch.state = TransferBatchState::ChangeDeposited(output_dbcs.clone());
+        }
+
+        if let TransferBatchState::ChangeDeposited(output_dbcs) = &batch.state {
+            self.multimap_remove(&batch.wallet.to_string(), batch.input_entries_hash.clone())
+                .await?;
+            batch.state = TransferBatchState::Completed(output_dbcs.clone());
+        }
+
+        match &batch.state {
+            TransferBatchState::Completed(output_dbcs) => {
+                debug!(
+                    "Batch of {} transfers from wallet at {} submitted",
+                    output_dbcs.len(),
+                    batch.wallet
+                );
+                Ok(output_dbcs.clone())
+            }
+            _ => Err(Error::DbcReissueError(
+                "Unexpectedly failed to build the output DBCs of the transfer batch".to_string(),
+            )),
+        }
+    }
+
+    ///
+    /// Private helpers
+    ///
+
+    /// Reissue DBCs for each of the output amounts from the DBCs of a wallet, returning the
+    /// output DBCs in the same order as the amounts. The change DBC, if any, is deposited in
+    /// the wallet, and the spent DBCs are marked as removed from it.
+    async fn reissue_from_wallet(
+        &self,
+        safeurl: &SafeUrl,
+        output_amounts: Vec<Token>,
+        owner_public_key: Option<bls::PublicKey>,
+    ) -> Result<Vec<Dbc>> {
+        let output_amount = total_amount(&output_amounts)?;
+
+        let spendable_dbcs = self.fetch_wallet(safeurl).await?;
+        let (input_dbcs_to_spend, input_dbcs_entries_hash, change_amount) =
+            select_inputs(spendable_dbcs, output_amount)?;
+
         // We can now reissue the output DBCs
-        let (output_dbc, change_dbc) = self
+        let (output_dbcs, change_dbc) = self
             .reissue_dbcs(
                 input_dbcs_to_spend,
-                output_amount,
+                output_amounts,
                 change_amount,
                 owner_public_key,
             )
             .await?;
 
         if let Some(change_dbc) = change_dbc {
-            self.insert_dbc_into_wallet(&safeurl, &change_dbc, "change-dbc".to_string())
+            self.insert_dbc_into_wallet(safeurl, &change_dbc, "change-dbc".to_string())
                 .await?;
         }
 
@@ -258,13 +554,9 @@ impl Safe {
         self.multimap_remove(&safeurl.to_string(), input_dbcs_entries_hash)
             .await?;
 
-        Ok(output_dbc)
+        Ok(output_dbcs)
     }
 
-    ///
-    /// Private helpers
-    ///
-
     /// Insert a DBC into the wallet's underlying `Multimap`.
     async fn insert_dbc_into_wallet(
         &self,
@@ -293,75 +585,272 @@ impl Safe {
         Ok(())
     }
 
-    /// Reissue DBCs and log the spent input DBCs on the network. Return the output DBC and the
-    /// change DBC if there is one.
+    /// Reissue DBCs and log the spent input DBCs on the network. Return the output DBCs,
+    /// in the same order as the output amounts, and the change DBC if there is one.
     async fn reissue_dbcs(
         &self,
         input_dbcs: Vec<Dbc>,
-        output_amount: Token,
+        output_amounts: Vec<Token>,
         change_amount: Token,
         public_key: Option<bls::PublicKey>,
-    ) -> Result<(Dbc, Option<Dbc>)> {
-        let output_owner = if let Some(pk) = public_key {
-            let owner = Owner::from(pk);
-            OwnerOnce::from_owner_base(owner, &mut rng::thread_rng())
-        } else {
-            let owner = Owner::from_random_secret_key(&mut rng::thread_rng());
-            OwnerOnce::from_owner_base(owner, &mut rng::thread_rng())
-        };
+    ) -> Result<(Vec<Dbc>, Option<Dbc>)> {
+        let outputs: Vec<_> = output_amounts
+            .into_iter()
+            .map(|amount| (amount, public_key))
+            .collect();
+        let reissue = self.build_reissue(&input_dbcs, &outputs, change_amount)?;
+        let spent_proof_shares = self.spend_reissue_inputs(&reissue).await?;
+        reissue.into_dbcs(spent_proof_shares)
+    }
 
+    /// Build the transaction reissuing the input DBCs into DBCs of the given amounts, each
+    /// owned by the given public key or a bearer one, and a change DBC if `change_amount` is
+    /// not zero.
+    fn build_reissue(
+        &self,
+        input_dbcs: &[Dbc],
+        outputs: &[(Token, Option<bls::PublicKey>)],
+        change_amount: Token,
+    ) -> Result<Reissue> {
         // TODO: enable the use ot decoys
         let mut tx_builder = TransactionBuilder::default()
             .set_decoys_per_input(0)
             .set_require_all_decoys(false)
-            .add_inputs_dbc_bearer(input_dbcs.iter())?
-            .add_output_by_amount(output_amount.as_nano(), output_owner);
+            .add_inputs_dbc_bearer(input_dbcs.iter())?;
+
+        let mut output_owners = Vec::with_capacity(outputs.len());
+        for (output_amount, public_key) in outputs {
+            let output_owner = if let Some(pk) = public_key {
+                let owner = Owner::from(*pk);
+                OwnerOnce::from_owner_base(owner, &mut rng::thread_rng())
+            } else {
+                let owner = Owner::from_random_secret_key(&mut rng::thread_rng());
+                OwnerOnce::from_owner_base(owner, &mut rng::thread_rng())
+            };
+            tx_builder =
+                tx_builder.add_output_by_amount(output_amount.as_nano(), output_owner.clone());
+            output_owners.push(output_owner);
+        }
 
         let client = self.get_safe_client()?;
-        let change_owneronce =
-            OwnerOnce::from_owner_base(client.dbc_owner(), &mut rng::thread_rng());
-        if change_amount.as_nano() > 0 {
+        let change_owner = if change_amount.as_nano() > 0 {
+            let change_owneronce =
+                OwnerOnce::from_owner_base(client.dbc_owner(), &mut rng::thread_rng());
             tx_builder =
                 tx_builder.add_output_by_amount(change_amount.as_nano(), change_owneronce.clone());
-        }
+            Some(change_owneronce)
+        } else {
+            None
+        };
 
-        let mut dbc_builder = tx_builder.build(&mut rng::thread_rng())?;
+        let dbc_builder = tx_builder.build(&mut rng::thread_rng())?;
 
-        // Build the output DBCs
-        // Spend all the input DBCs, collecting the spent proof shares for each of them
+        Ok(Reissue {
+            dbc_builder,
+            output_owners,
+            change_owner,
+        })
+    }
+
+    /// Spend all the input DBCs of a reissue, collecting the spent proof shares for each of them.
+    /// Spending them again with the same transaction, e.g. to retry after a failure, is harmless.
+    async fn spend_reissue_inputs(&self, reissue: &Reissue) -> Result<Vec<SpentProofShare>> {
         let client = self.get_safe_client()?;
-        for (keyimage, tx) in dbc_builder.inputs() {
+        let mut spent_proof_shares = Vec::new();
+        for (keyimage, tx) in reissue.dbc_builder.inputs() {
             // TODO: spend DBCs in parallel spawning tasks
             client.spend_dbc(keyimage, tx).await?;
-            let spent_proof_shares = client.spent_proof_shares(keyimage).await?;
-            // The `add_spent_proof_share` function already updated the `dbc_builder` object.
-            // No need to update the object outside manually.
-            dbc_builder = dbc_builder.add_spent_proof_shares(spent_proof_shares.into_iter());
+            spent_proof_shares.extend(client.spent_proof_shares(keyimage).await?);
         }
 
+        Ok(spent_proof_shares)
+    }
+}
+
+/// A batch of transfers out of a wallet, e.g. for payroll-style payouts, prepared by
+/// [`Safe::wallet_prepare_transfer_batch`] and submitted with
+/// [`Safe::wallet_submit_transfer_batch`].
+///
+/// All the transfers are part of a single transaction, signed when the batch is prepared and
+/// spending DBCs of one snapshot of the wallet, so either all of them are made or none is.
+/// A batch can be submitted again if it failed to, and once it succeeded, submitting it again
+/// returns the same output DBCs without spending anything else.
+pub struct TransferBatch {
+    wallet: SafeUrl,
+    recipients: Vec<(Token, Option<bls::PublicKey>)>,
+    input_entries_hash: BTreeSet<EntryHash>,
+    change_amount: Token,
+    state: TransferBatchState,
+}
+
+enum TransferBatchState {
+    Prepared(Reissue),
+    Reissued {
+        output_dbcs: Vec<Dbc>,
+        change_dbc: Option<Dbc>,
+    },
+    ChangeDeposited(Vec<Dbc>),
+    Completed(Vec<Dbc>),
+}
+
+impl TransferBatch {
+    /// The amounts and owners of the transfers, in the order they were given
+    pub fn recipients(&self) -> &[(Token, Option<bls::PublicKey>)] {
+        &self.recipients
+    }
+
+    /// The change deposited back in the wallet once the batch is submitted
+    pub fn change_amount(&self) -> Token {
+        self.change_amount
+    }
+
+    /// Whether the batch was successfully submitted
+    pub fn is_completed(&self) -> bool {
+        matches!(self.state, TransferBatchState::Completed(_))
+    }
+}
+
+impl fmt::Debug for TransferBatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TransferBatch")
+            .field("wallet", &self.wallet.to_string())
+            .field("recipients", &self.recipients)
+            .field("inputs", &self.input_entries_hash.len())
+            .field("change_amount", &self.change_amount)
+            .field("completed", &self.is_completed())
+            .finish()
+    }
+}
+
+// A transaction reissuing DBCs, signed by the owners of its inputs
+#[derive(Clone)]
+struct Reissue {
+    dbc_builder: DbcBuilder,
+    output_owners: Vec<OwnerOnce>,
+    change_owner: Option<OwnerOnce>,
+}
+
+impl Reissue {
+    // Build the output DBCs out of the spent proof shares of all the inputs. Return them in the
+    // same order as the outputs of the transaction, and the change DBC if there is one.
+    fn into_dbcs(
+        self,
+        spent_proof_shares: Vec<SpentProofShare>,
+    ) -> Result<(Vec<Dbc>, Option<Dbc>)> {
+        let dbc_builder = self
+            .dbc_builder
+            .add_spent_proof_shares(spent_proof_shares.into_iter());
+
         // TODO: perform the verification of the transaction and spentproofs before building DBCs.
         // This will be possible once sn_client APIs return a super-majority of spent proof shares.
         let dbcs = dbc_builder.build_without_verifying()?;
 
-        let mut output_dbc = None;
+        let mut output_dbcs = BTreeMap::new();
         let mut change_dbc = None;
         for (dbc, owneronce, _) in dbcs {
-            if change_owneronce == owneronce && change_amount.as_nano() > 0 {
+            if self.change_owner.as_ref() == Some(&owneronce) {
                 change_dbc = Some(dbc);
-            } else {
-                output_dbc = Some(dbc);
+            } else if let Some(index) = self
+                .output_owners
+                .iter()
+                .position(|owner| *owner == owneronce)
+            {
+                let _ = output_dbcs.insert(index, dbc);
             }
         }
 
-        match output_dbc {
-            None => Err(Error::DbcReissueError(
+        if output_dbcs.len() != self.output_owners.len() {
+            return Err(Error::DbcReissueError(
                 "Unexpectedly failed to generate output DBC. No balance were spent from the wallet.".to_string(),
-            )),
-            Some(dbc) => Ok((dbc, change_dbc)),
+            ));
         }
+
+        Ok((output_dbcs.into_values().collect(), change_dbc))
     }
 }
 
+// Check a spent proof is for the given transaction, and validly signed by a section we know of
+async fn spent_proof_status(
+    client: &Client,
+    spent_proof: &SpentProof,
+    tx_hash: &Hash,
+) -> SpentProofStatus {
+    if &spent_proof.content.transaction_hash != tx_hash {
+        return SpentProofStatus::OtherTransaction;
+    }
+
+    let spentbook_pk = &spent_proof.spentbook_pub_key;
+    if !spentbook_pk.verify(&spent_proof.spentbook_sig, spent_proof.content.hash()) {
+        SpentProofStatus::InvalidSignature
+    } else if client.is_current_section_key(spentbook_pk) {
+        SpentProofStatus::SignedByCurrentSection
+    } else if client.is_known_section_key(spentbook_pk).await {
+        SpentProofStatus::SignedByPastSection
+    } else {
+        SpentProofStatus::UnknownSpentbook
+    }
+}
+
+// Sum of the amounts, failing if it overflows
+fn total_amount(amounts: &[Token]) -> Result<Token> {
+    amounts
+        .iter()
+        .try_fold(Token::zero(), |total, amount| total.checked_add(*amount))
+        .ok_or_else(|| Error::InvalidAmount("Total output amount to reissue overflows".to_string()))
+}
+
+// Select DBCs of the wallet to spend for the output amount. Return them along with the
+// entries they are at in the wallet, and the change left over from spending them.
+fn select_inputs(
+    spendable_dbcs: WalletSpendableDbcs,
+    output_amount: Token,
+) -> Result<(Vec<Dbc>, BTreeSet<EntryHash>, Token)> {
+    // We'll combine one or more input DBCs and reissue:
+    // - one output DBC for each of the output amounts,
+    // - and another DBC for the change, which will be stored in the source Wallet.
+    let mut input_dbcs_to_spend = Vec::<Dbc>::new();
+    let mut input_dbcs_entries_hash = BTreeSet::<EntryHash>::new();
+    let mut total_input_amount = 0;
+    let mut change_amount = output_amount;
+    for (name, (dbc, entry_hash)) in spendable_dbcs.into_iter() {
+        let dbc_balance = match dbc.amount_secrets_bearer() {
+            Ok(amount_secrets) => Token::from_nano(amount_secrets.amount()),
+            Err(err) => {
+                warn!("Ignoring input DBC found in Wallet (entry: {}) due to error in revealing secret amount: {:?}", name, err);
+                continue;
+            }
+        };
+
+        // Add this DBC as input to be spent
+        input_dbcs_to_spend.push(dbc);
+        input_dbcs_entries_hash.insert(entry_hash);
+        total_input_amount += dbc_balance.as_nano();
+
+        // If we've already combined input DBCs for the total output amount, then stop
+        match change_amount.checked_sub(dbc_balance) {
+            Some(pending_output) => {
+                change_amount = pending_output;
+                if change_amount.as_nano() == 0 {
+                    break;
+                }
+            }
+            None => {
+                change_amount = Token::from_nano(dbc_balance.as_nano() - change_amount.as_nano());
+                break;
+            }
+        }
+    }
+
+    // Make sure total input amount gathered with input DBCs are enough for the output amount
+    if total_input_amount < output_amount.as_nano() {
+        return Err(Error::NotEnoughBalance(
+            Token::from_nano(total_input_amount).to_string(),
+        ));
+    }
+
+    Ok((input_dbcs_to_spend, input_dbcs_entries_hash, change_amount))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -370,6 +859,7 @@ mod tests {
     };
     use anyhow::{anyhow, Result};
     use sn_dbc::Owner;
+    use sn_interface::messaging::data::STORE_COST_PER_KIB;
 
     // TODO: allow to set an amount and SK to generate a DBC with,
     // instead of deserialising a hard-coded serialised DBC.
@@ -687,6 +1177,98 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_wallet_pay_and_store() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let wallet_xorurl = safe.wallet_create().await?;
+
+        let dbc = new_dbc(DBC_WITH_1_530_000_000)?;
+        safe.wallet_deposit(&wallet_xorurl, Some("deposited-dbc"), &dbc)
+            .await?;
+
+        let data = Bytes::from_static(b"Something worth paying for");
+        let xorurl = safe.pay_and_store(data.clone(), &wallet_xorurl).await?;
+
+        let retrieved = safe.files_get(&xorurl, None).await?;
+        assert_eq!(retrieved, data);
+
+        // a small file fits in a single chunk, charged for its first KiB
+        let current_balance = safe.wallet_balance(&wallet_xorurl).await?;
+        assert_eq!(
+            current_balance,
+            Token::from_nano(1_530_000_000 - STORE_COST_PER_KIB)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_wallet_payment_is_bound_to_the_data_paid_for() -> Result<()> {
+        let dbc = new_dbc(DBC_WITH_1_530_000_000)?;
+        let name = xor_name::rand::random();
+        let payment = DataPayment::new(dbc, name, 1024)?;
+        assert!(payment.is_bound_to(&name, 1024));
+
+        // the payment can't be presented for other data, nor for more of the same data
+        assert!(!payment.is_bound_to(&xor_name::rand::random(), 1024));
+        assert!(!payment.is_bound_to(&name, 1025));
+
+        // nor can a DBC be attached to the binding made with another one
+        let other_payment = DataPayment::new(new_dbc(DBC_WITH_12_230_000_000)?, name, 1024)?;
+        let unbound = DataPayment {
+            dbc: other_payment.dbc,
+            binding_sig: payment.binding_sig,
+        };
+        assert!(!unbound.is_bound_to(&name, 1024));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wallet_transfer_batch() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let wallet_xorurl = safe.wallet_create().await?;
+
+        let dbc = new_dbc(DBC_WITH_1_530_000_000)?;
+        safe.wallet_deposit(&wallet_xorurl, Some("deposited-dbc-1"), &dbc)
+            .await?;
+        let dbc = new_dbc(DBC_WITH_12_230_000_000)?;
+        safe.wallet_deposit(&wallet_xorurl, Some("deposited-dbc-2"), &dbc)
+            .await?;
+
+        let pk = bls::SecretKey::random().public_key();
+        let mut batch = safe
+            .wallet_prepare_transfer_batch(&wallet_xorurl, &[("2.35", None), ("1", Some(pk))])
+            .await?;
+        assert_eq!(batch.change_amount(), Token::from_nano(10_410_000_000));
+        // nothing is spent until the batch is submitted
+        let current_balance = safe.wallet_balance(&wallet_xorurl).await?;
+        assert_eq!(current_balance, Token::from_nano(13_760_000_000));
+
+        let output_dbcs = safe.wallet_submit_transfer_batch(&mut batch).await?;
+        assert!(batch.is_completed());
+        assert_eq!(output_dbcs.len(), 2);
+        let output_balance = output_dbcs[0]
+            .amount_secrets_bearer()
+            .map_err(|err| anyhow!("Couldn't read balance from output DBC: {:?}", err))?;
+        assert_eq!(output_balance.amount(), 2_350_000_000);
+        assert_eq!(*output_dbcs[1].owner_base(), Owner::from(pk));
+
+        let current_balance = safe.wallet_balance(&wallet_xorurl).await?;
+        assert_eq!(current_balance, Token::from_nano(10_410_000_000));
+
+        // submitting it again doesn't spend anything else
+        let resubmitted = safe.wallet_submit_transfer_batch(&mut batch).await?;
+        assert_eq!(
+            resubmitted.iter().map(Dbc::hash).collect::<Vec<_>>(),
+            output_dbcs.iter().map(Dbc::hash).collect::<Vec<_>>()
+        );
+        let current_balance = safe.wallet_balance(&wallet_xorurl).await?;
+        assert_eq!(current_balance, Token::from_nano(10_410_000_000));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_wallet_reissue_invalid_amount() -> Result<()> {
         let safe = new_safe_instance().await?;
@@ -774,6 +1356,79 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_dbc_verification_validity() {
+        let verification = DbcVerification {
+            amount: Some(Token::from_nano(1)),
+            spent_proofs: vec![
+                SpentProofStatus::SignedByCurrentSection,
+                SpentProofStatus::SignedByPastSection,
+            ],
+            inputs_without_proof: 0,
+            already_spent: Some(false),
+        };
+        assert!(verification.is_valid());
+
+        // the spent status of owned DBCs can't be told
+        let owned = DbcVerification {
+            amount: None,
+            already_spent: None,
+            ..verification.clone()
+        };
+        assert!(owned.is_valid());
+
+        for invalid in [
+            DbcVerification {
+                spent_proofs: vec![],
+                ..verification.clone()
+            },
+            DbcVerification {
+                spent_proofs: vec![
+                    SpentProofStatus::SignedByCurrentSection,
+                    SpentProofStatus::UnknownSpentbook,
+                ],
+                ..verification.clone()
+            },
+            DbcVerification {
+                inputs_without_proof: 1,
+                ..verification.clone()
+            },
+            DbcVerification {
+                already_spent: Some(true),
+                ..verification.clone()
+            },
+        ] {
+            assert!(!invalid.is_valid(), "{:?} deemed valid", invalid);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wallet_verify_reissued_dbc() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let wallet1_xorurl = safe.wallet_create().await?;
+        let wallet2_xorurl = safe.wallet_create().await?;
+
+        let dbc = new_dbc(DBC_WITH_1_530_000_000)?;
+        safe.wallet_deposit(&wallet1_xorurl, Some("deposited-dbc"), &dbc)
+            .await?;
+        let output_dbc = safe.wallet_reissue(&wallet1_xorurl, "0.25", None).await?;
+
+        let verification = safe.verify_dbc(&output_dbc).await?;
+        assert!(verification.is_valid(), "{:?}", verification);
+        assert_eq!(verification.amount, Some(Token::from_nano(250000000)));
+        assert_eq!(verification.already_spent, Some(false));
+
+        // once spent, it's not valid anymore
+        safe.wallet_deposit(&wallet2_xorurl, Some("reissued-dbc"), &output_dbc)
+            .await?;
+        let _ = safe.wallet_reissue(&wallet2_xorurl, "0.25", None).await?;
+        let verification = safe.verify_dbc(&output_dbc).await?;
+        assert_eq!(verification.already_spent, Some(true));
+        assert!(!verification.is_valid());
+
+        Ok(())
+    }
+
     const DBC_WITH_MAX: &str = "c4a1953dfa419234b8ab9a60dc0e6fc073a1576cf34a45345218b95e445fdfe7cae2cc4050352891483e93e9f6dc311766ead4835324c04a222ed4aace21410625747877cb83eb7b8b59e196c3ff607e097aecb647415024716856f44e5dd4ae47406706769a8f1498d11ecdbd58f7e83b24c3e498425b86a0b2a07d5277322ed2112a3a853a0c36c0e49fa6ce5543a763b8f891d8d98d5c0c509e62408b354eb18b21c417e7b51b043d52bfc2ed6b444b1b7b00f7e5a359ac712dab253404970000000000000001fb0aa04f1d01b1bdb5e033db74c8fa8ce0afa6a98992ffd5a6639bde4fc772c1bd546d885fd355619bd1a226c6cc743f40c939d39634f6bcc15a1a590e40d597d7c9a08e2346cbe7c06e1a514abedc93000000000000000138aab683457882ebc8dc80cd829f9e292837a00789952636524a544ae1fada10439ed9478b6d0127c5fd13ed74e9e18707ea919914faca624361a4cf56c46c5af7311712a4ce792b2c4b3d0dd6fa7e0e5739d453d82f9cd7957421539b6ce3df184007606ccc21ec98fcde61af1ff83a9a1836eb943a8951b5dc368662efdfcccfbefff5cc368353ebb6ea06217dfc317a28312d0436d55d8c210fcb1bb35295dcbe4b1f43e932c630f99b7629f83725efa04b8b5de60ad9c59e6f6ca02f865fcaa78f39f6245122ef72a9a594ece893bdcad0ed9c6d4ef70dbde8e0b6c674eaebb054dc2bae1057718f09d7272c74c322c200f360ac1e593cb4cd9386fff1a680b6334a5c553f25aeb8d5a78b2137e3beb3c686b1141ccfe6303b08882cf564cadab4d29bca09c7c9544ed427003a91d7cd080343cd4cc87e7ba621b0bad37e384428439d0bb0ece88bde3ee3018adb116f40b633c38418a34d6100baa97d84f76d09ede201b47e36a163e8d59f8724188b3f993d73d6ee05f98e665c7368012c9e10f5ca94dabf7b11c71816039a93e0d0ca221b4d69d1c3ce0afc416b8c05e8b49870eba331310eedfb4b866269b99cc1d0ab8ee3e8a3b574fe2bbbd100b882868cfffe28db4ae1ef9345e7bc94d3babdb3abe3708b7913d69c2a9d766f176093c7809358a5876d8aa36cafd671b87a8ff2a8edb14ad93d584ce65c867ac523a91164e2a6320fa22efa817915959a98e4af659c305b5a2f209341caed64a7e22d5e38811d3ad33cfb5252286b126061691e2b47d2f83e15131a0456562449812e0c4590b5ce2556ef9e07113f55aab3c518c74b8cf3703536de6fc83e775d5203b343af00322512fa97584c4522b8715b48b2d428dec09c2a87a5d2577fb9ea0a895fcd2b86a1d251e30b39e50a10596ddef7e61ab01b624b66fbd1528314628d483999ea0d457a625498817bc9b0215808e77d7f544fcd115d80b520d426990093797183e3bd8b70ce540f20045432f7ee915bf993fc1ddfeb2377b2accad1444174cafc74b6eb78fe764bca0d203b05231581f1cec51cc8d9c8557fc3743db85e126570964526c1c36fabedc8f7f7f9cb871895759777311242957e7ffafdcadde45381fb3ebaa89971361667b59c7d6b858be1aeb0eb7ba7dd643c3cb2ca63f9689ea6d3dcfd9c1503e5e6f264c9b2243a11bcf019be5c43902dd850fda28c9d88144d19a6df48a7672ae33b962ee24cbcc9d826f32e9b9ffbf4ea28932027a5b1ff6338142c68ba3353376735244e07dc49a1f92ca55b625a167dd298cbbdb3a3855b8636fb27178b72015b40789257e72f3e452860756392ec9b2173cb4c8f551193b5d6275c937de915379100000000000003a096535faeae2d5b27e4c0b37f2d8667128dbe048a2814ce4d24fc60f7a0dda7c87e1d3b30254c25aba701ff6eeb9ebe88000000000000000138aab683457882ebc8dc80cd829f9e292837a00789952636524a544ae1fada10439ed9478b6d0127c5fd13ed74e9e1875ec7f615f506c5b54a6f2c581b53054eb27f064c3216970f98165341f1f5e25e7508f4d421c3dba24ff7adbe61d61da910f652f9d986fe9649d2937a0f631c5c8d1a922817b5637b263ac7642066e81df5a8c4e8ecb51168fd2349c71534d4ab0000000000000001bd546d885fd355619bd1a226c6cc743f40c939d39634f6bcc15a1a590e40d597d7c9a08e2346cbe7c06e1a514abedc933dfe1a98a93b51d4c1da98fb7f71c82689cb90a92b4b882d2b7ebbfd63f167e82be854194397cba8f0a87a5fcd07848220c26efbccdc78006f7abb47d90d0f5600000000000000010454acf22d354960e1234aa7878e260bfbc16960378064edecba8edcf7245e860000000000000001a460961fa01de9af3e25b9fa2de0eae07fab57a77d008e32eed003fc9b0fab7c88d31227205b67c5869bfa190787a806a72fcf51b7215073a5fa096a33d633a38c1cbc6acb0e9352fa6ada19f2c940c47e47255f0eb1388be73eac4c63e9678586e57f4967288d544910477648557437c78fee95ed7ef617e040dddedcf87f4c447127fa3efdc4bf00000000000000285434cdc90dcf805084231785b0601ef76669a3850767f2e425ca9f823d6dabbe2da9cb85de80e0ff2bbd95e1302a5aa640f8a05e740ba15e12cc1653cf1fb3b07503b97f77e0564498040a9d77f02858dd0f084c98d7880ff61259b068734b08b6274864828e02dbb33cb912b9ae1f48964a9a8db13db72f9e702b9a60da2a6f1b8febd9e851bd678b191121f05b3994c34edb90f6cd72c9b4999fa51209b859bb9f14223e620cb1cc11137cc45f66fa000000000000002047adb7e64b0c19ed1203da940e2154497d81ab0964688819e37542352bb05a0107feb99b49f0234c9eaa95106453d0b2c16551530000000000000000000000000000000000000000000000000000000000000000";
 
     const DBC_WITH_12_230_000_000: &str = "2fce3f70d7ad38d48d81f3f67c11ea10388a95efff66ce444f3274fb261c119a839e3de7a4c97b3b2b12fde3af09990ed20f48b3a23d79ca9b39cf0b6f492b142c03d3c0ee34d087fa3b9989cab7f7fb4e2328587330ce226277b8062881b3a231b11365712ad3a82ece13af3699fb171f16001af12522a92d1cf6d788a919bf7aa7bc1cd70f0eac4f96967bdd9cb38338aab683457882ebc8dc80cd829f9e292837a00789952636524a544ae1fada10439ed9478b6d0127c5fd13ed74e9e1870000000000000001c428455eb4b5485f9465049a3a1b519a89b50ec3bef0c4ebbf45b7198cc348321ca2ae5ab0ed3dc52cc9cd341c4a482e9d9f5f2d81d981a11032821603b863baa19a900087052a2f799092f29d7e2c8b00000000000000016caf52b6919fa145146588809f6c0d2a0b70053c9501a52730bbfba00de94dfe72f14801d29d5b2d93c8b626d443498d34bf24d89d5012a6067ccb301919272553dcdf50cbf95240e47a3c7e967ba98e70b2a97e866c810a0ba806bb96a1b8308eb9b22b29b44ea5d33ae9e9c057261b09e21456123981952224dbf9cadfd50cfb02e0dc57ce55156f844c3a2c704a5be432d2444765dd15beb7fca57a20508f73df1043f8e2e5d8a56a2fb17fe475f2cca88c8d23eae27f3d172e3d6fad45c1b3232dc10ee46a7016df417935865a99b3761bffdefea6373584f50258ab9d60cddfc80ed76c9454bcaa8083f58e6c53d3095e06b02638eccd668031cf232b87ea9474b4166849a34ffbc805310520c8b19bafa7424df714becb9ce02d6d705b51b168a0b806798da550a0807fc1a8a9122908dbbd7e4838e65a9c9bdbc1fbf3e11a79187444274a828618e4debbcc3cee1ea0d1a157f21b8624bccc8eb58c91a60d84deb16ac42241ace0f3174c3abbca0d3331990e7c6db07ff24c8537060ea76348f96af31927c49c84a4e2c4cb8cdd95f86c0fcc18cb1d6c2a519ced3ad2e9903061dd3f3c8e02fafd969f53f52274faddb0b4f5aef19dc0e62277cbcda3e3ac39cfa4b709efbbdab65440a42e628fff051c83acd583a5e3bbfb2eb4807c5aa2303c7b52f1d539899572b674bd8fb4a529354e8087067a4e374e92b855a7ec523295867f310f7cea5d48bb0295a1c3ea47b6c4229f000d0c83ae22eb449656c25083a5fc99f99a23cf25fc2020941ffb392b3c8788c25b687b21610d35e3a5f12974b20778144d6930772381a694be5605f21142ca2121a7358e377fd20d50ff00ae3550aa2622ac8d8a642c41aec117b74c22732b297dbb4e4637595bb5b7d5a69414c7822e55efb9c40ddbc2a293532ee49c705e9bb2f3fff3f2dcce8c72b90c486b3a750ee594fd2b04c2dd9553e02a226001956bed24790349d243ca5d51167c4a348d19e42bf2cabc72c0510b8217e0ce5997b763fd11a881c8757f565e3ca3766fc5c3e011ad40d634666859a3a5fdbab6fb627c5abd1d80894424c5a1b0de74cfdf5eb17b9c8bb873f4515adc3e9d62d76f4f5bbb0ef453e369ad5ffa853ab6661f2bf242d91d098f5c59ceb4c1151b47f4cd5ee9dc98daca45898168f4ff69fb230d01d7660d0718cc1b6facbb7f8e9a73cb6a8b93aba7dddc450628588fbb916641abaab4a228bc2584950e34f86a8154b4f5773d07a4ad70b9c2fdff3d16933011664e8de0298c61cd2da7511b8a0cd63acf47156bd7f667929fce5b7257582f336683abf609ad7a4b9dca4395849d81da230d13822d01224d49f4d249d9881d2e876335824514448300000000000003a00af4916a22e0177947732d2d58db7e0325d004f1de5718b3275ae009845fa53ce6948ddd9623e445c8433d30c162c9a22b05a80d417ac2efc2bf684e091bc26a81bda1ec9253ed4657c2440710623741007bdb90e9d94121babea7484d25a49432e71b55250be2a5c8ac7716ec2c61218cca98458ae648e11d31c185d20e1eb21300e7b360f1553a128ff97d54c1510a26178421859bef1dae17e4cf496acc39b20059e48b5c94c7ab244dbfc368d12868af7cac467d8c18e8cb5b60c22b7e9151e013dd28b813254e65a73c24c0efae47b29cdc3a34286dabb6fd09d9ad8b121655394d272934013773667eae726cdbab0b42cfc2c0bd988ecacafead6f179046fa3a4f5fb8ca7a790e5685f262c0e770c20a700fdab00aeac3a8634d84102f750996b5323356ad1b3199702c2d23a64279726f35e0eb630f91794dda905093995d6b457782792564be257a191f2bdeedd01e76051f7aa740735b8c408c618b3b6edac93f7504d441855d89adb6e13ed2a60c81ac0a0b445a2d1447bc30f0f30376ab55995ad0b639c1dfddb3e29e8f8ddccb59e55d863117ca6bc1c8dbf42983ea889cd03b19ab7c76addddb6dbdd53856b92df8cc929f3d37eb27ba6ab68194fc55f9ca2943acd265538751335cdbf527675494d176282e59a9c8deb95645b5b2b8660456434701b182e08915dbb6de5bfacc5480e867a9f08c8fe2d209eaa44175912ce5fb782425e264587d8ad13a70eb83d0ac0b02095a8f6d029d588b80b4f7a96c73720344247eae9613a9ff6ca368dbd823c225304725a36aee0c1822fe554733bc074c50ea296ff6b60c8711f7ef216e1ff1b532b398ba13e6da8ebd4546dcf296a4c1f7b27da5ec6e771d23afc139af51d5e9b5f2be9f32a7ab949e86cb5158c0f61b5a31f81e9c9ddc8412fcdf971a7cc296e742bcae1d444928152841a927dd7985bb7ef06c1fcfa9a1b908e6b0f52a38321f325f4bff3af4064ce8266c93821bb81f9a0404ffff81520ff370ba1b831cae5db58cb943e59dac65a9092db9fb12b00424c49dc0b92356a5b2801c40b43f8d5262278b44eb980e5857397b6ec9570dde9ca80b5ab041bf81872f3f999182589771a75c8ef0e3b8603888dade9b1d449adaac0f08c7cddbe85df0871722ded236de4b7e5f999ad3f2c8e01cab184eaec5f2fa35b22586e7838e4dde010a37ff24927c8a4845794ee699ab54b57ba50e038008af5ea0bbaf3f4e627fe03809f973980b81ced08a602b7458158ab7684376535265761dc039bb13d01ff90e732b8df17d783338db830193034d48dca8ed42b077293db0aa1da1b14c0c4c0e76c7859b832a226464e2cad01aa41bf8fd1686933b55fd961ab336cdb697e851bf3b6c1142019475e0b2925d6727e15de30cbf7b342daf509778d2af676c53a3e962ddff846c310d768e00000000000003a058998e2c08c59208820948b33b051910a3156d3beff7984798c030f971904a9085c61447ded4a76575b5fcc40b5acd93b1d0b17ba74231d94f3df0f666e751067fdc9364a3a5d07ce0881d44fabf4cf0f478a2ec3d14a668e3e5f249d57138890a5db2846b458f82e77256f2f4b3f1412b0434831aad2b96fed8c9ed2e520a5c54ddfb9f72ee0932eea6ab7a58b2f14c5ef062717e9d1da9c7f213dd11bed65bd7693398c732e08b6fe96095c68e4d7e2d3378c3632e736358e88db56bad7329bfd83bac50c0649fcf4f8baf1f95a3b16a004a02b8d0a469b687ee7f91743232a46ae0eb27d0f45d18dcbc04aeb9595995802a4ddf42f2712248d38d5b7656916211ecc6bbfb4c62b6f9d3393061ead919662e53f356bf2327536dccedd7eee28b06514de978fcc965a3d6d507aba3b3af78e3ac52e3141e9fc1b51302616138f2e14ba340d1baea0b2d3d65e21fc94545f17e956292b4cfa8eaecdfa46344a88eb15f9e230a384caefaced24f6553f5ba7c7e41f9c9bbec71a9a5d8a33b994b6d1ce62a1334351057838c2fbe0aa3a204c17fb11e1970f36a8ef3a16d634890a98dee795c197ad586a5c0cf5a23e72d5931481ddf33a342c5bcc320965b98b46bfc2c51a106ce2dedc8f7196b1af0f30825854d5a34d7fc398aea4548e13d0289fe76ec71f4d9531e56d44d39562ca349fd9b62321e28e7d1c1c406199b5dd4841e12d1c649e9e3dd7db84c8499f61621c188fcf56da4915001bc82a8b8aaafa7001486df8dc215d02261640dfce04c79ba5d66ea5daec4fe8a7f2e39527c8c5ca8d8e05fe00e9f909d49a852a9c2ab96f185aa63ccd5e542b0355d8ee66b252a5a57ab0b30c704ea8055eea246d5700c0aadf23520a445e70b331b24fb8f8c43739403cba21d87a6a63e05cddfcf6934230c4f9420b796b70df0eb70f0c1a94c09c70b7e1bb6e2494fe4ae028c358a9048404e31e6191749b83f8cc942fc796459ce1669ea7c268d3c55cb2be9e8ed54400cd831c974fe59d63b043963e98330c7179038c3be277bcb8d9713234a48a7f2e84bdc41c6f822e7292a02407c9966834fc014727d1aa86ca08f39d5fb53b745bb16d2eb11f23af7955b1a5507ff670dc39d8bd929a2ac8ad2e72ed27d36c386223672d434474f9149d39079bd72977f573bee8686584eddcb1467c24c16a38b0e6f6a9c727f7cca2b7a7a86589fa507438ee0bdaa383ddb95ac2d3abe8d07fe06dc4980bbf6423d7496d4283f85cfc9dc79091eb8695d583ebf31a6aad857a6510e3bf268b634ec62ee7112be92a89633f394792332d8e0f5fcff45353bf8e55db172dae5d60dacd372c1e47f9ce8d497f7a18438fe6a3930c240b29ab1d91176ebd5934d23230b629f46f294f9926d4f33c5ba2c1cee78df2b3bb2d43c67423751cfd78ec652596dec00a06db900000000000003a0d28d2cf0c4c23a0ae40ab3a4a29ed7eaa11a3957bf5ee7b8e61c4f3858982f5fe8f40bb19cf728f76121c2cd51ab9a970000000000000003e8c9885501863a2462cf63cd2d4109746aeda1643d286fd6fb13014bef689dc66b34b6e5a6f83e88aead415c06737aaee5eb3c7fda86f635b3345c72329088cbae5eb375e33f880727993c4c01b736f93fc1520f3fa1b41a8c8eb7002dd6a1a996535faeae2d5b27e4c0b37f2d8667128dbe048a2814ce4d24fc60f7a0dda7c87e1d3b30254c25aba701ff6eeb9ebe8800000000000000011ca2ae5ab0ed3dc52cc9cd341c4a482e9d9f5f2d81d981a11032821603b863baa19a900087052a2f799092f29d7e2c8b158a686df05f9cb8c9f01f6499b27d21c499d31ad7d8274c4dff49192e73c184018a600ba8fc0e3734dd643706ae2af8922fffa4574a630845a4a91f0ce23518000000000000000125cf08c3ee49b669e5cfb0ad13de8e19691287addf6b5228a78fc769ce76c74e00000000000000018f4ba55337b209afca288eb85bfdd9d8919f20b31022d399f34a0170e4163a86dacac52a77e655366987ffdd726dd50bd51f2cc1b554fe2445b95cc78d176ac62f11e3c39cfb0fc1b3d02ad3b255eaba46d5d8587fe0faa7aae3e7d07aaabb9964eecbcba01e1dfcba56deb73c61fab162bdfbb3ce4bce16baa2e01c437b91dfc8213a62da4a4f5e00000000000000282697644c1845c35353295cdef403482ed7d11a8cab4e1d97817f61145a835c17f219c5ead6e1eec40b8fb085d256dd8020d97692739067230e15649334dbbc63bdfcc2d65a435ab30aadaee4494990ffddf25f5cabf451b691376deab52ade138932ac3b1fcab84cd6b9a6d88ee1d044bf495c5d1d08b3a448c5037d88d055dc481d2832a00c1d12349b1ed6ab62b6b718db76ee5066add05240cd94adb26c9afd87e674ddbc9f8d8578449cb7d6435f00000000000000204bcea50da17574d7dca87d2b13ccdd5754394d6d073c2ddfaee89a9a57b44e87efe52ff2a09ccdc8fea40d71af8be598d2c07cbc4a634060bbe2c11bcb537089130b30325c57b23492d9c0263fd01e5800000000";
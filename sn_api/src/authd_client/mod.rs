@@ -8,9 +8,11 @@
 
 mod authd_client_api;
 mod notifs_endpoint;
+mod remote_signer;
 
 use super::common;
 use super::constants;
 
 // Expose what's meant to be the public API
 pub use authd_client_api::{AuthAllowPrompt, AuthdStatus, PendingAuthReqs, SafeAuthdClient};
+pub use remote_signer::RemoteSigner;
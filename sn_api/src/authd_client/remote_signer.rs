@@ -0,0 +1,125 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{common::send_authd_request, SafeAuthdClient};
+use crate::Result;
+use async_trait::async_trait;
+use log::debug;
+use serde_json::json;
+use sn_client::{Error as ClientError, Result as ClientResult, Signer};
+use sn_interface::types::{PublicKey, Signature};
+use std::path::PathBuf;
+
+// Authenticator method for fetching the public key an app's requests are signed with
+const SN_AUTHD_METHOD_SIGNING_KEY: &str = "signing-key";
+
+// Authenticator method for signing data on behalf of an app
+const SN_AUTHD_METHOD_SIGN: &str = "sign";
+
+// Authenticator method for fetching the public key of a key from the account's keys container
+const SN_AUTHD_METHOD_LABELLED_KEY: &str = "labelled-key";
+
+// Authenticator method for signing data with a key from the account's keys container
+const SN_AUTHD_METHOD_SIGN_WITH_KEY: &str = "sign-with-key";
+
+/// A [`Signer`] which sends every sign request to authd, so the app never holds
+/// the secret key of the account it acts on behalf of.
+///
+/// authd applies the user's policy to each request, which may involve prompting the user
+/// through the endpoint subscribed to authorisation requests notifications, and it only
+/// sends back the signature once the request was allowed.
+///
+/// A signer can also be created for one of the labelled keys in the account's keys container,
+/// which authd only signs with for the apps the user granted the use of that key.
+#[derive(Clone, Debug)]
+pub struct RemoteSigner {
+    authd_endpoint: String,
+    authd_cert_path: PathBuf,
+    app_id: String,
+    key_label: Option<String>,
+    public_key: PublicKey,
+}
+
+impl RemoteSigner {
+    /// Create a signer for the given app, fetching from authd the public key its requests
+    /// shall be signed with. The app must have been authorised with authd beforehand.
+    pub async fn new(authd_client: &SafeAuthdClient, app_id: &str) -> Result<Self> {
+        debug!(
+            "Fetching signing key for app '{}' from remote authd...",
+            app_id
+        );
+        let public_key = send_authd_request::<PublicKey>(
+            &authd_client.authd_cert_path,
+            &authd_client.authd_endpoint,
+            SN_AUTHD_METHOD_SIGNING_KEY,
+            json!(app_id),
+        )
+        .await?;
+
+        Ok(Self {
+            authd_endpoint: authd_client.authd_endpoint.clone(),
+            authd_cert_path: authd_client.authd_cert_path.clone(),
+            app_id: app_id.to_string(),
+            key_label: None,
+            public_key,
+        })
+    }
+
+    /// Create a signer for the given app, which signs with the key of the given label from
+    /// the account's keys container. The use of that key must have been requested when
+    /// the app was authorised, see [`AuthReq::key_labels`](crate::AuthReq::key_labels).
+    pub async fn with_labelled_key(
+        authd_client: &SafeAuthdClient,
+        app_id: &str,
+        label: &str,
+    ) -> Result<Self> {
+        debug!(
+            "Fetching key labelled '{}' for app '{}' from remote authd...",
+            label, app_id
+        );
+        let public_key = send_authd_request::<PublicKey>(
+            &authd_client.authd_cert_path,
+            &authd_client.authd_endpoint,
+            SN_AUTHD_METHOD_LABELLED_KEY,
+            json!(vec![app_id, label]),
+        )
+        .await?;
+
+        Ok(Self {
+            authd_endpoint: authd_client.authd_endpoint.clone(),
+            authd_cert_path: authd_client.authd_cert_path.clone(),
+            app_id: app_id.to_string(),
+            key_label: Some(label.to_string()),
+            public_key,
+        })
+    }
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    async fn sign(&self, data: &[u8]) -> ClientResult<Signature> {
+        debug!("Requesting remote authd to sign for app '{}'", self.app_id);
+        let (method, params) = match &self.key_label {
+            Some(label) => (
+                SN_AUTHD_METHOD_SIGN_WITH_KEY,
+                json!(vec![self.app_id.clone(), label.clone(), hex::encode(data)]),
+            ),
+            None => (
+                SN_AUTHD_METHOD_SIGN,
+                json!(vec![self.app_id.clone(), hex::encode(data)]),
+            ),
+        };
+        send_authd_request::<Signature>(&self.authd_cert_path, &self.authd_endpoint, method, params)
+            .await
+            .map_err(|err| ClientError::Signing(format!("Remote authd failed to sign: {}", err)))
+    }
+}
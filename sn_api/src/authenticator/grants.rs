@@ -0,0 +1,182 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::{
+    ipc::resp::{AuthGranted, GrantExpiry},
+    Error, Result,
+};
+use log::debug;
+use std::{collections::BTreeMap, time::Duration};
+
+/// How long the authorisations granted without an expiry remain valid for.
+pub const DEFAULT_GRANT_VALIDITY: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Registry of the authorisations granted to apps, keeping track of how many times each
+/// of them was used so the ones which lapsed are refused, and can be garbage-collected.
+#[derive(Debug, Default)]
+pub struct AuthGrants {
+    grants: BTreeMap<String, AppGrant>,
+}
+
+#[derive(Debug)]
+struct AppGrant {
+    granted: AuthGranted,
+    uses: u64,
+}
+
+impl AppGrant {
+    fn is_expired(&self) -> bool {
+        self.granted
+            .expiry
+            .map(|expiry| expiry.is_expired(self.uses))
+            .unwrap_or(false)
+    }
+}
+
+impl AuthGrants {
+    /// Registers the authorisation granted to the app, replacing any previous one.
+    /// It lapses after `DEFAULT_GRANT_VALIDITY` unless it has an expiry of its own.
+    pub fn insert(&mut self, app_id: &str, mut granted: AuthGranted) {
+        if granted.expiry.is_none() {
+            granted.expiry = Some(GrantExpiry::after(DEFAULT_GRANT_VALIDITY));
+        }
+        let _ = self
+            .grants
+            .insert(app_id.to_string(), AppGrant { granted, uses: 0 });
+    }
+
+    /// Returns the authorisation granted to the app, counting this as one use of it.
+    /// An `AuthExpired` error is returned if the authorisation lapsed, in which case
+    /// the app needs to be authorised again.
+    pub fn use_grant(&mut self, app_id: &str) -> Result<&AuthGranted> {
+        let grant = self.grants.get_mut(app_id).ok_or_else(|| {
+            Error::AuthenticatorError(format!("Application '{}' is not authorised", app_id))
+        })?;
+
+        if grant.is_expired() {
+            return Err(Error::AuthExpired(format!(
+                "Authorisation of application '{}' expired",
+                app_id
+            )));
+        }
+
+        grant.uses += 1;
+        Ok(&grant.granted)
+    }
+
+    /// Returns the authorisation granted to the app, if it's still valid.
+    pub fn get(&self, app_id: &str) -> Option<&AuthGranted> {
+        self.grants
+            .get(app_id)
+            .filter(|grant| !grant.is_expired())
+            .map(|grant| &grant.granted)
+    }
+
+    /// Removes the authorisation granted to the app, returning it if there was one.
+    pub fn revoke(&mut self, app_id: &str) -> Option<AuthGranted> {
+        self.grants.remove(app_id).map(|grant| grant.granted)
+    }
+
+    /// Removes all the authorisations which lapsed, returning the ids of their apps.
+    pub fn remove_expired(&mut self) -> Vec<String> {
+        let expired: Vec<String> = self
+            .grants
+            .iter()
+            .filter(|(_, grant)| grant.is_expired())
+            .map(|(app_id, _)| app_id.clone())
+            .collect();
+
+        for app_id in &expired {
+            let _ = self.grants.remove(app_id);
+        }
+
+        if !expired.is_empty() {
+            debug!("Expired authorisations removed: {:?}", expired);
+        }
+
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::GrantExpiry;
+    use anyhow::{anyhow, Result};
+    use sn_interface::types::Keypair;
+    use std::time::{Duration, SystemTime};
+
+    fn auth_granted(expiry: Option<GrantExpiry>) -> AuthGranted {
+        AuthGranted {
+            app_keypair: Keypair::new_ed25519(),
+            bootstrap_config: None,
+            bootstrap_config_valid_until: None,
+            expiry,
+        }
+    }
+
+    #[test]
+    fn test_auth_grants_lapse_after_max_uses() -> Result<()> {
+        let mut grants = AuthGrants::default();
+        grants.insert("app", auth_granted(Some(GrantExpiry::uses(2))));
+        grants.insert("other-app", auth_granted(None));
+        // authorisations granted without an expiry get the default one
+        assert!(grants
+            .get("other-app")
+            .and_then(|granted| granted.expiry)
+            .and_then(|expiry| expiry.valid_until)
+            .is_some());
+
+        let _ = grants.use_grant("app")?;
+        let _ = grants.use_grant("app")?;
+        match grants.use_grant("app") {
+            Err(Error::AuthExpired(_)) => {}
+            other => return Err(anyhow!("AuthExpired error expected, got: {:?}", other)),
+        }
+        assert!(grants.get("app").is_none());
+
+        for _ in 0..10 {
+            let _ = grants.use_grant("other-app")?;
+        }
+
+        assert_eq!(grants.remove_expired(), vec!["app".to_string()]);
+        assert!(grants.revoke("app").is_none());
+        assert!(grants.get("other-app").is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_auth_grants_lapse_after_duration() -> Result<()> {
+        let mut grants = AuthGrants::default();
+        let lapsed = GrantExpiry {
+            valid_until: Some(SystemTime::now() - Duration::from_secs(1)),
+            max_uses: Some(100),
+        };
+        grants.insert("lapsed-app", auth_granted(Some(lapsed)));
+        grants.insert(
+            "app",
+            auth_granted(Some(GrantExpiry::after(Duration::from_secs(3600)))),
+        );
+
+        assert!(matches!(
+            grants.use_grant("lapsed-app"),
+            Err(Error::AuthExpired(_))
+        ));
+        let _ = grants.use_grant("app")?;
+
+        assert_eq!(grants.remove_expired(), vec!["lapsed-app".to_string()]);
+        assert!(grants.remove_expired().is_empty());
+        assert!(matches!(
+            grants.use_grant("lapsed-app"),
+            Err(Error::AuthenticatorError(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -6,9 +6,13 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+mod grants;
+
+pub use grants::{AuthGrants, DEFAULT_GRANT_VALIDITY};
+
 use crate::{
     ipc::{req::AuthReq, resp::AuthGranted},
-    Result, SafeAuthReq,
+    Error, Result, SafeAuthReq,
 };
 use hmac::Hmac;
 use rand_07::{rngs::StdRng, SeedableRng};
@@ -19,7 +23,7 @@ use std::{
     collections::HashSet,
     net::SocketAddr,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{Arc, Mutex, MutexGuard},
 };
 use tiny_keccak::{Hasher, Sha3};
 use xor_name::{XorName, XOR_NAME_LEN};
@@ -101,6 +105,8 @@ pub struct SafeAuthenticator {
     config_path: Option<PathBuf>,
     #[allow(dead_code)]
     bootstrap_contacts: Option<HashSet<SocketAddr>>,
+    // Authorisations granted to the apps
+    grants: Mutex<AuthGrants>,
 }
 
 impl SafeAuthenticator {
@@ -115,6 +121,7 @@ impl SafeAuthenticator {
         //     safe: None,
         //     config_path,
         //     bootstrap_contacts,
+        //     grants: Mutex::default(),
         // }
     }
 
@@ -334,8 +341,30 @@ impl SafeAuthenticator {
     }
 
     // TODO: update terminology around apps auth here
-    pub async fn revoke_app(&self, _y: &str) -> Result<()> {
-        unimplemented!()
+    pub async fn revoke_app(&self, app_id: &str) -> Result<()> {
+        match self.lock_grants()?.revoke(app_id) {
+            Some(_) => Ok(()),
+            None => Err(Error::AuthenticatorError(format!(
+                "Application '{}' is not authorised",
+                app_id
+            ))),
+        }
+    }
+
+    /// Checks the app is authorised to make a request, counting the request as one use of its
+    /// authorisation. An `AuthExpired` error is returned if the authorisation lapsed, the
+    /// lapsed authorisations of all apps being dropped along the way.
+    pub fn check_app_grant(&self, app_id: &str) -> Result<AuthGranted> {
+        let mut grants = self.lock_grants()?;
+        let granted = grants.use_grant(app_id).map(AuthGranted::clone);
+        let _ = grants.remove_expired();
+        granted
+    }
+
+    fn lock_grants(&self) -> Result<MutexGuard<'_, AuthGrants>> {
+        self.grants.lock().map_err(|err| {
+            Error::AuthenticatorError(format!("Failed to access the apps authorisations: {}", err))
+        })
     }
 
     /// Decode requests and trigger application authorisation against the current client
@@ -460,10 +489,14 @@ impl SafeAuthenticator {
         //         }
         //     };
 
-        //     Ok(AuthGranted {
+        //     let auth_granted = AuthGranted {
         //         app_keypair: keypair,
         //         bootstrap_config: self.bootstrap_contacts.clone(),
-        //     })
+        //         bootstrap_config_valid_until: None,
+        //         expiry: None,
+        //     };
+        //     self.lock_grants()?.insert(&auth_req.app_id, auth_granted.clone());
+        //     Ok(auth_granted)
         // } else {
         //     Err(Error::AuthenticatorError(
         //         "No Safe is currently unlocked".to_string(),
@@ -534,6 +567,38 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_app_grants_are_checked_and_revoked() -> Result<()> {
+        let authenticator = SafeAuthenticator::default();
+        let granted = AuthGranted {
+            app_keypair: Keypair::new_ed25519(),
+            bootstrap_config: None,
+            bootstrap_config_valid_until: None,
+            expiry: Some(crate::ipc::GrantExpiry::uses(1)),
+        };
+        authenticator
+            .lock_grants()?
+            .insert("lapsing-app", granted.clone());
+        authenticator.lock_grants()?.insert("app", granted);
+
+        let _ = authenticator.check_app_grant("lapsing-app")?;
+        assert!(matches!(
+            authenticator.check_app_grant("lapsing-app"),
+            Err(Error::AuthExpired(_))
+        ));
+        // the lapsed authorisation was dropped
+        assert!(matches!(
+            authenticator.check_app_grant("lapsing-app"),
+            Err(Error::AuthenticatorError(_))
+        ));
+
+        authenticator.revoke_app("app").await?;
+        assert!(authenticator.check_app_grant("app").is_err());
+        assert!(authenticator.revoke_app("app").await.is_err());
+
+        Ok(())
+    }
+
     proptest! {
         #[test]
         fn proptest_always_get_same_info_from_from_phrase_and_pw(s in "\\PC*", p in "\\PC*") {
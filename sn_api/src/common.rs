@@ -6,7 +6,11 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{constants::SN_AUTHD_CONNECTION_IDLE_TIMEOUT, Error, Result};
+use super::{
+    constants::SN_AUTHD_CONNECTION_IDLE_TIMEOUT,
+    ipc::{IpcError, IpcMsg},
+    Error, Result,
+};
 use log::info;
 use qjsonrpc::ClientEndpoint;
 use serde::de::DeserializeOwned;
@@ -14,7 +18,7 @@ use std::path::Path;
 use tokio::runtime;
 
 pub mod auth_types {
-    use crate::ipc::req::IpcReq;
+    use crate::ipc::{req::IpcReq, resp::GrantExpiry};
     use serde::{Deserialize, Serialize};
 
     pub type SafeAuthReq = IpcReq;
@@ -28,6 +32,9 @@ pub mod auth_types {
         pub name: String,
         /// The application provider/vendor (e.g. MaidSafe)
         pub vendor: String,
+        /// When the authorisation granted to the application lapses, if ever.
+        #[serde(default)]
+        pub expiry: Option<GrantExpiry>,
     }
 
     // Type of the list of authorised applications in a Safe
@@ -81,7 +88,14 @@ where
         .send(method, params)
         .await
         .map_err(|err| match err {
-            qjsonrpc::Error::RemoteEndpointError(msg) => Error::AuthdError(msg),
+            // authd reports IPC errors encoded as an `IpcMsg::Err`
+            qjsonrpc::Error::RemoteEndpointError(msg) => match IpcMsg::from_string(&msg) {
+                Ok(IpcMsg::Err(IpcError::AuthExpired)) => {
+                    Error::AuthExpired("Authorisation of the application expired".to_string())
+                }
+                Ok(IpcMsg::Err(error)) => Error::AuthdError(error.to_string()),
+                _ => Error::AuthdError(msg),
+            },
             other => Error::AuthdClientError(other.to_string()),
         })
 }
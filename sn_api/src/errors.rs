@@ -6,6 +6,8 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use super::credentials::CredentialsError;
+use super::files::FilesMapViolation;
 use super::ipc::IpcError;
 use super::nrs::NrsMap;
 use super::safeurl::{Error as UrlError, SafeUrl, XorUrl};
@@ -33,6 +35,9 @@ pub enum Error {
     /// AuthdError
     #[error("AuthdError: {0}")]
     AuthdError(String),
+    /// AuthExpired
+    #[error("AuthExpired: {0}. The app needs to be authorised again")]
+    AuthExpired(String),
     /// AuthdAlreadyStarted
     #[error("AuthdAlreadyStarted: {0}")]
     AuthdAlreadyStarted(String),
@@ -51,6 +56,12 @@ pub enum Error {
     /// ContentNotFound
     #[error("ContentNotFound: {0}")]
     ContentNotFound(String),
+    /// Credentials
+    #[error("Credentials: {0}")]
+    Credentials(#[from] CredentialsError),
+    /// CorruptFilesMap
+    #[error("CorruptFilesMap: {0}")]
+    CorruptFilesMap(String, Vec<FilesMapViolation>),
     /// ContentError
     #[error("ContentError: {0}")]
     ContentError(String),
@@ -91,6 +102,9 @@ pub enum Error {
     /// InvalidInput
     #[error("InvalidInput: {0}")]
     InvalidInput(String),
+    /// InvalidNodeAttestation
+    #[error("InvalidNodeAttestation: {0}")]
+    InvalidNodeAttestation(String),
     /// InvalidMediaType
     #[error("InvalidMediaType: {0}")]
     InvalidMediaType(String),
@@ -100,6 +114,9 @@ pub enum Error {
     /// NrsNameAlreadyExists
     #[error("NrsNameAlreadyExists: {0}")]
     NrsNameAlreadyExists(String),
+    /// KeyLabelAlreadyExists
+    #[error("KeyLabelAlreadyExists: {0}")]
+    KeyLabelAlreadyExists(String),
     /// Serialisation
     #[error("Serialisation: {0}")]
     Serialisation(String),
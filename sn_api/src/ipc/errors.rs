@@ -29,4 +29,7 @@ pub enum IpcError {
     /// App is not registered.
     #[error("App is not registered")]
     UnknownApp,
+    /// The authorisation granted to the app expired, it needs to be authorised again.
+    #[error("App authorisation expired")]
+    AuthExpired,
 }
@@ -16,17 +16,12 @@ mod errors;
 pub use self::{
     errors::IpcError,
     req::{AuthReq, IpcReq},
-    resp::{AuthGranted, IpcResp},
+    resp::{AuthGranted, BootstrapInfo, GrantExpiry, IpcResp},
 };
-use serde::{Deserialize, Serialize};
-use std::{collections::BTreeSet, net::SocketAddr, u32};
+pub use sn_interface::network_knowledge::NodeConfig;
 
-/// The Node configuration for the network we are participating in.
-///
-/// The `PublicKey` is the Genesis Key that is either supplied or created upon the launching the
-/// first node of the network. The `BTreeSet` is a list of `SocketAddr`, which is an IP address and
-/// port number. This is a list of the nodes participating in the network.
-pub type NodeConfig = (bls::PublicKey, BTreeSet<SocketAddr>);
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, u32};
 
 /// IPC message.
 #[allow(clippy::large_enum_variant)]
@@ -42,6 +37,17 @@ pub enum IpcMsg {
 
 impl IpcMsg {
     pub fn new_auth_req(app_id: &str, app_name: &str, app_vendor: &str) -> Self {
+        Self::new_auth_req_with_keys(app_id, app_name, app_vendor, BTreeSet::new())
+    }
+
+    /// Auth request which also asks for the use of the keys with the given labels
+    /// from the account's keys container
+    pub fn new_auth_req_with_keys(
+        app_id: &str,
+        app_name: &str,
+        app_vendor: &str,
+        key_labels: BTreeSet<String>,
+    ) -> Self {
         use rand::Rng;
         // Generate the number in range 1..MAX inclusive.
         let req_id: u32 = rand::thread_rng().gen_range(0..u32::max_value()) + 1;
@@ -51,6 +57,7 @@ impl IpcMsg {
             app_id: app_id.to_string(),
             app_name: app_name.to_string(),
             app_vendor: app_vendor.to_string(),
+            key_labels,
         }))
     }
 
@@ -58,6 +65,10 @@ impl IpcMsg {
         Self::Req(IpcReq::Unregistered(user_data.to_vec()))
     }
 
+    pub fn new_bootstrap_refresh_req() -> Self {
+        Self::Req(IpcReq::BootstrapRefresh)
+    }
+
     pub fn to_string(&self) -> Result<String, IpcError> {
         serde_json::to_string(self).map_err(|_| IpcError::EncodeDecodeError)
     }
@@ -8,6 +8,7 @@
 
 use crate::common::auth_types::SafeAuthReqId;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 
 /// IPC request.
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
@@ -18,6 +19,9 @@ pub enum IpcReq {
     /// Unregistered application request, used to obtain network bootstrapping information.
     /// Takes arbitrary user data as `Vec<u8>`, returns bootstrap information.
     Unregistered(Vec<u8>),
+    /// Bootstrap refresh request, used by an application to obtain up to date network
+    /// bootstrapping information, e.g. once the one it was given has expired.
+    BootstrapRefresh,
 }
 
 /// Represents an authorisation request.
@@ -31,4 +35,8 @@ pub struct AuthReq {
     pub app_name: String,
     /// The application provider/vendor (e.g. MaidSafe)
     pub app_vendor: String,
+    /// Labels of the keys from the account's keys container the application requests to
+    /// sign with. authd signs with them on its behalf, never handing it their secret keys.
+    #[serde(default)]
+    pub key_labels: BTreeSet<String>,
 }
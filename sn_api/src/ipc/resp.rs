@@ -9,6 +9,7 @@
 use super::{IpcError, NodeConfig};
 use serde::{Deserialize, Serialize};
 use sn_interface::types::Keypair;
+use std::time::{Duration, SystemTime};
 
 /// IPC response.
 #[allow(clippy::large_enum_variant)]
@@ -18,6 +19,8 @@ pub enum IpcResp {
     Auth(Result<AuthGranted, IpcError>),
     /// Unregistered client.
     Unregistered(Result<NodeConfig, IpcError>),
+    /// Refreshed network bootstrap information.
+    BootstrapRefresh(Result<BootstrapInfo, IpcError>),
 }
 
 /// It represents the authentication response.
@@ -29,4 +32,76 @@ pub struct AuthGranted {
     /// Network bootstrap information.
     /// Useful to reuse bootstrap nodes and speed up access.
     pub bootstrap_config: Option<NodeConfig>,
+
+    /// Time until which the bootstrap information is expected to remain valid.
+    /// Once it's passed, it should be refreshed with an `IpcReq::BootstrapRefresh` request.
+    pub bootstrap_config_valid_until: Option<SystemTime>,
+
+    /// When the authorisation lapses, after which the app needs to be authorised again.
+    /// `None` if it lapses after the authenticator's default validity.
+    #[serde(default)]
+    pub expiry: Option<GrantExpiry>,
+}
+
+/// When an authorisation granted to an app lapses: at a given time, after a number
+/// of uses, or whichever comes first if both are set.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct GrantExpiry {
+    /// Time the authorisation lapses at.
+    pub valid_until: Option<SystemTime>,
+    /// Number of requests the app can make with the authorisation.
+    pub max_uses: Option<u64>,
+}
+
+impl GrantExpiry {
+    /// Expiry of an authorisation lapsing once the given duration elapsed from now.
+    pub fn after(duration: Duration) -> Self {
+        Self {
+            valid_until: Some(SystemTime::now() + duration),
+            max_uses: None,
+        }
+    }
+
+    /// Expiry of an authorisation lapsing after the given number of uses.
+    pub fn uses(max_uses: u64) -> Self {
+        Self {
+            valid_until: None,
+            max_uses: Some(max_uses),
+        }
+    }
+
+    /// Returns whether the authorisation lapsed, given the number of times it was used.
+    pub fn is_expired(&self, uses: u64) -> bool {
+        let time_lapsed = self
+            .valid_until
+            .map(|valid_until| valid_until <= SystemTime::now())
+            .unwrap_or(false);
+        let uses_lapsed = self
+            .max_uses
+            .map(|max_uses| uses >= max_uses)
+            .unwrap_or(false);
+
+        time_lapsed || uses_lapsed
+    }
+}
+
+/// Network bootstrap information, as provided by the Authenticator at a given time.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct BootstrapInfo {
+    /// Network bootstrap information.
+    pub bootstrap_config: NodeConfig,
+
+    /// Time until which the bootstrap information is expected to remain valid,
+    /// `None` if the Authenticator doesn't know when it will go stale.
+    pub valid_until: Option<SystemTime>,
+}
+
+impl BootstrapInfo {
+    /// Returns whether the bootstrap information is past its validity time,
+    /// and thus should be refreshed.
+    pub fn is_expired(&self) -> bool {
+        self.valid_until
+            .map(|valid_until| valid_until <= SystemTime::now())
+            .unwrap_or(false)
+    }
 }
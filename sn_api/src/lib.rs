@@ -21,9 +21,11 @@ mod errors;
 mod safeurl;
 
 // re-export these useful types from sn_data_types
+pub use sn_interface::messaging::data::{SectionStats, StorageReceipt};
+pub use sn_interface::network_knowledge::{NetworkSnapshot, NetworkSnapshotDiff};
 pub use sn_interface::types::{
-    BytesAddress, DataAddress, Keypair, PublicKey, RegisterAddress, SafeKeyAddress, Scope,
-    SecretKey, Token,
+    BytesAddress, DataAddress, Keypair, NodeAttestation, PublicKey, RegisterAddress,
+    SafeKeyAddress, Scope, SecretKey, Token,
 };
 
 #[cfg(feature = "app")]
@@ -32,7 +34,7 @@ pub use app::*;
 pub use ipc::*;
 
 #[cfg(feature = "app")]
-pub use xor_name::XorName;
+pub use xor_name::{Prefix, XorName};
 
 #[cfg(feature = "authenticator")]
 pub use authenticator::*;
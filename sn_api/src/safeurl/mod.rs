@@ -17,7 +17,10 @@ pub use errors::{Error, Result};
 use multibase::{decode as base_decode, encode as base_encode, Base};
 use serde::{Deserialize, Serialize};
 use sn_interface::types::{BytesAddress, DataAddress, RegisterAddress, SafeKeyAddress, Scope};
-use std::fmt;
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
 use tracing::{info, trace, warn};
 use url::Url;
 use url_parts::UrlParts;
@@ -37,6 +40,9 @@ const XOR_URL_VERSION_1: u64 = 0x1; // TODO: consider using 16 bits
 const XOR_URL_STR_MAX_LENGTH: usize = 44;
 const XOR_NAME_BYTES_OFFSET: usize = 5; // offset where to find the XoR name bytes
 const URL_VERSION_QUERY_NAME: &str = "v";
+const URL_INTEGRITY_QUERY_NAME: &str = "integrity";
+// Query params with a meaning of their own, whose values are validated when set
+const URL_RESERVED_QUERY_NAMES: [&str; 2] = [URL_VERSION_QUERY_NAME, URL_INTEGRITY_QUERY_NAME];
 
 /// The XOR-URL type
 pub type XorUrl = String;
@@ -733,13 +739,18 @@ impl SafeUrl {
             }
         }
 
-        self.query_string = pairs.finish();
-        trace!("Set query_string: {}", self.query_string);
-
+        if key == URL_INTEGRITY_QUERY_NAME {
+            if let Some(pin) = val {
+                let _ = parse_integrity_pin(pin)?;
+            }
+        }
         if key == URL_VERSION_QUERY_NAME {
             self.set_content_version_internal(val)?;
         }
 
+        self.query_string = pairs.finish();
+        trace!("Set query_string: {}", self.query_string);
+
         Ok(())
     }
 
@@ -758,6 +769,9 @@ impl SafeUrl {
         // tbd: another option could be to throw an error if input
         // contains ?v.
         let v_option = Self::query_key_last_internal(query, URL_VERSION_QUERY_NAME);
+        if let Some(pin) = Self::query_key_last_internal(query, URL_INTEGRITY_QUERY_NAME) {
+            let _ = parse_integrity_pin(&pin)?;
+        }
         self.set_content_version_internal(v_option.as_deref())?;
 
         self.query_string = query.to_string();
@@ -811,6 +825,77 @@ impl SafeUrl {
         Self::query_key_first_internal(&self.query_string, key)
     }
 
+    /// returns the last value of a query param, parsed into the requested type.
+    ///
+    /// eg in safe://name?raw=true&age=5
+    ///    `query_param::<bool>("raw")` returns Some(true),
+    ///    and `query_param::<u8>("name")` returns None.
+    pub fn query_param<T: FromStr>(&self, key: &str) -> Result<Option<T>> {
+        self.query_key_last(key)
+            .map(|val| {
+                val.parse::<T>().map_err(|_| {
+                    Error::InvalidInput(format!(
+                        "Query param '{}' has a value of an unexpected type: '{}'",
+                        key, val
+                    ))
+                })
+            })
+            .transpose()
+    }
+
+    /// sets or unsets a query param from a typed value.
+    ///
+    /// This is a shortcut for `set_query_key` with the value converted to a string.
+    pub fn set_query_param<T: Display>(&mut self, key: &str, val: Option<T>) -> Result<()> {
+        let val = val.map(|val| val.to_string());
+        self.set_query_key(key, val.as_deref())
+    }
+
+    /// returns true if the query param has a meaning of its own for the SafeUrl,
+    /// i.e. the content version ("?v=") and the integrity pin ("?integrity=").
+    pub fn is_reserved_query_key(key: &str) -> bool {
+        URL_RESERVED_QUERY_NAMES.contains(&key)
+    }
+
+    /// gets the integrity pin, i.e. the XorName of the content the URL is expected to resolve to.
+    ///
+    /// This is a shortcut method for getting the "?integrity=" query param.
+    pub fn integrity_pin(&self) -> Option<XorName> {
+        // the param was validated when set, so it can't fail to parse
+        self.query_key_last(URL_INTEGRITY_QUERY_NAME)
+            .and_then(|pin| parse_integrity_pin(&pin).ok())
+    }
+
+    /// sets the integrity pin, i.e. the XorName of the content the URL is expected to resolve to.
+    ///
+    /// This is a shortcut method for setting the "?integrity=" query param, hex encoded.
+    pub fn set_integrity_pin(&mut self, pin: Option<XorName>) {
+        let pin = pin.map(|name| hex::encode(name.0));
+        // a hex encoded XorName is always a valid pin
+        self.set_query_key(URL_INTEGRITY_QUERY_NAME, pin.as_deref())
+            .unwrap_or_else(|e| {
+                warn!("{}", e);
+            });
+    }
+
+    /// carries over the query params of another URL, e.g. the one this URL was resolved from,
+    /// leaving out the reserved ones which only apply to the content the other URL refers to.
+    ///
+    /// Params already set on this URL are kept as they are.
+    pub fn inherit_query_params(&mut self, other: &SafeUrl) {
+        let own_pairs = self.query_pairs();
+        let mut pairs = url::form_urlencoded::Serializer::new(String::new());
+        for (k, v) in &own_pairs {
+            let _res = pairs.append_pair(k, v);
+        }
+        for (k, v) in other.query_pairs() {
+            if !Self::is_reserved_query_key(&k) && !own_pairs.iter().any(|(own, _)| *own == k) {
+                let _res = pairs.append_pair(&k, &v);
+            }
+        }
+        self.query_string = pairs.finish();
+    }
+
     /// sets url fragment
     pub fn set_fragment(&mut self, fragment: String) {
         self.fragment = fragment;
@@ -1180,6 +1265,24 @@ impl SafeUrl {
     }
 }
 
+// Parses the value of an integrity pin, a hex encoded XorName
+fn parse_integrity_pin(pin: &str) -> Result<XorName> {
+    let bytes = hex::decode(pin)
+        .ok()
+        .filter(|bytes| bytes.len() == XOR_NAME_LEN);
+    match bytes {
+        Some(bytes) => {
+            let mut name = XorName::default();
+            name.0.copy_from_slice(&bytes);
+            Ok(name)
+        }
+        None => Err(Error::InvalidInput(format!(
+            "{} param could not be parsed as a hex encoded XorName. invalid: '{}'",
+            URL_INTEGRITY_QUERY_NAME, pin
+        ))),
+    }
+}
+
 impl fmt::Display for SafeUrl {
     /// serializes the URL to a string.
     ///
@@ -1618,6 +1721,71 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_url_typed_query_params() -> Result<()> {
+        let mut x = SafeUrl::from_url("safe://myname?raw=true&age=5&name=John+Doe")?;
+        assert_eq!(x.query_param::<bool>("raw")?, Some(true));
+        assert_eq!(x.query_param::<u8>("age")?, Some(5));
+        assert_eq!(x.query_param::<u8>("height")?, None);
+        assert!(x.query_param::<u8>("name").is_err());
+
+        x.set_query_param("age", Some(6))?;
+        x.set_query_param::<bool>("raw", None)?;
+        assert_eq!(x.to_string(), "safe://myname?age=6&name=John+Doe");
+
+        // params survive re-encoding
+        let xorurl = SafeUrl::from_url(&x.to_xorurl_string())?;
+        assert_eq!(xorurl.query_param::<u8>("age")?, Some(6));
+        assert_eq!(xorurl.query_key_last("name"), Some("John Doe".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_url_integrity_pin() -> Result<()> {
+        let mut x = SafeUrl::from_url("safe://myname?raw=true")?;
+        let name = xor_name::rand::random::<XorName>();
+        x.set_integrity_pin(Some(name));
+        assert_eq!(x.integrity_pin(), Some(name));
+        assert_eq!(
+            x.to_string(),
+            format!("safe://myname?raw=true&integrity={}", hex::encode(name.0))
+        );
+
+        let parsed = SafeUrl::from_url(&x.to_string())?;
+        assert_eq!(parsed.integrity_pin(), Some(name));
+
+        // reserved params are validated however they're set
+        assert!(x
+            .set_query_key(URL_INTEGRITY_QUERY_NAME, Some("abc"))
+            .is_err());
+        assert!(SafeUrl::from_url("safe://myname?integrity=abc").is_err());
+        assert_eq!(x.integrity_pin(), Some(name));
+
+        x.set_integrity_pin(None);
+        assert_eq!(x.integrity_pin(), None);
+        assert_eq!(x.to_string(), "safe://myname?raw=true");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_url_inherit_query_params() -> Result<()> {
+        let random_hash = EntryHash(rand::thread_rng().gen::<[u8; 32]>());
+        let version_hash = VersionHash::from(&random_hash);
+        let mut from = SafeUrl::from_url("safe://myname?raw=true&lang=en")?;
+        from.set_content_version(Some(version_hash));
+        from.set_integrity_pin(Some(xor_name::rand::random::<XorName>()));
+
+        let mut x = SafeUrl::from_url("safe://othername?lang=fr")?;
+        x.inherit_query_params(&from);
+        assert_eq!(x.to_string(), "safe://othername?lang=fr&raw=true");
+        assert_eq!(x.content_version(), None);
+        assert_eq!(x.integrity_pin(), None);
+
+        Ok(())
+    }
+
     #[test]
     fn test_url_set_sub_names() -> Result<()> {
         let mut x = SafeUrl::from_url("safe://sub1.sub2.myname")?;
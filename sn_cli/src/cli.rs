@@ -107,6 +107,7 @@ pub async fn run() -> Result<()> {
             location,
             recursive,
             follow_links,
+            symlink_policy,
         } => {
             if let Some(cmd) = cmd {
                 xorurl_commander(cmd, output_fmt, safe.xorurl_base).await
@@ -115,6 +116,7 @@ pub async fn run() -> Result<()> {
                     location,
                     recursive,
                     follow_links,
+                    symlink_policy,
                     output_fmt,
                     safe.xorurl_base,
                 )
@@ -9,10 +9,10 @@
 use super::config::Config;
 use crate::{APP_ID, APP_NAME, APP_VENDOR};
 use color_eyre::{eyre::eyre, eyre::WrapErr, Result};
-use sn_api::{Keypair, Safe};
+use sn_api::{credentials::Credentials, Keypair, Safe};
 use std::{
+    env,
     fs::{create_dir_all, File},
-    io::Write,
     path::{Path, PathBuf},
     time::Duration,
 };
@@ -20,6 +20,9 @@ use tracing::{debug, info, warn};
 
 const AUTH_CREDENTIALS_FILENAME: &str = "credentials";
 
+// Passphrase the CLI's credentials are encrypted with, if any
+const SN_CLI_CREDENTIALS_PASSPHRASE: &str = "SN_CLI_CREDENTIALS_PASSPHRASE";
+
 #[allow(dead_code)]
 pub async fn authorise_cli(
     endpoint: Option<String>,
@@ -27,7 +30,7 @@ pub async fn authorise_cli(
     authd_cert_path: &Path,
     config: &Config,
 ) -> Result<()> {
-    let (mut file, file_path) = create_credentials_file(config)?;
+    let (_, file_path) = create_credentials_file(config)?;
     println!("Authorising CLI application...");
     if !is_self_authing {
         println!("Note you can use this CLI from another console to authorise it with 'auth allow' command. Alternativelly, you can also use '--self-auth' flag with 'auth unlock' command to automatically self authorise the CLI app.");
@@ -43,10 +46,8 @@ pub async fn authorise_cli(
     .await
     .wrap_err("Application authorisation failed")?;
 
-    let serialised_keypair = serde_json::to_string(&app_keypair)
-        .wrap_err("Unable to serialise the credentials obtained")?;
-
-    file.write_all(serialised_keypair.as_bytes())
+    Credentials::new(app_keypair, Some(APP_NAME.to_string()))
+        .write(&file_path, credentials_passphrase().as_deref())
         .wrap_err_with(|| format!("Unable to write credentials in {}", file_path.display(),))?;
 
     println!("Safe CLI app was successfully authorised");
@@ -59,7 +60,7 @@ pub async fn authorise_cli(
 pub async fn connect(safe: &mut Safe, config: &Config, timeout: Duration) -> Result<()> {
     debug!("Connecting...");
 
-    let app_keypair = if let Ok((_, keypair)) = read_credentials(safe, config) {
+    let app_keypair = if let Ok((_, keypair)) = read_credentials(config) {
         keypair
     } else {
         None
@@ -80,6 +81,7 @@ pub async fn connect(safe: &mut Safe, config: &Config, timeout: Duration) -> Res
             client_cfg.as_deref(),
             Some(timeout),
             config.dbc_owner.clone(),
+            None,
         )
         .await
     {
@@ -92,6 +94,7 @@ pub async fn connect(safe: &mut Safe, config: &Config, timeout: Duration) -> Res
                 None,
                 Some(timeout),
                 config.dbc_owner.clone(),
+                None,
             )
             .await
             .wrap_err("Failed to connect with read-only access")
@@ -113,10 +116,10 @@ pub fn create_credentials_file(config: &Config) -> Result<(File, PathBuf)> {
     Ok((file, file_path))
 }
 
-pub fn read_credentials(safe: &Safe, config: &Config) -> Result<(PathBuf, Option<Keypair>)> {
+pub fn read_credentials(config: &Config) -> Result<(PathBuf, Option<Keypair>)> {
     let (_, path) = get_credentials_file_path(config)?;
-    let keypair = match safe.deserialize_keypair(&path) {
-        Ok(kp) => Some(kp),
+    let keypair = match Credentials::load(&path, credentials_passphrase().as_deref()) {
+        Ok(credentials) => Some(credentials.keypair),
         Err(e) => {
             debug!("Unable to read credentials from {}: {}", path.display(), e);
             None
@@ -125,6 +128,12 @@ pub fn read_credentials(safe: &Safe, config: &Config) -> Result<(PathBuf, Option
     Ok((path, keypair))
 }
 
+/// The passphrase to encrypt and decrypt the CLI's credentials with, if one was set in the
+/// environment. Credentials written without one are left unencrypted.
+pub fn credentials_passphrase() -> Option<String> {
+    env::var(SN_CLI_CREDENTIALS_PASSPHRASE).ok()
+}
+
 #[allow(dead_code)]
 pub fn clear_credentials(config: &Config) -> Result<()> {
     let (_, file_path) = create_credentials_file(config).context("Failed to clear credentials")?;
@@ -10,13 +10,12 @@ use color_eyre::{eyre::bail, eyre::eyre, eyre::WrapErr, Help, Report, Result};
 use comfy_table::Table;
 use serde::{Deserialize, Serialize};
 use sn_api::keys::deserialize_keypair;
-use sn_api::{NodeConfig, PublicKey};
+use sn_api::NodeConfig;
 use sn_dbc::Owner;
 use std::{
-    collections::{BTreeMap, BTreeSet},
+    collections::BTreeMap,
     default::Default,
     fmt,
-    net::SocketAddr,
     path::{Path, PathBuf},
     thread,
     time::Duration,
@@ -72,8 +71,8 @@ impl NetworkLauncher for SnLaunchToolNetworkLauncher {
 
 #[derive(Deserialize, Debug, Serialize, Clone)]
 pub enum NetworkInfo {
-    /// The node configuration is a genesis key, which is a BLS public key, and a set of nodes
-    /// participating in the network, which are an IPv4 and port address pair.
+    /// The node configuration, i.e. the genesis key, which is a BLS public key, and a set of
+    /// nodes participating in the network, along with the name of the network if it has one.
     NodeConfig(NodeConfig),
     /// A URL or file path where the network connection information can be fetched/read from.
     ConnInfoLocation(String),
@@ -91,10 +90,10 @@ impl fmt::Display for NetworkInfo {
 impl NetworkInfo {
     pub async fn matches(&self, node_config: &NodeConfig) -> bool {
         match self {
-            Self::NodeConfig(nc) => nc == node_config,
+            Self::NodeConfig(nc) => same_network(nc, node_config),
             Self::ConnInfoLocation(config_location) => {
                 match retrieve_node_config(config_location).await {
-                    Ok(info) => info == *node_config,
+                    Ok(info) => same_network(&info, node_config),
                     Err(_) => false,
                 }
             }
@@ -443,22 +442,25 @@ async fn retrieve_node_config(location: &str) -> Result<NodeConfig> {
 }
 
 fn deserialise_node_config(bytes: &[u8]) -> Result<NodeConfig> {
-    let deserialized: (String, BTreeSet<SocketAddr>) = serde_json::from_slice(bytes)?;
-    let genesis_key = PublicKey::bls_from_hex(&deserialized.0)?
-        .bls()
-        .ok_or_else(|| eyre!("Unexpectedly failed to obtain (BLS) genesis key."))?;
-    Ok((genesis_key, deserialized.1))
+    NodeConfig::from_json(bytes).map_err(|err| eyre!(err))
 }
 
 pub fn serialise_node_config(node_config: &NodeConfig) -> Result<String> {
-    let genesis_key_hex = hex::encode(node_config.0.to_bytes());
-    serde_json::to_string(&(genesis_key_hex, node_config.1.clone()))
+    node_config
+        .to_json()
         .wrap_err_with(|| "Failed to serialise network connection info")
 }
 
+// Whether both configs are of the same network, regardless of the format they were read from
+fn same_network(node_config: &NodeConfig, other: &NodeConfig) -> bool {
+    node_config.genesis_key == other.genesis_key
+        && node_config.contacts == other.contacts
+        && node_config.network_name == other.network_name
+}
+
 #[cfg(test)]
 mod constructor {
-    use super::{Config, NetworkInfo};
+    use super::{Config, NetworkInfo, NodeConfig};
     use assert_fs::prelude::*;
     use color_eyre::{eyre::eyre, Result};
     use predicates::prelude::*;
@@ -555,7 +557,7 @@ mod constructor {
         assert_eq!(config.networks_iter().count(), 1);
         assert_eq!(network_name, "existing_network");
         match network_info {
-            NetworkInfo::NodeConfig((_, contacts)) => {
+            NetworkInfo::NodeConfig(NodeConfig { contacts, .. }) => {
                 assert_eq!(contacts.len(), 2);
 
                 let node: SocketAddr = "127.0.0.1:12000".parse()?;
@@ -632,9 +634,9 @@ mod read_current_node_config {
 
         let (node_config_path, node_config) = config.read_current_node_config().await?;
 
-        let genesis_key = node_config.0;
+        let genesis_key = node_config.genesis_key;
         let retrieved_genesis_key_hex = hex::encode(genesis_key.to_bytes());
-        let nodes = node_config.1;
+        let nodes = node_config.contacts;
         assert_eq!(genesis_key_hex, retrieved_genesis_key_hex);
         assert_eq!(node_config_file.path(), node_config_path);
         assert_eq!(nodes.len(), 11);
@@ -696,7 +698,7 @@ mod read_current_node_config {
 
 #[cfg(test)]
 mod add_network {
-    use super::{Config, NetworkInfo};
+    use super::{Config, NetworkInfo, NodeConfig};
     use assert_fs::prelude::*;
     use color_eyre::{
         eyre::{bail, eyre},
@@ -977,7 +979,10 @@ mod add_network {
         let result = config
             .add_network(
                 "existing_network",
-                Some(NetworkInfo::NodeConfig((secret_key.public_key(), nodes))),
+                Some(NetworkInfo::NodeConfig(NodeConfig::new(
+                    secret_key.public_key(),
+                    nodes,
+                ))),
             )
             .await;
 
@@ -995,14 +1000,14 @@ mod add_network {
         assert_eq!(network_name, "existing_network");
         match network_info {
             NetworkInfo::NodeConfig(node_config) => {
-                assert_eq!(node_config.1.len(), 2);
+                assert_eq!(node_config.contacts.len(), 2);
 
                 let node: SocketAddr = "10.0.0.1:12000".parse()?;
-                assert_eq!(node_config.1.get(&node), Some(&node));
+                assert_eq!(node_config.contacts.get(&node), Some(&node));
                 let node: SocketAddr = "10.0.0.2:12000".parse()?;
-                assert_eq!(node_config.1.get(&node), Some(&node));
+                assert_eq!(node_config.contacts.get(&node), Some(&node));
 
-                let public_key = node_config.0;
+                let public_key = node_config.genesis_key;
                 assert_eq!(hex::encode(public_key.to_bytes()), genesis_key);
             }
             NetworkInfo::ConnInfoLocation(_) => {
@@ -1054,7 +1059,10 @@ mod add_network {
         let result = config
             .add_network(
                 "new_network",
-                Some(NetworkInfo::NodeConfig((secret_key.public_key(), nodes))),
+                Some(NetworkInfo::NodeConfig(NodeConfig::new(
+                    secret_key.public_key(),
+                    nodes,
+                ))),
             )
             .await;
 
@@ -1071,14 +1079,14 @@ mod add_network {
         assert_eq!(network_name, "new_network");
         match network_info {
             NetworkInfo::NodeConfig(node_config) => {
-                assert_eq!(node_config.1.len(), 2);
+                assert_eq!(node_config.contacts.len(), 2);
 
                 let node: SocketAddr = "10.0.0.1:12000".parse()?;
-                assert_eq!(node_config.1.get(&node), Some(&node));
+                assert_eq!(node_config.contacts.get(&node), Some(&node));
                 let node: SocketAddr = "10.0.0.2:12000".parse()?;
-                assert_eq!(node_config.1.get(&node), Some(&node));
+                assert_eq!(node_config.contacts.get(&node), Some(&node));
 
-                let public_key = node_config.0;
+                let public_key = node_config.genesis_key;
                 assert_eq!(hex::encode(public_key.to_bytes()), genesis_key);
             }
             NetworkInfo::ConnInfoLocation(_) => {
@@ -141,11 +141,11 @@ pub fn node_join(
         sn_launch_tool_args.push(verbosity_arg);
     }
     sn_launch_tool_args.push("--genesis-key".to_string());
-    let genesis_key = hex::encode(node_config.0.to_bytes());
+    let genesis_key = hex::encode(node_config.genesis_key.to_bytes());
     sn_launch_tool_args.push(genesis_key);
 
     sn_launch_tool_args.push("--hard-coded-contacts".to_string());
-    let contacts = &node_config.1;
+    let contacts = &node_config.contacts;
     let contacts_list = contacts
         .iter()
         .map(|c| c.to_string())
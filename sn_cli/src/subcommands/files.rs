@@ -10,7 +10,7 @@ use super::{
     files_get::{process_get_command, FileExistsAction, ProgressIndicator},
     helpers::{
         gen_processed_files_table, get_from_arg_or_stdin, get_from_stdin, get_target_url, if_tty,
-        notice_dry_run, parse_stdin_arg, pluralize, serialise_output,
+        notice_dry_run, parse_stdin_arg, pluralize, serialise_output, symlink_policy_from_args,
     },
     OutputFmt,
 };
@@ -20,7 +20,7 @@ use color_eyre::{eyre::bail, eyre::eyre, Result};
 use comfy_table::Table;
 use serde::Serialize;
 use sn_api::{
-    files::{FilesMap, ProcessedFiles},
+    files::{CacheControl, FilesMap, ProcessedFiles, SymlinkPolicy},
     nrs::VersionHash,
     resolver::SafeData,
     Safe, SafeUrl, XorUrl,
@@ -107,9 +107,12 @@ pub enum FilesSubCommands {
         /// Recursively upload folders and files found in the source location
         #[structopt(short = "r", long = "recursive")]
         recursive: bool,
-        /// Follow symlinks
+        /// Follow symlinks, same as '--symlink-policy follow-target'
         #[structopt(short = "l", long = "follow-links")]
         follow_links: bool,
+        /// How to handle symlinks found in the source location: store the symlink itself, store the file or folder it points to, or leave it out (default is 'store-as-link')
+        #[structopt(long = "symlink-policy", possible_values = &["store-as-link", "follow-target", "skip"], conflicts_with = "follow-links")]
+        symlink_policy: Option<SymlinkPolicy>,
     },
     /// Get a file or folder from the SAFE Network
     Get {
@@ -137,15 +140,21 @@ pub enum FilesSubCommands {
         /// Recursively sync folders and files found in the source location
         #[structopt(short = "r", long = "recursive")]
         recursive: bool,
-        /// Follow symlinks
+        /// Follow symlinks, same as '--symlink-policy follow-target'
         #[structopt(short = "l", long = "follow-links")]
         follow_links: bool,
+        /// How to handle symlinks found in the source location: store the symlink itself, store the file or folder it points to, or leave it out (default is 'store-as-link')
+        #[structopt(long = "symlink-policy", possible_values = &["store-as-link", "follow-target", "skip"], conflicts_with = "follow-links")]
+        symlink_policy: Option<SymlinkPolicy>,
         /// Delete files found at the target FilesContainer that are not in the source location. This is only allowed when --recursive is passed as well
         #[structopt(short = "d", long = "delete")]
         delete: bool,
         /// Automatically update the NRS name to link to the new version of the FilesContainer. This is only allowed if an NRS URL was provided, and if the NRS name is currently linked to a specific version of the FilesContainer
         #[structopt(short = "u", long = "update-nrs")]
         update_nrs: bool,
+        /// Also report how each entry of the FilesContainer changed: its old and new size, modification time and link, and which of its metadata fields changed
+        #[structopt(long = "details")]
+        details: bool,
     },
     #[structopt(name = "add")]
     /// Add a file to an existing FilesContainer on the network
@@ -166,9 +175,15 @@ pub enum FilesSubCommands {
         /// Overwrite the file on the FilesContainer if there already exists a file with the same name
         #[structopt(short = "f", long = "force")]
         force: bool,
-        /// Follow symlinks
+        /// Follow symlinks, same as '--symlink-policy follow-target'
         #[structopt(short = "l", long = "follow-links")]
         follow_links: bool,
+        /// How to handle symlinks found in the source location: store the symlink itself, store the file or folder it points to, or leave it out (default is 'store-as-link')
+        #[structopt(long = "symlink-policy", possible_values = &["store-as-link", "follow-target", "skip"], conflicts_with = "follow-links")]
+        symlink_policy: Option<SymlinkPolicy>,
+        /// Caching hints for gateways and clients to apply to the file, in the form of a Cache-Control header, e.g. 'max-age=3600, immutable'
+        #[structopt(long = "cache-control")]
+        cache_control: Option<CacheControl>,
     },
     #[structopt(name = "rm")]
     /// Remove a file from an existing FilesContainer on the network
@@ -210,13 +225,15 @@ pub async fn files_commander(
             dst,
             recursive,
             follow_links,
+            symlink_policy,
         } => {
+            let symlink_policy = symlink_policy_from_args(follow_links, symlink_policy);
             // create FilesContainer from a given path to local files/folders
             if safe.dry_run_mode && OutputFmt::Pretty == output_fmt {
                 notice_dry_run();
             }
             let (files_container_xorurl, processed_files, _) = safe
-                .files_container_create_from(&location, dst.as_deref(), recursive, follow_links)
+                .files_container_create_from(&location, dst.as_deref(), recursive, symlink_policy)
                 .await?;
 
             // Now let's just print out a list of the files uploaded/processed
@@ -240,26 +257,30 @@ pub async fn files_commander(
             target,
             recursive,
             follow_links,
+            symlink_policy,
             delete,
             update_nrs,
+            details,
         } => {
+            let symlink_policy = symlink_policy_from_args(follow_links, symlink_policy);
             let target = get_from_arg_or_stdin(target, None)?;
             let mut target_url = get_target_url(&target)?;
             if safe.dry_run_mode && OutputFmt::Pretty == output_fmt {
                 notice_dry_run();
             }
             // Update the FilesContainer on the Network
-            let (content, processed_files) = safe
-                .files_container_sync(
+            let report = safe
+                .files_container_sync_report(
                     &location,
                     &target_url.to_string(),
                     recursive,
-                    follow_links,
+                    symlink_policy,
                     delete,
                     update_nrs,
                 )
                 .await?;
-            let version = content.map(|(version, _)| version);
+            let processed_files = report.processed_files;
+            let version = report.version.map(|(version, _)| version);
 
             // Now let's just print out a list of the files synced/processed
             let (table, success_count) = gen_processed_files_table(&processed_files, true);
@@ -273,6 +294,11 @@ pub async fn files_commander(
                         version_str, target_url
                     );
                     println!("{table}");
+                    if details {
+                        for (path, diff) in &report.entry_changes {
+                            println!("{}: {}", path, diff);
+                        }
+                    }
                 } else if !processed_files.is_empty() {
                     println!(
                         "No changes were made to FilesContainer ({}) at \"{}\"",
@@ -286,6 +312,16 @@ pub async fn files_commander(
                         version_str, target
                     );
                 }
+            } else if details {
+                let mut url = target_url.clone();
+                url.set_content_version(version);
+                println!(
+                    "{}",
+                    serialise_output(
+                        &(url.to_string(), &processed_files, &report.entry_changes),
+                        output_fmt
+                    )
+                );
             } else {
                 print_serialized_output(target.to_string(), version, &processed_files, output_fmt);
             }
@@ -296,8 +332,11 @@ pub async fn files_commander(
             target,
             update_nrs,
             follow_links,
+            symlink_policy,
             force,
+            cache_control,
         } => {
+            let symlink_policy = symlink_policy_from_args(follow_links, symlink_policy);
             // Validate that location and target are not both "", ie stdin.
             let target_url = target.unwrap_or_else(|| "".to_string());
             if target_url.is_empty() && location.is_empty() {
@@ -316,10 +355,10 @@ pub async fn files_commander(
                 if location.is_empty() {
                     let file_content = get_from_stdin(Some("...awaiting file's content to add from STDIN"))?;
                     // Update the FilesContainer on the Network
-                    safe.files_container_add_from_raw(Bytes::from(file_content), &target_url, force, update_nrs).await?
+                    safe.files_container_add_from_raw(Bytes::from(file_content), &target_url, force, update_nrs, cache_control).await?
                 } else {
                     // Update the FilesContainer on the Network
-                    safe.files_container_add(&location, &target_url, force, update_nrs, follow_links).await?
+                    safe.files_container_add(&location, &target_url, force, update_nrs, symlink_policy, cache_control).await?
                 };
 
             // Now let's just print out a list of the files synced/processed
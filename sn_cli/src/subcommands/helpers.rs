@@ -13,7 +13,7 @@ use comfy_table::Table;
 use num_traits::Float;
 use serde::ser::Serialize;
 use sn_api::{
-    files::{FilesMapChange, ProcessedFiles},
+    files::{FilesMapChange, ProcessedFiles, SymlinkPolicy},
     multimap::Multimap,
     nrs::NrsMap,
     wallet::Dbc,
@@ -122,6 +122,40 @@ pub fn processed_files_err_report<T: std::fmt::Display>(err: &T) -> (String, Str
     ("E".to_string(), format!("<{}>", err))
 }
 
+// Sign and link to report for a change made to a FilesContainer item,
+// symlinks left out as per the symlink policy being reported with an 'S' sign
+pub fn processed_file_report(change: &FilesMapChange) -> (String, String) {
+    match change {
+        FilesMapChange::Failed(err) => processed_files_err_report(&err),
+        FilesMapChange::Added(link) => ("+".to_string(), link.clone()),
+        FilesMapChange::Updated(link) => ("*".to_string(), link.clone()),
+        FilesMapChange::Removed(link) => ("-".to_string(), link.clone()),
+        FilesMapChange::Symlink {
+            change: Some(change),
+            ..
+        } => processed_file_report(change),
+        FilesMapChange::Symlink {
+            policy,
+            change: None,
+        } => (
+            "S".to_string(),
+            format!("<symlink skipped as per '{}' policy>", policy),
+        ),
+    }
+}
+
+// Symlink policy as per the '--follow-links' and '--symlink-policy' args
+pub fn symlink_policy_from_args(
+    follow_links: bool,
+    symlink_policy: Option<SymlinkPolicy>,
+) -> SymlinkPolicy {
+    if follow_links {
+        SymlinkPolicy::FollowTarget
+    } else {
+        symlink_policy.unwrap_or_default()
+    }
+}
+
 pub fn gen_processed_files_table(
     processed_files: &ProcessedFiles,
     show_change_sign: bool,
@@ -133,12 +167,7 @@ pub fn gen_processed_files_table(
             success_count += 1;
         }
 
-        let (change_sign, link) = match change {
-            FilesMapChange::Failed(err) => processed_files_err_report(&err),
-            FilesMapChange::Added(link) => ("+".to_string(), link.clone()),
-            FilesMapChange::Updated(link) => ("*".to_string(), link.clone()),
-            FilesMapChange::Removed(link) => ("-".to_string(), link.clone()),
-        };
+        let (change_sign, link) = processed_file_report(change);
 
         if show_change_sign {
             table.add_row(&vec![change_sign, file_name.display().to_string(), link]);
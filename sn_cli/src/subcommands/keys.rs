@@ -7,10 +7,12 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{helpers::serialise_output, OutputFmt};
-use crate::operations::auth_and_connect::{get_credentials_file_path, read_credentials};
+use crate::operations::auth_and_connect::{
+    credentials_passphrase, get_credentials_file_path, read_credentials,
+};
 use crate::operations::config::Config;
 use color_eyre::{eyre::WrapErr, Result};
-use sn_api::{resolver::SafeUrl, Keypair, Safe, XorName};
+use sn_api::{credentials::Credentials, resolver::SafeUrl, Keypair, Safe, XorName};
 use structopt::StructOpt;
 
 #[derive(StructOpt, Debug)]
@@ -39,7 +41,7 @@ pub async fn key_commander(
 ) -> Result<()> {
     match cmd {
         KeysSubCommands::Show { show_sk } => {
-            match read_credentials(safe, config)? {
+            match read_credentials(config)? {
                 (file_path, Some(keypair)) => {
                     let xorname = XorName::from(keypair.public_key());
                     let xorurl = SafeUrl::encode_safekey(xorname, safe.xorurl_base)?;
@@ -63,8 +65,9 @@ pub async fn key_commander(
 
             if for_cli {
                 let (_, path) = get_credentials_file_path(config)?;
-                safe.serialize_keypair(&keypair, &path)
-                    .wrap_err("Unable to serialize keypair to file")?;
+                Credentials::new(keypair, Some("cli".to_string()))
+                    .write(&path, credentials_passphrase().as_deref())
+                    .wrap_err("Unable to write keypair to the credentials file")?;
                 println!("Keypair saved at {}", path.display());
                 println!("Safe CLI now has write access to the network");
             }
@@ -158,7 +161,7 @@ mod create_command {
         assert!(result.is_ok());
         credentials_file.assert(predicate::path::is_file());
 
-        let (_, keypair) = read_credentials(&safe, &config)?;
+        let (_, keypair) = read_credentials(&config)?;
         let keypair =
             keypair.ok_or_else(|| eyre!("The command should have generated a keypair"))?;
         match keypair {
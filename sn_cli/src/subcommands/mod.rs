@@ -22,6 +22,7 @@ pub mod update;
 pub mod wallet;
 pub mod xorurl;
 
+use sn_api::files::SymlinkPolicy;
 use structopt::{clap::AppSettings, StructOpt};
 
 #[derive(PartialEq, Clone, Copy, Debug)]
@@ -132,9 +133,12 @@ pub enum SubCommands {
         /// Recursively crawl folders and files found in the location
         #[structopt(short = "r", long = "recursive")]
         recursive: bool,
-        /// Follow symlinks
+        /// Follow symlinks, same as '--symlink-policy follow-target'
         #[structopt(short = "l", long = "follow-links")]
         follow_links: bool,
+        /// How to handle symlinks found in the location: store the symlink itself, store the file or folder it points to, or leave it out (default is 'store-as-link')
+        #[structopt(long = "symlink-policy", possible_values = &["store-as-link", "follow-target", "skip"], conflicts_with = "follow-links")]
+        symlink_policy: Option<SymlinkPolicy>,
     },
     #[structopt(
         name = "update",
@@ -8,9 +8,10 @@
 
 use crate::operations::config::{Config, NetworkInfo};
 use color_eyre::{eyre::bail, eyre::eyre, Result};
-use sn_api::PublicKey;
+use sn_api::{NodeConfig, PublicKey, Safe};
 use std::collections::BTreeSet;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use structopt::StructOpt;
 use tracing::debug;
 
@@ -51,6 +52,15 @@ pub enum NetworksSubCommands {
         /// Network to remove
         network_name: String,
     },
+    #[structopt(name = "snapshot")]
+    /// Show a snapshot of the network, as exported by an app or from the admin socket of a node
+    Snapshot {
+        /// Location of the snapshot
+        location: PathBuf,
+        /// Location of a later snapshot, to show how the network differs in it instead
+        #[structopt(long = "diff")]
+        later: Option<PathBuf>,
+    },
 }
 
 pub async fn networks_commander(
@@ -122,7 +132,7 @@ pub async fn networks_commander(
             let net_info = config
                 .add_network(
                     &network_name,
-                    Some(NetworkInfo::NodeConfig((genesis_key, set))),
+                    Some(NetworkInfo::NodeConfig(NodeConfig::new(genesis_key, set))),
                 )
                 .await?;
             println!(
@@ -133,6 +143,16 @@ pub async fn networks_commander(
         Some(NetworksSubCommands::Remove { network_name }) => {
             config.remove_network(&network_name).await?
         }
+        Some(NetworksSubCommands::Snapshot { location, later }) => {
+            let snapshot = Safe::load_network_snapshot(&location)?;
+            match later {
+                Some(later) => {
+                    let later = Safe::load_network_snapshot(&later)?;
+                    print!("{}", snapshot.diff(&later));
+                }
+                None => print!("{}", snapshot),
+            }
+        }
         None => config.print_networks().await,
     }
 
@@ -146,6 +166,7 @@ mod networks_set_command {
     use assert_fs::prelude::*;
     use color_eyre::{eyre::eyre, Result};
     use predicates::prelude::*;
+    use sn_api::NodeConfig;
     use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 
     #[tokio::test]
@@ -187,7 +208,11 @@ mod networks_set_command {
             .ok_or_else(|| eyre!("failed to obtain item from networks list"))?;
         assert_eq!(network_name, "new_network");
         match network_info {
-            NetworkInfo::NodeConfig((public_key, contacts)) => {
+            NetworkInfo::NodeConfig(NodeConfig {
+                genesis_key: public_key,
+                contacts,
+                ..
+            }) => {
                 assert_eq!(contacts.len(), 2);
 
                 let node: SocketAddr = "127.0.0.1:12000".parse()?;
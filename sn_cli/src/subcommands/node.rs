@@ -8,7 +8,7 @@
 
 use crate::operations::{config::Config, config::NetworkLauncher, node::*};
 use color_eyre::{eyre::eyre, Help, Result};
-use sn_api::PublicKey;
+use sn_api::{NodeConfig, PublicKey};
 use std::{collections::BTreeSet, net::SocketAddr, path::PathBuf};
 use structopt::StructOpt;
 use tracing::debug;
@@ -183,7 +183,7 @@ pub async fn node_commander(
                     for contact in contacts {
                         set.insert(contact);
                     }
-                    (genesis_key, set)
+                    NodeConfig::new(genesis_key, set)
                 } else {
                     return Err(eyre!(
                         "If the --contacts-list argument is used, the genesis key must also be supplied."
@@ -7,11 +7,14 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
-    helpers::{gen_processed_files_table, get_from_arg_or_stdin, serialise_output, xorname_to_hex},
+    helpers::{
+        gen_processed_files_table, get_from_arg_or_stdin, processed_file_report, serialise_output,
+        symlink_policy_from_args, xorname_to_hex,
+    },
     OutputFmt,
 };
 use color_eyre::{eyre::eyre, Result};
-use sn_api::{files::FilesMapChange, PublicKey, Safe, SafeUrl, XorName, XorUrlBase};
+use sn_api::{files::SymlinkPolicy, PublicKey, Safe, SafeUrl, XorName, XorUrlBase};
 use structopt::StructOpt;
 
 // Defines subcommands of 'xorurl'
@@ -92,7 +95,8 @@ pub async fn xorurl_commander(
 pub async fn xorurl_of_files(
     location: Option<String>,
     recursive: bool,
-    follow_symlinks: bool,
+    follow_links: bool,
+    symlink_policy: Option<SymlinkPolicy>,
     output_fmt: OutputFmt,
     xorurl_base: XorUrlBase,
 ) -> Result<()> {
@@ -100,8 +104,9 @@ pub async fn xorurl_of_files(
     let safe = Safe::dry_runner(Some(xorurl_base));
 
     let location = get_from_arg_or_stdin(location, Some("...awaiting location path from stdin"))?;
+    let symlink_policy = symlink_policy_from_args(follow_links, symlink_policy);
     let (_, processed_files, _) = safe
-        .files_container_create_from(&location, None, recursive, follow_symlinks)
+        .files_container_create_from(&location, None, recursive, symlink_policy)
         .await?;
 
     // Now let's just print out a list of the xorurls
@@ -116,13 +121,8 @@ pub async fn xorurl_of_files(
     } else {
         let mut list = Vec::<(String, String)>::new();
         for (file_name, change) in processed_files {
-            let link = match change {
-                FilesMapChange::Failed(err) => format!("<{}>", err),
-                FilesMapChange::Added(link)
-                | FilesMapChange::Updated(link)
-                | FilesMapChange::Removed(link) => link,
-            };
-
+            // the error is reported instead of the link of a failed change, even of a symlink
+            let (_, link) = processed_file_report(&change);
             list.push((file_name.display().to_string(), link));
         }
         println!("{}", serialise_output(&list, output_fmt));
@@ -0,0 +1,90 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use criterion::{criterion_group, criterion_main, Criterion, SamplingMode, Throughput};
+use eyre::Result;
+use sn_client::{utils::test_utils::read_network_conn_info, Client, ClientConfig, ReadAhead};
+use sn_interface::types::utils::random_bytes;
+use sn_interface::types::{BytesAddress, Scope};
+use tokio::runtime::Runtime;
+
+const FILE_SIZE: usize = 20 * 1024 * 1024;
+
+async fn new_client() -> Result<Client> {
+    let (genesis_key, bootstrap_nodes) = read_network_conn_info()?;
+    let config = ClientConfig::new(None, None, genesis_key, None, None, None, None).await;
+    Ok(Client::new(config, bootstrap_nodes, None, None).await?)
+}
+
+/// This bench requires a network already set up
+async fn upload_file() -> Result<BytesAddress> {
+    let client = new_client().await?;
+    Ok(client
+        .upload(random_bytes(FILE_SIZE), Scope::Public)
+        .await?)
+}
+
+/// This bench requires a network already set up
+async fn read_stream(address: BytesAddress, read_ahead: ReadAhead) -> Result<()> {
+    // a new client each time, so no chunks are read from the cache of a previous one
+    let client = new_client().await?;
+    let mut reader = client.read_stream(address, read_ahead).await?;
+
+    let mut read = 0;
+    while let Some(chunk) = reader.next_chunk().await? {
+        read += chunk.len();
+    }
+    assert_eq!(read, FILE_SIZE);
+
+    Ok(())
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let address = match runtime.block_on(upload_file()) {
+        Ok(address) => address,
+        Err(error) => {
+            println!("read stream bench failed to upload file: {:?}", error);
+            return;
+        }
+    };
+
+    let mut group = c.benchmark_group("read-stream-sampling");
+    group.measurement_time(std::time::Duration::from_secs(90));
+    group.sampling_mode(SamplingMode::Flat);
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(FILE_SIZE as u64));
+
+    // sustained throughput of sequential reads, fetching chunks only on demand or ahead
+    let read_aheads = [
+        ("no read-ahead", ReadAhead::none()),
+        ("default read-ahead", ReadAhead::default()),
+        (
+            "wide read-ahead",
+            ReadAhead {
+                min_chunks: 8,
+                max_chunks: 32,
+            },
+        ),
+    ];
+    for (name, read_ahead) in read_aheads {
+        group.bench_function(format!("read stream 20mb with {}", name), |b| {
+            b.to_async(&runtime).iter(|| async {
+                match read_stream(address, read_ahead).await {
+                    Ok(_) => {}
+                    Err(error) => println!("20mb read stream bench failed with {:?}", error),
+                }
+            });
+        });
+    }
+
+    group.finish()
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
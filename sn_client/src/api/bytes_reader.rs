@@ -0,0 +1,338 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Client;
+use crate::{Error, QuerySizeClass, Result};
+
+use bytes::Bytes;
+use futures::FutureExt;
+use self_encryption::{DataMap, EncryptedChunk};
+use std::collections::{BTreeMap, VecDeque};
+use tokio::task::{self, JoinHandle};
+
+/// How many chunks a [`BytesReader`] fetches ahead of the one being read.
+///
+/// The reader starts by fetching `min_chunks` ahead. Each time the chunk to be read
+/// isn't retrieved yet, i.e. the reader is consumed faster than chunks are fetched,
+/// the window is doubled, up to `max_chunks`. When chunks keep being retrieved before
+/// they're read, the window shrinks back one chunk at a time, down to `min_chunks`,
+/// so slow consumers don't hold more chunks in memory than they need.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReadAhead {
+    /// Number of chunks fetched ahead when the reader is created, and at the least
+    pub min_chunks: usize,
+    /// Maximum number of chunks fetched ahead
+    pub max_chunks: usize,
+}
+
+impl ReadAhead {
+    /// No chunks are fetched ahead, each chunk being fetched only once it's read.
+    pub fn none() -> Self {
+        Self {
+            min_chunks: 0,
+            max_chunks: 0,
+        }
+    }
+}
+
+impl Default for ReadAhead {
+    fn default() -> Self {
+        Self {
+            min_chunks: 2,
+            max_chunks: 8,
+        }
+    }
+}
+
+/// Reader of the [`Bytes`] stored at some address, chunk by chunk, as obtained with
+/// [`Client::read_stream`]. The following chunks are fetched ahead while the current one
+/// is being consumed, as per its [`ReadAhead`] settings.
+#[derive(Debug)]
+pub struct BytesReader {
+    client: Client,
+    content: Content,
+    // number of bytes left to be read
+    remaining: usize,
+}
+
+#[derive(Debug)]
+enum Content {
+    // the bytes of a SmallFile, which are all read at once
+    Small(Option<Bytes>),
+    SelfEncrypted(ChunksPipeline),
+}
+
+#[derive(Debug)]
+struct ChunksPipeline {
+    data_map: DataMap,
+    read_ahead: ReadAhead,
+    // current number of chunks fetched ahead of the one being read
+    window: usize,
+    // number of chunks read in a row which were already retrieved
+    ready_streak: usize,
+    // index of the next chunk to be read
+    next_read: usize,
+    // index of the next chunk to be fetched
+    next_fetch: usize,
+    // offset of the next chunk to be read in the self-encrypted data
+    position: usize,
+    in_flight: VecDeque<(usize, JoinHandle<Result<EncryptedChunk>>)>,
+    fetched: BTreeMap<usize, EncryptedChunk>,
+}
+
+impl BytesReader {
+    pub(crate) fn small(client: Client, bytes: Bytes) -> Self {
+        Self {
+            client,
+            remaining: bytes.len(),
+            content: Content::Small(Some(bytes)),
+        }
+    }
+
+    pub(crate) fn self_encrypted(
+        client: Client,
+        data_map: DataMap,
+        size: Option<usize>,
+        read_ahead: ReadAhead,
+    ) -> Self {
+        let read_ahead = ReadAhead {
+            min_chunks: read_ahead.min_chunks.min(read_ahead.max_chunks),
+            ..read_ahead
+        };
+        Self {
+            client,
+            // the padding of padded data is never read
+            remaining: size.unwrap_or_else(|| data_map.file_size()),
+            content: Content::SelfEncrypted(ChunksPipeline {
+                data_map,
+                read_ahead,
+                window: read_ahead.min_chunks,
+                ready_streak: 0,
+                next_read: 0,
+                next_fetch: 0,
+                position: 0,
+                in_flight: VecDeque::new(),
+                fetched: BTreeMap::new(),
+            }),
+        }
+    }
+
+    /// Number of bytes left to be read.
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// Current number of chunks fetched ahead of the one being read.
+    pub fn read_ahead_window(&self) -> usize {
+        match &self.content {
+            Content::Small(_) => 0,
+            Content::SelfEncrypted(pipeline) => pipeline.window,
+        }
+    }
+
+    /// Reads the content of the next chunk, or `None` once all the bytes were read.
+    #[instrument(skip(self), level = "trace")]
+    pub async fn next_chunk(&mut self) -> Result<Option<Bytes>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        let mut bytes = match &mut self.content {
+            Content::Small(bytes) => match bytes.take() {
+                Some(bytes) => bytes,
+                None => return Ok(None),
+            },
+            Content::SelfEncrypted(pipeline) => pipeline.read_next(&self.client).await?,
+        };
+
+        bytes.truncate(self.remaining);
+        self.remaining -= bytes.len();
+
+        Ok(Some(bytes))
+    }
+
+    /// Reads all the bytes left, chunk by chunk.
+    pub async fn read_to_end(&mut self) -> Result<Bytes> {
+        let mut bytes = Vec::with_capacity(self.remaining);
+        while let Some(chunk) = self.next_chunk().await? {
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(Bytes::from(bytes))
+    }
+}
+
+impl ChunksPipeline {
+    async fn read_next(&mut self, client: &Client) -> Result<Bytes> {
+        let infos = self.data_map.infos();
+        let index = self.next_read;
+        let len =
+            infos
+                .get(index)
+                .map(|info| info.src_size)
+                .ok_or(Error::NotEnoughChunksRetrieved {
+                    expected: index + 1,
+                    retrieved: index,
+                })?;
+
+        let seek = self_encryption::seek_info(self.data_map.file_size(), self.position, len);
+        let range = seek.index_range;
+
+        let mut stalled = false;
+        for needed in range.start..range.end + 1 {
+            self.fill(client);
+            while !self.fetched.contains_key(&needed) {
+                stalled |= self.retrieve_next().await?;
+                self.fill(client);
+            }
+        }
+        self.adjust_window(stalled);
+        self.fill(client);
+
+        let encrypted_chunks: Vec<_> = (range.start..range.end + 1)
+            .filter_map(|i| self.fetched.get(&i).cloned())
+            .collect();
+        let bytes = self_encryption::decrypt_range(
+            &self.data_map,
+            &encrypted_chunks,
+            seek.relative_pos,
+            len,
+        )?;
+
+        self.next_read += 1;
+        self.position += len;
+        let next_read = self.next_read;
+        self.fetched.retain(|i, _| *i + 1 >= next_read);
+
+        Ok(bytes)
+    }
+
+    // Waits for the oldest chunk in flight, returning whether we had to wait for it.
+    async fn retrieve_next(&mut self) -> Result<bool> {
+        let (index, mut handle) = match self.in_flight.pop_front() {
+            Some(in_flight) => in_flight,
+            None => {
+                return Err(Error::NotEnoughChunksRetrieved {
+                    expected: self.next_fetch + 1,
+                    retrieved: self.next_fetch,
+                })
+            }
+        };
+
+        let (result, stalled) = match (&mut handle).now_or_never() {
+            Some(result) => (result, false),
+            None => (handle.await, true),
+        };
+
+        // as when reading all chunks at once, failing to join is not retrieving the chunk
+        let chunk = result.map_err(|_| Error::NotEnoughChunksRetrieved {
+            expected: index + 1,
+            retrieved: index,
+        })??;
+        let _ = self.fetched.insert(index, chunk);
+
+        Ok(stalled)
+    }
+
+    // Fetches the chunk being read and the ones ahead of it, up to the window.
+    fn fill(&mut self, client: &Client) {
+        let infos = self.data_map.infos();
+        let until = (self.next_read + 1 + self.window).min(infos.len());
+        while self.next_fetch < until {
+            let info = infos[self.next_fetch].clone();
+            let client = client.clone();
+            let handle = task::spawn(async move {
+                let size_class = QuerySizeClass::of_chunk(info.src_size);
+                let chunk = client
+                    .get_chunk_of_size_class(&info.dst_hash, size_class)
+                    .await?;
+                Ok(EncryptedChunk {
+                    index: info.index,
+                    content: chunk.value().clone(),
+                })
+            });
+            self.in_flight.push_back((self.next_fetch, handle));
+            self.next_fetch += 1;
+        }
+    }
+
+    fn adjust_window(&mut self, stalled: bool) {
+        let ReadAhead {
+            min_chunks,
+            max_chunks,
+        } = self.read_ahead;
+
+        if stalled {
+            self.ready_streak = 0;
+            self.window = (self.window * 2).max(1).min(max_chunks);
+        } else {
+            self.ready_streak += 1;
+            if self.ready_streak > self.window && self.window > min_chunks {
+                self.ready_streak = 0;
+                self.window -= 1;
+            }
+        }
+
+        trace!(
+            "Read-ahead window of {} chunks after reading chunk {} (stalled: {})",
+            self.window,
+            self.next_read,
+            stalled
+        );
+    }
+}
+
+impl Drop for ChunksPipeline {
+    fn drop(&mut self) {
+        // chunks fetched ahead are no longer needed if the reader is dropped early
+        for (_, handle) in &self.in_flight {
+            handle.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReadAhead;
+    use crate::utils::test_utils::{create_test_client, init_logger};
+    use sn_interface::types::{utils::random_bytes, Scope};
+
+    use eyre::Result;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn read_stream_with_read_ahead() -> Result<()> {
+        init_logger();
+        let client = create_test_client().await?;
+
+        let size = 10 * 1024 * 1024;
+        let bytes = random_bytes(size);
+        let address = client.upload(bytes.clone(), Scope::Public).await?;
+
+        for read_ahead in [ReadAhead::none(), ReadAhead::default()] {
+            let mut reader = client.read_stream(address, read_ahead).await?;
+            assert_eq!(reader.remaining(), size);
+
+            let mut read = Vec::with_capacity(size);
+            while let Some(chunk) = reader.next_chunk().await? {
+                assert!(reader.read_ahead_window() <= read_ahead.max_chunks);
+                read.extend_from_slice(&chunk);
+            }
+
+            assert_eq!(read, bytes);
+            assert_eq!(reader.remaining(), 0);
+        }
+
+        // SmallFiles are read at once
+        let small = random_bytes(1024);
+        let address = client.upload(small.clone(), Scope::Private).await?;
+        let mut reader = client.read_stream(address, ReadAhead::default()).await?;
+        assert_eq!(reader.read_to_end().await?, small);
+        assert!(reader.next_chunk().await?.is_none());
+
+        Ok(())
+    }
+}
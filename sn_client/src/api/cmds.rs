@@ -7,42 +7,177 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::Client;
-use crate::Error;
+use crate::{bandwidth::Lane, Error, ErrorMsg, OperationKind};
 use backoff::{backoff::Backoff, ExponentialBackoff};
 use bytes::Bytes;
 use sn_interface::messaging::{
-    data::{DataCmd, ServiceMsg},
-    ServiceAuth, WireMsg,
+    data::{CmdAckLevel, DataCmd, ServiceMsg, StorageReceipt},
+    ServiceAuth, SigningDomain, WireMsg,
 };
 use sn_interface::types::{PublicKey, Signature};
-use tokio::time::Duration;
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    sync::Mutex,
+    task::JoinHandle,
+    time::{Duration, Instant},
+};
 use xor_name::XorName;
 
 const MAX_RETRY_COUNT: f32 = 5.0;
 
+/// Sequence numbers of the last ordered cmds sent to each data, shared by the copies of a client.
+///
+/// The cmds of a stream are sent one at a time, its lock being held while one of them is sent.
+/// They're numbered from the time they're sent, so the numbers keep growing when the client is
+/// started again.
+#[derive(Debug, Default)]
+pub(crate) struct OrderedCmdStreams(std::sync::Mutex<BTreeMap<XorName, Arc<Mutex<u64>>>>);
+
+impl OrderedCmdStreams {
+    fn stream(&self, dst: XorName) -> Arc<Mutex<u64>> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(dst)
+            .or_default()
+            .clone()
+    }
+}
+
+// Sequence number of the ordered cmd sent after the one with the given number
+fn next_seq(last_seq: u64) -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64;
+    now.max(last_seq + 1)
+}
+
+/// Delivery of a cmd sent with [`Client::send_cmd_in_background`], resolving once the Elders
+/// acknowledged it as far as its ack level, or with the error sending it failed with, e.g. the
+/// acks timing out.
+///
+/// Dropping it doesn't stop the cmd from being sent.
+#[derive(Debug)]
+pub struct CmdDelivery {
+    dst: XorName,
+    task: JoinHandle<Result<Option<StorageReceipt>, Error>>,
+}
+
+impl CmdDelivery {
+    /// Name of the data the cmd is sent to.
+    pub fn dst(&self) -> XorName {
+        self.dst
+    }
+}
+
+impl Future for CmdDelivery {
+    type Output = Result<Option<StorageReceipt>, Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let dst = self.dst;
+        Pin::new(&mut self.task)
+            .poll(cx)
+            .map(|result| result.unwrap_or(Err(Error::CmdSendingAborted(dst))))
+    }
+}
+
 impl Client {
+    /// Send a Cmd to the network in the background, as [`Client::send_cmd_with_ack_level`]
+    /// does, returning right away with its [`CmdDelivery`] to be awaited for the cmd to be
+    /// acknowledged, or to have failed.
+    #[instrument(skip(self), level = "debug")]
+    pub fn send_cmd_in_background(&self, cmd: DataCmd, ack_level: CmdAckLevel) -> CmdDelivery {
+        let dst = cmd.dst_name();
+        let client = self.clone();
+        let task = tokio::spawn(async move {
+            client
+                .send_cmd_with_retry_count(cmd, ack_level, false, MAX_RETRY_COUNT)
+                .await
+        });
+
+        CmdDelivery { dst, task }
+    }
+
     /// Send a Cmd to the network and await a response.
     /// Cmds are not retried if the timeout is hit.
     #[instrument(skip(self), level = "debug")]
     pub async fn send_cmd_without_retry(&self, cmd: DataCmd) -> Result<(), Error> {
-        self.send_cmd_with_retry_count(cmd, 1.0).await
+        let _receipt = self
+            .send_cmd_with_retry_count(cmd, CmdAckLevel::default(), false, 1.0)
+            .await?;
+        Ok(())
+    }
+
+    /// Send a Cmd to the network, waiting for its handling to be acknowledged as far as the
+    /// given ack level, from not at all to the data it writes being replicated.
+    /// The storage receipt of the content is returned for cmds storing chunks, unless it's
+    /// sent without waiting for any acknowledgement.
+    /// Cmds are automatically retried using exponential backoff if an error is returned.
+    ///
+    /// When `ordered` is set, the cmd is applied after the ordered cmds sent before it to the
+    /// same data, waiting for them to be sent. Should one of them be retried late, after the
+    /// cmd was applied, it's rejected by the Elders with [`ErrorMsg::CmdOutOfOrder`], rather
+    /// than applied out of order.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn send_cmd_with_ack_level(
+        &self,
+        cmd: DataCmd,
+        ack_level: CmdAckLevel,
+        ordered: bool,
+    ) -> Result<Option<StorageReceipt>, Error> {
+        self.send_cmd_with_retry_count(cmd, ack_level, ordered, MAX_RETRY_COUNT)
+            .await
     }
 
     // Send a Cmd to the network and await a response.
     // Cmds are automatically retried if an error is returned
     // This function is a private helper.
     #[instrument(skip(self), level = "debug")]
-    async fn send_cmd_with_retry_count(&self, cmd: DataCmd, retry_count: f32) -> Result<(), Error> {
+    async fn send_cmd_with_retry_count(
+        &self,
+        cmd: DataCmd,
+        ack_level: CmdAckLevel,
+        ordered: bool,
+        retry_count: f32,
+    ) -> Result<Option<StorageReceipt>, Error> {
+        let started = Instant::now();
         let client_pk = self.public_key();
         let dst_name = cmd.dst_name();
+        let operation = OperationKind::from(&cmd);
 
         let debug_cmd = format!("{:?}", cmd);
 
+        // the stream of ordered cmds to the data is held until the cmd is sent
+        let stream = ordered.then(|| self.ordered_cmd_streams.stream(dst_name));
+        let mut last_seq = match &stream {
+            Some(stream) => Some(stream.lock().await),
+            None => None,
+        };
+        let seq = last_seq.as_mut().map(|last_seq| {
+            **last_seq = next_seq(**last_seq);
+            **last_seq
+        });
+
         let serialised_cmd = {
-            let msg = ServiceMsg::Cmd(cmd);
+            let msg = ServiceMsg::Cmd {
+                cmd,
+                ack_level,
+                seq,
+            };
             WireMsg::serialize_msg_payload(&msg)?
         };
-        let signature = self.keypair.sign(&serialised_cmd);
+        let signature = self
+            .signer
+            .sign(&SigningDomain::Service.signable_bytes(&serialised_cmd))
+            .await?;
 
         let op_limit = self.cmd_timeout;
 
@@ -61,15 +196,18 @@ impl Client {
         let _ = span.enter();
 
         let mut attempt = 1.0;
-        loop {
+        let mut retries = 0;
+        let res = loop {
             debug!("Attempting {:?} (attempt #{})", debug_cmd, attempt);
 
             let res = self
-                .send_signed_cmd(
+                .send_signed_cmd_in_lane(
                     dst_name,
                     client_pk,
                     serialised_cmd.clone(),
                     signature.clone(),
+                    ack_level,
+                    Lane::from(operation),
                 )
                 .await;
 
@@ -78,6 +216,15 @@ impl Client {
                 break Ok(cmd_result);
             }
 
+            // a later cmd was applied already, so the cmd won't ever be
+            if let Err(Error::ErrorCmd {
+                source: ErrorMsg::CmdOutOfOrder { .. },
+                ..
+            }) = &res
+            {
+                break res;
+            }
+
             trace!(
                 "Failed response on {debug_cmd} attempt #{attempt}: {:?}",
                 res
@@ -86,32 +233,84 @@ impl Client {
             attempt += 1.0;
 
             if let Some(delay) = backoff.next_backoff() {
+                // Elders rate limiting us tell us how long to back off for
+                let delay = match &res {
+                    Err(Error::ErrorCmd {
+                        source: ErrorMsg::TryAgainLater { backoff: wait },
+                        ..
+                    }) => delay.max(*wait),
+                    _ => delay,
+                };
                 debug!("Sleeping for {delay:?} before trying cmd {debug_cmd:?} again");
                 tokio::time::sleep(delay).await;
+                retries += 1;
             } else {
                 // we're done trying
                 break res;
             }
-        }
+        };
+
+        self.report_operation(operation, &dst_name, started, retries, &res);
+
+        res
     }
 
     /// Send a signed DataCmd to the network.
     /// This is to be part of a public API, for the user to
     /// provide the serialised and already signed cmd.
+    /// The signature is expected to be made over the [`SigningDomain::Service`] signable bytes
+    /// of the serialised cmd, which is to carry the same `ack_level` as the one given.
+    /// The storage receipt of the content is returned for cmds storing chunks, if the Elders
+    /// returned enough shares of it.
+    /// The cmd is sent in the lane of the bandwidth limits of the data it carries, or in the
+    /// bulk lane if it can't be told from the serialised cmd.
     pub async fn send_signed_cmd(
         &self,
         dst_address: XorName,
         client_pk: PublicKey,
         serialised_cmd: Bytes,
         signature: Signature,
-    ) -> Result<(), Error> {
+        ack_level: CmdAckLevel,
+    ) -> Result<Option<StorageReceipt>, Error> {
+        let lane = match rmp_serde::from_slice(&serialised_cmd) {
+            Ok(ServiceMsg::Cmd { cmd, .. }) => Lane::from(OperationKind::from(&cmd)),
+            _ => Lane::Bulk,
+        };
+        self.send_signed_cmd_in_lane(
+            dst_address,
+            client_pk,
+            serialised_cmd,
+            signature,
+            ack_level,
+            lane,
+        )
+        .await
+    }
+
+    // Send a signed DataCmd to the network, in the given lane of the bandwidth limits.
+    async fn send_signed_cmd_in_lane(
+        &self,
+        dst_address: XorName,
+        client_pk: PublicKey,
+        serialised_cmd: Bytes,
+        signature: Signature,
+        ack_level: CmdAckLevel,
+        lane: Lane,
+    ) -> Result<Option<StorageReceipt>, Error> {
         let auth = ServiceAuth {
             public_key: client_pk,
             signature,
         };
 
         self.session
-            .send_cmd(dst_address, auth, serialised_cmd)
+            .send_cmd(
+                dst_address,
+                auth,
+                serialised_cmd,
+                ack_level,
+                &self.bandwidth,
+                lane,
+            )
             .await
     }
 
@@ -120,6 +319,21 @@ impl Client {
     /// This function is a helper private to this module.
     #[instrument(skip_all, level = "debug", name = "client-api send cmd")]
     pub(crate) async fn send_cmd(&self, cmd: DataCmd) -> Result<(), Error> {
-        self.send_cmd_with_retry_count(cmd, MAX_RETRY_COUNT).await
+        let _receipt = self
+            .send_cmd_with_ack_level(cmd, CmdAckLevel::default(), false)
+            .await?;
+        Ok(())
+    }
+
+    /// Send a DataCmd storing a chunk to the network, returning its storage receipt
+    /// if the Elders returned enough shares of it.
+    /// Cmds are automatically retried using exponential backoff if an error is returned.
+    #[instrument(skip_all, level = "debug", name = "client-api send cmd for receipt")]
+    pub(crate) async fn send_cmd_for_receipt(
+        &self,
+        cmd: DataCmd,
+    ) -> Result<Option<StorageReceipt>, Error> {
+        self.send_cmd_with_ack_level(cmd, CmdAckLevel::default(), false)
+            .await
     }
 }
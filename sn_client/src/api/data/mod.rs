@@ -7,8 +7,10 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 mod pac_man;
+mod padding;
 
-pub(crate) use pac_man::{encrypt_large, to_chunk, DataMapLevel};
+pub(crate) use pac_man::{encrypt_large, encrypt_padded, to_chunk, DataMapLevel};
+pub use padding::PaddingBuckets;
 
 use crate::{Error, Result};
 
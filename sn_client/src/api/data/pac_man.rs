@@ -23,6 +23,9 @@ pub(crate) enum DataMapLevel {
     // resulting from chunking up a previous level data map.
     // This happens when that previous level data map was too big to fit in a chunk itself.
     Additional(DataMap),
+    // Same as `First`, for source data which was padded before being self-encrypted.
+    // Holds the size of the source data, which the padding is stripped down to.
+    PaddedFirst(DataMap, u64),
 }
 
 #[allow(unused)]
@@ -31,7 +34,7 @@ pub(crate) fn encrypt_from_path(
     encryption: Option<&impl Encryption>,
 ) -> Result<(BytesAddress, Vec<Chunk>)> {
     let (data_map, encrypted_chunks) = encrypt_file(path)?;
-    pack(data_map, encrypted_chunks, encryption)
+    pack(DataMapLevel::First(data_map), encrypted_chunks, encryption)
 }
 
 pub(crate) fn encrypt_large(
@@ -39,7 +42,22 @@ pub(crate) fn encrypt_large(
     encryption: Option<&impl Encryption>,
 ) -> Result<(BytesAddress, Vec<Chunk>)> {
     let (data_map, encrypted_chunks) = encrypt_data(data)?;
-    pack(data_map, encrypted_chunks, encryption)
+    pack(DataMapLevel::First(data_map), encrypted_chunks, encryption)
+}
+
+/// Same as [`encrypt_large`], for data padded up from the given size of the source data,
+/// which is recorded along with the data map for reads to strip the padding.
+pub(crate) fn encrypt_padded(
+    padded: Bytes,
+    size: usize,
+    encryption: Option<&impl Encryption>,
+) -> Result<(BytesAddress, Vec<Chunk>)> {
+    let (data_map, encrypted_chunks) = encrypt_data(padded)?;
+    pack(
+        DataMapLevel::PaddedFirst(data_map, size as u64),
+        encrypted_chunks,
+        encryption,
+    )
 }
 
 /// Returns the top-most chunk address through which the entire
@@ -47,7 +65,7 @@ pub(crate) fn encrypt_large(
 /// If encryption is provided, the additional secret key level chunks are encrypted with it.
 /// This is necessary if the data is meant to be private, since a `DataMap` is used to find and decrypt the original file.
 pub(crate) fn pack(
-    data_map: DataMap,
+    first_level: DataMapLevel,
     encrypted_chunks: Vec<EncryptedChunk>,
     encryption: Option<&impl Encryption>,
 ) -> Result<(BytesAddress, Vec<Chunk>)> {
@@ -58,7 +76,7 @@ pub(crate) fn pack(
     // self encrypted into additional chunks, and now we have a new secret key
     // which points to all of those additional chunks.. and so on.
     let mut chunks = vec![];
-    let mut chunk_content = pack_data_map(DataMapLevel::First(data_map))?;
+    let mut chunk_content = pack_data_map(first_level)?;
 
     // appeasing of compiler inference shenanigans..
     // no need to encrypt what is self-encrypted, thus we pass in `None` for those cases
@@ -0,0 +1,105 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use bytes::{Bytes, BytesMut};
+use rand::RngCore;
+use self_encryption::MIN_ENCRYPTABLE_BYTES;
+use serde::{Deserialize, Serialize};
+use std::{num::ParseIntError, str::FromStr};
+
+/// Schedule of the sizes private data is padded up to before being self-encrypted, so the
+/// size of its chunks doesn't leak its exact size.
+///
+/// Data is padded with random bytes up to the smallest bucket it fits in, and the padding is
+/// stripped when it's read back. Data larger than all the buckets is not padded.
+/// The default schedule has no buckets, i.e. nothing is padded.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaddingBuckets(Vec<usize>);
+
+impl PaddingBuckets {
+    /// Creates a schedule of the given bucket sizes, in bytes.
+    pub fn new(sizes: impl IntoIterator<Item = usize>) -> Self {
+        let mut sizes: Vec<_> = sizes.into_iter().collect();
+        sizes.sort_unstable();
+        sizes.dedup();
+        Self(sizes)
+    }
+
+    /// Bucket sizes, from the smallest one.
+    pub fn sizes(&self) -> &[usize] {
+        &self.0
+    }
+
+    /// Whether there are no buckets, i.e. nothing is padded.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the size data of the given size is padded up to, if it's padded at all.
+    /// Padded data is always large enough to be self-encrypted.
+    pub(crate) fn padded_size(&self, size: usize) -> Option<usize> {
+        let bucket = self.0.iter().copied().find(|bucket| *bucket >= size)?;
+        Some(bucket.max(MIN_ENCRYPTABLE_BYTES))
+    }
+
+    /// Returns the bytes followed by random ones, up to their bucket size.
+    pub(crate) fn pad(&self, bytes: &Bytes) -> Option<Bytes> {
+        let padded_size = self.padded_size(bytes.len())?;
+        let mut padding = vec![0; padded_size - bytes.len()];
+        rand::thread_rng().fill_bytes(&mut padding);
+
+        let mut padded = BytesMut::with_capacity(padded_size);
+        padded.extend_from_slice(bytes);
+        padded.extend_from_slice(&padding);
+        Some(padded.freeze())
+    }
+}
+
+impl FromStr for PaddingBuckets {
+    type Err = ParseIntError;
+
+    /// Parses a comma-separated list of bucket sizes, in bytes, e.g. `1024,16384,262144`.
+    fn from_str(sizes: &str) -> Result<Self, Self::Err> {
+        let sizes = sizes
+            .split(',')
+            .map(str::trim)
+            .filter(|size| !size.is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(sizes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::Result;
+
+    #[test]
+    fn data_is_padded_up_to_the_smallest_bucket_it_fits_in() -> Result<()> {
+        let buckets: PaddingBuckets = "4096, 1024,1,1024".parse()?;
+        assert_eq!(buckets.sizes(), &[1, 1024, 4096]);
+
+        assert_eq!(buckets.padded_size(1), Some(MIN_ENCRYPTABLE_BYTES));
+        assert_eq!(buckets.padded_size(2), Some(1024));
+        assert_eq!(buckets.padded_size(1024), Some(1024));
+        assert_eq!(buckets.padded_size(1025), Some(4096));
+        assert_eq!(buckets.padded_size(4097), None);
+        assert_eq!(PaddingBuckets::default().padded_size(1), None);
+
+        let bytes = Bytes::from_static(b"small secret");
+        let padded = buckets
+            .pad(&bytes)
+            .ok_or_else(|| eyre::eyre!("not padded"))?;
+        assert_eq!(padded.len(), 1024);
+        assert_eq!(padded.slice(..bytes.len()), bytes);
+
+        assert!("1024,big".parse::<PaddingBuckets>().is_err());
+        Ok(())
+    }
+}
@@ -7,19 +7,26 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
-    data::{encrypt_large, to_chunk, LargeFile, SmallFile},
-    Client,
+    data::{encrypt_large, encrypt_padded, to_chunk, LargeFile, SmallFile},
+    BytesReader, Client, ReadAhead,
+};
+use crate::{api::data::DataMapLevel, utils::encryption, Error, QuerySizeClass, Result};
+use sn_interface::messaging::data::{
+    DataCmd, DataPayment, DataQuery, Error as ErrorMsg, QueryResponse, StorageReceipt,
+};
+use sn_interface::types::{
+    BytesAddress, Chunk, ChunkAddress, Encryption, PublicKey, Scope, MAX_CHUNK_SIZE_IN_BYTES,
+    TEMPORARY_CHUNK_HEADER_SIZE,
 };
-use crate::{api::data::DataMapLevel, utils::encryption, Error, Result};
-use sn_interface::messaging::data::{DataCmd, DataQuery, QueryResponse};
-use sn_interface::types::{BytesAddress, Chunk, ChunkAddress, Encryption, PublicKey, Scope};
 
+use backoff::{backoff::Backoff, ExponentialBackoff};
 use bincode::deserialize;
 use bytes::Bytes;
 use futures::future::join_all;
 use itertools::Itertools;
 use self_encryption::{self, ChunkInfo, DataMap, EncryptedChunk};
-use tokio::task;
+use std::time::SystemTime;
+use tokio::{task, time::Duration};
 use tracing::trace;
 use xor_name::XorName;
 
@@ -28,6 +35,60 @@ struct HeadChunk {
     address: BytesAddress,
 }
 
+// Data map of some bytes, unpacked from their head chunk
+struct UnpackedDataMap {
+    data_map: DataMap,
+    // size of the bytes, if they were padded before being self-encrypted
+    size: Option<usize>,
+    // data maps of the additional levels of chunks the data map was stored in
+    additional_levels: Vec<DataMap>,
+}
+
+/// Layout of the chunks some [`Bytes`] are stored in, as obtained from their head chunk
+/// with [`Client::inspect_bytes`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataMapInfo {
+    /// Address of the head chunk, whose scope tells if it's encrypted with the owner's keys
+    pub address: BytesAddress,
+    /// Size of the head chunk, as stored
+    pub head_chunk_size: usize,
+    /// Size of the bytes
+    pub size: usize,
+    /// Size the bytes were padded up to before being self-encrypted, if they were
+    pub padded_size: Option<usize>,
+    /// Whether the bytes are self-encrypted, or else stored in the head chunk itself
+    pub self_encrypted: bool,
+    /// Chunks holding the self-encrypted bytes, in the order they are read
+    pub chunks: Vec<ChunkDetails>,
+    /// Chunks holding the data maps of the bytes which were too large to fit in the head chunk,
+    /// from the head chunk down
+    pub data_map_chunks: Vec<ChunkDetails>,
+}
+
+/// A self-encrypted chunk, as recorded in a data map.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkDetails {
+    /// Position of the chunk in the self-encrypted data
+    pub index: usize,
+    /// Name of the chunk, i.e. the hash of its encrypted content
+    pub name: XorName,
+    /// Hash of the content of the chunk before it was encrypted, from which its keys derive
+    pub src_hash: XorName,
+    /// Size of the content of the chunk before it was encrypted
+    pub src_size: usize,
+}
+
+impl From<&ChunkInfo> for ChunkDetails {
+    fn from(info: &ChunkInfo) -> Self {
+        Self {
+            index: info.index,
+            name: info.dst_hash,
+            src_hash: info.src_hash,
+            src_size: info.src_size,
+        }
+    }
+}
+
 impl Client {
     #[instrument(skip(self), level = "debug")]
     /// Reads [`Bytes`] from the network, whose contents are contained within on or more chunks.
@@ -35,14 +96,18 @@ impl Client {
         let chunk = self.get_chunk(address.name()).await?;
 
         // first try to deserialize a LargeFile, if it works, we go and seek it
-        if let Ok(data_map) = self
+        if let Ok(UnpackedDataMap { data_map, size, .. }) = self
             .unpack_head_chunk(HeadChunk {
                 chunk: chunk.clone(),
                 address,
             })
             .await
         {
-            self.read_all(data_map).await
+            let mut bytes = self.read_all(data_map).await?;
+            if let Some(size) = size {
+                bytes.truncate(size);
+            }
+            Ok(bytes)
         } else {
             // if an error occurs, we assume it's a SmallFile
             self.get_bytes(chunk, address.scope())
@@ -83,13 +148,19 @@ impl Client {
 
         // First try to deserialize a LargeFile, if it works, we go and seek it.
         // If an error occurs, we consider it to be a SmallFile.
-        if let Ok(data_map) = self
+        if let Ok(UnpackedDataMap { data_map, size, .. }) = self
             .unpack_head_chunk(HeadChunk {
                 chunk: chunk.clone(),
                 address,
             })
             .await
         {
+            // the padding of padded data is never read
+            let length = match size {
+                Some(size) if position >= size => return Ok(Bytes::new()),
+                Some(size) => length.min(size - position),
+                None => length,
+            };
             return self.seek(data_map, position, length).await;
         }
 
@@ -104,8 +175,101 @@ impl Client {
         Ok(bytes)
     }
 
+    /// Reads the [`Bytes`] at the given address chunk by chunk, with the returned [`BytesReader`].
+    ///
+    /// Only the head chunk is read upfront. The chunks of the content are then fetched as
+    /// they're read, as well as the following ones as per the `read_ahead` settings, so
+    /// sequential reads of large files don't stall waiting for each chunk in turn.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn read_stream(
+        &self,
+        address: BytesAddress,
+        read_ahead: ReadAhead,
+    ) -> Result<BytesReader> {
+        let chunk = self.get_chunk(address.name()).await?;
+
+        // as when reading them at once, the bytes are a SmallFile if there's no data map to unpack
+        match self
+            .unpack_head_chunk(HeadChunk {
+                chunk: chunk.clone(),
+                address,
+            })
+            .await
+        {
+            Ok(UnpackedDataMap { data_map, size, .. }) => Ok(BytesReader::self_encrypted(
+                self.clone(),
+                data_map,
+                size,
+                read_ahead,
+            )),
+            Err(_) => Ok(BytesReader::small(
+                self.clone(),
+                self.get_bytes(chunk, address.scope())?,
+            )),
+        }
+    }
+
+    /// Reads the data map of the [`Bytes`] at the given address, to get the chunks they are
+    /// stored in, without reading their content, e.g. to prefetch them or estimate the cost of
+    /// retrieving them.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn inspect_bytes(&self, address: BytesAddress) -> Result<DataMapInfo> {
+        let chunk = self.get_chunk(address.name()).await?;
+        let head_chunk_size = chunk.value().len();
+
+        // as when reading them, the bytes are a SmallFile if there's no data map to unpack
+        let info = match self
+            .unpack_head_chunk(HeadChunk {
+                chunk: chunk.clone(),
+                address,
+            })
+            .await
+        {
+            Ok(UnpackedDataMap {
+                data_map,
+                size,
+                additional_levels,
+            }) => DataMapInfo {
+                address,
+                head_chunk_size,
+                size: size.unwrap_or_else(|| data_map.file_size()),
+                padded_size: size.map(|_| data_map.file_size()),
+                self_encrypted: true,
+                chunks: data_map.infos().iter().map(ChunkDetails::from).collect(),
+                data_map_chunks: additional_levels
+                    .iter()
+                    .flat_map(|level| level.infos())
+                    .map(|info| ChunkDetails::from(&info))
+                    .collect(),
+            },
+            Err(_) => DataMapInfo {
+                address,
+                head_chunk_size,
+                size: self.get_bytes(chunk, address.scope())?.len(),
+                padded_size: None,
+                self_encrypted: false,
+                chunks: vec![],
+                data_map_chunks: vec![],
+            },
+        };
+
+        Ok(info)
+    }
+
     #[instrument(skip(self), level = "trace")]
     pub(crate) async fn get_chunk(&self, name: &XorName) -> Result<Chunk> {
+        let size_class = QuerySizeClass::from(&DataQuery::GetChunk(ChunkAddress(*name)));
+        self.get_chunk_of_size_class(name, size_class).await
+    }
+
+    // Fetches a chunk, with the query timeout of the given size class, e.g. as estimated from
+    // the size of the chunk recorded in a data map.
+    #[instrument(skip(self), level = "trace")]
+    pub(crate) async fn get_chunk_of_size_class(
+        &self,
+        name: &XorName,
+        size_class: QuerySizeClass,
+    ) -> Result<Chunk> {
         // first check it's not already in our Chunks' cache
         if let Some(chunk) = self
             .chunks_cache
@@ -117,27 +281,88 @@ impl Client {
             return Ok(chunk.clone());
         }
 
+        let chunk = match self.query_chunk(name, size_class).await {
+            Err(error) if self.repair_missing_chunks && is_missing_chunk_error(&error) => {
+                self.repair_missing_chunk(name, size_class, error).await?
+            }
+            res => res?,
+        };
+
+        let _ = self.chunks_cache.write().await.insert(chunk.clone());
+
+        Ok(chunk)
+    }
+
+    // Private helper to fetch a chunk from the network.
+    async fn query_chunk(&self, name: &XorName, size_class: QuerySizeClass) -> Result<Chunk> {
         let res = self
-            .send_query(DataQuery::GetChunk(ChunkAddress(*name)))
+            .send_query_of_size_class(DataQuery::GetChunk(ChunkAddress(*name)), size_class)
             .await?;
 
         let operation_id = res.operation_id;
-        let chunk: Chunk = match res.response {
+        match res.response {
             QueryResponse::GetChunk(result) => {
                 result.map_err(|err| Error::from((err, operation_id)))
             }
+            response => Err(Error::UnexpectedQueryResponse(response)),
+        }
+    }
+
+    // Reports a chunk we couldn't retrieve to the Elders of its section, so they
+    // have their Adults replicate it again, then retries fetching it with backoff.
+    // The original error is returned if the chunk still cannot be retrieved.
+    #[instrument(skip(self), level = "debug")]
+    async fn repair_missing_chunk(
+        &self,
+        name: &XorName,
+        size_class: QuerySizeClass,
+        error: Error,
+    ) -> Result<Chunk> {
+        debug!(
+            "Reporting missing chunk {:?} after error: {:?}",
+            name, error
+        );
+        let res = self
+            .send_query(DataQuery::ReportMissingChunk(ChunkAddress(*name)))
+            .await?;
+
+        let operation_id = res.operation_id;
+        match res.response {
+            QueryResponse::ReportMissingChunk((result, _)) => {
+                result.map_err(|err| Error::from((err, operation_id)))?
+            }
             response => return Err(Error::UnexpectedQueryResponse(response)),
-        }?;
+        }
 
-        let _ = self.chunks_cache.write().await.insert(chunk.clone());
+        let mut backoff = ExponentialBackoff {
+            initial_interval: Duration::from_secs(1),
+            max_interval: Duration::from_secs(10),
+            max_elapsed_time: Some(self.query_timeouts.get(size_class)),
+            ..Default::default()
+        };
 
-        Ok(chunk)
+        // this seems needed for custom settings to take effect
+        backoff.reset();
+
+        while let Some(delay) = backoff.next_backoff() {
+            debug!("Sleeping for {delay:?} before trying to fetch repaired chunk {name:?}");
+            tokio::time::sleep(delay).await;
+
+            match self.query_chunk(name, size_class).await {
+                Ok(chunk) => return Ok(chunk),
+                Err(err) => debug!("Repaired chunk {:?} not retrieved yet: {:?}", name, err),
+            }
+        }
+
+        Err(error)
     }
 
     /// Tries to chunk the bytes, returning an address and chunks, without storing anything to network.
     #[instrument(skip_all, level = "trace")]
     pub fn chunk_bytes(&self, bytes: Bytes, scope: Scope) -> Result<(BytesAddress, Vec<Chunk>)> {
-        if let Ok(file) = LargeFile::new(bytes.clone()) {
+        if let Some(padded) = self.pad(&bytes, scope) {
+            Self::encrypt_padded(padded, bytes.len(), scope, self.public_key())
+        } else if let Ok(file) = LargeFile::new(bytes.clone()) {
             Self::encrypt_large(file, scope, self.public_key())
         } else {
             let file = SmallFile::new(bytes)?;
@@ -158,6 +383,20 @@ impl Client {
         encrypt_large(file.bytes(), owner.as_ref())
     }
 
+    /// Encrypts a [`LargeFile`] of padded data, recording the size of the data it was padded
+    /// up from, and returns the resulting address and all chunks.
+    /// Does not store anything to the network.
+    #[instrument(skip(file), level = "trace")]
+    fn encrypt_padded(
+        file: LargeFile,
+        size: usize,
+        scope: Scope,
+        public_key: PublicKey,
+    ) -> Result<(BytesAddress, Vec<Chunk>)> {
+        let owner = encryption(scope, public_key);
+        encrypt_padded(file.bytes(), size, owner.as_ref())
+    }
+
     /// Packages a [`SmallFile`] and returns the resulting address and the chunk.
     /// The chunk content will be in plain text if it has public scope, or encrypted if it is instead private.
     /// Does not store anything to the network.
@@ -182,9 +421,31 @@ impl Client {
 
     /// Directly writes [`Bytes`] to the network in the
     /// form of immutable chunks, without any batching.
+    ///
+    /// Private bytes are padded up to their bucket size first, if the client is configured with
+    /// [`PaddingBuckets`](crate::api::PaddingBuckets).
     #[instrument(skip(self, bytes), level = "debug")]
     pub async fn upload(&self, bytes: Bytes, scope: Scope) -> Result<BytesAddress> {
-        if let Ok(file) = LargeFile::new(bytes.clone()) {
+        let (address, _receipts) = self.upload_with_receipts(bytes, scope).await?;
+        Ok(address)
+    }
+
+    /// Directly writes [`Bytes`] to the network in the
+    /// form of immutable chunks, without any batching.
+    /// It also returns the storage receipts of the chunks, proving the network accepted them.
+    /// Chunks whose Elders didn't return enough shares of their receipt have none.
+    #[instrument(skip(self, bytes), level = "debug")]
+    pub async fn upload_with_receipts(
+        &self,
+        bytes: Bytes,
+        scope: Scope,
+    ) -> Result<(BytesAddress, Vec<StorageReceipt>)> {
+        if let Some(padded) = self.pad(&bytes, scope) {
+            let (head_address, all_chunks) =
+                Self::encrypt_padded(padded, bytes.len(), scope, self.public_key())?;
+            let receipts = self.store_all(all_chunks).await?;
+            Ok((head_address, receipts))
+        } else if let Ok(file) = LargeFile::new(bytes.clone()) {
             self.upload_large(file, scope).await
         } else {
             let file = SmallFile::new(bytes)?;
@@ -209,6 +470,86 @@ impl Client {
         Ok((address, bytes))
     }
 
+    /// Writes [`Bytes`] to the network as temporary data, which expires once the given `ttl` elapsed.
+    ///
+    /// The data is stored in a single chunk recording its expiry, so it's limited to slightly less
+    /// than a chunk. Adults stop serving it once it's expired, and eventually remove it, so it suits
+    /// caches and ephemeral app state which shouldn't live forever on the network.
+    /// It's read back with [`Client::read_bytes`] until then.
+    #[instrument(skip(self, bytes), level = "debug")]
+    pub async fn store_temporary(
+        &self,
+        bytes: Bytes,
+        scope: Scope,
+        ttl: Duration,
+    ) -> Result<BytesAddress> {
+        let bytes = match encryption(scope, self.public_key()) {
+            Some(encryption) => encryption.encrypt(bytes)?,
+            None => bytes,
+        };
+        let max_size = MAX_CHUNK_SIZE_IN_BYTES - TEMPORARY_CHUNK_HEADER_SIZE;
+        if bytes.len() > max_size {
+            return Err(Error::TooLargeAsTemporaryData(max_size));
+        }
+
+        let chunk = Chunk::new_temporary(&bytes, SystemTime::now() + ttl);
+        let address = match scope {
+            Scope::Public => BytesAddress::Public(*chunk.name()),
+            Scope::Private => BytesAddress::Private(*chunk.name()),
+        };
+        self.store_chunk(chunk).await?;
+
+        Ok(address)
+    }
+
+    /// Writes a chunk to the network, along with the payment for storing it.
+    /// The chunks to be paid for are obtained with [`Client::chunk_bytes`].
+    #[instrument(skip_all, level = "debug")]
+    /// The storage receipt of the chunk is returned if its Elders returned enough shares of it.
+    #[instrument(skip_all, level = "debug")]
+    pub async fn store_paid_chunk(
+        &self,
+        chunk: Chunk,
+        payment: DataPayment,
+    ) -> Result<Option<StorageReceipt>> {
+        self.send_cmd_for_receipt(DataCmd::StorePaidChunk { chunk, payment })
+            .await
+    }
+
+    /// Reads the head chunk of the [`Bytes`] at the given address, i.e. the chunk holding
+    /// the data map of a LargeFile, or the content of a SmallFile.
+    ///
+    /// Since all other chunks are addressed by their content, storing the head chunk back
+    /// with [`Client::store_chunk`] is enough to make the bytes readable again at that address.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn read_head_chunk(&self, address: BytesAddress) -> Result<Chunk> {
+        self.get_chunk(address.name()).await
+    }
+
+    /// Checks all the chunks of the [`Bytes`] at the given address can be read from the network,
+    /// without decrypting them, failing as reading the bytes would if any of them can't.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn verify_all_chunks_stored(&self, address: BytesAddress) -> Result<()> {
+        let chunk = self.get_chunk(address.name()).await?;
+        // as when reading them, the bytes are a SmallFile, held by the head chunk alone,
+        // if there's no data map to unpack
+        if let Ok(UnpackedDataMap { data_map, .. }) =
+            self.unpack_head_chunk(HeadChunk { chunk, address }).await
+        {
+            let _chunks = Self::try_get_chunks(self, data_map.infos()).await?;
+        }
+        Ok(())
+    }
+
+    /// Writes a chunk to the network as is, e.g. a head chunk read with [`Client::read_head_chunk`].
+    #[instrument(skip_all, level = "debug")]
+    pub async fn store_chunk(&self, chunk: Chunk) -> Result<()> {
+        let _receipt = self
+            .send_cmd_for_receipt(DataCmd::StoreChunk(chunk))
+            .await?;
+        Ok(())
+    }
+
     /// Calculates a LargeFile's/SmallFile's address from self encrypted chunks,
     /// without storing them onto the network.
     #[instrument(skip(bytes), level = "debug")]
@@ -228,12 +569,25 @@ impl Client {
     /// Directly writes a [`LargeFile`] to the network in the
     /// form of immutable self encrypted chunks, without any batching.
     #[instrument(skip_all, level = "trace")]
-    async fn upload_large(&self, large: LargeFile, scope: Scope) -> Result<BytesAddress> {
+    async fn upload_large(
+        &self,
+        large: LargeFile,
+        scope: Scope,
+    ) -> Result<(BytesAddress, Vec<StorageReceipt>)> {
         let (head_address, all_chunks) = Self::encrypt_large(large, scope, self.public_key())?;
+        let receipts = self.store_all(all_chunks).await?;
+        Ok((head_address, receipts))
+    }
 
+    // Stores all the chunks of some data concurrently, returning their storage receipts.
+    async fn store_all(&self, all_chunks: Vec<Chunk>) -> Result<Vec<StorageReceipt>> {
         let tasks = all_chunks.into_iter().map(|chunk| {
             let writer = self.clone();
-            task::spawn(async move { writer.send_cmd(DataCmd::StoreChunk(chunk)).await })
+            task::spawn(async move {
+                writer
+                    .send_cmd_for_receipt(DataCmd::StoreChunk(chunk))
+                    .await
+            })
         });
 
         let respones = join_all(tasks)
@@ -242,27 +596,43 @@ impl Client {
             .flatten() // swallows errors
             .collect_vec();
 
+        let mut receipts = vec![];
         for res in respones {
             // fail with any issue here
-            let _ = res?;
+            receipts.extend(res?);
         }
 
-        Ok(head_address)
+        Ok(receipts)
     }
 
     /// Directly writes a [`SmallFile`] to the network in the
     /// form of a single chunk, without any batching.
     #[instrument(skip_all, level = "trace")]
-    async fn upload_small(&self, small: SmallFile, scope: Scope) -> Result<BytesAddress> {
+    async fn upload_small(
+        &self,
+        small: SmallFile,
+        scope: Scope,
+    ) -> Result<(BytesAddress, Vec<StorageReceipt>)> {
         let (address, chunk) = Self::package_small(small, scope, self.public_key())?;
-        self.send_cmd(DataCmd::StoreChunk(chunk)).await?;
-        Ok(address)
+        let receipt = self
+            .send_cmd_for_receipt(DataCmd::StoreChunk(chunk))
+            .await?;
+        Ok((address, receipt.into_iter().collect()))
     }
 
     // --------------------------------------------
     // ---------- Private helpers -----------------
     // --------------------------------------------
 
+    // Pads non-empty private bytes up to their bucket size, if the client is configured to.
+    fn pad(&self, bytes: &Bytes, scope: Scope) -> Option<LargeFile> {
+        if matches!(scope, Scope::Public) || bytes.is_empty() {
+            return None;
+        }
+        let padded = self.padding.pad(bytes)?;
+        LargeFile::new(padded).ok()
+    }
+
     // Gets and decrypts chunks from the network using nothing else but the data map,
     // then returns the raw data.
     async fn read_all(&self, data_map: DataMap) -> Result<Bytes> {
@@ -304,7 +674,11 @@ impl Client {
         let tasks = chunks_info.into_iter().map(|chunk_info| {
             let client = client.clone();
             task::spawn(async move {
-                match client.get_chunk(&chunk_info.dst_hash).await {
+                let size_class = QuerySizeClass::of_chunk(chunk_info.src_size);
+                match client
+                    .get_chunk_of_size_class(&chunk_info.dst_hash, size_class)
+                    .await
+                {
                     Ok(chunk) => Ok(EncryptedChunk {
                         index: chunk_info.index,
                         content: chunk.value().clone(),
@@ -343,44 +717,73 @@ impl Client {
     /// Extracts a file DataMapLevel from a head chunk.
     /// If the DataMapLevel is not the first level mapping directly to the user's contents,
     /// the process repeats itself until it obtains the first level DataMapLevel.
+    /// The size of the user's contents is returned along with its data map if they were padded,
+    /// as well as the data maps of the additional levels.
     #[instrument(skip_all, level = "trace")]
-    async fn unpack_head_chunk(&self, chunk: HeadChunk) -> Result<DataMap> {
+    async fn unpack_head_chunk(&self, chunk: HeadChunk) -> Result<UnpackedDataMap> {
         let HeadChunk { mut chunk, address } = chunk;
+        let mut additional_levels = vec![];
         loop {
             let bytes = self.get_bytes(chunk, address.scope())?;
 
-            match deserialize(&bytes)? {
-                DataMapLevel::First(data_map) => {
-                    return Ok(data_map);
-                }
+            let (data_map, size) = match deserialize(&bytes)? {
+                DataMapLevel::First(data_map) => (data_map, None),
+                DataMapLevel::PaddedFirst(data_map, size) => (data_map, Some(size as usize)),
                 DataMapLevel::Additional(data_map) => {
-                    let serialized_chunk = self.read_all(data_map).await?;
+                    let serialized_chunk = self.read_all(data_map.clone()).await?;
                     chunk = deserialize(&serialized_chunk)?;
+                    additional_levels.push(data_map);
+                    continue;
                 }
-            }
+            };
+
+            return Ok(UnpackedDataMap {
+                data_map,
+                size,
+                additional_levels,
+            });
         }
     }
 
     /// If scope == Scope::Private, decrypts contents with the client encryption keys.
     /// Else returns the content bytes.
+    /// Fails if it's a temporary chunk which is expired.
     #[instrument(skip_all, level = "trace")]
     fn get_bytes(&self, chunk: Chunk, scope: Scope) -> Result<Bytes> {
+        if chunk.is_expired_at(SystemTime::now()) {
+            return Err(Error::DataExpired(*chunk.name()));
+        }
+        let content = chunk.value().clone();
+
         if matches!(scope, Scope::Public) {
-            Ok(chunk.value().clone())
+            Ok(content)
         } else {
             let owner = encryption(scope, self.public_key()).ok_or(Error::NoEncryptionObject)?;
-            Ok(owner.decrypt(chunk.value().clone())?)
+            Ok(owner.decrypt(content)?)
         }
     }
 }
 
+// Elders don't forward not-found responses from Adults, so a missing
+// chunk is usually reported to us as no response at all.
+fn is_missing_chunk_error(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::NoResponse
+            | Error::ErrorMsg {
+                source: ErrorMsg::ChunkNotFound(_),
+                ..
+            }
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use crate::utils::test_utils::create_test_client_with;
     use crate::{
         api::file_apis::LargeFile,
         utils::test_utils::{create_test_client, init_logger},
-        Client,
+        Client, PaddingBuckets,
     };
     use sn_interface::types::log_markers::LogMarker;
     use sn_interface::types::{utils::random_bytes, BytesAddress, Keypair, Scope};
@@ -437,7 +840,7 @@ mod tests {
 
         // Test storing private file with the same value.
         // Should not conflict and return same address
-        let address = client
+        let (address, _receipts) = client
             .upload_large(file.clone(), Scope::Private)
             .instrument(tracing::info_span!(
                 "checking no conflict on same private upload"
@@ -446,7 +849,7 @@ mod tests {
         assert_eq!(address, private_address);
 
         // Test storing public file with the same value. Should not conflict.
-        let public_address = client
+        let (public_address, _receipts) = client
             .upload_large(file.clone(), Scope::Public)
             .instrument(tracing::info_span!("checking no conflict on public upload"))
             .await?;
@@ -464,6 +867,81 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn store_and_read_padded_private_bytes() -> Result<()> {
+        init_logger();
+        let _start_span = tracing::info_span!("store_and_read_padded_private_bytes").entered();
+
+        let client = create_test_client()
+            .await?
+            .with_padding(PaddingBuckets::new([4096]));
+        let bytes = random_bytes(100);
+
+        // the padding is stored, but never read back
+        let (address, chunks) = client.chunk_bytes(bytes.clone(), Scope::Private)?;
+        let (unpadded_address, _) = client
+            .with_padding(PaddingBuckets::default())
+            .chunk_bytes(bytes.clone(), Scope::Private)?;
+        assert_ne!(address, unpadded_address);
+        assert!(chunks.len() > 1);
+
+        let address = client.upload(bytes.clone(), Scope::Private).await?;
+        compare(bytes.clone(), client.read_bytes(address).await?)?;
+        compare(
+            bytes.slice(90..),
+            client.read_from(address, 90, 4096).await?,
+        )?;
+        assert!(client.read_from(address, 100, 10).await?.is_empty());
+
+        // public bytes are not padded
+        let (public_address, _) = client.chunk_bytes(bytes.clone(), Scope::Public)?;
+        assert_eq!(
+            public_address,
+            Client::calculate_address(bytes, Scope::Public)?
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn inspect_bytes_without_reading_them() -> Result<()> {
+        init_logger();
+        let _start_span = tracing::info_span!("inspect_bytes_without_reading_them").entered();
+
+        let client = create_test_client().await?;
+        let bytes = random_bytes(5 * 1024 * 1024);
+        let (chunked_address, all_chunks) = client.chunk_bytes(bytes.clone(), Scope::Public)?;
+        let address = client.upload(bytes.clone(), Scope::Public).await?;
+        assert_eq!(address, chunked_address);
+
+        let info = client.inspect_bytes(address).await?;
+        assert_eq!(info.address, address);
+        assert_eq!(info.size, bytes.len());
+        assert_eq!(info.padded_size, None);
+        assert!(info.self_encrypted);
+        assert_eq!(
+            info.chunks.iter().map(|c| c.src_size).sum::<usize>(),
+            bytes.len()
+        );
+        // all the chunks but the head one are listed
+        assert_eq!(
+            info.chunks.len() + info.data_map_chunks.len() + 1,
+            all_chunks.len()
+        );
+        for chunk in &info.chunks {
+            assert!(all_chunks.iter().any(|c| c.name() == &chunk.name));
+        }
+
+        let small = random_bytes(100);
+        let address = client.upload(small, Scope::Private).await?;
+        let info = client.inspect_bytes(address).await?;
+        assert_eq!(info.size, 100);
+        assert!(!info.self_encrypted);
+        assert!(info.chunks.is_empty());
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn seek_with_unknown_length() -> Result<()> {
         init_logger();
@@ -675,6 +1153,42 @@ mod tests {
         store_and_read(&client, size, Scope::Private).await
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn upload_returns_verifiable_receipts() -> Result<()> {
+        init_logger();
+        let client = create_test_client().await?;
+        let bytes = random_bytes(LARGE_FILE_SIZE_MIN);
+
+        let (_, all_chunks) = client.chunk_bytes(bytes.clone(), Scope::Public)?;
+        let (_, receipts) = client.upload_with_receipts(bytes, Scope::Public).await?;
+
+        assert_eq!(receipts.len(), all_chunks.len());
+        for receipt in receipts {
+            assert!(receipt.verify());
+            assert!(all_chunks
+                .iter()
+                .any(|chunk| *chunk.name() == receipt.content.name));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn temporary_data_expires() -> Result<()> {
+        init_logger();
+        let client = create_test_client().await?;
+        let bytes = random_bytes(1024);
+        let address = client
+            .store_temporary(bytes.clone(), Scope::Private, Duration::from_secs(20))
+            .await?;
+        assert_eq!(client.read_bytes(address).await?, bytes);
+
+        tokio::time::sleep(Duration::from_secs(25)).await;
+        assert!(client.read_bytes(address).await.is_err());
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn store_and_read_1mb() -> Result<()> {
         init_logger();
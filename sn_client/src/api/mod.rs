@@ -6,31 +6,55 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+mod bytes_reader;
 mod cmds;
 mod data;
 mod file_apis;
 mod queries;
 mod register_apis;
+mod section_members;
 mod spentbook_apis;
 
+pub use bytes_reader::{BytesReader, ReadAhead};
+pub use cmds::CmdDelivery;
+pub use data::PaddingBuckets;
+pub use file_apis::{ChunkDetails, DataMapInfo};
 pub use register_apis::RegisterWriteAheadLog;
 
-use crate::{connections::Session, errors::Error, ClientConfig};
+#[cfg(any(test, feature = "test-utils"))]
+use crate::MockNetwork;
+use crate::{
+    bandwidth::BandwidthLimiter,
+    connections::{Session, Transport},
+    errors::Error,
+    query_timeouts::QueryTimeoutMetrics,
+    BandwidthLimits, ClientConfig, ConnectionState, OperationKind, Outcome, QuerySizeClass,
+    QueryTimeoutStats, QueryTimeouts, RoutingCacheStats, Signer, Telemetry, TelemetryEvent,
+    Throughput,
+};
+use backoff::{backoff::Backoff, ExponentialBackoff};
+use cmds::OrderedCmdStreams;
 use sn_dbc::{rng, Owner};
 use sn_interface::messaging::{
-    data::{CmdError, DataQuery, RegisterQuery, ServiceMsg},
-    ServiceAuth, WireMsg,
+    data::{CmdError, DataQuery, RegisterQuery, ServiceMsg, StorageReceipt},
+    NetworkName, ServiceAuth, SigningDomain, WireMsg,
 };
-use sn_interface::network_knowledge::prefix_map::NetworkPrefixMap;
 use sn_interface::network_knowledge::utils::read_prefix_map_from_disk;
-use sn_interface::types::{Chunk, Keypair, Peer, PublicKey, RegisterAddress};
+use sn_interface::network_knowledge::{prefix_map::NetworkPrefixMap, NetworkSnapshot};
+use sn_interface::types::{Chunk, Keypair, Peer, PublicKey, RegisterAddress, Signature};
 
 use bytes::Bytes;
 use itertools::Itertools;
-use std::{collections::BTreeSet, net::SocketAddr, sync::Arc};
+use secured_linked_list::SecuredLinkedList;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::SocketAddr,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 use tokio::{
     sync::{mpsc::Receiver, RwLock},
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tracing::{debug, info};
 use uluru::LRUCache;
@@ -43,6 +67,16 @@ const CHUNK_CACHE_SIZE: usize = 50;
 // Number of times to retry network probe on client startup
 const NETWORK_PROBE_RETRY_COUNT: usize = 5; // 5 x 5 second wait in between = ~25 seconds (plus ~ 3 seconds in between attempts internal to `make_contact`)
 
+// Time after which we give up on reconnecting to the Elders, once the connections to them were lost
+const RECONNECT_MAX_ELAPSED_TIME: Duration = Duration::from_secs(30);
+
+// Maximum time waited before the first attempt to reconnect, picked at random for the clients
+// which lost their connections in the same network blip not to reconnect all at once
+const RECONNECT_MAX_INITIAL_DELAY: Duration = Duration::from_millis(500);
+
+// Share by which each delay between attempts to reconnect is randomly shortened or lengthened
+const RECONNECT_RANDOMIZATION_FACTOR: f64 = 0.5;
+
 // LRU cache to keep the Chunks we retrieve.
 type ChunksCache = LRUCache<Chunk, CHUNK_CACHE_SIZE>;
 
@@ -50,13 +84,20 @@ type ChunksCache = LRUCache<Chunk, CHUNK_CACHE_SIZE>;
 #[derive(Clone, Debug)]
 pub struct Client {
     keypair: Keypair,
+    signer: Arc<dyn Signer>,
     dbc_owner: Owner,
     #[allow(dead_code)]
     incoming_errors: Arc<RwLock<Receiver<CmdError>>>,
     session: Session,
-    pub(crate) query_timeout: Duration,
+    pub(crate) query_timeouts: QueryTimeouts,
+    query_timeout_metrics: Arc<QueryTimeoutMetrics>,
     pub(crate) cmd_timeout: Duration,
+    ordered_cmd_streams: Arc<OrderedCmdStreams>,
     chunks_cache: Arc<RwLock<ChunksCache>>,
+    repair_missing_chunks: bool,
+    telemetry: Option<Arc<dyn Telemetry>>,
+    pub(crate) bandwidth: Arc<BandwidthLimiter>,
+    padding: PaddingBuckets,
 }
 
 /// Easily manage connections to/from The Safe Network with the client and its APIs.
@@ -84,6 +125,38 @@ impl Client {
         Client::create_with(config, bootstrap_nodes, optional_keypair, dbc_owner, true).await
     }
 
+    /// Create a client sending its msgs to the given in-memory [`MockNetwork`] rather than to the
+    /// network, for apps to be tested without one. The config is to hold the genesis key of the
+    /// mock network.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn new_mock(
+        config: ClientConfig,
+        network: MockNetwork,
+        optional_keypair: Option<Keypair>,
+        dbc_owner: Option<Owner>,
+    ) -> Result<Self, Error> {
+        if config.genesis_key != network.genesis_key() {
+            return Err(Error::GenesisKeyMismatch);
+        }
+
+        // the genesis section is known, as if read from a stored PrefixMap, any other being
+        // learnt about through AE
+        let prefix_map = NetworkPrefixMap::new(config.genesis_key);
+        let _ = prefix_map
+            .update(
+                network.genesis_section(),
+                &SecuredLinkedList::new(config.genesis_key),
+            )
+            .map_err(|_| Error::NoNetworkKnowledge)?;
+        Self::init(
+            config,
+            Transport::Mock(network),
+            optional_keypair,
+            dbc_owner,
+            prefix_map,
+        )
+    }
+
     #[instrument]
     pub(crate) async fn create_with(
         config: ClientConfig,
@@ -92,23 +165,16 @@ impl Client {
         dbc_owner: Option<Owner>,
         read_prefixmap: bool,
     ) -> Result<Self, Error> {
-        let keypair = match optional_keypair {
-            Some(id) => {
-                info!("Client started for specific pk: {:?}", id.public_key());
-                id
-            }
-            None => {
-                let keypair = Keypair::new_ed25519();
-                info!(
-                    "Client started for new randomly created pk: {:?}",
-                    keypair.public_key()
-                );
-                keypair
-            }
-        };
-
         let home_dir = dirs_next::home_dir().ok_or(Error::CouldNotReadHomeDir)?;
 
+        // Contacts set in the config are contacted rather than the given bootstrap nodes,
+        // or the Elders known from the stored PrefixMap
+        let (bootstrap_nodes, read_prefixmap) = if config.bootstrap.contacts.is_empty() {
+            (bootstrap_nodes, read_prefixmap)
+        } else {
+            (config.bootstrap.contacts.clone(), false)
+        };
+
         // Read NetworkPrefixMap from `.safe/prefix_map` if present else check client root dir
         let prefix_map = if read_prefixmap {
             match read_prefix_map_from_disk(
@@ -134,73 +200,22 @@ impl Client {
             return Err(Error::GenesisKeyMismatch);
         }
 
-        // Incoming error notifiers
-        let (err_sender, err_receiver) = tokio::sync::mpsc::channel::<CmdError>(10);
-
-        let client_pk = keypair.public_key();
-
-        // Bootstrap to the network, connecting to a section based
-        // on a public key of our choice.
-        debug!(
-            "Creating new session with genesis key: {:?} ",
-            config.genesis_key
-        );
-        debug!(
-            "Creating new session with genesis key (in hex format): {} ",
-            hex::encode(config.genesis_key.to_bytes())
-        );
-
-        // Create a session with the network
-        let session = Session::new(
-            client_pk,
-            config.genesis_key,
-            config.qp2p,
-            err_sender,
-            config.local_addr,
-            config.cmd_ack_wait,
+        let ip_preference = config.ip_preference;
+        let transport = Transport::network(config.local_addr, config.qp2p.clone())?;
+        let client = Self::init(
+            config,
+            transport,
+            optional_keypair,
+            dbc_owner,
             prefix_map.clone(),
         )?;
-
-        let client = Self {
-            keypair,
-            dbc_owner: dbc_owner
-                .unwrap_or_else(|| Owner::from_random_secret_key(&mut rng::thread_rng())),
-            session,
-            incoming_errors: Arc::new(RwLock::new(err_receiver)),
-            query_timeout: config.query_timeout,
-            cmd_timeout: config.cmd_timeout,
-            chunks_cache: Arc::new(RwLock::new(ChunksCache::default())),
-        };
+        let client_pk = client.public_key();
 
         // TODO: The message being sent below is a temporary solution to fetch network info for
         // the client. Ideally the client should be able to send proper AE-Probe messages to the
         // trigger the AE flows.
-
-        fn generate_probe_msg(
-            client: &Client,
-            pk: PublicKey,
-        ) -> Result<(XorName, ServiceAuth, Bytes), Error> {
-            // Generate a random query to send a dummy message
-            let random_dst_addr = xor_name::rand::random();
-            let serialised_cmd = {
-                let msg = ServiceMsg::Query(DataQuery::Register(RegisterQuery::Get(
-                    RegisterAddress::Public {
-                        name: random_dst_addr,
-                        tag: 1,
-                    },
-                )));
-                WireMsg::serialize_msg_payload(&msg)?
-            };
-            let signature = client.keypair.sign(&serialised_cmd);
-            let auth = ServiceAuth {
-                public_key: pk,
-                signature,
-            };
-
-            Ok((random_dst_addr, auth, serialised_cmd))
-        }
-
-        let (random_dst_addr, auth, serialised_cmd) = generate_probe_msg(&client, client_pk)?;
+        let (random_dst_addr, auth, serialised_cmd) =
+            generate_probe_msg(&client, client_pk, xor_name::rand::random())?;
 
         // either use our known prefixmap elders, or fallback to plain node config file
         let bootstrap_nodes = {
@@ -208,10 +223,11 @@ impl Client {
                 sap.elders_vec()
             } else {
                 // these peers will be nonsense peers, and dropped after we connect. Replaced by whatever SectionAuthorityProvider peers we have received
-                // therefore we use a random name for them initially
-                bootstrap_nodes
-                    .iter()
-                    .copied()
+                // therefore we use a random name for them initially.
+                // The ones of our preferred IP version are contacted first.
+                ip_preference
+                    .sort_contacts(bootstrap_nodes.iter().copied())
+                    .into_iter()
                     .map(|socket| Peer::new(xor_name::rand::random(), socket))
                     .collect_vec()
             }
@@ -243,7 +259,8 @@ impl Client {
 
             tokio::time::sleep(Duration::from_secs(5)).await;
 
-            let (random_dst_addr, auth, serialised_cmd) = generate_probe_msg(&client, client_pk)?;
+            let (random_dst_addr, auth, serialised_cmd) =
+                generate_probe_msg(&client, client_pk, xor_name::rand::random())?;
 
             initial_probe = client
                 .session
@@ -259,9 +276,83 @@ impl Client {
         Ok(client)
     }
 
+    // Creates the client, with a session which isn't connected to the network yet, sending its
+    // msgs through the given transport.
+    fn init(
+        config: ClientConfig,
+        transport: Transport,
+        optional_keypair: Option<Keypair>,
+        dbc_owner: Option<Owner>,
+        prefix_map: NetworkPrefixMap,
+    ) -> Result<Self, Error> {
+        let keypair = match optional_keypair {
+            Some(id) => {
+                info!("Client started for specific pk: {:?}", id.public_key());
+                id
+            }
+            None => {
+                let keypair = Keypair::new_ed25519();
+                info!(
+                    "Client started for new randomly created pk: {:?}",
+                    keypair.public_key()
+                );
+                keypair
+            }
+        };
+
+        // Incoming error notifiers
+        let (err_sender, err_receiver) = tokio::sync::mpsc::channel::<CmdError>(10);
+
+        let client_pk = keypair.public_key();
+
+        // Bootstrap to the network, connecting to a section based
+        // on a public key of our choice.
+        debug!(
+            "Creating new session with genesis key: {:?} ",
+            config.genesis_key
+        );
+        debug!(
+            "Creating new session with genesis key (in hex format): {} ",
+            hex::encode(config.genesis_key.to_bytes())
+        );
+
+        // Create a session with the network
+        let session = Session::new(
+            client_pk,
+            config.genesis_key,
+            transport,
+            err_sender,
+            config.cmd_ack_wait,
+            prefix_map,
+            config.network_name.as_deref().map(NetworkName::new),
+            config.bootstrap.contacts_per_batch,
+        )?;
+
+        Ok(Self {
+            signer: Arc::new(keypair.clone()),
+            keypair,
+            dbc_owner: dbc_owner
+                .unwrap_or_else(|| Owner::from_random_secret_key(&mut rng::thread_rng())),
+            session,
+            incoming_errors: Arc::new(RwLock::new(err_receiver)),
+            query_timeouts: config
+                .query_timeouts
+                .unwrap_or_else(|| QueryTimeouts::from_query_timeout(config.query_timeout)),
+            query_timeout_metrics: Arc::new(QueryTimeoutMetrics::default()),
+            cmd_timeout: config.cmd_timeout,
+            ordered_cmd_streams: Arc::new(OrderedCmdStreams::default()),
+            chunks_cache: Arc::new(RwLock::new(ChunksCache::default())),
+            repair_missing_chunks: true,
+            telemetry: None,
+            bandwidth: Arc::new(BandwidthLimiter::new(config.bandwidth_limits)),
+            padding: config.padding_buckets,
+        })
+    }
+
     /// Return the client's keypair.
     ///
-    /// Useful for retrieving the PublicKey or KeyPair in the event you need to _sign_ something
+    /// Useful for retrieving the PublicKey or KeyPair in the event you need to _sign_ something.
+    /// Note this is not the keypair the client signs with if it was set up with another [`Signer`].
     ///
     /// # Examples
     ///
@@ -278,7 +369,12 @@ impl Client {
     /// TODO: update once data types are crdt compliant
     ///
     pub fn public_key(&self) -> PublicKey {
-        self.keypair().public_key()
+        self.signer.public_key()
+    }
+
+    /// Sign the given data with the client's [`Signer`].
+    pub async fn sign(&self, data: &[u8]) -> Result<Signature, Error> {
+        self.signer.sign(data).await
     }
 
     /// Return the client's DBC owner, which will be a secret key.
@@ -287,6 +383,242 @@ impl Client {
     pub fn dbc_owner(&self) -> Owner {
         self.dbc_owner.clone()
     }
+
+    /// Check a storage receipt was signed by a section of the network we are connected to.
+    pub async fn verify_storage_receipt(&self, receipt: &StorageReceipt) -> Result<(), Error> {
+        if !receipt.verify() {
+            return Err(Error::InvalidStorageReceipt(
+                "signature doesn't match its content".to_string(),
+            ));
+        }
+
+        if !self
+            .session
+            .is_known_section_key(&receipt.sig.public_key)
+            .await
+        {
+            return Err(Error::InvalidStorageReceipt(format!(
+                "signed with a key which is not known as a section key: {:?}",
+                receipt.sig.public_key
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Check whether the key is the current key of one of the sections of the network we are
+    /// connected to.
+    pub fn is_current_section_key(&self, key: &bls::PublicKey) -> bool {
+        self.session.is_current_section_key(key)
+    }
+
+    /// Check whether the key is a key of a section of the network we are connected to, either
+    /// its current one or one it had before, as found walking the chains of section keys we know.
+    pub async fn is_known_section_key(&self, key: &bls::PublicKey) -> bool {
+        self.session.is_known_section_key(key).await
+    }
+
+    /// Return a snapshot of what this client knows of the network, i.e. the sections it learnt
+    /// about and its connections to their Elders, e.g. to attach to a bug report.
+    pub async fn network_snapshot(&self) -> NetworkSnapshot {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.session.network_snapshot(timestamp).await
+    }
+
+    /// Return the number of msgs received which were sent within another network, and dropped.
+    pub fn other_network_msgs_dropped(&self) -> u64 {
+        self.session.other_network_msgs_dropped()
+    }
+
+    /// Return the stats of the cache of the sections responsible for the data names msgs were
+    /// sent for, including how many msgs were retried after being sent to a stale section.
+    pub fn routing_cache_stats(&self) -> RoutingCacheStats {
+        self.session.routing_cache_stats()
+    }
+
+    /// Return how the queries sent by this client, and the copies sharing its connections, went
+    /// by the size class of the data they fetched, e.g. how many of them timed out.
+    pub fn query_timeout_stats(&self) -> BTreeMap<QuerySizeClass, QueryTimeoutStats> {
+        self.query_timeout_metrics.stats()
+    }
+
+    /// Return a copy of this client, sharing its connections and caches, which does
+    /// (or does not) report missing chunks to the network for repair when reading them.
+    ///
+    /// Chunk repair is enabled by default. Disabling it makes a read fail as soon as a chunk
+    /// can't be found, which suits latency-sensitive reads better than waiting for the repair.
+    pub fn with_chunk_repair(&self, repair_missing_chunks: bool) -> Self {
+        Self {
+            repair_missing_chunks,
+            ..self.clone()
+        }
+    }
+
+    /// Return a copy of this client, sharing its connections and caches, which signs
+    /// everything it sends to the network with the given [`Signer`] instead of its keypair.
+    pub fn with_signer(&self, signer: Arc<dyn Signer>) -> Self {
+        Self {
+            signer,
+            ..self.clone()
+        }
+    }
+
+    /// Return a copy of this client, sharing its connections and caches, which reports a
+    /// [`TelemetryEvent`] to the given [`Telemetry`] for each operation it sends to the network.
+    pub fn with_telemetry(&self, telemetry: Arc<dyn Telemetry>) -> Self {
+        Self {
+            telemetry: Some(telemetry),
+            ..self.clone()
+        }
+    }
+
+    /// Return a copy of this client, sharing its connections and caches, whose transfers are
+    /// limited by the given [`BandwidthLimits`] instead of the ones of this client, e.g. to run
+    /// a background sync at a lower rate than the interactive operations.
+    ///
+    /// The limits are shared by the copy and its own copies, but not with this client.
+    pub fn with_bandwidth_limits(&self, limits: BandwidthLimits) -> Self {
+        Self {
+            bandwidth: Arc::new(BandwidthLimiter::new(limits)),
+            ..self.clone()
+        }
+    }
+
+    /// Return a copy of this client, sharing its connections and caches, which pads the private
+    /// data it uploads according to the given [`PaddingBuckets`] instead of the ones of this client.
+    pub fn with_padding(&self, padding: PaddingBuckets) -> Self {
+        Self {
+            padding,
+            ..self.clone()
+        }
+    }
+
+    /// Return the limits to the rate of data this client sends and receives.
+    pub fn bandwidth_limits(&self) -> BandwidthLimits {
+        self.bandwidth.limits()
+    }
+
+    /// Return the rate at which this client, and the copies sharing its bandwidth limits,
+    /// recently sent and received data.
+    pub fn throughput(&self) -> Throughput {
+        self.bandwidth.throughput()
+    }
+
+    // Private helper to reconnect to the Elders closest to the given name, after the connections
+    // to them were lost, after a random delay and retrying with jittered exponential backoff.
+    pub(crate) async fn reconnect(&self, dst_name: XorName) -> Result<(), Error> {
+        self.report_connection_state(ConnectionState::Disconnected);
+
+        let mut backoff = ExponentialBackoff {
+            initial_interval: Duration::from_millis(500),
+            max_interval: Duration::from_secs(5),
+            max_elapsed_time: Some(RECONNECT_MAX_ELAPSED_TIME),
+            randomization_factor: RECONNECT_RANDOMIZATION_FACTOR,
+            ..Default::default()
+        };
+
+        // this seems needed for custom settings to take effect
+        backoff.reset();
+
+        let initial_delay = RECONNECT_MAX_INITIAL_DELAY.mul_f64(rand::random::<f64>());
+        debug!("Reconnecting in {:?}", initial_delay);
+        tokio::time::sleep(initial_delay).await;
+
+        let mut attempt = 1;
+        loop {
+            self.report_connection_state(ConnectionState::Reconnecting { attempt });
+            let (dst_address, auth, serialised_query) =
+                generate_probe_msg(self, self.public_key(), dst_name)?;
+            match self
+                .session
+                .reconnect_to_elders(dst_address, auth, serialised_query)
+                .await
+            {
+                Ok(()) => {
+                    info!("Reconnected to the Elders closest to {:?}", dst_name);
+                    self.report_connection_state(ConnectionState::Reconnected);
+                    return Ok(());
+                }
+                Err(error) => match backoff.next_backoff() {
+                    Some(delay) => {
+                        warn!(
+                            "Reconnection attempt #{} failed, trying again in {:?}: {:?}",
+                            attempt, delay, error
+                        );
+                        tokio::time::sleep(delay).await;
+                        attempt += 1;
+                    }
+                    None => {
+                        warn!("Giving up on reconnecting after {} attempts", attempt);
+                        self.report_connection_state(ConnectionState::ReconnectFailed);
+                        return Err(error);
+                    }
+                },
+            }
+        }
+    }
+
+    // Private helper to report a change of the connections state to the telemetry, if any was set.
+    fn report_connection_state(&self, state: ConnectionState) {
+        if let Some(telemetry) = &self.telemetry {
+            telemetry.on_connection_state(state);
+        }
+    }
+
+    // Private helper to report the outcome of an operation to the telemetry, if any was set.
+    pub(crate) fn report_operation<T>(
+        &self,
+        operation: OperationKind,
+        dst_name: &XorName,
+        started: Instant,
+        retries: usize,
+        result: &Result<T, Error>,
+    ) {
+        if let Some(telemetry) = &self.telemetry {
+            let outcome = match result {
+                Ok(_) => Outcome::Success,
+                Err(error) => Outcome::Failure(error.to_string()),
+            };
+            telemetry.on_event(TelemetryEvent {
+                operation,
+                section_prefix: self.session.section_prefix_of(dst_name),
+                elapsed: started.elapsed(),
+                retries,
+                outcome,
+                throughput: self.bandwidth.throughput(),
+            });
+        }
+    }
+}
+
+// Generate a query of a Register at the given address, sent as a dummy message to probe
+// the network, or reconnect to it.
+fn generate_probe_msg(
+    client: &Client,
+    pk: PublicKey,
+    dst_addr: XorName,
+) -> Result<(XorName, ServiceAuth, Bytes), Error> {
+    let serialised_cmd = {
+        let msg = ServiceMsg::Query(DataQuery::Register(RegisterQuery::Get(
+            RegisterAddress::Public {
+                name: dst_addr,
+                tag: 1,
+            },
+        )));
+        WireMsg::serialize_msg_payload(&msg)?
+    };
+    let signature = client
+        .keypair
+        .sign(&SigningDomain::Service.signable_bytes(&serialised_cmd));
+    let auth = ServiceAuth {
+        public_key: pk,
+        signature,
+    };
+
+    Ok((dst_addr, auth, serialised_cmd))
 }
 
 #[cfg(test)]
@@ -375,4 +707,63 @@ mod tests {
         assert_eq!(dbc_owner, client.dbc_owner());
         Ok(())
     }
+
+    #[derive(Debug, Default)]
+    struct ConnectionStates(std::sync::Mutex<Vec<ConnectionState>>);
+
+    impl Telemetry for ConnectionStates {
+        fn on_event(&self, _event: TelemetryEvent) {}
+
+        fn on_connection_state(&self, state: ConnectionState) {
+            self.0.lock().expect("poisoned lock").push(state);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn client_reconnects_to_elders() -> Result<()> {
+        init_logger();
+        let states = Arc::new(ConnectionStates::default());
+        let client = create_test_client().await?.with_telemetry(states.clone());
+
+        let bytes = random_bytes(self_encryption::MIN_ENCRYPTABLE_BYTES / 2);
+        let address = client.upload(bytes.clone(), Scope::Public).await?;
+
+        client.reconnect(*address.name()).await?;
+        assert_eq!(
+            *states.0.lock().expect("poisoned lock"),
+            vec![
+                ConnectionState::Disconnected,
+                ConnectionState::Reconnecting { attempt: 1 },
+                ConnectionState::Reconnected
+            ]
+        );
+
+        // queries are sent over the new connections
+        let read_data = client.read_bytes(address).await?;
+        assert_eq!(read_data, bytes);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn section_key_history_leads_to_current_key() -> Result<()> {
+        init_logger();
+        let client = create_test_client().await?;
+
+        // the root prefix leads to a section however many times the network split
+        let history = client
+            .get_section_key_history(xor_name::Prefix::default(), None)
+            .await?;
+        let current_key = *history.last_key();
+        assert!(client.is_known_section_key(&current_key).await);
+
+        // a light client which checked the history before only needs what followed
+        let latest = client
+            .get_section_key_history(xor_name::Prefix::default(), Some(current_key))
+            .await?;
+        assert_eq!(latest.root_key(), &current_key);
+        assert!(latest.len() <= history.len());
+
+        Ok(())
+    }
 }
@@ -6,16 +6,21 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::Client;
-use crate::{connections::QueryResult, errors::Error};
+use super::{section_members::SectionMembersReassembly, Client};
+use crate::{
+    bandwidth::Lane, connections::QueryResult, errors::Error, OperationKind, QuerySizeClass,
+};
 use bytes::Bytes;
 use rand::Rng;
+use secured_linked_list::SecuredLinkedList;
 use sn_interface::messaging::{
-    data::{DataQuery, ServiceMsg},
-    ServiceAuth, WireMsg,
+    data::{DataQuery, QueryResponse, SectionMember, SectionStats, ServiceMsg},
+    ServiceAuth, SigningDomain, WireMsg,
 };
 use sn_interface::types::{PublicKey, Signature};
+use tokio::time::Instant;
 use tracing::{debug, info_span};
+use xor_name::Prefix;
 
 // We divide the total query timeout by this number.
 // This also represents the max retries possible, while still staying within the max_timeout.
@@ -24,9 +29,25 @@ const MAX_RETRY_COUNT: f32 = 5.0;
 impl Client {
     /// Send a Query to the network and await a response.
     /// Queries are automatically retried using exponential backoff if the timeout is hit.
+    /// If the connections to the Elders are lost, the client reconnects to them and sends
+    /// the query again.
+    /// The timeout is the one of the [`QuerySizeClass`] of the query, as estimated from its type.
     #[instrument(skip(self), level = "debug")]
     pub async fn send_query(&self, query: DataQuery) -> Result<QueryResult, Error> {
-        self.send_query_with_retry_count(query, MAX_RETRY_COUNT)
+        let size_class = QuerySizeClass::from(&query);
+        self.send_query_with_retry_count(query, size_class, MAX_RETRY_COUNT)
+            .await
+    }
+
+    /// Send a Query to the network and await a response, as [`Client::send_query`] does, with
+    /// the timeout of the given [`QuerySizeClass`], e.g. as estimated from a data map.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn send_query_of_size_class(
+        &self,
+        query: DataQuery,
+        size_class: QuerySizeClass,
+    ) -> Result<QueryResult, Error> {
+        self.send_query_with_retry_count(query, size_class, MAX_RETRY_COUNT)
             .await
     }
 
@@ -34,7 +55,9 @@ impl Client {
     /// Queries are not retried if the timeout is hit.
     #[instrument(skip(self), level = "debug")]
     pub async fn send_query_without_retry(&self, query: DataQuery) -> Result<QueryResult, Error> {
-        self.send_query_with_retry_count(query, 1.0).await
+        let size_class = QuerySizeClass::from(&query);
+        self.send_query_with_retry_count(query, size_class, 1.0)
+            .await
     }
 
     // Send a Query to the network and await a response.
@@ -44,25 +67,39 @@ impl Client {
     async fn send_query_with_retry_count(
         &self,
         query: DataQuery,
+        size_class: QuerySizeClass,
         retry_count: f32,
     ) -> Result<QueryResult, Error> {
+        let started = Instant::now();
         let client_pk = self.public_key();
         let msg = ServiceMsg::Query(query.clone());
         let serialised_query = WireMsg::serialize_msg_payload(&msg)?;
-        let signature = self.keypair.sign(&serialised_query);
+        let signature = self
+            .signer
+            .sign(&SigningDomain::Service.signable_bytes(&serialised_query))
+            .await?;
 
         let mut rng = rand::rngs::OsRng;
 
         // Add jitter so not all clients retry at the same rate. This divider will knock on to the overall retry window
         // and should help prevent elders from being conseceutively overwhelmed
         let jitter = rng.gen_range(1.0..1.5);
-        let attempt_timeout = self.query_timeout.div_f32(retry_count + jitter);
-        trace!("Setting up query retry, interval is: {:?}", attempt_timeout);
+        let attempt_timeout = self
+            .query_timeouts
+            .get(size_class)
+            .div_f32(retry_count + jitter);
+        trace!(
+            "Setting up {:?} query retry, interval is: {:?}",
+            size_class,
+            attempt_timeout
+        );
+        self.query_timeout_metrics
+            .record(size_class, |stats| stats.queries += 1);
 
         let span = info_span!("Attempting a query");
         let _ = span.enter();
         let mut attempt = 1.0;
-        loop {
+        let res = loop {
             debug!(
                 "Attempting {:?} (attempt #{}) with a query timeout of {:?}",
                 query, attempt, attempt_timeout
@@ -81,21 +118,53 @@ impl Client {
 
             if let Ok(Ok(query_result)) = res {
                 break Ok(query_result);
-            } else if attempt > MAX_RETRY_COUNT {
+            } else if let Ok(Err(error)) = res {
+                if error.is_connection_lost() {
+                    self.query_timeout_metrics
+                        .record(size_class, |stats| stats.attempts_disconnected += 1);
+                    // queries don't change any data, so they can be sent again once reconnected
+                    warn!("Connections lost while sending {:?}: {:?}", query, error);
+                    if let Err(error) = self.reconnect(query.dst_name()).await {
+                        break Err(error);
+                    }
+                } else {
+                    self.query_timeout_metrics
+                        .record(size_class, |stats| stats.attempts_failed += 1);
+                }
+            } else {
+                self.query_timeout_metrics
+                    .record(size_class, |stats| stats.attempts_elapsed += 1);
+            }
+
+            if attempt > MAX_RETRY_COUNT {
                 debug!(
                     "Retries ({}) all failed returning no response for {:?}",
                     MAX_RETRY_COUNT, query
                 );
+                self.query_timeout_metrics
+                    .record(size_class, |stats| stats.timed_out += 1);
                 break Err(Error::NoResponse);
             }
 
             attempt += 1.0;
-        }
+        };
+
+        self.report_operation(
+            OperationKind::from(&query),
+            &query.dst_name(),
+            started,
+            attempt as usize - 1,
+            &res,
+        );
+
+        res
     }
 
     /// Send a Query to the network and await a response.
     /// This is part of a public API, for the user to
     /// provide the serialised and already signed query.
+    /// The signature is expected to be made over the [`SigningDomain::Service`] signable bytes
+    /// of the serialised query.
     pub async fn send_signed_query(
         &self,
         query: DataQuery,
@@ -109,6 +178,90 @@ impl Client {
             signature,
         };
 
-        self.session.send_query(query, auth, serialised_query).await
+        let lane = Lane::from(OperationKind::from(&query));
+        self.session
+            .send_query(query, auth, serialised_query, self.bandwidth.clone(), lane)
+            .await
+    }
+
+    /// Get the stats of the section with the given prefix, as reported by its Elders.
+    /// If the section split since, the stats of the child section the prefix leads to are returned.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn get_section_stats(&self, prefix: Prefix) -> Result<SectionStats, Error> {
+        let query = DataQuery::GetSectionStats(prefix);
+        let query_result = self.send_query(query).await?;
+        match query_result.response {
+            QueryResponse::GetSectionStats((res, op_id)) => {
+                res.map_err(|err| Error::ErrorMsg { source: err, op_id })
+            }
+            _ => Err(Error::ReceivedUnexpectedEvent),
+        }
+    }
+
+    /// Get the chain of keys of the section with the given prefix, from the given key it had, or
+    /// from the genesis key if none, to its current key, each key being signed with the one
+    /// before it. The chain is checked to be such, and to start from that key.
+    ///
+    /// Light clients which only trust the genesis key, or a key they checked before, can then
+    /// validate what was signed with any key of the chain, e.g. old storage receipts, spent proofs
+    /// or SAPs, without keeping track of the whole network.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn get_section_key_history(
+        &self,
+        prefix: Prefix,
+        since: Option<bls::PublicKey>,
+    ) -> Result<SecuredLinkedList, Error> {
+        let query = DataQuery::GetSectionKeyHistory { prefix, since };
+        let query_result = self.send_query(query).await?;
+        let history = match query_result.response {
+            QueryResponse::GetSectionKeyHistory((res, op_id)) => {
+                res.map_err(|err| Error::ErrorMsg { source: err, op_id })?
+            }
+            _ => return Err(Error::ReceivedUnexpectedEvent),
+        };
+
+        let from_key = since.unwrap_or_else(|| self.session.genesis_key());
+        if history.root_key() != &from_key {
+            return Err(Error::InvalidSectionKeyHistory(format!(
+                "starts from {:?} instead of {:?}",
+                history.root_key(),
+                from_key
+            )));
+        }
+        if !history.self_verify() {
+            return Err(Error::InvalidSectionKeyHistory(
+                "a key is not signed by the one before it".to_string(),
+            ));
+        }
+
+        Ok(history)
+    }
+
+    /// Get the members of the section with the given prefix, as reported by its Elders, sorted
+    /// by name. They are retrieved a page at a time, each page fitting in a single msg however
+    /// large the section is, and the pages put back together. If the section changes meanwhile,
+    /// its members are retrieved again from the first one.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn get_section_members(&self, prefix: Prefix) -> Result<Vec<SectionMember>, Error> {
+        let mut reassembly = SectionMembersReassembly::default();
+        let mut continuation = None;
+        loop {
+            let query = DataQuery::GetSectionMembers {
+                prefix,
+                continuation,
+            };
+            let query_result = self.send_query(query).await?;
+            let page = match query_result.response {
+                QueryResponse::GetSectionMembers((res, op_id)) => {
+                    res.map_err(|err| Error::ErrorMsg { source: err, op_id })?
+                }
+                _ => return Err(Error::ReceivedUnexpectedEvent),
+            };
+
+            continuation = reassembly.add_page(page)?;
+            if continuation.is_none() {
+                return Ok(reassembly.into_members());
+            }
+        }
     }
 }
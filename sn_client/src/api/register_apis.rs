@@ -10,8 +10,9 @@ use super::Client;
 
 use crate::Error;
 use sn_interface::messaging::data::{
-    CreateRegister, DataCmd, DataQuery, DeleteRegister, EditRegister, QueryResponse, RegisterCmd,
-    RegisterQuery, SignedRegisterCreate, SignedRegisterDelete, SignedRegisterEdit,
+    CmdAckLevel, CreateRegister, DataCmd, DataQuery, DeleteRegister, EditRegister, QueryResponse,
+    RegisterCmd, RegisterQuery, SignedRegisterCreate, SignedRegisterDelete, SignedRegisterEdit,
+    SignedRegisterEditBatch,
 };
 use sn_interface::types::{
     register::{Action, Entry, EntryHash, Permissions, Policy, Register, User},
@@ -37,10 +38,19 @@ impl Client {
     /// Publish all register mutation operations in a WAL to the network
     /// Incrementing the WAL index as successful writes are sent out. Stops at the first error.
     /// Starts publishing from the index when called again with the same WAL.
+    ///
+    /// When `ordered` is set, the operations are applied in the order they're published, along
+    /// with the ones published before with `ordered` set, see [`Client::send_cmd_with_ack_level`].
     #[instrument(skip(self), level = "debug")]
-    pub async fn publish_register_ops(&self, wal: RegisterWriteAheadLog) -> Result<(), Error> {
+    pub async fn publish_register_ops(
+        &self,
+        wal: RegisterWriteAheadLog,
+        ordered: bool,
+    ) -> Result<(), Error> {
         for cmd in wal.iter() {
-            self.send_cmd(cmd.clone()).await?;
+            let _receipt = self
+                .send_cmd_with_ack_level(cmd.clone(), CmdAckLevel::default(), ordered)
+                .await?;
         }
         Ok(())
     }
@@ -71,13 +81,13 @@ impl Client {
             size: u16::MAX, // TODO: use argument
             policy,
         };
-        let signature = self.keypair.sign(&bincode::serialize(&op)?);
+        let signature = self.signer.sign(&bincode::serialize(&op)?).await?;
 
         let cmd = DataCmd::Register(RegisterCmd::Create {
             cmd: SignedRegisterCreate {
                 op,
                 auth: sn_interface::messaging::ServiceAuth {
-                    public_key: self.keypair.public_key(),
+                    public_key: self.public_key(),
                     signature,
                 },
             },
@@ -98,12 +108,12 @@ impl Client {
     #[instrument(skip(self), level = "debug")]
     pub async fn delete_register(&self, address: Address) -> Result<RegisterWriteAheadLog, Error> {
         let op = DeleteRegister(address);
-        let signature = self.keypair.sign(&bincode::serialize(&op)?);
+        let signature = self.signer.sign(&bincode::serialize(&op)?).await?;
 
         let update = SignedRegisterDelete {
             op,
             auth: sn_interface::messaging::ServiceAuth {
-                public_key: self.keypair.public_key(),
+                public_key: self.public_key(),
                 signature,
             },
         };
@@ -136,14 +146,81 @@ impl Client {
 
         // Let's check the policy/permissions to make sure this operation is allowed,
         // otherwise it will fail when the operation is applied on the network replica.
-        let public_key = self.keypair.public_key();
+        let public_key = self.public_key();
+        register.check_permissions(Action::Write, Some(User::Key(public_key)))?;
+
+        let (hash, edit) = self
+            .sign_register_write(&mut register, address, entry, children)
+            .await?;
+
+        // Finally we package the mutation for the network's replicas (it's now ready to be sent)
+        let cmd = DataCmd::Register(RegisterCmd::Edit(edit));
+        let batch = vec![cmd];
+        Ok((hash, batch))
+    }
+
+    /// Write several entries to Register at once
+    ///
+    /// Returns a write ahead log (WAL) of register operations, note that the changes are not uploaded to the
+    /// network until the WAL is published with `publish_register_ops`
+    ///
+    /// The entries are written one after the other, each of them the child of the one before it,
+    /// the first one being the child of the given `children`. They are all packed into a single
+    /// cmd, so they're sent to the network in one go, where they're all applied or none of them.
+    /// The hashes of the entries are returned in the same order as the entries.
+    #[instrument(skip(self, entries, children), level = "debug")]
+    pub async fn write_batch_to_register(
+        &self,
+        address: Address,
+        entries: Vec<Entry>,
+        children: BTreeSet<EntryHash>,
+    ) -> Result<(Vec<EntryHash>, RegisterWriteAheadLog), Error> {
+        debug!(
+            "Writing {} entries to register at {:?}",
+            entries.len(),
+            address
+        );
+        let mut register = self.get_register(address).await?;
+
+        let public_key = self.public_key();
         register.check_permissions(Action::Write, Some(User::Key(public_key)))?;
 
-        // We can now write the entry to the Register
-        let (hash, op) = register.write(entry, children)?;
+        let mut hashes = Vec::new();
+        let mut edits = Vec::new();
+        let mut children = children;
+        for entry in entries {
+            let (hash, edit) = self
+                .sign_register_write(&mut register, address, entry, children)
+                .await?;
+            hashes.push(hash);
+            edits.push(edit);
+            children = BTreeSet::from([hash]);
+        }
+
+        let cmd = DataCmd::Register(RegisterCmd::EditBatch(SignedRegisterEditBatch {
+            address,
+            edits,
+        }));
+        Ok((hashes, vec![cmd]))
+    }
+
+    // Writes the entry to our replica of the Register, returning the edit to send to the network.
+    // The entry is written as ours and signed by us, for it to be attributable to us in Registers
+    // anyone can write to.
+    async fn sign_register_write(
+        &self,
+        register: &mut Register,
+        address: Address,
+        entry: Entry,
+        children: BTreeSet<EntryHash>,
+    ) -> Result<(EntryHash, SignedRegisterEdit), Error> {
+        let public_key = self.public_key();
+        let (hash, mut op) = register.write(entry, children)?;
+        op.source = User::Key(public_key);
+        op.signature = Some(self.signer.sign(&bincode::serialize(&op.crdt_op)?).await?);
         let op = EditRegister { address, edit: op };
 
-        let signature = self.keypair.sign(&bincode::serialize(&op)?);
+        let signature = self.signer.sign(&bincode::serialize(&op)?).await?;
 
         let edit = SignedRegisterEdit {
             op,
@@ -153,10 +230,7 @@ impl Client {
             },
         };
 
-        // Finally we package the mutation for the network's replicas (it's now ready to be sent)
-        let cmd = DataCmd::Register(RegisterCmd::Edit(edit));
-        let batch = vec![cmd];
-        Ok((hash, batch))
+        Ok((hash, edit))
     }
 
     //----------------------
@@ -330,7 +404,7 @@ mod tests {
         batch.append(&mut batch2);
 
         // publish that batch to the network
-        client.publish_register_ops(batch).await?;
+        client.publish_register_ops(batch, false).await?;
         tokio::time::sleep(one_sec).await;
 
         // check they're both there
@@ -376,7 +450,7 @@ mod tests {
         let (_address, batch) = client
             .create_register(name, tag, private_policy(owner))
             .await?;
-        client.publish_register_ops(batch).await?;
+        client.publish_register_ops(batch, false).await?;
 
         // small delay to ensure logs have written
         tokio::time::sleep(delay).await;
@@ -402,7 +476,7 @@ mod tests {
         let (address, batch) = client
             .create_register(name, tag, public_policy(owner))
             .await?;
-        client.publish_register_ops(batch).await?;
+        client.publish_register_ops(batch, false).await?;
 
         let mut total = 0;
         let value_1 = random_register_entry();
@@ -413,7 +487,7 @@ mod tests {
             let (_value1_hash, batch) = client
                 .write_to_register(address, value_1.clone(), BTreeSet::new())
                 .await?;
-            client.publish_register_ops(batch).await?;
+            client.publish_register_ops(batch, false).await?;
 
             let elapsed = now.elapsed().as_millis();
             total += elapsed;
@@ -441,7 +515,7 @@ mod tests {
         let (address, batch) = client
             .create_register(name, tag, private_policy(owner))
             .await?;
-        client.publish_register_ops(batch).await?;
+        client.publish_register_ops(batch, false).await?;
 
         let delay = tokio::time::Duration::from_secs(1);
         tokio::time::sleep(delay).await;
@@ -458,7 +532,7 @@ mod tests {
         let (address, batch) = client
             .create_register(name, tag, public_policy(owner))
             .await?;
-        client.publish_register_ops(batch).await?;
+        client.publish_register_ops(batch, false).await?;
 
         tokio::time::sleep(delay).await;
         let register = client.get_register(address).await?;
@@ -486,7 +560,7 @@ mod tests {
         let (address, batch) = client
             .create_register(name, tag, private_policy(owner))
             .await?;
-        client.publish_register_ops(batch).await?;
+        client.publish_register_ops(batch, false).await?;
 
         let delay = tokio::time::Duration::from_secs(1);
         tokio::time::sleep(delay).await;
@@ -544,7 +618,7 @@ mod tests {
         let (address, batch) = client
             .create_register(name, tag, public_none_policy(owner)) // trying to set write perms to false for the owner (will not be reflected as long as the user is the owner, as an owner will have full authority)
             .await?;
-        client.publish_register_ops(batch).await?;
+        client.publish_register_ops(batch, false).await?;
 
         let delay = tokio::time::Duration::from_secs(1);
         tokio::time::sleep(delay).await;
@@ -585,6 +659,41 @@ mod tests {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn register_ordered_writes() -> Result<()> {
+        init_logger();
+        let _outer_span = tracing::info_span!("test__register_ordered_writes").entered();
+
+        let client = create_test_client().await?;
+
+        let name = xor_name::rand::random();
+        let tag = 10;
+        let owner = User::Key(client.public_key());
+
+        let (address, batch) = client
+            .create_register(name, tag, public_policy(owner))
+            .await?;
+        client.publish_register_ops(batch, true).await?;
+
+        // each entry follows the previous one, so only the last one is left as head
+        let mut parents = BTreeSet::new();
+        let mut last_entry = None;
+        for _ in 0..3 {
+            let value = random_register_entry();
+            let (hash, batch) = client
+                .write_to_register(address, value.clone(), parents)
+                .await?;
+            client.publish_register_ops(batch, true).await?;
+            parents = BTreeSet::from([hash]);
+            last_entry = Some((hash, value));
+        }
+
+        let hashes = retry_loop_for_pattern!(client.read_register(address), Ok(hashes) if hashes.iter().next() == last_entry.as_ref())?;
+        assert_eq!(1, hashes.len());
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn register_write() -> Result<()> {
         init_logger();
@@ -599,14 +708,14 @@ mod tests {
         let (address, batch) = client
             .create_register(name, tag, public_policy(owner))
             .await?;
-        client.publish_register_ops(batch).await?;
+        client.publish_register_ops(batch, false).await?;
 
         let value_1 = random_register_entry();
 
         let (value1_hash, batch) = client
             .write_to_register(address, value_1.clone(), BTreeSet::new())
             .await?;
-        client.publish_register_ops(batch).await?;
+        client.publish_register_ops(batch, false).await?;
 
         // now check last entry
         let hashes = retry_loop_for_pattern!(client.read_register(address), Ok(hashes) if !hashes.is_empty())?;
@@ -625,7 +734,7 @@ mod tests {
             .write_to_register(address, value_2.clone(), BTreeSet::new())
             .await?;
 
-        client.publish_register_ops(batch).await?;
+        client.publish_register_ops(batch, false).await?;
 
         // and then lets check all entries are returned
         // NB: these will not be ordered according to insertion order, but according to the hashes of the values.
@@ -686,7 +795,7 @@ mod tests {
         let (address, batch) = client
             .create_register(name, tag, private_policy(owner))
             .await?;
-        client.publish_register_ops(batch).await?;
+        client.publish_register_ops(batch, false).await?;
 
         // Assert that the data is stored.
         let current_owner = client.get_register_owner(address).await?;
@@ -710,7 +819,7 @@ mod tests {
         let (address, batch) = client
             .create_register(name, tag, private_policy(owner))
             .await?;
-        client.publish_register_ops(batch).await?;
+        client.publish_register_ops(batch, false).await?;
 
         let delay = tokio::time::Duration::from_secs(1);
         tokio::time::sleep(delay).await;
@@ -720,14 +829,14 @@ mod tests {
         assert!(register.is_private());
 
         let batch2 = client.delete_register(address).await?;
-        client.publish_register_ops(batch2).await?;
+        client.publish_register_ops(batch2, false).await?;
 
-        client.query_timeout = Duration::from_secs(5); // override with a short timeout
+        client.query_timeouts.small = Duration::from_secs(5); // override with a short timeout
         let mut res = client.get_register(address).await;
         while res.is_ok() {
             // attempt to delete register again (perhaps a message was dropped)
             let batch3 = client.delete_register(address).await?;
-            client.publish_register_ops(batch3).await?;
+            client.publish_register_ops(batch3, false).await?;
             tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
             res = client.get_register(address).await;
         }
@@ -757,7 +866,7 @@ mod tests {
         let (address, batch) = client
             .create_register(name, tag, public_policy(owner))
             .await?;
-        client.publish_register_ops(batch).await?;
+        client.publish_register_ops(batch, false).await?;
 
         let delay = tokio::time::Duration::from_secs(1);
         tokio::time::sleep(delay).await;
@@ -766,7 +875,7 @@ mod tests {
         assert!(register.is_public());
 
         let batch2 = client.delete_register(address).await?;
-        match client.publish_register_ops(batch2).await {
+        match client.publish_register_ops(batch2, false).await {
             Err(Error::ErrorCmd {
                 source: ErrorMsg::InvalidOperation(_),
                 ..
@@ -799,7 +908,7 @@ mod tests {
         let (address, batch) = client
             .create_register(name, tag, public_policy(owner))
             .await?;
-        client.publish_register_ops(batch).await?;
+        client.publish_register_ops(batch, false).await?;
         tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
 
         let register = client.get_register(address).await?;
@@ -0,0 +1,222 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::Error;
+use sn_interface::messaging::data::{MembersContinuation, SectionMember, SectionMembersPage};
+
+// Number of times the members of a section are paged through again from the first one,
+// after the section key changed meanwhile, before giving up on them
+const MAX_RESTARTS: usize = 3;
+
+// Puts the pages of the members of a section back together, as they are received.
+#[derive(Debug, Default)]
+pub(super) struct SectionMembersReassembly {
+    section_key: Option<bls::PublicKey>,
+    members: Vec<SectionMember>,
+    restarts: usize,
+}
+
+impl SectionMembersReassembly {
+    // Adds the page received for the last continuation returned, or the first page.
+    // Returns where the next page continues from, if this one isn't the last.
+    pub(super) fn add_page(
+        &mut self,
+        page: SectionMembersPage,
+    ) -> Result<Option<MembersContinuation>, Error> {
+        if matches!(self.section_key, Some(key) if key != page.section_key) {
+            // The Elders started over from the first member, as the section changed
+            self.restarts += 1;
+            if self.restarts > MAX_RESTARTS {
+                return Err(Error::InvalidSectionMembers(format!(
+                    "section key changed {} times while its members were retrieved",
+                    self.restarts
+                )));
+            }
+            self.members.clear();
+        }
+        self.section_key = Some(page.section_key);
+
+        // Members have to follow the ones received so far, so that paging makes progress
+        let mut last = self.members.last().map(|member| member.name);
+        for member in &page.members {
+            if matches!(last, Some(last) if member.name <= last) {
+                return Err(Error::InvalidSectionMembers(
+                    "members are not sorted by name across pages".to_string(),
+                ));
+            }
+            last = Some(member.name);
+        }
+        if let Some(continuation) = &page.continuation {
+            if page.members.is_empty()
+                || continuation.section_key != page.section_key
+                || Some(continuation.after) != last
+            {
+                return Err(Error::InvalidSectionMembers(
+                    "page doesn't continue from its last member".to_string(),
+                ));
+            }
+        }
+
+        self.members.extend(page.members);
+        Ok(page.continuation)
+    }
+
+    // Members of the section, sorted by name.
+    pub(super) fn into_members(self) -> Vec<SectionMember> {
+        self.members
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SectionMembersReassembly, MAX_RESTARTS};
+    use crate::Error;
+    use sn_interface::messaging::data::{
+        MembersContinuation, SectionMember, SectionMembersPage, MAX_SECTION_MEMBERS_PAGE_SIZE,
+    };
+
+    use eyre::Result;
+    use std::net::{Ipv4Addr, SocketAddr};
+    use xor_name::{Prefix, XorName};
+
+    fn members(count: usize) -> Vec<SectionMember> {
+        let mut members: Vec<_> = (0..count)
+            .map(|index| SectionMember {
+                name: xor_name::rand::random(),
+                addr: SocketAddr::from((Ipv4Addr::LOCALHOST, 12000 + index as u16)),
+                is_elder: index < 7,
+            })
+            .collect();
+        members.sort_by_key(|member| member.name);
+        members
+    }
+
+    // Pages through the members as an Elder would, putting them back together
+    fn page_through(
+        members: &[SectionMember],
+        section_key: bls::PublicKey,
+        max_size: usize,
+    ) -> Result<(Vec<SectionMember>, usize)> {
+        let mut reassembly = SectionMembersReassembly::default();
+        let mut continuation = None;
+        let mut pages = 0;
+        loop {
+            let page = SectionMembersPage::new(
+                Prefix::default(),
+                section_key,
+                members.to_vec(),
+                continuation.as_ref(),
+                max_size,
+            );
+            pages += 1;
+            continuation = reassembly.add_page(page)?;
+            if continuation.is_none() {
+                return Ok((reassembly.into_members(), pages));
+            }
+        }
+    }
+
+    #[test]
+    fn pages_are_put_back_together_at_boundary_sizes() -> Result<()> {
+        let section_key = bls::SecretKey::random().public_key();
+        let members = members(900);
+        let member_size = bincode::serialized_size(&members[0])? as usize;
+
+        // a page is too small for any member
+        let (reassembled, pages) = page_through(&members, section_key, 0)?;
+        assert_eq!(reassembled, members);
+        assert_eq!(pages, members.len());
+
+        // pages fit exactly 300 members, or one less, pages being sized as if followed by another
+        let empty_page_size = bincode::serialized_size(&SectionMembersPage {
+            prefix: Prefix::default(),
+            section_key,
+            members: vec![],
+            continuation: Some(MembersContinuation {
+                section_key,
+                after: XorName::default(),
+            }),
+        })? as usize;
+        let max_size = empty_page_size + 300 * member_size;
+        let (reassembled, pages) = page_through(&members, section_key, max_size)?;
+        assert_eq!(reassembled, members);
+        assert_eq!(pages, 3);
+        let (reassembled, pages) = page_through(&members, section_key, max_size - 1)?;
+        assert_eq!(reassembled, members);
+        assert_eq!(pages, 4);
+
+        // a large section over the max page size
+        let (reassembled, pages) =
+            page_through(&members, section_key, MAX_SECTION_MEMBERS_PAGE_SIZE)?;
+        assert_eq!(reassembled, members);
+        assert!(pages > 1);
+
+        // no members at all
+        let (reassembled, pages) = page_through(&[], section_key, 0)?;
+        assert!(reassembled.is_empty());
+        assert_eq!(pages, 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn members_are_put_together_again_when_the_section_key_changes() -> Result<()> {
+        let members = members(10);
+        let mut section_key = bls::SecretKey::random().public_key();
+        let mut reassembly = SectionMembersReassembly::default();
+
+        let mut continuation = reassembly.add_page(SectionMembersPage::new(
+            Prefix::default(),
+            section_key,
+            members.clone(),
+            None,
+            0,
+        ))?;
+        for _ in 0..MAX_RESTARTS {
+            section_key = bls::SecretKey::random().public_key();
+            continuation = reassembly.add_page(SectionMembersPage::new(
+                Prefix::default(),
+                section_key,
+                members.clone(),
+                continuation.as_ref(),
+                0,
+            ))?;
+            assert_eq!(continuation.map(|c| c.after), Some(members[0].name));
+        }
+
+        let result = reassembly.add_page(SectionMembersPage::new(
+            Prefix::default(),
+            bls::SecretKey::random().public_key(),
+            members,
+            continuation.as_ref(),
+            0,
+        ));
+        assert!(matches!(result, Err(Error::InvalidSectionMembers(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn pages_not_making_progress_are_rejected() -> Result<()> {
+        let section_key = bls::SecretKey::random().public_key();
+        let members = members(4);
+        let mut reassembly = SectionMembersReassembly::default();
+
+        let first =
+            SectionMembersPage::new(Prefix::default(), section_key, members.clone(), None, 0);
+        let _ = reassembly.add_page(first.clone())?;
+
+        // the same page again would have us loop forever
+        assert!(matches!(
+            reassembly.add_page(first),
+            Err(Error::InvalidSectionMembers(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,438 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::OperationKind;
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, sync::Mutex};
+use tokio::{
+    sync::{Semaphore, SemaphorePermit},
+    time::{Duration, Instant},
+};
+
+// Period the throughput is averaged over.
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(5);
+// Share of each bandwidth limit reserved to the control lane, as its inverse, the bulk lane
+// being given the rest.
+const CONTROL_LANE_SHARE_INVERSE: u64 = 10;
+// Maximum number of msgs sent concurrently in the bulk lane, whether limits are set or not,
+// for the msgs of the control lane not to queue up behind them.
+const MAX_BULK_SENDS_IN_FLIGHT: usize = 32;
+
+/// Limits to the rate at which a [`Client`] sends data to, and receives data from, the network,
+/// in bytes per second. No limit is applied to a direction left as `None`.
+///
+/// The limits are shared by all the operations of the client, however many run concurrently,
+/// so e.g. a background sync can be kept from saturating the user's uplink. Msgs are accounted
+/// for once per Elder they're sent to, and responses once per Elder they're received from.
+///
+/// A tenth of each limit is reserved to the msgs other than chunks, e.g. the register and
+/// spentbook ones the authenticator and wallets send, so they don't queue up behind the chunks
+/// of large uploads or downloads, and interactive operations stay responsive. The reserve is
+/// a minimum: those msgs also use the share of the chunks while it's left idle.
+/// Whatever the limits, only so many chunks are sent concurrently, while the other msgs
+/// are sent right away.
+///
+/// [`Client`]: crate::Client
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BandwidthLimits {
+    /// Maximum number of bytes sent per second.
+    pub upload: Option<u64>,
+    /// Maximum number of bytes received per second.
+    pub download: Option<u64>,
+}
+
+/// Rate at which a [`Client`] recently sent and received data, in bytes per second,
+/// averaged over the last few seconds.
+///
+/// [`Client`]: crate::Client
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Throughput {
+    /// Number of bytes sent per second.
+    pub upload: u64,
+    /// Number of bytes received per second.
+    pub download: u64,
+}
+
+/// Lane of the [`BandwidthLimits`] a msg is sent or received in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Lane {
+    /// Chunks, and msgs whose content isn't known, given most of the limits.
+    Bulk,
+    /// Any other msgs, e.g. of registers or spentbooks, given a share of the limits reserved
+    /// to them, on top of the share of the bulk lane left idle.
+    Control,
+}
+
+impl From<OperationKind> for Lane {
+    fn from(operation: OperationKind) -> Self {
+        match operation {
+            OperationKind::ChunkRead | OperationKind::ChunkWrite => Self::Bulk,
+            OperationKind::MissingChunkReport
+            | OperationKind::RegisterRead
+            | OperationKind::RegisterWrite
+            | OperationKind::SpentbookRead
+            | OperationKind::SpentbookWrite
+            | OperationKind::SectionStatsRead
+            | OperationKind::SectionKeyHistoryRead
+            | OperationKind::SectionMembersRead => Self::Control,
+        }
+    }
+}
+
+/// Enforces the [`BandwidthLimits`] of a client, and measures its [`Throughput`].
+#[derive(Debug)]
+pub(crate) struct BandwidthLimiter {
+    limits: BandwidthLimits,
+    upload: Direction,
+    download: Direction,
+    bulk_sends: Semaphore,
+}
+
+/// Slot of a msg being sent, to be held until it's been sent.
+#[derive(Debug)]
+pub(crate) struct SendSlot<'a> {
+    _permit: Option<SemaphorePermit<'a>>,
+}
+
+impl BandwidthLimiter {
+    pub(crate) fn new(limits: BandwidthLimits) -> Self {
+        Self {
+            limits,
+            upload: Direction::new(limits.upload),
+            download: Direction::new(limits.download),
+            bulk_sends: Semaphore::new(MAX_BULK_SENDS_IN_FLIGHT),
+        }
+    }
+
+    pub(crate) fn limits(&self) -> BandwidthLimits {
+        self.limits
+    }
+
+    /// Wait until sending the given number of bytes is within the upload limit of the lane,
+    /// and, in the bulk lane, until a slot is free for it.
+    pub(crate) async fn upload(&self, bytes: usize, lane: Lane) -> SendSlot<'_> {
+        let permit = match lane {
+            Lane::Bulk => self.bulk_sends.acquire().await.ok(),
+            Lane::Control => None,
+        };
+        self.upload.transfer(bytes, lane).await;
+        SendSlot { _permit: permit }
+    }
+
+    /// Account for the given number of bytes received in the lane. As they were received
+    /// already, it's the queries sent next which wait for the download to be within the limit.
+    pub(crate) fn download(&self, bytes: usize, lane: Lane) {
+        let _wait = self.download.record(bytes, lane, Instant::now());
+    }
+
+    /// Wait until the bytes received so far are within the download limit of the lane.
+    pub(crate) async fn wait_for_download(&self, lane: Lane) {
+        let wait = self.download.reserve(0, lane, Instant::now());
+        if !wait.is_zero() {
+            trace!("Waiting {:?} to keep within the download limit", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    pub(crate) fn throughput(&self) -> Throughput {
+        let now = Instant::now();
+        Throughput {
+            upload: self.upload.meter_rate(now),
+            download: self.download.meter_rate(now),
+        }
+    }
+}
+
+// Limiter and meter of one direction of the transfers, with a bucket per lane
+#[derive(Debug)]
+struct Direction {
+    buckets: Option<(Mutex<TokenBucket>, Mutex<TokenBucket>)>,
+    meter: Mutex<Meter>,
+}
+
+impl Direction {
+    fn new(limit: Option<u64>) -> Self {
+        let now = Instant::now();
+        Self {
+            buckets: limit.map(|rate| {
+                let control_rate = rate / CONTROL_LANE_SHARE_INVERSE;
+                (
+                    Mutex::new(TokenBucket::new(rate - control_rate, now)),
+                    Mutex::new(TokenBucket::new(control_rate, now)),
+                )
+            }),
+            meter: Mutex::new(Meter::default()),
+        }
+    }
+
+    async fn transfer(&self, bytes: usize, lane: Lane) {
+        let wait = self.record(bytes, lane, Instant::now());
+        if !wait.is_zero() {
+            trace!("Waiting {:?} to keep within the bandwidth limit", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    // Measures the transfer and takes its tokens, returning how long to wait before proceeding
+    // with it.
+    fn record(&self, bytes: usize, lane: Lane, now: Instant) -> Duration {
+        if let Ok(mut meter) = self.meter.lock() {
+            meter.record(bytes, now);
+        }
+        self.reserve(bytes, lane, now)
+    }
+
+    // Takes the tokens for the transfer from the bucket of its lane, returning how long to wait
+    // before proceeding with it.
+    // Control transfers the control bucket can't let through right away take the tokens left
+    // idle in the bulk bucket, if there are enough of them, rather than wait.
+    fn reserve(&self, bytes: usize, lane: Lane, now: Instant) -> Duration {
+        let (bulk, control) = match &self.buckets {
+            None => return Duration::ZERO,
+            Some(buckets) => buckets,
+        };
+        let reserve = |bucket: &Mutex<TokenBucket>| {
+            bucket
+                .lock()
+                .map(|mut bucket| bucket.reserve(bytes, now))
+                .unwrap_or_default()
+        };
+        if lane == Lane::Bulk {
+            return reserve(bulk);
+        }
+
+        let mut control = match control.lock() {
+            Ok(control) => control,
+            Err(_) => return Duration::ZERO,
+        };
+        if control.try_take(bytes, now) {
+            return Duration::ZERO;
+        }
+        let borrowed = bulk
+            .lock()
+            .map(|mut bulk| bulk.try_take(bytes, now))
+            .unwrap_or_default();
+        if borrowed {
+            Duration::ZERO
+        } else {
+            control.reserve(bytes, now)
+        }
+    }
+
+    fn meter_rate(&self, now: Instant) -> u64 {
+        self.meter
+            .lock()
+            .map(|mut meter| meter.rate(now))
+            .unwrap_or_default()
+    }
+}
+
+// Token bucket holding up to a second worth of transfers.
+// Transfers larger than what's available are let through once the bucket refilled enough for
+// them, and transfers reserved while it's in debt queue up behind them.
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64, now: Instant) -> Self {
+        let rate = rate.max(1) as f64;
+        Self {
+            rate,
+            tokens: rate,
+            refilled: now,
+        }
+    }
+
+    // Takes the tokens for the transfer, returning how long to wait before proceeding with it.
+    fn reserve(&mut self, bytes: usize, now: Instant) -> Duration {
+        self.refill(now);
+        self.tokens -= bytes as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+
+    // Takes the tokens for the transfer only if there are enough of them for it to proceed
+    // right away.
+    fn try_take(&mut self, bytes: usize, now: Instant) -> bool {
+        self.refill(now);
+        if self.tokens < bytes as f64 {
+            return false;
+        }
+        self.tokens -= bytes as f64;
+        true
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.refilled = now;
+    }
+}
+
+// Bytes transferred over the last `THROUGHPUT_WINDOW`
+#[derive(Debug, Default)]
+struct Meter {
+    transfers: VecDeque<(Instant, usize)>,
+}
+
+impl Meter {
+    fn record(&mut self, bytes: usize, now: Instant) {
+        self.prune(now);
+        self.transfers.push_back((now, bytes));
+    }
+
+    fn rate(&mut self, now: Instant) -> u64 {
+        self.prune(now);
+        let total: usize = self.transfers.iter().map(|(_, bytes)| bytes).sum();
+        (total as f64 / THROUGHPUT_WINDOW.as_secs_f64()) as u64
+    }
+
+    fn prune(&mut self, now: Instant) {
+        while let Some((at, _)) = self.transfers.front() {
+            if now.saturating_duration_since(*at) < THROUGHPUT_WINDOW {
+                break;
+            }
+            let _ = self.transfers.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transfers_over_the_limit_wait_for_the_bucket_to_refill() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(1000, now);
+
+        // a second worth of transfers goes through right away
+        assert_eq!(bucket.reserve(600, now), Duration::ZERO);
+        assert_eq!(bucket.reserve(400, now), Duration::ZERO);
+        // then they're spread at the rate of the limit, queuing up behind each other
+        assert_eq!(bucket.reserve(500, now), Duration::from_millis(500));
+        assert_eq!(bucket.reserve(500, now), Duration::from_secs(1));
+
+        // the bucket refills over time, up to a second worth of transfers
+        let later = now + Duration::from_secs(10);
+        assert_eq!(bucket.reserve(1000, later), Duration::ZERO);
+        assert_eq!(bucket.reserve(100, later), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn control_transfers_dont_queue_up_behind_bulk_ones() {
+        let direction = Direction::new(Some(10_000));
+        let now = Instant::now();
+
+        // a bulk transfer puts the bulk lane in debt for a while
+        assert_eq!(direction.reserve(9000, Lane::Bulk, now), Duration::ZERO);
+        assert_eq!(
+            direction.reserve(9000, Lane::Bulk, now),
+            Duration::from_secs(1)
+        );
+
+        // while the control lane has its own share of the limit
+        assert_eq!(direction.reserve(1000, Lane::Control, now), Duration::ZERO);
+        assert_eq!(
+            direction.reserve(500, Lane::Control, now),
+            Duration::from_millis(500)
+        );
+
+        // no limit, no lanes
+        let unlimited = Direction::new(None);
+        assert_eq!(
+            unlimited.reserve(1_000_000, Lane::Bulk, now),
+            Duration::ZERO
+        );
+    }
+
+    #[test]
+    fn control_transfers_use_the_idle_bulk_share() {
+        let direction = Direction::new(Some(10_000));
+        let now = Instant::now();
+
+        // with no bulk transfers, control ones get the whole limit, not only their share
+        assert_eq!(direction.reserve(1000, Lane::Control, now), Duration::ZERO);
+        assert_eq!(direction.reserve(9000, Lane::Control, now), Duration::ZERO);
+        assert_eq!(
+            direction.reserve(500, Lane::Control, now),
+            Duration::from_millis(500)
+        );
+
+        // the bulk share borrowed is taken from the bulk transfers which follow
+        assert_eq!(
+            direction.reserve(9000, Lane::Bulk, now),
+            Duration::from_secs(1)
+        );
+    }
+
+    #[test]
+    fn downloads_hold_back_the_transfers_which_follow() {
+        let direction = Direction::new(Some(10_000));
+        let now = Instant::now();
+
+        // the responses of several Elders were received, putting the lane in debt
+        for _ in 0..3 {
+            let _wait = direction.record(6000, Lane::Bulk, now);
+        }
+        assert_eq!(
+            direction.reserve(0, Lane::Bulk, now),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            direction.meter_rate(now),
+            18_000 / THROUGHPUT_WINDOW.as_secs()
+        );
+
+        // until it's paid back
+        let later = now + Duration::from_secs(1);
+        assert_eq!(direction.reserve(0, Lane::Bulk, later), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn control_sends_dont_wait_for_bulk_slots() {
+        let limiter = BandwidthLimiter::new(BandwidthLimits::default());
+        let mut bulk_sends = Vec::new();
+        for _ in 0..MAX_BULK_SENDS_IN_FLIGHT {
+            bulk_sends.push(limiter.upload(1000, Lane::Bulk).await);
+        }
+
+        // with all the bulk slots taken, bulk sends wait for one to be freed
+        assert!(
+            tokio::time::timeout(Duration::from_millis(10), limiter.upload(1000, Lane::Bulk))
+                .await
+                .is_err()
+        );
+        // while control sends go ahead
+        let _send = limiter.upload(1000, Lane::Control).await;
+
+        let _ = bulk_sends.pop();
+        let _send = limiter.upload(1000, Lane::Bulk).await;
+    }
+
+    #[test]
+    fn throughput_is_averaged_over_the_window() {
+        let now = Instant::now();
+        let mut meter = Meter::default();
+
+        meter.record(5000, now);
+        meter.record(5000, now + Duration::from_secs(2));
+        assert_eq!(meter.rate(now + Duration::from_secs(2)), 2000);
+
+        // older transfers drop out of the window
+        assert_eq!(meter.rate(now + THROUGHPUT_WINDOW), 1000);
+        assert_eq!(meter.rate(now + THROUGHPUT_WINDOW * 2), 0);
+    }
+}
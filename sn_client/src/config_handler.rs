@@ -6,11 +6,13 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::{Error, Result};
+use crate::{BandwidthLimits, Error, PaddingBuckets, QueryTimeouts, Result};
 use qp2p::Config as QuicP2pConfig;
 use serde::{Deserialize, Serialize};
+use sn_interface::types::IpPreference;
 use std::{
-    net::{Ipv4Addr, SocketAddr},
+    collections::BTreeSet,
+    net::SocketAddr,
     path::{Path, PathBuf},
     time::Duration,
 };
@@ -20,8 +22,6 @@ use tokio::{
 };
 use tracing::{debug, warn};
 
-const DEFAULT_LOCAL_ADDR: (Ipv4Addr, u16) = (Ipv4Addr::UNSPECIFIED, 0);
-
 /// Default amount of time to wait for operations to succeed (query/cmd) before giving up and returning an error.
 pub const DEFAULT_OPERATION_TIMEOUT: Duration = Duration::from_secs(120);
 /// Default amount of time to wait (to keep the client alive) after sending a cmd. This allows AE messages to be parsed/resent.
@@ -30,8 +30,19 @@ pub const DEFAULT_ACK_WAIT: Duration = Duration::from_secs(10);
 
 const DEFAULT_ROOT_DIR_NAME: &str = "root_dir";
 const SN_QUERY_TIMEOUT: &str = "SN_QUERY_TIMEOUT";
+const SN_QUERY_TIMEOUTS: &str = "SN_QUERY_TIMEOUTS";
 const SN_CMD_TIMEOUT: &str = "SN_CMD_TIMEOUT";
 const SN_AE_WAIT: &str = "SN_AE_WAIT";
+const SN_NETWORK_NAME: &str = "SN_NETWORK_NAME";
+const SN_IP_PREFERENCE: &str = "SN_IP_PREFERENCE";
+const SN_UPLOAD_LIMIT: &str = "SN_UPLOAD_LIMIT";
+const SN_DOWNLOAD_LIMIT: &str = "SN_DOWNLOAD_LIMIT";
+const SN_PADDING_BUCKETS: &str = "SN_PADDING_BUCKETS";
+const SN_BOOTSTRAP_CONTACTS: &str = "SN_BOOTSTRAP_CONTACTS";
+const SN_CONTACTS_PER_BATCH: &str = "SN_CONTACTS_PER_BATCH";
+
+// Number of nodes contacted at once when starting, unless configured otherwise
+const DEFAULT_CONTACTS_PER_BATCH: usize = 3;
 
 /// Configuration for sn_client.
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -46,10 +57,71 @@ pub struct ClientConfig {
     pub qp2p: QuicP2pConfig,
     /// The amount of time to wait for responses to queries before giving up and returning an error.
     pub query_timeout: Duration,
+    /// The amount of time to wait for responses to queries, by the size class of the data they
+    /// fetch, before giving up and returning an error. If not set, they're derived from
+    /// `query_timeout`.
+    #[serde(default)]
+    pub query_timeouts: Option<QueryTimeouts>,
     /// The amount of time to wait for cmds to not error before giving up and returning an error.
     pub cmd_timeout: Duration,
     /// The amount of time to wait after a cmd is sent for AE flows to complete.
     pub cmd_ack_wait: Duration,
+    /// Name of the network, conventionally its genesis key in hex format. When set, it is carried
+    /// in all the msgs we send, and msgs sent within any other network are dropped.
+    #[serde(default)]
+    pub network_name: Option<String>,
+    /// IP version to prefer when picking the local address and contacting the network.
+    #[serde(default)]
+    pub ip_preference: IpPreference,
+    /// Limits to the rate of data sent to and received from the network, shared by all the
+    /// operations of the client.
+    #[serde(default)]
+    pub bandwidth_limits: BandwidthLimits,
+    /// Sizes private data is padded up to before being uploaded, so its size isn't leaked.
+    #[serde(default)]
+    pub padding_buckets: PaddingBuckets,
+    /// How the client makes contact with the network when starting.
+    #[serde(default)]
+    pub bootstrap: BootstrapConfig,
+}
+
+/// How a client makes contact with the network when starting, probing nodes for the knowledge
+/// of the network it needs, in batches, until one of them responds.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct BootstrapConfig {
+    /// Nodes to contact, if any. They are contacted rather than the Elders known from the
+    /// PrefixMap stored, which is then not read, and rather than the bootstrap nodes given to the
+    /// client, e.g. read from the node config file.
+    pub contacts: BTreeSet<SocketAddr>,
+    /// Maximum number of nodes contacted at once, more of them being contacted only when none
+    /// of the ones contacted already responded.
+    pub contacts_per_batch: usize,
+}
+
+impl Default for BootstrapConfig {
+    fn default() -> Self {
+        Self {
+            contacts: BTreeSet::new(),
+            contacts_per_batch: DEFAULT_CONTACTS_PER_BATCH,
+        }
+    }
+}
+
+impl BootstrapConfig {
+    /// Parses the comma-separated addresses of the nodes to contact, e.g.
+    /// `10.0.0.1:12000,10.0.0.2:12000`.
+    pub fn parse_contacts(contacts: &str) -> Result<BTreeSet<SocketAddr>, String> {
+        contacts
+            .split(',')
+            .map(str::trim)
+            .filter(|contact| !contact.is_empty())
+            .map(|contact| {
+                contact
+                    .parse()
+                    .map_err(|err| format!("invalid contact '{}': {}", contact, err))
+            })
+            .collect()
+    }
 }
 
 impl ClientConfig {
@@ -59,10 +131,26 @@ impl ClientConfig {
     /// QuicP2P config. In either case, `bootstrap_nodes` will be used to override the initial
     /// network contacts.
     ///
-    /// If `local_addr` is not specified, `127.0.0.1:0` will be used (e.g. localhost with a random
-    /// port).
+    /// If `local_addr` is not specified, `0.0.0.0:0` will be used (i.e. all interfaces with a random
+    /// port), or `[::]:0` if IPv6 is preferred through the `SN_IP_PREFERENCE` env var.
     ///
     /// If `query_timeout` is not specified, [`DEFAULT_OPERATION_TIMEOUT`] will be used.
+    /// It's the timeout of the largest queries, the smaller ones being given a fraction of it,
+    /// unless another timeout is given, through `query_timeout` or the `SN_QUERY_TIMEOUT` env
+    /// var, which is then the timeout of all queries. Either way, the timeouts of small, medium
+    /// and large queries can be given in seconds through the `SN_QUERY_TIMEOUTS` env var,
+    /// e.g. `30,60,120`.
+    ///
+    /// No bandwidth limits are set, unless given in bytes per second through the
+    /// `SN_UPLOAD_LIMIT` and `SN_DOWNLOAD_LIMIT` env vars.
+    ///
+    /// Private data is not padded, unless a comma-separated list of bucket sizes in bytes
+    /// is given through the `SN_PADDING_BUCKETS` env var.
+    ///
+    /// The nodes contacted when starting are the ones of the network known, unless a
+    /// comma-separated list of their addresses is given through the `SN_BOOTSTRAP_CONTACTS` env
+    /// var. They are contacted 3 at a time, unless another number is given through the
+    /// `SN_CONTACTS_PER_BATCH` env var.
     pub async fn new(
         root_dir: Option<&Path>,
         local_addr: Option<SocketAddr>,
@@ -104,6 +192,24 @@ impl ClientConfig {
             Err(_) => query_timeout,
         };
 
+        // if we have an env var for this, let's override
+        let query_timeouts = match std::env::var(SN_QUERY_TIMEOUTS) {
+            Ok(timeouts) => match timeouts.parse::<QueryTimeouts>() {
+                Ok(timeouts) => {
+                    warn!(
+                        "Query timeouts set from env var {}: {:?}",
+                        SN_QUERY_TIMEOUTS, timeouts
+                    );
+                    Some(timeouts)
+                }
+                Err(error) => {
+                    warn!("There was an error parsing {} env var value: '{}'. Query timeouts will be derived from the query timeout: {:?}", SN_QUERY_TIMEOUTS, timeouts, error);
+                    None
+                }
+            },
+            Err(_) => None,
+        };
+
         // if we have an env var for this, let's override
         let cmd_timeout = match std::env::var(SN_CMD_TIMEOUT) {
             Ok(timeout) => match timeout.parse() {
@@ -140,18 +246,134 @@ impl ClientConfig {
             Err(_) => cmd_ack_wait,
         };
 
+        // if we have an env var for this, we only exchange msgs within that network
+        let network_name = std::env::var(SN_NETWORK_NAME).ok();
+        if let Some(name) = &network_name {
+            warn!(
+                "Network name set from env var {}: {}",
+                SN_NETWORK_NAME, name
+            );
+        }
+
+        // if we have an env var for this, let's override
+        let ip_preference = match std::env::var(SN_IP_PREFERENCE) {
+            Ok(preference) => match preference.parse() {
+                Ok(preference) => {
+                    warn!(
+                        "IP preference set from env var {}: {}",
+                        SN_IP_PREFERENCE, preference
+                    );
+                    preference
+                }
+                Err(error) => {
+                    warn!("There was an error parsing {} env var value: '{}'. Default IP preference will be used: {:?}", SN_IP_PREFERENCE, preference, error);
+                    IpPreference::default()
+                }
+            },
+            Err(_) => IpPreference::default(),
+        };
+
+        let bandwidth_limits = BandwidthLimits {
+            upload: bandwidth_limit_from_env(SN_UPLOAD_LIMIT),
+            download: bandwidth_limit_from_env(SN_DOWNLOAD_LIMIT),
+        };
+
+        // if we have an env var for this, let's override
+        let padding_buckets = match std::env::var(SN_PADDING_BUCKETS) {
+            Ok(buckets) => match buckets.parse::<PaddingBuckets>() {
+                Ok(buckets) => {
+                    warn!(
+                        "Padding buckets set from env var {}: {:?}",
+                        SN_PADDING_BUCKETS,
+                        buckets.sizes()
+                    );
+                    buckets
+                }
+                Err(error) => {
+                    warn!("There was an error parsing {} env var value: '{}'. Private data won't be padded: {:?}", SN_PADDING_BUCKETS, buckets, error);
+                    PaddingBuckets::default()
+                }
+            },
+            Err(_) => PaddingBuckets::default(),
+        };
+
+        let bootstrap = bootstrap_from_env();
+
         info!(
-            "Client set to use a query timeout of {:?}, and AE await post-put for {:?}",
-            query_timeout, cmd_ack_wait
+            "Client set to use query timeouts of {:?}, and AE await post-put for {:?}",
+            query_timeouts.unwrap_or_else(|| QueryTimeouts::from_query_timeout(query_timeout)),
+            cmd_ack_wait
         );
         Self {
-            local_addr: local_addr.unwrap_or_else(|| SocketAddr::from(DEFAULT_LOCAL_ADDR)),
+            local_addr: local_addr.unwrap_or_else(|| ip_preference.unspecified_addr()),
             root_dir: root_dir.clone(),
             genesis_key,
             qp2p,
             query_timeout,
+            query_timeouts,
             cmd_timeout,
             cmd_ack_wait,
+            network_name,
+            ip_preference,
+            bandwidth_limits,
+            padding_buckets,
+            bootstrap,
+        }
+    }
+}
+
+// Bootstrap config, with the contacts and the number of them contacted at once set from the env
+// vars, if any
+fn bootstrap_from_env() -> BootstrapConfig {
+    let mut bootstrap = BootstrapConfig::default();
+
+    if let Ok(contacts) = std::env::var(SN_BOOTSTRAP_CONTACTS) {
+        match BootstrapConfig::parse_contacts(&contacts) {
+            Ok(contacts) => {
+                warn!(
+                    "Bootstrap contacts set from env var {}: {:?}",
+                    SN_BOOTSTRAP_CONTACTS, contacts
+                );
+                bootstrap.contacts = contacts;
+            }
+            Err(error) => {
+                warn!("There was an error parsing {} env var value: '{}'. The nodes of the network known will be contacted: {}", SN_BOOTSTRAP_CONTACTS, contacts, error);
+            }
+        }
+    }
+
+    if let Ok(count) = std::env::var(SN_CONTACTS_PER_BATCH) {
+        match count.parse() {
+            Ok(count) if count > 0 => {
+                warn!(
+                    "Number of nodes contacted at once set from env var {}: {}",
+                    SN_CONTACTS_PER_BATCH, count
+                );
+                bootstrap.contacts_per_batch = count;
+            }
+            _ => {
+                warn!("There was an error parsing {} env var value: '{}', expected a number greater than 0. {} nodes will be contacted at once", SN_CONTACTS_PER_BATCH, count, DEFAULT_CONTACTS_PER_BATCH);
+            }
+        }
+    }
+
+    bootstrap
+}
+
+// Bandwidth limit set from the env var, if any
+fn bandwidth_limit_from_env(var: &str) -> Option<u64> {
+    let limit = std::env::var(var).ok()?;
+    match limit.parse() {
+        Ok(limit) => {
+            warn!("Bandwidth limit set from env var {}: {}B/s", var, limit);
+            Some(limit)
+        }
+        Err(error) => {
+            warn!(
+                "There was an error parsing {} env var value: '{}'. No bandwidth limit will be applied: {:?}",
+                var, limit, error
+            );
+            None
         }
     }
 }
@@ -195,7 +417,7 @@ fn project_dirs() -> Result<PathBuf> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::utils::test_utils::init_logger;
+    use crate::{utils::test_utils::init_logger, QuerySizeClass};
     use bincode::serialize;
     use eyre::Result;
     use rand::{distributions::Alphanumeric, thread_rng, Rng};
@@ -269,16 +491,37 @@ mod tests {
             })
             .unwrap_or(DEFAULT_ACK_WAIT);
 
+        let expected_ip_preference: IpPreference = std::env::var(SN_IP_PREFERENCE)
+            .map(|v| v.parse().unwrap_or_default())
+            .unwrap_or_default();
+
         let expected_config = ClientConfig {
-            local_addr: (Ipv4Addr::UNSPECIFIED, 0).into(),
+            local_addr: expected_ip_preference.unspecified_addr(),
             root_dir: root_dir.clone(),
             genesis_key,
             qp2p: QuicP2pConfig {
                 ..Default::default()
             },
             query_timeout: expected_query_timeout,
+            query_timeouts: std::env::var(SN_QUERY_TIMEOUTS)
+                .ok()
+                .and_then(|v| v.parse().ok()),
             cmd_timeout: expected_cmd_timeout,
             cmd_ack_wait: expected_cmd_ack_wait,
+            network_name: std::env::var(SN_NETWORK_NAME).ok(),
+            ip_preference: expected_ip_preference,
+            bandwidth_limits: BandwidthLimits {
+                upload: std::env::var(SN_UPLOAD_LIMIT)
+                    .ok()
+                    .and_then(|v| v.parse().ok()),
+                download: std::env::var(SN_DOWNLOAD_LIMIT)
+                    .ok()
+                    .and_then(|v| v.parse().ok()),
+            },
+            padding_buckets: std::env::var(SN_PADDING_BUCKETS)
+                .map(|v| v.parse().unwrap_or_default())
+                .unwrap_or_default(),
+            bootstrap: bootstrap_from_env(),
         };
         assert_eq!(format!("{:?}", config), format!("{:?}", expected_config));
         assert_eq!(serialize(&config)?, serialize(&expected_config)?);
@@ -304,4 +547,54 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn saved_query_timeout_is_kept_without_query_timeouts() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let genesis_key = bls::SecretKey::random().public_key();
+        let query_timeout = DEFAULT_OPERATION_TIMEOUT * 3;
+        let config = ClientConfig::new(
+            Some(temp_dir.path()),
+            None,
+            genesis_key,
+            None,
+            Some(query_timeout),
+            None,
+            None,
+        )
+        .await;
+
+        // as saved before the query timeouts were configurable by size class
+        let mut saved = serde_json::to_value(&config)?;
+        let _ = saved
+            .as_object_mut()
+            .ok_or_else(|| eyre::eyre!("config isn't saved as an object"))?
+            .remove("query_timeouts");
+        let loaded: ClientConfig = serde_json::from_value(saved)?;
+
+        assert_eq!(loaded.query_timeout, query_timeout);
+        assert!(loaded.query_timeouts.is_none());
+        assert_eq!(
+            QueryTimeouts::from_query_timeout(loaded.query_timeout).get(QuerySizeClass::Small),
+            query_timeout
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn bootstrap_contacts_are_parsed_from_a_list() -> Result<()> {
+        let contacts = BootstrapConfig::parse_contacts("10.0.0.1:12000, [::1]:12001,")
+            .map_err(|err: String| eyre::eyre!(err))?;
+        assert_eq!(
+            contacts.into_iter().collect::<Vec<_>>(),
+            vec!["10.0.0.1:12000".parse()?, "[::1]:12001".parse()?]
+        );
+        assert!(BootstrapConfig::parse_contacts("10.0.0.1").is_err());
+        assert!(BootstrapConfig::parse_contacts("")
+            .map_err(|err: String| eyre::eyre!(err))?
+            .is_empty());
+
+        Ok(())
+    }
 }
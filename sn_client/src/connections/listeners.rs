@@ -11,7 +11,7 @@ use super::Session;
 use crate::{
     connections::{
         messaging::{send_msg, NUM_OF_ELDERS_SUBSET_FOR_QUERIES},
-        PendingCmdAcks,
+        CmdAcked, PendingCmdAcks,
     },
     Error, Result,
 };
@@ -32,7 +32,7 @@ use qp2p::{Close, ConnectionError, ConnectionIncoming as IncomingMsgs, SendError
 use rand::rngs::OsRng;
 use rand::seq::SliceRandom;
 use secured_linked_list::SecuredLinkedList;
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::atomic::Ordering};
 use tracing::Instrument;
 
 impl Session {
@@ -52,11 +52,13 @@ impl Session {
 
         let _handle = tokio::spawn(async move {
             loop {
-                match Self::listen_for_incoming_msg(addr, &mut incoming_msgs).await {
+                match Self::listen_for_incoming_msg(&session, addr, &mut incoming_msgs).await {
                     Ok(Some(msg)) => {
                         if first {
                             first = false;
-                            session.peer_links.add_incoming(&peer, conn.clone()).await;
+                            if let Some(peer_links) = session.peer_links() {
+                                peer_links.add_incoming(&peer, conn.clone()).await;
+                            }
                         }
 
                         if let Err(err) = Self::handle_msg(msg, peer, session.clone()).await {
@@ -97,16 +99,60 @@ impl Session {
 
     #[instrument(skip_all, level = "debug")]
     pub(crate) async fn listen_for_incoming_msg(
+        session: &Session,
         src: SocketAddr,
         incoming_msgs: &mut IncomingMsgs,
     ) -> Result<Option<MsgType>, Error> {
-        if let Some(msg) = incoming_msgs.next().await? {
+        while let Some(msg) = incoming_msgs.next().await? {
             trace!("Incoming msg from {:?}", src);
-            let msg_type = WireMsg::deserialize(msg)?;
-            Ok(Some(msg_type))
-        } else {
-            Ok(None)
+            if let Some(msg) = Self::accept_msg(session, src, msg)? {
+                return Ok(Some(msg));
+            }
+        }
+
+        Ok(None)
+    }
+
+    // Handles a msg received from a node of the mock network in its own task, as the msgs
+    // received over a connection are.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub(crate) fn spawn_msg_handler(session: Session, src_peer: Peer, msg: Bytes) {
+        let _handle = tokio::spawn(async move {
+            let result = match Self::accept_msg(&session, src_peer.addr(), msg) {
+                Ok(Some(msg)) => Self::handle_msg(msg, src_peer, session).await,
+                Ok(None) => Ok(()),
+                Err(error) => Err(error),
+            };
+            if let Err(error) = result {
+                error!("Error while handling incoming msg: {:?}", error);
+            }
+        });
+    }
+
+    // Deserialises the msg received, unless it was sent within another network
+    fn accept_msg(session: &Session, src: SocketAddr, msg: Bytes) -> Result<Option<MsgType>> {
+        let wire_msg = WireMsg::from(msg)?;
+        if !wire_msg.is_from_network(session.network_name) {
+            let _ = session.other_network_msgs.fetch_add(1, Ordering::Relaxed);
+            debug!(
+                "{:?} {:?} from {:?}, sent within network {:?}",
+                LogMarker::OtherNetworkMsgDropped,
+                wire_msg.msg_id(),
+                src,
+                wire_msg.network_name()
+            );
+            return Ok(None);
+        }
+        if let Some(trace_id) = wire_msg.trace_id() {
+            debug!(
+                "Msg {:?} from {:?} is part of trace {}",
+                wire_msg.msg_id(),
+                src,
+                trace_id
+            );
         }
+
+        Ok(Some(wire_msg.into_msg()?))
     }
 
     #[instrument(skip_all, level = "debug")]
@@ -216,7 +262,7 @@ impl Session {
         cmds: PendingCmdAcks,
         correlation_id: MsgId,
         src: SocketAddr,
-        error: Option<CmdError>,
+        response: Result<CmdAcked, CmdError>,
     ) {
         if let Some(sender) = cmds.get(&correlation_id) {
             trace!(
@@ -224,7 +270,7 @@ impl Session {
                 src,
                 correlation_id
             );
-            let result = sender.try_send((src, error));
+            let result = sender.try_send((src, response));
             if result.is_err() {
                 trace!("Error sending cmd response on a channel for cmd_id {:?}: {:?}. (It has likely been removed)", correlation_id, result)
             }
@@ -287,16 +333,38 @@ impl Session {
                     ..
                 } => {
                     warn!("CmdError was received for {correlation_id:?}: {:?}", error);
-                    Self::send_cmd_response(cmds, correlation_id, src_peer.addr(), Some(error));
+                    Self::send_cmd_response(cmds, correlation_id, src_peer.addr(), Err(error));
                 }
-                ServiceMsg::CmdAck { correlation_id } => {
+                ServiceMsg::CmdAck {
+                    correlation_id,
+                    receipt_share,
+                } => {
                     debug!(
                         "CmdAck was received for Message{:?} w/ID: {:?} from {:?}",
                         msg_id,
                         correlation_id,
                         src_peer.addr()
                     );
-                    Self::send_cmd_response(cmds, correlation_id, src_peer.addr(), None);
+                    Self::send_cmd_response(
+                        cmds,
+                        correlation_id,
+                        src_peer.addr(),
+                        Ok(CmdAcked::Processed(receipt_share)),
+                    );
+                }
+                ServiceMsg::CmdReplicated { correlation_id } => {
+                    debug!(
+                        "CmdReplicated was received for Message{:?} w/ID: {:?} from {:?}",
+                        msg_id,
+                        correlation_id,
+                        src_peer.addr()
+                    );
+                    Self::send_cmd_response(
+                        cmds,
+                        correlation_id,
+                        src_peer.addr(),
+                        Ok(CmdAcked::Replicated),
+                    );
                 }
                 _ => {
                     warn!("Ignoring unexpected msg type received: {:?}", msg);
@@ -333,9 +401,34 @@ impl Session {
         )
         .await;
 
+        // the resent msg is still part of the same client operation
+        let trace_id = WireMsg::from(bounced_msg.clone())
+            .ok()
+            .and_then(|wire_msg| wire_msg.trace_id());
+
         if let Some((msg_id, elders, service_msg, dst_location, auth)) =
             Self::new_target_elders(bounced_msg.clone(), &target_sap).await?
         {
+            // the msg was bounced, so whichever section we had cached for its name is stale
+            let dst_name = dst_location.name();
+            if session.routing_cache.invalidate(&dst_name) {
+                debug!(
+                    "Msg {:?} was sent to a stale section cached for {:?}",
+                    msg_id, dst_name
+                );
+            }
+            // only sections of SAPs we could verify, and so are in our network knowledge,
+            // are cached
+            let prefix = target_sap.prefix();
+            if prefix.matches(&dst_name)
+                && session
+                    .network
+                    .get_signed(&prefix)
+                    .map_or(false, |section| section.value == target_sap)
+            {
+                session.routing_cache.insert(prefix);
+            }
+
             let ae_msg_src_name = src_peer.name();
             // here we send this to only one elder for each AE message we get in. We _should_ have one per elder we sent to.
             // deterministically send to most elder based upon sender
@@ -349,12 +442,13 @@ impl Session {
             // there should always be one
             if let Some(elder) = target_elder {
                 let payload = WireMsg::serialize_msg_payload(&service_msg)?;
-                let wire_msg = WireMsg::new_msg(
+                let mut wire_msg = WireMsg::new_msg(
                     msg_id,
                     payload,
                     AuthKind::Service(auth.into_inner()),
                     dst_location,
                 )?;
+                wire_msg.set_trace_id(trace_id);
 
                 debug!("Resending original message on AE-Redirect with updated details. Expecting an AE-Retry next");
 
@@ -460,7 +554,7 @@ impl Session {
         );
 
         let (target_count, dst_address_of_bounced_msg) = match service_msg.clone() {
-            ServiceMsg::Cmd(cmd) => (at_least_one_correct_elder(), cmd.dst_name()),
+            ServiceMsg::Cmd { cmd, .. } => (at_least_one_correct_elder(), cmd.dst_name()),
             ServiceMsg::Query(query) => (NUM_OF_ELDERS_SUBSET_FOR_QUERIES, query.dst_name()),
             _ => {
                 warn!(
@@ -6,15 +6,25 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{QueryResult, Session};
+use super::{routing_cache::RoutingCache, QueryResult, RoutingCacheStats, Session, Transport};
 
-use crate::{connections::CmdResponse, Error, Result};
+use crate::{
+    bandwidth::{BandwidthLimiter, Lane},
+    connections::{CmdAcked, CmdResponse},
+    Error, Result,
+};
 use sn_interface::at_least_one_correct_elder_for_sap;
 use sn_interface::messaging::{
-    data::{CmdError, DataQuery, QueryResponse},
-    AuthKind, DstLocation, MsgId, ServiceAuth, WireMsg,
+    data::{
+        CmdAckLevel, CmdError, DataQuery, OperationId, QueryResponse, StorageReceipt,
+        StorageReceiptShare,
+    },
+    system::SectionAuth,
+    AuthKind, DstLocation, MsgId, NetworkName, ServiceAuth, TraceId, WireMsg,
+};
+use sn_interface::network_knowledge::{
+    prefix_map::NetworkPrefixMap, NetworkSnapshot, SectionAuthorityProvider,
 };
-use sn_interface::network_knowledge::prefix_map::NetworkPrefixMap;
 use sn_interface::types::{Peer, PeerLinks, PublicKey, SendToOneError};
 
 use backoff::{backoff::Backoff, ExponentialBackoff};
@@ -24,91 +34,147 @@ use futures::future::join_all;
 use qp2p::{Close, Config as QuicP2pConfig, ConnectionError, Endpoint, SendError};
 use rand::{rngs::OsRng, seq::SliceRandom};
 use secured_linked_list::SecuredLinkedList;
-use std::{net::SocketAddr, path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use tokio::{
     sync::mpsc::{channel, Sender},
     sync::RwLock,
     task::JoinHandle,
 };
 use tracing::{debug, error, trace, warn};
-use xor_name::XorName;
+use xor_name::{Prefix, XorName};
 
 // Number of Elders subset to send queries to
 pub(crate) const NUM_OF_ELDERS_SUBSET_FOR_QUERIES: usize = 3;
 
-// Number of bootstrap nodes to attempt to contact per batch (if provided by the node_config)
-pub(crate) const NODES_TO_CONTACT_PER_STARTUP_BATCH: usize = 3;
-
 // Duration of wait for the node to have chance to pickup network knowledge at the beginning
 const INITIAL_WAIT: u64 = 1;
 
 // Number of retries for sending a message due to a connection issue.
 const CLIENT_SEND_RETRIES: usize = 1;
 
+// How long the responses of the other Elders are waited for once a query was answered, to
+// account for them in the download limits.
+const LATE_QUERY_RESPONSES_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl Transport {
+    /// Binds an endpoint at the given address, msgs being sent from it over connections.
+    pub(crate) fn network(local_addr: SocketAddr, qp2p_config: QuicP2pConfig) -> Result<Self> {
+        let endpoint = Endpoint::new_client(local_addr, qp2p_config)?;
+        let peer_links = PeerLinks::new(endpoint.clone());
+        Ok(Self::Network {
+            endpoint,
+            peer_links,
+        })
+    }
+}
+
 impl Session {
     /// Acquire a session by bootstrapping to a section, maintaining connections to several nodes.
     #[instrument(skip(err_sender), level = "debug")]
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         client_pk: PublicKey,
         genesis_key: bls::PublicKey,
-        qp2p_config: QuicP2pConfig,
+        transport: Transport,
         err_sender: Sender<CmdError>,
-        local_addr: SocketAddr,
         cmd_ack_wait: Duration,
         prefix_map: NetworkPrefixMap,
+        network_name: Option<NetworkName>,
+        contacts_per_batch: usize,
     ) -> Result<Session> {
-        let endpoint = Endpoint::new_client(local_addr, qp2p_config)?;
-        let peer_links = PeerLinks::new(endpoint.clone());
-
         let session = Session {
             pending_queries: Arc::new(DashMap::default()),
             incoming_err_sender: Arc::new(err_sender),
             pending_cmds: Arc::new(DashMap::default()),
-            endpoint,
+            transport,
             network: Arc::new(prefix_map),
+            routing_cache: Arc::new(RoutingCache::default()),
             genesis_key,
             initial_connection_check_msg_id: Arc::new(RwLock::new(None)),
             cmd_ack_wait,
-            peer_links,
             all_sections_chains: Arc::new(RwLock::new(SecuredLinkedList::new(genesis_key))),
+            network_name,
+            other_network_msgs: Arc::new(AtomicU64::new(0)),
+            contacts_per_batch: contacts_per_batch.max(1),
         };
 
         Ok(session)
     }
 
-    #[instrument(skip(self, auth, payload), level = "debug", name = "session send cmd")]
+    // Links to the nodes, unless msgs are delivered to a mock network
+    pub(super) fn peer_links(&self) -> Option<&PeerLinks> {
+        match &self.transport {
+            Transport::Network { peer_links, .. } => Some(peer_links),
+            #[cfg(any(test, feature = "test-utils"))]
+            Transport::Mock(_) => None,
+        }
+    }
+
+    // Address of our endpoint, unless msgs are delivered to a mock network
+    fn public_addr(&self) -> Option<SocketAddr> {
+        match &self.transport {
+            Transport::Network { endpoint, .. } => Some(endpoint.public_addr()),
+            #[cfg(any(test, feature = "test-utils"))]
+            Transport::Mock(_) => None,
+        }
+    }
+
+    #[instrument(
+        skip(self, auth, payload, bandwidth),
+        level = "debug",
+        name = "session send cmd"
+    )]
     pub(crate) async fn send_cmd(
         &self,
         dst_address: XorName,
         auth: ServiceAuth,
         payload: Bytes,
-    ) -> Result<()> {
-        let endpoint = self.endpoint.clone();
+        ack_level: CmdAckLevel,
+        bandwidth: &BandwidthLimiter,
+        lane: Lane,
+    ) -> Result<Option<StorageReceipt>> {
         // TODO: Consider other approach: Keep a session per section!
 
         let (section_pk, elders) = self.get_cmd_elders(dst_address).await?;
 
         let msg_id = MsgId::new();
+        let trace_id = TraceId::random();
 
         debug!(
-            "Sending cmd w/id {:?}, from {}, to {} Elders w/ dst: {:?}",
+            "Sending cmd w/id {:?}, trace {}, from {:?}, to {} Elders w/ dst: {:?}",
             msg_id,
-            endpoint.public_addr(),
+            trace_id,
+            self.public_addr(),
             elders.len(),
             dst_address
         );
 
-        let dst_location = DstLocation::Section {
-            name: dst_address,
-            section_pk,
-        };
+        let dst_location = DstLocation::section(dst_address, section_pk);
 
         let msg_kind = AuthKind::Service(auth);
-        let wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst_location)?;
+        let payload_len = payload.len();
+        let mut wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst_location)?;
+        wire_msg.set_trace_id(Some(trace_id));
 
         let elders_len = elders.len();
+        let _slot = bandwidth.upload(payload_len * elders_len, lane).await;
+        if ack_level == CmdAckLevel::None {
+            send_msg(self.clone(), elders, wire_msg, msg_id).await?;
+            trace!("Cmd {:?} sent, not waiting for any ack", msg_id);
+            return Ok(None);
+        }
+
         // The insertion of channel will be executed AFTER the completion of the `send_message`.
-        let (sender, mut receiver) = channel::<CmdResponse>(elders_len);
+        // Elders acknowledge cmds they accepted and, when asked, their data got replicated.
+        let (sender, mut receiver) = channel::<CmdResponse>(elders_len * 2);
         let _ = self.pending_cmds.insert(msg_id, sender);
         trace!("Inserted channel for cmd {:?}", msg_id);
 
@@ -121,27 +187,56 @@ impl Session {
         // The period is expected to have AE completed, hence no extra wait is required.
         let mut received_ack = 0;
         let mut received_err = 0;
+        let mut receipt_shares = vec![];
+        let mut receipt = None;
         let mut attempts = 0;
         let interval = Duration::from_millis(1000);
         let expected_cmd_ack_wait_attempts =
             std::cmp::max(10, self.cmd_ack_wait.as_millis() / interval.as_millis());
         loop {
             match receiver.try_recv() {
-                Ok((src, None)) => {
-                    received_ack += 1;
-                    trace!(
-                        "received CmdAck of {:?} from {:?}, so far {} / {}",
-                        msg_id,
-                        src,
-                        received_ack,
-                        expected_acks
-                    );
-                    if received_ack >= expected_acks {
+                Ok((src, Ok(acked))) => {
+                    match acked {
+                        CmdAcked::Processed(receipt_share) => {
+                            if matches!(ack_level, CmdAckLevel::Processed | CmdAckLevel::FastAck) {
+                                received_ack += 1;
+                            }
+                            trace!(
+                                "received CmdAck of {:?} from {:?}, so far {} / {}",
+                                msg_id,
+                                src,
+                                received_ack,
+                                expected_acks
+                            );
+                            if let Some(receipt_share) = receipt_share {
+                                receipt_shares.push(receipt_share);
+                                if receipt.is_none() {
+                                    receipt = StorageReceiptShare::aggregate(&receipt_shares);
+                                }
+                            }
+                        }
+                        CmdAcked::Replicated => {
+                            if ack_level == CmdAckLevel::Replicated {
+                                received_ack += 1;
+                            }
+                            trace!(
+                                "received CmdReplicated of {:?} from {:?}, so far {} / {}",
+                                msg_id,
+                                src,
+                                received_ack,
+                                expected_acks
+                            );
+                        }
+                    }
+                    // when receipt shares are coming in, we keep waiting until we can aggregate them
+                    if received_ack >= expected_acks
+                        && (receipt_shares.is_empty() || receipt.is_some())
+                    {
                         let _ = self.pending_cmds.remove(&msg_id);
                         break;
                     }
                 }
-                Ok((src, Some(error))) => {
+                Ok((src, Err(error))) => {
                     received_err += 1;
                     error!(
                         "received error response {:?} of cmd {:?} from {:?}, so far {} acks vs. {} errors",
@@ -163,6 +258,15 @@ impl Session {
                     "Terminated with insufficient CmdAcks for {:?}, {} / {} acks received",
                     msg_id, received_ack, expected_acks
                 );
+                let _ = self.pending_cmds.remove(&msg_id);
+                // the data not being replicated is not to go unnoticed when it was asked for
+                if ack_level == CmdAckLevel::Replicated && received_ack < expected_acks {
+                    return Err(Error::CmdNotReplicated {
+                        msg_id,
+                        confirmations: received_ack,
+                        required: expected_acks,
+                    });
+                }
                 break;
             }
             trace!(
@@ -173,8 +277,16 @@ impl Session {
             tokio::time::sleep(interval).await;
         }
 
+        if receipt.is_none() && !receipt_shares.is_empty() {
+            warn!(
+                "Not enough storage receipt shares received for {:?} to aggregate them: {}",
+                msg_id,
+                receipt_shares.len()
+            );
+        }
+
         trace!("Wait for any cmd response/reaction (AE msgs eg), is over)");
-        Ok(())
+        Ok(receipt)
     }
 
     #[instrument(skip_all, level = "debug")]
@@ -184,9 +296,9 @@ impl Session {
         query: DataQuery,
         auth: ServiceAuth,
         payload: Bytes,
+        bandwidth: Arc<BandwidthLimiter>,
+        lane: Lane,
     ) -> Result<QueryResult> {
-        let endpoint = self.endpoint.clone();
-
         let chunk_addr = if let DataQuery::GetChunk(address) = query {
             Some(address)
         } else {
@@ -198,12 +310,14 @@ impl Session {
         let (section_pk, elders) = self.get_query_elders(dst).await?;
         let elders_len = elders.len();
         let msg_id = MsgId::new();
+        let trace_id = TraceId::random();
 
         debug!(
-            "Sending query message {:?}, msg_id: {:?}, from {}, to the {} Elders closest to data name: {:?}",
+            "Sending query message {:?}, msg_id: {:?}, trace {}, from {}, to the {} Elders closest to data name: {:?}",
             query,
             msg_id,
-            endpoint.public_addr(),
+            trace_id,
+            self.public_addr(),
             elders_len,
             elders
         );
@@ -225,14 +339,25 @@ impl Session {
             warn!("No op_id found for query");
         }
 
-        let dst_location = DstLocation::Section {
-            name: dst,
-            section_pk,
-        };
+        let dst_location = DstLocation::section(dst, section_pk);
         let msg_kind = AuthKind::Service(auth);
-        let wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst_location)?;
-
-        send_msg_in_bg(self.clone(), elders, wire_msg, msg_id)?;
+        let payload_len = payload.len();
+        let mut wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst_location)?;
+        wire_msg.set_trace_id(Some(trace_id));
+
+        // the responses received so far are to be within the download limit before more
+        // are asked for
+        bandwidth.wait_for_download(lane).await;
+        let _slot = bandwidth.upload(payload_len * elders_len, lane).await;
+
+        // The msg is sent before awaiting any response, so a lost connection is
+        // reported right away instead of once the query times out
+        if let Err(error) = send_msg(self.clone(), elders, wire_msg, msg_id).await {
+            if let Ok(op_id) = query.operation_id() {
+                self.remove_query_listener(&op_id, msg_id);
+            }
+            return Err(error);
+        }
 
         // TODO:
         // We are now simply accepting the very first valid response we receive,
@@ -245,10 +370,16 @@ impl Session {
         // from byzantine nodes, however for mutable data (non-Chunk responses) we will
         // have to review the approach.
         let mut discarded_responses: usize = 0;
+        let mut received_responses: usize = 0;
 
         let response = loop {
             let mut error_response = None;
-            match (receiver.recv().await, chunk_addr) {
+            let received = receiver.recv().await;
+            if let Some(response) = &received {
+                received_responses += 1;
+                bandwidth.download(response_size(response), lane);
+            }
+            match (received, chunk_addr) {
                 (Some(QueryResponse::GetChunk(Ok(chunk))), Some(chunk_addr)) => {
                     // We are dealing with Chunk query responses, thus we validate its hash
                     // matches its xorname, if so, we don't need to await for more responses
@@ -298,19 +429,27 @@ impl Session {
 
         if let Some(query) = &response {
             if let Ok(query_op_id) = query.operation_id() {
-                // Remove the response sender
-                trace!("Removing channel for {:?}", (msg_id, &query_op_id));
-                // let _old_channel =
-                if let Some(mut entry) = self.pending_queries.get_mut(&query_op_id) {
-                    let listeners_for_op = entry.value_mut();
-                    if let Some(index) = listeners_for_op
-                        .iter()
-                        .position(|(id, _sender)| *id == msg_id)
-                    {
-                        let _old_listener = listeners_for_op.swap_remove(index);
-                    }
+                let late_responses = elders_len.saturating_sub(received_responses);
+                if late_responses == 0 {
+                    self.remove_query_listener(&query_op_id, msg_id);
                 } else {
-                    warn!("No listeners found for our op_id: {:?}", query_op_id)
+                    // the other Elders' responses are received all the same
+                    let session = self.clone();
+                    let bandwidth = bandwidth.clone();
+                    let _handle = tokio::spawn(async move {
+                        let _ = tokio::time::timeout(LATE_QUERY_RESPONSES_TIMEOUT, async {
+                            for _ in 0..late_responses {
+                                match receiver.recv().await {
+                                    Some(response) => {
+                                        bandwidth.download(response_size(&response), lane)
+                                    }
+                                    None => break,
+                                }
+                            }
+                        })
+                        .await;
+                        session.remove_query_listener(&query_op_id, msg_id);
+                    });
                 }
             }
         }
@@ -329,6 +468,55 @@ impl Session {
         }
     }
 
+    // Remove the response sender of the query sent with the given msg id
+    fn remove_query_listener(&self, op_id: &OperationId, msg_id: MsgId) {
+        trace!("Removing channel for {:?}", (msg_id, op_id));
+        if let Some(mut entry) = self.pending_queries.get_mut(op_id) {
+            let listeners_for_op = entry.value_mut();
+            if let Some(index) = listeners_for_op
+                .iter()
+                .position(|(id, _sender)| *id == msg_id)
+            {
+                let _old_listener = listeners_for_op.swap_remove(index);
+            }
+        } else {
+            warn!("No listeners found for our op_id: {:?}", op_id)
+        }
+    }
+
+    /// Drop our connections to the Elders of the section closest to the given name,
+    /// and connect to them again by sending them the given probe msg.
+    /// The knowledge of the network and the listeners of pending queries are kept,
+    /// so queries can be sent again over the new connections.
+    #[instrument(skip(self, auth, payload), level = "debug")]
+    pub(crate) async fn reconnect_to_elders(
+        &self,
+        dst_address: XorName,
+        auth: ServiceAuth,
+        payload: Bytes,
+    ) -> Result<()> {
+        let (section_pk, elders) = self.get_query_elders(dst_address).await?;
+        if let Some(peer_links) = self.peer_links() {
+            for elder in &elders {
+                peer_links.disconnect(*elder).await;
+            }
+        }
+
+        let msg_id = MsgId::new();
+        debug!(
+            "Reconnecting to {} Elders closest to {:?}, with probe msg {:?}",
+            elders.len(),
+            dst_address,
+            msg_id
+        );
+
+        let dst_location = DstLocation::section(dst_address, section_pk);
+        let msg_kind = AuthKind::Service(auth);
+        let wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst_location)?;
+
+        send_msg(self.clone(), elders, wire_msg, msg_id).await
+    }
+
     #[instrument(skip_all, level = "debug")]
     pub(crate) async fn make_contact_with_nodes(
         &self,
@@ -337,7 +525,6 @@ impl Session {
         auth: ServiceAuth,
         payload: Bytes,
     ) -> Result<(), Error> {
-        let endpoint = self.endpoint.clone();
         // Get DataSection elders details.
         // TODO: we should be able to handle using an pre-existing prefixmap. This is here for when that's in place.
         let (elders_or_adults, section_pk) =
@@ -356,17 +543,14 @@ impl Session {
 
         debug!(
             "Making initial contact with nodes. Our PublicAddr: {:?}. Using {:?} to {} nodes: {:?}",
-            endpoint.public_addr(),
+            self.public_addr(),
             msg_id,
             elders_or_adults.len(),
             elders_or_adults
         );
 
         // TODO: Don't use genesis key if we have a full section
-        let dst_location = DstLocation::Section {
-            name: dst_address,
-            section_pk,
-        };
+        let dst_location = DstLocation::section(dst_address, section_pk);
         let msg_kind = AuthKind::Service(auth);
         let wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst_location)?;
 
@@ -380,7 +564,7 @@ impl Session {
         let initial_contacts = elders_or_adults
             .clone()
             .into_iter()
-            .take(NODES_TO_CONTACT_PER_STARTUP_BATCH)
+            .take(self.contacts_per_batch)
             .collect();
 
         send_msg_in_bg(self.clone(), initial_contacts, wire_msg.clone(), msg_id)?;
@@ -422,7 +606,7 @@ impl Session {
             // only after a couple of waits do we try contacting more nodes...
             // This just gives the initial contacts more time.
             if knowledge_checks > 2 {
-                let mut start_pos = outgoing_msg_rounds * NODES_TO_CONTACT_PER_STARTUP_BATCH;
+                let mut start_pos = outgoing_msg_rounds * self.contacts_per_batch;
                 outgoing_msg_rounds += 1;
 
                 // if we'd run over known contacts, then we just go to the end
@@ -432,7 +616,7 @@ impl Session {
 
                 last_start_pos = start_pos;
 
-                let next_batch_end = start_pos + NODES_TO_CONTACT_PER_STARTUP_BATCH;
+                let next_batch_end = start_pos + self.contacts_per_batch;
 
                 // if we'd run over known contacts, then we just go to the end
                 let next_contacts = if next_batch_end > elders_or_adults.len() {
@@ -443,8 +627,7 @@ impl Session {
 
                     next
                 } else {
-                    elders_or_adults[start_pos..start_pos + NODES_TO_CONTACT_PER_STARTUP_BATCH]
-                        .to_vec()
+                    elders_or_adults[start_pos..next_batch_end].to_vec()
                 };
 
                 trace!("Sending out another batch of initial contact msgs to new nodes");
@@ -473,9 +656,68 @@ impl Session {
         Ok(())
     }
 
+    /// Prefix of the section we know to be closest to the given name, if any.
+    pub(crate) fn section_prefix_of(&self, name: &XorName) -> Option<Prefix> {
+        self.network
+            .closest_or_opposite(name, None)
+            .map(|sap| sap.prefix())
+    }
+
+    /// Whether the given key is the current key of one of the sections we know of.
+    pub(crate) fn is_current_section_key(&self, key: &bls::PublicKey) -> bool {
+        self.network.section_keys().contains(key)
+    }
+
+    /// Genesis key of the network.
+    pub(crate) fn genesis_key(&self) -> bls::PublicKey {
+        self.genesis_key
+    }
+
+    /// Whether the given key is a section key of the network we know of.
+    pub(crate) async fn is_known_section_key(&self, key: &bls::PublicKey) -> bool {
+        self.network.section_keys().contains(key)
+            || self.all_sections_chains.read().await.has_key(key)
+    }
+
+    /// Snapshot of the sections we know of, and of our connections to their Elders.
+    pub(crate) async fn network_snapshot(&self, timestamp: u64) -> NetworkSnapshot {
+        let mut connections = vec![];
+        if let Some(peer_links) = self.peer_links() {
+            for peer in peer_links.linked_peers().await {
+                let connected = peer_links.is_connected(&peer).await;
+                connections.push((peer, connected));
+            }
+        }
+        NetworkSnapshot::new(&self.network, connections, timestamp)
+    }
+
+    /// Number of msgs received which were sent within another network, and dropped.
+    pub(crate) fn other_network_msgs_dropped(&self) -> u64 {
+        self.other_network_msgs.load(Ordering::Relaxed)
+    }
+
+    /// Stats of the cache of the sections responsible for the data names we sent msgs for.
+    pub(crate) fn routing_cache_stats(&self) -> RoutingCacheStats {
+        self.routing_cache.stats()
+    }
+
+    // Section to send msgs for the given name to: the one cached as responsible for it, if any,
+    // or else the closest one we know of, which redirects us with AE if it's not the right one.
+    // Either way, its current SAP is the one in our network knowledge.
+    fn route(&self, dst: &XorName) -> Option<SectionAuth<SectionAuthorityProvider>> {
+        if let Some(prefix) = self.routing_cache.route(dst) {
+            match self.network.get_signed(&prefix) {
+                Some(section) => return Some(section),
+                // the section split or merged since we cached it
+                None => self.routing_cache.remove(&prefix),
+            }
+        }
+        self.network.closest_or_opposite(dst, None)
+    }
+
     async fn get_query_elders(&self, dst: XorName) -> Result<(bls::PublicKey, Vec<Peer>)> {
         // Get DataSection elders details. Resort to own section if DataSection is not available.
-        let sap = self.network.closest_or_opposite(&dst, None);
+        let sap = self.route(&dst);
         let (section_pk, mut elders) = if let Some(sap) = &sap {
             (sap.section_key(), sap.elders_vec())
         } else {
@@ -502,7 +744,7 @@ impl Session {
     }
 
     async fn get_cmd_elders(&self, dst_address: XorName) -> Result<(bls::PublicKey, Vec<Peer>)> {
-        let a_close_sap = self.network.closest_or_opposite(&dst_address, None);
+        let a_close_sap = self.route(&dst_address);
         let the_close_sap = a_close_sap.clone().map(|auth| auth.value);
         // Get DataSection elders details.
         let (mut elders, section_pk) = if let Some(sap) = a_close_sap {
@@ -560,12 +802,24 @@ pub(super) fn send_msg_in_bg(
 pub(super) async fn send_msg(
     session: Session,
     nodes: Vec<Peer>,
-    wire_msg: WireMsg,
+    mut wire_msg: WireMsg,
     msg_id: MsgId,
 ) -> Result<()> {
     let priority = wire_msg.clone().into_msg()?.priority();
+    wire_msg.set_network_name(session.network_name);
     let msg_bytes = wire_msg.serialize()?;
 
+    let (endpoint, peer_links) = match &session.transport {
+        Transport::Network {
+            endpoint,
+            peer_links,
+        } => (endpoint.clone(), peer_links.clone()),
+        #[cfg(any(test, feature = "test-utils"))]
+        Transport::Mock(network) => {
+            return network.deliver(&session, nodes, msg_bytes, msg_id).await
+        }
+    };
+
     let mut last_error = None;
     drop(wire_msg);
 
@@ -576,11 +830,12 @@ pub(super) async fn send_msg(
 
     for peer in nodes.clone() {
         let session = session.clone();
+        let peer_links = peer_links.clone();
         let msg_bytes_clone = msg_bytes.clone();
         let peer_name = peer.name();
 
         let task_handle: JoinHandle<(XorName, Result<()>)> = tokio::spawn(async move {
-            let link = session.peer_links.get_or_create(&peer).await;
+            let link = peer_links.get_or_create(&peer).await;
 
             let listen = |conn, incoming_msgs| {
                 Session::spawn_msg_listener_thread(session.clone(), peer, conn, incoming_msgs);
@@ -660,7 +915,7 @@ pub(super) async fn send_msg(
         trace!(
             "Sending the message ({:?}) from {} to {}/{} of the nodes failed: {:?}",
             msg_id,
-            session.endpoint.public_addr(),
+            endpoint.public_addr(),
             failures,
             nodes.len(),
             nodes,
@@ -691,3 +946,8 @@ pub(crate) async fn create_safe_dir() -> Result<PathBuf, Error> {
 
     Ok(root_dir)
 }
+
+// Number of bytes a query response takes on the wire, roughly
+fn response_size(response: &QueryResponse) -> usize {
+    bincode::serialized_size(response).unwrap_or_default() as usize
+}
@@ -8,18 +8,25 @@
 
 mod listeners;
 mod messaging;
+mod routing_cache;
+
+pub use routing_cache::RoutingCacheStats;
+
+#[cfg(any(test, feature = "test-utils"))]
+use crate::MockNetwork;
 
 use sn_interface::messaging::{
-    data::{CmdError, OperationId, QueryResponse},
-    MsgId,
+    data::{CmdError, OperationId, QueryResponse, StorageReceiptShare},
+    MsgId, NetworkName,
 };
 use sn_interface::network_knowledge::prefix_map::NetworkPrefixMap;
 use sn_interface::types::PeerLinks;
 
 use dashmap::DashMap;
 use qp2p::Endpoint;
+use routing_cache::RoutingCache;
 use secured_linked_list::SecuredLinkedList;
-use std::sync::Arc;
+use std::sync::{atomic::AtomicU64, Arc};
 use tokio::{
     sync::{mpsc::Sender, RwLock},
     time::Duration,
@@ -29,7 +36,20 @@ use tokio::{
 type PendingQueryResponses = Arc<DashMap<OperationId, Vec<(MsgId, QueryResponseSender)>>>;
 type QueryResponseSender = Sender<QueryResponse>;
 
-type CmdResponse = (std::net::SocketAddr, Option<CmdError>);
+// Either the cmd was acknowledged, or an error was returned
+type CmdResponse = (
+    std::net::SocketAddr,
+    std::result::Result<CmdAcked, CmdError>,
+);
+
+// How far the handling of a cmd was acknowledged by an Elder
+#[derive(Debug)]
+enum CmdAcked {
+    // The cmd was accepted, with a share of the storage receipt for stored chunks
+    Processed(Option<StorageReceiptShare>),
+    // The Adults meant to hold the data written confirmed storing it
+    Replicated,
+}
 type PendingCmdAcks = Arc<DashMap<MsgId, Sender<CmdResponse>>>;
 
 #[derive(Debug)]
@@ -38,10 +58,23 @@ pub struct QueryResult {
     pub operation_id: OperationId,
 }
 
+// How the msgs of a session reach the nodes
+#[derive(Clone, Debug)]
+pub(super) enum Transport {
+    // Over connections to the nodes, from the session's endpoint
+    Network {
+        endpoint: Endpoint,
+        peer_links: PeerLinks,
+    },
+    // Delivered to an in-memory network, no endpoint being bound
+    #[cfg(any(test, feature = "test-utils"))]
+    Mock(MockNetwork),
+}
+
 #[derive(Clone, Debug)]
 pub(super) struct Session {
-    // Session endpoint.
-    endpoint: Endpoint,
+    // How msgs are sent to the nodes
+    transport: Transport,
     // Channels for sending responses to upper layers
     pending_queries: PendingQueryResponses,
     // Channels for sending errors to upper layer
@@ -51,6 +84,8 @@ pub(super) struct Session {
     pending_cmds: PendingCmdAcks,
     /// All elders we know about from AE messages
     network: Arc<NetworkPrefixMap>,
+    /// Sections responsible for the data names we sent msgs for, as learnt from AE messages
+    routing_cache: Arc<RoutingCache>,
     /// A DAG containing all section chains of the whole network that we are aware of
     all_sections_chains: Arc<RwLock<SecuredLinkedList>>,
     /// Network's genesis key
@@ -59,6 +94,10 @@ pub(super) struct Session {
     initial_connection_check_msg_id: Arc<RwLock<Option<MsgId>>>,
     /// Standard time to await potential AE messages:
     cmd_ack_wait: Duration,
-    /// Links to nodes
-    peer_links: PeerLinks,
+    /// Name of the network we only exchange msgs within, if any
+    network_name: Option<NetworkName>,
+    /// Number of msgs received which were sent within another network, and dropped
+    other_network_msgs: Arc<AtomicU64>,
+    /// Maximum number of nodes contacted at once when starting
+    contacts_per_batch: usize,
 }
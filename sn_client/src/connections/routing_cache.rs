@@ -0,0 +1,148 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use dashmap::DashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use xor_name::{Prefix, XorName};
+
+/// Stats of the cache of the sections responsible for the data names msgs are sent for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RoutingCacheStats {
+    /// Number of sections cached
+    pub cached_sections: usize,
+    /// Number of msgs sent to a section found in the cache
+    pub hits: u64,
+    /// Number of msgs sent to the closest section known instead, as none in the cache was
+    /// responsible for their data name
+    pub misses: u64,
+    /// Number of msgs bounced back with AE msgs after being sent to a cached section,
+    /// which was no longer the one responsible for their data name
+    pub stale_target_retries: u64,
+}
+
+// Cache of the prefixes of the sections responsible for the data names msgs are sent for, as
+// learnt from the SAPs of AE msgs. Unlike the closest section known, which may only be a
+// neighbour of the section responsible for a name, and so bounces the msg back with a redirect,
+// a cached section matches the name, so the msg gets there without an extra round trip.
+// Only prefixes are cached: the Elders of a section are resolved from our current network
+// knowledge, which is kept up to date through AE as sections churn.
+#[derive(Debug, Default)]
+pub(super) struct RoutingCache {
+    sections: DashSet<Prefix>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    stale_target_retries: AtomicU64,
+}
+
+impl RoutingCache {
+    // Prefix of the section responsible for the given name, if cached.
+    pub(super) fn route(&self, name: &XorName) -> Option<Prefix> {
+        let section = self
+            .sections
+            .iter()
+            .map(|prefix| *prefix)
+            .filter(|prefix| prefix.matches(name))
+            .max_by_key(|prefix| prefix.bit_count());
+
+        let counter = if section.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        let _ = counter.fetch_add(1, Ordering::Relaxed);
+
+        section
+    }
+
+    // Caches the prefix of a section, replacing the sections it split from or merged out of.
+    pub(super) fn insert(&self, prefix: Prefix) {
+        self.sections
+            .retain(|cached| *cached == prefix || !cached.is_compatible(&prefix));
+        let _ = self.sections.insert(prefix);
+    }
+
+    // Removes a prefix our network knowledge doesn't have a section for anymore.
+    pub(super) fn remove(&self, prefix: &Prefix) {
+        let _ = self.sections.remove(prefix);
+    }
+
+    // Removes the section cached for the given name, after a msg sent for that name was bounced
+    // back with an AE msg. Returns whether there was one, i.e. whether the msg was sent to a
+    // stale target because of the cache, which is then accounted for.
+    pub(super) fn invalidate(&self, name: &XorName) -> bool {
+        let stale: Vec<Prefix> = self
+            .sections
+            .iter()
+            .map(|prefix| *prefix)
+            .filter(|prefix| prefix.matches(name))
+            .collect();
+
+        for prefix in &stale {
+            let _ = self.sections.remove(prefix);
+        }
+
+        if stale.is_empty() {
+            false
+        } else {
+            let _ = self.stale_target_retries.fetch_add(1, Ordering::Relaxed);
+            true
+        }
+    }
+
+    pub(super) fn stats(&self) -> RoutingCacheStats {
+        RoutingCacheStats {
+            cached_sections: self.sections.len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            stale_target_retries: self.stale_target_retries.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RoutingCache, RoutingCacheStats};
+
+    use eyre::Result;
+    use xor_name::Prefix;
+
+    #[test]
+    fn routes_to_cached_sections_until_invalidated() -> Result<()> {
+        let cache = RoutingCache::default();
+        let p0 = Prefix::default().pushed(false);
+        let p1 = Prefix::default().pushed(true);
+        let name_in_p0 = p0.substituted_in(xor_name::rand::random());
+        let name_in_p1 = p1.substituted_in(xor_name::rand::random());
+
+        cache.insert(p0);
+        assert_eq!(cache.route(&name_in_p0), Some(p0));
+        assert!(cache.route(&name_in_p1).is_none());
+
+        // the section splitting replaces it with its children
+        let p00 = p0.pushed(false);
+        cache.insert(p00);
+        assert!(cache
+            .route(&p0.pushed(true).substituted_in(name_in_p0))
+            .is_none());
+        assert_eq!(cache.route(&p00.substituted_in(name_in_p0)), Some(p00));
+
+        assert!(cache.invalidate(&p00.substituted_in(name_in_p0)));
+        assert!(!cache.invalidate(&name_in_p1));
+        assert_eq!(
+            cache.stats(),
+            RoutingCacheStats {
+                cached_sections: 0,
+                hits: 2,
+                misses: 2,
+                stale_target_retries: 1,
+            }
+        );
+
+        Ok(())
+    }
+}
@@ -16,6 +16,7 @@ use sn_interface::types::Error as DtError;
 use std::io;
 use std::net::SocketAddr;
 use thiserror::Error;
+use xor_name::XorName;
 
 /// Specialisation of `std::Result` for Client.
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -31,6 +32,9 @@ pub enum Error {
     /// Initial network contact failed
     #[error("Initial network contact probe failed.")]
     NetworkContact,
+    /// The client's signer failed to sign
+    #[error("Failed to sign: {0}")]
+    Signing(String),
     /// Genesis Key from the config and the PrefixMap mismatch
     #[error("Genesis Key from the config and the PrefixMap mismatch. You may need to remove your prefixmap or update your config file.")]
     GenesisKeyMismatch,
@@ -88,6 +92,12 @@ pub enum Error {
         "The provided bytes is too large to store as a `SmallFile`. Store as a LargeFile instead."
     )]
     TooLargeAsSmallFile,
+    /// The provided bytes is too large to store as temporary data.
+    #[error("The provided bytes is too large to store as temporary data, which is limited to {0} bytes.")]
+    TooLargeAsTemporaryData(usize),
+    /// The temporary data read is expired.
+    #[error("The temporary data at {0:?} is expired.")]
+    DataExpired(XorName),
     /// No query response before timeout
     #[error("Query timed out")]
     QueryTimedOut,
@@ -172,6 +182,40 @@ pub enum Error {
         /// Number of Chunks generated
         chunked: usize,
     },
+    /// Not enough Elders confirmed the data written by a cmd sent with the
+    /// `CmdAckLevel::Replicated` ack level got replicated, before giving up on waiting
+    #[error("Only {confirmations} of the {required} Elders' confirmations required were received that the data written by cmd {msg_id:?} got replicated")]
+    CmdNotReplicated {
+        /// MsgId of the cmd
+        msg_id: MsgId,
+        /// Number of Elders which confirmed the data got replicated
+        confirmations: usize,
+        /// Number of confirmations required
+        required: usize,
+    },
+    /// The task sending a cmd in the background stopped before the cmd was acknowledged
+    #[error("The sending of cmd to {0:?} was aborted")]
+    CmdSendingAborted(XorName),
+    /// A storage receipt could not be verified
+    #[error("Invalid storage receipt: {0}")]
+    InvalidStorageReceipt(String),
+    /// A history of section keys could not be verified
+    #[error("Invalid section key history: {0}")]
+    InvalidSectionKeyHistory(String),
+    /// The pages of the members of a section could not be put together
+    #[error("Invalid section members: {0}")]
+    InvalidSectionMembers(String),
+}
+
+impl Error {
+    /// Whether the error is due to the connection to a node being lost, or not
+    /// being possible to establish, in which case reconnecting may fix it.
+    pub fn is_connection_lost(&self) -> bool {
+        matches!(
+            self,
+            Self::QuicP2pConnection(_) | Self::QuicP2pSend(qp2p::SendError::ConnectionLost(_))
+        )
+    }
 }
 
 impl From<(CmdError, MsgId)> for Error {
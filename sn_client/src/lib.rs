@@ -60,16 +60,34 @@
 #[macro_use]
 extern crate tracing;
 
+mod bandwidth;
 mod config_handler;
 mod connections;
 mod errors;
+#[cfg(any(test, feature = "test-utils"))]
+mod mock_network;
+mod query_timeouts;
+mod signer;
+mod telemetry;
 
 // Export public API.
-pub use api::{Client, RegisterWriteAheadLog};
-pub use config_handler::{ClientConfig, DEFAULT_ACK_WAIT, DEFAULT_OPERATION_TIMEOUT};
+pub use api::{
+    BytesReader, ChunkDetails, Client, CmdDelivery, DataMapInfo, PaddingBuckets, ReadAhead,
+    RegisterWriteAheadLog,
+};
+pub use bandwidth::{BandwidthLimits, Throughput};
+pub use config_handler::{
+    BootstrapConfig, ClientConfig, DEFAULT_ACK_WAIT, DEFAULT_OPERATION_TIMEOUT,
+};
+pub use connections::RoutingCacheStats;
 pub use errors::ErrorMsg;
 pub use errors::{Error, Result};
+#[cfg(any(test, feature = "test-utils"))]
+pub use mock_network::{Faults, MockNetwork, MockSection};
 pub use qp2p::Config as QuicP2pConfig;
+pub use query_timeouts::{QuerySizeClass, QueryTimeoutStats, QueryTimeouts};
+pub use signer::Signer;
+pub use telemetry::{ConnectionState, OperationKind, Outcome, Telemetry, TelemetryEvent};
 
 /// Client trait and related constants.
 pub mod api;
@@ -0,0 +1,1005 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::{connections::Session, Error, Result};
+
+use bytes::Bytes;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use secured_linked_list::SecuredLinkedList;
+use serde::Serialize;
+use sn_interface::elder_count;
+use sn_interface::messaging::{
+    data::{
+        CmdAckLevel, CmdError, CreateRegister, DataCmd, DataQuery, DeleteRegister, EditRegister,
+        Error as ErrorMsg, ExtendRegister, OperationId, QueryResponse, RegisterCmd, RegisterQuery,
+        ServiceMsg, SignedRegisterCreate, SignedRegisterDelete, SignedRegisterEdit,
+        SignedRegisterExtend,
+    },
+    system::{KeyedSig, SectionAuth, SystemMsg},
+    AuthKind, DstLocation, EndUser, MsgId, MsgType, NetworkName, NodeAuth, ServiceAuth,
+    SigningDomain, VerifyAuthority, WireMsg,
+};
+use sn_interface::network_knowledge::{
+    NodeInfo, NodeState, SectionAuthorityProvider, MIN_ADULT_AGE,
+};
+use sn_interface::types::{
+    convert_dt_error_to_error_msg,
+    keys::ed25519,
+    register::{Action, Register, User},
+    Chunk, DataAddress, Keypair, Peer, PublicKey, RegisterAddress,
+};
+use std::{
+    collections::BTreeMap,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::{Mutex, RwLock};
+use xor_name::{Prefix, XorName};
+
+/// Faults injected by a [`MockNetwork`] into the msgs sent to it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Faults {
+    /// Time each msg takes to be handled
+    pub latency: Duration,
+    /// Ratio of the msgs which are dropped, from 0.0 to 1.0, failing the attempt to send them
+    /// to the Elders they're dropped on the way to, as when the connections to them are lost.
+    /// Clients retry them as usual.
+    pub drop_rate: f64,
+}
+
+/// A section of a [`MockNetwork`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MockSection {
+    /// Prefix of the names the section is responsible for
+    pub prefix: Prefix,
+    /// Current key of the section, changing whenever it splits or its Elders change
+    pub key: bls::PublicKey,
+}
+
+/// An in-memory stand-in for the network, storing chunks and registers, which a [`Client`]
+/// created with [`Client::new_mock`] sends its msgs to, so apps can be tested without a testnet.
+///
+/// Only the connections to the Elders are mocked: clients send their msgs and handle the
+/// responses to them as they do on the network. Whether a msg is dropped on its way to an Elder
+/// is decided by a random number generator seeded with the seed the network is created with, the
+/// content of the msg, and how many times the same content was sent before, so a test sending the
+/// same msgs gets the same results on each run, whichever order concurrent msgs are handled in. Storage costs and payments aren't checked, and spentbooks and section stats aren't
+/// supported.
+///
+/// The network starts as a single section, which tests can split, or change the Elders of, at
+/// any time. Clients learn about it as they do on the network, through AE: each Elder a msg is
+/// sent to checks it as nodes do, bouncing it in a signed AE-Redirect if the Elder isn't one of
+/// the section responsible for its destination anymore, or in a signed AE-Retry if it was sent
+/// with an outdated section key, which the client updates its knowledge with and sends the msg
+/// again.
+///
+/// The network can be shared by several clients, each of them holding a clone of it.
+///
+/// [`Client`]: crate::Client
+/// [`Client::new_mock`]: crate::Client::new_mock
+#[derive(Clone, Debug)]
+pub struct MockNetwork {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    genesis_section: SectionAuth<SectionAuthorityProvider>,
+    chunks: RwLock<BTreeMap<XorName, Chunk>>,
+    registers: RwLock<BTreeMap<RegisterAddress, Register>>,
+    faults: RwLock<Faults>,
+    topology: RwLock<Topology>,
+    seed: u64,
+    rng: Mutex<StdRng>,
+    // number of times msgs of the same content were sent, to any node
+    attempts: Mutex<BTreeMap<XorName, u64>>,
+    msgs_dropped: AtomicU64,
+    msgs_redirected: AtomicU64,
+}
+
+// Sections of the network, and the nodes which are or were Elders of any of them
+#[derive(Debug)]
+struct Topology {
+    sections: BTreeMap<Prefix, SectionState>,
+    // nodes stay members of the section matching their name once they're not Elders anymore
+    nodes: BTreeMap<XorName, NodeInfo>,
+    next_port: u16,
+}
+
+#[derive(Debug)]
+struct SectionState {
+    secret_key_set: bls::SecretKeySet,
+    signed_sap: SectionAuth<SectionAuthorityProvider>,
+    // chain of the section keys, from the genesis key to the current one
+    chain: SecuredLinkedList,
+}
+
+impl MockNetwork {
+    /// Creates a network of a single section, the msgs to drop being picked with a generator
+    /// using the given seed.
+    pub fn new(seed: u64) -> Result<Self> {
+        let mut topology = Topology {
+            sections: BTreeMap::new(),
+            nodes: BTreeMap::new(),
+            next_port: 1,
+        };
+        let elders = topology.gen_nodes(&Prefix::default(), elder_count());
+        let secret_key_set = bls::SecretKeySet::random(0, &mut rand::thread_rng());
+        let genesis_key = secret_key_set.public_keys().public_key();
+        let genesis = SectionState::new(
+            secret_key_set,
+            Prefix::default(),
+            elders,
+            SecuredLinkedList::new(genesis_key),
+        )?;
+        let genesis_section = genesis.signed_sap.clone();
+        let _ = topology.sections.insert(Prefix::default(), genesis);
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                genesis_section,
+                chunks: RwLock::new(BTreeMap::new()),
+                registers: RwLock::new(BTreeMap::new()),
+                faults: RwLock::new(Faults::default()),
+                topology: RwLock::new(topology),
+                seed,
+                rng: Mutex::new(StdRng::seed_from_u64(seed)),
+                attempts: Mutex::new(BTreeMap::new()),
+                msgs_dropped: AtomicU64::new(0),
+                msgs_redirected: AtomicU64::new(0),
+            }),
+        })
+    }
+
+    /// Genesis key clients of the network are to be configured with.
+    pub fn genesis_key(&self) -> bls::PublicKey {
+        self.inner.genesis_section.section_key()
+    }
+
+    /// Sets the faults injected into the msgs sent from now on.
+    pub async fn set_faults(&self, faults: Faults) {
+        *self.inner.faults.write().await = faults;
+    }
+
+    /// Number of msgs dropped so far.
+    pub fn msgs_dropped(&self) -> u64 {
+        self.inner.msgs_dropped.load(Ordering::Relaxed)
+    }
+
+    /// Number of msgs bounced by AE so far, as they were sent to a section which isn't the one
+    /// responsible for their destination anymore, or with an outdated section key.
+    pub fn msgs_redirected(&self) -> u64 {
+        self.inner.msgs_redirected.load(Ordering::Relaxed)
+    }
+
+    /// Sections of the network, sorted by prefix.
+    pub async fn sections(&self) -> Vec<MockSection> {
+        self.inner
+            .topology
+            .read()
+            .await
+            .sections
+            .values()
+            .map(SectionState::section)
+            .collect()
+    }
+
+    /// Splits the section with the given prefix into its two children, each with a new key.
+    /// The Elders of the section stay Elders of the child matching their name, new ones joining
+    /// for each child to have as many Elders as the section had.
+    /// Returns the children, or None if there's no section with the prefix.
+    pub async fn split_section(&self, prefix: Prefix) -> Option<[MockSection; 2]> {
+        let mut topology = self.inner.topology.write().await;
+        let parent = topology.sections.remove(&prefix)?;
+        let mut children = vec![];
+        for child in [prefix.pushed(false), prefix.pushed(true)] {
+            let mut elders: Vec<_> = topology
+                .elders_of(&parent)
+                .into_iter()
+                .filter(|elder| child.matches(&elder.name()))
+                .collect();
+            let missing = elder_count().saturating_sub(elders.len());
+            elders.extend(topology.gen_nodes(&child, missing));
+            let section = match parent.next(child, elders) {
+                Ok(section) => section,
+                Err(error) => {
+                    error!(
+                        "Mock network failed to split section {:?}: {:?}",
+                        prefix, error
+                    );
+                    let _ = topology.sections.insert(prefix, parent);
+                    return None;
+                }
+            };
+            children.push(section.section());
+            let _ = topology.sections.insert(child, section);
+        }
+        debug!("Mock network split section {:?}", prefix);
+        Some([children[0], children[1]])
+    }
+
+    /// Changes the Elders of the section with the given prefix, one of them being replaced by a
+    /// new one, and the section getting a new key.
+    /// Returns the section as it now is, or None if there's no section with the prefix.
+    pub async fn churn_elders(&self, prefix: Prefix) -> Option<MockSection> {
+        let mut topology = self.inner.topology.write().await;
+        let section = topology.sections.remove(&prefix)?;
+        let mut elders = topology.elders_of(&section);
+        let demoted = self.inner.rng.lock().await.gen_range(0..elders.len());
+        let _ = elders.remove(demoted);
+        elders.extend(topology.gen_nodes(&prefix, 1));
+        let section = match section.next(prefix, elders) {
+            Ok(next) => next,
+            Err(error) => {
+                error!(
+                    "Mock network failed to change the Elders of {:?}: {:?}",
+                    prefix, error
+                );
+                let _ = topology.sections.insert(prefix, section);
+                return None;
+            }
+        };
+        let churned = section.section();
+        let _ = topology.sections.insert(prefix, section);
+        debug!("Mock network changed the Elders of section {:?}", prefix);
+        Some(churned)
+    }
+
+    /// Whether the chunk with the given name is stored.
+    pub async fn has_chunk(&self, name: &XorName) -> bool {
+        self.inner.chunks.read().await.contains_key(name)
+    }
+
+    /// Removes the chunk with the given name, as if all its holders were lost.
+    pub async fn remove_chunk(&self, name: &XorName) -> bool {
+        self.inner.chunks.write().await.remove(name).is_some()
+    }
+
+    /// Replica of the register at the given address, if it's stored.
+    pub async fn register(&self, address: &RegisterAddress) -> Option<Register> {
+        self.inner.registers.read().await.get(address).cloned()
+    }
+
+    // SAP of the genesis section, as known to a client from the start
+    pub(crate) fn genesis_section(&self) -> SectionAuth<SectionAuthorityProvider> {
+        self.inner.genesis_section.clone()
+    }
+
+    // Delivers the msg to each of the given nodes, as sending it to them over connections does,
+    // the responses they send back being handled by the session as if received over them.
+    // Fails as sending the msg does, if it's dropped on the way to most of the nodes.
+    pub(crate) async fn deliver(
+        &self,
+        session: &Session,
+        nodes: Vec<Peer>,
+        msg_bytes: Bytes,
+        msg_id: MsgId,
+    ) -> Result<()> {
+        let mut delivered = 0;
+        let mut last_error = None;
+        for peer in &nodes {
+            match self.receive(peer, msg_bytes.clone()).await {
+                Ok(responses) => {
+                    delivered += 1;
+                    for response in responses {
+                        Session::spawn_msg_handler(session.clone(), *peer, response);
+                    }
+                }
+                Err(error) => {
+                    debug!(
+                        "Mock network failed to deliver msg {:?} to {:?}: {:?}",
+                        msg_id, peer, error
+                    );
+                    last_error = Some(error);
+                }
+            }
+        }
+
+        match last_error {
+            Some(error) if nodes.len() - delivered > delivered => Err(error),
+            _ => Ok(()),
+        }
+    }
+
+    // Receives the msg sent to the given node, which handles it as nodes do, after waiting for as
+    // long as it takes to be handled, unless it's dropped. Returns the msgs sent back.
+    async fn receive(&self, peer: &Peer, msg_bytes: Bytes) -> Result<Vec<Bytes>> {
+        let faults = *self.inner.faults.read().await;
+        tokio::time::sleep(faults.latency).await;
+
+        let wire_msg = WireMsg::from(msg_bytes.clone())?;
+        if faults.drop_rate > 0.0 && self.is_dropped(&wire_msg, faults.drop_rate).await {
+            let _ = self.inner.msgs_dropped.fetch_add(1, Ordering::Relaxed);
+            debug!("Mock network dropped msg {:?}", wire_msg.msg_id());
+            return Err(Error::NoResponse);
+        }
+
+        let (msg_id, auth, msg, dst_location) = match wire_msg.into_msg()? {
+            MsgType::Service {
+                msg_id,
+                auth,
+                msg,
+                dst_location,
+            } => (msg_id, auth.into_inner(), msg, dst_location),
+            _ => return Err(Error::ReceivedUnexpectedEvent),
+        };
+        let network_name = wire_msg.network_name();
+        let client = DstLocation::EndUser(EndUser(XorName::from(auth.public_key)));
+
+        let topology = self.inner.topology.read().await;
+        let node = topology
+            .nodes
+            .get(&peer.name())
+            .filter(|node| node.addr == peer.addr())
+            .ok_or(Error::PeerConnection(peer.addr()))?;
+        let section = topology
+            .section_of(&node.name())
+            .ok_or(Error::PeerConnection(peer.addr()))?;
+
+        if let Some(ae_msg) = topology.check_for_entropy(node, section, &dst_location, msg_bytes) {
+            let _ = self.inner.msgs_redirected.fetch_add(1, Ordering::Relaxed);
+            debug!(
+                "Mock network node {:?} bounced msg {:?} sent to {:?}",
+                node.name(),
+                msg_id,
+                dst_location
+            );
+            let response = node_msg(node, section.key(), client, &ae_msg, network_name)?;
+            return Ok(vec![response]);
+        }
+
+        let node = node.clone();
+        drop(topology);
+
+        let responses = match msg {
+            ServiceMsg::Cmd { cmd, ack_level, .. } => {
+                self.handle_cmd(msg_id, cmd, ack_level, auth.public_key)
+                    .await
+            }
+            ServiceMsg::Query(query) => {
+                vec![self.handle_query(msg_id, query, auth.public_key).await?]
+            }
+            _ => return Err(Error::ReceivedUnexpectedEvent),
+        };
+
+        responses
+            .iter()
+            .map(|response| service_msg(&node, client, response, network_name))
+            .collect()
+    }
+
+    // Whether the msg is dropped, as picked by a generator seeded from the msg's payload rather
+    // than from its id, which differs each time it's sent again, nor from the names of the nodes,
+    // which differ on each run. A msg is sent to its nodes one after the other, so the attempts
+    // to send the same content are counted in the same order whatever other msgs are sent.
+    async fn is_dropped(&self, wire_msg: &WireMsg, drop_rate: f64) -> bool {
+        let content = XorName::from_content(&wire_msg.payload);
+        let attempt = {
+            let mut attempts = self.inner.attempts.lock().await;
+            let attempt = attempts.entry(content).or_insert(0);
+            *attempt += 1;
+            *attempt
+        };
+        let seed = XorName::from_content_parts(&[
+            &self.inner.seed.to_be_bytes(),
+            &content.0,
+            &attempt.to_be_bytes(),
+        ]);
+        StdRng::from_seed(seed.0).gen_bool(drop_rate.min(1.0))
+    }
+
+    // Applies the cmd, returning the msgs an Elder acknowledges it with
+    async fn handle_cmd(
+        &self,
+        correlation_id: MsgId,
+        cmd: DataCmd,
+        ack_level: CmdAckLevel,
+        requester: PublicKey,
+    ) -> Vec<ServiceMsg> {
+        let result = match cmd {
+            DataCmd::StoreChunk(chunk) | DataCmd::StorePaidChunk { chunk, .. } => {
+                let _ = self.inner.chunks.write().await.insert(*chunk.name(), chunk);
+                Ok(())
+            }
+            DataCmd::Register(cmd) => {
+                let mut registers = self.inner.registers.write().await;
+                apply_register_cmd(&mut registers, cmd, requester)
+            }
+            DataCmd::Spentbook(_) => Err(ErrorMsg::InvalidOperation(
+                "spentbooks are not supported by the mock network".to_string(),
+            )),
+        };
+
+        match result {
+            Ok(()) if ack_level == CmdAckLevel::Replicated => vec![
+                ServiceMsg::CmdAck {
+                    correlation_id,
+                    receipt_share: None,
+                },
+                ServiceMsg::CmdReplicated { correlation_id },
+            ],
+            Ok(()) => vec![ServiceMsg::CmdAck {
+                correlation_id,
+                receipt_share: None,
+            }],
+            Err(error) => vec![ServiceMsg::CmdError {
+                error: CmdError::Data(error),
+                correlation_id,
+            }],
+        }
+    }
+
+    async fn handle_query(
+        &self,
+        correlation_id: MsgId,
+        query: DataQuery,
+        requester: PublicKey,
+    ) -> Result<ServiceMsg> {
+        let operation_id = query
+            .operation_id()
+            .map_err(|_| Error::UnknownOperationId)?;
+
+        let response = match &query {
+            DataQuery::GetChunk(address) => QueryResponse::GetChunk(
+                self.inner
+                    .chunks
+                    .read()
+                    .await
+                    .get(address.name())
+                    .cloned()
+                    .ok_or(ErrorMsg::ChunkNotFound(*address.name())),
+            ),
+            DataQuery::ReportMissingChunk(_) => {
+                QueryResponse::ReportMissingChunk((Ok(()), operation_id))
+            }
+            DataQuery::Register(query) => {
+                let registers = self.inner.registers.read().await;
+                read_register(&registers, query, User::Key(requester), operation_id)
+            }
+            DataQuery::Spentbook(_)
+            | DataQuery::GetSectionStats(_)
+            | DataQuery::GetSectionKeyHistory { .. }
+            | DataQuery::GetSectionMembers { .. } => query
+                .error(ErrorMsg::InvalidOperation(
+                    "query not supported by the mock network".to_string(),
+                ))
+                .map_err(|source| Error::ErrorMsg {
+                    source,
+                    op_id: operation_id,
+                })?,
+        };
+
+        Ok(ServiceMsg::QueryResponse {
+            response,
+            correlation_id,
+        })
+    }
+}
+
+impl Topology {
+    // Section the given name is in, i.e. the one whose prefix is the longest to match it
+    fn section_of(&self, name: &XorName) -> Option<&SectionState> {
+        self.sections
+            .iter()
+            .filter(|(prefix, _)| prefix.matches(name))
+            .max_by_key(|(prefix, _)| prefix.bit_count())
+            .map(|(_, section)| section)
+    }
+
+    fn elders_of(&self, section: &SectionState) -> Vec<NodeInfo> {
+        section
+            .signed_sap
+            .elders()
+            .filter_map(|elder| self.nodes.get(&elder.name()).cloned())
+            .collect()
+    }
+
+    // Creates the given number of nodes with names matching the prefix
+    fn gen_nodes(&mut self, prefix: &Prefix, count: usize) -> Vec<NodeInfo> {
+        (0..count)
+            .map(|_| {
+                let keypair = ed25519::gen_keypair(&prefix.range_inclusive(), MIN_ADULT_AGE);
+                let addr = SocketAddr::from(([192, 0, 2, 0], self.next_port));
+                self.next_port = self.next_port.wrapping_add(1);
+                let node = NodeInfo::new(keypair, addr);
+                let _ = self.nodes.insert(node.name(), node.clone());
+                node
+            })
+            .collect()
+    }
+
+    // AE msg the node sends back for the msg it received, if any, as nodes check the msgs sent
+    // to them: the msg is redirected to the Elders of the section of the node if it isn't one of
+    // them, or to the section responsible for its destination if it's not the node's, or it's
+    // to be sent again with the current key of the section if it wasn't.
+    fn check_for_entropy(
+        &self,
+        node: &NodeInfo,
+        section: &SectionState,
+        dst_location: &DstLocation,
+        bounced_msg: Bytes,
+    ) -> Option<SystemMsg> {
+        let dst_name = dst_location.name();
+        let redirect_to = if !section.signed_sap.contains_elder(&node.name()) {
+            Some(section)
+        } else if !section.signed_sap.prefix().matches(&dst_name) {
+            self.section_of(&dst_name)
+        } else {
+            None
+        };
+        if let Some(target) = redirect_to {
+            return Some(SystemMsg::AntiEntropyRedirect {
+                section_auth: target.signed_sap.value.to_msg(),
+                section_signed: target.signed_sap.sig.clone(),
+                section_chain: target.chain.clone(),
+                bounced_msg,
+            });
+        }
+
+        let dst_key = dst_location.section_pk()?;
+        if dst_key == section.key() {
+            return None;
+        }
+        let proof_chain = section
+            .chain
+            .get_proof_chain(&dst_key, &section.key())
+            .unwrap_or_else(|_| section.chain.clone());
+        Some(SystemMsg::AntiEntropyRetry {
+            section_auth: section.signed_sap.value.to_msg(),
+            section_signed: section.signed_sap.sig.clone(),
+            proof_chain,
+            bounced_msg,
+        })
+    }
+}
+
+impl SectionState {
+    fn new(
+        secret_key_set: bls::SecretKeySet,
+        prefix: Prefix,
+        elders: Vec<NodeInfo>,
+        chain: SecuredLinkedList,
+    ) -> Result<Self> {
+        let sap = SectionAuthorityProvider::new(
+            elders.iter().map(NodeInfo::peer),
+            prefix,
+            elders
+                .iter()
+                .map(|elder| NodeState::joined(elder.peer(), None)),
+            secret_key_set.public_keys(),
+            0,
+            sn_interface::data_copy_count(),
+        );
+        let sig = sign(&secret_key_set.secret_key(), &sap)?;
+        Ok(Self {
+            secret_key_set,
+            signed_sap: SectionAuth { value: sap, sig },
+            chain,
+        })
+    }
+
+    // Section with the given prefix and Elders succeeding this one, its key being signed by
+    // the key of this one
+    fn next(&self, prefix: Prefix, elders: Vec<NodeInfo>) -> Result<Self> {
+        let secret_key_set = bls::SecretKeySet::random(0, &mut rand::thread_rng());
+        let key = secret_key_set.public_keys().public_key();
+        let mut chain = self.chain.clone();
+        let KeyedSig { signature, .. } = sign(&self.secret_key_set.secret_key(), &key)?;
+        chain
+            .insert(&self.key(), key, signature)
+            .map_err(|error| Error::Signing(error.to_string()))?;
+        Self::new(secret_key_set, prefix, elders, chain)
+    }
+
+    fn key(&self) -> bls::PublicKey {
+        self.signed_sap.section_key()
+    }
+
+    fn section(&self) -> MockSection {
+        MockSection {
+            prefix: self.signed_sap.prefix(),
+            key: self.key(),
+        }
+    }
+}
+
+// System msg sent by the given node of the section with the given key, signed by it as nodes
+// sign the msgs they send
+fn node_msg(
+    node: &NodeInfo,
+    section_key: bls::PublicKey,
+    dst: DstLocation,
+    msg: &SystemMsg,
+    network_name: Option<NetworkName>,
+) -> Result<Bytes> {
+    let payload = WireMsg::serialize_msg_payload(msg)?;
+    let auth = NodeAuth::authorize(section_key, &node.keypair, &payload).into_inner();
+    let mut wire_msg =
+        WireMsg::new_system_msg(MsgId::new(), msg, payload, AuthKind::Node(auth), dst)?;
+    wire_msg.set_network_name(network_name);
+    Ok(wire_msg.serialize()?)
+}
+
+// Response to a client sent by the given node, signed by it as nodes sign their responses to
+// clients
+fn service_msg(
+    node: &NodeInfo,
+    dst: DstLocation,
+    msg: &ServiceMsg,
+    network_name: Option<NetworkName>,
+) -> Result<Bytes> {
+    let keypair = Keypair::Ed25519(node.keypair.clone());
+    let payload = WireMsg::serialize_msg_payload(msg)?;
+    let auth = ServiceAuth {
+        public_key: keypair.public_key(),
+        signature: keypair.sign(&SigningDomain::Service.signable_bytes(&payload)),
+    };
+    let mut wire_msg = WireMsg::new_msg(MsgId::new(), payload, AuthKind::Service(auth), dst)?;
+    wire_msg.set_network_name(network_name);
+    Ok(wire_msg.serialize()?)
+}
+
+// Signature of the serialised payload, as sections sign their SAPs and the keys succeeding theirs
+fn sign<T: Serialize>(secret_key: &bls::SecretKey, payload: &T) -> Result<KeyedSig> {
+    Ok(KeyedSig {
+        public_key: secret_key.public_key(),
+        signature: secret_key.sign(&bincode::serialize(payload)?),
+    })
+}
+
+// Applies the cmd as Adults do, the signatures over the ops being checked.
+fn apply_register_cmd(
+    registers: &mut BTreeMap<RegisterAddress, Register>,
+    cmd: RegisterCmd,
+    requester: PublicKey,
+) -> std::result::Result<(), ErrorMsg> {
+    match cmd {
+        RegisterCmd::Create {
+            cmd: SignedRegisterCreate { op, auth },
+            ..
+        } => {
+            verify(&op, auth)?;
+            let address = op.address();
+            if registers.contains_key(&address) {
+                return Err(ErrorMsg::DataExists);
+            }
+            let register = match op {
+                CreateRegister::Empty {
+                    name,
+                    tag,
+                    size,
+                    policy,
+                } => Register::new(name, tag, policy, size),
+                CreateRegister::Populated(register) => register,
+            };
+            let _ = registers.insert(address, register);
+            Ok(())
+        }
+        RegisterCmd::Edit(edit) => {
+            let register = stored(registers, &edit.op.address)?;
+            apply_edit(register, edit)
+        }
+        RegisterCmd::EditBatch(batch) => {
+            batch.validate()?;
+            let register = stored(registers, &batch.address)?;
+            // applied to a copy first, for none of the edits to be applied if any fails
+            let mut edited = register.clone();
+            for (index, edit) in batch.edits.into_iter().enumerate() {
+                if let Err(error) = apply_edit(&mut edited, edit) {
+                    let rejected = BTreeMap::from([(index, format!("{:?}", error))]);
+                    return Err(ErrorMsg::RegisterBatchRejected(rejected));
+                }
+            }
+            *register = edited;
+            Ok(())
+        }
+        RegisterCmd::Delete(SignedRegisterDelete { op, auth }) => {
+            let DeleteRegister(address) = op;
+            if address.is_public() {
+                return Err(ErrorMsg::InvalidOperation(
+                    "public registers cannot be deleted".to_string(),
+                ));
+            }
+            verify(&op, auth)?;
+            match registers.get(&address) {
+                // already deleted, or never existed
+                None => Ok(()),
+                Some(register) if register.owner() != User::Key(requester) => {
+                    Err(ErrorMsg::InvalidOwner(requester))
+                }
+                Some(_) => {
+                    let _ = registers.remove(&address);
+                    Ok(())
+                }
+            }
+        }
+        RegisterCmd::Extend {
+            cmd: SignedRegisterExtend { op, auth },
+            ..
+        } => {
+            verify(&op, auth)?;
+            let ExtendRegister {
+                address,
+                extend_with,
+            } = op;
+            stored(registers, &address)?.increment_cap(extend_with);
+            Ok(())
+        }
+    }
+}
+
+fn apply_edit(
+    register: &mut Register,
+    SignedRegisterEdit { op, auth }: SignedRegisterEdit,
+) -> std::result::Result<(), ErrorMsg> {
+    let writer = auth.public_key;
+    verify(&op, auth)?;
+    let EditRegister { edit, .. } = op;
+    register
+        .check_write_op(&edit, User::Key(writer))
+        .and_then(|()| register.apply_op(edit))
+        .map_err(convert_dt_error_to_error_msg)
+}
+
+fn read_register(
+    registers: &BTreeMap<RegisterAddress, Register>,
+    query: &RegisterQuery,
+    requester: User,
+    operation_id: OperationId,
+) -> QueryResponse {
+    let register = match registers.get(&query.dst_address()) {
+        Some(register) => register
+            .check_permissions(Action::Read, Some(requester))
+            .map(|()| register)
+            .map_err(convert_dt_error_to_error_msg),
+        None => Err(ErrorMsg::DataNotFound(DataAddress::Register(
+            query.dst_address(),
+        ))),
+    };
+
+    match query {
+        RegisterQuery::Get(_) => {
+            QueryResponse::GetRegister((register.map(Register::clone), operation_id))
+        }
+        RegisterQuery::Read(_) => {
+            QueryResponse::ReadRegister((register.map(Register::read), operation_id))
+        }
+        RegisterQuery::GetOwner(_) => {
+            QueryResponse::GetRegisterOwner((register.map(Register::owner), operation_id))
+        }
+        RegisterQuery::GetEntry { hash, .. } => QueryResponse::GetRegisterEntry((
+            register.and_then(|register| {
+                register
+                    .get(*hash)
+                    .map(Clone::clone)
+                    .map_err(convert_dt_error_to_error_msg)
+            }),
+            operation_id,
+        )),
+        RegisterQuery::GetPolicy(_) => QueryResponse::GetRegisterPolicy((
+            register.map(|register| register.policy().clone()),
+            operation_id,
+        )),
+        RegisterQuery::GetUserPermissions { user, .. } => {
+            QueryResponse::GetRegisterUserPermissions((
+                register.and_then(|register| {
+                    register
+                        .permissions(*user)
+                        .map_err(convert_dt_error_to_error_msg)
+                }),
+                operation_id,
+            ))
+        }
+    }
+}
+
+fn stored<'a>(
+    registers: &'a mut BTreeMap<RegisterAddress, Register>,
+    address: &RegisterAddress,
+) -> std::result::Result<&'a mut Register, ErrorMsg> {
+    registers
+        .get_mut(address)
+        .ok_or(ErrorMsg::DataNotFound(DataAddress::Register(*address)))
+}
+
+// Checks the op was signed by the one it claims to be signed by.
+fn verify<T: Serialize>(op: &T, auth: ServiceAuth) -> std::result::Result<(), ErrorMsg> {
+    let payload =
+        bincode::serialize(op).map_err(|error| ErrorMsg::InvalidOperation(error.to_string()))?;
+    let _ = auth
+        .verify_authority(payload)
+        .map_err(|_| ErrorMsg::InvalidOperation("invalid signature".to_string()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Faults, MockNetwork};
+    use crate::{utils::test_utils::init_logger, Client, ClientConfig, Error};
+    use sn_interface::messaging::data::{CmdAckLevel, DataCmd};
+    use sn_interface::types::{utils::random_bytes, Chunk, Scope};
+
+    use sn_interface::types::register::{Policy, PublicPolicy, User};
+
+    use eyre::{eyre, Result};
+    use std::{
+        collections::{BTreeMap, BTreeSet},
+        time::Duration,
+    };
+    use xor_name::{Prefix, XorName};
+
+    async fn mock_client(network: &MockNetwork) -> Result<Client> {
+        let config = ClientConfig::new(
+            None,
+            None,
+            network.genesis_key(),
+            None,
+            Some(Duration::from_secs(10)),
+            Some(Duration::from_secs(10)),
+            None,
+        )
+        .await;
+        Ok(Client::new_mock(config, network.clone(), None, None)?)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn data_is_stored_in_memory() -> Result<()> {
+        init_logger();
+        let network = MockNetwork::new(0)?;
+        let client = mock_client(&network).await?;
+
+        let bytes = random_bytes(3 * 1024 * 1024);
+        let address = client.upload(bytes.clone(), Scope::Public).await?;
+        // read by another client of the same network
+        let reader = mock_client(&network).await?;
+        assert_eq!(reader.read_bytes(address).await?, bytes);
+
+        let name = xor_name::rand::random();
+        let (address, batch) = client.create_register(name, 10, policy(&client)).await?;
+        client.publish_register_ops(batch, false).await?;
+        let (_, batch) = client
+            .write_to_register(address, b"entry".to_vec(), BTreeSet::new())
+            .await?;
+        client.publish_register_ops(batch, false).await?;
+
+        let entries = client.read_register(address).await?;
+        assert_eq!(entries.len(), 1);
+        assert!(network.register(&address).await.is_some());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn cmds_sent_in_background_resolve_once_acked() -> Result<()> {
+        init_logger();
+        let network = MockNetwork::new(0)?;
+        network
+            .set_faults(Faults {
+                latency: Duration::from_millis(50),
+                drop_rate: 0.0,
+            })
+            .await;
+        let client = mock_client(&network).await?;
+
+        let chunk = Chunk::new(random_bytes(1024));
+        let name = *chunk.name();
+        let delivery =
+            client.send_cmd_in_background(DataCmd::StoreChunk(chunk), CmdAckLevel::Processed);
+        assert_eq!(delivery.dst(), name);
+
+        let _receipt = delivery.await?;
+        assert!(network.has_chunk(&name).await);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn dropped_msgs_are_retried_deterministically() -> Result<()> {
+        init_logger();
+
+        // the same queries are sent on each run
+        let names: Vec<_> = (0..5u8).map(|i| XorName::from_content(&[i])).collect();
+        let mut dropped = vec![];
+        for _ in 0..2 {
+            let network = MockNetwork::new(42)?;
+            network
+                .set_faults(Faults {
+                    latency: Duration::from_millis(1),
+                    drop_rate: 0.5,
+                })
+                .await;
+            let client = mock_client(&network).await?;
+
+            let queries = names.iter().map(|name| client.get_chunk(name));
+            for result in futures::future::join_all(queries).await {
+                match result {
+                    Err(Error::ErrorMsg { .. }) | Err(Error::NoResponse) => {}
+                    other => panic!("unexpected result: {:?}", other),
+                }
+            }
+            dropped.push(network.msgs_dropped());
+        }
+
+        assert!(dropped[0] > 0);
+        // the same msgs are dropped with the same seed, though the queries are sent concurrently
+        assert_eq!(dropped[0], dropped[1]);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn clients_follow_section_splits_and_elder_changes() -> Result<()> {
+        init_logger();
+        let network = MockNetwork::new(0)?;
+        let client = mock_client(&network).await?;
+
+        let bytes = random_bytes(1024);
+        let address = client.upload(bytes.clone(), Scope::Public).await?;
+        let name = xor_name::rand::random();
+        let (register, batch) = client.create_register(name, 10, policy(&client)).await?;
+        client.publish_register_ops(batch, false).await?;
+        assert_eq!(network.msgs_redirected(), 0);
+
+        let children = network
+            .split_section(Prefix::default())
+            .await
+            .ok_or_else(|| eyre!("no root section to split"))?;
+        assert_eq!(network.sections().await, children.to_vec());
+        assert!(network.split_section(Prefix::default()).await.is_none());
+
+        // msgs sent to the Elders of the genesis section are bounced by AE, and sent again to
+        // the Elders of the child responsible for their destination
+        assert_eq!(client.read_bytes(address).await?, bytes);
+        let (_, batch) = client
+            .write_to_register(register, b"after split".to_vec(), BTreeSet::new())
+            .await?;
+        client.publish_register_ops(batch, false).await?;
+        let redirected = network.msgs_redirected();
+        assert!(redirected > 0);
+        let child = children
+            .iter()
+            .find(|child| child.prefix.matches(register.name()))
+            .ok_or_else(|| eyre!("no child responsible for the register"))?;
+        assert!(client.is_current_section_key(&child.key));
+
+        // the new Elders are learnt about the same way
+        let section = network
+            .churn_elders(network.sections().await[0].prefix)
+            .await
+            .ok_or_else(|| eyre!("no section to change the Elders of"))?;
+        assert!(!network
+            .sections()
+            .await
+            .iter()
+            .any(|s| s.key == children[0].key));
+        let name = loop {
+            let name: XorName = xor_name::rand::random();
+            if section.prefix.matches(&name) {
+                break name;
+            }
+        };
+        let (_, batch) = client.create_register(name, 10, policy(&client)).await?;
+        client.publish_register_ops(batch, false).await?;
+        assert!(network.msgs_redirected() > redirected);
+        assert!(client.is_current_section_key(&section.key));
+        assert_eq!(client.read_register(register).await?.len(), 1);
+
+        Ok(())
+    }
+
+    fn policy(client: &Client) -> Policy {
+        Policy::Public(PublicPolicy {
+            owner: User::Key(client.public_key()),
+            permissions: BTreeMap::new(),
+        })
+    }
+}
@@ -0,0 +1,222 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::DEFAULT_OPERATION_TIMEOUT;
+use serde::{Deserialize, Serialize};
+use sn_interface::messaging::data::DataQuery;
+use std::{collections::BTreeMap, str::FromStr, sync::Mutex};
+use tokio::time::Duration;
+
+// Chunks whose content, as recorded in their data map, is up to this size are small
+const SMALL_CHUNK_MAX_SIZE: usize = 64 * 1024;
+// Chunks whose content is up to this size are medium, the larger ones, up to ~1MB, being large
+const MEDIUM_CHUNK_MAX_SIZE: usize = 512 * 1024;
+
+/// Size class of the data a query fetches, which its timeout is picked by, from the
+/// [`QueryTimeouts`] of the client.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum QuerySizeClass {
+    /// Queries of registers, spentbooks or sections, and of small chunks.
+    Small,
+    /// Queries of chunks of unknown size, e.g. head chunks, and of mid-sized chunks.
+    Medium,
+    /// Queries of chunks of up to ~1MB, as most of the ones large files are stored in.
+    Large,
+}
+
+impl QuerySizeClass {
+    /// Class of the query of a chunk whose content is of the given size, as recorded in the
+    /// data map of the file it's part of.
+    pub fn of_chunk(size: usize) -> Self {
+        if size <= SMALL_CHUNK_MAX_SIZE {
+            Self::Small
+        } else if size <= MEDIUM_CHUNK_MAX_SIZE {
+            Self::Medium
+        } else {
+            Self::Large
+        }
+    }
+}
+
+impl From<&DataQuery> for QuerySizeClass {
+    /// Class of a query whose size can't be estimated any better than by its type.
+    fn from(query: &DataQuery) -> Self {
+        match query {
+            DataQuery::GetChunk(_) => Self::Medium,
+            DataQuery::ReportMissingChunk(_)
+            | DataQuery::Register(_)
+            | DataQuery::Spentbook(_)
+            | DataQuery::GetSectionStats(_)
+            | DataQuery::GetSectionKeyHistory { .. }
+            | DataQuery::GetSectionMembers { .. } => Self::Small,
+        }
+    }
+}
+
+/// Time to wait for the response to a query of each [`QuerySizeClass`], including all its
+/// retries, before giving up and returning an error.
+///
+/// Small queries fail faster than large ones, rather than all of them waiting as long as a
+/// chunk of ~1MB may take to be fetched.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct QueryTimeouts {
+    /// Timeout of small queries.
+    pub small: Duration,
+    /// Timeout of medium queries.
+    pub medium: Duration,
+    /// Timeout of large queries.
+    pub large: Duration,
+}
+
+impl QueryTimeouts {
+    /// Timeouts derived from the given one, which large queries are given, medium and small
+    /// ones being given a half and a quarter of it respectively.
+    pub fn scaled(large: Duration) -> Self {
+        Self {
+            small: large / 4,
+            medium: large / 2,
+            large,
+        }
+    }
+
+    /// Timeouts of a client configured with the given query timeout. The default one is scaled,
+    /// but any other was set explicitly, so all the queries are given it, rather than the
+    /// smaller ones being given a fraction of it.
+    pub fn from_query_timeout(query_timeout: Duration) -> Self {
+        if query_timeout == DEFAULT_OPERATION_TIMEOUT {
+            Self::scaled(query_timeout)
+        } else {
+            Self {
+                small: query_timeout,
+                medium: query_timeout,
+                large: query_timeout,
+            }
+        }
+    }
+
+    /// Timeout of the queries of the given class.
+    pub fn get(&self, class: QuerySizeClass) -> Duration {
+        match class {
+            QuerySizeClass::Small => self.small,
+            QuerySizeClass::Medium => self.medium,
+            QuerySizeClass::Large => self.large,
+        }
+    }
+}
+
+impl Default for QueryTimeouts {
+    fn default() -> Self {
+        Self::scaled(DEFAULT_OPERATION_TIMEOUT)
+    }
+}
+
+impl FromStr for QueryTimeouts {
+    type Err = String;
+
+    /// Parses the comma-separated timeouts of small, medium and large queries, in seconds,
+    /// e.g. `30,60,120`.
+    fn from_str(timeouts: &str) -> Result<Self, Self::Err> {
+        let timeouts = timeouts
+            .split(',')
+            .map(|timeout| timeout.trim().parse().map(Duration::from_secs))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| err.to_string())?;
+        match timeouts[..] {
+            [small, medium, large] => Ok(Self {
+                small,
+                medium,
+                large,
+            }),
+            _ => Err(format!(
+                "expected the timeouts of small, medium and large queries, got {} timeouts",
+                timeouts.len()
+            )),
+        }
+    }
+}
+
+/// How the queries of a [`QuerySizeClass`] sent by a client went, telling whether their
+/// timeout fits them: failed attempts are retried within the timeout, until it elapses.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct QueryTimeoutStats {
+    /// Number of queries sent.
+    pub queries: u64,
+    /// Number of queries which got no response within their timeout, after all their attempts.
+    pub timed_out: u64,
+    /// Number of attempts which got no response within their share of the timeout.
+    pub attempts_elapsed: u64,
+    /// Number of attempts which failed as the connections to the Elders were lost.
+    pub attempts_disconnected: u64,
+    /// Number of attempts which failed with any other error.
+    pub attempts_failed: u64,
+}
+
+/// Records the [`QueryTimeoutStats`] of a client, and the copies sharing them.
+#[derive(Debug, Default)]
+pub(crate) struct QueryTimeoutMetrics(Mutex<BTreeMap<QuerySizeClass, QueryTimeoutStats>>);
+
+impl QueryTimeoutMetrics {
+    pub(crate) fn record(
+        &self,
+        class: QuerySizeClass,
+        update: impl FnOnce(&mut QueryTimeoutStats),
+    ) {
+        let mut stats = self
+            .0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        update(stats.entry(class).or_default())
+    }
+
+    pub(crate) fn stats(&self) -> BTreeMap<QuerySizeClass, QueryTimeoutStats> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::{eyre, Result};
+    use sn_interface::types::ChunkAddress;
+
+    #[test]
+    fn queries_are_given_the_timeout_of_their_size_class() -> Result<()> {
+        assert_eq!(QuerySizeClass::of_chunk(1024), QuerySizeClass::Small);
+        assert_eq!(QuerySizeClass::of_chunk(256 * 1024), QuerySizeClass::Medium);
+        assert_eq!(QuerySizeClass::of_chunk(1024 * 1024), QuerySizeClass::Large);
+        assert_eq!(
+            QuerySizeClass::from(&DataQuery::GetChunk(ChunkAddress(xor_name::rand::random()))),
+            QuerySizeClass::Medium
+        );
+
+        let timeouts: QueryTimeouts = "10, 20,40".parse().map_err(|err: String| eyre!(err))?;
+        assert_eq!(timeouts, QueryTimeouts::scaled(Duration::from_secs(40)));
+        assert_eq!(timeouts.get(QuerySizeClass::Small), Duration::from_secs(10));
+        assert_eq!(
+            QueryTimeouts::default().get(QuerySizeClass::Large),
+            DEFAULT_OPERATION_TIMEOUT
+        );
+        assert_eq!(
+            QueryTimeouts::from_query_timeout(DEFAULT_OPERATION_TIMEOUT),
+            QueryTimeouts::default()
+        );
+        // a configured timeout isn't shortened for smaller queries
+        let configured = DEFAULT_OPERATION_TIMEOUT * 2;
+        assert_eq!(
+            QueryTimeouts::from_query_timeout(configured).get(QuerySizeClass::Small),
+            configured
+        );
+
+        assert!("10,20".parse::<QueryTimeouts>().is_err());
+        assert!("10,20,long".parse::<QueryTimeouts>().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::Result;
+use async_trait::async_trait;
+use sn_interface::types::{Keypair, PublicKey, Signature};
+use std::fmt::Debug;
+
+/// Signs everything a [`Client`] sends to the network on behalf of its user.
+///
+/// By default the client signs with its own [`Keypair`], but a signer which never exposes
+/// the secret key to the client, e.g. one delegating to a remote service, can be set instead.
+///
+/// [`Client`]: crate::Client
+#[async_trait]
+pub trait Signer: Debug + Send + Sync {
+    /// Public key the signatures can be verified with.
+    fn public_key(&self) -> PublicKey;
+
+    /// Sign the given data.
+    async fn sign(&self, data: &[u8]) -> Result<Signature>;
+}
+
+#[async_trait]
+impl Signer for Keypair {
+    fn public_key(&self) -> PublicKey {
+        Keypair::public_key(self)
+    }
+
+    async fn sign(&self, data: &[u8]) -> Result<Signature> {
+        Ok(Keypair::sign(self, data))
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::Throughput;
+use sn_interface::messaging::data::{DataCmd, DataQuery};
+use std::fmt::Debug;
+use tokio::time::Duration;
+use xor_name::Prefix;
+
+/// Receiver of the [`TelemetryEvent`]s emitted by a [`Client`] for each operation it
+/// sends to the network, so apps can feed the client performance into their own monitoring.
+///
+/// Events are reported synchronously from the task performing the operation,
+/// thus implementations are expected to return quickly, e.g. by queuing the event.
+///
+/// [`Client`]: crate::Client
+pub trait Telemetry: Debug + Send + Sync {
+    /// Called once an operation has completed, whether it succeeded or not.
+    fn on_event(&self, event: TelemetryEvent);
+
+    /// Called when the state of the client connections to the network changes,
+    /// i.e. when they were lost and the client is reconnecting.
+    fn on_connection_state(&self, _state: ConnectionState) {}
+}
+
+/// State of the connections of a [`Client`] to the Elders it sends operations to.
+///
+/// [`Client`]: crate::Client
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConnectionState {
+    /// The connections to the Elders were lost while sending an operation.
+    Disconnected,
+    /// Reconnecting to the Elders, with the number of this attempt, starting at 1.
+    Reconnecting {
+        /// Number of this attempt
+        attempt: usize,
+    },
+    /// The connections to the Elders were re-established, in-flight queries are retried.
+    Reconnected,
+    /// All attempts to reconnect failed, the operation which lost its connections fails.
+    ReconnectFailed,
+}
+
+/// Type of operation a [`TelemetryEvent`] is about.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum OperationKind {
+    /// Read of a Chunk.
+    ChunkRead,
+    /// Write of a Chunk.
+    ChunkWrite,
+    /// Report of a Chunk which couldn't be read, for it to be repaired.
+    MissingChunkReport,
+    /// Read of a Register.
+    RegisterRead,
+    /// Write of a Register.
+    RegisterWrite,
+    /// Read of a Spentbook.
+    SpentbookRead,
+    /// Write of a Spentbook.
+    SpentbookWrite,
+    /// Read of the statistics of a section.
+    SectionStatsRead,
+    /// Read of the history of the keys of a section.
+    SectionKeyHistoryRead,
+    /// Read of the members of a section.
+    SectionMembersRead,
+}
+
+impl From<&DataQuery> for OperationKind {
+    fn from(query: &DataQuery) -> Self {
+        match query {
+            DataQuery::GetChunk(_) => Self::ChunkRead,
+            DataQuery::ReportMissingChunk(_) => Self::MissingChunkReport,
+            DataQuery::Register(_) => Self::RegisterRead,
+            DataQuery::Spentbook(_) => Self::SpentbookRead,
+            DataQuery::GetSectionStats(_) => Self::SectionStatsRead,
+            DataQuery::GetSectionKeyHistory { .. } => Self::SectionKeyHistoryRead,
+            DataQuery::GetSectionMembers { .. } => Self::SectionMembersRead,
+        }
+    }
+}
+
+impl From<&DataCmd> for OperationKind {
+    fn from(cmd: &DataCmd) -> Self {
+        match cmd {
+            DataCmd::StoreChunk(_) | DataCmd::StorePaidChunk { .. } => Self::ChunkWrite,
+            DataCmd::Register(_) => Self::RegisterWrite,
+            DataCmd::Spentbook(_) => Self::SpentbookWrite,
+        }
+    }
+}
+
+/// Final outcome of an operation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    /// The operation succeeded.
+    Success,
+    /// The operation failed, with the description of its last error.
+    Failure(String),
+}
+
+/// Structured information about an operation sent to the network.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TelemetryEvent {
+    /// Type of operation.
+    pub operation: OperationKind,
+    /// Prefix of the section the operation was sent to, if it was known to the client.
+    pub section_prefix: Option<Prefix>,
+    /// Total time taken by the operation, including all its retries.
+    pub elapsed: Duration,
+    /// Number of times the operation was retried after its first attempt.
+    pub retries: usize,
+    /// Outcome of the operation.
+    pub outcome: Outcome,
+    /// Throughput of the client when the operation completed, across all its operations.
+    pub throughput: Throughput,
+}
@@ -16,12 +16,12 @@ use dirs_next::home_dir;
 use eyre::{eyre, Context, Result};
 #[cfg(test)]
 pub use sn_interface::init_logger;
-use sn_interface::types::PublicKey;
+use sn_interface::network_knowledge::NodeConfig;
 #[cfg(test)]
 use std::future::Future;
 #[cfg(test)]
 use std::time::Duration;
-use std::{collections::BTreeSet, fs::File, io::BufReader, net::SocketAddr, path::Path};
+use std::{collections::BTreeSet, fs, net::SocketAddr, path::Path};
 #[cfg(test)]
 pub use test_client::{
     create_test_client, create_test_client_with, get_dbc_owner_from_secret_key_hex,
@@ -80,26 +80,20 @@ pub fn read_network_conn_info() -> Result<(bls::PublicKey, BTreeSet<SocketAddr>)
     let user_dir = home_dir().ok_or_else(|| eyre!("Could not fetch home directory"))?;
     let conn_info_path = user_dir.join(Path::new(GENESIS_CONN_INFO_FILEPATH));
 
-    let file = File::open(&conn_info_path).with_context(|| {
+    let bytes = fs::read(&conn_info_path).with_context(|| {
         format!(
             "Failed to open node connection information file at '{}'",
             conn_info_path.display(),
         )
     })?;
-    let reader = BufReader::new(file);
-    let (genesis_key_hex, bootstrap_nodes): (String, BTreeSet<SocketAddr>) =
-        serde_json::from_reader(reader).with_context(|| {
-            format!(
-                "Failed to parse content of node connection information file at '{}'",
-                conn_info_path.display(),
-            )
-        })?;
-
-    let genesis_key = PublicKey::bls_from_hex(&genesis_key_hex)?
-        .bls()
-        .ok_or_else(|| eyre!("Unexpectedly failed to obtain (BLS) genesis key."))?;
+    let node_config = NodeConfig::from_json(&bytes).with_context(|| {
+        format!(
+            "Failed to parse content of node connection information file at '{}'",
+            conn_info_path.display(),
+        )
+    })?;
 
-    Ok((genesis_key, bootstrap_nodes))
+    Ok((node_config.genesis_key, node_config.contacts))
 }
 
 #[cfg(test)]
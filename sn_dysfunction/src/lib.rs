@@ -45,19 +45,22 @@ extern crate tracing;
 
 mod detection;
 mod error;
+mod signals;
 
 use xor_name::XorName;
 
 use crate::error::Result;
 use dashmap::DashMap;
+use signals::SignalScores;
 use sn_interface::messaging::data::OperationId;
-use std::collections::{BTreeSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::RwLock;
 
 pub use crate::error::Error;
 pub use detection::{DysfunctionSeverity, IssueType};
+pub use signals::{PolicyAction, Signal, SignalPolicy};
 
 /// Some reproducible xorname derived from the operation. This is a permanent reference needed for logging all dysfunction.
 type NodeIdentifier = XorName;
@@ -76,6 +79,10 @@ pub struct DysfunctionDetection {
     /// operation ID.
     pub unfulfilled_ops: Arc<DashMap<NodeIdentifier, Arc<RwLock<Vec<OperationId>>>>>, // OperationId = [u8; 32]
     adults: Arc<RwLock<Vec<XorName>>>,
+    // Weighted signals logged against a node, scored with decay
+    signals: SignalScores,
+    // When each of the pending request operations started, to tell slow responses
+    pending_since: Arc<DashMap<(NodeIdentifier, OperationId), Instant>>,
 }
 
 impl DysfunctionDetection {
@@ -86,9 +93,37 @@ impl DysfunctionDetection {
             knowledge_issues: Arc::new(DashMap::new()),
             unfulfilled_ops: Arc::new(DashMap::new()),
             adults: Arc::new(RwLock::new(our_adults)),
+            signals: SignalScores::new(SignalPolicy::default()),
+            pending_since: Arc::new(DashMap::new()),
         }
     }
 
+    /// Uses the given policy to weigh and score the signals, instead of the default one.
+    pub fn with_signal_policy(mut self, policy: SignalPolicy) -> Self {
+        self.signals = SignalScores::new(policy);
+        self
+    }
+
+    /// Adds a signal of misbehaviour to the score of the node.
+    pub async fn track_signal(&self, node_id: NodeIdentifier, signal: Signal) {
+        debug!("Adding a new signal to the dysfunction tracker: {signal:?} against {node_id}");
+        self.signals.record(node_id, signal, Instant::now()).await
+    }
+
+    /// Gets the current score of each of the nodes signals were tracked against lately.
+    pub async fn signal_scores(&self) -> BTreeMap<NodeIdentifier, f32> {
+        self.signals.scores(Instant::now()).await
+    }
+
+    /// Gets the action to take against each of the nodes whose score crossed a threshold of
+    /// the signal policy. Only the given `elders` can be demoted.
+    pub async fn get_policy_actions(
+        &self,
+        elders: &BTreeSet<NodeIdentifier>,
+    ) -> BTreeMap<NodeIdentifier, PolicyAction> {
+        self.signals.actions(elders, Instant::now()).await
+    }
+
     /// Adds an issue to the dysfunction tracker.
     ///
     /// The `op_id` only applies when adding an operational issue.
@@ -116,6 +151,7 @@ impl DysfunctionDetection {
                 })?;
                 debug!("New issue has associated operation ID: {op_id:#?}");
                 v.write().await.push(op_id);
+                let _ = self.pending_since.insert((node_id, op_id), Instant::now());
             }
         }
         Ok(())
@@ -147,6 +183,7 @@ impl DysfunctionDetection {
                 }
             });
             if has_removed {
+                self.track_response_time(node_id, operation_id).await;
                 trace!(
                     "Pending operation removed for node: {:?} op: {:?}",
                     node_id,
@@ -163,6 +200,16 @@ impl DysfunctionDetection {
         has_removed
     }
 
+    // Tracks a slow response signal against the node if it took too long to fulfil the operation
+    async fn track_response_time(&self, node_id: &NodeIdentifier, operation_id: OperationId) {
+        if let Some((_, since)) = self.pending_since.remove(&(*node_id, operation_id)) {
+            let slow_after = self.signals.policy().slow_query_response_after;
+            if since.elapsed() >= slow_after {
+                self.track_signal(*node_id, Signal::SlowQueryResponse).await;
+            }
+        }
+    }
+
     /// Gets the unfulfilled operation IDs for a given node.
     ///
     /// This is for convenience, to wrap reading the concurrent data structure that stores the
@@ -209,6 +256,9 @@ impl DysfunctionDetection {
             let _ = self.knowledge_issues.remove(node);
             let _ = self.unfulfilled_ops.remove(node);
         }
+        self.pending_since
+            .retain(|(node, _), _| current_members.contains(node));
+        self.signals.retain(&current_members).await;
     }
 }
 
@@ -226,11 +276,12 @@ pub(crate) fn get_mean_of(data: &[f32]) -> Option<f32> {
 
 #[cfg(test)]
 mod tests {
-    use super::{DysfunctionDetection, IssueType};
+    use super::{DysfunctionDetection, IssueType, PolicyAction, Signal, SignalPolicy};
     use eyre::Error;
     use sn_interface::messaging::data::OperationId;
-    use std::collections::BTreeSet;
+    use std::collections::{BTreeMap, BTreeSet};
     use std::sync::Once;
+    use std::time::Duration;
     use xor_name::{rand::random as random_xorname, XorName};
 
     type Result<T, E = Error> = std::result::Result<T, E>;
@@ -495,6 +546,46 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn slow_responses_add_up_to_policy_actions() -> Result<()> {
+        let adults = (0..10).map(|_| random_xorname()).collect::<Vec<XorName>>();
+        let policy = SignalPolicy {
+            slow_query_response_after: Duration::ZERO,
+            slow_query_response_weight: 5.0,
+            ..SignalPolicy::default()
+        };
+        let dysfunctional_detection =
+            DysfunctionDetection::new(adults.clone()).with_signal_policy(policy);
+
+        for i in 0..2 {
+            let op_id = OperationId([i; 32]);
+            dysfunctional_detection
+                .track_issue(adults[0], IssueType::PendingRequestOperation(Some(op_id)))
+                .await?;
+            assert!(
+                dysfunctional_detection
+                    .request_operation_fulfilled(&adults[0], op_id)
+                    .await
+            );
+        }
+        dysfunctional_detection
+            .track_signal(adults[1], Signal::InvalidSignature)
+            .await;
+
+        let elders = BTreeSet::from([adults[0]]);
+        assert_eq!(
+            dysfunctional_detection.get_policy_actions(&elders).await,
+            BTreeMap::from([(adults[0], PolicyAction::Demote)])
+        );
+
+        dysfunctional_detection
+            .retain_members_only(adults[1..].iter().copied().collect())
+            .await;
+        let scores = dysfunctional_detection.signal_scores().await;
+        assert_eq!(scores.keys().collect::<Vec<_>>(), [&adults[1]]);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn request_operation_fulfilled_should_return_false_for_node_with_no_ops() -> Result<()> {
         let adults = (0..10).map(|_| random_xorname()).collect::<Vec<XorName>>();
@@ -0,0 +1,288 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::NodeIdentifier;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::RwLock;
+
+// Scores decayed below this are forgotten
+const NEGLIGIBLE_SCORE: f32 = 0.01;
+
+/// Signals of a node misbehaving, each adding its weight to the dysfunction score of the node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Signal {
+    /// The node didn't reply to an AE msg or keep-alive ping we sent it
+    MissedAeReply,
+    /// The node responded to a data query, but too slowly
+    SlowQueryResponse,
+    /// The node didn't confirm storing data it was told to store
+    FailedStorageProbe,
+    /// The node sent a msg with an invalid signature
+    InvalidSignature,
+}
+
+/// Action the dysfunction score of a node calls for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PolicyAction {
+    /// Demote the Elder, for another node of the section to take its place
+    Demote,
+    /// Vote the node offline
+    Offline,
+}
+
+/// Weights of the signals, how fast the scores they add up to decay, and the scores from which
+/// action is taken against a node.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignalPolicy {
+    /// Weight of a `Signal::MissedAeReply`
+    pub missed_ae_reply_weight: f32,
+    /// Weight of a `Signal::SlowQueryResponse`
+    pub slow_query_response_weight: f32,
+    /// Weight of a `Signal::FailedStorageProbe`
+    pub failed_storage_probe_weight: f32,
+    /// Weight of a `Signal::InvalidSignature`
+    pub invalid_signature_weight: f32,
+    /// How long it takes a score to decay to half of it
+    pub half_life: Duration,
+    /// How long a node can take to respond to a data query before its response is deemed slow
+    pub slow_query_response_after: Duration,
+    /// Score from which an Elder is demoted
+    pub demotion_threshold: f32,
+    /// Score from which a node is voted offline
+    pub offline_threshold: f32,
+}
+
+impl Default for SignalPolicy {
+    fn default() -> Self {
+        Self {
+            missed_ae_reply_weight: 1.0,
+            slow_query_response_weight: 0.5,
+            failed_storage_probe_weight: 2.0,
+            invalid_signature_weight: 5.0,
+            half_life: Duration::from_secs(10 * 60),
+            slow_query_response_after: Duration::from_secs(5),
+            demotion_threshold: 10.0,
+            offline_threshold: 20.0,
+        }
+    }
+}
+
+impl SignalPolicy {
+    /// Weight the signal adds to the score of a node.
+    pub fn weight(&self, signal: Signal) -> f32 {
+        match signal {
+            Signal::MissedAeReply => self.missed_ae_reply_weight,
+            Signal::SlowQueryResponse => self.slow_query_response_weight,
+            Signal::FailedStorageProbe => self.failed_storage_probe_weight,
+            Signal::InvalidSignature => self.invalid_signature_weight,
+        }
+    }
+
+    /// Action the score of a node calls for, if any. Only Elders are demoted.
+    pub fn action(&self, score: f32, is_elder: bool) -> Option<PolicyAction> {
+        if score >= self.offline_threshold {
+            Some(PolicyAction::Offline)
+        } else if is_elder && score >= self.demotion_threshold {
+            Some(PolicyAction::Demote)
+        } else {
+            None
+        }
+    }
+
+    fn decay(&self, score: f32, elapsed: Duration) -> f32 {
+        if self.half_life.is_zero() {
+            return 0.0;
+        }
+        score * 0.5_f32.powf(elapsed.as_secs_f32() / self.half_life.as_secs_f32())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct DecayingScore {
+    value: f32,
+    updated: Instant,
+}
+
+/// Scores of the signals recorded against each node, decaying over time so only recent
+/// misbehaviour adds up to action being taken.
+#[derive(Clone, Debug)]
+pub(crate) struct SignalScores {
+    policy: SignalPolicy,
+    scores: Arc<RwLock<BTreeMap<NodeIdentifier, DecayingScore>>>,
+}
+
+impl SignalScores {
+    pub(crate) fn new(policy: SignalPolicy) -> Self {
+        Self {
+            policy,
+            scores: Arc::new(RwLock::new(BTreeMap::new())),
+        }
+    }
+
+    pub(crate) fn policy(&self) -> &SignalPolicy {
+        &self.policy
+    }
+
+    /// Adds the weight of the signal to the score of the node.
+    pub(crate) async fn record(&self, node: NodeIdentifier, signal: Signal, now: Instant) {
+        let weight = self.policy.weight(signal);
+        let mut scores = self.scores.write().await;
+        let score = scores.entry(node).or_insert(DecayingScore {
+            value: 0.0,
+            updated: now,
+        });
+        score.value = self
+            .policy
+            .decay(score.value, now.saturating_duration_since(score.updated))
+            + weight;
+        score.updated = now;
+        trace!(
+            "Recorded {signal:?} against {node}, its score is now {}",
+            score.value
+        );
+    }
+
+    /// Current scores of the nodes signals were recorded against lately.
+    pub(crate) async fn scores(&self, now: Instant) -> BTreeMap<NodeIdentifier, f32> {
+        let mut scores = self.scores.write().await;
+        let decayed: BTreeMap<_, _> = scores
+            .iter()
+            .map(|(node, score)| {
+                let elapsed = now.saturating_duration_since(score.updated);
+                (*node, self.policy.decay(score.value, elapsed))
+            })
+            .filter(|(_, score)| *score >= NEGLIGIBLE_SCORE)
+            .collect();
+        scores.retain(|node, _| decayed.contains_key(node));
+        decayed
+    }
+
+    /// Actions the current scores call for, against any of the nodes.
+    pub(crate) async fn actions(
+        &self,
+        elders: &BTreeSet<NodeIdentifier>,
+        now: Instant,
+    ) -> BTreeMap<NodeIdentifier, PolicyAction> {
+        self.scores(now)
+            .await
+            .into_iter()
+            .filter_map(|(node, score)| {
+                let action = self.policy.action(score, elders.contains(&node))?;
+                Some((node, action))
+            })
+            .collect()
+    }
+
+    /// Forgets the scores of the nodes which are not members anymore.
+    pub(crate) async fn retain(&self, members: &BTreeSet<NodeIdentifier>) {
+        self.scores
+            .write()
+            .await
+            .retain(|node, _| members.contains(node));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xor_name::rand::random as random_xorname;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    fn test_policy() -> SignalPolicy {
+        SignalPolicy {
+            half_life: secs(60),
+            ..SignalPolicy::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn repeated_signals_add_up_to_an_offline_vote() {
+        let scores = SignalScores::new(test_policy());
+        let (node, other) = (random_xorname(), random_xorname());
+        let start = Instant::now();
+
+        // one missed reply after another, faster than they decay
+        for i in 0..19 {
+            scores
+                .record(node, Signal::MissedAeReply, start + secs(i))
+                .await;
+        }
+        scores
+            .record(other, Signal::SlowQueryResponse, start + secs(18))
+            .await;
+        let elders = BTreeSet::new();
+        assert!(scores.actions(&elders, start + secs(18)).await.is_empty());
+
+        scores
+            .record(node, Signal::InvalidSignature, start + secs(19))
+            .await;
+        assert_eq!(
+            scores.actions(&elders, start + secs(19)).await,
+            BTreeMap::from([(node, PolicyAction::Offline)])
+        );
+    }
+
+    #[tokio::test]
+    async fn only_elders_are_demoted() {
+        let scores = SignalScores::new(test_policy());
+        let (elder, adult) = (random_xorname(), random_xorname());
+        let start = Instant::now();
+
+        for node in [elder, adult] {
+            for _ in 0..3 {
+                scores.record(node, Signal::FailedStorageProbe, start).await;
+            }
+            scores.record(node, Signal::InvalidSignature, start).await;
+        }
+
+        let actions = scores.actions(&BTreeSet::from([elder]), start).await;
+        assert_eq!(actions, BTreeMap::from([(elder, PolicyAction::Demote)]));
+    }
+
+    #[tokio::test]
+    async fn scores_decay_until_forgotten() {
+        let scores = SignalScores::new(test_policy());
+        let node = random_xorname();
+        let start = Instant::now();
+
+        for _ in 0..4 {
+            scores.record(node, Signal::InvalidSignature, start).await;
+        }
+        let elders = BTreeSet::from([node]);
+        assert_eq!(
+            scores.actions(&elders, start).await,
+            BTreeMap::from([(node, PolicyAction::Offline)])
+        );
+
+        // halved after a half-life, which is only enough to be demoted
+        let score = scores.scores(start + secs(60)).await[&node];
+        assert!((score - 10.0).abs() < 0.01, "unexpected score {}", score);
+        assert_eq!(
+            scores.actions(&elders, start + secs(60)).await,
+            BTreeMap::from([(node, PolicyAction::Demote)])
+        );
+
+        // a signal adds to what's left of the score
+        scores
+            .record(node, Signal::InvalidSignature, start + secs(120))
+            .await;
+        let score = scores.scores(start + secs(120)).await[&node];
+        assert!((score - 10.0).abs() < 0.01, "unexpected score {}", score);
+
+        assert!(scores.scores(start + secs(3600)).await.is_empty());
+        assert!(scores.scores.read().await.is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use sn_interface::messaging::{
+    data::{DataCmd, ServiceMsg},
+    signature_aggregator::SignatureAggregator,
+    system::SigShare,
+    AuthKind, DstLocation, MsgId, ServiceAuth, SigningDomain, WireMsg,
+};
+use sn_interface::types::{
+    register::{Register, RegisterOp, User},
+    utils::random_bytes,
+    Chunk, Keypair,
+};
+use std::collections::BTreeSet;
+use tokio::runtime::Runtime;
+use xor_name::XorName;
+
+// Sizes of the payloads msgs are benched with, from a small cmd to a full Chunk
+const PAYLOAD_SIZES: [usize; 3] = [1024, 100 * 1024, 1024 * 1024];
+
+// Number of Elders whose signature shares are accumulated
+const ELDER_COUNT: usize = 7;
+
+// A WireMsg of a client storing a Chunk of the given size
+fn chunk_wire_msg(size: usize) -> WireMsg {
+    let keypair = Keypair::new_ed25519();
+    let msg = ServiceMsg::Cmd(DataCmd::StoreChunk(Chunk::new(random_bytes(size))));
+    let payload = WireMsg::serialize_msg_payload(&msg).expect("failed to serialise msg");
+    let auth = ServiceAuth {
+        public_key: keypair.public_key(),
+        signature: keypair.sign(&SigningDomain::Service.signable_bytes(&payload)),
+    };
+    let dst_location = DstLocation::section(
+        xor_name::rand::random(),
+        bls::SecretKey::random().public_key(),
+    );
+
+    WireMsg::new_msg(MsgId::new(), payload, AuthKind::Service(auth), dst_location)
+        .expect("failed to create wire msg")
+}
+
+fn bench_wire_msg(c: &mut Criterion) {
+    let mut group = c.benchmark_group("wire_msg");
+
+    for size in PAYLOAD_SIZES {
+        let wire_msg = chunk_wire_msg(size);
+        let bytes = wire_msg.serialize().expect("failed to serialise wire msg");
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+
+        group.bench_with_input(
+            BenchmarkId::new("encode", size),
+            &wire_msg,
+            |b, wire_msg| b.iter(|| wire_msg.serialize().expect("failed to serialise wire msg")),
+        );
+        group.bench_with_input(BenchmarkId::new("decode", size), &bytes, |b, bytes| {
+            b.iter(|| {
+                WireMsg::from(bytes.clone())
+                    .and_then(|wire_msg| wire_msg.into_msg())
+                    .expect("failed to deserialise wire msg")
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_signing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("signing");
+
+    let secret_key_set = bls::SecretKeySet::random(ELDER_COUNT / 2, &mut rand::thread_rng());
+    let keypairs = [
+        ("ed25519", Keypair::new_ed25519()),
+        ("bls", Keypair::new_bls()),
+        (
+            "bls_share",
+            Keypair::new_bls_share(
+                0,
+                secret_key_set.secret_key_share(0),
+                secret_key_set.public_keys(),
+            ),
+        ),
+    ];
+
+    for size in PAYLOAD_SIZES {
+        let payload = SigningDomain::Service.signable_bytes(random_bytes(size));
+        group.throughput(Throughput::Bytes(payload.len() as u64));
+
+        for (key_type, keypair) in &keypairs {
+            let public_key = keypair.public_key();
+            let signature = keypair.sign(&payload);
+
+            group.bench_with_input(
+                BenchmarkId::new(format!("sign/{}", key_type), size),
+                &payload,
+                |b, payload| b.iter(|| keypair.sign(payload)),
+            );
+            group.bench_with_input(
+                BenchmarkId::new(format!("verify/{}", key_type), size),
+                &payload,
+                |b, payload| {
+                    b.iter(|| {
+                        public_key
+                            .verify(&signature, payload)
+                            .expect("invalid signature")
+                    })
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+fn bench_accumulation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("accumulation");
+    let runtime = Runtime::new().expect("failed to create runtime");
+
+    let secret_key_set = bls::SecretKeySet::random(ELDER_COUNT / 2, &mut rand::thread_rng());
+    let payload = random_bytes(1024);
+    let sig_shares: Vec<SigShare> = (0..ELDER_COUNT)
+        .map(|index| {
+            SigShare::new(
+                secret_key_set.public_keys(),
+                index,
+                &secret_key_set.secret_key_share(index),
+                &payload,
+            )
+        })
+        .collect();
+
+    // the shares of all the Elders are added, the signature being aggregated
+    // once enough of them were
+    group.bench_function(BenchmarkId::new("sig_shares", ELDER_COUNT), |b| {
+        b.to_async(&runtime).iter_batched(
+            SignatureAggregator::default,
+            |aggregator| async {
+                let mut aggregated = 0;
+                for sig_share in &sig_shares {
+                    if aggregator.add(&payload, sig_share.clone()).await.is_ok() {
+                        aggregated += 1;
+                    }
+                }
+                assert_eq!(aggregated, 1);
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+// A Register replica the owner wrote the given number of entries to, along with the ops written
+fn written_register(
+    owner: &Keypair,
+    name: XorName,
+    writes: usize,
+) -> (Register, Vec<RegisterOp<Vec<u8>>>) {
+    let authority = User::Key(owner.public_key());
+    let mut register = Register::new_public(authority, name, 15_000, None, u16::MAX);
+
+    let mut ops = Vec::with_capacity(writes);
+    let mut children = BTreeSet::new();
+    for _ in 0..writes {
+        let (hash, op) = register
+            .write(random_bytes(64).to_vec(), children)
+            .expect("failed to write to register");
+        children = BTreeSet::from([hash]);
+        ops.push(op);
+    }
+
+    (register, ops)
+}
+
+fn bench_register_merge(c: &mut Criterion) {
+    let mut group = c.benchmark_group("register_merge");
+
+    let owner = Keypair::new_ed25519();
+    let name = xor_name::rand::random();
+    let (replica, _) = written_register(&owner, name, 0);
+
+    for writes in [10, 100, 1_000] {
+        let (_, ops) = written_register(&owner, name, writes);
+        group.throughput(Throughput::Elements(writes as u64));
+
+        // the ops of another replica are applied to ours, as when replicas are merged
+        group.bench_with_input(BenchmarkId::new("apply_ops", writes), &ops, |b, ops| {
+            b.iter_batched(
+                || replica.clone(),
+                |mut replica| {
+                    for op in ops {
+                        replica.apply_op(op.clone()).expect("failed to apply op");
+                    }
+                    replica
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_wire_msg,
+    bench_signing,
+    bench_accumulation,
+    bench_register_merge
+);
+criterion_main!(benches);
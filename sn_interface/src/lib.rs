@@ -54,7 +54,8 @@ pub fn at_least_one_correct_elder_for_sap(sap: Option<SectionAuthorityProvider>)
     }
 }
 
-/// Get the expected chunk copy count for our network.
+/// Get the expected chunk copy count for a new network, i.e. of its genesis section.
+/// Sections then carry their own count in their SAP, which their Elders can agree to change.
 /// Defaults to DEFAULT_DATA_COPY_COUNT, but can be overridden by the env var SN_DATA_COPY_COUNT.
 pub fn data_copy_count() -> usize {
     // if we have an env var for this, lets override
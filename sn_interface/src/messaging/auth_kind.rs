@@ -41,16 +41,15 @@ impl AuthKind {
     /// The src location of the msg.
     pub fn src(&self) -> SrcLocation {
         match self {
-            Self::NodeBlsShare(auth) => SrcLocation::Node {
-                name: auth.src_name,
-                section_pk: auth.sig_share.public_key_set.public_key(),
-            },
-            Self::Node(auth) => SrcLocation::Node {
-                name: crate::types::PublicKey::Ed25519(auth.node_ed_pk).into(),
-                section_pk: auth.section_pk,
-            },
+            Self::NodeBlsShare(auth) => {
+                SrcLocation::node(auth.src_name, auth.sig_share.public_key_set.public_key())
+            }
+            Self::Node(auth) => SrcLocation::node(
+                crate::types::PublicKey::Ed25519(auth.node_ed_pk).into(),
+                auth.section_pk,
+            ),
             #[cfg(any(feature = "chunks", feature = "registers"))]
-            Self::Service(auth) => SrcLocation::EndUser(super::EndUser(auth.public_key.into())),
+            Self::Service(auth) => SrcLocation::end_user(auth.public_key.into()),
         }
     }
 }
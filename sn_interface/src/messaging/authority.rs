@@ -7,8 +7,9 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
+    accept_legacy_msg_signatures,
     system::{KeyedSig, SigShare},
-    Error, Result,
+    Error, Result, SigningDomain,
 };
 use crate::{
     messaging::signature_aggregator::{Error as AggregatorError, SignatureAggregator},
@@ -46,7 +47,8 @@ pub struct NodeAuth {
 }
 
 impl NodeAuth {
-    /// Construct verified node authority by signing a payload.
+    /// Construct verified node authority by signing a message payload.
+    /// The payload is signed within the [`SigningDomain::Node`] domain.
     pub fn authorize(
         section_pk: BlsPublicKey,
         keypair: &EdKeypair,
@@ -55,7 +57,7 @@ impl NodeAuth {
         AuthorityProof(NodeAuth {
             section_pk,
             node_ed_pk: keypair.public,
-            signature: keypair.sign(payload.as_ref()),
+            signature: keypair.sign(&SigningDomain::Node.signable_bytes(payload)),
         })
     }
 }
@@ -82,13 +84,20 @@ pub struct SectionAuth {
 
 impl SectionAuth {
     /// Try to construct verified section authority by aggregating a new share.
+    /// Shares are aggregated over the [`SigningDomain::Section`] domain separated payload,
+    /// unless legacy signatures are accepted and the share was made over the raw payload.
     pub async fn try_authorize(
         aggregator: SignatureAggregator,
         share: BlsShareAuth,
         payload: impl AsRef<[u8]>,
     ) -> Result<AuthorityProof<Self>, AggregatorError> {
+        let mut signed_bytes = SigningDomain::Section.signable_bytes(payload.as_ref());
+        if accept_legacy_msg_signatures() && !share.sig_share.verify(&signed_bytes) {
+            signed_bytes = payload.as_ref().to_vec();
+        }
+
         let sig = aggregator
-            .add(payload.as_ref(), share.sig_share.clone())
+            .add(&signed_bytes, share.sig_share.clone())
             .await?;
 
         if share.sig_share.public_key_set.public_key() != sig.public_key {
@@ -126,6 +135,31 @@ impl<T: VerifyAuthority> AuthorityProof<T> {
         inner.verify_authority(payload).map(Self)
     }
 
+    /// Verify the authority of `inner` over a message payload signed within the given domain.
+    ///
+    /// If legacy signatures are accepted (see [`accept_legacy_msg_signatures`]), a signature
+    /// made over the raw payload is also considered valid.
+    pub fn verify_in_domain(
+        inner: T,
+        domain: SigningDomain,
+        payload: impl AsRef<[u8]>,
+    ) -> Result<Self>
+    where
+        T: Clone,
+    {
+        match Self::verify(inner.clone(), domain.signable_bytes(payload.as_ref())) {
+            Ok(proof) => Ok(proof),
+            Err(error) if accept_legacy_msg_signatures() => {
+                trace!(
+                    "Falling back to legacy signature verification for {:?} msg",
+                    domain
+                );
+                Self::verify(inner, payload).map_err(|_| error)
+            }
+            Err(error) => Err(error),
+        }
+    }
+
     /// Drop the proof of validity and return the wrapped value.
     pub fn into_inner(self) -> T {
         self.0
@@ -6,6 +6,8 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+#[cfg(feature = "chunks")]
+use super::DataPayment;
 use super::{CmdError, Error, RegisterCmd, SpentbookCmd};
 use crate::types::Chunk;
 use serde::{Deserialize, Serialize};
@@ -25,6 +27,16 @@ pub enum DataCmd {
     ///
     /// [`Chunk`]: crate::types::Chunk
     StoreChunk(Chunk),
+    #[cfg(feature = "chunks")]
+    /// [`Chunk`] write operation, paid for with the attached [`DataPayment`].
+    ///
+    /// [`Chunk`]: crate::types::Chunk
+    StorePaidChunk {
+        /// The chunk to store
+        chunk: Chunk,
+        /// Payment covering the cost of storing the chunk
+        payment: DataPayment,
+    },
     #[cfg(feature = "registers")]
     /// [`Register`] write operation.
     ///
@@ -45,6 +57,8 @@ impl DataCmd {
         match self {
             #[cfg(feature = "chunks")]
             StoreChunk(_) => CmdError::Data(error),
+            #[cfg(feature = "chunks")]
+            StorePaidChunk { .. } => CmdError::Data(error),
             #[cfg(feature = "registers")]
             Register(c) => c.error(error),
             #[cfg(feature = "spentbook")]
@@ -58,6 +72,8 @@ impl DataCmd {
         match self {
             #[cfg(feature = "chunks")]
             StoreChunk(c) => *c.name(),
+            #[cfg(feature = "chunks")]
+            StorePaidChunk { chunk, .. } => *chunk.name(),
             #[cfg(feature = "registers")]
             Register(c) => c.name(), // TODO: c.dst_id(), as to not co-locate private and public and different tags of same name.
             #[cfg(feature = "spentbook")]
@@ -9,7 +9,7 @@
 use super::RegisterCmd;
 use crate::{
     messaging::SectionAuth,
-    types::{Error, RegisterAddress, Result, SpentbookAddress},
+    types::{Error, PublicKey, RegisterAddress, Result, SpentbookAddress},
 };
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -20,6 +20,13 @@ use xor_name::XorName;
 pub struct MetadataExchange {
     /// Adult storage levels.
     pub adult_levels: BTreeMap<XorName, StorageLevel>,
+    /// Payments accepted for storing data, by the name of the key image of their DBC, along
+    /// with the name of the data each of them paid for.
+    #[serde(default)]
+    pub spent_payments: BTreeMap<XorName, XorName>,
+    /// Sequence numbers of the latest ordered cmds applied, by client and data written to.
+    #[serde(default)]
+    pub applied_cmds: BTreeMap<(PublicKey, XorName), u64>,
 }
 
 /// Data to be exchanged between Register stores.
@@ -8,9 +8,9 @@
 
 use crate::types::register::User;
 use crate::types::DataAddress;
-use crate::types::PublicKey;
+use crate::types::{PublicKey, Token};
 use serde::{Deserialize, Serialize};
-use std::result;
+use std::{collections::BTreeMap, result, time::Duration};
 use thiserror::Error;
 use xor_name::{Prefix, XorName};
 
@@ -73,4 +73,42 @@ pub enum Error {
     /// Destination is either outdated or incorrect
     #[error("Destination is either outdated or wrong")]
     WrongDestination,
+    /// The payment attached to a cmd could not be validated
+    #[error("Invalid payment: {0}")]
+    InvalidPayment(String),
+    /// The payment attached to a cmd doesn't cover the cost of the operation
+    #[error("Insufficient payment: {paid} was paid but {required} is required")]
+    InsufficientPayment {
+        /// Amount which was paid
+        paid: Token,
+        /// Amount required for the operation
+        required: Token,
+    },
+    /// Storing the data requires a payment, and the cmd carried none
+    #[error("Storing this data requires a payment")]
+    PaymentRequired,
+    /// The DBC attached to a cmd already paid for storing other data
+    #[error("The payment was already used to pay for storing other data")]
+    PaymentAlreadyUsed,
+    /// Edits of a batch of Register edits were rejected, so none of them was applied
+    #[error("Edits of the Register batch were rejected, by their index: {0:?}")]
+    RegisterBatchRejected(BTreeMap<usize, String>),
+    /// The client sent more cmds than the section accepts from it, and has to back off
+    #[error("Too many cmds were sent, try again in {backoff:?}")]
+    TryAgainLater {
+        /// How long to wait for before sending the cmd again
+        backoff: Duration,
+    },
+    /// An ordered cmd was sent after one the client sent later to the same data was applied,
+    /// so it's not applied, as it would be out of order
+    #[error("Cmd #{seq} is out of order, cmd #{applied} to the same data was applied already")]
+    CmdOutOfOrder {
+        /// Sequence number of the cmd rejected
+        seq: u64,
+        /// Sequence number of the latest cmd applied
+        applied: u64,
+    },
+    /// The section key a history of section keys was requested from isn't in the section chain
+    #[error("Section key not found in the section chain: {0:?}")]
+    UnknownSectionKey(bls::PublicKey),
 }
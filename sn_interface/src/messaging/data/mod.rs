@@ -11,8 +11,13 @@
 mod cmd;
 mod data_exchange;
 mod errors;
+#[cfg(feature = "chunks")]
+mod payment;
 mod query;
+mod receipt;
 mod register;
+mod section_members;
+mod section_stats;
 mod spentbook;
 
 pub use self::{
@@ -23,13 +28,25 @@ pub use self::{
     },
     errors::{Error, Result},
     query::DataQuery,
+    receipt::{
+        ReceiptDurability, StorageReceipt, StorageReceiptContent, StorageReceiptShare,
+        RECEIPT_TIMESTAMP_GRANULARITY_SECS,
+    },
     register::{
         CreateRegister, DeleteRegister, EditRegister, ExtendRegister, RegisterCmd, RegisterQuery,
-        SignedRegisterCreate, SignedRegisterDelete, SignedRegisterEdit, SignedRegisterExtend,
+        SignedRegisterCreate, SignedRegisterDelete, SignedRegisterEdit, SignedRegisterEditBatch,
+        SignedRegisterExtend,
+    },
+    section_members::{
+        MembersContinuation, SectionMember, SectionMembersPage, MAX_SECTION_MEMBERS_PAGE_SIZE,
     },
+    section_stats::SectionStats,
     spentbook::{SpentbookCmd, SpentbookQuery},
 };
 
+#[cfg(feature = "chunks")]
+pub use self::payment::{store_cost, DataPayment, STORE_COST_PER_KIB};
+
 use crate::types::{
     register::{Entry, EntryHash, Permissions, Policy, Register, User},
     Chunk, ChunkAddress, DataAddress,
@@ -39,6 +56,7 @@ use crate::{
     types::utils,
 };
 use bytes::Bytes;
+use secured_linked_list::SecuredLinkedList;
 use serde::{Deserialize, Serialize};
 use sn_dbc::SpentProofShare;
 use std::{
@@ -47,7 +65,7 @@ use std::{
     fmt::{self, Debug, Display, Formatter},
 };
 use tiny_keccak::{Hasher, Sha3};
-use xor_name::XorName;
+use xor_name::{Prefix, XorName};
 
 /// Derivable Id of an operation. Query/Response should return the same id for simple tracking purposes.
 /// TODO: make uniquer per requester for some operations
@@ -81,6 +99,64 @@ pub fn chunk_operation_id(address: &ChunkAddress) -> Result<OperationId> {
     Ok(OperationId(output))
 }
 
+/// Return operation Id of a missing chunk report.
+/// It's different from the chunk's operation Id so the report's response
+/// is never mistaken for the response to a query for the chunk itself.
+pub fn missing_chunk_report_operation_id(address: &ChunkAddress) -> Result<OperationId> {
+    let bytes = utils::encode(address).map_err(|_| Error::NoOperationId)?;
+    let mut hasher = Sha3::v256();
+    let mut output = [0; 32];
+    hasher.update(b"missing-chunk-report");
+    hasher.update(bytes.as_bytes());
+    hasher.finalize(&mut output);
+
+    Ok(OperationId(output))
+}
+
+/// Return operation Id of a query for the statistics of the section matching the given prefix.
+pub fn section_stats_operation_id(prefix: &Prefix) -> Result<OperationId> {
+    let bytes = utils::encode(prefix).map_err(|_| Error::NoOperationId)?;
+    let mut hasher = Sha3::v256();
+    let mut output = [0; 32];
+    hasher.update(b"section-stats");
+    hasher.update(bytes.as_bytes());
+    hasher.finalize(&mut output);
+
+    Ok(OperationId(output))
+}
+
+/// Return operation Id of a query for the history of the keys of the section matching the given
+/// prefix, from the given key.
+pub fn section_key_history_operation_id(
+    prefix: &Prefix,
+    since: Option<&bls::PublicKey>,
+) -> Result<OperationId> {
+    let bytes = utils::encode(&(prefix, since)).map_err(|_| Error::NoOperationId)?;
+    let mut hasher = Sha3::v256();
+    let mut output = [0; 32];
+    hasher.update(b"section-key-history");
+    hasher.update(bytes.as_bytes());
+    hasher.finalize(&mut output);
+
+    Ok(OperationId(output))
+}
+
+/// Return operation Id of a query for the page of the members of the section matching the given
+/// prefix, which follows the given continuation.
+pub fn section_members_operation_id(
+    prefix: &Prefix,
+    continuation: Option<&MembersContinuation>,
+) -> Result<OperationId> {
+    let bytes = utils::encode(&(prefix, continuation)).map_err(|_| Error::NoOperationId)?;
+    let mut hasher = Sha3::v256();
+    let mut output = [0; 32];
+    hasher.update(b"section-members");
+    hasher.update(bytes.as_bytes());
+    hasher.finalize(&mut output);
+
+    Ok(OperationId(output))
+}
+
 /// A message indicating that an error occurred as a node was handling a client's message.
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
@@ -102,10 +178,23 @@ pub struct ServiceError {
 pub enum ServiceMsg {
     /// Messages that lead to mutation.
     ///
-    /// There will be no response to these messages on success, only if something went wrong. Due to
-    /// the eventually consistent nature of the network, it may be necessary to continually retry
-    /// operations that depend on the effects of mutations.
-    Cmd(DataCmd),
+    /// Depending on the requested [`CmdAckLevel`], there will be a [`CmdAck`] and/or a
+    /// [`CmdReplicated`] response on success, or no response at all. Due to the eventually
+    /// consistent nature of the network, it may be necessary to continually retry operations
+    /// that depend on the effects of mutations.
+    ///
+    /// [`CmdAck`]: Self::CmdAck
+    /// [`CmdReplicated`]: Self::CmdReplicated
+    Cmd {
+        /// The cmd
+        cmd: DataCmd,
+        /// How far the handling of the cmd is to be acknowledged
+        ack_level: CmdAckLevel,
+        /// Sequence number of the cmd among the ones the client sends to the same data, for
+        /// cmds to be applied in the order they're sent. Elders then reject a cmd once they
+        /// applied one with a higher number, so a cmd retried late can't overtake a later one.
+        seq: Option<u64>,
+    },
     /// A read-only operation.
     ///
     /// Senders should eventually receive either a corresponding [`QueryResponse`] or an error in
@@ -139,14 +228,58 @@ pub enum ServiceMsg {
         ///
         /// [`Cmd`]: Self::Cmd
         correlation_id: MsgId,
+        /// Share of the receipt for the content accepted, for [`Cmd`]s storing chunks.
+        ///
+        /// [`Cmd`]: Self::Cmd
+        receipt_share: Option<StorageReceiptShare>,
+    },
+    /// CmdReplicated will be sent back to the client once the Adults meant to hold the data
+    /// written by a [`Cmd`] sent with [`CmdAckLevel::Replicated`] confirmed storing it.
+    ///
+    /// [`Cmd`]: Self::Cmd
+    CmdReplicated {
+        /// ID of causing [`Cmd`] message.
+        ///
+        /// [`Cmd`]: Self::Cmd
+        correlation_id: MsgId,
     },
 }
 
+/// Largest chunk Elders acknowledge ahead of having the Adults store it, when asked to with
+/// [`CmdAckLevel::FastAck`].
+pub const FAST_ACK_MAX_CHUNK_SIZE: usize = 64 * 1024;
+
+/// How far the handling of a [`Cmd`] is acknowledged to the client sending it, trading latency
+/// for durability.
+///
+/// [`Cmd`]: ServiceMsg::Cmd
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum CmdAckLevel {
+    /// The cmd is not acknowledged, the client returns as soon as it's sent.
+    None,
+    /// Elders acknowledge the cmd with a [`ServiceMsg::CmdAck`] once they accepted it and
+    /// instructed the Adults to store its data.
+    Processed,
+    /// Elders acknowledge a chunk of up to [`FAST_ACK_MAX_CHUNK_SIZE`] bytes as soon as they
+    /// validated it, ahead of having the Adults store it, which carries on in the background.
+    /// Its receipt attests it was only validated then. Other cmds are acknowledged as `Processed`.
+    FastAck,
+    /// On top of acknowledging the cmd, Elders send a [`ServiceMsg::CmdReplicated`] once the
+    /// Adults meant to hold its data confirmed storing it.
+    Replicated,
+}
+
+impl Default for CmdAckLevel {
+    fn default() -> Self {
+        Self::Processed
+    }
+}
+
 impl ServiceMsg {
     /// Returns the destination address for cmds and Queries only.
     pub fn dst_address(&self) -> Option<XorName> {
         match self {
-            Self::Cmd(cmd) => Some(cmd.dst_name()),
+            Self::Cmd { cmd, .. } => Some(cmd.dst_name()),
             Self::Query(query) => Some(query.dst_name()),
             _ => None,
         }
@@ -173,6 +306,8 @@ pub enum QueryResponse {
     //
     /// Response to [`ChunkRead::Get`].
     GetChunk(Result<Chunk>),
+    /// Response to [`DataQuery::ReportMissingChunk`].
+    ReportMissingChunk((Result<()>, OperationId)),
     //
     // ===== Register Data =====
     //
@@ -196,6 +331,12 @@ pub enum QueryResponse {
     //
     // ===== Other =====
     //
+    /// Response to [`DataQuery::GetSectionStats`].
+    GetSectionStats((Result<SectionStats>, OperationId)),
+    /// Response to [`DataQuery::GetSectionKeyHistory`].
+    GetSectionKeyHistory((Result<SecuredLinkedList>, OperationId)),
+    /// Response to [`DataQuery::GetSectionMembers`].
+    GetSectionMembers((Result<SectionMembersPage>, OperationId)),
     /// Failed to create id generation
     FailedToCreateOperationId,
 }
@@ -206,6 +347,7 @@ impl QueryResponse {
         use QueryResponse::*;
         match self {
             GetChunk(result) => result.is_ok(),
+            ReportMissingChunk((result, _op_id)) => result.is_ok(),
             GetRegister((result, _op_id)) => result.is_ok(),
             GetRegisterEntry((result, _op_id)) => result.is_ok(),
             GetRegisterOwner((result, _op_id)) => result.is_ok(),
@@ -213,6 +355,9 @@ impl QueryResponse {
             GetRegisterPolicy((result, _op_id)) => result.is_ok(),
             GetRegisterUserPermissions((result, _op_id)) => result.is_ok(),
             SpentProofShares((result, _op_id)) => result.is_ok(),
+            GetSectionStats((result, _op_id)) => result.is_ok(),
+            GetSectionKeyHistory((result, _op_id)) => result.is_ok(),
+            GetSectionMembers((result, _op_id)) => result.is_ok(),
             FailedToCreateOperationId => false,
         }
     }
@@ -226,6 +371,7 @@ impl QueryResponse {
                 Ok(_) => false,
                 Err(error) => matches!(*error, ErrorMsg::ChunkNotFound(_)),
             },
+            ReportMissingChunk(_) => false,
             GetRegister((result, _op_id)) => match result {
                 Ok(_) => false,
                 Err(error) => matches!(*error, ErrorMsg::DataNotFound(_)),
@@ -254,6 +400,9 @@ impl QueryResponse {
                 Ok(_) => false,
                 Err(error) => matches!(*error, ErrorMsg::DataNotFound(_)),
             },
+            GetSectionStats(_) => false,
+            GetSectionKeyHistory(_) => false,
+            GetSectionMembers(_) => false,
             FailedToCreateOperationId => false,
         }
     }
@@ -283,13 +432,17 @@ impl QueryResponse {
                     Err(Error::InvalidQueryResponseErrorForOperationId)
                 }
             },
-            GetRegister((_, operation_id))
+            ReportMissingChunk((_, operation_id))
+            | GetRegister((_, operation_id))
             | GetRegisterEntry((_, operation_id))
             | GetRegisterOwner((_, operation_id))
             | ReadRegister((_, operation_id))
             | GetRegisterPolicy((_, operation_id))
             | GetRegisterUserPermissions((_, operation_id))
-            | SpentProofShares((_, operation_id)) => Ok(*operation_id),
+            | SpentProofShares((_, operation_id))
+            | GetSectionStats((_, operation_id))
+            | GetSectionKeyHistory((_, operation_id))
+            | GetSectionMembers((_, operation_id)) => Ok(*operation_id),
             FailedToCreateOperationId => Err(Error::NoOperationId),
         }
     }
@@ -408,17 +561,59 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn missing_chunk_report_has_its_own_operation_id() -> Result<()> {
+        let address = ChunkAddress(xor_name::rand::random());
+        let report_op_id = missing_chunk_report_operation_id(&address)?;
+        assert_ne!(chunk_operation_id(&address)?, report_op_id);
+        assert_eq!(
+            DataQuery::ReportMissingChunk(address).operation_id()?,
+            report_op_id
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn section_stats_operation_id_is_per_prefix() -> Result<()> {
+        let prefix = Prefix::default().pushed(true);
+        let op_id = section_stats_operation_id(&prefix)?;
+        assert_ne!(section_stats_operation_id(&Prefix::default())?, op_id);
+        assert_eq!(DataQuery::GetSectionStats(prefix).operation_id()?, op_id);
+        Ok(())
+    }
+
+    #[test]
+    fn section_key_history_operation_id_is_per_starting_key() -> Result<()> {
+        let prefix = Prefix::default().pushed(true);
+        let since = bls::SecretKey::random().public_key();
+        let op_id = section_key_history_operation_id(&prefix, Some(&since))?;
+        assert_ne!(section_key_history_operation_id(&prefix, None)?, op_id);
+        assert_ne!(section_stats_operation_id(&prefix)?, op_id);
+        assert_eq!(
+            DataQuery::GetSectionKeyHistory {
+                prefix,
+                since: Some(since)
+            }
+            .operation_id()?,
+            op_id
+        );
+        Ok(())
+    }
+
     #[test]
     fn wire_msg_payload() -> Result<()> {
-        use crate::messaging::data::DataCmd;
-        use crate::messaging::data::ServiceMsg;
+        use crate::messaging::data::{CmdAckLevel, DataCmd, ServiceMsg};
         use crate::messaging::WireMsg;
 
         let chunks = (0..10).map(|_| Chunk::new(random_bytes(3072)));
 
         for chunk in chunks {
             let (original_msg, serialised_cmd) = {
-                let msg = ServiceMsg::Cmd(DataCmd::StoreChunk(chunk));
+                let msg = ServiceMsg::Cmd {
+                    cmd: DataCmd::StoreChunk(chunk),
+                    ack_level: CmdAckLevel::Replicated,
+                    seq: Some(7),
+                };
                 let bytes = WireMsg::serialize_msg_payload(&msg)?;
                 (msg, bytes)
             };
@@ -0,0 +1,77 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::types::Token;
+
+use serde::{Deserialize, Serialize};
+use sn_dbc::Dbc;
+use xor_name::XorName;
+
+/// Price of storing each KiB of data, in nanos.
+pub const STORE_COST_PER_KIB: u64 = 1_000;
+
+// Tag prepended to the bytes a payment is bound to data with, so the signature binding it can't
+// be mistaken for a signature over anything else
+const PAYMENT_BINDING_TAG: &[u8] = b"sn_data_payment";
+
+/// Payment attached to a cmd storing data on the network.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct DataPayment {
+    /// A bearer DBC, whose amount has to cover the cost of storing the data.
+    /// The inputs it was reissued from must have been logged as spent in the spentbook.
+    pub dbc: Dbc,
+    /// Signature by the key of the DBC over the name and size of the data paid for, so the
+    /// payment can't be presented for other data.
+    pub binding_sig: bls::Signature,
+}
+
+impl DataPayment {
+    /// Pays with the given bearer DBC for storing `num_bytes` of data at the given name.
+    pub fn new(dbc: Dbc, name: XorName, num_bytes: usize) -> Result<Self, sn_dbc::Error> {
+        let binding_sig = dbc
+            .secret_key_bearer()?
+            .sign(binding_bytes(&name, num_bytes));
+        Ok(Self { dbc, binding_sig })
+    }
+
+    /// Whether the payment was made for storing `num_bytes` of data at the given name.
+    pub fn is_bound_to(&self, name: &XorName, num_bytes: usize) -> bool {
+        match self.dbc.secret_key_bearer() {
+            Ok(secret_key) => secret_key
+                .public_key()
+                .verify(&self.binding_sig, binding_bytes(name, num_bytes)),
+            Err(_) => false,
+        }
+    }
+}
+
+// Bytes signed to bind a payment to the data it pays for
+fn binding_bytes(name: &XorName, num_bytes: usize) -> Vec<u8> {
+    let mut bytes = PAYMENT_BINDING_TAG.to_vec();
+    bytes.extend_from_slice(&name.0);
+    bytes.extend_from_slice(&(num_bytes as u64).to_be_bytes());
+    bytes
+}
+
+/// Cost of storing the given number of bytes of data, with every started KiB being charged.
+pub fn store_cost(num_bytes: usize) -> Token {
+    let kibs = (num_bytes as u64 + 1023) / 1024;
+    Token::from_nano(kibs.max(1).saturating_mul(STORE_COST_PER_KIB))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn store_cost_is_charged_per_started_kib() {
+        assert_eq!(store_cost(0), Token::from_nano(STORE_COST_PER_KIB));
+        assert_eq!(store_cost(1024), Token::from_nano(STORE_COST_PER_KIB));
+        assert_eq!(store_cost(1025), Token::from_nano(2 * STORE_COST_PER_KIB));
+    }
+}
@@ -7,12 +7,13 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
-    chunk_operation_id, register::RegisterQuery, spentbook::SpentbookQuery, Error, OperationId,
-    QueryResponse, Result,
+    chunk_operation_id, missing_chunk_report_operation_id, register::RegisterQuery,
+    section_key_history_operation_id, section_members_operation_id, section_stats_operation_id,
+    spentbook::SpentbookQuery, Error, MembersContinuation, OperationId, QueryResponse, Result,
 };
 use crate::types::{ChunkAddress, ReplicatedDataAddress, SpentbookAddress};
 use serde::{Deserialize, Serialize};
-use xor_name::XorName;
+use xor_name::{Prefix, XorName};
 
 /// Data queries - retrieving data and inspecting their structure.
 ///
@@ -30,6 +31,14 @@ pub enum DataQuery {
     /// [`Chunk`]: crate::types::Chunk
     /// [`GetChunk`]: QueryResponse::GetChunk
     GetChunk(ChunkAddress),
+    #[cfg(feature = "chunks")]
+    /// Report a [`Chunk`] which couldn't be retrieved from the network, so Elders
+    /// can trigger a replication check for it among their Adults.
+    ///
+    /// This should eventually lead to a [`ReportMissingChunk`] response.
+    /// [`Chunk`]: crate::types::Chunk
+    /// [`ReportMissingChunk`]: QueryResponse::ReportMissingChunk
+    ReportMissingChunk(ChunkAddress),
     #[cfg(feature = "registers")]
     /// [`Register`] read operation.
     ///
@@ -40,6 +49,43 @@ pub enum DataQuery {
     ///
     /// [`Spentbook`]: crate::types::spentbook::Spentbook
     Spentbook(SpentbookQuery),
+    /// Retrieve the statistics of the section matching the given prefix, which its Elders
+    /// answer with themselves.
+    ///
+    /// This should eventually lead to a [`GetSectionStats`] response.
+    /// [`GetSectionStats`]: QueryResponse::GetSectionStats
+    GetSectionStats(Prefix),
+    /// Retrieve the chain of keys of the section matching the given prefix, from the given key
+    /// it had, or from the genesis key if none, to its current key, each key being signed with
+    /// the one before it. Its Elders answer with their own section chain.
+    ///
+    /// This lets light clients validate what was signed with an older section key, e.g. storage
+    /// receipts, spent proofs or SAPs, without keeping track of the whole network.
+    ///
+    /// This should eventually lead to a [`GetSectionKeyHistory`] response.
+    /// [`GetSectionKeyHistory`]: QueryResponse::GetSectionKeyHistory
+    GetSectionKeyHistory {
+        /// Prefix of the section
+        prefix: Prefix,
+        /// Key the history starts from, the genesis key if none
+        since: Option<bls::PublicKey>,
+    },
+    /// Retrieve a page of the members of the section matching the given prefix, sorted by name,
+    /// which its Elders answer with themselves. The first page is retrieved without continuation,
+    /// the following ones with the continuation of the page before.
+    ///
+    /// The members of large sections don't fit in a single msg, hence the pages, each of them
+    /// being kept within [`MAX_SECTION_MEMBERS_PAGE_SIZE`] bytes.
+    ///
+    /// This should eventually lead to a [`GetSectionMembers`] response.
+    /// [`GetSectionMembers`]: QueryResponse::GetSectionMembers
+    /// [`MAX_SECTION_MEMBERS_PAGE_SIZE`]: super::MAX_SECTION_MEMBERS_PAGE_SIZE
+    GetSectionMembers {
+        /// Prefix of the section
+        prefix: Prefix,
+        /// Where the page before left off, if this isn't the first page
+        continuation: Option<MembersContinuation>,
+    },
 }
 
 impl DataQuery {
@@ -50,10 +96,30 @@ impl DataQuery {
         match self {
             #[cfg(feature = "chunks")]
             GetChunk(_) => Ok(QueryResponse::GetChunk(Err(error))),
+            #[cfg(feature = "chunks")]
+            ReportMissingChunk(address) => Ok(QueryResponse::ReportMissingChunk((
+                Err(error),
+                missing_chunk_report_operation_id(address)?,
+            ))),
             #[cfg(feature = "registers")]
             Register(q) => q.error(error),
             #[cfg(feature = "spentbook")]
             Spentbook(q) => q.error(error),
+            GetSectionStats(prefix) => Ok(QueryResponse::GetSectionStats((
+                Err(error),
+                section_stats_operation_id(prefix)?,
+            ))),
+            GetSectionKeyHistory { prefix, since } => Ok(QueryResponse::GetSectionKeyHistory((
+                Err(error),
+                section_key_history_operation_id(prefix, since.as_ref())?,
+            ))),
+            GetSectionMembers {
+                prefix,
+                continuation,
+            } => Ok(QueryResponse::GetSectionMembers((
+                Err(error),
+                section_members_operation_id(prefix, continuation.as_ref())?,
+            ))),
         }
     }
 
@@ -62,25 +128,33 @@ impl DataQuery {
         use DataQuery::*;
         match self {
             #[cfg(feature = "chunks")]
-            GetChunk(address) => *address.name(),
+            GetChunk(address) | ReportMissingChunk(address) => *address.name(),
             #[cfg(feature = "registers")]
             Register(q) => q.dst_name(),
             #[cfg(feature = "spentbook")]
             Spentbook(q) => q.dst_name(),
+            GetSectionStats(prefix)
+            | GetSectionKeyHistory { prefix, .. }
+            | GetSectionMembers { prefix, .. } => prefix.name(),
         }
     }
 
-    /// Returns the address of the data
-    pub fn address(&self) -> ReplicatedDataAddress {
+    /// Returns the address of the data, or `None` if the query is not about any data
+    pub fn address(&self) -> Option<ReplicatedDataAddress> {
         match self {
             #[cfg(feature = "chunks")]
-            DataQuery::GetChunk(address) => ReplicatedDataAddress::Chunk(*address),
+            DataQuery::GetChunk(address) | DataQuery::ReportMissingChunk(address) => {
+                Some(ReplicatedDataAddress::Chunk(*address))
+            }
             #[cfg(feature = "registers")]
-            DataQuery::Register(read) => ReplicatedDataAddress::Register(read.dst_address()),
+            DataQuery::Register(read) => Some(ReplicatedDataAddress::Register(read.dst_address())),
             #[cfg(feature = "spentbook")]
-            DataQuery::Spentbook(read) => {
-                ReplicatedDataAddress::Spentbook(SpentbookAddress::new(*read.dst_address().name()))
-            }
+            DataQuery::Spentbook(read) => Some(ReplicatedDataAddress::Spentbook(
+                SpentbookAddress::new(*read.dst_address().name()),
+            )),
+            DataQuery::GetSectionStats(_)
+            | DataQuery::GetSectionKeyHistory { .. }
+            | DataQuery::GetSectionMembers { .. } => None,
         }
     }
 
@@ -92,10 +166,20 @@ impl DataQuery {
         match self {
             #[cfg(feature = "chunks")]
             DataQuery::GetChunk(address) => chunk_operation_id(address),
+            #[cfg(feature = "chunks")]
+            DataQuery::ReportMissingChunk(address) => missing_chunk_report_operation_id(address),
             #[cfg(feature = "registers")]
             DataQuery::Register(read) => read.operation_id(),
             #[cfg(feature = "spentbook")]
             DataQuery::Spentbook(read) => read.operation_id(),
+            DataQuery::GetSectionStats(prefix) => section_stats_operation_id(prefix),
+            DataQuery::GetSectionKeyHistory { prefix, since } => {
+                section_key_history_operation_id(prefix, since.as_ref())
+            }
+            DataQuery::GetSectionMembers {
+                prefix,
+                continuation,
+            } => section_members_operation_id(prefix, continuation.as_ref()),
         }
     }
 }
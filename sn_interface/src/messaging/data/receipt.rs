@@ -0,0 +1,287 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::messaging::system::{KeyedSig, SigShare};
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use xor_name::XorName;
+
+/// Granularity of the time a section attests having accepted some content at, in seconds.
+/// Elders round the time down to it, so their signature shares are over the same content.
+pub const RECEIPT_TIMESTAMP_GRANULARITY_SECS: u64 = 60;
+
+/// Tag prepended to the content of a receipt before it is signed, so a receipt signature can't
+/// be passed off as the section's signature over anything else, or the other way round.
+pub const RECEIPT_SIGNING_TAG: &[u8] = b"SN_STORAGE_RECEIPT";
+
+/// How durably the content a storage receipt is for was stored when the receipt was signed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum ReceiptDurability {
+    /// The Elders validated the content and acknowledged it before having the Adults store it,
+    /// which they were still doing when signing the receipt.
+    ElderValidated,
+    /// The Elders instructed the Adults to store the content before acknowledging it.
+    Replicated,
+}
+
+impl ReceiptDurability {
+    fn signable_byte(&self) -> u8 {
+        match self {
+            Self::ElderValidated => 0,
+            Self::Replicated => 1,
+        }
+    }
+}
+
+/// What a storage receipt attests: content a section accepted to store, when, and how durably.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct StorageReceiptContent {
+    /// Name of the chunk which was accepted
+    pub name: XorName,
+    /// Time the chunk was accepted at, in seconds since the Unix epoch,
+    /// rounded down to `RECEIPT_TIMESTAMP_GRANULARITY_SECS`
+    pub timestamp: u64,
+    /// How far the chunk was stored when it was acknowledged
+    pub durability: ReceiptDurability,
+}
+
+impl StorageReceiptContent {
+    /// Content of the receipt for a chunk accepted now, and which the Adults were instructed
+    /// to store.
+    pub fn new(name: XorName) -> Self {
+        Self::with_durability(name, ReceiptDurability::Replicated)
+    }
+
+    /// Content of the receipt for a chunk accepted now, but acknowledged ahead of the Adults
+    /// being instructed to store it.
+    pub fn elder_validated(name: XorName) -> Self {
+        Self::with_durability(name, ReceiptDurability::ElderValidated)
+    }
+
+    fn with_durability(name: XorName, durability: ReceiptDurability) -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        Self {
+            name,
+            timestamp: now - now % RECEIPT_TIMESTAMP_GRANULARITY_SECS,
+            durability,
+        }
+    }
+
+    /// The same content, as accepted within the window before.
+    pub fn previous_window(&self) -> Self {
+        Self {
+            timestamp: self
+                .timestamp
+                .saturating_sub(RECEIPT_TIMESTAMP_GRANULARITY_SECS),
+            ..*self
+        }
+    }
+
+    /// Bytes a receipt signature is made over.
+    pub fn signable_bytes(&self) -> Vec<u8> {
+        let mut bytes = RECEIPT_SIGNING_TAG.to_vec();
+        bytes.extend_from_slice(&self.name.0);
+        bytes.extend_from_slice(&self.timestamp.to_be_bytes());
+        bytes.push(self.durability.signable_byte());
+        bytes
+    }
+}
+
+/// Share of a storage receipt, signed by one of the Elders of the section which accepted the content.
+///
+/// The Elder signs the content as of the window before as well, for its share to be aggregated
+/// with those of the Elders which accepted the content before the window changed.
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct StorageReceiptShare {
+    /// What the receipt attests
+    pub content: StorageReceiptContent,
+    /// The Elder's signature share over the content
+    pub sig_share: SigShare,
+    /// The Elder's signature share over the content as of the previous window
+    pub previous_window_sig_share: SigShare,
+}
+
+impl StorageReceiptShare {
+    /// Aggregate the shares into a receipt, once enough of them are over the same content with
+    /// the same section key. Returns `None` if there are not enough of them yet.
+    pub fn aggregate<'a>(
+        shares: impl IntoIterator<Item = &'a StorageReceiptShare>,
+    ) -> Option<StorageReceipt> {
+        let mut groups = BTreeMap::<_, Vec<&SigShare>>::new();
+        for share in shares {
+            let windows = [
+                (share.content, &share.sig_share),
+                (
+                    share.content.previous_window(),
+                    &share.previous_window_sig_share,
+                ),
+            ];
+            for (content, sig_share) in windows {
+                if !sig_share.verify(&content.signable_bytes()) {
+                    continue;
+                }
+                let key = (content, sig_share.public_key_set.public_key());
+                groups.entry(key).or_default().push(sig_share);
+            }
+        }
+
+        groups
+            .into_iter()
+            .find_map(|((content, public_key), sig_shares)| {
+                let public_key_set = &sig_shares.first()?.public_key_set;
+                let shares: BTreeMap<_, _> = sig_shares
+                    .iter()
+                    .map(|sig_share| (sig_share.index, &sig_share.signature_share))
+                    .collect();
+                if shares.len() <= public_key_set.threshold() {
+                    return None;
+                }
+                let signature = public_key_set.combine_signatures(shares).ok()?;
+                Some(StorageReceipt {
+                    content,
+                    sig: KeyedSig {
+                        public_key,
+                        signature,
+                    },
+                })
+            })
+    }
+}
+
+/// Proof that a section of the network accepted to store some content, signed with its section key.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct StorageReceipt {
+    /// What the receipt attests
+    pub content: StorageReceiptContent,
+    /// Section signature over the content
+    pub sig: KeyedSig,
+}
+
+impl StorageReceipt {
+    /// Whether the receipt was signed with its section key.
+    /// Whether that key belongs to the network is to be checked separately.
+    pub fn verify(&self) -> bool {
+        self.sig.verify(&self.content.signable_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SecretKeySet;
+
+    fn receipt_share(
+        secret_key_set: &SecretKeySet,
+        index: usize,
+        content: StorageReceiptContent,
+    ) -> StorageReceiptShare {
+        let sign = |content: StorageReceiptContent| {
+            SigShare::new(
+                secret_key_set.public_keys(),
+                index,
+                &secret_key_set.secret_key_share(index),
+                &content.signable_bytes(),
+            )
+        };
+        StorageReceiptShare {
+            content,
+            sig_share: sign(content),
+            previous_window_sig_share: sign(content.previous_window()),
+        }
+    }
+
+    #[test]
+    fn receipt_is_aggregated_from_enough_shares() {
+        let secret_key_set = SecretKeySet::random();
+        let public_key_set = secret_key_set.public_keys();
+        let content = StorageReceiptContent::new(xor_name::rand::random());
+
+        let shares: Vec<_> = (0..=secret_key_set.threshold())
+            .map(|index| receipt_share(&secret_key_set, index, content))
+            .collect();
+
+        let (last, first) = shares.split_last().expect("no receipt shares");
+        assert!(StorageReceiptShare::aggregate(first).is_none());
+        assert!(StorageReceiptShare::aggregate(first.iter().chain([last])).is_some());
+
+        let receipt = StorageReceiptShare::aggregate(&shares).expect("receipt not aggregated");
+        assert!(receipt.verify());
+        assert_eq!(receipt.sig.public_key, public_key_set.public_key());
+
+        let mut tampered = receipt;
+        tampered.content.timestamp += RECEIPT_TIMESTAMP_GRANULARITY_SECS;
+        assert!(!tampered.verify());
+    }
+
+    #[test]
+    fn receipt_is_aggregated_from_shares_across_windows() {
+        let secret_key_set = SecretKeySet::random();
+        let content = StorageReceiptContent::new(xor_name::rand::random());
+        let next_window = StorageReceiptContent {
+            timestamp: content.timestamp + RECEIPT_TIMESTAMP_GRANULARITY_SECS,
+            ..content
+        };
+
+        // half the Elders accepted the content just before the window changed
+        let shares: Vec<_> = (0..=secret_key_set.threshold())
+            .map(|index| {
+                let content = if index % 2 == 0 { content } else { next_window };
+                receipt_share(&secret_key_set, index, content)
+            })
+            .collect();
+
+        let receipt = StorageReceiptShare::aggregate(&shares).expect("receipt not aggregated");
+        assert!(receipt.verify());
+        assert_eq!(receipt.content, content);
+
+        // but not with those which accepted it two windows later
+        let later_window = StorageReceiptContent {
+            timestamp: content.timestamp + 2 * RECEIPT_TIMESTAMP_GRANULARITY_SECS,
+            ..content
+        };
+        let shares: Vec<_> = (0..=secret_key_set.threshold())
+            .map(|index| {
+                let content = if index % 2 == 0 {
+                    content
+                } else {
+                    later_window
+                };
+                receipt_share(&secret_key_set, index, content)
+            })
+            .collect();
+        assert!(StorageReceiptShare::aggregate(&shares).is_none());
+    }
+
+    #[test]
+    fn receipt_durability_is_signed() {
+        let secret_key_set = SecretKeySet::random();
+        let content = StorageReceiptContent::elder_validated(xor_name::rand::random());
+
+        let shares: Vec<_> = (0..=secret_key_set.threshold())
+            .map(|index| receipt_share(&secret_key_set, index, content))
+            .collect();
+        let receipt = StorageReceiptShare::aggregate(&shares).expect("receipt not aggregated");
+        assert!(receipt.verify());
+        assert_eq!(
+            receipt.content.durability,
+            ReceiptDurability::ElderValidated
+        );
+
+        // a receipt of an acknowledged chunk can't be passed off as one of a replicated chunk
+        let mut relabelled = receipt;
+        relabelled.content.durability = ReceiptDurability::Replicated;
+        assert!(!relabelled.verify());
+    }
+}
@@ -8,7 +8,7 @@
 
 use super::{CmdError, Error, QueryResponse, Result};
 
-use crate::messaging::{data::OperationId, SectionAuth};
+use crate::messaging::{data::OperationId, SectionAuth, VerifyAuthority};
 use crate::types::register::{EntryHash, Register};
 use crate::types::{
     register::{Entry, Policy, RegisterOp, User},
@@ -17,6 +17,7 @@ use crate::types::{
 use tiny_keccak::{Hasher, Sha3};
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use xor_name::XorName;
 
 /// [`Register`] read operations.
@@ -86,6 +87,8 @@ pub enum RegisterCmd {
     },
     /// Edit the [`Register`].
     Edit(SignedRegisterEdit),
+    /// Edit the [`Register`] with a batch of write operations, all of them applied or none.
+    EditBatch(SignedRegisterEditBatch),
     /// Delete the [`Register`].
     Delete(SignedRegisterDelete),
     /// Extend the size of the [`Register`].
@@ -215,6 +218,16 @@ pub struct SignedRegisterEdit {
     pub auth: crate::messaging::ServiceAuth,
 }
 
+/// A batch of [`Register`] write operations signed by the requester, applied in order
+/// and atomically.
+#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct SignedRegisterEditBatch {
+    /// The address of the [`Register`] to edit.
+    pub address: RegisterAddress,
+    /// The operations to perform, in order.
+    pub edits: Vec<SignedRegisterEdit>,
+}
+
 /// A [`Register`] write operation.
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub struct SignedRegisterDelete {
@@ -243,6 +256,46 @@ impl SignedRegisterEdit {
     }
 }
 
+impl SignedRegisterEditBatch {
+    /// Returns the dst address of the register.
+    pub fn dst_address(&self) -> &RegisterAddress {
+        &self.address
+    }
+
+    /// Checks each of the edits is for the register of the batch and validly signed.
+    /// The edits rejected are reported by their index in the batch, along with the reason.
+    pub fn validate(&self) -> Result<()> {
+        if self.edits.is_empty() {
+            return Err(Error::InvalidOperation(
+                "Empty batch of Register edits".to_string(),
+            ));
+        }
+
+        let rejected: BTreeMap<_, _> = self
+            .edits
+            .iter()
+            .enumerate()
+            .filter_map(|(index, SignedRegisterEdit { op, auth })| {
+                let reason = if op.address != self.address {
+                    format!("edit of another Register: {:?}", op.address)
+                } else {
+                    match bincode::serialize(op) {
+                        Ok(payload) => auth.clone().verify_authority(payload).err()?.to_string(),
+                        Err(err) => format!("edit could not be serialised: {}", err),
+                    }
+                };
+                Some((index, reason))
+            })
+            .collect();
+
+        if rejected.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::RegisterBatchRejected(rejected))
+        }
+    }
+}
+
 impl SignedRegisterDelete {
     /// Returns the dst address of the register.
     pub fn dst_address(&self) -> &RegisterAddress {
@@ -351,6 +404,7 @@ impl RegisterCmd {
         match self {
             Self::Create { cmd, .. } => cmd.dst_address(),
             Self::Edit(cmd) => *cmd.dst_address(),
+            Self::EditBatch(cmd) => *cmd.dst_address(),
             Self::Delete(cmd) => *cmd.dst_address(),
             Self::Extend { cmd, .. } => *cmd.dst_address(),
         }
@@ -0,0 +1,242 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use bls::PublicKey as BlsPublicKey;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use xor_name::{Prefix, XorName};
+
+/// Max size in bytes of a serialised page of the members of a section, so that the response
+/// it goes in fits in a single msg however large the section is.
+pub const MAX_SECTION_MEMBERS_PAGE_SIZE: usize = 32 * 1024;
+
+/// A member of a section, as listed in the response to a [`DataQuery::GetSectionMembers`].
+///
+/// [`DataQuery::GetSectionMembers`]: super::DataQuery::GetSectionMembers
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct SectionMember {
+    /// Name of the node
+    pub name: XorName,
+    /// Address the node can be reached at
+    pub addr: SocketAddr,
+    /// Whether the node is an Elder of the section
+    pub is_elder: bool,
+}
+
+/// Where a page of the members of a section left off, to be sent back in the query for the
+/// next page.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct MembersContinuation {
+    /// Key of the section when the page was sent. If the section key changed since, the members
+    /// may have too, so the next page starts over with the first member.
+    pub section_key: BlsPublicKey,
+    /// Name of the last member of the page, the next page listing the members after it
+    pub after: XorName,
+}
+
+/// A page of the members of a section, sorted by name, as reported by one of its Elders.
+///
+/// This is the response to a [`DataQuery::GetSectionMembers`], which only fits the members of
+/// a large section over several msgs.
+///
+/// [`DataQuery::GetSectionMembers`]: super::DataQuery::GetSectionMembers
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SectionMembersPage {
+    /// Prefix of the section
+    pub prefix: Prefix,
+    /// Current key of the section
+    pub section_key: BlsPublicKey,
+    /// Members of the page
+    pub members: Vec<SectionMember>,
+    /// Where to continue from to get the next page, if this isn't the last one
+    pub continuation: Option<MembersContinuation>,
+}
+
+impl SectionMembersPage {
+    /// Builds the page of the given members which follows the given continuation, with as many
+    /// members as fit within `max_size` bytes once serialised. The members are expected to be
+    /// sorted by name.
+    ///
+    /// A page always holds at least one member, unless there is none left, so that paging
+    /// through the members makes progress whatever the size given.
+    pub fn new(
+        prefix: Prefix,
+        section_key: BlsPublicKey,
+        members: impl IntoIterator<Item = SectionMember>,
+        continuation: Option<&MembersContinuation>,
+        max_size: usize,
+    ) -> Self {
+        // members may have changed along with the section key, in which case we start over
+        let after = continuation
+            .filter(|continuation| continuation.section_key == section_key)
+            .map(|continuation| continuation.after);
+
+        let mut page = Self {
+            prefix,
+            section_key,
+            members: vec![],
+            continuation: Some(MembersContinuation {
+                section_key,
+                after: XorName::default(),
+            }),
+        };
+        // size of the page without members, as if it's followed by another one
+        let mut size = serialised_size(&page);
+
+        let mut members = members
+            .into_iter()
+            .skip_while(|member| after.map_or(false, |after| member.name <= after))
+            .peekable();
+
+        page.continuation = None;
+        while let Some(member) = members.peek() {
+            size = size.saturating_add(serialised_size(member));
+            if size > max_size && !page.members.is_empty() {
+                page.continuation = page.members.last().map(|last| MembersContinuation {
+                    section_key,
+                    after: last.name,
+                });
+                break;
+            }
+            if let Some(member) = members.next() {
+                page.members.push(member);
+            }
+        }
+
+        page
+    }
+}
+
+fn serialised_size<T: Serialize>(value: &T) -> usize {
+    bincode::serialized_size(value).map_or(usize::MAX, |size| size as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{serialised_size, MembersContinuation, SectionMember, SectionMembersPage};
+
+    use std::net::{Ipv4Addr, SocketAddr};
+    use xor_name::{Prefix, XorName};
+
+    fn members(count: usize) -> Vec<SectionMember> {
+        let mut members: Vec<_> = (0..count)
+            .map(|index| SectionMember {
+                name: xor_name::rand::random(),
+                addr: SocketAddr::from((Ipv4Addr::LOCALHOST, 12000 + index as u16)),
+                is_elder: index < 7,
+            })
+            .collect();
+        members.sort_by_key(|member| member.name);
+        members
+    }
+
+    // Size of a page holding all the given members, were it followed by another one
+    fn full_page_size(members: &[SectionMember], section_key: bls::PublicKey) -> usize {
+        serialised_size(&SectionMembersPage {
+            prefix: Prefix::default(),
+            section_key,
+            members: members.to_vec(),
+            continuation: Some(MembersContinuation {
+                section_key,
+                after: XorName::default(),
+            }),
+        })
+    }
+
+    #[test]
+    fn members_fitting_exactly_are_in_a_single_page() {
+        let section_key = bls::SecretKey::random().public_key();
+        let members = members(10);
+        let max_size = full_page_size(&members, section_key);
+
+        let page = SectionMembersPage::new(
+            Prefix::default(),
+            section_key,
+            members.clone(),
+            None,
+            max_size,
+        );
+        assert_eq!(page.members, members);
+        assert_eq!(page.continuation, None);
+        assert!(serialised_size(&page) <= max_size);
+    }
+
+    #[test]
+    fn members_over_the_max_size_by_a_byte_continue_in_the_next_page() {
+        let section_key = bls::SecretKey::random().public_key();
+        let members = members(10);
+        let max_size = full_page_size(&members, section_key) - 1;
+
+        let first = SectionMembersPage::new(
+            Prefix::default(),
+            section_key,
+            members.clone(),
+            None,
+            max_size,
+        );
+        assert_eq!(first.members, members[..9]);
+        assert_eq!(
+            first.continuation,
+            Some(MembersContinuation {
+                section_key,
+                after: members[8].name,
+            })
+        );
+        assert!(serialised_size(&first) <= max_size);
+
+        let second = SectionMembersPage::new(
+            Prefix::default(),
+            section_key,
+            members.clone(),
+            first.continuation.as_ref(),
+            max_size,
+        );
+        assert_eq!(second.members, members[9..]);
+        assert_eq!(second.continuation, None);
+    }
+
+    #[test]
+    fn pages_hold_a_member_each_when_too_small_for_any() {
+        let section_key = bls::SecretKey::random().public_key();
+        let members = members(3);
+
+        let mut continuation = None;
+        for member in &members {
+            let page = SectionMembersPage::new(
+                Prefix::default(),
+                section_key,
+                members.clone(),
+                continuation.as_ref(),
+                0,
+            );
+            assert_eq!(page.members, vec![member.clone()]);
+            continuation = page.continuation;
+        }
+        assert_eq!(continuation, None);
+    }
+
+    #[test]
+    fn continuation_from_another_section_key_starts_over() {
+        let section_key = bls::SecretKey::random().public_key();
+        let members = members(4);
+        let stale = MembersContinuation {
+            section_key: bls::SecretKey::random().public_key(),
+            after: members[1].name,
+        };
+
+        let page = SectionMembersPage::new(
+            Prefix::default(),
+            section_key,
+            members.clone(),
+            Some(&stale),
+            usize::MAX,
+        );
+        assert_eq!(page.members, members);
+        assert_eq!(page.continuation, None);
+    }
+}
@@ -0,0 +1,38 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use bls::PublicKey as BlsPublicKey;
+use serde::{Deserialize, Serialize};
+use xor_name::Prefix;
+
+/// Statistics of a section, as reported by one of its Elders.
+///
+/// This is the response to a [`DataQuery::GetSectionStats`], e.g. for network explorers, or
+/// apps which take the capacity of the network into account.
+///
+/// [`DataQuery::GetSectionStats`]: super::DataQuery::GetSectionStats
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SectionStats {
+    /// Prefix of the section
+    pub prefix: Prefix,
+    /// Current key of the section
+    pub section_key: BlsPublicKey,
+    /// Number of keys in the section chain up to the current one, i.e. its generation
+    pub section_key_generation: u64,
+    /// Number of Elders of the section
+    pub elder_count: usize,
+    /// Number of Adults of the section
+    pub adult_count: usize,
+    /// Approximate number of chunks stored in the section. It's the number of chunks the
+    /// reporting Elder had its Adults store since it started, hence a lower bound.
+    pub approx_stored_chunks: u64,
+    /// Number of copies of each data the section stores
+    pub data_copy_count: usize,
+    /// Whether the section currently accepts new nodes joining
+    pub joins_allowed: bool,
+}
@@ -6,6 +6,8 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use super::DstLocation;
+
 use std::result;
 use thiserror::Error;
 
@@ -39,4 +41,30 @@ pub enum Error {
     /// Message read was built with an unsupported version.
     #[error("Unsupported messaging protocol version: {0}")]
     UnsupportedVersion(u16),
+
+    /// A handshake msg is over the max size, once its frames are put back together.
+    #[error(
+        "Handshake of {0} bytes is over the max size of {} bytes",
+        super::system::MAX_HANDSHAKE_SIZE
+    )]
+    HandshakeTooLarge(usize),
+
+    /// A frame of a handshake msg doesn't fit with the ones received before, being past its
+    /// last frame, or carrying nothing, so the handshake was dropped.
+    #[error("Unexpected handshake frame {received}, the last frame being {last}")]
+    UnexpectedHandshakeFrame {
+        /// Index of the last frame of the handshake, as far as known
+        last: u32,
+        /// Index of the frame received
+        received: u32,
+    },
+
+    /// A msg was built for a destination it can't be sent to, given its authority.
+    #[error("Invalid destination {dst_location:?}: {reason}")]
+    InvalidDstLocation {
+        /// The destination of the msg
+        dst_location: DstLocation,
+        /// Why the msg can't be sent to it
+        reason: &'static str,
+    },
 }
@@ -6,6 +6,8 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use super::{system::SystemMsg, AuthKind, Error, Result};
+
 use bls::PublicKey as BlsPublicKey;
 use serde::{Deserialize, Serialize};
 use xor_name::{Prefix, XorName};
@@ -37,6 +39,21 @@ pub enum SrcLocation {
 }
 
 impl SrcLocation {
+    /// Source of a msg from a single node with the given name, of the section with the given key.
+    pub fn node(name: XorName, section_pk: BlsPublicKey) -> Self {
+        Self::Node { name, section_pk }
+    }
+
+    /// Source of a msg from the section close to the given name, with the given key.
+    pub fn section(name: XorName, section_pk: BlsPublicKey) -> Self {
+        Self::Section { name, section_pk }
+    }
+
+    /// Source of a msg from the end user with the given name.
+    pub fn end_user(name: XorName) -> Self {
+        Self::EndUser(EndUser(name))
+    }
+
     /// Returns the name of this location.
     pub fn name(&self) -> XorName {
         match self {
@@ -57,8 +74,8 @@ impl SrcLocation {
     pub fn to_dst(self) -> DstLocation {
         match self {
             Self::EndUser(user) => DstLocation::EndUser(user),
-            Self::Node { name, section_pk } => DstLocation::Node { name, section_pk },
-            Self::Section { name, section_pk } => DstLocation::Section { name, section_pk },
+            Self::Node { name, section_pk } => DstLocation::node(name, section_pk),
+            Self::Section { name, section_pk } => DstLocation::section(name, section_pk),
         }
     }
 }
@@ -85,6 +102,59 @@ pub enum DstLocation {
 }
 
 impl DstLocation {
+    /// Destination of a msg to a single node with the given name, of the section with the
+    /// given key.
+    pub fn node(name: XorName, section_pk: BlsPublicKey) -> Self {
+        Self::Node { name, section_pk }
+    }
+
+    /// Destination of a msg to the nodes of the section whose prefix matches the given name,
+    /// with the given key.
+    pub fn section(name: XorName, section_pk: BlsPublicKey) -> Self {
+        Self::Section { name, section_pk }
+    }
+
+    /// Destination of a msg to the end user with the given name, proxied by the Elders it's
+    /// connected to.
+    pub fn end_user(name: XorName) -> Self {
+        Self::EndUser(EndUser(name))
+    }
+
+    /// Checks a msg with the given authority can be sent to this destination.
+    ///
+    /// End users are only sent service msgs, as those are the only ones the Elders proxy to
+    /// them, so a system msg to an end user is rejected.
+    pub fn validate_for(&self, msg_kind: &AuthKind) -> Result<()> {
+        match (self, msg_kind) {
+            #[cfg(any(feature = "chunks", feature = "registers"))]
+            (Self::EndUser(_), AuthKind::Service(_)) => Ok(()),
+            (Self::EndUser(_), _) => Err(Error::InvalidDstLocation {
+                dst_location: *self,
+                reason: "only service msgs can be sent to an end user",
+            }),
+            (Self::Node { .. } | Self::Section { .. }, _) => Ok(()),
+        }
+    }
+
+    /// Checks the given system msg can be sent to this destination.
+    ///
+    /// The only system msgs end users are sent are the anti-entropy msgs bouncing their msgs
+    /// back at them, updating them on the section they sent them to, so any other system msg to
+    /// an end user is rejected.
+    pub fn validate_for_system_msg(&self, msg: &SystemMsg) -> Result<()> {
+        match (self, msg) {
+            (
+                Self::EndUser(_),
+                SystemMsg::AntiEntropyRetry { .. } | SystemMsg::AntiEntropyRedirect { .. },
+            ) => Ok(()),
+            (Self::EndUser(_), _) => Err(Error::InvalidDstLocation {
+                dst_location: *self,
+                reason: "only anti-entropy system msgs can be sent to an end user",
+            }),
+            (Self::Node { .. } | Self::Section { .. }, _) => Ok(()),
+        }
+    }
+
     /// Returns the section pk if it's not EndUser.
     pub fn section_pk(&self) -> Option<BlsPublicKey> {
         match self {
@@ -35,10 +35,16 @@ mod errors;
 mod location;
 // Message ID definition
 mod msg_id;
+// Name of the network messages are sent within
+mod network_name;
 // Types of messages and corresponding source authorities
 mod auth_kind;
 // SectionAuthorityProvider
 mod sap;
+// Domain separation of message signatures
+mod signing_domain;
+// Ids correlating the messages of a client operation
+mod trace_id;
 
 pub use self::{
     auth_kind::AuthKind,
@@ -48,6 +54,11 @@ pub use self::{
     errors::{Error, Result},
     location::{DstLocation, EndUser, SrcLocation},
     msg_id::{MsgId, MESSAGE_ID_LEN},
+    network_name::NetworkName,
     sap::SectionAuthorityProvider,
     serialisation::{MsgType, NodeMsgAuthority, WireMsg},
+    signing_domain::{
+        accept_legacy_msg_signatures, SigningDomain, SIGNING_DOMAIN_TAG, SIGNING_DOMAIN_VERSION,
+    },
+    trace_id::{TraceId, TRACE_ID_LEN},
 };
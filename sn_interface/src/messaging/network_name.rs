@@ -0,0 +1,55 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use hex_fmt::HexFmt;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use xor_name::XorName;
+
+/// Identifies the network a message was sent within, so nodes and clients of different
+/// networks (e.g. a community network and the main one) can't accidentally talk to each other.
+///
+/// It is the hash of a name shared by all the nodes and clients of a network,
+/// conventionally the hex-encoded genesis key of the network.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct NetworkName(XorName);
+
+impl NetworkName {
+    /// Derive the `NetworkName` from the name of the network
+    pub fn new(name: &str) -> Self {
+        Self(XorName::from_content(name.as_bytes()))
+    }
+
+    /// Derive the `NetworkName` from the genesis key of the network
+    pub fn from_genesis_key(genesis_key: &bls::PublicKey) -> Self {
+        Self::new(&hex::encode(genesis_key.to_bytes()))
+    }
+}
+
+impl fmt::Debug for NetworkName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NetworkName({:0.10})", HexFmt(&self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn network_name_from_genesis_key() {
+        let genesis_key = bls::SecretKey::random().public_key();
+        let name = NetworkName::from_genesis_key(&genesis_key);
+
+        assert_eq!(name, NetworkName::new(&hex::encode(genesis_key.to_bytes())));
+        assert_ne!(
+            name,
+            NetworkName::from_genesis_key(&bls::SecretKey::random().public_key())
+        );
+    }
+}
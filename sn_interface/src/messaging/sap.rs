@@ -39,6 +39,8 @@ pub struct SectionAuthorityProvider {
     pub members: BTreeMap<XorName, NodeState>,
     /// The membership generation this SAP was instantiated on
     pub membership_gen: Generation,
+    /// Number of copies of each data the section stores
+    pub data_copy_count: usize,
 }
 
 impl Borrow<Prefix> for SectionAuthorityProvider {
@@ -89,19 +89,25 @@ impl MsgType {
                 ..
             } => DKG_MSG_PRIORITY,
 
-            // Inter-node comms for AE updates
+            // Inter-node comms for AE updates, keep-alives and peer exchange
             MsgType::System {
                 msg:
                     SystemMsg::AntiEntropyRetry { .. }
                     | SystemMsg::AntiEntropyRedirect { .. }
                     | SystemMsg::AntiEntropyUpdate { .. }
-                    | SystemMsg::AntiEntropyProbe,
+                    | SystemMsg::AntiEntropyProbe
+                    | SystemMsg::KeepAlive
+                    | SystemMsg::KeepAliveResponse
+                    | SystemMsg::PeerExchange(_),
                 ..
             } => ANTIENTROPY_MSG_PRIORITY,
 
             // Join responses
             MsgType::System {
-                msg: SystemMsg::JoinResponse(_) | SystemMsg::JoinAsRelocatedResponse(_),
+                msg:
+                    SystemMsg::JoinResponse(_)
+                    | SystemMsg::JoinAsRelocatedResponse(_)
+                    | SystemMsg::ReachabilityCheckResponse(_),
                 ..
             } => JOIN_RESPONSE_PRIORITY,
 
@@ -110,9 +116,12 @@ impl MsgType {
                 msg:
                     SystemMsg::Relocate(_)
                     | SystemMsg::JoinRequest(_)
+                    | SystemMsg::ReachabilityCheck
                     | SystemMsg::JoinAsRelocatedRequest(_)
+                    | SystemMsg::HandshakeFrame(_)
                     | SystemMsg::Propose { .. }
                     | SystemMsg::StartConnectivityTest(_)
+                    | SystemMsg::FeatureActivation(_)
                     | SystemMsg::MembershipVotes(_)
                     | SystemMsg::MembershipAE(_)
                     | SystemMsg::HandoverAE(_)
@@ -138,6 +147,7 @@ impl MsgType {
                 msg:
                     SystemMsg::NodeCmd(_)
                     | SystemMsg::NodeEvent(NodeEvent::CouldNotStoreData { .. })
+                    | SystemMsg::NodeEvent(NodeEvent::DataReplicated { .. })
                     | SystemMsg::NodeQuery(_)
                     | SystemMsg::NodeQueryResponse { .. },
                 ..
@@ -146,7 +156,7 @@ impl MsgType {
             // Client <-> node service comms
             #[cfg(any(feature = "chunks", feature = "registers"))]
             MsgType::Service {
-                msg: ServiceMsg::Cmd(_),
+                msg: ServiceMsg::Cmd { .. },
                 ..
             } => SERVICE_CMD_PRIORITY,
             #[cfg(any(feature = "chunks", feature = "registers"))]
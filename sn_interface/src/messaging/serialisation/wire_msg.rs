@@ -10,8 +10,8 @@ use super::wire_msg_header::WireMsgHeader;
 use crate::messaging::{
     data::{ServiceError, ServiceMsg},
     system::SystemMsg,
-    AuthKind, AuthorityProof, DstLocation, Error, MsgId, MsgType, NodeMsgAuthority, Result,
-    ServiceAuth,
+    AuthKind, AuthorityProof, DstLocation, Error, MsgId, MsgType, NetworkName, NodeMsgAuthority,
+    Result, ServiceAuth, SigningDomain, TraceId,
 };
 use bls::PublicKey as BlsPublicKey;
 use bytes::Bytes;
@@ -68,12 +68,40 @@ impl WireMsg {
         msg_kind: AuthKind,
         dst_location: DstLocation,
     ) -> Result<Self> {
-        Ok(Self {
+        dst_location.validate_for(&msg_kind)?;
+
+        Ok(Self::new_unchecked(msg_id, payload, msg_kind, dst_location))
+    }
+
+    /// Creates a new `WireMsg` carrying the given system msg, serialised as the provided
+    /// payload, with the provided `MsgKind`.
+    ///
+    /// Unlike with `new_msg`, which only sends service msgs to end users, the anti-entropy msgs
+    /// bouncing an end user's msg back at it can be sent to it.
+    pub fn new_system_msg(
+        msg_id: MsgId,
+        msg: &SystemMsg,
+        payload: Bytes,
+        msg_kind: AuthKind,
+        dst_location: DstLocation,
+    ) -> Result<Self> {
+        dst_location.validate_for_system_msg(msg)?;
+
+        Ok(Self::new_unchecked(msg_id, payload, msg_kind, dst_location))
+    }
+
+    fn new_unchecked(
+        msg_id: MsgId,
+        payload: Bytes,
+        msg_kind: AuthKind,
+        dst_location: DstLocation,
+    ) -> Self {
+        Self {
             header: WireMsgHeader::new(msg_id, msg_kind, dst_location),
             payload,
             #[cfg(feature = "test-utils")]
             payload_debug: None,
-        })
+        }
     }
 
     /// Attempts to create an instance of WireMsg by deserialising the bytes provided.
@@ -130,9 +158,9 @@ impl WireMsg {
                     ..
                 }) = &msg
                 {
-                    AuthorityProof::verify(auth, payload)?
+                    AuthorityProof::verify_in_domain(auth, SigningDomain::Service, payload)?
                 } else {
-                    AuthorityProof::verify(auth, &self.payload)?
+                    AuthorityProof::verify_in_domain(auth, SigningDomain::Service, &self.payload)?
                 };
 
                 Ok(MsgType::Service {
@@ -149,8 +177,9 @@ impl WireMsg {
 
                 Ok(MsgType::System {
                     msg_id: self.header.msg_envelope.msg_id,
-                    msg_authority: NodeMsgAuthority::Node(AuthorityProof::verify(
+                    msg_authority: NodeMsgAuthority::Node(AuthorityProof::verify_in_domain(
                         node_signed,
+                        SigningDomain::Node,
                         &self.payload,
                     )?),
                     dst_location: self.header.msg_envelope.dst_location,
@@ -167,8 +196,9 @@ impl WireMsg {
 
                 Ok(MsgType::System {
                     msg_id: self.header.msg_envelope.msg_id,
-                    msg_authority: NodeMsgAuthority::BlsShare(AuthorityProof::verify(
+                    msg_authority: NodeMsgAuthority::BlsShare(AuthorityProof::verify_in_domain(
                         bls_share_signed,
+                        SigningDomain::Section,
                         &self.payload,
                     )?),
                     dst_location: self.header.msg_envelope.dst_location,
@@ -188,6 +218,33 @@ impl WireMsg {
         self.header.msg_envelope.msg_id = msg_id;
     }
 
+    /// Return the name of the network this message was sent within, if the sender set one
+    pub fn network_name(&self) -> Option<NetworkName> {
+        self.header.msg_envelope.network_name
+    }
+
+    /// Set the name of the network this message is sent within
+    pub fn set_network_name(&mut self, network_name: Option<NetworkName>) {
+        self.header.msg_envelope.network_name = network_name;
+    }
+
+    /// Return the trace id of the client operation this message is part of, if any
+    pub fn trace_id(&self) -> Option<TraceId> {
+        self.header.msg_envelope.trace_id
+    }
+
+    /// Set the trace id of the client operation this message is part of
+    pub fn set_trace_id(&mut self, trace_id: Option<TraceId>) {
+        self.header.msg_envelope.trace_id = trace_id;
+    }
+
+    /// Whether this message may be accepted by a node or client of the network named `ours`.
+    /// Messages are accepted by anyone when `ours` is not set, otherwise their network name
+    /// must match it.
+    pub fn is_from_network(&self, ours: Option<NetworkName>) -> bool {
+        ours.is_none() || self.network_name() == ours
+    }
+
     /// Return the kind of this message
     pub fn msg_kind(&self) -> &AuthKind {
         &self.header.msg_envelope.msg_kind
@@ -231,7 +288,9 @@ impl WireMsg {
 
     /// Convenience function which validates the signature on a ServiceMsg.
     pub fn verify_sig(auth: ServiceAuth, msg: ServiceMsg) -> Result<AuthorityProof<ServiceAuth>> {
-        Self::serialize_msg_payload(&msg).and_then(|payload| AuthorityProof::verify(auth, &payload))
+        Self::serialize_msg_payload(&msg).and_then(|payload| {
+            AuthorityProof::verify_in_domain(auth, SigningDomain::Service, &payload)
+        })
     }
 
     #[cfg(feature = "test-utils")]
@@ -252,13 +311,16 @@ mod tests {
         messaging::{
             data::{DataQuery, ServiceMsg, StorageLevel},
             system::{NodeCmd, SystemMsg},
-            AuthorityProof, MsgId, NodeAuth, ServiceAuth,
+            AuthorityProof, MsgId, NodeAuth, ServiceAuth, SigningDomain,
         },
+        network_knowledge::test_utils::{gen_section_authority_provider, section_signed},
         types::{ChunkAddress, Keypair},
     };
     use bls::SecretKey;
     use eyre::Result;
     use rand_07::rngs::OsRng;
+    use secured_linked_list::SecuredLinkedList;
+    use xor_name::Prefix;
 
     #[test]
     fn serialisation_node_msg() -> Result<()> {
@@ -268,10 +330,7 @@ mod tests {
 
         let dst_name = xor_name::rand::random();
         let dst_section_pk = SecretKey::random().public_key();
-        let dst_location = DstLocation::Node {
-            name: dst_name,
-            section_pk: dst_section_pk,
-        };
+        let dst_location = DstLocation::node(dst_name, dst_section_pk);
 
         let msg_id = MsgId::new();
         let pk = crate::types::PublicKey::Bls(dst_section_pk);
@@ -318,10 +377,7 @@ mod tests {
 
         let dst_name = xor_name::rand::random();
         let dst_section_pk = SecretKey::random().public_key();
-        let dst_location = DstLocation::Node {
-            name: dst_name,
-            section_pk: dst_section_pk,
-        };
+        let dst_location = DstLocation::node(dst_name, dst_section_pk);
 
         let msg_id = MsgId::new();
 
@@ -331,9 +387,11 @@ mod tests {
         let payload = WireMsg::serialize_msg_payload(&client_msg)?;
         let auth = ServiceAuth {
             public_key: src_client_keypair.public_key(),
-            signature: src_client_keypair.sign(&payload),
+            signature: src_client_keypair.sign(&SigningDomain::Service.signable_bytes(&payload)),
         };
-        let auth_proof = AuthorityProof::verify(auth.clone(), &payload).unwrap();
+        let auth_proof =
+            AuthorityProof::verify_in_domain(auth.clone(), SigningDomain::Service, &payload)
+                .unwrap();
 
         let msg_kind = AuthKind::Service(auth);
 
@@ -361,4 +419,175 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn system_msg_to_end_user_is_rejected() -> Result<()> {
+        let src_section_pk = SecretKey::random().public_key();
+        let src_node_keypair = ed25519_dalek::Keypair::generate(&mut OsRng);
+        let end_user = DstLocation::end_user(xor_name::rand::random());
+
+        let payload = WireMsg::serialize_msg_payload(&SystemMsg::AntiEntropyProbe)?;
+        let msg_kind = AuthKind::Node(
+            NodeAuth::authorize(src_section_pk, &src_node_keypair, &payload).into_inner(),
+        );
+        assert!(matches!(
+            WireMsg::new_msg(MsgId::new(), payload.clone(), msg_kind.clone(), end_user),
+            Err(Error::InvalidDstLocation { dst_location, .. }) if dst_location == end_user
+        ));
+
+        // the same msg can be sent to a node or a section
+        let dst_section_pk = SecretKey::random().public_key();
+        for dst_location in [
+            DstLocation::node(xor_name::rand::random(), dst_section_pk),
+            DstLocation::section(xor_name::rand::random(), dst_section_pk),
+        ] {
+            let _ = WireMsg::new_msg(
+                MsgId::new(),
+                payload.clone(),
+                msg_kind.clone(),
+                dst_location,
+            )?;
+        }
+
+        // whereas a client gets the responses to its service msgs
+        let node_keypair = Keypair::new_ed25519();
+        let client_msg =
+            ServiceMsg::Query(DataQuery::GetChunk(ChunkAddress(xor_name::rand::random())));
+        let payload = WireMsg::serialize_msg_payload(&client_msg)?;
+        let auth = ServiceAuth {
+            public_key: node_keypair.public_key(),
+            signature: node_keypair.sign(&SigningDomain::Service.signable_bytes(&payload)),
+        };
+        let _ = WireMsg::new_msg(MsgId::new(), payload, AuthKind::Service(auth), end_user)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn ae_retry_to_end_user_is_accepted() -> Result<()> {
+        let (section_auth, _, sk_set) =
+            gen_section_authority_provider(Prefix::default(), crate::elder_count());
+        let signed_sap = section_signed(sk_set.secret_key(), section_auth.to_msg())?;
+        let src_section_pk = sk_set.public_keys().public_key();
+        let src_node_keypair = ed25519_dalek::Keypair::generate(&mut OsRng);
+        let end_user = DstLocation::end_user(xor_name::rand::random());
+
+        // a client whose msg was bounced is told about the section it sent it to
+        let ae_retry = SystemMsg::AntiEntropyRetry {
+            section_auth: signed_sap.value,
+            section_signed: signed_sap.sig,
+            proof_chain: SecuredLinkedList::new(src_section_pk),
+            bounced_msg: Bytes::from_static(b"bounced msg"),
+        };
+        let payload = WireMsg::serialize_msg_payload(&ae_retry)?;
+        let msg_kind = AuthKind::Node(
+            NodeAuth::authorize(src_section_pk, &src_node_keypair, &payload).into_inner(),
+        );
+        let wire_msg =
+            WireMsg::new_system_msg(MsgId::new(), &ae_retry, payload, msg_kind, end_user)?;
+        assert_eq!(wire_msg.dst_location(), &end_user);
+
+        // whereas the other system msgs are only sent to nodes
+        let probe = SystemMsg::AntiEntropyProbe;
+        let payload = WireMsg::serialize_msg_payload(&probe)?;
+        let msg_kind = AuthKind::Node(
+            NodeAuth::authorize(src_section_pk, &src_node_keypair, &payload).into_inner(),
+        );
+        let result = WireMsg::new_system_msg(MsgId::new(), &probe, payload, msg_kind, end_user);
+        assert!(matches!(
+            result,
+            Err(Error::InvalidDstLocation { dst_location, .. }) if dst_location == end_user
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn signature_from_another_domain_is_rejected() -> Result<()> {
+        let src_client_keypair = Keypair::new_ed25519();
+        let dst_location =
+            DstLocation::node(xor_name::rand::random(), SecretKey::random().public_key());
+
+        let client_msg =
+            ServiceMsg::Query(DataQuery::GetChunk(ChunkAddress(xor_name::rand::random())));
+        let payload = WireMsg::serialize_msg_payload(&client_msg)?;
+
+        // a signature over the payload within the Node domain mustn't be valid as a Service one
+        let auth = ServiceAuth {
+            public_key: src_client_keypair.public_key(),
+            signature: src_client_keypair.sign(&SigningDomain::Node.signable_bytes(&payload)),
+        };
+        let wire_msg =
+            WireMsg::new_msg(MsgId::new(), payload, AuthKind::Service(auth), dst_location)?;
+
+        assert!(matches!(
+            WireMsg::from(wire_msg.serialize()?)?.into_msg(),
+            Err(Error::InvalidSignature)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn network_name_is_carried_in_header() -> Result<()> {
+        let src_client_keypair = Keypair::new_ed25519();
+        let dst_location =
+            DstLocation::node(xor_name::rand::random(), SecretKey::random().public_key());
+
+        let client_msg =
+            ServiceMsg::Query(DataQuery::GetChunk(ChunkAddress(xor_name::rand::random())));
+        let payload = WireMsg::serialize_msg_payload(&client_msg)?;
+        let auth = ServiceAuth {
+            public_key: src_client_keypair.public_key(),
+            signature: src_client_keypair.sign(&SigningDomain::Service.signable_bytes(&payload)),
+        };
+        let mut wire_msg =
+            WireMsg::new_msg(MsgId::new(), payload, AuthKind::Service(auth), dst_location)?;
+
+        let ours = NetworkName::new("comnet");
+        let theirs = NetworkName::new("main");
+
+        // unnamed messages are only accepted when we have no network name set
+        let deserialized = WireMsg::from(wire_msg.serialize()?)?;
+        assert_eq!(deserialized.network_name(), None);
+        assert!(deserialized.is_from_network(None));
+        assert!(!deserialized.is_from_network(Some(ours)));
+
+        wire_msg.set_network_name(Some(ours));
+        let deserialized = WireMsg::from(wire_msg.serialize()?)?;
+        assert_eq!(deserialized, wire_msg);
+        assert_eq!(deserialized.network_name(), Some(ours));
+        assert!(deserialized.is_from_network(Some(ours)));
+        assert!(!deserialized.is_from_network(Some(theirs)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn trace_id_is_carried_in_header() -> Result<()> {
+        let src_client_keypair = Keypair::new_ed25519();
+        let dst_location =
+            DstLocation::node(xor_name::rand::random(), SecretKey::random().public_key());
+
+        let client_msg =
+            ServiceMsg::Query(DataQuery::GetChunk(ChunkAddress(xor_name::rand::random())));
+        let payload = WireMsg::serialize_msg_payload(&client_msg)?;
+        let auth = ServiceAuth {
+            public_key: src_client_keypair.public_key(),
+            signature: src_client_keypair.sign(&SigningDomain::Service.signable_bytes(&payload)),
+        };
+        let mut wire_msg =
+            WireMsg::new_msg(MsgId::new(), payload, AuthKind::Service(auth), dst_location)?;
+        assert_eq!(WireMsg::from(wire_msg.serialize()?)?.trace_id(), None);
+
+        let trace_id = TraceId::random();
+        wire_msg.set_trace_id(Some(trace_id));
+        let deserialized = WireMsg::from(wire_msg.serialize()?)?;
+        assert_eq!(deserialized, wire_msg);
+        assert_eq!(deserialized.trace_id(), Some(trace_id));
+        // the trace id is not signed, so setting it keeps the msg valid
+        assert!(deserialized.into_msg().is_ok());
+
+        Ok(())
+    }
 }
@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::messaging::{AuthKind, DstLocation, Error, MsgId, Result};
+use crate::messaging::{AuthKind, DstLocation, Error, MsgId, NetworkName, Result, TraceId};
 use bincode::{
     config::{BigEndian, FixintEncoding, WithOtherEndian, WithOtherIntEncoding},
     Options,
@@ -31,7 +31,9 @@ pub struct WireMsgHeader {
 }
 
 // The message envelope contains the ID of the message, the authority
-// signing this message (if any), as well as destination information
+// signing this message (if any), destination information, as well as
+// the name of the network it was sent within (if the sender has one set)
+// and the trace id of the client operation it is part of (if any).
 // This is all part of the message header, and it gets deserialized
 // when the `WireMsgHeader` is deserialized, allowing the caller to read
 // all this information before deciding to deserialise the actual message payload.
@@ -40,6 +42,10 @@ pub struct MsgEnvelope {
     pub msg_id: MsgId,
     pub msg_kind: AuthKind,
     pub dst_location: DstLocation,
+    #[serde(default)]
+    pub network_name: Option<NetworkName>,
+    #[serde(default)]
+    pub trace_id: Option<TraceId>,
 }
 
 // The first two fields in the header. This is not part of the public interface.
@@ -81,6 +87,8 @@ impl WireMsgHeader {
                 msg_id,
                 msg_kind,
                 dst_location,
+                network_name: None,
+                trace_id: None,
             },
         }
     }
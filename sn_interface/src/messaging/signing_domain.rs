@@ -0,0 +1,114 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use lazy_static::lazy_static;
+
+/// Tag prepended to every message payload before it is signed.
+pub const SIGNING_DOMAIN_TAG: &[u8] = b"SN_MSG";
+
+/// Version of the domain separation scheme.
+/// Bumping it invalidates all signatures made with a previous version.
+pub const SIGNING_DOMAIN_VERSION: u16 = 1;
+
+// Env var which, when set to `true`, makes the verification fall back to the raw
+// (legacy) payload when the domain separated one doesn't match the signature.
+const SN_ACCEPT_LEGACY_MSG_SIGNATURES: &str = "SN_ACCEPT_LEGACY_MSG_SIGNATURES";
+
+lazy_static! {
+    static ref ACCEPT_LEGACY_MSG_SIGNATURES: bool = read_accept_legacy_msg_signatures();
+}
+
+fn read_accept_legacy_msg_signatures() -> bool {
+    match std::env::var(SN_ACCEPT_LEGACY_MSG_SIGNATURES) {
+        Ok(value) => match value.parse() {
+            Ok(accept) => {
+                warn!(
+                    "Legacy msg signatures acceptance set from env var {}: {}",
+                    SN_ACCEPT_LEGACY_MSG_SIGNATURES, accept
+                );
+                accept
+            }
+            Err(error) => {
+                warn!("There was an error parsing {:?} env var, legacy msg signatures won't be accepted: {:?}", SN_ACCEPT_LEGACY_MSG_SIGNATURES, error);
+                false
+            }
+        },
+        Err(_) => false,
+    }
+}
+
+/// Returns whether signatures made over the raw message payload (i.e. without domain
+/// separation) are still accepted. This is only meant to be enabled while a network is
+/// being upgraded, and can be turned on with the `SN_ACCEPT_LEGACY_MSG_SIGNATURES` env var.
+pub fn accept_legacy_msg_signatures() -> bool {
+    *ACCEPT_LEGACY_MSG_SIGNATURES
+}
+
+/// The domain a message signature is bound to.
+///
+/// The bytes actually signed are `"SN_MSG" || msg_type || version || payload`, so a signature
+/// produced for one kind of message (or for anything else than a message, e.g. a register op)
+/// cannot be replayed as the authority of a different kind of message.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum SigningDomain {
+    /// Messages signed by clients, or by nodes on behalf of the client-facing service.
+    Service,
+    /// Messages signed by a single node with its Ed25519 key.
+    Node,
+    /// Messages signed by Elders with their BLS key share, as well as
+    /// the section signature aggregated from those shares.
+    Section,
+}
+
+impl SigningDomain {
+    fn msg_type(&self) -> u8 {
+        match self {
+            Self::Service => 0,
+            Self::Node => 1,
+            Self::Section => 2,
+        }
+    }
+
+    /// Returns the domain separated bytes to be signed, and verified, for the given payload.
+    pub fn signable_bytes(&self, payload: impl AsRef<[u8]>) -> Vec<u8> {
+        let payload = payload.as_ref();
+        let mut bytes = Vec::with_capacity(
+            SIGNING_DOMAIN_TAG.len() + 1 + std::mem::size_of::<u16>() + payload.len(),
+        );
+        bytes.extend_from_slice(SIGNING_DOMAIN_TAG);
+        bytes.push(self.msg_type());
+        bytes.extend_from_slice(&SIGNING_DOMAIN_VERSION.to_be_bytes());
+        bytes.extend_from_slice(payload);
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signable_bytes_layout() {
+        let bytes = SigningDomain::Node.signable_bytes(b"payload");
+        assert_eq!(&bytes[..6], b"SN_MSG");
+        assert_eq!(bytes[6], 1);
+        assert_eq!(&bytes[7..9], &SIGNING_DOMAIN_VERSION.to_be_bytes());
+        assert_eq!(&bytes[9..], b"payload");
+    }
+
+    #[test]
+    fn domains_do_not_collide() {
+        let payload = b"same payload";
+        let service = SigningDomain::Service.signable_bytes(payload);
+        let node = SigningDomain::Node.signable_bytes(payload);
+        let section = SigningDomain::Section.signable_bytes(payload);
+        assert_ne!(service, node);
+        assert_ne!(node, section);
+        assert_ne!(service, section);
+    }
+}
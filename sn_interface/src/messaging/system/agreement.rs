@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{KeyedSig, NodeState};
+use super::{FeatureActivation, KeyedSig, NodeState};
 use crate::{messaging::SectionAuthorityProvider, types::Peer};
 use ed25519_dalek::{PublicKey, Signature};
 use itertools::Itertools;
@@ -37,6 +37,8 @@ pub struct DkgSessionId {
     pub bootstrap_members: BTreeSet<NodeState>,
     /// The membership generation this SAP was instantiated at
     pub membership_gen: Generation,
+    /// Number of copies of each data the section is to store
+    pub data_copy_count: usize,
 }
 
 impl DkgSessionId {
@@ -60,6 +62,8 @@ impl DkgSessionId {
         for member in self.bootstrap_members.iter() {
             hasher.update(&member.name);
         }
+
+        hasher.update(&(self.data_copy_count as u64).to_le_bytes());
     }
 
     pub fn elder_names(&self) -> impl Iterator<Item = XorName> + '_ {
@@ -173,4 +177,9 @@ pub enum Proposal {
     NewElders(SectionAuth<SectionAuthorityProvider>),
     /// Proposal to change whether new nodes are allowed to join our section.
     JoinsAllowed(bool),
+    /// Proposal to change the number of copies of each data our section stores.
+    /// It's applied with the next SAP, which is generated right away.
+    DataCopyCount(usize),
+    /// Proposal to activate a feature, supported by enough of the members of our section.
+    ActivateFeature(FeatureActivation),
 }
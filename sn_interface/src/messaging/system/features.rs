@@ -0,0 +1,240 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use serde::{Deserialize, Serialize};
+use std::{fmt, ops::BitOr};
+
+/// Version of the protocol nodes speak when joining a section, bumped whenever it changes in a
+/// way nodes of the previous versions can't follow.
+pub const PROTOCOL_VERSION: u16 = 1;
+
+/// Oldest version of the protocol Elders let nodes join with. Nodes which didn't advertise a
+/// protocol version speak version 0.
+pub const MIN_PROTOCOL_VERSION: u16 = 1;
+
+/// Version of the binary a node runs, advertised when it joins a section.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub struct NodeVersion {
+    /// Major version
+    pub major: u16,
+    /// Minor version
+    pub minor: u16,
+    /// Patch version
+    pub patch: u16,
+}
+
+impl NodeVersion {
+    /// Create a `NodeVersion`
+    pub const fn new(major: u16, minor: u16, patch: u16) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse a `major.minor.patch` version, as crates are versioned. Any pre-release or build
+    /// metadata suffix is ignored.
+    pub fn parse(version: &str) -> Option<Self> {
+        let version = version.split(|c| c == '-' || c == '+').next()?;
+        let mut numbers = version.split('.').map(|number| number.parse::<u16>().ok());
+        let major = numbers.next()??;
+        let minor = numbers.next()??;
+        let patch = numbers.next()??;
+        if numbers.next().is_some() {
+            return None;
+        }
+        Some(Self::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for NodeVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Optional parts of the protocol a node supports, advertised along with its protocol version
+/// when joining a section. Bits unknown to a node are kept as they are, for nodes of newer
+/// versions to advertise capabilities older ones just don't rely on.
+#[derive(Clone, Copy, Default, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct Capabilities(u32);
+
+impl Capabilities {
+    /// Sends handshake msgs too large for a single msg as `HandshakeFrame`s.
+    pub const HANDSHAKE_FRAMES: Self = Self(1);
+    /// Attests it listens at the address it joins from, answering `JoinResponse::AddrChallenge`.
+    pub const ADDR_ATTESTATION: Self = Self(1 << 1);
+
+    /// Capabilities Elders require of the nodes joining their section.
+    pub const REQUIRED: Self = Self::ADDR_ATTESTATION;
+
+    // Names of the known capabilities, for them to be listed when debugging
+    const NAMES: &'static [(Self, &'static str)] = &[
+        (Self::HANDSHAKE_FRAMES, "HANDSHAKE_FRAMES"),
+        (Self::ADDR_ATTESTATION, "ADDR_ATTESTATION"),
+    ];
+
+    /// No capabilities, as advertised by nodes predating them.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// The capabilities of this version.
+    pub const fn ours() -> Self {
+        Self::HANDSHAKE_FRAMES.union(Self::ADDR_ATTESTATION)
+    }
+
+    /// The capabilities of either.
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// The capabilities of both, i.e. the ones to be relied on between two nodes.
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// The capabilities of `self` which `other` lacks.
+    pub const fn difference(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    /// Whether all the capabilities of `other` are in `self`.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether there are no capabilities.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for Capabilities {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
+
+impl fmt::Debug for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut set = f.debug_set();
+        let mut unknown = *self;
+        for (capability, name) in Self::NAMES {
+            if self.contains(*capability) {
+                let _ = set.entry(&format_args!("{}", name));
+                unknown = unknown.difference(*capability);
+            }
+        }
+        if !unknown.is_empty() {
+            let _ = set.entry(&format_args!("{:#x}", unknown.0));
+        }
+        set.finish()
+    }
+}
+
+/// Protocol features which nodes only make use of once the Elders of their section agreed
+/// enough of its members run a version supporting them, so nodes of older versions keep
+/// working alongside them during an upgrade.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum Feature {
+    /// Adults confirm storing the data of client cmds with the `Replicated` ack level.
+    ReplicationConfirmation,
+}
+
+impl Feature {
+    /// All the features which can be activated.
+    pub const ALL: &'static [Feature] = &[Feature::ReplicationConfirmation];
+
+    /// The first node version supporting the feature.
+    pub const fn min_version(&self) -> NodeVersion {
+        match self {
+            Self::ReplicationConfirmation => NodeVersion::new(0, 62, 1),
+        }
+    }
+
+    /// Whether a node of the given version supports the feature. Nodes which didn't advertise
+    /// their version are older than any feature.
+    pub fn is_supported_by(&self, version: Option<NodeVersion>) -> bool {
+        version.map_or(false, |version| version >= self.min_version())
+    }
+}
+
+/// Activation of a feature by a section, agreed by its Elders.
+///
+/// The feature takes effect once the section reaches the given generation, i.e. the length of
+/// its section chain, so all its members switch to it upon the same change of section key.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub struct FeatureActivation {
+    /// The feature activated
+    pub feature: Feature,
+    /// The generation of the section the feature takes effect at
+    pub generation: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_version_is_parsed_from_crate_versions() {
+        assert_eq!(
+            NodeVersion::parse("0.62.1"),
+            Some(NodeVersion::new(0, 62, 1))
+        );
+        assert_eq!(
+            NodeVersion::parse("1.2.3-alpha.1+build"),
+            Some(NodeVersion::new(1, 2, 3))
+        );
+        assert_eq!(NodeVersion::parse("1.2"), None);
+        assert_eq!(NodeVersion::parse("1.2.3.4"), None);
+        assert_eq!(NodeVersion::parse("1.x.3"), None);
+
+        assert_eq!(NodeVersion::new(0, 62, 1).to_string(), "0.62.1");
+    }
+
+    #[test]
+    fn capabilities_are_combined_as_sets() {
+        let ours = Capabilities::ours();
+        let older = Capabilities::ADDR_ATTESTATION;
+        let newer = Capabilities(ours.0 | 1 << 31);
+
+        assert!(ours.contains(Capabilities::REQUIRED));
+        assert!(!Capabilities::empty().contains(Capabilities::REQUIRED));
+        assert_eq!(ours.intersection(older), older);
+        assert_eq!(ours.intersection(newer), ours);
+        assert_eq!(
+            Capabilities::REQUIRED.difference(Capabilities::HANDSHAKE_FRAMES),
+            Capabilities::ADDR_ATTESTATION
+        );
+        assert!(Capabilities::REQUIRED.difference(ours).is_empty());
+        assert_eq!(
+            Capabilities::HANDSHAKE_FRAMES | Capabilities::ADDR_ATTESTATION,
+            ours
+        );
+
+        assert_eq!(
+            format!("{:?}", newer),
+            "{HANDSHAKE_FRAMES, ADDR_ATTESTATION, 0x80000000}"
+        );
+    }
+
+    #[test]
+    fn features_are_supported_from_their_min_version() {
+        let feature = Feature::ReplicationConfirmation;
+        let min = feature.min_version();
+
+        assert!(feature.is_supported_by(Some(min)));
+        assert!(feature.is_supported_by(Some(NodeVersion::new(min.major + 1, 0, 0))));
+        assert!(!feature.is_supported_by(Some(NodeVersion::new(0, min.minor - 1, 9))));
+        assert!(!feature.is_supported_by(None));
+    }
+}
@@ -0,0 +1,426 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::SystemMsg;
+use crate::messaging::{Error, MsgId, Result, WireMsg};
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+use xor_name::XorName;
+
+/// Max size in bytes of the part of a handshake msg carried by a single frame, so that it's
+/// delivered whole by transports limited to small msgs.
+pub const MAX_HANDSHAKE_FRAME_SIZE: usize = 8 * 1024;
+
+/// Max size in bytes of a handshake msg once its frames are put back together. Larger handshakes
+/// are rejected rather than truncated.
+pub const MAX_HANDSHAKE_SIZE: usize = 256 * 1024;
+
+/// How long the frames of a handshake are held on to, waiting for the rest of them.
+pub const HANDSHAKE_ASSEMBLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+// Max number of handshakes being put back together at once, the oldest being dropped beyond it
+const MAX_PENDING_HANDSHAKES: usize = 100;
+
+// Max number of handshakes being put back together at once for the same peer, its oldest being
+// dropped beyond it, so a single peer can't crowd the others out
+const MAX_PENDING_HANDSHAKES_PER_PEER: usize = 2;
+
+/// A part of a handshake msg, such as a `JoinRequest`, too large to be sent whole.
+#[derive(Clone, PartialEq, Serialize, Deserialize, custom_debug::Debug)]
+pub struct HandshakeFrame {
+    /// Id of the handshake, shared by all its frames
+    pub handshake_id: MsgId,
+    /// Index of the frame within the handshake, starting from 0
+    pub index: u32,
+    /// Continuation marker, set on all the frames of the handshake but the last one
+    pub more: bool,
+    /// Part of the serialised handshake msg
+    #[debug(skip)]
+    pub payload: Bytes,
+}
+
+impl HandshakeFrame {
+    /// Splits the serialised handshake msg into frames carrying at most `max_frame_size` bytes
+    /// of it each. A handshake over [`MAX_HANDSHAKE_SIZE`] is an error, as it would be rejected.
+    pub fn split(
+        handshake_id: MsgId,
+        handshake: &[u8],
+        max_frame_size: usize,
+    ) -> Result<Vec<Self>> {
+        if handshake.len() > MAX_HANDSHAKE_SIZE {
+            return Err(Error::HandshakeTooLarge(handshake.len()));
+        }
+
+        let max_frame_size = max_frame_size.max(1);
+        let count = ((handshake.len() + max_frame_size - 1) / max_frame_size).max(1);
+
+        Ok((0..count)
+            .map(|index| {
+                let start = index * max_frame_size;
+                let end = handshake.len().min(start + max_frame_size);
+                Self {
+                    handshake_id,
+                    index: index as u32,
+                    more: index + 1 < count,
+                    payload: Bytes::copy_from_slice(&handshake[start.min(end)..end]),
+                }
+            })
+            .collect())
+    }
+}
+
+/// Msgs to send for the given handshake msg: the msg itself when it fits in a single frame of
+/// `max_frame_size` bytes, or the `SystemMsg::HandshakeFrame`s it's split into otherwise.
+pub fn handshake_msgs(msg: SystemMsg, max_frame_size: usize) -> Result<Vec<SystemMsg>> {
+    let payload = WireMsg::serialize_msg_payload(&msg)?;
+    if payload.len() <= max_frame_size {
+        return Ok(vec![msg]);
+    }
+
+    Ok(
+        HandshakeFrame::split(MsgId::new(), &payload, max_frame_size)?
+            .into_iter()
+            .map(SystemMsg::HandshakeFrame)
+            .collect(),
+    )
+}
+
+/// Puts the frames of the handshake msgs received from peers back together.
+///
+/// Frames of a handshake may arrive in any order, and are held on to by index until they're all
+/// in. A frame past the last one, an empty one, or one taking the handshake over
+/// [`MAX_HANDSHAKE_SIZE`], gets the whole handshake dropped, for the peer to start over.
+#[derive(Debug, Default)]
+pub struct HandshakeAssembler {
+    pending: BTreeMap<(XorName, MsgId), PendingHandshake>,
+}
+
+#[derive(Debug)]
+struct PendingHandshake {
+    frames: BTreeMap<u32, Bytes>,
+    size: usize,
+    last_index: Option<u32>,
+    started_at: Instant,
+}
+
+impl PendingHandshake {
+    fn new() -> Self {
+        Self {
+            frames: BTreeMap::new(),
+            size: 0,
+            last_index: None,
+            started_at: Instant::now(),
+        }
+    }
+
+    // Adds the frame, returning whether all the frames of the handshake are in
+    fn add(&mut self, frame: HandshakeFrame) -> Result<bool> {
+        let past_last = if frame.more {
+            self.last_index
+                .map(|last| frame.index >= last)
+                .unwrap_or(false)
+        } else {
+            let last = self.last_index.unwrap_or(frame.index);
+            let highest = self.frames.keys().next_back().copied().unwrap_or(0);
+            frame.index != last || highest > frame.index
+        };
+        // only a handshake of a single frame may carry nothing, so the size bounds the frames
+        let empty = frame.payload.is_empty() && (frame.index > 0 || frame.more);
+        if past_last || empty {
+            return Err(Error::UnexpectedHandshakeFrame {
+                last: self.last_index.unwrap_or(u32::MAX),
+                received: frame.index,
+            });
+        }
+
+        if !frame.more {
+            self.last_index = Some(frame.index);
+        }
+        if !self.frames.contains_key(&frame.index) {
+            self.size += frame.payload.len();
+            if self.size > MAX_HANDSHAKE_SIZE {
+                return Err(Error::HandshakeTooLarge(self.size));
+            }
+            let _ = self.frames.insert(frame.index, frame.payload);
+        }
+
+        Ok(self
+            .last_index
+            .map(|last| self.frames.len() as u64 == u64::from(last) + 1)
+            .unwrap_or(false))
+    }
+}
+
+impl HandshakeAssembler {
+    /// Adds a frame received from the peer with the given name. Returns the handshake msg, still
+    /// serialised, once its last frame was added.
+    pub fn add_frame(&mut self, sender: XorName, frame: HandshakeFrame) -> Result<Option<Bytes>> {
+        self.pending
+            .retain(|_, pending| pending.started_at.elapsed() < HANDSHAKE_ASSEMBLY_TIMEOUT);
+
+        let key = (sender, frame.handshake_id);
+        let mut pending = match self.pending.remove(&key) {
+            Some(pending) => pending,
+            None => {
+                self.make_room_for(sender);
+                PendingHandshake::new()
+            }
+        };
+
+        if !pending.add(frame)? {
+            let _ = self.pending.insert(key, pending);
+            return Ok(None);
+        }
+
+        let mut handshake = Vec::with_capacity(pending.size);
+        for payload in pending.frames.values() {
+            handshake.extend_from_slice(payload);
+        }
+
+        Ok(Some(Bytes::from(handshake)))
+    }
+
+    // Drops the oldest handshake of the peer if it has too many of them pending, then the oldest
+    // of all if there are too many pending overall, before a new one from the peer is added
+    fn make_room_for(&mut self, sender: XorName) {
+        let of_sender = self
+            .pending
+            .iter()
+            .filter(|((name, _), _)| *name == sender)
+            .count();
+        if of_sender >= MAX_PENDING_HANDSHAKES_PER_PEER {
+            self.drop_oldest(|name| *name == sender);
+        }
+        if self.pending.len() >= MAX_PENDING_HANDSHAKES {
+            self.drop_oldest(|_| true);
+        }
+    }
+
+    fn drop_oldest(&mut self, of: impl Fn(&XorName) -> bool) {
+        let oldest = self
+            .pending
+            .iter()
+            .filter(|((name, _), _)| of(name))
+            .min_by_key(|(_, pending)| pending.started_at)
+            .map(|(key, _)| *key);
+        if let Some(oldest) = oldest {
+            let _ = self.pending.remove(&oldest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        handshake_msgs, HandshakeAssembler, HandshakeFrame, MAX_HANDSHAKE_FRAME_SIZE,
+        MAX_HANDSHAKE_SIZE, MAX_PENDING_HANDSHAKES_PER_PEER,
+    };
+    use crate::messaging::{
+        system::{Capabilities, JoinRequest, ResourceProofResponse, SystemMsg, PROTOCOL_VERSION},
+        Error, MsgId, WireMsg,
+    };
+
+    use bytes::Bytes;
+    use ed25519_dalek::Signature;
+    use eyre::{eyre, Result};
+    use std::collections::VecDeque;
+
+    fn reassemble(frames: Vec<HandshakeFrame>) -> Result<Bytes> {
+        let mut assembler = HandshakeAssembler::default();
+        let sender = xor_name::rand::random();
+        let count = frames.len();
+        for (index, frame) in frames.into_iter().enumerate() {
+            match assembler.add_frame(sender, frame)? {
+                Some(handshake) if index + 1 == count => return Ok(handshake),
+                Some(_) => return Err(eyre!("handshake put together before its last frame")),
+                None => {}
+            }
+        }
+        Err(eyre!("handshake not put together after its last frame"))
+    }
+
+    #[test]
+    fn handshakes_are_put_back_together_at_boundary_sizes() -> Result<()> {
+        let max_frame_size = 16;
+        for size in [
+            0,
+            1,
+            max_frame_size - 1,
+            max_frame_size,
+            max_frame_size + 1,
+            100,
+        ] {
+            let handshake: Vec<u8> = (0..size).map(|byte| byte as u8).collect();
+            let frames = HandshakeFrame::split(MsgId::new(), &handshake, max_frame_size)?;
+
+            assert_eq!(
+                frames.len(),
+                ((size + max_frame_size - 1) / max_frame_size).max(1)
+            );
+            assert!(frames
+                .iter()
+                .all(|frame| frame.payload.len() <= max_frame_size));
+            assert!(frames[..frames.len() - 1].iter().all(|frame| frame.more));
+            assert!(!frames[frames.len() - 1].more);
+
+            assert_eq!(reassemble(frames)?, handshake);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn handshakes_over_the_max_size_are_rejected_not_truncated() -> Result<()> {
+        let handshake = vec![0; MAX_HANDSHAKE_SIZE + 1];
+        assert!(matches!(
+            HandshakeFrame::split(MsgId::new(), &handshake, MAX_HANDSHAKE_FRAME_SIZE),
+            Err(Error::HandshakeTooLarge(_))
+        ));
+
+        // a peer could still send more frames than the max size allows
+        let handshake_id = MsgId::new();
+        let mut frames = HandshakeFrame::split(handshake_id, &handshake[1..], 1024)?;
+        if let Some(last) = frames.last_mut() {
+            last.more = true;
+        }
+        frames.push(HandshakeFrame {
+            handshake_id,
+            index: frames.len() as u32,
+            more: false,
+            payload: Bytes::from_static(&[0]),
+        });
+        assert!(matches!(
+            reassemble(frames),
+            Err(error) if matches!(
+                error.downcast_ref::<Error>(),
+                Some(Error::HandshakeTooLarge(size)) if *size == MAX_HANDSHAKE_SIZE + 1
+            )
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn frames_out_of_order_are_put_back_together() -> Result<()> {
+        let handshake: Vec<u8> = (0..64).collect();
+        let frames = HandshakeFrame::split(MsgId::new(), &handshake, 16)?;
+        let sender = xor_name::rand::random();
+        let mut assembler = HandshakeAssembler::default();
+
+        // the last frame first, then the others, one of them twice
+        assert_eq!(assembler.add_frame(sender, frames[3].clone())?, None);
+        assert_eq!(assembler.add_frame(sender, frames[1].clone())?, None);
+        assert_eq!(assembler.add_frame(sender, frames[1].clone())?, None);
+        assert_eq!(assembler.add_frame(sender, frames[0].clone())?, None);
+        assert_eq!(
+            assembler.add_frame(sender, frames[2].clone())?,
+            Some(handshake.into())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn frames_past_the_last_one_drop_the_handshake() -> Result<()> {
+        let handshake = vec![7; 64];
+        let mut frames = HandshakeFrame::split(MsgId::new(), &handshake, 16)?;
+        let sender = xor_name::rand::random();
+        let mut assembler = HandshakeAssembler::default();
+
+        assert_eq!(assembler.add_frame(sender, frames[2].clone())?, None);
+        let mut past_last = frames[3].clone();
+        past_last.index = 1;
+        assert!(matches!(
+            assembler.add_frame(sender, past_last),
+            Err(Error::UnexpectedHandshakeFrame { received: 1, .. })
+        ));
+        // the handshake was dropped, so its earlier frames are gone
+        for frame in frames.iter().filter(|frame| frame.index != 2) {
+            assert_eq!(assembler.add_frame(sender, frame.clone())?, None);
+        }
+
+        // frames of the same handshake from another peer are put together apart
+        let other_sender = xor_name::rand::random();
+        let last = frames.pop();
+        for frame in frames {
+            assert_eq!(assembler.add_frame(other_sender, frame)?, None);
+        }
+        let last = last.ok_or_else(|| eyre!("no frames"))?;
+        assert_eq!(
+            assembler.add_frame(other_sender, last)?,
+            Some(handshake.into())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn a_peer_only_has_a_few_handshakes_pending() -> Result<()> {
+        let handshake = vec![7; 32];
+        let sender = xor_name::rand::random();
+        let mut assembler = HandshakeAssembler::default();
+
+        let first = HandshakeFrame::split(MsgId::new(), &handshake, 16)?;
+        assert_eq!(assembler.add_frame(sender, first[0].clone())?, None);
+        for _ in 0..MAX_PENDING_HANDSHAKES_PER_PEER {
+            let other = HandshakeFrame::split(MsgId::new(), &handshake, 16)?;
+            assert_eq!(assembler.add_frame(sender, other[0].clone())?, None);
+        }
+
+        // the oldest handshake of the peer was dropped, so its last frame is all there is of it
+        assert_eq!(assembler.add_frame(sender, first[1].clone())?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn only_handshakes_over_a_frame_are_split() -> Result<()> {
+        let join_request = SystemMsg::JoinRequest(Box::new(JoinRequest {
+            section_key: bls::SecretKey::random().public_key(),
+            resource_proof_response: Some(ResourceProofResponse {
+                solution: 0,
+                data: VecDeque::from(vec![0; 4 * MAX_HANDSHAKE_FRAME_SIZE]),
+                nonce: [0; 32],
+                nonce_signature: Signature::from_bytes(&[0; 64])?,
+                reachability_attestation: None,
+            }),
+            genesis_key: bls::SecretKey::random().public_key(),
+            version: None,
+            protocol_version: PROTOCOL_VERSION,
+            capabilities: Capabilities::ours(),
+        }));
+        let payload = WireMsg::serialize_msg_payload(&join_request)?;
+
+        assert_eq!(
+            handshake_msgs(join_request.clone(), payload.len())?,
+            vec![join_request.clone()]
+        );
+
+        let msgs = handshake_msgs(join_request.clone(), MAX_HANDSHAKE_FRAME_SIZE)?;
+        assert_eq!(
+            msgs.len(),
+            (payload.len() + MAX_HANDSHAKE_FRAME_SIZE - 1) / MAX_HANDSHAKE_FRAME_SIZE
+        );
+        let frames = msgs
+            .into_iter()
+            .map(|msg| match msg {
+                SystemMsg::HandshakeFrame(frame) => Ok(frame),
+                other => Err(eyre!("unexpected msg: {:?}", other)),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let reassembled: SystemMsg = rmp_serde::from_slice(&reassemble(frames)?)?;
+        assert_eq!(reassembled, join_request);
+
+        Ok(())
+    }
+}
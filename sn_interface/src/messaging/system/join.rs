@@ -6,13 +6,22 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{agreement::SectionAuth, KeyedSig, NodeState};
+use super::{
+    agreement::SectionAuth, Capabilities, FeatureActivation, KeyedSig, NodeState, NodeVersion,
+    MIN_PROTOCOL_VERSION,
+};
 use crate::messaging::SectionAuthorityProvider;
+use crate::types::keys::ed25519;
 use bls::PublicKey as BlsPublicKey;
-use ed25519_dalek::Signature;
+use ed25519_dalek::{Keypair, Signature, Verifier};
 use secured_linked_list::SecuredLinkedList;
 use serde::{Deserialize, Serialize};
-use std::{collections::VecDeque, net::SocketAddr};
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display, Formatter},
+    net::SocketAddr,
+};
+use xor_name::XorName;
 
 /// Request to join a section
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -21,6 +30,35 @@ pub struct JoinRequest {
     pub section_key: BlsPublicKey,
     /// Proof of the resource proofing.
     pub resource_proof_response: Option<ResourceProofResponse>,
+    /// Genesis key of the network the peer is trying to join.
+    pub genesis_key: BlsPublicKey,
+    /// Version of the peer's binary, for the section to know which features it supports.
+    #[serde(default)]
+    pub version: Option<NodeVersion>,
+    /// Version of the protocol the peer speaks, `0` for peers predating protocol versions.
+    #[serde(default)]
+    pub protocol_version: u16,
+    /// Optional parts of the protocol the peer supports.
+    #[serde(default)]
+    pub capabilities: Capabilities,
+}
+
+impl JoinRequest {
+    /// Checks the peer speaks a version of the protocol we accept and has the capabilities we
+    /// require, returning the capabilities both sides support, which are the only ones to be
+    /// relied on with the peer. Peers of newer versions are to speak ours.
+    pub fn negotiate_protocol(&self) -> Result<Capabilities, JoinRejectionReason> {
+        let missing = Capabilities::REQUIRED.difference(self.capabilities);
+        if self.protocol_version < MIN_PROTOCOL_VERSION || !missing.is_empty() {
+            return Err(JoinRejectionReason::IncompatibleProtocol {
+                min_version: MIN_PROTOCOL_VERSION,
+                found: self.protocol_version,
+                missing,
+            });
+        }
+
+        Ok(Capabilities::ours().intersection(self.capabilities))
+    }
 }
 
 /// Joining peer's proof of resolvement of given resource proofing challenge.
@@ -37,6 +75,94 @@ pub struct ResourceProofResponse {
     #[allow(missing_docs)]
     #[debug(with = "crate::types::Signature::fmt_ed25519")]
     pub nonce_signature: Signature,
+    /// Attestation that the peer received the `JoinResponse::AddrChallenge` it was dialled back
+    /// with, at the address it joins from.
+    #[serde(default)]
+    pub reachability_attestation: Option<ReachabilityAttestation>,
+}
+
+/// Proof a joining peer listens at the address it joins from: the challenge an Elder dialled it
+/// back with at that address, signed by the joining peer's key.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, custom_debug::Debug)]
+pub struct ReachabilityAttestation {
+    /// Address the peer was dialled back at
+    pub addr: SocketAddr,
+    /// Nonce of the challenge
+    #[debug(skip)]
+    pub nonce: [u8; 32],
+    /// Signature of the Elder which sent the challenge, over the name of the peer, the address
+    /// and the nonce
+    #[debug(with = "crate::types::Signature::fmt_ed25519")]
+    pub nonce_signature: Signature,
+    /// Signature of the joining peer over the address and the nonce
+    #[debug(with = "crate::types::Signature::fmt_ed25519")]
+    pub signature: Signature,
+}
+
+impl ReachabilityAttestation {
+    /// Attests, with the keypair of the joining peer, that it received the challenge it was
+    /// dialled back with at the given address.
+    pub fn new(
+        keypair: &Keypair,
+        addr: SocketAddr,
+        nonce: [u8; 32],
+        nonce_signature: Signature,
+    ) -> Self {
+        let signature = ed25519::sign(&Self::signed_bytes(&addr, &nonce), keypair);
+        Self {
+            addr,
+            nonce,
+            nonce_signature,
+            signature,
+        }
+    }
+
+    /// Checks the attestation is for the address the peer with the given name joins from, and
+    /// signed by it. The challenge itself is left to be checked by the Elder which sent it.
+    pub fn verify(&self, name: &XorName, addr: SocketAddr) -> Result<(), AddrAttestationFailure> {
+        if self.addr != addr {
+            return Err(AddrAttestationFailure::AddrMismatch(self.addr));
+        }
+
+        let public_key =
+            ed25519::pub_key(name).map_err(|_| AddrAttestationFailure::InvalidSignature)?;
+        public_key
+            .verify(
+                &Self::signed_bytes(&self.addr, &self.nonce),
+                &self.signature,
+            )
+            .map_err(|_| AddrAttestationFailure::InvalidSignature)
+    }
+
+    fn signed_bytes(addr: &SocketAddr, nonce: &[u8; 32]) -> Vec<u8> {
+        let mut bytes = addr.to_string().into_bytes();
+        bytes.extend_from_slice(nonce);
+        bytes
+    }
+}
+
+/// Why the address a peer joins from couldn't be attested
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AddrAttestationFailure {
+    /// The peer sent its resource proof without attesting its address
+    Missing,
+    /// The peer attested another address than the one it joins from
+    AddrMismatch(SocketAddr),
+    /// The challenge attested wasn't sent by the Elder to the peer, at the address it joins from
+    InvalidChallenge,
+    /// The attestation isn't signed by the peer
+    InvalidSignature,
+}
+
+impl Display for AddrAttestationFailure {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Missing => write!(formatter, "no attestation was sent"),
+            Self::AddrMismatch(addr) => write!(formatter, "{} was attested instead", addr),
+            Self::InvalidChallenge => write!(formatter, "the challenge attested is invalid"),
+            Self::InvalidSignature => write!(formatter, "the attestation signature is invalid"),
+        }
+    }
 }
 
 /// Response to a request to join a section
@@ -54,6 +180,16 @@ pub enum JoinResponse {
         #[allow(missing_docs)]
         nonce_signature: Signature,
     },
+    /// Challenge sent by an Elder dialling the joining peer back at the address it joins from, on
+    /// a connection of its own. The peer attests it received it along with its resource proof.
+    AddrChallenge {
+        /// Address the peer was dialled back at
+        addr: SocketAddr,
+        #[allow(missing_docs)]
+        nonce: [u8; 32],
+        /// Signature of the Elder over the name of the peer, the address and the nonce
+        nonce_signature: Signature,
+    },
     /// Up to date section information for a joining peer to retry its join request with
     Retry {
         /// Current `SectionAuthorityProvider` of the section.
@@ -80,6 +216,8 @@ pub enum JoinResponse {
         node_state: SectionAuth<NodeState>,
         /// Full verifiable section chain
         section_chain: SecuredLinkedList,
+        /// Features activated in the section so far, as agreed by its Elders
+        activated_features: Vec<SectionAuth<FeatureActivation>>,
     },
     /// Join was rejected
     Rejected(JoinRejectionReason),
@@ -88,8 +226,196 @@ pub enum JoinResponse {
 /// Reason of a join request being rejected
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum JoinRejectionReason {
-    /// No new peers are currently accepted for joining
+    /// The section has enough nodes, no new peers are currently accepted for joining
     JoinsDisallowed,
     /// The requesting node is not externally reachable
     NodeNotReachable(SocketAddr),
+    /// The requesting node is trying to join another network than the section's,
+    /// whose genesis key is given
+    WrongNetwork(BlsPublicKey),
+    /// The age of the requesting node is not the one the section expects
+    BadAge {
+        /// Age the section expects
+        expected: u8,
+        /// Age of the requesting node
+        found: u8,
+    },
+    /// The requesting node is on the denylist of the Elder rejecting it, each Elder keeping its own
+    Denylisted,
+    /// The requesting node didn't solve the resource proof challenge it was sent
+    ResourceProofFailed,
+    /// The requesting node didn't prove it listens at the address it joins from
+    AddrNotAttested {
+        /// Address the node joins from
+        addr: SocketAddr,
+        /// Why the address couldn't be attested
+        failure: AddrAttestationFailure,
+    },
+    /// The requesting node speaks a version of the protocol older than the section accepts, or
+    /// lacks capabilities the section requires
+    IncompatibleProtocol {
+        /// Oldest protocol version the section accepts
+        min_version: u16,
+        /// Protocol version of the requesting node
+        found: u16,
+        /// Capabilities the section requires which the requesting node lacks
+        missing: Capabilities,
+    },
+}
+
+impl Display for JoinRejectionReason {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::JoinsDisallowed => write!(formatter, "the section is full"),
+            Self::NodeNotReachable(addr) => {
+                write!(
+                    formatter,
+                    "the node is not externally reachable at {}",
+                    addr
+                )
+            }
+            Self::WrongNetwork(genesis_key) => write!(
+                formatter,
+                "the section belongs to another network, whose genesis key is {}",
+                hex::encode(genesis_key.to_bytes())
+            ),
+            Self::BadAge { expected, found } => write!(
+                formatter,
+                "the node's age is {} while the section expects {}",
+                found, expected
+            ),
+            Self::Denylisted => write!(formatter, "the node is denylisted by this Elder"),
+            Self::ResourceProofFailed => {
+                write!(formatter, "the node failed the resource proof challenge")
+            }
+            Self::AddrNotAttested { addr, failure } => write!(
+                formatter,
+                "the node didn't prove it listens at {}: {}",
+                addr, failure
+            ),
+            Self::IncompatibleProtocol {
+                min_version,
+                found,
+                missing,
+            } => write!(
+                formatter,
+                "the node speaks protocol version {} while the section accepts {} onwards, \
+                 and lacks the capabilities {:?}",
+                found, min_version, missing
+            ),
+        }
+    }
+}
+
+/// Outcome of a peer dialling back a node which asked it to with a `SystemMsg::ReachabilityCheck`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Reachability {
+    /// The peer could connect to the node at the given address
+    Reachable(SocketAddr),
+    /// The peer could not connect to the node at the given address
+    Unreachable(SocketAddr),
+}
+
+impl Reachability {
+    /// Address the node was dialled back at
+    pub fn addr(&self) -> SocketAddr {
+        match self {
+            Self::Reachable(addr) | Self::Unreachable(addr) => *addr,
+        }
+    }
+
+    /// Whether the node could be dialled back
+    pub fn is_reachable(&self) -> bool {
+        matches!(self, Self::Reachable(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AddrAttestationFailure, JoinRejectionReason, JoinRequest, ReachabilityAttestation,
+    };
+    use crate::messaging::system::{Capabilities, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION};
+    use crate::{network_knowledge::MIN_ADULT_AGE, types::keys::ed25519};
+
+    use ed25519_dalek::Signature;
+    use eyre::Result;
+    use std::net::{Ipv4Addr, SocketAddr};
+    use xor_name::Prefix;
+
+    #[test]
+    fn attestations_are_only_valid_for_the_attested_addr_and_key() -> Result<()> {
+        let keypair = ed25519::gen_keypair(&Prefix::default().range_inclusive(), MIN_ADULT_AGE);
+        let name = ed25519::name(&keypair.public);
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, 12000));
+        let attestation =
+            ReachabilityAttestation::new(&keypair, addr, [7; 32], Signature::from_bytes(&[0; 64])?);
+
+        assert_eq!(attestation.verify(&name, addr), Ok(()));
+
+        let other_addr = SocketAddr::from((Ipv4Addr::LOCALHOST, 12001));
+        assert_eq!(
+            attestation.verify(&name, other_addr),
+            Err(AddrAttestationFailure::AddrMismatch(addr))
+        );
+
+        let other_keypair =
+            ed25519::gen_keypair(&Prefix::default().range_inclusive(), MIN_ADULT_AGE);
+        let other_name = ed25519::name(&other_keypair.public);
+        assert_eq!(
+            attestation.verify(&other_name, addr),
+            Err(AddrAttestationFailure::InvalidSignature)
+        );
+
+        // the nonce can't be swapped for another one
+        let mut tampered = attestation;
+        tampered.nonce = [8; 32];
+        assert_eq!(
+            tampered.verify(&name, addr),
+            Err(AddrAttestationFailure::InvalidSignature)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn peers_of_incompatible_protocols_are_rejected() {
+        let genesis_key = bls::SecretKey::random().public_key();
+        let join_request = |protocol_version, capabilities| JoinRequest {
+            section_key: genesis_key,
+            resource_proof_response: None,
+            genesis_key,
+            version: None,
+            protocol_version,
+            capabilities,
+        };
+
+        assert_eq!(
+            join_request(PROTOCOL_VERSION, Capabilities::ours()).negotiate_protocol(),
+            Ok(Capabilities::ours())
+        );
+
+        // peers lacking optional capabilities, or of newer versions, are downgraded to ours
+        assert_eq!(
+            join_request(PROTOCOL_VERSION + 1, Capabilities::REQUIRED).negotiate_protocol(),
+            Ok(Capabilities::REQUIRED)
+        );
+
+        assert_eq!(
+            join_request(MIN_PROTOCOL_VERSION - 1, Capabilities::ours()).negotiate_protocol(),
+            Err(JoinRejectionReason::IncompatibleProtocol {
+                min_version: MIN_PROTOCOL_VERSION,
+                found: MIN_PROTOCOL_VERSION - 1,
+                missing: Capabilities::empty(),
+            })
+        );
+        assert_eq!(
+            join_request(PROTOCOL_VERSION, Capabilities::HANDSHAKE_FRAMES).negotiate_protocol(),
+            Err(JoinRejectionReason::IncompatibleProtocol {
+                min_version: MIN_PROTOCOL_VERSION,
+                found: PROTOCOL_VERSION,
+                missing: Capabilities::ADDR_ATTESTATION,
+            })
+        );
+    }
 }
@@ -7,6 +7,8 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 mod agreement;
+mod features;
+mod handshake;
 mod join;
 mod join_as_relocated;
 mod msg_authority;
@@ -16,7 +18,17 @@ mod signed;
 
 use crate::network_knowledge::SapCandidate;
 pub use agreement::{DkgFailureSig, DkgFailureSigSet, DkgSessionId, Proposal, SectionAuth};
-pub use join::{JoinRejectionReason, JoinRequest, JoinResponse, ResourceProofResponse};
+pub use features::{
+    Capabilities, Feature, FeatureActivation, NodeVersion, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION,
+};
+pub use handshake::{
+    handshake_msgs, HandshakeAssembler, HandshakeFrame, HANDSHAKE_ASSEMBLY_TIMEOUT,
+    MAX_HANDSHAKE_FRAME_SIZE, MAX_HANDSHAKE_SIZE,
+};
+pub use join::{
+    AddrAttestationFailure, JoinRejectionReason, JoinRequest, JoinResponse, Reachability,
+    ReachabilityAttestation, ResourceProofResponse,
+};
 pub use join_as_relocated::{JoinAsRelocatedRequest, JoinAsRelocatedResponse};
 pub use msg_authority::NodeMsgAuthorityUtils;
 pub use node_msgs::{NodeCmd, NodeEvent, NodeQuery, NodeQueryResponse};
@@ -28,6 +40,7 @@ use sn_consensus::{Generation, SignedVote};
 pub type SectionPeers = BTreeSet<SectionAuth<NodeState>>;
 
 use crate::messaging::{EndUser, MsgId, SectionAuthorityProvider};
+use crate::types::{KeyBundle, Peer};
 use bls_dkg::key_gen::message::Message as DkgMessage;
 use bytes::Bytes;
 use secured_linked_list::SecuredLinkedList;
@@ -86,6 +99,13 @@ pub enum SystemMsg {
     },
     /// Probes the network by sending a message to a random or chosen dst triggering an AE flow.
     AntiEntropyProbe,
+    /// Sent to a watched peer we haven't heard from for a while, to find out whether it's alive
+    KeepAlive,
+    /// Response to a `KeepAlive`
+    KeepAliveResponse,
+    /// Sent periodically to some of the peers we watch, sharing a sample of the nodes we heard
+    /// from lately, for them to bootstrap through should their contacts have churned away
+    PeerExchange(Vec<Peer>),
     #[cfg(feature = "back-pressure")]
     /// Sent when a msg-consuming node wants to update a msg-producing node on the number of msgs per s it wants to receive.
     /// It tells the node to adjust msg sending rate according to the provided value in this msg.
@@ -100,10 +120,18 @@ pub enum SystemMsg {
     JoinRequest(Box<JoinRequest>),
     /// Response to a `JoinRequest`
     JoinResponse(Box<JoinResponse>),
+    /// Sent from a bootstrapping peer to one of its contacts, asking to be dialled back, to find
+    /// out whether it's reachable by the network before requesting to join
+    ReachabilityCheck,
+    /// Response to a `ReachabilityCheck`
+    ReachabilityCheckResponse(Reachability),
     /// Sent from a peer to the section requesting to join as relocated from another section
     JoinAsRelocatedRequest(Box<JoinAsRelocatedRequest>),
     /// Response to a `JoinAsRelocatedRequest`
     JoinAsRelocatedResponse(Box<JoinAsRelocatedResponse>),
+    /// Part of a `JoinRequest` or `JoinAsRelocatedRequest` too large to be sent whole, which is
+    /// handled once all its frames are put back together
+    HandshakeFrame(HandshakeFrame),
     /// Sent to the new elder candidates to start the DKG process.
     DkgStart(DkgSessionId),
     /// Message sent when a DKG session has not started
@@ -171,10 +199,16 @@ pub enum SystemMsg {
         // TODO: try to remove this in favor of the msg header MsgKind sig share we already have
         /// BLS signature share
         sig_share: SigShare,
+        /// Proof the proposer holds the key share it signed with, as its own.
+        /// Required for `SectionInfo` proposals, made by the participants of a DKG with the
+        /// key share they just got out of it.
+        key_bundle: Option<KeyBundle>,
     },
     /// Message that notifies a section to test
     /// the connectivity to a node
     StartConnectivityTest(XorName),
+    /// Activation of a feature agreed by the Elders, sent to the members of the section.
+    FeatureActivation(SectionAuth<FeatureActivation>),
     /// Events are facts about something that happened on a node.
     NodeEvent(NodeEvent),
     /// The returned error, from any msg handling on recipient node.
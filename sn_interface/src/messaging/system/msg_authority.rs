@@ -24,18 +24,15 @@ pub trait NodeMsgAuthorityUtils {
 impl NodeMsgAuthorityUtils for NodeMsgAuthority {
     fn src_location(&self) -> SrcLocation {
         match self {
-            NodeMsgAuthority::Node(node_auth) => SrcLocation::Node {
-                name: ed25519::name(&node_auth.node_ed_pk),
-                section_pk: node_auth.section_pk,
-            },
-            NodeMsgAuthority::BlsShare(bls_share_auth) => SrcLocation::Section {
-                name: bls_share_auth.src_name,
-                section_pk: bls_share_auth.section_pk,
-            },
-            NodeMsgAuthority::Section(section_auth) => SrcLocation::Section {
-                name: section_auth.src_name,
-                section_pk: section_auth.sig.public_key,
-            },
+            NodeMsgAuthority::Node(node_auth) => {
+                SrcLocation::node(ed25519::name(&node_auth.node_ed_pk), node_auth.section_pk)
+            }
+            NodeMsgAuthority::BlsShare(bls_share_auth) => {
+                SrcLocation::section(bls_share_auth.src_name, bls_share_auth.section_pk)
+            }
+            NodeMsgAuthority::Section(section_auth) => {
+                SrcLocation::section(section_auth.src_name, section_auth.sig.public_key)
+            }
         }
     }
 
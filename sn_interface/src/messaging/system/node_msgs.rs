@@ -19,7 +19,7 @@ use crate::types::{
 
 use serde::{Deserialize, Serialize};
 use sn_dbc::SpentProofShare;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use xor_name::XorName;
 
 /// cmd message sent among nodes
@@ -46,14 +46,31 @@ pub enum NodeCmd {
     },
     /// Tells an Adult to store a replica of the data
     ReplicateData(Vec<ReplicatedData>),
+    /// Tells an Adult to store a replica of the data written by a client, and to confirm it did
+    /// with a [`NodeEvent::DataReplicated`], for the client to be told once all Adults did
+    ReplicateDataAndConfirm {
+        /// The data to store
+        data: ReplicatedData,
+        /// ID of the client cmd which wrote the data
+        correlation_id: MsgId,
+    },
     /// Tells an Adult to fetch and replicate data from the sender
     SendAnyMissingRelevantData(Vec<ReplicatedDataAddress>),
+    /// Tells an Adult holding any of the data to send it to the
+    /// other Adults which are meant to hold a replica of it
+    RepairData(Vec<ReplicatedDataAddress>),
     /// Sent to all promoted nodes (also sibling if any) after
     /// a completed transition to a new constellation.
     ReceiveMetadata {
         /// Metadata
         metadata: MetadataExchange,
     },
+    /// Sent by an Elder to the other Elders of its section when it accepted payments for
+    /// storing data, so none of them accepts the same payment for other data
+    RecordSpentPayments {
+        /// Name of the key image of each DBC, along with the name of the data it paid for
+        payments: BTreeMap<XorName, XorName>,
+    },
 }
 
 /// Event message sent among nodes
@@ -70,6 +87,13 @@ pub enum NodeEvent {
         /// Whether store failed due to full
         full: bool,
     },
+    #[cfg(any(feature = "chunks", feature = "registers"))]
+    /// Sent by an Adult to the Elder which told it to store a client's data with a
+    /// [`NodeCmd::ReplicateDataAndConfirm`], once it stored it
+    DataReplicated {
+        /// ID of the client cmd which wrote the data
+        correlation_id: MsgId,
+    },
 }
 
 /// Query originating at a node
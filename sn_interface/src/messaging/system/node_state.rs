@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use std::net::SocketAddr;
 use xor_name::{XorName, XOR_NAME_LEN};
 
+use super::NodeVersion;
 use crate::types::Peer;
 
 /// Information about a member of our section.
@@ -24,6 +25,9 @@ pub struct NodeState {
     pub state: MembershipState,
     /// To avoid sybil attack via relocation, a relocated node's original name will be recorded.
     pub previous_name: Option<XorName>,
+    /// Version of the node's binary, as advertised when it joined.
+    #[serde(default)]
+    pub version: Option<NodeVersion>,
 }
 
 impl NodeState {
@@ -34,6 +38,7 @@ impl NodeState {
             addr,
             state: MembershipState::Joined,
             previous_name,
+            version: None,
         }
     }
 
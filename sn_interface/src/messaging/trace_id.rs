@@ -0,0 +1,146 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use hex_fmt::HexFmt;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Length of a `TraceId`, in bytes
+pub const TRACE_ID_LEN: usize = 16;
+
+// Version and flags (sampled) of the `traceparent`s we write out
+const TRACEPARENT_VERSION: &str = "00";
+const TRACEPARENT_FLAGS: &str = "01";
+
+/// Identifies the operation of a client a message is part of, so it can be followed across the
+/// logs of the client and of all the nodes involved in it, e.g. when it was slow.
+///
+/// It's set by the client on the messages of an operation, and carried over by nodes into the
+/// messages they send while handling them, including the responses to the client.
+/// It's compatible with the trace id of the [W3C Trace Context], so it can be taken from, or
+/// handed over to, other tracing systems.
+///
+/// [W3C Trace Context]: https://www.w3.org/TR/trace-context/
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct TraceId([u8; TRACE_ID_LEN]);
+
+impl TraceId {
+    /// Generate a new random `TraceId`
+    pub fn random() -> Self {
+        // all zeroes is an invalid trace id
+        loop {
+            let bytes = rand::random::<[u8; TRACE_ID_LEN]>();
+            if bytes != [0; TRACE_ID_LEN] {
+                return Self(bytes);
+            }
+        }
+    }
+
+    /// Return the bytes of the id
+    pub fn as_bytes(&self) -> &[u8; TRACE_ID_LEN] {
+        &self.0
+    }
+
+    /// Format the id as a W3C `traceparent` header value, for the span with the given id
+    pub fn traceparent(&self, span_id: u64) -> String {
+        format!(
+            "{}-{}-{:016x}-{}",
+            TRACEPARENT_VERSION, self, span_id, TRACEPARENT_FLAGS
+        )
+    }
+
+    /// Parse the trace id out of a W3C `traceparent` header value
+    pub fn from_traceparent(traceparent: &str) -> Option<Self> {
+        let mut parts = traceparent.split('-');
+        let version = parts.next()?;
+        let trace_id = parts.next()?;
+        let span_id = parts.next()?;
+        let flags = parts.next()?;
+        let is_hex = |part: &str, len| {
+            part.len() == len && part.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+        };
+        // later versions may append fields, but version `ff` is invalid
+        if !is_hex(version, 2)
+            || version == "ff"
+            || (version == TRACEPARENT_VERSION && parts.next().is_some())
+            || !is_hex(span_id, 16)
+            || span_id == "0000000000000000"
+            || !is_hex(flags, 2)
+        {
+            return None;
+        }
+
+        Self::from_hex(trace_id)
+    }
+
+    /// Parse the 32 lowercase hex characters a `TraceId` is displayed as
+    pub fn from_hex(trace_id: &str) -> Option<Self> {
+        if trace_id.bytes().any(|b| b.is_ascii_uppercase()) {
+            return None;
+        }
+        let mut bytes = [0; TRACE_ID_LEN];
+        hex::decode_to_slice(trace_id, &mut bytes).ok()?;
+        if bytes == [0; TRACE_ID_LEN] {
+            return None;
+        }
+        Some(Self(bytes))
+    }
+}
+
+impl fmt::Display for TraceId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", HexFmt(&self.0))
+    }
+}
+
+impl fmt::Debug for TraceId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TraceId({})", HexFmt(&self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trace_id_round_trips_through_traceparent() {
+        let trace_id = TraceId::random();
+        let traceparent = trace_id.traceparent(0x00f0_67aa_0ba9_02b7);
+
+        assert_eq!(traceparent.len(), 55);
+        assert!(traceparent.ends_with("-00f067aa0ba902b7-01"));
+        assert_eq!(TraceId::from_traceparent(&traceparent), Some(trace_id));
+    }
+
+    #[test]
+    fn trace_id_is_read_from_w3c_traceparents() {
+        let valid = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        assert_eq!(
+            TraceId::from_traceparent(valid).map(|id| id.to_string()),
+            Some("4bf92f3577b34da6a3ce929d0e0e4736".to_string())
+        );
+        // a later version may carry more fields
+        assert!(TraceId::from_traceparent(
+            "cc-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-ff"
+        )
+        .is_some());
+
+        for invalid in [
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e47-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-ff",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736",
+        ] {
+            assert_eq!(TraceId::from_traceparent(invalid), None, "{}", invalid);
+        }
+    }
+}
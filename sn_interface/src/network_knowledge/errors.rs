@@ -37,6 +37,8 @@ pub enum Error {
     UntrustedSectionAuthProvider(String),
     #[error("Proof chain cannot be trusted: {0}")]
     UntrustedProofChain(String),
+    #[error("Invalid node config: {0}")]
+    InvalidNodeConfig(String),
     #[error("Invalid genesis key of provided prefix map: {}", hex::encode(_0.to_bytes()))]
     InvalidGenesisKey(bls::PublicKey),
     #[error("Cannot route. Delivery group size: {}, candidates: {}.", _0, _1)]
@@ -7,12 +7,14 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 mod errors;
+mod node_config;
 mod node_info;
 pub mod node_state;
 pub mod prefix_map;
 pub mod section_authority_provider;
 pub mod section_keys;
 mod section_peers;
+mod snapshot;
 pub mod utils;
 
 #[cfg(any(test, feature = "test-utils"))]
@@ -20,9 +22,13 @@ pub use self::section_authority_provider::test_utils;
 
 pub use self::section_keys::{SectionKeyShare, SectionKeysProvider};
 
+pub use node_config::{NodeConfig, NODE_CONFIG_FORMAT_VERSION};
 pub use node_info::NodeInfo;
 pub use node_state::NodeState;
 pub use section_authority_provider::{SapCandidate, SectionAuthUtils, SectionAuthorityProvider};
+pub use snapshot::{
+    ConnectionSnapshot, NetworkSnapshot, NetworkSnapshotDiff, SectionChange, SectionSnapshot,
+};
 
 use crate::messaging::{
     system::{
@@ -709,6 +715,14 @@ impl NetworkKnowledge {
         self.section_peers.get(name)
     }
 
+    /// Get the section signed info for the member with the given name.
+    pub async fn get_section_signed_member(
+        &self,
+        name: &XorName,
+    ) -> Option<SectionAuth<NodeState>> {
+        self.section_peers.get_section_signed(name)
+    }
+
     /// Get info for the member with the given name either from current members list,
     /// or from the archive of left/relocated members
     pub async fn is_either_member_or_archived(
@@ -744,6 +758,7 @@ fn create_first_section_authority_provider(
         [NodeState::joined(peer, None)],
         pk_set.clone(),
         0,
+        crate::data_copy_count(),
     );
     let sig = create_first_sig(pk_set, sk_share, &section_auth)?;
     Ok(SectionAuth::new(section_auth, sig))
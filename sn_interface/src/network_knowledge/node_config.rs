@@ -0,0 +1,259 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{Error, Result};
+use crate::types::PublicKey;
+
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, net::SocketAddr};
+
+/// Version of the format node configs are written out with.
+pub const NODE_CONFIG_FORMAT_VERSION: u32 = 1;
+
+/// The configuration to bootstrap to the network we are participating in.
+///
+/// It's written out by nodes as a contact file, for clients and other nodes to join the network.
+/// Contact files written before the format was versioned, i.e. holding only the genesis key and
+/// the list of contacts, are read as version `0`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "NodeConfigFile", into = "NodeConfigFile")]
+pub struct NodeConfig {
+    /// Genesis key of the network, either supplied or created upon launching its first node
+    pub genesis_key: bls::PublicKey,
+    /// Addresses of some nodes participating in the network
+    pub contacts: BTreeSet<SocketAddr>,
+    /// Name of the network, if it's not derived from its genesis key
+    pub network_name: Option<String>,
+    /// Version of the format the config was read from
+    pub format_version: u32,
+}
+
+impl NodeConfig {
+    /// Creates a config in the latest format.
+    pub fn new(genesis_key: bls::PublicKey, contacts: BTreeSet<SocketAddr>) -> Self {
+        Self {
+            genesis_key,
+            contacts,
+            network_name: None,
+            format_version: NODE_CONFIG_FORMAT_VERSION,
+        }
+    }
+
+    /// Checks the config can be used to bootstrap to a network.
+    pub fn validate(&self) -> Result<()> {
+        if self.format_version > NODE_CONFIG_FORMAT_VERSION {
+            return Err(Error::InvalidNodeConfig(format!(
+                "format version {} is not supported, the latest one being {}",
+                self.format_version, NODE_CONFIG_FORMAT_VERSION
+            )));
+        }
+        if self.contacts.is_empty() {
+            return Err(Error::InvalidNodeConfig(
+                "it has no contacts to bootstrap from".to_string(),
+            ));
+        }
+        if self.network_name.as_deref() == Some("") {
+            return Err(Error::InvalidNodeConfig(
+                "the network name is empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reads a config from the content of a contact file, in any of the formats it was written in.
+    pub fn from_json(bytes: &[u8]) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_slice(bytes)
+            .map_err(|err| Error::InvalidNodeConfig(format!("it's not valid JSON: {}", err)))?;
+        // parsing the format it's in explicitly, for its error to say what's wrong with it
+        let file = if value.get(0).map_or(false, |key| key.is_string()) {
+            serde_json::from_value::<(String, BTreeSet<SocketAddr>)>(value)
+                .map(|(genesis_key, contacts)| NodeConfigFile::Legacy(genesis_key, contacts))
+        } else if value.is_array() {
+            serde_json::from_value::<(bls::PublicKey, BTreeSet<SocketAddr>)>(value)
+                .map(|(genesis_key, contacts)| NodeConfigFile::LegacyKey(genesis_key, contacts))
+        } else {
+            serde_json::from_value::<VersionedNodeConfigFile>(value).map(NodeConfigFile::Versioned)
+        }
+        .map_err(|err| Error::InvalidNodeConfig(err.to_string()))?;
+
+        Self::try_from(file)
+    }
+
+    /// Serialises the config to be written out as a contact file, in the latest format.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|err| Error::InvalidNodeConfig(format!("it can't be serialised: {}", err)))
+    }
+}
+
+// Formats of the node configs, as written out
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum NodeConfigFile {
+    Versioned(VersionedNodeConfigFile),
+    // Genesis key and contacts, as written before the format was versioned
+    Legacy(String, BTreeSet<SocketAddr>),
+    // Same, with the genesis key serialised as is rather than hex-encoded, as the CLI used to
+    // store the configs of the networks in its settings
+    LegacyKey(bls::PublicKey, BTreeSet<SocketAddr>),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VersionedNodeConfigFile {
+    format_version: u32,
+    // hex-encoded
+    genesis_key: String,
+    contacts: BTreeSet<SocketAddr>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    network_name: Option<String>,
+}
+
+impl TryFrom<NodeConfigFile> for NodeConfig {
+    type Error = Error;
+
+    fn try_from(file: NodeConfigFile) -> Result<Self> {
+        let (genesis_key_hex, contacts, network_name, format_version) = match file {
+            NodeConfigFile::Versioned(file) => (
+                file.genesis_key,
+                file.contacts,
+                file.network_name,
+                file.format_version,
+            ),
+            NodeConfigFile::Legacy(genesis_key, contacts) => (genesis_key, contacts, None, 0),
+            NodeConfigFile::LegacyKey(genesis_key, contacts) => {
+                let config = Self {
+                    genesis_key,
+                    contacts,
+                    network_name: None,
+                    format_version: 0,
+                };
+                config.validate()?;
+                return Ok(config);
+            }
+        };
+        let genesis_key = PublicKey::bls_from_hex(&genesis_key_hex)
+            .ok()
+            .and_then(|key| key.bls())
+            .ok_or_else(|| {
+                Error::InvalidNodeConfig(format!(
+                    "genesis key '{}' is not a hex-encoded BLS public key",
+                    genesis_key_hex
+                ))
+            })?;
+
+        let config = Self {
+            genesis_key,
+            contacts,
+            network_name,
+            format_version,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+impl From<NodeConfig> for NodeConfigFile {
+    fn from(config: NodeConfig) -> Self {
+        Self::Versioned(VersionedNodeConfigFile {
+            format_version: NODE_CONFIG_FORMAT_VERSION,
+            genesis_key: hex::encode(config.genesis_key.to_bytes()),
+            contacts: config.contacts,
+            network_name: config.network_name,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::Result;
+
+    fn gen_config() -> Result<NodeConfig> {
+        let genesis_key = bls::SecretKey::random().public_key();
+        let contacts: Vec<SocketAddr> =
+            vec!["127.0.0.1:12000".parse()?, "[2001:db8::1]:12000".parse()?];
+        Ok(NodeConfig::new(genesis_key, contacts.into_iter().collect()))
+    }
+
+    #[test]
+    fn config_is_read_back_the_same() -> Result<()> {
+        let mut config = gen_config()?;
+        assert_eq!(NodeConfig::from_json(config.to_json()?.as_bytes())?, config);
+
+        config.network_name = Some("testnet".to_string());
+        assert_eq!(NodeConfig::from_json(config.to_json()?.as_bytes())?, config);
+        let serialised = serde_json::to_string(&config)?;
+        assert_eq!(serde_json::from_str::<NodeConfig>(&serialised)?, config);
+
+        Ok(())
+    }
+
+    #[test]
+    fn legacy_config_is_read() -> Result<()> {
+        let config = gen_config()?;
+        let legacy = serde_json::to_string(&(
+            hex::encode(config.genesis_key.to_bytes()),
+            config.contacts.clone(),
+        ))?;
+
+        let read = NodeConfig::from_json(legacy.as_bytes())?;
+        assert_eq!(read.genesis_key, config.genesis_key);
+        assert_eq!(read.contacts, config.contacts);
+        assert_eq!(read.format_version, 0);
+
+        // as the CLI used to store them in its settings
+        let legacy_key = serde_json::to_string(&(config.genesis_key, config.contacts.clone()))?;
+        let read = serde_json::from_str::<NodeConfig>(&legacy_key)?;
+        assert_eq!(read.genesis_key, config.genesis_key);
+        assert_eq!(read, NodeConfig::from_json(legacy_key.as_bytes())?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn malformed_config_is_rejected_with_the_reason() -> Result<()> {
+        let config = gen_config()?;
+        let genesis_key_hex = hex::encode(config.genesis_key.to_bytes());
+
+        let no_contacts = format!(
+            r#"{{"format_version":1,"genesis_key":"{}","contacts":[]}}"#,
+            genesis_key_hex
+        );
+        let invalid_key =
+            r#"{"format_version":1,"genesis_key":"abcd","contacts":["127.0.0.1:12000"]}"#;
+        let invalid_contact = format!(
+            r#"{{"format_version":1,"genesis_key":"{}","contacts":["localhost"]}}"#,
+            genesis_key_hex
+        );
+        let future_version = format!(
+            r#"{{"format_version":9,"genesis_key":"{}","contacts":["127.0.0.1:12000"]}}"#,
+            genesis_key_hex
+        );
+
+        for (json, reason) in [
+            (no_contacts.as_str(), "no contacts"),
+            (invalid_key, "not a hex-encoded BLS public key"),
+            (invalid_contact.as_str(), "invalid socket address"),
+            (future_version.as_str(), "not supported"),
+            ("not json", "not valid JSON"),
+        ] {
+            match NodeConfig::from_json(json.as_bytes()) {
+                Err(Error::InvalidNodeConfig(msg)) => assert!(
+                    msg.contains(reason),
+                    "expected '{}' in error: {}",
+                    reason,
+                    msg
+                ),
+                other => panic!("unexpected result for {}: {:?}", json, other),
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -7,7 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::messaging::system::{
-    MembershipState, NodeState as NodeStateMsg, RelocateDetails, SectionAuth,
+    MembershipState, NodeState as NodeStateMsg, NodeVersion, RelocateDetails, SectionAuth,
 };
 use crate::network_knowledge::{section_has_room_for_node, Error, Result};
 use crate::types::Peer;
@@ -22,6 +22,7 @@ pub struct NodeState {
     peer: Peer,
     state: MembershipState,
     previous_name: Option<XorName>,
+    version: Option<NodeVersion>,
 }
 
 impl serde::Serialize for NodeState {
@@ -41,6 +42,7 @@ impl NodeState {
             peer,
             state: MembershipState::Joined,
             previous_name,
+            version: None,
         }
     }
 
@@ -51,6 +53,7 @@ impl NodeState {
             peer,
             state: MembershipState::Left,
             previous_name,
+            version: None,
         }
     }
 
@@ -65,6 +68,7 @@ impl NodeState {
             peer,
             state: MembershipState::Relocated(Box::new(relocate_details)),
             previous_name,
+            version: None,
         }
     }
 
@@ -146,6 +150,11 @@ impl NodeState {
         self.previous_name
     }
 
+    /// Version of the node's binary, if it advertised it when joining.
+    pub fn version(&self) -> Option<NodeVersion> {
+        self.version
+    }
+
     pub fn age(&self) -> u8 {
         self.peer.age()
     }
@@ -188,6 +197,7 @@ impl NodeState {
             addr: self.addr(),
             state: self.state.clone(),
             previous_name: self.previous_name,
+            version: self.version,
         }
     }
 }
@@ -207,6 +217,7 @@ impl NodeStateMsg {
             peer: Peer::new(self.name, self.addr),
             state: self.state,
             previous_name: self.previous_name,
+            version: self.version,
         }
     }
 }
@@ -66,6 +66,7 @@ pub struct SectionAuthorityProvider {
     elders: BTreeSet<Peer>,
     members: BTreeSet<NodeState>,
     membership_gen: Generation,
+    data_copy_count: usize,
 }
 
 /// SectionAuthorityProvider candidates for handover consensus to vote on
@@ -122,13 +123,15 @@ impl<'de> serde::Deserialize<'de> for SectionAuthorityProvider {
 }
 
 impl SectionAuthorityProvider {
-    /// Creates a new `SectionAuthorityProvider` with the given members, prefix and public keyset.
+    /// Creates a new `SectionAuthorityProvider` with the given members, prefix and public keyset,
+    /// for a section storing `data_copy_count` copies of each data.
     pub fn new<E, M>(
         elders: E,
         prefix: Prefix,
         members: M,
         pk_set: PublicKeySet,
         membership_gen: Generation,
+        data_copy_count: usize,
     ) -> Self
     where
         E: IntoIterator<Item = Peer>,
@@ -140,6 +143,7 @@ impl SectionAuthorityProvider {
             elders: elders.into_iter().collect(),
             members: members.into_iter().collect(),
             membership_gen,
+            data_copy_count,
         }
     }
 
@@ -154,6 +158,7 @@ impl SectionAuthorityProvider {
                 .map(|n| n.into_state()),
             pk_set,
             session_id.membership_gen,
+            session_id.data_copy_count,
         )
     }
 
@@ -174,6 +179,11 @@ impl SectionAuthorityProvider {
         self.membership_gen
     }
 
+    /// Number of copies of each data the section stores, i.e. its replication factor.
+    pub fn data_copy_count(&self) -> usize {
+        self.data_copy_count
+    }
+
     /// A convenience function since we often use SAP elders as recipients.
     pub fn elders_vec(&self) -> Vec<Peer> {
         self.elders.iter().cloned().collect()
@@ -229,6 +239,7 @@ impl SectionAuthorityProvider {
                 .map(|state| (state.name(), state.to_msg()))
                 .collect(),
             membership_gen: self.membership_gen,
+            data_copy_count: self.data_copy_count,
         }
     }
 }
@@ -254,6 +265,7 @@ impl SectionAuthorityProviderMsg {
                 .map(|(_name, state)| state.into_state()),
             self.public_key_set,
             self.membership_gen,
+            self.data_copy_count,
         )
     }
 }
@@ -324,8 +336,14 @@ pub mod test_utils {
         let elders = nodes.iter().map(NodeInfo::peer);
         let members = nodes.iter().map(|i| NodeState::joined(i.peer(), None));
         let secret_key_set = SecretKeySet::random();
-        let section_auth =
-            SectionAuthorityProvider::new(elders, prefix, members, secret_key_set.public_keys(), 0);
+        let section_auth = SectionAuthorityProvider::new(
+            elders,
+            prefix,
+            members,
+            secret_key_set.public_keys(),
+            0,
+            crate::data_copy_count(),
+        );
 
         (section_auth, nodes, secret_key_set)
     }
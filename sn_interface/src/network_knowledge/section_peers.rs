@@ -47,6 +47,11 @@ impl SectionPeers {
         self.members.get(name).map(|state| state.value.clone())
     }
 
+    /// Get the section signed `NodeState` for the member with the given name.
+    pub(super) fn get_section_signed(&self, name: &XorName) -> Option<SectionAuth<NodeState>> {
+        self.members.get(name).map(|state| state.value().clone())
+    }
+
     /// Returns whether the given peer is currently a member of our section.
     pub(super) fn is_member(&self, name: &XorName) -> bool {
         self.members.get(name).is_some()
@@ -0,0 +1,356 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::prefix_map::NetworkPrefixMap;
+use crate::types::Peer;
+
+use bls::PublicKey as BlsPublicKey;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+use xor_name::Prefix;
+
+/// What a client or a node knows of the network at some point: the sections it knows, with
+/// their Elders, and the peers it has connections to.
+///
+/// Snapshots are meant to be attached to bug reports, so they only carry public knowledge, no
+/// key or signature share of any kind. Two snapshots, e.g. taken before and after an issue, or
+/// by two peers, can be compared with [`NetworkSnapshot::diff`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkSnapshot {
+    /// When the snapshot was taken, in seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Genesis key of the network
+    pub genesis_key: BlsPublicKey,
+    /// Sections known, ordered by prefix
+    pub sections: Vec<SectionSnapshot>,
+    /// Peers there are connections to, or were recently, ordered by name
+    pub connections: Vec<ConnectionSnapshot>,
+}
+
+/// A section known, as part of a [`NetworkSnapshot`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionSnapshot {
+    /// Prefix of the section
+    pub prefix: Prefix,
+    /// Latest key of the section known
+    pub key: BlsPublicKey,
+    /// Elders of the section, as of its latest key known, ordered by name
+    pub elders: Vec<Peer>,
+}
+
+/// A peer there's a connection to, as part of a [`NetworkSnapshot`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConnectionSnapshot {
+    /// The peer
+    pub peer: Peer,
+    /// Whether the connection was open when the snapshot was taken
+    pub connected: bool,
+}
+
+impl NetworkSnapshot {
+    /// Snapshot of the sections in the given prefix map, and of the given connections.
+    pub fn new(
+        prefix_map: &NetworkPrefixMap,
+        connections: impl IntoIterator<Item = (Peer, bool)>,
+        timestamp: u64,
+    ) -> Self {
+        let mut sections: Vec<_> = prefix_map
+            .all()
+            .into_iter()
+            .map(|sap| {
+                let mut elders = sap.elders_vec();
+                elders.sort();
+                SectionSnapshot {
+                    prefix: sap.prefix(),
+                    key: sap.section_key(),
+                    elders,
+                }
+            })
+            .collect();
+        sections.sort_by_key(|section| section.prefix);
+
+        let mut connections: Vec<_> = connections
+            .into_iter()
+            .map(|(peer, connected)| ConnectionSnapshot { peer, connected })
+            .collect();
+        connections.sort_by_key(|connection| connection.peer);
+
+        Self {
+            timestamp,
+            genesis_key: prefix_map.genesis_key(),
+            sections,
+            connections,
+        }
+    }
+
+    /// How the network as known in the given, later, snapshot differs from this one.
+    pub fn diff(&self, later: &Self) -> NetworkSnapshotDiff {
+        let before: BTreeMap<_, _> = self
+            .sections
+            .iter()
+            .map(|section| (section.prefix, section))
+            .collect();
+        let after: BTreeMap<_, _> = later
+            .sections
+            .iter()
+            .map(|section| (section.prefix, section))
+            .collect();
+
+        let sections_added = after
+            .keys()
+            .filter(|prefix| !before.contains_key(prefix))
+            .copied()
+            .collect();
+        let sections_removed = before
+            .keys()
+            .filter(|prefix| !after.contains_key(prefix))
+            .copied()
+            .collect();
+        let sections_changed = before
+            .iter()
+            .filter_map(|(prefix, old)| {
+                let new = after.get(prefix)?;
+                let old_elders: BTreeSet<_> = old.elders.iter().collect();
+                let new_elders: BTreeSet<_> = new.elders.iter().collect();
+                let change = SectionChange {
+                    prefix: *prefix,
+                    old_key: old.key,
+                    new_key: new.key,
+                    elders_added: new_elders.difference(&old_elders).map(|p| **p).collect(),
+                    elders_removed: old_elders.difference(&new_elders).map(|p| **p).collect(),
+                };
+                if change.old_key != change.new_key
+                    || !change.elders_added.is_empty()
+                    || !change.elders_removed.is_empty()
+                {
+                    Some(change)
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        let connected = |snapshot: &Self| -> BTreeSet<Peer> {
+            snapshot
+                .connections
+                .iter()
+                .filter(|connection| connection.connected)
+                .map(|connection| connection.peer)
+                .collect()
+        };
+        let (connected_before, connected_after) = (connected(self), connected(later));
+
+        NetworkSnapshotDiff {
+            same_network: self.genesis_key == later.genesis_key,
+            sections_added,
+            sections_removed,
+            sections_changed,
+            peers_connected: connected_after
+                .difference(&connected_before)
+                .copied()
+                .collect(),
+            peers_disconnected: connected_before
+                .difference(&connected_after)
+                .copied()
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for NetworkSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Network {} (taken at {})",
+            hex::encode(self.genesis_key.to_bytes()),
+            self.timestamp
+        )?;
+        writeln!(f, "Sections known: {}", self.sections.len())?;
+        for section in &self.sections {
+            writeln!(
+                f,
+                "  {:?} key {:?}, {} Elders:",
+                section.prefix,
+                section.key,
+                section.elders.len()
+            )?;
+            for elder in &section.elders {
+                writeln!(f, "    {}", elder)?;
+            }
+        }
+        writeln!(f, "Connections: {}", self.connections.len())?;
+        for connection in &self.connections {
+            let state = if connection.connected {
+                "connected"
+            } else {
+                "disconnected"
+            };
+            writeln!(f, "  {} {}", connection.peer, state)?;
+        }
+        Ok(())
+    }
+}
+
+/// How the network as known in a [`NetworkSnapshot`] differs from an earlier one.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkSnapshotDiff {
+    /// Whether both snapshots are of the same network, i.e. have the same genesis key
+    pub same_network: bool,
+    /// Prefixes of the sections only known in the later snapshot, e.g. as a section split
+    pub sections_added: Vec<Prefix>,
+    /// Prefixes of the sections only known in the earlier snapshot
+    pub sections_removed: Vec<Prefix>,
+    /// Sections known in both snapshots, whose key or Elders changed
+    pub sections_changed: Vec<SectionChange>,
+    /// Peers connected only in the later snapshot
+    pub peers_connected: Vec<Peer>,
+    /// Peers connected only in the earlier snapshot
+    pub peers_disconnected: Vec<Peer>,
+}
+
+/// How a section changed between two [`NetworkSnapshot`]s.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionChange {
+    /// Prefix of the section
+    pub prefix: Prefix,
+    /// Key of the section in the earlier snapshot
+    pub old_key: BlsPublicKey,
+    /// Key of the section in the later snapshot
+    pub new_key: BlsPublicKey,
+    /// Elders only in the later snapshot
+    pub elders_added: Vec<Peer>,
+    /// Elders only in the earlier snapshot
+    pub elders_removed: Vec<Peer>,
+}
+
+impl NetworkSnapshotDiff {
+    /// Whether the snapshots are of the same network, which is known the same in both.
+    pub fn is_empty(&self) -> bool {
+        self.same_network
+            && self.sections_added.is_empty()
+            && self.sections_removed.is_empty()
+            && self.sections_changed.is_empty()
+            && self.peers_connected.is_empty()
+            && self.peers_disconnected.is_empty()
+    }
+}
+
+impl fmt::Display for NetworkSnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences");
+        }
+        if !self.same_network {
+            writeln!(f, "The snapshots are of different networks")?;
+        }
+        for prefix in &self.sections_added {
+            writeln!(f, "+ section {:?}", prefix)?;
+        }
+        for prefix in &self.sections_removed {
+            writeln!(f, "- section {:?}", prefix)?;
+        }
+        for change in &self.sections_changed {
+            writeln!(f, "~ section {:?}", change.prefix)?;
+            if change.old_key != change.new_key {
+                writeln!(f, "    key {:?} -> {:?}", change.old_key, change.new_key)?;
+            }
+            for elder in &change.elders_added {
+                writeln!(f, "    + Elder {}", elder)?;
+            }
+            for elder in &change.elders_removed {
+                writeln!(f, "    - Elder {}", elder)?;
+            }
+        }
+        for peer in &self.peers_connected {
+            writeln!(f, "+ connected to {}", peer)?;
+        }
+        for peer in &self.peers_disconnected {
+            writeln!(f, "- disconnected from {}", peer)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConnectionSnapshot, NetworkSnapshot, SectionSnapshot};
+    use crate::{network_knowledge::test_utils::gen_addr, types::Peer};
+
+    use eyre::Result;
+    use xor_name::Prefix;
+
+    fn section(prefix: Prefix, key: bls::PublicKey, elders: &[Peer]) -> SectionSnapshot {
+        SectionSnapshot {
+            prefix,
+            key,
+            elders: elders.to_vec(),
+        }
+    }
+
+    #[test]
+    fn snapshots_diff_tells_how_the_network_changed() -> Result<()> {
+        let genesis_key = bls::SecretKey::random().public_key();
+        let key = bls::SecretKey::random().public_key();
+        let peers: Vec<_> = (0..4)
+            .map(|_| Peer::new(xor_name::rand::random(), gen_addr()))
+            .collect();
+        let prefix_0 = Prefix::default().pushed(false);
+        let prefix_1 = Prefix::default().pushed(true);
+
+        let before = NetworkSnapshot {
+            timestamp: 1,
+            genesis_key,
+            sections: vec![
+                section(prefix_0, key, &peers[..2]),
+                section(prefix_1, key, &peers[2..]),
+            ],
+            connections: vec![ConnectionSnapshot {
+                peer: peers[0],
+                connected: true,
+            }],
+        };
+        assert!(before.diff(&before).is_empty());
+
+        // the snapshots are exchanged as JSON
+        let json = serde_json::to_string(&before)?;
+        assert_eq!(serde_json::from_str::<NetworkSnapshot>(&json)?, before);
+
+        let new_key = bls::SecretKey::random().public_key();
+        let after = NetworkSnapshot {
+            timestamp: 2,
+            genesis_key,
+            sections: vec![
+                section(prefix_0, new_key, &peers[..1]),
+                section(prefix_1.pushed(false), key, &peers[2..]),
+                section(prefix_1.pushed(true), key, &peers[2..]),
+            ],
+            connections: vec![ConnectionSnapshot {
+                peer: peers[1],
+                connected: true,
+            }],
+        };
+        let diff = before.diff(&after);
+
+        assert!(diff.same_network);
+        assert_eq!(
+            diff.sections_added,
+            vec![prefix_1.pushed(false), prefix_1.pushed(true)]
+        );
+        assert_eq!(diff.sections_removed, vec![prefix_1]);
+        assert_eq!(diff.sections_changed.len(), 1);
+        assert_eq!(diff.sections_changed[0].new_key, new_key);
+        assert_eq!(diff.sections_changed[0].elders_removed, vec![peers[1]]);
+        assert_eq!(diff.peers_connected, vec![peers[1]]);
+        assert_eq!(diff.peers_disconnected, vec![peers[0]]);
+
+        Ok(())
+    }
+}
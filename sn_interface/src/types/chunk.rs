@@ -7,21 +7,33 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{ChunkAddress, XorName};
-use bytes::Bytes;
+use bytes::{BufMut, Bytes, BytesMut};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tiny_keccak::{Hasher, Sha3};
 
 /// Maximum allowed size for a serialised Chunk to grow to.
 pub const MAX_CHUNK_SIZE_IN_BYTES: usize = 1024 * 1024 + 10 * 1024;
 
+// Domain separation tag of the names of temporary chunks, so they can never be
+// the name of a permanent chunk
+const TEMPORARY_CHUNK_NAME_TAG: &[u8] = b"sn-temporary-chunk";
+
+/// Size of the header recording the expiry of temporary chunks, ahead of their data,
+/// when they're stored.
+pub const TEMPORARY_CHUNK_HEADER_SIZE: usize = std::mem::size_of::<u64>();
+
 /// Chunk, an immutable chunk of data
 #[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, custom_debug::Debug)]
 pub struct Chunk {
     /// Network address. Omitted when serialising and
-    /// calculated from the `value` when deserialising.
+    /// calculated from the `value` and `expiry` when deserialising.
     address: ChunkAddress,
     /// Contained data.
     #[debug(skip)]
     value: Bytes,
+    /// Expiry of temporary chunks, as seconds since the epoch.
+    expiry: Option<u64>,
 }
 
 impl Chunk {
@@ -30,9 +42,76 @@ impl Chunk {
         Self {
             address: ChunkAddress(XorName::from_content(value.as_ref())),
             value,
+            expiry: None,
         }
     }
 
+    /// Creates a new temporary `Chunk`, holding the given data until the given expiry.
+    ///
+    /// The expiry is covered by the name of the chunk, which is derived apart from the names of
+    /// permanent chunks, so a permanent chunk can't be taken for a temporary one whatever its
+    /// content. Adults stop serving the chunk once it's expired, and remove it when scrubbing
+    /// their store.
+    pub fn new_temporary(data: &[u8], expiry: SystemTime) -> Self {
+        let expiry_secs = expiry
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        Self::temporary(Bytes::copy_from_slice(data), expiry_secs)
+    }
+
+    fn temporary(value: Bytes, expiry_secs: u64) -> Self {
+        Self {
+            address: ChunkAddress(temporary_name(&value, expiry_secs)),
+            value,
+            expiry: Some(expiry_secs),
+        }
+    }
+
+    /// Rebuilds a chunk from the bytes it was stored as, see `stored_bytes`.
+    ///
+    /// Whether it's a temporary chunk is told by the name it's stored under, never by its
+    /// content. Bytes which match neither kind of name give a permanent chunk whose address
+    /// differs from the given one, for the caller to find out it's corrupt.
+    pub fn from_stored(address: &ChunkAddress, bytes: Bytes) -> Self {
+        if bytes.len() >= TEMPORARY_CHUNK_HEADER_SIZE {
+            let mut expiry_secs = [0; TEMPORARY_CHUNK_HEADER_SIZE];
+            expiry_secs.copy_from_slice(&bytes[..TEMPORARY_CHUNK_HEADER_SIZE]);
+            let expiry_secs = u64::from_be_bytes(expiry_secs);
+            let data = bytes.slice(TEMPORARY_CHUNK_HEADER_SIZE..);
+            if temporary_name(&data, expiry_secs) == *address.name() {
+                return Self::temporary(data, expiry_secs);
+            }
+        }
+        Self::new(bytes)
+    }
+
+    /// Returns the bytes to store the chunk as: its value, preceded by its expiry
+    /// if it's a temporary chunk.
+    pub fn stored_bytes(&self) -> Bytes {
+        match self.expiry {
+            Some(expiry_secs) => {
+                let mut bytes =
+                    BytesMut::with_capacity(TEMPORARY_CHUNK_HEADER_SIZE + self.value.len());
+                bytes.put_u64(expiry_secs);
+                bytes.put_slice(&self.value);
+                bytes.freeze()
+            }
+            None => self.value.clone(),
+        }
+    }
+
+    /// Returns the expiry of a temporary chunk, or `None` if the chunk doesn't expire.
+    pub fn expiry(&self) -> Option<SystemTime> {
+        self.expiry
+            .map(|expiry_secs| UNIX_EPOCH + Duration::from_secs(expiry_secs))
+    }
+
+    /// Returns `true` if this is a temporary chunk which is expired at the given time.
+    pub fn is_expired_at(&self, now: SystemTime) -> bool {
+        self.expiry().map_or(false, |expiry| expiry <= now)
+    }
+
     /// Returns the value.
     pub fn value(&self) -> &Bytes {
         &self.value
@@ -55,7 +134,10 @@ impl Chunk {
 
     /// Returns size of this chunk after serialisation.
     pub fn serialised_size(&self) -> usize {
-        self.value.len()
+        match self.expiry {
+            Some(_) => TEMPORARY_CHUNK_HEADER_SIZE + self.value.len(),
+            None => self.value.len(),
+        }
     }
 
     /// Returns `true` if the size is valid.
@@ -66,26 +148,43 @@ impl Chunk {
 
 impl Serialize for Chunk {
     fn serialize<S: Serializer>(&self, serialiser: S) -> Result<S::Ok, S::Error> {
-        // Address is omitted since it's derived from value
-        self.value.serialize(serialiser)
+        // Address is omitted since it's derived from value and expiry
+        (&self.value, self.expiry).serialize(serialiser)
     }
 }
 
 impl<'de> Deserialize<'de> for Chunk {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        let value = Deserialize::deserialize(deserializer)?;
-        Ok(Self::new(value))
+        let (value, expiry) = Deserialize::deserialize(deserializer)?;
+        Ok(match expiry {
+            Some(expiry_secs) => Self::temporary(value, expiry_secs),
+            None => Self::new(value),
+        })
     }
 }
 
+// Name of a temporary chunk, covering its expiry along with its data
+fn temporary_name(data: &[u8], expiry_secs: u64) -> XorName {
+    let mut hasher = Sha3::v256();
+    hasher.update(TEMPORARY_CHUNK_NAME_TAG);
+    hasher.update(&expiry_secs.to_be_bytes());
+    hasher.update(data);
+    let mut name = XorName::default();
+    hasher.finalize(&mut name.0);
+    name
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::types::{utils, Chunk, Error, Result};
+    use crate::types::{utils, Chunk, ChunkAddress, Error, Result};
     use bytes::Bytes;
     use hex::encode;
     use rand_07::{self, Rng, SeedableRng};
     use rand_xorshift::XorShiftRng;
-    use std::{env, iter, thread};
+    use std::{
+        env, iter, thread,
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    };
 
     #[test]
     fn deterministic_name() {
@@ -142,6 +241,64 @@ mod tests {
         assert_eq!(&expected_name, &chunk_name);
     }
 
+    #[test]
+    fn temporary_chunk_expiry() -> Result<()> {
+        let now = SystemTime::now();
+        let expiry = now + Duration::from_secs(60);
+        let chunk = Chunk::new_temporary(b"ephemeral state", expiry);
+
+        let expiry_secs = expiry
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        assert_eq!(
+            chunk.expiry(),
+            Some(UNIX_EPOCH + Duration::from_secs(expiry_secs))
+        );
+        assert_eq!(chunk.value(), &Bytes::from_static(b"ephemeral state"));
+        assert!(!chunk.is_expired_at(now));
+        assert!(chunk.is_expired_at(expiry + Duration::from_secs(1)));
+
+        // the expiry is covered by the name of the chunk
+        let later = Chunk::new_temporary(b"ephemeral state", expiry + Duration::from_secs(60));
+        assert_ne!(chunk.name(), later.name());
+
+        let permanent = Chunk::new(Bytes::from_static(b"ephemeral state"));
+        assert_eq!(permanent.expiry(), None);
+        assert_ne!(chunk.name(), permanent.name());
+        assert!(!permanent.is_expired_at(expiry + Duration::from_secs(1)));
+
+        let stored = Chunk::from_stored(chunk.address(), chunk.stored_bytes());
+        assert_eq!(stored, chunk);
+        assert_eq!(
+            utils::deserialise::<Chunk>(&utils::serialise(&chunk)?)?,
+            chunk
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn permanent_chunk_is_never_taken_for_a_temporary_one() -> Result<()> {
+        // the stored bytes of a temporary chunk, uploaded as a permanent chunk
+        let temporary = Chunk::new_temporary(b"ephemeral state", UNIX_EPOCH);
+        let permanent = Chunk::new(temporary.stored_bytes());
+        assert_eq!(permanent.expiry(), None);
+        assert!(!permanent.is_expired_at(SystemTime::now()));
+
+        let stored = Chunk::from_stored(permanent.address(), permanent.stored_bytes());
+        assert_eq!(stored, permanent);
+        assert_eq!(stored.expiry(), None);
+
+        // and bytes which don't match the name they're stored under are left for the caller
+        // to find out they're corrupt
+        let address = ChunkAddress(xor_name::rand::random());
+        let stored = Chunk::from_stored(&address, temporary.stored_bytes());
+        assert_ne!(stored.address(), &address);
+
+        Ok(())
+    }
+
     #[test]
     fn serialisation() -> Result<()> {
         let mut rng = get_rng();
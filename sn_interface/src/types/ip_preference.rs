@@ -0,0 +1,140 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    str::FromStr,
+};
+
+/// IP version preferred to listen on, and to contact peers with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum IpPreference {
+    /// Listen on IPv4 only, contacting peers over IPv4 first
+    V4,
+    /// Listen on IPv6, and on IPv4 too where the host supports dual-stack sockets,
+    /// contacting peers over IPv6 first
+    V6,
+}
+
+impl Default for IpPreference {
+    fn default() -> Self {
+        Self::V4
+    }
+}
+
+impl IpPreference {
+    /// Address to listen on when none was given, with a random unused port.
+    ///
+    /// For `IpPreference::V6` it's `[::]:0`, which dual-stack hosts also accept IPv4 traffic on.
+    pub fn unspecified_addr(&self) -> SocketAddr {
+        match self {
+            Self::V4 => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+            Self::V6 => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+        }
+    }
+
+    /// Whether the given address is of the preferred IP version
+    pub fn prefers(&self, addr: &SocketAddr) -> bool {
+        match self {
+            Self::V4 => addr.is_ipv4(),
+            Self::V6 => addr.is_ipv6(),
+        }
+    }
+
+    /// Order the given contacts so the ones of the preferred IP version are tried first,
+    /// keeping the others as fallback. The relative order of the contacts is otherwise kept.
+    pub fn sort_contacts(&self, contacts: impl IntoIterator<Item = SocketAddr>) -> Vec<SocketAddr> {
+        let (mut preferred, fallback): (Vec<_>, Vec<_>) =
+            contacts.into_iter().partition(|addr| self.prefers(addr));
+        preferred.extend(fallback);
+        preferred
+    }
+}
+
+impl Display for IpPreference {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::V4 => write!(formatter, "v4"),
+            Self::V6 => write!(formatter, "v6"),
+        }
+    }
+}
+
+impl FromStr for IpPreference {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "v4" => Ok(Self::V4),
+            "v6" => Ok(Self::V6),
+            other => Err(format!(
+                "Unknown IP preference '{}', expected 'v4' or 'v6'",
+                other
+            )),
+        }
+    }
+}
+
+/// Turn an IPv4-mapped IPv6 address, as reported for IPv4 peers reaching a dual-stack socket,
+/// back into the plain IPv4 address the peer is known by.
+pub fn canonical_addr(addr: SocketAddr) -> SocketAddr {
+    match addr.ip() {
+        IpAddr::V6(ip) => match ip.octets() {
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, a, b, c, d] => {
+                SocketAddr::from((Ipv4Addr::new(a, b, c, d), addr.port()))
+            }
+            _ => addr,
+        },
+        IpAddr::V4(_) => addr,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preferred_contacts_come_first() {
+        let v4_a: SocketAddr = "10.0.0.1:12000".parse().unwrap();
+        let v4_b: SocketAddr = "10.0.0.2:12000".parse().unwrap();
+        let v6_a: SocketAddr = "[2001:db8::1]:12000".parse().unwrap();
+        let v6_b: SocketAddr = "[2001:db8::2]:12000".parse().unwrap();
+        let contacts = vec![v4_a, v6_a, v4_b, v6_b];
+
+        assert_eq!(
+            IpPreference::V6.sort_contacts(contacts.clone()),
+            vec![v6_a, v6_b, v4_a, v4_b]
+        );
+        assert_eq!(
+            IpPreference::V4.sort_contacts(contacts),
+            vec![v4_a, v4_b, v6_a, v6_b]
+        );
+    }
+
+    #[test]
+    fn ip_preference_from_str() {
+        assert_eq!("v4".parse(), Ok(IpPreference::V4));
+        assert_eq!("v6".parse(), Ok(IpPreference::V6));
+        assert!("ipv6".parse::<IpPreference>().is_err());
+        assert!(IpPreference::V6.unspecified_addr().ip().is_unspecified());
+        assert!(IpPreference::V6.unspecified_addr().is_ipv6());
+    }
+
+    #[test]
+    fn mapped_addrs_are_made_canonical() {
+        let mapped: SocketAddr = "[::ffff:192.168.1.10]:12000".parse().unwrap();
+        let loopback: SocketAddr = "[::1]:12000".parse().unwrap();
+        let v4: SocketAddr = "192.168.1.10:12000".parse().unwrap();
+
+        assert_eq!(canonical_addr(mapped), v4);
+        assert_eq!(canonical_addr(loopback), loopback);
+        assert_eq!(canonical_addr(v4), v4);
+    }
+}
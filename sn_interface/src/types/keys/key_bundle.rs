@@ -0,0 +1,164 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::ed25519;
+use bls::{PublicKeySet, PublicKeyShare, SecretKeyShare, SignatureShare};
+use ed25519_dalek::Verifier;
+use serde::{Deserialize, Serialize};
+use xor_name::XorName;
+
+// Domains of the cross-signatures, so they can't be replayed as signatures of anything else
+const ED25519_SIG_DOMAIN: &[u8] = b"sn-key-bundle-bls-share";
+const BLS_SHARE_SIG_DOMAIN: &[u8] = b"sn-key-bundle-ed25519";
+
+/// A node's Ed25519 identity along with a BLS key share, cross-signed to prove the node
+/// controls both of them.
+///
+/// It's sent by nodes being promoted to Elder along with their first use of the key share they
+/// got out of DKG, so a node can't register a key share it doesn't hold as its own.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct KeyBundle {
+    ed25519: ed25519::PublicKey,
+    bls_share: PublicKeyShare,
+    bls_share_index: usize,
+    // Signature of the BLS public key share by the Ed25519 key
+    ed25519_sig: ed25519::Signature,
+    // Signature of the Ed25519 public key by the BLS key share
+    bls_share_sig: SignatureShare,
+}
+
+impl KeyBundle {
+    /// Creates the bundle of the given Ed25519 keypair and the `index`-th BLS key share.
+    pub fn new(
+        ed25519_keypair: &ed25519::Keypair,
+        bls_share_index: usize,
+        secret_key_share: &SecretKeyShare,
+    ) -> Self {
+        let bls_share = secret_key_share.public_key_share();
+        let ed25519_sig = ed25519::sign(
+            &Self::ed25519_signable_bytes(&bls_share, bls_share_index),
+            ed25519_keypair,
+        );
+        let bls_share_sig =
+            secret_key_share.sign(Self::bls_share_signable_bytes(&ed25519_keypair.public));
+
+        Self {
+            ed25519: ed25519_keypair.public,
+            bls_share,
+            bls_share_index,
+            ed25519_sig,
+            bls_share_sig,
+        }
+    }
+
+    /// Name of the node the bundle is of.
+    pub fn name(&self) -> XorName {
+        ed25519::name(&self.ed25519)
+    }
+
+    /// Ed25519 public key of the node.
+    pub fn ed25519_public_key(&self) -> &ed25519::PublicKey {
+        &self.ed25519
+    }
+
+    /// BLS public key share of the node.
+    pub fn bls_share(&self) -> &PublicKeyShare {
+        &self.bls_share
+    }
+
+    /// Index of the BLS key share in its key set.
+    pub fn bls_share_index(&self) -> usize {
+        self.bls_share_index
+    }
+
+    /// Verifies the cross-signatures, i.e. that whoever made the bundle holds both keys.
+    pub fn verify(&self) -> bool {
+        let ed25519_signed = Self::ed25519_signable_bytes(&self.bls_share, self.bls_share_index);
+        let bls_share_signed = Self::bls_share_signable_bytes(&self.ed25519);
+
+        self.ed25519
+            .verify(&ed25519_signed, &self.ed25519_sig)
+            .is_ok()
+            && self.bls_share.verify(&self.bls_share_sig, bls_share_signed)
+    }
+
+    /// Verifies the bundle is a valid one of the node with the given name, holding the
+    /// `index`-th key share of the given key set.
+    pub fn verify_for(&self, name: &XorName, public_key_set: &PublicKeySet, index: usize) -> bool {
+        self.name() == *name
+            && self.bls_share_index == index
+            && public_key_set.public_key_share(index) == self.bls_share
+            && self.verify()
+    }
+
+    fn ed25519_signable_bytes(bls_share: &PublicKeyShare, index: usize) -> Vec<u8> {
+        let mut bytes = ED25519_SIG_DOMAIN.to_vec();
+        bytes.extend(bls_share.to_bytes());
+        bytes.extend((index as u64).to_be_bytes());
+        bytes
+    }
+
+    fn bls_share_signable_bytes(ed25519: &ed25519::PublicKey) -> Vec<u8> {
+        let mut bytes = BLS_SHARE_SIG_DOMAIN.to_vec();
+        bytes.extend(ed25519.to_bytes());
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gen_keys() -> (ed25519::Keypair, bls::SecretKeySet) {
+        let ed25519_keypair = ed25519::Keypair::generate(&mut rand_07::thread_rng());
+        let secret_key_set = bls::SecretKeySet::random(1, &mut rand::thread_rng());
+        (ed25519_keypair, secret_key_set)
+    }
+
+    #[test]
+    fn bundle_of_held_keys_is_valid() {
+        let (ed25519_keypair, secret_key_set) = gen_keys();
+        let bundle = KeyBundle::new(&ed25519_keypair, 1, &secret_key_set.secret_key_share(1));
+
+        assert!(bundle.verify());
+        assert!(bundle.verify_for(
+            &ed25519::name(&ed25519_keypair.public),
+            &secret_key_set.public_keys(),
+            1
+        ));
+        // it doesn't hold for another node nor another key share
+        assert!(!bundle.verify_for(&xor_name::rand::random(), &secret_key_set.public_keys(), 1));
+        assert!(!bundle.verify_for(&bundle.name(), &secret_key_set.public_keys(), 0));
+    }
+
+    #[test]
+    fn bundle_with_a_key_share_not_held_is_invalid() {
+        let (ed25519_keypair, secret_key_set) = gen_keys();
+        let (other_ed25519_keypair, _) = gen_keys();
+        let other_bundle = KeyBundle::new(
+            &other_ed25519_keypair,
+            0,
+            &secret_key_set.secret_key_share(0),
+        );
+
+        // claiming the key share of another node, reusing its signature as we can't make one
+        let ed25519_sig = ed25519::sign(
+            &KeyBundle::ed25519_signable_bytes(&other_bundle.bls_share, 0),
+            &ed25519_keypair,
+        );
+        let bundle = KeyBundle {
+            ed25519: ed25519_keypair.public,
+            bls_share: other_bundle.bls_share,
+            bls_share_index: 0,
+            ed25519_sig,
+            bls_share_sig: other_bundle.bls_share_sig,
+        };
+        assert!(!bundle.verify());
+        assert!(!bundle.verify_for(&bundle.name(), &secret_key_set.public_keys(), 0));
+    }
+}
@@ -13,6 +13,7 @@
 //! secret key.
 
 pub mod ed25519;
+pub(super) mod key_bundle;
 pub(super) mod keypair;
 pub(super) mod node_keypairs;
 pub(super) mod public_key;
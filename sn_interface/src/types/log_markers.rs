@@ -33,6 +33,7 @@ pub enum LogMarker {
     // Malice
     DeviantsDetected,
     ProposeOffline,
+    ProposeDemotion,
     AcceptedNodeAsOffline,
     IgnoredNodeAsOffline,
     // Messaging
@@ -45,6 +46,8 @@ pub enum LogMarker {
     // Data
     DataStoreReceivedAtElder,
     DataQueryReceviedAtElder,
+    DataQueryAnsweredFromCache,
+    DataQueryDelegated,
     // Chunks
     StoringChunk,
     StoredNewChunk,
@@ -56,10 +59,25 @@ pub enum LogMarker {
     DataReorganisationUnderway,
     QueuingMissingReplicatedData,
     SendingMissingReplicatedData,
+    MissingChunkReported,
+    RepairingReportedData,
+    // Data integrity
+    CorruptChunkQuarantined,
+    ExpiredChunkRemoved,
+    ChunkScrubPassCompleted,
     // Register
     RegisterWrite,
     RegisterQueryReceivedAtElder,
     RegisterQueryReceivedAtAdult,
+    // Network isolation
+    OtherNetworkMsgDropped,
+    // Inbound rate limiting
+    InboundMsgRateLimited,
+    // Duplicate msgs suppression
+    DuplicateMsgDropped,
+    // Blacklisting of peers violating the protocol
+    PeerBlacklisted,
+    BlacklistedPeerRefused,
     // Routing cmds
     DispatchHandleMsgCmd,
     DispatchSendMsgCmd,
@@ -92,6 +110,7 @@ pub enum LogMarker {
     SendDirect,
     SendDirectToNodes,
     SendResourceProofChallenge,
+    SendAddrChallenge,
     SendJoinRequest,
     SendJoinAsRelocatedResponse,
     SendJoinRejected,
@@ -102,13 +121,20 @@ pub enum LogMarker {
     SendJoinsDisallowed,
     SendDKGUnderway,
     SendNodeApproval,
+    SendReachabilityCheckResponse,
     // Approved to join
     ReceivedJoinApproval,
+    // Dialled back, or not, ahead of joining
+    ReachabilityChecked,
+    // Self-checked after going down uncleanly
+    RecoveryChecked,
     // Connections
     ConnectionOpened,
     ConnectionClosed,
+    PeerLiveness,
     ConnectionReused,
     // Relocation
     RelocateStart,
     RelocateEnd,
+    RelocateTimedOut,
 }
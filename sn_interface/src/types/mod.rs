@@ -23,6 +23,8 @@ mod address;
 mod cache;
 mod chunk;
 mod errors;
+mod ip_preference;
+mod node_attestation;
 mod peer;
 mod token;
 
@@ -34,15 +36,21 @@ pub use address::{
     SafeKeyAddress, Scope, SpentbookAddress,
 };
 pub use cache::Cache;
-pub use chunk::{Chunk, MAX_CHUNK_SIZE_IN_BYTES};
+pub use chunk::{Chunk, MAX_CHUNK_SIZE_IN_BYTES, TEMPORARY_CHUNK_HEADER_SIZE};
 pub use errors::{convert_dt_error_to_error_msg, Error, Result};
+pub use ip_preference::{canonical_addr, IpPreference};
 pub use keys::{
+    key_bundle::KeyBundle,
     keypair::{BlsKeypairShare, Encryption, Keypair, OwnerType, Signing},
     node_keypairs::NodeKeypairs,
     public_key::PublicKey,
     secret_key::SecretKey,
     signature::{Signature, SignatureShare},
 };
+pub use node_attestation::{
+    NodeAttestation, NodeAttestationError, MAX_NODE_ATTESTATION_AGE,
+    MAX_NODE_ATTESTATION_CLOCK_SKEW,
+};
 pub use peer::Peer;
 pub use token::Token;
 
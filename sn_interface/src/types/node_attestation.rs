@@ -0,0 +1,283 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::keys::ed25519;
+use crate::messaging::system::{MembershipState, NodeState, SectionAuth};
+use crate::network_knowledge::SectionAuthUtils;
+
+use bls::PublicKey as BlsPublicKey;
+use ed25519_dalek::{Keypair, PublicKey, Signature, Verifier};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use xor_name::{Prefix, XorName};
+
+// Tag prepended to the bytes signed, so the signature can't be passed off as one over a msg
+const NODE_ATTESTATION_TAG: &[u8] = b"sn_node_identity_attestation";
+
+/// How long after it was signed an attestation is still accepted, in seconds.
+pub const MAX_NODE_ATTESTATION_AGE: u64 = 24 * 60 * 60;
+
+/// How far ahead of the verifier's clock an attestation may claim to be signed, in seconds.
+pub const MAX_NODE_ATTESTATION_CLOCK_SKEW: u64 = 5 * 60;
+
+/// Statement by a node of its identity, signed with its key, which the operator running the
+/// node can publish to prove they run it.
+///
+/// The attestation carries the section signed state of the node as a member of its section, so
+/// it proves the node joined the section, as long as the key the state is signed with is checked
+/// against the network, e.g. with the section chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeAttestation {
+    /// Name of the node
+    pub name: XorName,
+    /// Public key of the node, which its name derives from
+    pub node_pk: PublicKey,
+    /// Prefix of the section of the node
+    pub section_prefix: Prefix,
+    /// Current key of the section of the node
+    pub section_pk: BlsPublicKey,
+    /// Generation of the authority of the section, i.e. the length of its chain of keys
+    pub sap_generation: u64,
+    /// When the attestation was signed, in seconds since the Unix epoch
+    pub timestamp: u64,
+    /// State of the node as a member of its section, signed by the section
+    pub node_state: SectionAuth<NodeState>,
+    /// Signature of the node over all the above
+    pub signature: Signature,
+}
+
+/// Why a [`NodeAttestation`] is invalid.
+#[derive(Clone, Copy, Debug, Error, Eq, PartialEq)]
+pub enum NodeAttestationError {
+    /// The name of the node isn't the one of its public key
+    #[error("The name attested isn't the one of the node's public key")]
+    NameMismatch,
+    /// The attestation isn't signed with the key of the node
+    #[error("The attestation isn't signed by the node")]
+    InvalidSignature,
+    /// The section signed state isn't the one of the node as a member of its section
+    #[error("The attestation doesn't prove the node is a member of its section")]
+    NotSectionMember,
+    /// The state of the node isn't signed with the key it claims
+    #[error("The state of the node isn't signed by its section")]
+    InvalidMembershipSignature,
+    /// The attestation was signed too long ago, or claims to be signed in the future
+    #[error("The attestation was signed at {0}, which is too long ago or in the future")]
+    Stale(u64),
+}
+
+impl NodeAttestation {
+    /// Signs an attestation of the identity of the node with the given keypair, along with its
+    /// section signed state as a member of its section.
+    pub fn new(
+        keypair: &Keypair,
+        section_prefix: Prefix,
+        section_pk: BlsPublicKey,
+        sap_generation: u64,
+        timestamp: u64,
+        node_state: SectionAuth<NodeState>,
+    ) -> Self {
+        let name = ed25519::name(&keypair.public);
+        let signed_bytes = Self::signed_bytes(
+            &name,
+            &keypair.public,
+            &section_prefix,
+            &section_pk,
+            sap_generation,
+            timestamp,
+            &node_state,
+        );
+
+        Self {
+            name,
+            node_pk: keypair.public,
+            section_prefix,
+            section_pk,
+            sap_generation,
+            timestamp,
+            node_state,
+            signature: ed25519::sign(&signed_bytes, keypair),
+        }
+    }
+
+    /// Checks the attestation is signed by the node it's about, and carries its state as a
+    /// current member of its section, signed with the key the state claims. Whether that key is
+    /// one of the section is left to check against the network.
+    pub fn verify(&self) -> Result<(), NodeAttestationError> {
+        if ed25519::name(&self.node_pk) != self.name {
+            return Err(NodeAttestationError::NameMismatch);
+        }
+
+        let signed_bytes = Self::signed_bytes(
+            &self.name,
+            &self.node_pk,
+            &self.section_prefix,
+            &self.section_pk,
+            self.sap_generation,
+            self.timestamp,
+            &self.node_state,
+        );
+        self.node_pk
+            .verify(&signed_bytes, &self.signature)
+            .map_err(|_| NodeAttestationError::InvalidSignature)?;
+
+        if self.node_state.name != self.name || self.node_state.state != MembershipState::Joined {
+            return Err(NodeAttestationError::NotSectionMember);
+        }
+        if !self.node_state.self_verify() {
+            return Err(NodeAttestationError::InvalidMembershipSignature);
+        }
+
+        Ok(())
+    }
+
+    /// Checks the attestation was signed no longer than [`MAX_NODE_ATTESTATION_AGE`] before
+    /// `now`, nor more than [`MAX_NODE_ATTESTATION_CLOCK_SKEW`] after it, in seconds since the
+    /// Unix epoch, so an old attestation of a node which since left can't be passed off.
+    pub fn check_freshness(&self, now: u64) -> Result<(), NodeAttestationError> {
+        let too_old = self.timestamp.saturating_add(MAX_NODE_ATTESTATION_AGE) < now;
+        let in_future = self.timestamp > now.saturating_add(MAX_NODE_ATTESTATION_CLOCK_SKEW);
+        if too_old || in_future {
+            return Err(NodeAttestationError::Stale(self.timestamp));
+        }
+        Ok(())
+    }
+
+    fn signed_bytes(
+        name: &XorName,
+        node_pk: &PublicKey,
+        section_prefix: &Prefix,
+        section_pk: &BlsPublicKey,
+        sap_generation: u64,
+        timestamp: u64,
+        node_state: &SectionAuth<NodeState>,
+    ) -> Vec<u8> {
+        let mut bytes = NODE_ATTESTATION_TAG.to_vec();
+        bytes.extend_from_slice(&name.0);
+        bytes.extend_from_slice(node_pk.as_bytes());
+        bytes.extend_from_slice(&(section_prefix.bit_count() as u64).to_le_bytes());
+        bytes.extend_from_slice(&section_prefix.name().0);
+        bytes.extend_from_slice(&section_pk.to_bytes());
+        bytes.extend_from_slice(&sap_generation.to_le_bytes());
+        bytes.extend_from_slice(&timestamp.to_le_bytes());
+        bytes.extend_from_slice(&node_state.sig.public_key.to_bytes());
+        bytes.extend_from_slice(&node_state.sig.signature.to_bytes());
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NodeAttestation, NodeAttestationError, MAX_NODE_ATTESTATION_AGE};
+    use crate::{
+        messaging::system::{NodeState, SectionAuth},
+        network_knowledge::{test_utils::section_signed, MIN_ADULT_AGE},
+        types::keys::ed25519,
+    };
+
+    use ed25519_dalek::Keypair;
+    use eyre::Result;
+    use std::net::{Ipv4Addr, SocketAddr};
+    use xor_name::{Prefix, XorName};
+
+    const TIMESTAMP: u64 = 1_650_000_000;
+
+    fn node_state(section_sk: &bls::SecretKey, name: XorName) -> Result<SectionAuth<NodeState>> {
+        let addr = SocketAddr::from((Ipv4Addr::LOCALHOST, 12345));
+        Ok(section_signed(
+            section_sk,
+            NodeState::joined(name, addr, None),
+        )?)
+    }
+
+    fn attestation(
+        keypair: &Keypair,
+        section_sk: &bls::SecretKey,
+        node_state: SectionAuth<NodeState>,
+    ) -> NodeAttestation {
+        NodeAttestation::new(
+            keypair,
+            Prefix::default(),
+            section_sk.public_key(),
+            3,
+            TIMESTAMP,
+            node_state,
+        )
+    }
+
+    #[test]
+    fn attestation_is_only_valid_as_signed_by_the_node() -> Result<()> {
+        let keypair = ed25519::gen_keypair(&Prefix::default().range_inclusive(), MIN_ADULT_AGE);
+        let section_sk = bls::SecretKey::random();
+        let node_state = node_state(&section_sk, ed25519::name(&keypair.public))?;
+        let attestation = attestation(&keypair, &section_sk, node_state);
+        attestation.verify()?;
+
+        let mut tampered = attestation.clone();
+        tampered.sap_generation += 1;
+        assert_eq!(
+            tampered.verify(),
+            Err(NodeAttestationError::InvalidSignature)
+        );
+
+        let other_keypair =
+            ed25519::gen_keypair(&Prefix::default().range_inclusive(), MIN_ADULT_AGE);
+        let mut impersonated = attestation;
+        impersonated.name = ed25519::name(&other_keypair.public);
+        assert_eq!(
+            impersonated.verify(),
+            Err(NodeAttestationError::NameMismatch)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn attestation_is_only_valid_with_the_section_signed_state_of_the_node() -> Result<()> {
+        let keypair = ed25519::gen_keypair(&Prefix::default().range_inclusive(), MIN_ADULT_AGE);
+        let section_sk = bls::SecretKey::random();
+
+        // the state of another node, even signed by the section, doesn't do
+        let other_state = node_state(&section_sk, xor_name::rand::random())?;
+        assert_eq!(
+            attestation(&keypair, &section_sk, other_state).verify(),
+            Err(NodeAttestationError::NotSectionMember)
+        );
+
+        // nor does a state signed with another key than the one it claims
+        let mut forged_state = node_state(&section_sk, ed25519::name(&keypair.public))?;
+        forged_state.sig.signature = bls::SecretKey::random().sign(b"forged");
+        assert_eq!(
+            attestation(&keypair, &section_sk, forged_state).verify(),
+            Err(NodeAttestationError::InvalidMembershipSignature)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn attestation_is_only_valid_for_a_while() -> Result<()> {
+        let keypair = ed25519::gen_keypair(&Prefix::default().range_inclusive(), MIN_ADULT_AGE);
+        let section_sk = bls::SecretKey::random();
+        let node_state = node_state(&section_sk, ed25519::name(&keypair.public))?;
+        let attestation = attestation(&keypair, &section_sk, node_state);
+
+        attestation.check_freshness(TIMESTAMP)?;
+        attestation.check_freshness(TIMESTAMP + MAX_NODE_ATTESTATION_AGE)?;
+        assert_eq!(
+            attestation.check_freshness(TIMESTAMP + MAX_NODE_ATTESTATION_AGE + 1),
+            Err(NodeAttestationError::Stale(TIMESTAMP))
+        );
+        assert_eq!(
+            attestation.check_freshness(TIMESTAMP - MAX_NODE_ATTESTATION_AGE),
+            Err(NodeAttestationError::Stale(TIMESTAMP))
+        );
+
+        Ok(())
+    }
+}
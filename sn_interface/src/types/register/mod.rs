@@ -13,12 +13,13 @@ mod reg_crdt;
 pub use metadata::{Action, Entry};
 pub use policy::{
     Permissions, Policy, PrivatePermissions, PrivatePolicy, PublicPermissions, PublicPolicy, User,
+    WriteAccess,
 };
 pub use reg_crdt::EntryHash;
 
 pub(crate) use reg_crdt::{CrdtOperation, RegisterCrdt};
 
-use super::{Error, Result};
+use super::{utils, Error, Result};
 use crate::{types::RegisterAddress, types::Scope};
 use self_encryption::MIN_ENCRYPTABLE_BYTES;
 use serde::{Deserialize, Serialize};
@@ -169,6 +170,12 @@ impl Register {
         self.crdt.get(hash).ok_or(Error::NoSuchEntry)
     }
 
+    /// Return the hashes of the entries the one corresponding to the provided 'hash' was written
+    /// on top of, i.e. the `children` it was written with, which allows to walk its history.
+    pub fn children(&self, hash: EntryHash) -> Result<BTreeSet<EntryHash>> {
+        self.crdt.children(hash).ok_or(Error::NoSuchEntry)
+    }
+
     /// Read the last entry, or entries when there are branches, if the register is not empty.
     pub fn read(&self) -> BTreeSet<(EntryHash, Entry)> {
         self.crdt.read()
@@ -223,14 +230,34 @@ impl Register {
         let requester = requester.unwrap_or(self.authority);
         self.policy.is_action_allowed(requester, action)
     }
+
+    /// Helper to check the given `writer` can apply the CRDT operation.
+    ///
+    /// Besides the writer having the permission to write, entries of a Register anyone can
+    /// write to are required to be signed by their writer, for them to be attributable.
+    pub fn check_write_op(&self, op: &RegisterOp<Entry>, writer: User) -> Result<()> {
+        self.check_permissions(Action::Write, Some(writer))?;
+        if !self.policy.is_public_append() {
+            return Ok(());
+        }
+
+        let public_key = match (writer, op.source) {
+            (User::Key(public_key), User::Key(source)) if source == public_key => public_key,
+            _ => return Err(Error::AccessDenied(writer)),
+        };
+        let signature = op.signature.as_ref().ok_or(Error::CrdtMissingOpSignature)?;
+        public_key
+            .verify(signature, utils::serialise(&op.crdt_op)?)
+            .map_err(|_| Error::InvalidSignature)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::super::{
         register::{
-            Entry, EntryHash, Permissions, PrivatePermissions, PrivatePolicy, PublicPermissions,
-            PublicPolicy, Register, RegisterOp, User,
+            Action, Entry, EntryHash, Permissions, Policy, PrivatePermissions, PrivatePolicy,
+            PublicPermissions, PublicPolicy, Register, RegisterOp, User, WriteAccess,
         },
         utils, Error, Keypair, Result,
     };
@@ -379,9 +406,19 @@ mod tests {
         let third_entry = register.get(entry3_hash)?;
         assert_eq!(third_entry, &entry3);
 
+        assert_eq!(register.children(entry1_hash)?, BTreeSet::new());
+        assert_eq!(
+            register.children(entry3_hash)?,
+            vec![entry1_hash, entry2_hash].into_iter().collect()
+        );
+
         let non_existing_hash = EntryHash::default();
         let entry_not_found = register.get(non_existing_hash);
         assert_eq!(entry_not_found, Err(Error::NoSuchEntry));
+        assert_eq!(
+            register.children(non_existing_hash),
+            Err(Error::NoSuchEntry)
+        );
 
         Ok(())
     }
@@ -525,6 +562,85 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn register_write_access_policies() -> Result<()> {
+        let owner = User::Key(Keypair::new_ed25519().public_key());
+        let writer = Keypair::new_ed25519().public_key();
+        let other = User::Key(Keypair::new_ed25519().public_key());
+
+        let owner_only = Policy::with_write_access(owner, Scope::Public, &WriteAccess::OwnerOnly)?;
+        assert!(owner_only.is_action_allowed(owner, Action::Write).is_ok());
+        assert!(owner_only.is_action_allowed(other, Action::Write).is_err());
+
+        let writers = WriteAccess::Writers(BTreeSet::from([writer]));
+        for scope in [Scope::Public, Scope::Private] {
+            let policy = Policy::with_write_access(owner, scope, &writers)?;
+            assert!(policy
+                .is_action_allowed(User::Key(writer), Action::Write)
+                .is_ok());
+            assert!(policy.is_action_allowed(other, Action::Write).is_err());
+            assert!(!policy.is_public_append());
+        }
+
+        let public_append =
+            Policy::with_write_access(owner, Scope::Public, &WriteAccess::PublicAppend)?;
+        assert!(public_append
+            .is_action_allowed(other, Action::Write)
+            .is_ok());
+        assert!(public_append.is_public_append());
+        assert_eq!(
+            Policy::with_write_access(owner, Scope::Private, &WriteAccess::PublicAppend),
+            Err(Error::InvalidOperation)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_public_append_requires_entries_signed_by_their_writer() -> Result<()> {
+        let owner_keypair = Keypair::new_ed25519();
+        let owner = User::Key(owner_keypair.public_key());
+        let writer_keypair = Keypair::new_ed25519();
+        let writer = User::Key(writer_keypair.public_key());
+
+        let policy =
+            match Policy::with_write_access(owner, Scope::Public, &WriteAccess::PublicAppend)? {
+                Policy::Public(policy) => policy,
+                Policy::Private(_) => panic!("a public policy was expected"),
+            };
+        let mut register = Register::new_public(
+            owner,
+            xor_name::rand::random(),
+            43_000,
+            Some(policy),
+            u16::MAX,
+        );
+
+        let (_, mut op) = register.write(random_register_entry(), BTreeSet::new())?;
+        op.source = writer;
+        assert_eq!(
+            register.check_write_op(&op, writer),
+            Err(Error::CrdtMissingOpSignature)
+        );
+
+        // signed by someone else than its writer
+        let forged_op = sign_register_op(op.clone(), &owner_keypair)?;
+        assert_eq!(
+            register.check_write_op(&forged_op, writer),
+            Err(Error::InvalidSignature)
+        );
+        // attributed to someone else than its writer
+        assert_eq!(
+            register.check_write_op(&forged_op, owner),
+            Err(Error::AccessDenied(owner))
+        );
+
+        let signed_op = sign_register_op(op, &writer_keypair)?;
+        register.check_write_op(&signed_op, writer)?;
+
+        Ok(())
+    }
+
     // Helpers for tests
 
     fn sign_register_op(mut op: RegisterOp<Entry>, keypair: &Keypair) -> Result<RegisterOp<Entry>> {
@@ -6,10 +6,25 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::super::{Error, PublicKey, Result};
+use super::super::{Error, PublicKey, Result, Scope};
 use super::Action;
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeMap, hash::Hash};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    hash::Hash,
+};
+
+/// Who can write to a Register besides its owner, to build its [`Policy`] with.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Debug)]
+pub enum WriteAccess {
+    /// Only the owner can write.
+    OwnerOnly,
+    /// The owner and the given writers can write.
+    Writers(BTreeSet<PublicKey>),
+    /// Anyone can append entries, each signed by its writer. Only Public Registers can be
+    /// appended to by anyone.
+    PublicAppend,
+}
 
 /// Wrapper type for permissions, which can be public or private.
 #[derive(Clone, Serialize, Deserialize, PartialEq, PartialOrd, Ord, Eq, Hash, Debug)]
@@ -21,6 +36,49 @@ pub enum Policy {
 }
 
 impl Policy {
+    /// Builds the policy of a Register of the given scope, owned by `owner`, whose write access
+    /// is as given. Readers of a Private Register are its owner and writers.
+    pub fn with_write_access(owner: User, scope: Scope, access: &WriteAccess) -> Result<Self> {
+        let writers: Vec<User> = match access {
+            WriteAccess::OwnerOnly => vec![],
+            WriteAccess::Writers(keys) => keys.iter().copied().map(User::Key).collect(),
+            WriteAccess::PublicAppend => vec![User::Anyone],
+        };
+
+        let policy = match scope {
+            Scope::Public => {
+                let permissions = std::iter::once(owner)
+                    .chain(writers)
+                    .map(|user| (user, PublicPermissions::new(true)))
+                    .collect();
+                Policy::Public(PublicPolicy { owner, permissions })
+            }
+            Scope::Private => {
+                if *access == WriteAccess::PublicAppend {
+                    return Err(Error::InvalidOperation);
+                }
+                let permissions = std::iter::once(owner)
+                    .chain(writers)
+                    .map(|user| (user, PrivatePermissions::new(true, true)))
+                    .collect();
+                Policy::Private(PrivatePolicy { owner, permissions })
+            }
+        };
+
+        Ok(policy)
+    }
+
+    /// Returns true if anyone can write to the Register, in which case each entry is to be
+    /// signed by its writer.
+    pub fn is_public_append(&self) -> bool {
+        match self {
+            Policy::Public(policy) => {
+                policy.is_action_allowed_by_user(&User::Anyone, Action::Write) == Some(true)
+            }
+            Policy::Private(_) => false,
+        }
+    }
+
     /// Returns true if `action` is allowed for the provided user.
     pub fn is_action_allowed(&self, requester: User, action: Action) -> Result<()> {
         match self {
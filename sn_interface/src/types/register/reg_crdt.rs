@@ -145,6 +145,14 @@ impl RegisterCrdt {
         self.data.node(hash.0).map(|node| &node.value)
     }
 
+    /// Get the hashes of the entries the one corresponding to the provided `hash` was written
+    /// on top of, if it exists.
+    pub(crate) fn children(&self, hash: EntryHash) -> Option<BTreeSet<EntryHash>> {
+        self.data
+            .node(hash.0)
+            .map(|node| node.children.iter().copied().map(EntryHash).collect())
+    }
+
     /// Read current entries (multiple entries occur on concurrent writes).
     pub(crate) fn read(&self) -> BTreeSet<(EntryHash, Entry)> {
         self.data
@@ -10,11 +10,20 @@ use super::errors::convert_bincode_error;
 // use sn_interface::network_knowledge::prefix_map::NetworkPrefixMap;
 use super::{Error, Result};
 use bytes::Bytes;
+use hmac::{Hmac, Mac, NewMac};
 use multibase::{self, Base};
 use rand::rngs::OsRng;
 use rand::Rng;
 use rayon::current_num_threads;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha3::Sha3_256;
+
+/// Number of PBKDF2 rounds stretching a passphrase into the key data is encrypted with.
+pub const PASSPHRASE_KDF_ROUNDS: u32 = 100_000;
+
+// Tag the MAC of data encrypted with a passphrase is made over along with the data, so the key
+// isn't used to authenticate anything else
+const PASSPHRASE_MAC_TAG: &[u8] = b"SN_PASSPHRASE_MAC";
 
 /// Wrapper for raw bincode::serialise.
 pub fn serialise<T: Serialize>(data: &T) -> Result<Vec<u8>> {
@@ -48,6 +57,57 @@ pub fn decode<I: AsRef<str>, O: DeserializeOwned>(encoded: I) -> Result<O> {
     deserialise(&decoded).map_err(|e| Error::FailedToParse(e.to_string()))
 }
 
+/// Derives the BLS secret key data is encrypted with from a passphrase and a salt, with
+/// PBKDF2-HMAC-SHA3-256.
+///
+/// The key is made from the derived bytes as they are, so the same passphrase and salt always
+/// give the same key, whichever the versions of our dependencies.
+pub fn passphrase_secret_key(passphrase: &str, salt: &[u8]) -> Result<bls::SecretKey> {
+    let mut key_bytes = [0; bls::SK_SIZE];
+    pbkdf2::pbkdf2::<Hmac<Sha3_256>>(
+        passphrase.as_bytes(),
+        salt,
+        PASSPHRASE_KDF_ROUNDS,
+        &mut key_bytes,
+    );
+    // clearing the two most significant bits keeps the big-endian scalar below the curve order
+    key_bytes[0] &= 0x3f;
+    bls::SecretKey::from_bytes(key_bytes).map_err(|err| {
+        Error::FailedToParse(format!(
+            "Couldn't make a secret key from the passphrase: {:?}",
+            err
+        ))
+    })
+}
+
+/// MAC of data encrypted with a key derived by [`passphrase_secret_key`], keyed with that same key.
+///
+/// Decrypting with the key derived from a wrong passphrase gives garbage rather than failing, so
+/// the MAC is to be stored along with the encrypted data, and checked with
+/// [`verify_passphrase_mac`] before decrypting it.
+pub fn passphrase_mac(secret_key: &bls::SecretKey, encrypted: &[u8]) -> Result<[u8; 32]> {
+    let mac = passphrase_hmac(secret_key, encrypted)?;
+    Ok(mac.finalize().into_bytes().into())
+}
+
+/// Whether the MAC made by [`passphrase_mac`] over the encrypted data was made with this key,
+/// i.e. whether the key was derived from the passphrase the data was encrypted with.
+pub fn verify_passphrase_mac(secret_key: &bls::SecretKey, encrypted: &[u8], mac: &[u8]) -> bool {
+    match passphrase_hmac(secret_key, encrypted) {
+        // compared in constant time
+        Ok(expected) => expected.verify(mac).is_ok(),
+        Err(_) => false,
+    }
+}
+
+fn passphrase_hmac(secret_key: &bls::SecretKey, encrypted: &[u8]) -> Result<Hmac<Sha3_256>> {
+    let mut mac = Hmac::<Sha3_256>::new_varkey(&secret_key.to_bytes())
+        .map_err(|err| Error::FailedToParse(format!("Couldn't make a MAC key: {:?}", err)))?;
+    mac.update(PASSPHRASE_MAC_TAG);
+    mac.update(encrypted);
+    Ok(mac)
+}
+
 /// Generates a random vector using provided `length`.
 pub fn random_bytes(length: usize) -> Bytes {
     use rayon::prelude::*;
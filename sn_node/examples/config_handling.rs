@@ -90,6 +90,14 @@ async fn main() -> Result<()> {
         assert_eq!(file_config.local_addr, config.local_addr);
     }
 
+    if !command_line_args.listen_addrs.is_empty() {
+        assert_eq!(command_line_args.listen_addrs, config.listen_addrs);
+    } else {
+        assert_eq!(file_config.listen_addrs, config.listen_addrs);
+    }
+
+    assert_eq!(command_line_args.ip_preference, config.ip_preference);
+
     if command_line_args.first {
         assert!(config.first);
     }
@@ -111,6 +119,40 @@ async fn main() -> Result<()> {
         config.network_config.forward_port,
         !command_line_args.skip_auto_port_forwarding
     );
+    assert_eq!(
+        config.require_chunk_payments,
+        file_config.require_chunk_payments || command_line_args.require_chunk_payments
+    );
+
+    if command_line_args.admin_addr.is_some() {
+        assert_eq!(command_line_args.admin_addr, config.admin_addr);
+    } else {
+        assert_eq!(file_config.admin_addr, config.admin_addr);
+    }
+
+    if command_line_args.notify_url.is_some() {
+        assert_eq!(command_line_args.notify_url, config.notify_url);
+    } else {
+        assert_eq!(file_config.notify_url, config.notify_url);
+    }
+
+    if command_line_args.notify_socket.is_some() {
+        assert_eq!(command_line_args.notify_socket, config.notify_socket);
+    } else {
+        assert_eq!(file_config.notify_socket, config.notify_socket);
+    }
+
+    if command_line_args.identity_passphrase_file.is_some() {
+        assert_eq!(
+            command_line_args.identity_passphrase_file,
+            config.identity_passphrase_file
+        );
+    } else {
+        assert_eq!(
+            file_config.identity_passphrase_file,
+            config.identity_passphrase_file
+        );
+    }
 
     if !command_line_args.hard_coded_contacts.is_empty() {
         assert_eq!(
@@ -175,6 +217,29 @@ async fn main() -> Result<()> {
         )
     }
 
+    assert_eq!(
+        command_line_args.bootstrap_retry_initial_delay_sec,
+        config.bootstrap_retry_initial_delay_sec
+    );
+    assert_eq!(
+        command_line_args.bootstrap_retry_max_delay_sec,
+        config.bootstrap_retry_max_delay_sec
+    );
+    if command_line_args.bootstrap_retry_max_attempts.is_some() {
+        assert_eq!(
+            command_line_args.bootstrap_retry_max_attempts,
+            config.bootstrap_retry_max_attempts
+        )
+    } else {
+        assert_eq!(
+            file_config.bootstrap_retry_max_attempts,
+            config.bootstrap_retry_max_attempts
+        )
+    }
+
+    assert_eq!(command_line_args.peer_msg_rate, config.peer_msg_rate);
+    assert_eq!(command_line_args.peer_msg_burst, config.peer_msg_burst);
+
     clear_disk_config().await?;
 
     Ok(())
@@ -243,6 +243,34 @@ async fn handle_event(index: usize, node: &mut NodeApi, event: Event) -> bool {
         Event::ChurnJoinMissError => {
             info!("Node #{} detected churn join miss and will restart", index);
         }
+        Event::ReachabilityChecked(reachability) => {
+            info!("Node #{} reachability checked: {:?}", index, reachability);
+        }
+        Event::Joined { name, prefix, age } => {
+            info!(
+                "Node #{} joined - name: {}, prefix: {:?}, age: {}",
+                index, name, prefix, age
+            );
+        }
+        Event::PeerLost { name } => {
+            info!("Node #{} lost peer {}", index, name);
+        }
+        Event::Terminated => {
+            info!("Node #{} terminated", index);
+            return false;
+        }
+        Event::RecoveryChecked(report) => {
+            info!("Node #{} recovery checked: {:?}", index, report);
+        }
+        Event::Incident(incident) => {
+            info!("Node #{} task panicked: {:?}", index, incident);
+        }
+        Event::ConfigReloaded(config) => {
+            info!("Node #{} config reloaded: {:?}", index, config);
+        }
+        Event::ConfigRejected { reason } => {
+            info!("Node #{} config rejected: {}", index, reason);
+        }
         Event::SectionSplit {
             elders,
             self_status_change,
@@ -269,6 +297,10 @@ async fn handle_event(index: usize, node: &mut NodeApi, event: Event) -> bool {
             "Node #{} relocation started - previous_name: {}",
             index, previous_name
         ),
+        Event::RelocationTimedOut { previous_name, dst } => info!(
+            "Node #{} relocation to {} timed out - previous_name: {}",
+            index, dst, previous_name
+        ),
         Event::Relocated { previous_name, .. } => {
             let new_name = node.name().await;
             info!(
@@ -341,6 +341,11 @@ impl Network {
                         self.stats.relocation_attempts += 1;
                     }
                 }
+                RoutingEvent::RelocationTimedOut { .. } => {
+                    if let Some(Node::Joined { is_relocating, .. }) = self.nodes.get_mut(&id) {
+                        *is_relocating = false;
+                    }
+                }
                 RoutingEvent::Relocated { .. } => {
                     if let Some(Node::Joined {
                         node,
@@ -467,10 +472,7 @@ impl Network {
             correlation_id: MsgId::new(),
         };
 
-        let dst_location = DstLocation::Section {
-            name: dst,
-            section_pk: public_key_set.public_key(),
-        };
+        let dst_location = DstLocation::section(dst, public_key_set.public_key());
 
         let wire_msg = node.sign_single_src_msg(node_msg, dst_location).await?;
 
@@ -30,32 +30,50 @@
 use color_eyre::{Section, SectionExt};
 use eyre::{eyre, ErrReport, Result, WrapErr};
 use file_rotate::{compression::Compression, suffix::AppendCount, ContentLimit, FileRotate};
-use sn_node::node::{add_connection_info, set_connection_info, Config, Error, Event, NodeApi};
+use sn_node::node::{
+    add_connection_info, set_connection_info, AdminSocket, Config, Error, Event, LogStream,
+    NodeApi, Notifier, Reachability,
+};
 
 use self_update::{cargo_crate_version, Status};
 #[cfg(not(feature = "tokio-console"))]
 use sn_interface::LogFormatter;
-use std::{fmt::Debug, fs::File, io, path::Path};
+use std::{fmt::Debug, fs::File, io, path::Path, sync::Arc};
 use std::{io::Write, process::exit};
 use structopt::{clap, StructOpt};
 use tokio::sync::RwLockReadGuard;
 use tokio::time::{sleep, Duration};
+use tokio_util::sync::CancellationToken;
 use tracing::{self, error, info, trace, warn};
 
 use tracing_appender::non_blocking::WorkerGuard;
 #[cfg(not(feature = "tokio-console"))]
-use tracing_subscriber::filter::EnvFilter;
+use tracing_subscriber::{filter::EnvFilter, prelude::*, reload};
 
 #[cfg(not(feature = "tokio-console"))]
 const MODULE_NAME: &str = "sn_node";
-const BOOTSTRAP_RETRY_TIME_SEC: u64 = 30;
+const JOIN_TIMEOUT_SEC: u64 = 30;
+
+// How the node is started again once it went down
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Restart {
+    // joining the network anew, e.g. once no longer a member of any section
+    Rejoin,
+    // from the state it persisted, e.g. once a task of it was given up on
+    Resume,
+}
+
+// Applies the log level set in the config file of the node
+type LogLevelReloader = Box<dyn Fn(tracing::Level) -> Result<()>>;
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     #[cfg(feature = "tokio-console")]
     console_subscriber::init();
 
-    // loops ready to catch any ChurnJoinMiss
+    // loops ready to catch any ChurnJoinMiss, or relocation given up on, to rejoin the network,
+    // as well as any subsystem given up on, to resume from the persisted state
+    let mut restart = None;
     loop {
         // start runtime
         let handle = std::thread::Builder::new()
@@ -63,28 +81,50 @@ fn main() -> Result<()> {
             .stack_size(16 * 1024 * 1024)
             .spawn(move || {
                 let rt = tokio::runtime::Runtime::new()?;
-                match rt.block_on(run_node()) {
-                    Ok(_) => {}
+                // the node is shut down cleanly on Ctrl-C, rather than dropped with the runtime
+                let cancellation = CancellationToken::new();
+                let _ = rt.spawn(cancel_on_ctrl_c(cancellation.clone()));
+                let resume = restart == Some(Restart::Resume);
+                let next_restart = match rt.block_on(run_node(cancellation, resume)) {
+                    Ok(_) => None,
                     Err(error) => {
-                        error!("{error}")
+                        error!("{error}");
+                        match error.downcast_ref::<Error>() {
+                            Some(Error::ChurnJoinMiss | Error::RelocationTimeout(_)) => {
+                                Some(Restart::Rejoin)
+                            }
+                            Some(Error::SubsystemFailed { .. }) => Some(Restart::Resume),
+                            _ => None,
+                        }
                     }
                 };
                 rt.shutdown_timeout(Duration::from_secs(2));
 
                 // let rt = tokio::runtime::Runtime::new()?;
                 // rt.block_on(run_node())?;
-                Ok(())
+                Ok(next_restart)
             })
             .wrap_err("Failed to spawn node thread")?;
 
         // join it
         match handle.join() {
+            Ok(Ok(Some(Restart::Rejoin))) => {
+                warn!("Node is no longer a member of any section, restarting node...");
+                restart = Some(Restart::Rejoin);
+                continue;
+            }
+            Ok(Ok(Some(Restart::Resume))) => {
+                warn!("A task of the node was given up on, restarting node from its persisted state...");
+                restart = Some(Restart::Resume);
+                continue;
+            }
             Ok(result) => {
-                return result;
+                return result.map(|_| ());
             }
             Err(error) => {
                 if let Some(Error::ChurnJoinMiss) = error.downcast_ref::<Error>() {
                     warn!("Received churn join miss, restarting node...");
+                    restart = Some(Restart::Rejoin);
                     continue;
                 }
                 // thread panic errors cannot be converted to `eyre::Report` as they are not `Sync`, so
@@ -163,7 +203,8 @@ impl Debug for FileRotateAppender {
         f.debug_struct("FileRotateAppender").finish()
     }
 }
-async fn run_node() -> Result<()> {
+// Runs the node until it's cancelled or goes down, resuming it from the state it persisted if so
+async fn run_node(cancellation: CancellationToken, resume: bool) -> Result<()> {
     let config = Config::new().await?;
 
     if let Some(c) = &config.completions() {
@@ -179,6 +220,10 @@ async fn run_node() -> Result<()> {
     // ==============
 
     let mut _optional_guard: Option<WorkerGuard> = None;
+    #[allow(unused_mut)]
+    let mut reload_log_level: Option<LogLevelReloader> = None;
+    // Log events streamed to the operator tools connected to the admin socket
+    let log_stream = LogStream::default();
 
     #[cfg(not(feature = "tokio-console"))]
     {
@@ -187,11 +232,7 @@ async fn run_node() -> Result<()> {
             // If we have an error (ie RUST_LOG not set or otherwise), we check the verbosity flags
             Err(_) => {
                 // we manually determine level filter instead of using tracing EnvFilter.
-                let level_filter = config.verbose();
-                let module_filter = format!("{}={}", MODULE_NAME, level_filter)
-                    .parse()
-                    .wrap_err("BUG: invalid module filter constructed")?;
-                EnvFilter::from_default_env().add_directive(module_filter)
+                module_filter(config.verbose())?
             }
         };
 
@@ -237,22 +278,30 @@ async fn run_node() -> Result<()> {
                 .with_writer(non_blocking);
 
             if config.json_logs {
-                builder.json().init();
+                let builder = builder.json().with_filter_reloading();
+                reload_log_level = Some(log_level_reloader(builder.reload_handle()));
+                builder.finish().with(log_stream.clone()).init();
             } else {
-                builder.event_format(LogFormatter::default()).init();
+                let builder = builder
+                    .event_format(LogFormatter::default())
+                    .with_filter_reloading();
+                reload_log_level = Some(log_level_reloader(builder.reload_handle()));
+                builder.finish().with(log_stream.clone()).init();
             }
 
             Some(guard)
         } else {
             println!("Starting logging to stdout");
 
-            tracing_subscriber::fmt()
+            let builder = tracing_subscriber::fmt()
                 .with_thread_names(true)
                 .with_ansi(false)
                 .with_env_filter(filter)
                 .with_target(false)
                 .event_format(LogFormatter::default())
-                .init();
+                .with_filter_reloading();
+            reload_log_level = Some(log_level_reloader(builder.reload_handle()));
+            builder.finish().with(log_stream.clone()).init();
 
             None
         };
@@ -274,6 +323,17 @@ async fn run_node() -> Result<()> {
         }
     }
 
+    let admin_node_handle = if let Some(admin_addr) = config.admin_addr {
+        let admin_socket = AdminSocket::bind(admin_addr, log_stream)
+            .await
+            .wrap_err("Failed to start the admin socket")?;
+        let admin_node_handle = admin_socket.node_handle();
+        let _handle = tokio::spawn(admin_socket.serve());
+        Some(admin_node_handle)
+    } else {
+        None
+    };
+
     let message = format!(
         "Running {} v{}",
         Config::clap().get_name(),
@@ -281,11 +341,15 @@ async fn run_node() -> Result<()> {
     );
     info!("\n\n{}\n{}", message, "=".repeat(message.len()));
 
-    let log = format!("The network is not accepting nodes right now. Retrying after {BOOTSTRAP_RETRY_TIME_SEC} seconds");
-
-    let bootstrap_retry_duration = Duration::from_secs(BOOTSTRAP_RETRY_TIME_SEC);
+    let join_timeout = Duration::from_secs(JOIN_TIMEOUT_SEC);
+    let mut bootstrap_retries = config.bootstrap_retries();
     let (node, mut event_stream) = loop {
-        match NodeApi::new(&config, bootstrap_retry_duration).await {
+        let started = if resume {
+            NodeApi::resume(&config, join_timeout).await
+        } else {
+            NodeApi::new(&config, join_timeout).await
+        };
+        let failure = match started {
             Ok(result) => break result,
             Err(Error::CannotConnectEndpoint(qp2p::EndpointError::Upnp(error))) => {
                 return Err(error).suggestion(
@@ -300,27 +364,36 @@ async fn run_node() -> Result<()> {
                         .header("Disable port forwarding or change your router settings"),
                 );
             }
-            Err(Error::TryJoinLater) => {
-                println!("{}", log);
-                info!("{}", log);
+            Err(Error::TryJoinLater) => "The network is not accepting nodes right now.".to_string(),
+            Err(Error::BootstrapFailed) => {
+                "None of the contacts could be reached to bootstrap to the network.".to_string()
             }
+            // Unreachable nodes aren't run as non-listening clients of their section: the Elders
+            // only make members of the nodes they can dial, so such a node would never earn
+            // membership. They try again instead, for their operator to get them reachable
+            // meanwhile, their reachability being reported on the admin socket.
             Err(Error::NodeNotReachable(addr)) => {
-                let err_msg = format!(
+                if let Some(admin_node_handle) = &admin_node_handle {
+                    admin_node_handle
+                        .set_reachability(Reachability::Unreachable(addr))
+                        .await;
+                }
+                format!(
                     "Unfortunately we are unable to establish a connection to your machine ({}) either through a \
-                    public IP address, or via IGD on your router. Please ensure that IGD is enabled on your router - \
-                    if it is and you are still unable to add your node to the testnet, then skip adding a node for this \
-                    testnet iteration. You can still use the testnet as a client, uploading and downloading content, etc. \
-                    https://safenetforum.org/",
+                    public IP address, or via IGD on your router. Please ensure that IGD is enabled on your router, \
+                    or that the address given with --public-addr is forwarded to this machine. You can still use \
+                    the network as a client, uploading and downloading content, etc. https://safenetforum.org/",
                     addr
-                );
-                println!("{}", err_msg);
-                error!("{}", err_msg);
-                exit(1);
+                )
+            }
+            Err(Error::JoinRejected(reason)) => {
+                format!("The network refused to let this node join: {}.", reason)
+            }
+            Err(Error::DenylistedByElders(elders)) => {
+                format!("{} Elders of the section denylisted this node.", elders)
             }
             Err(Error::JoinTimeout) => {
-                let message = format!("Encountered a timeout while trying to join the network. Retrying after {BOOTSTRAP_RETRY_TIME_SEC} seconds.");
-                println!("{}", &message);
-                error!("{}", &message);
+                "Encountered a timeout while trying to join the network.".to_string()
             }
             Err(e) => {
                 let log_path = if let Some(path) = config.log_dir() {
@@ -334,15 +407,41 @@ async fn run_node() -> Result<()> {
                     address to be used using --first", log_path)
                 );
             }
+        };
+
+        match bootstrap_retries.next_delay() {
+            Some(delay) => {
+                let message = format!("{} Retrying after {} seconds.", failure, delay.as_secs());
+                println!("{}", message);
+                warn!("{}", message);
+                if cancelled_during(delay, &cancellation).await {
+                    return Ok(());
+                }
+            }
+            None => {
+                return Err(eyre!(
+                    "{} Giving up joining the network after {} attempts.",
+                    failure,
+                    bootstrap_retries.attempts()
+                ))
+                .suggestion(
+                    "The number of attempts can be raised with --bootstrap-retry-max-attempts, \
+                    or left unlimited by not supplying it.",
+                );
+            }
         }
-        sleep(bootstrap_retry_duration).await;
     };
 
+    let node = Arc::new(node);
+    if let Some(admin_node_handle) = admin_node_handle {
+        admin_node_handle.set(node.clone());
+    }
+
     let our_conn_info = node.our_connection_info().await;
 
     if config.is_first() {
         let genesis_key = node.genesis_key().await;
-        set_connection_info(genesis_key, our_conn_info)
+        set_connection_info(genesis_key, our_conn_info, config.network_name.clone())
             .await
             .unwrap_or_else(|err| {
                 error!("Unable to write our connection info to disk: {:?}", err);
@@ -355,17 +454,98 @@ async fn run_node() -> Result<()> {
             });
     }
 
+    // Membership decisions and Elder changes are delivered to the endpoint set, if any
+    let notifier = config
+        .notifier_target()
+        .map_err(|error| eyre!(error))?
+        .map(Notifier::start);
+
     // This just keeps the node going as long as routing goes
-    while let Some(event) = event_stream.next().await {
+    while let Some(event) = event_stream.next_until_cancelled(&cancellation).await {
         trace!("Routing event! {:?}", event);
-        if let Event::ChurnJoinMissError = event {
-            return Err(Error::ChurnJoinMiss).map_err(ErrReport::msg);
+        if let Some(notifier) = &notifier {
+            notifier.notify(&event);
+        }
+        match event {
+            Event::ChurnJoinMissError => {
+                shut_down(&node).await;
+                return Err(Error::ChurnJoinMiss).map_err(ErrReport::msg);
+            }
+            Event::RelocationTimedOut { dst, .. } => {
+                shut_down(&node).await;
+                return Err(Error::RelocationTimeout(dst)).map_err(ErrReport::msg);
+            }
+            Event::Incident(incident) if !incident.restarted => {
+                shut_down(&node).await;
+                return Err(Error::SubsystemFailed {
+                    subsystem: incident.subsystem,
+                    task: incident.task,
+                })
+                .map_err(ErrReport::msg);
+            }
+            Event::ConfigReloaded(live_config) => {
+                if let (Some(level), Some(reload)) = (live_config.log_level(), &reload_log_level) {
+                    match reload(level) {
+                        Ok(()) => info!("Log level set to {} from config file", level),
+                        Err(error) => error!("Failed to set log level: {:?}", error),
+                    }
+                }
+            }
+            Event::ConfigRejected { reason } => {
+                println!("Config file change not applied: {}", reason);
+            }
+            _ => {}
         }
     }
 
+    shut_down(&node).await;
     Ok(())
 }
 
+// Cancels the given token on Ctrl-C, for the node to be shut down
+async fn cancel_on_ctrl_c(cancellation: CancellationToken) {
+    match tokio::signal::ctrl_c().await {
+        Ok(()) => {
+            info!("Received Ctrl-C, shutting the node down");
+            cancellation.cancel();
+        }
+        Err(error) => error!("Failed to listen for Ctrl-C: {:?}", error),
+    }
+}
+
+// Sleeps for the given delay, unless the given token is cancelled meanwhile, returning whether it was
+async fn cancelled_during(delay: Duration, cancellation: &CancellationToken) -> bool {
+    tokio::select! {
+        _ = sleep(delay) => false,
+        _ = cancellation.cancelled() => true,
+    }
+}
+
+// Waits for the node to be torn down, before it's restarted or the process exits
+async fn shut_down(node: &NodeApi) {
+    if let Err(error) = node.shutdown().await {
+        error!("Failed to shut the node down cleanly: {:?}", error);
+    }
+}
+
+// Filter of the logs of the node at the given level
+#[cfg(not(feature = "tokio-console"))]
+fn module_filter(level: tracing::Level) -> Result<EnvFilter> {
+    let module_filter = format!("{}={}", MODULE_NAME, level)
+        .parse()
+        .wrap_err("BUG: invalid module filter constructed")?;
+    Ok(EnvFilter::from_default_env().add_directive(module_filter))
+}
+
+#[cfg(not(feature = "tokio-console"))]
+fn log_level_reloader<S: 'static>(handle: reload::Handle<EnvFilter, S>) -> LogLevelReloader {
+    Box::new(move |level| {
+        handle
+            .reload(module_filter(level)?)
+            .wrap_err("Failed to reload log filter")
+    })
+}
+
 fn update() -> Result<Status, Box<dyn (::std::error::Error)>> {
     info!("Checking for updates...");
     let target = self_update::get_target();
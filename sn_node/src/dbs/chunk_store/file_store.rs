@@ -6,41 +6,57 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{Error, Result};
+use super::{ChunkStore, Error, Result};
 
 use crate::UsedSpace;
 use sn_interface::types::{Chunk, ChunkAddress};
 
+use async_trait::async_trait;
 use bytes::Bytes;
-use std::path::{Path, PathBuf};
-use tokio::io::AsyncWriteExt;
+use std::{
+    collections::BTreeSet,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::{io::AsyncWriteExt, sync::Mutex};
 use walkdir::WalkDir;
 use xor_name::{Prefix, XorName};
 
 const BIT_TREE_DEPTH: usize = 20;
 const CHUNK_DB_DIR: &str = "chunkdb";
+const CHUNK_QUARANTINE_DIR: &str = "chunkdb_quarantine";
+// Number of files written since the last flush after which they're flushed right away, for the
+// ones waiting for it not to pile up when flushes are far apart
+const MAX_UNFLUSHED_FILES: usize = 1024;
 
-/// A disk store for chunks
+/// A disk store for chunks, holding each of them in its own file
 #[derive(Clone, Debug)]
-pub(crate) struct ChunkStore {
+pub(crate) struct FileChunkStore {
     bit_tree_depth: usize,
     chunk_store_path: PathBuf,
+    quarantine_path: PathBuf,
     used_space: UsedSpace,
+    // files written since the last flush
+    unflushed: Arc<Mutex<BTreeSet<PathBuf>>>,
 }
 
-impl ChunkStore {
-    /// Creates a new `ChunkStore` at location `root/CHUNK_DB_DIR`
+impl FileChunkStore {
+    /// Creates a new `FileChunkStore` at location `root/CHUNK_DB_DIR`
     ///
-    /// If the location specified already contains a ChunkStore, it is simply used
+    /// If the location specified already contains a FileChunkStore, it is simply used
     ///
     /// Used space of the dir is tracked
     pub(crate) fn new<P: AsRef<Path>>(root: P, used_space: UsedSpace) -> Result<Self> {
         let chunk_store_path = root.as_ref().join(CHUNK_DB_DIR);
+        let quarantine_path = root.as_ref().join(CHUNK_QUARANTINE_DIR);
 
-        Ok(ChunkStore {
+        Ok(FileChunkStore {
             bit_tree_depth: BIT_TREE_DEPTH,
             chunk_store_path,
+            quarantine_path,
             used_space,
+            unflushed: Arc::new(Mutex::new(BTreeSet::new())),
         })
     }
 
@@ -83,80 +99,140 @@ impl ChunkStore {
         Ok(ChunkAddress::decode_from_zbase32(filename)?)
     }
 
-    // ---------------------- api methods ----------------------
+    fn list_all_files(&self) -> Result<Vec<String>> {
+        list_files_in(&self.chunk_store_path)
+    }
+
+    #[allow(unused)]
+    /// quickly find chunks related or not to a section, might be useful when adults change sections
+    /// not used yet
+    pub(crate) fn list_files_without_prefix(&self, prefix: Prefix) -> Result<Vec<String>> {
+        let all_files = self.list_all_files()?;
+        let prefix_path = self.prefix_tree_path(prefix.name(), prefix.bit_count());
+        let outside_prefix = all_files
+            .into_iter()
+            .filter(|p| !Path::new(&p).starts_with(&prefix_path.as_path()))
+            .collect();
+        Ok(outside_prefix)
+    }
 
-    pub(crate) fn can_add(&self, size: usize) -> bool {
+    /// quickly find chunks related or not to a section, might be useful when adults change sections
+    fn list_files_with_prefix(&self, prefix: Prefix) -> Result<Vec<String>> {
+        let prefix_path = self.prefix_tree_path(prefix.name(), prefix.bit_count());
+        list_files_in(prefix_path.as_path())
+    }
+}
+
+#[async_trait]
+impl ChunkStore for FileChunkStore {
+    fn can_add(&self, size: usize) -> bool {
         self.used_space.can_add(size)
     }
 
-    pub(crate) async fn write_chunk(&self, data: &Chunk) -> Result<ChunkAddress> {
+    async fn put(&self, data: &Chunk) -> Result<ChunkAddress> {
         let addr = data.address();
         let filepath = self.address_to_filepath(addr)?;
         if let Some(dirs) = filepath.parent() {
             tokio::fs::create_dir_all(dirs).await?;
         }
 
-        let mut file = tokio::fs::File::create(filepath).await?;
-        file.write_all(data.value()).await?;
-
-        self.used_space.increase(data.value().len());
+        let bytes = data.stored_bytes();
+        let mut file = tokio::fs::File::create(&filepath).await?;
+        file.write_all(&bytes).await?;
+
+        self.used_space.increase(bytes.len());
+        let unflushed = {
+            let mut unflushed = self.unflushed.lock().await;
+            let _ = unflushed.insert(filepath);
+            unflushed.len()
+        };
+        if unflushed >= MAX_UNFLUSHED_FILES {
+            self.flush().await?;
+        }
 
         Ok(*addr)
     }
 
-    #[allow(dead_code)]
-    pub(crate) async fn delete_chunk(&self, addr: &ChunkAddress) -> Result<()> {
+    async fn get(&self, addr: &ChunkAddress) -> Result<Chunk> {
+        let file_path = self.address_to_filepath(addr)?;
+        let bytes = match tokio::fs::read(file_path).await {
+            Ok(bytes) => Bytes::from(bytes),
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                return Err(Error::ChunkNotFound(*addr.name()))
+            }
+            Err(error) => return Err(error.into()),
+        };
+        let chunk = Chunk::from_stored(addr, bytes);
+        Ok(chunk)
+    }
+
+    async fn delete(&self, addr: &ChunkAddress) -> Result<()> {
         let filepath = self.address_to_filepath(addr)?;
-        let meta = tokio::fs::metadata(filepath.clone()).await?;
+        let meta = match tokio::fs::metadata(filepath.clone()).await {
+            Ok(meta) => meta,
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                return Err(Error::ChunkNotFound(*addr.name()))
+            }
+            Err(error) => return Err(error.into()),
+        };
         tokio::fs::remove_file(filepath).await?;
         self.used_space.decrease(meta.len() as usize);
         Ok(())
     }
 
-    pub(crate) async fn read_chunk(&self, addr: &ChunkAddress) -> Result<Chunk> {
-        let file_path = self.address_to_filepath(addr)?;
-        let bytes = Bytes::from(tokio::fs::read(file_path).await?);
-        let chunk = Chunk::new(bytes);
-        Ok(chunk)
+    async fn quarantine(&self, addr: &ChunkAddress) -> Result<()> {
+        let filepath = self.address_to_filepath(addr)?;
+        let meta = match tokio::fs::metadata(filepath.clone()).await {
+            Ok(meta) => meta,
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                return Err(Error::ChunkNotFound(*addr.name()))
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        tokio::fs::create_dir_all(&self.quarantine_path).await?;
+        let quarantined_path = self.quarantine_path.join(addr.encode_to_zbase32()?);
+        tokio::fs::rename(filepath, quarantined_path).await?;
+        self.used_space.decrease(meta.len() as usize);
+        Ok(())
     }
 
-    pub(crate) fn chunk_file_exists(&self, addr: &ChunkAddress) -> Result<bool> {
+    async fn contains(&self, addr: &ChunkAddress) -> Result<bool> {
         let filepath = self.address_to_filepath(addr)?;
         Ok(filepath.exists())
     }
 
-    pub(crate) fn list_all_files(&self) -> Result<Vec<String>> {
-        list_files_in(&self.chunk_store_path)
-    }
-
-    pub(crate) fn list_all_chunk_addresses(&self) -> Result<Vec<ChunkAddress>> {
-        let all_files = self.list_all_files()?;
-        let all_addrs = all_files
+    fn list_range(&self, prefix: Prefix) -> Result<Vec<ChunkAddress>> {
+        // the prefix tree is only `bit_tree_depth` deep, so deeper prefixes need an extra filtering
+        self.list_files_with_prefix(prefix)?
             .iter()
             .map(|filepath| self.filepath_to_address(filepath))
-            .collect();
-        all_addrs
+            .filter(|addr| match addr {
+                Ok(addr) => prefix.matches(addr.name()),
+                Err(_) => true,
+            })
+            .collect()
     }
 
-    #[allow(unused)]
-    /// quickly find chunks related or not to a section, might be useful when adults change sections
-    /// not used yet
-    pub(crate) fn list_files_without_prefix(&self, prefix: Prefix) -> Result<Vec<String>> {
-        let all_files = self.list_all_files()?;
-        let prefix_path = self.prefix_tree_path(prefix.name(), prefix.bit_count());
-        let outside_prefix = all_files
-            .into_iter()
-            .filter(|p| !Path::new(&p).starts_with(&prefix_path.as_path()))
-            .collect();
-        Ok(outside_prefix)
+    async fn used_space(&self) -> Result<u64> {
+        let mut used = 0;
+        for filepath in self.list_all_files()? {
+            used += tokio::fs::metadata(filepath).await?.len();
+        }
+        Ok(used)
     }
 
-    #[allow(unused)]
-    /// quickly find chunks related or not to a section, might be useful when adults change sections
-    /// not used yet
-    pub(crate) fn list_files_with_prefix(&self, prefix: Prefix) -> Result<Vec<String>> {
-        let prefix_path = self.prefix_tree_path(prefix.name(), prefix.bit_count());
-        list_files_in(prefix_path.as_path())
+    async fn flush(&self) -> Result<()> {
+        let unflushed = std::mem::take(&mut *self.unflushed.lock().await);
+        for filepath in unflushed {
+            match tokio::fs::File::open(&filepath).await {
+                Ok(file) => file.sync_all().await?,
+                // removed since it was written, there's nothing to flush
+                Err(error) if error.kind() == ErrorKind::NotFound => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+        Ok(())
     }
 }
 
@@ -185,9 +261,9 @@ mod tests {
     use rayon::prelude::*;
     use tempfile::tempdir;
 
-    fn init_chunk_disk_store() -> ChunkStore {
+    fn init_chunk_disk_store() -> FileChunkStore {
         let root = tempdir().expect("Failed to create temporary directory for chunk disk store");
-        ChunkStore::new(root.path(), UsedSpace::new(usize::MAX))
+        FileChunkStore::new(root.path(), UsedSpace::new(usize::MAX))
             .expect("Failed to create chunk disk store")
     }
 
@@ -199,15 +275,9 @@ mod tests {
         for _ in 0..10 {
             let chunk = Chunk::new(random_bytes(100));
 
-            let addr = store
-                .write_chunk(&chunk)
-                .await
-                .expect("Failed to write chunk.");
+            let addr = store.put(&chunk).await.expect("Failed to write chunk.");
 
-            let read_chunk = store
-                .read_chunk(&addr)
-                .await
-                .expect("Failed to read chunk.");
+            let read_chunk = store.get(&addr).await.expect("Failed to read chunk.");
 
             assert_eq!(chunk.value(), read_chunk.value());
         }
@@ -232,13 +302,30 @@ mod tests {
         write_and_read_chunks(&chunks, store).await;
     }
 
-    async fn write_and_read_chunks(chunks: &[Chunk], store: ChunkStore) {
+    #[tokio::test(flavor = "multi_thread")]
+    async fn unflushed_files_are_bounded() -> Result<()> {
+        let root = tempdir()?;
+        let store = FileChunkStore::new(root.path(), UsedSpace::new(usize::MAX))?;
+
+        for _ in 0..MAX_UNFLUSHED_FILES - 1 {
+            let _ = store.put(&Chunk::new(random_bytes(10))).await?;
+        }
+        assert_eq!(store.unflushed.lock().await.len(), MAX_UNFLUSHED_FILES - 1);
+
+        // flushed once there are too many of them
+        let _ = store.put(&Chunk::new(random_bytes(10))).await?;
+        assert!(store.unflushed.lock().await.is_empty());
+
+        Ok(())
+    }
+
+    async fn write_and_read_chunks(chunks: &[Chunk], store: FileChunkStore) {
         // write all chunks
-        let tasks = chunks.iter().map(|c| store.write_chunk(c));
+        let tasks = chunks.iter().map(|c| store.put(c));
         let results = join_all(tasks).await;
 
         // read all chunks
-        let tasks = results.iter().flatten().map(|addr| store.read_chunk(addr));
+        let tasks = results.iter().flatten().map(|addr| store.get(addr));
         let results = join_all(tasks).await;
         let read_chunks: Vec<&Chunk> = results.iter().flatten().collect();
 
@@ -0,0 +1,337 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+mod file_store;
+#[cfg(feature = "sled-chunk-store")]
+mod sled_store;
+mod write_ahead;
+
+pub(crate) use file_store::FileChunkStore;
+#[cfg(feature = "sled-chunk-store")]
+pub(crate) use sled_store::SledChunkStore;
+pub(crate) use write_ahead::WriteAheadChunkStore;
+
+use super::Result;
+
+use crate::UsedSpace;
+use sn_interface::types::{Chunk, ChunkAddress};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Debug, Display, Formatter},
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+};
+use xor_name::Prefix;
+
+/// A store for chunks, abstracting the way they are laid out on disk.
+#[async_trait]
+pub(crate) trait ChunkStore: Debug + Send + Sync {
+    /// Whether a chunk of the given size would fit within the allocated space
+    fn can_add(&self, size: usize) -> bool;
+
+    /// Store a chunk, overwriting it if it was already stored
+    async fn put(&self, chunk: &Chunk) -> Result<ChunkAddress>;
+
+    /// Read a chunk, failing with `Error::ChunkNotFound` if it's not stored
+    async fn get(&self, addr: &ChunkAddress) -> Result<Chunk>;
+
+    /// Remove a chunk, failing with `Error::ChunkNotFound` if it's not stored
+    async fn delete(&self, addr: &ChunkAddress) -> Result<()>;
+
+    /// Move a chunk out of the store, keeping its content aside for later inspection.
+    /// Fails with `Error::ChunkNotFound` if it's not stored.
+    async fn quarantine(&self, addr: &ChunkAddress) -> Result<()>;
+
+    /// Whether a chunk is stored
+    async fn contains(&self, addr: &ChunkAddress) -> Result<bool>;
+
+    /// List the addresses of all the stored chunks matching the given prefix.
+    /// The whole store is listed with `Prefix::default()`.
+    fn list_range(&self, prefix: Prefix) -> Result<Vec<ChunkAddress>>;
+
+    /// Number of bytes the stored chunks currently take up
+    async fn used_space(&self) -> Result<u64>;
+
+    /// Make sure all the writes made so far have reached the disk
+    async fn flush(&self) -> Result<()>;
+
+    /// Bring the store back to a consistent state after the node went down,
+    /// returning the number of chunk writes which had to be recovered
+    async fn recover(&self) -> Result<usize> {
+        Ok(0)
+    }
+}
+
+/// Backend a node stores its chunks with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ChunkStoreBackend {
+    /// One file per chunk, laid out in a tree of directories following the chunk name bits
+    File,
+    /// A sled db, only available when built with the `sled-chunk-store` feature
+    Sled,
+}
+
+impl Default for ChunkStoreBackend {
+    fn default() -> Self {
+        Self::File
+    }
+}
+
+impl Display for ChunkStoreBackend {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::File => write!(formatter, "file"),
+            Self::Sled => write!(formatter, "sled"),
+        }
+    }
+}
+
+impl FromStr for ChunkStoreBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "file" => Ok(Self::File),
+            "sled" => Ok(Self::Sled),
+            other => Err(format!(
+                "Unknown chunk store backend '{}', expected 'file' or 'sled'",
+                other
+            )),
+        }
+    }
+}
+
+/// How durable chunk writes are by the time they're acknowledged.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ChunkDurability {
+    /// Each write is flushed to the disk on its own
+    Sync,
+    /// Writes made while the previous batch is being committed are logged together,
+    /// and flushed to the disk at once
+    Batched,
+    /// Writes are not flushed, it's left to the OS to write them to the disk
+    Relaxed,
+}
+
+impl Default for ChunkDurability {
+    fn default() -> Self {
+        Self::Batched
+    }
+}
+
+impl Display for ChunkDurability {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Sync => write!(formatter, "sync"),
+            Self::Batched => write!(formatter, "batched"),
+            Self::Relaxed => write!(formatter, "relaxed"),
+        }
+    }
+}
+
+impl FromStr for ChunkDurability {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "sync" => Ok(Self::Sync),
+            "batched" => Ok(Self::Batched),
+            "relaxed" => Ok(Self::Relaxed),
+            other => Err(format!(
+                "Unknown chunk durability mode '{}', expected 'sync', 'batched' or 'relaxed'",
+                other
+            )),
+        }
+    }
+}
+
+/// How a node stores its chunks.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ChunkStoreConfig {
+    /// Backend the chunks are stored with
+    pub backend: ChunkStoreBackend,
+    /// How durable chunk writes are by the time they're acknowledged
+    pub durability: ChunkDurability,
+}
+
+/// Open the chunk store of the given backend, located within `root`.
+///
+/// If the location already contains a store for that backend, it is simply used.
+pub(crate) fn open_chunk_store(
+    root: &Path,
+    config: ChunkStoreConfig,
+    used_space: UsedSpace,
+) -> Result<Arc<dyn ChunkStore>> {
+    let store: Arc<dyn ChunkStore> = match config.backend {
+        ChunkStoreBackend::File => Arc::new(FileChunkStore::new(root, used_space)?),
+        #[cfg(feature = "sled-chunk-store")]
+        ChunkStoreBackend::Sled => Arc::new(SledChunkStore::new(root, used_space)?),
+        #[cfg(not(feature = "sled-chunk-store"))]
+        ChunkStoreBackend::Sled => {
+            return Err(super::Error::UnsupportedChunkStoreBackend(config.backend))
+        }
+    };
+
+    Ok(Arc::new(WriteAheadChunkStore::new(
+        root,
+        store,
+        config.durability,
+    )))
+}
+
+/// Move all the chunks held by the `from` backend into the `to` backend, both located within `root`.
+///
+/// Chunks are removed from the source store only once they've all been written to the destination
+/// one, so an interrupted migration can simply be run again. Returns the number of chunks migrated.
+pub(crate) async fn migrate_chunks(
+    root: &Path,
+    from: ChunkStoreBackend,
+    to: ChunkStoreBackend,
+) -> Result<usize> {
+    if from == to {
+        return Ok(0);
+    }
+
+    // the space is already taken on disk, there's no point in limiting it here,
+    // and everything is flushed once all chunks are copied over
+    let open = |backend| {
+        let config = ChunkStoreConfig {
+            backend,
+            durability: ChunkDurability::Relaxed,
+        };
+        open_chunk_store(root, config, UsedSpace::new(usize::MAX))
+    };
+    let source = open(from)?;
+    let destination = open(to)?;
+
+    // writes the node didn't get to complete with the source backend are recovered first
+    let _recovered = source.recover().await?;
+
+    let addresses = source.list_range(Prefix::default())?;
+    info!(
+        "Migrating {} chunks from the {} chunk store to the {} one",
+        addresses.len(),
+        from,
+        to
+    );
+
+    for addr in &addresses {
+        let chunk = source.get(addr).await?;
+        let _ = destination.put(&chunk).await?;
+    }
+    destination.flush().await?;
+
+    let mut migrated = 0;
+    for addr in &addresses {
+        source.delete(addr).await?;
+        migrated += 1;
+    }
+
+    info!("{} chunks migrated to the {} chunk store", migrated, to);
+
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sn_interface::types::utils::random_bytes;
+    use tempfile::tempdir;
+
+    #[test]
+    fn backend_from_str() {
+        assert_eq!("file".parse(), Ok(ChunkStoreBackend::File));
+        assert_eq!("sled".parse(), Ok(ChunkStoreBackend::Sled));
+        assert!("rocksdb".parse::<ChunkStoreBackend>().is_err());
+    }
+
+    #[test]
+    fn durability_from_str() {
+        assert_eq!("sync".parse(), Ok(ChunkDurability::Sync));
+        assert_eq!("batched".parse(), Ok(ChunkDurability::Batched));
+        assert_eq!("relaxed".parse(), Ok(ChunkDurability::Relaxed));
+        assert!("fsync".parse::<ChunkDurability>().is_err());
+    }
+
+    #[tokio::test]
+    async fn list_range_matches_prefix() -> Result<()> {
+        let root = tempdir()?;
+        let store = open_chunk_store(
+            root.path(),
+            ChunkStoreConfig::default(),
+            UsedSpace::new(usize::MAX),
+        )?;
+
+        let mut addresses = vec![];
+        for _ in 0..10 {
+            addresses.push(store.put(&Chunk::new(random_bytes(100))).await?);
+        }
+
+        let mut all = store.list_range(Prefix::default())?;
+        all.sort();
+        addresses.sort();
+        assert_eq!(all, addresses);
+
+        let prefix = Prefix::default().pushed(true);
+        let matching = store.list_range(prefix)?;
+        assert!(matching.iter().all(|addr| prefix.matches(addr.name())));
+        assert_eq!(
+            matching.len(),
+            addresses
+                .iter()
+                .filter(|addr| prefix.matches(addr.name()))
+                .count()
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "sled-chunk-store")]
+    #[tokio::test]
+    async fn migrate_between_backends() -> Result<()> {
+        let root = tempdir()?;
+        let file_store = open_chunk_store(
+            root.path(),
+            ChunkStoreConfig::default(),
+            UsedSpace::new(usize::MAX),
+        )?;
+
+        let chunks: Vec<Chunk> = std::iter::repeat_with(|| Chunk::new(random_bytes(100)))
+            .take(5)
+            .collect();
+        for chunk in &chunks {
+            let _ = file_store.put(chunk).await?;
+        }
+
+        let migrated = migrate_chunks(
+            root.path(),
+            ChunkStoreBackend::File,
+            ChunkStoreBackend::Sled,
+        )
+        .await?;
+        assert_eq!(migrated, chunks.len());
+        assert!(file_store.list_range(Prefix::default())?.is_empty());
+
+        let sled_store = open_chunk_store(
+            root.path(),
+            ChunkStoreConfig {
+                backend: ChunkStoreBackend::Sled,
+                ..ChunkStoreConfig::default()
+            },
+            UsedSpace::new(usize::MAX),
+        )?;
+        for chunk in &chunks {
+            assert_eq!(&sled_store.get(chunk.address()).await?, chunk);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{ChunkStore, Error, Result};
+
+use crate::{dbs::SLED_FLUSH_TIME_MS, UsedSpace};
+use sn_interface::types::{Chunk, ChunkAddress};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use sled::{Db, Tree};
+use std::path::Path;
+use xor_name::{Prefix, XorName, XOR_NAME_LEN};
+
+const CHUNK_SLED_DB_DIR: &str = "chunkdb_sled";
+const CHUNK_TREE_NAME: &str = "chunks";
+const QUARANTINE_TREE_NAME: &str = "quarantine";
+
+/// A sled db store for chunks, keyed by the chunk names
+#[derive(Clone, Debug)]
+pub(crate) struct SledChunkStore {
+    db: Db,
+    chunks: Tree,
+    quarantine: Tree,
+    used_space: UsedSpace,
+}
+
+impl SledChunkStore {
+    /// Creates a new `SledChunkStore` at location `root/CHUNK_SLED_DB_DIR`
+    ///
+    /// If the location specified already contains a SledChunkStore, it is simply used
+    pub(crate) fn new<P: AsRef<Path>>(root: P, used_space: UsedSpace) -> Result<Self> {
+        let db = sled::Config::default()
+            .path(root.as_ref().join(CHUNK_SLED_DB_DIR))
+            .flush_every_ms(SLED_FLUSH_TIME_MS)
+            .open()?;
+        let chunks = db.open_tree(CHUNK_TREE_NAME)?;
+        let quarantine = db.open_tree(QUARANTINE_TREE_NAME)?;
+
+        Ok(Self {
+            db,
+            chunks,
+            quarantine,
+            used_space,
+        })
+    }
+}
+
+#[async_trait]
+impl ChunkStore for SledChunkStore {
+    fn can_add(&self, size: usize) -> bool {
+        self.used_space.can_add(size)
+    }
+
+    async fn put(&self, data: &Chunk) -> Result<ChunkAddress> {
+        let addr = data.address();
+        let bytes = data.stored_bytes();
+        let previous = self.chunks.insert(addr.name(), bytes.as_ref())?;
+        if let Some(previous) = previous {
+            self.used_space.decrease(previous.len());
+        }
+        self.used_space.increase(bytes.len());
+
+        Ok(*addr)
+    }
+
+    async fn get(&self, addr: &ChunkAddress) -> Result<Chunk> {
+        match self.chunks.get(addr.name())? {
+            Some(value) => Ok(Chunk::from_stored(addr, Bytes::copy_from_slice(&value))),
+            None => Err(Error::ChunkNotFound(*addr.name())),
+        }
+    }
+
+    async fn delete(&self, addr: &ChunkAddress) -> Result<()> {
+        match self.chunks.remove(addr.name())? {
+            Some(value) => {
+                self.used_space.decrease(value.len());
+                Ok(())
+            }
+            None => Err(Error::ChunkNotFound(*addr.name())),
+        }
+    }
+
+    async fn quarantine(&self, addr: &ChunkAddress) -> Result<()> {
+        match self.chunks.remove(addr.name())? {
+            Some(value) => {
+                let len = value.len();
+                let _previous = self.quarantine.insert(addr.name(), value)?;
+                self.used_space.decrease(len);
+                Ok(())
+            }
+            None => Err(Error::ChunkNotFound(*addr.name())),
+        }
+    }
+
+    async fn contains(&self, addr: &ChunkAddress) -> Result<bool> {
+        Ok(self.chunks.contains_key(addr.name())?)
+    }
+
+    fn list_range(&self, prefix: Prefix) -> Result<Vec<ChunkAddress>> {
+        // keys are sorted, so all the names matching the prefix lie within its bounds
+        let lower = prefix.lower_bound();
+        let upper = prefix.upper_bound();
+
+        self.chunks
+            .range(lower.0..=upper.0)
+            .keys()
+            .map(|key| {
+                let key = key?;
+                // we expect xornames as keys
+                if key.len() != XOR_NAME_LEN {
+                    return Err(Error::CouldNotParseDbKey(key.to_vec()));
+                }
+                let mut name = XorName::default();
+                name.0.copy_from_slice(&key);
+                Ok(ChunkAddress(name))
+            })
+            .collect()
+    }
+
+    async fn used_space(&self) -> Result<u64> {
+        Ok(self.db.size_on_disk()?)
+    }
+
+    async fn flush(&self) -> Result<()> {
+        let _flushed_bytes = self.db.flush_async().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sn_interface::types::utils::random_bytes;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_put_get_delete_chunk() -> Result<()> {
+        let root = tempdir()?;
+        let store = SledChunkStore::new(root.path(), UsedSpace::new(usize::MAX))?;
+
+        let chunk = Chunk::new(random_bytes(100));
+        let addr = store.put(&chunk).await?;
+        assert!(store.contains(&addr).await?);
+        assert_eq!(store.get(&addr).await?, chunk);
+        assert_eq!(store.list_range(Prefix::default())?, vec![addr]);
+
+        store.delete(&addr).await?;
+        assert!(!store.contains(&addr).await?);
+        assert!(matches!(
+            store.get(&addr).await,
+            Err(Error::ChunkNotFound(name)) if name == *addr.name()
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,354 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{ChunkDurability, ChunkStore, Error, Result};
+
+use sn_interface::types::{Chunk, ChunkAddress};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::{
+    fs::{File, OpenOptions},
+    io::AsyncWriteExt,
+    sync::{oneshot, Mutex},
+};
+use xor_name::{Prefix, XorName, XOR_NAME_LEN};
+
+const WAL_FILE_NAME: &str = "chunkdb_wal";
+// Size the log can grow to before the chunks it holds are flushed in the store and it's emptied
+const WAL_CHECKPOINT_SIZE: u64 = 64 * 1024 * 1024;
+// Each record is the chunk name, followed by the length of its content, and the content itself
+const RECORD_HEADER_LEN: usize = XOR_NAME_LEN + std::mem::size_of::<u32>();
+
+type WriteResult = std::result::Result<ChunkAddress, String>;
+type PendingWrite = (Chunk, oneshot::Sender<WriteResult>);
+
+/// A chunk store making the writes to an inner store durable before acknowledging them.
+///
+/// In `ChunkDurability::Batched` mode, writes made while a batch is being committed are grouped
+/// into the next one. The writes of a batch are appended together to a write-ahead log, which
+/// is fsync'd once for the whole batch. They are then written to the inner store without waiting
+/// for them to reach the disk, since the log can be replayed with `ChunkStore::recover` should
+/// the node crash. Chunks deleted since the log was last emptied may be brought back by a replay.
+///
+/// In `ChunkDurability::Sync` mode, each write is flushed to the disk on its own.
+#[derive(Clone, Debug)]
+pub(crate) struct WriteAheadChunkStore {
+    inner: Arc<dyn ChunkStore>,
+    durability: ChunkDurability,
+    wal_path: PathBuf,
+    // only written to by the task committing a batch, while holding the lock
+    wal: Arc<Mutex<Option<File>>>,
+    pending: Arc<Mutex<Vec<PendingWrite>>>,
+}
+
+impl WriteAheadChunkStore {
+    /// Wraps the given store, keeping the write-ahead log at location `root/WAL_FILE_NAME`
+    pub(crate) fn new(
+        root: &Path,
+        inner: Arc<dyn ChunkStore>,
+        durability: ChunkDurability,
+    ) -> Self {
+        Self {
+            inner,
+            durability,
+            wal_path: root.join(WAL_FILE_NAME),
+            wal: Arc::new(Mutex::new(None)),
+            pending: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    // Adds the chunk to the current batch, and waits for the batch to be committed.
+    // The first write of a batch spawns the task committing it, which only starts
+    // once the previous batch was committed, so more writes can join it meanwhile.
+    async fn put_batched(&self, chunk: &Chunk) -> Result<ChunkAddress> {
+        let (sender, receiver) = oneshot::channel();
+        let first_of_batch = {
+            let mut pending = self.pending.lock().await;
+            pending.push((chunk.clone(), sender));
+            pending.len() == 1
+        };
+
+        if first_of_batch {
+            let store = self.clone();
+            let _handle = tokio::spawn(async move { store.commit_next_batch().await });
+        }
+
+        match receiver.await {
+            Ok(Ok(addr)) => Ok(addr),
+            Ok(Err(error)) => Err(Error::WriteAheadLog(error)),
+            Err(_) => Err(Error::WriteAheadLog(
+                "Chunk write was dropped before being committed".to_string(),
+            )),
+        }
+    }
+
+    async fn commit_next_batch(&self) {
+        let mut wal = self.wal.lock().await;
+        let batch = std::mem::take(&mut *self.pending.lock().await);
+        if batch.is_empty() {
+            return;
+        }
+        trace!("Committing a batch of {} chunk writes", batch.len());
+
+        let logged = self
+            .append_to_wal(&mut wal, &batch)
+            .await
+            .map_err(|error| format!("Failed to log chunk writes: {:?}", error));
+
+        for (chunk, sender) in batch {
+            let result = match &logged {
+                Ok(()) => self
+                    .inner
+                    .put(&chunk)
+                    .await
+                    .map_err(|error| error.to_string()),
+                Err(error) => Err(error.clone()),
+            };
+            // the writer may have gone away, there's nobody else to tell
+            let _ = sender.send(result);
+        }
+
+        if let Err(error) = self.checkpoint_if_needed(&mut wal).await {
+            warn!(
+                "Failed to checkpoint the chunk write-ahead log: {:?}",
+                error
+            );
+        }
+    }
+
+    async fn append_to_wal(&self, wal: &mut Option<File>, batch: &[PendingWrite]) -> Result<()> {
+        let mut records = Vec::new();
+        for (chunk, _) in batch {
+            let bytes = chunk.stored_bytes();
+            let len = u32::try_from(bytes.len()).map_err(|_| {
+                Error::WriteAheadLog(format!("Chunk too big to be logged: {:?}", chunk.name()))
+            })?;
+            records.extend_from_slice(&chunk.name().0);
+            records.extend_from_slice(&len.to_be_bytes());
+            records.extend_from_slice(&bytes);
+        }
+
+        if wal.is_none() {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.wal_path)
+                .await?;
+            *wal = Some(file);
+        }
+
+        if let Some(file) = wal {
+            file.write_all(&records).await?;
+            file.sync_data().await?;
+        }
+
+        Ok(())
+    }
+
+    // Once the log grows too big, we make sure all it holds has reached the disk in the
+    // inner store, after which it can be emptied
+    async fn checkpoint_if_needed(&self, wal: &mut Option<File>) -> Result<()> {
+        let file = match wal {
+            Some(file) => file,
+            None => return Ok(()),
+        };
+
+        if file.metadata().await?.len() < WAL_CHECKPOINT_SIZE {
+            return Ok(());
+        }
+
+        debug!("Checkpointing the chunk write-ahead log");
+        self.inner.flush().await?;
+        file.set_len(0).await?;
+        file.sync_data().await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ChunkStore for WriteAheadChunkStore {
+    fn can_add(&self, size: usize) -> bool {
+        self.inner.can_add(size)
+    }
+
+    async fn put(&self, chunk: &Chunk) -> Result<ChunkAddress> {
+        match self.durability {
+            ChunkDurability::Batched => self.put_batched(chunk).await,
+            ChunkDurability::Sync => {
+                let addr = self.inner.put(chunk).await?;
+                self.inner.flush().await?;
+                Ok(addr)
+            }
+            ChunkDurability::Relaxed => self.inner.put(chunk).await,
+        }
+    }
+
+    async fn get(&self, addr: &ChunkAddress) -> Result<Chunk> {
+        self.inner.get(addr).await
+    }
+
+    async fn delete(&self, addr: &ChunkAddress) -> Result<()> {
+        self.inner.delete(addr).await
+    }
+
+    async fn quarantine(&self, addr: &ChunkAddress) -> Result<()> {
+        self.inner.quarantine(addr).await
+    }
+
+    async fn contains(&self, addr: &ChunkAddress) -> Result<bool> {
+        self.inner.contains(addr).await
+    }
+
+    fn list_range(&self, prefix: Prefix) -> Result<Vec<ChunkAddress>> {
+        self.inner.list_range(prefix)
+    }
+
+    async fn used_space(&self) -> Result<u64> {
+        self.inner.used_space().await
+    }
+
+    async fn flush(&self) -> Result<()> {
+        self.inner.flush().await
+    }
+
+    async fn recover(&self) -> Result<usize> {
+        let mut wal = self.wal.lock().await;
+
+        let bytes = match tokio::fs::read(&self.wal_path).await {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(0),
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut replayed = 0;
+        let mut remaining = bytes.as_slice();
+        while let Some((chunk, rest)) = next_record(remaining) {
+            let _ = self.inner.put(&chunk).await?;
+            replayed += 1;
+            remaining = rest;
+        }
+
+        if !remaining.is_empty() {
+            // the node went down while this batch was being logged, so it was never acknowledged
+            warn!(
+                "Discarding {} bytes of incomplete records at the end of the chunk write-ahead log",
+                remaining.len()
+            );
+        }
+
+        self.inner.flush().await?;
+        *wal = None;
+        tokio::fs::remove_file(&self.wal_path).await?;
+
+        info!(
+            "{} chunk writes replayed from the write-ahead log",
+            replayed
+        );
+
+        Ok(replayed)
+    }
+}
+
+// Parses the record at the start of the given bytes, returning the chunk it holds along with the
+// bytes that follow it, or `None` if the record is incomplete or its content doesn't match its name
+fn next_record(bytes: &[u8]) -> Option<(Chunk, &[u8])> {
+    if bytes.len() < RECORD_HEADER_LEN {
+        return None;
+    }
+
+    let mut name = XorName::default();
+    name.0.copy_from_slice(&bytes[..XOR_NAME_LEN]);
+    let mut len = [0; std::mem::size_of::<u32>()];
+    len.copy_from_slice(&bytes[XOR_NAME_LEN..RECORD_HEADER_LEN]);
+    let len = u32::from_be_bytes(len) as usize;
+
+    let rest = &bytes[RECORD_HEADER_LEN..];
+    if rest.len() < len {
+        return None;
+    }
+
+    let address = ChunkAddress(name);
+    let chunk = Chunk::from_stored(&address, Bytes::copy_from_slice(&rest[..len]));
+    if *chunk.address() != address {
+        return None;
+    }
+
+    Some((chunk, &rest[len..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dbs::chunk_store::FileChunkStore, UsedSpace};
+    use futures::future::join_all;
+    use sn_interface::types::utils::random_bytes;
+    use tempfile::tempdir;
+
+    fn init_store(root: &Path, durability: ChunkDurability) -> Result<WriteAheadChunkStore> {
+        let inner = FileChunkStore::new(root, UsedSpace::new(usize::MAX))?;
+        Ok(WriteAheadChunkStore::new(root, Arc::new(inner), durability))
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn batched_writes_are_all_stored() -> Result<()> {
+        let root = tempdir()?;
+        let store = init_store(root.path(), ChunkDurability::Batched)?;
+
+        let chunks: Vec<Chunk> = std::iter::repeat_with(|| Chunk::new(random_bytes(100)))
+            .take(20)
+            .collect();
+        let results = join_all(chunks.iter().map(|chunk| store.put(chunk))).await;
+        assert!(results.iter().all(|result| result.is_ok()));
+
+        for chunk in &chunks {
+            assert_eq!(&store.get(chunk.address()).await?, chunk);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn logged_writes_are_replayed() -> Result<()> {
+        let root = tempdir()?;
+        let chunks: Vec<Chunk> = std::iter::repeat_with(|| Chunk::new(random_bytes(100)))
+            .take(3)
+            .collect();
+
+        // simulate a crash after the batch was logged, but before it reached the store,
+        // with a last record only partially written
+        let mut log = Vec::new();
+        for chunk in &chunks {
+            log.extend_from_slice(&chunk.name().0);
+            log.extend_from_slice(&(chunk.value().len() as u32).to_be_bytes());
+            log.extend_from_slice(chunk.value());
+        }
+        let torn = Chunk::new(random_bytes(100));
+        log.extend_from_slice(&torn.name().0);
+        log.extend_from_slice(&(torn.value().len() as u32).to_be_bytes());
+        log.extend_from_slice(&torn.value()[..50]);
+        std::fs::write(root.path().join(WAL_FILE_NAME), log)?;
+
+        let store = init_store(root.path(), ChunkDurability::Batched)?;
+        assert_eq!(store.recover().await?, chunks.len());
+
+        for chunk in &chunks {
+            assert_eq!(&store.get(chunk.address()).await?, chunk);
+        }
+        assert!(!store.contains(torn.address()).await?);
+        assert!(!root.path().join(WAL_FILE_NAME).exists());
+
+        Ok(())
+    }
+}
@@ -6,6 +6,8 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use super::ChunkStoreBackend;
+
 use sn_interface::messaging::data::Error as ErrorMsg;
 use sn_interface::types::{
     convert_dt_error_to_error_msg, DataAddress, PublicKey, ReplicatedDataAddress,
@@ -100,6 +102,15 @@ pub enum Error {
     /// No filename found
     #[error("Path contains no file name")]
     NoFilename,
+    /// The chunk store backend was not enabled at build time.
+    #[error("Chunk store backend not supported by this build: {0}")]
+    UnsupportedChunkStoreBackend(ChunkStoreBackend),
+    /// Chunk writes couldn't be made durable through the write-ahead log.
+    #[error("Chunk write-ahead log error: {0}")]
+    WriteAheadLog(String),
+    /// A batch of register edits was rejected
+    #[error("Invalid batch of register edits: {0}")]
+    InvalidRegisterBatch(ErrorMsg),
 }
 
 /// Convert db error to messaging error message for sending over the network.
@@ -112,6 +123,7 @@ pub(crate) fn convert_to_error_msg(error: Error) -> ErrorMsg {
         Error::TempDirCreationFailed(_) => ErrorMsg::FailedToWriteFile,
         Error::DataExists => ErrorMsg::DataExists,
         Error::NetworkData(error) => convert_dt_error_to_error_msg(error),
+        Error::InvalidRegisterBatch(error) => error,
         other => ErrorMsg::InvalidOperation(format!("Failed to perform operation: {:?}", other)),
     }
 }
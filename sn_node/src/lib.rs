@@ -43,7 +43,7 @@ extern crate tracing;
 
 mod dbs;
 
-pub use dbs::UsedSpace;
+pub use dbs::{ChunkDurability, ChunkStoreBackend, ChunkStoreConfig, UsedSpace};
 
 pub mod node;
 
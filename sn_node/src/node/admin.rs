@@ -0,0 +1,439 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::{Error, LogFilter, LogStream, NodeApi, Reachability, Result};
+
+use serde::{Deserialize, Serialize};
+use std::{fmt, net::SocketAddr, sync::Arc};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::{OnceCell, RwLock, Semaphore},
+};
+
+// Max number of operator tools connected at once, further connections being turned away
+const MAX_ADMIN_CONNECTIONS: usize = 16;
+// Max size in bytes of a request, which is a single line of JSON
+const MAX_REQUEST_SIZE: u64 = 64 * 1024;
+// Error replied to the requests needing the node to have joined the network, until it did
+const NOT_JOINED_ERROR: &str = "the node hasn't joined the network yet";
+
+// A request of an operator tool, e.g. `{"method": "logs subscribe", "params": {"level": "debug"}}`
+#[derive(Debug, Deserialize)]
+#[serde(tag = "method", content = "params")]
+enum AdminRequest {
+    // Streams the log events matching the filter, until the tool disconnects
+    #[serde(rename = "logs subscribe")]
+    LogsSubscribe(LogFilter),
+    // Replies with an attestation of the identity of the node, signed with its key
+    #[serde(rename = "node attestation")]
+    NodeAttestation,
+    // Replies with a snapshot of what the node knows of the network
+    #[serde(rename = "network snapshot")]
+    NetworkSnapshot,
+    // Replies with the peers the node has sessions with
+    #[serde(rename = "routing table")]
+    RoutingTable,
+    // Replies with the status of the node, e.g. for monitoring tools to poll
+    #[serde(rename = "status")]
+    Status,
+    // Replies with the endpoints blacklisted for violating the protocol
+    #[serde(rename = "blacklist")]
+    Blacklist,
+    // Clears the blacklist, replying with whether any endpoint was on it
+    #[serde(rename = "blacklist clear")]
+    BlacklistClear,
+}
+
+#[derive(Debug, Serialize)]
+struct AdminError {
+    error: String,
+}
+
+// Status of a node which hasn't joined the network yet
+#[derive(Debug, Serialize)]
+struct JoiningStatus {
+    error: String,
+    // outcome of the connectivity self-test of the last attempt to join, if it got that far
+    reachability: Option<Reachability>,
+}
+
+/// Handle the node is given to the [`AdminSocket`] through, once it joined the network.
+#[derive(Clone, Default)]
+pub struct AdminNodeHandle {
+    node: Arc<OnceCell<Arc<NodeApi>>>,
+    reachability: Arc<RwLock<Option<Reachability>>>,
+}
+
+impl fmt::Debug for AdminNodeHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdminNodeHandle")
+            .field("joined", &self.node.initialized())
+            .finish()
+    }
+}
+
+impl AdminNodeHandle {
+    /// Gives the node which joined the network to the admin socket. Only the first node given
+    /// is kept.
+    pub fn set(&self, node: Arc<NodeApi>) {
+        if self.node.set(node).is_err() {
+            warn!("The admin socket was already given a node");
+        }
+    }
+
+    /// Records the outcome of the connectivity self-test of an attempt to join the network,
+    /// reported by the `status` of the node until it joined, e.g. for the operator to find out
+    /// their node can't be dialled back.
+    pub async fn set_reachability(&self, reachability: Reachability) {
+        *self.reachability.write().await = Some(reachability);
+    }
+
+    fn get(&self) -> Option<&Arc<NodeApi>> {
+        self.node.get()
+    }
+}
+
+/// Local socket operator tools connect to, to inspect the running node.
+///
+/// Each connection carries one request, as a line of JSON, replied to with lines of JSON. The
+/// methods are:
+/// - `logs subscribe`, streaming the log events of the node matching the filter given as its
+///   params, a [`LogFilter`], as [`LogStreamItem`]s.
+/// - `node attestation`, replying with a [`NodeAttestation`] of the node, signed with its key,
+///   which operators can publish to prove they run it. It fails until the node joined the
+///   network, and was given through the [`AdminNodeHandle`].
+/// - `network snapshot`, replying with a [`NetworkSnapshot`] of the sections the node knows,
+///   and of its connections, to attach to bug reports. It fails until the node joined the
+///   network as well.
+/// - `routing table`, replying with the [`RoutingTable`] of the node, listing the peers it has
+///   sessions with, to debug its connectivity. It fails until the node joined the network too.
+/// - `status`, replying with the [`NodeStatus`] of the node, e.g. its connected peer count and
+///   uptime, for monitoring tools to poll. It fails until the node joined the network too, then
+///   reporting the outcome of the connectivity self-test of its last attempt to join.
+/// - `blacklist`, replying with the endpoints blacklisted for violating the protocol, mapped to
+///   their number of violations, and `blacklist clear`, clearing them all. Both fail until the
+///   node joined the network too.
+///
+/// [`LogStreamItem`]: crate::node::LogStreamItem
+/// [`NodeAttestation`]: sn_interface::types::NodeAttestation
+/// [`NetworkSnapshot`]: sn_interface::network_knowledge::NetworkSnapshot
+/// [`RoutingTable`]: crate::node::RoutingTable
+/// [`NodeStatus`]: crate::node::NodeStatus
+#[derive(Debug)]
+pub struct AdminSocket {
+    listener: TcpListener,
+    log_stream: LogStream,
+    node: AdminNodeHandle,
+    connections: Arc<Semaphore>,
+}
+
+impl AdminSocket {
+    /// Listens on the given address, which has to be a loopback one, as operator tools aren't
+    /// authenticated.
+    pub async fn bind(addr: SocketAddr, log_stream: LogStream) -> Result<Self> {
+        if !addr.ip().is_loopback() {
+            return Err(Error::Configuration(format!(
+                "The admin socket can only listen on a loopback address, not {}",
+                addr
+            )));
+        }
+
+        let listener = TcpListener::bind(addr).await?;
+        info!("Admin socket listening on {:?}", listener.local_addr()?);
+
+        Ok(Self {
+            listener,
+            log_stream,
+            node: AdminNodeHandle::default(),
+            connections: Arc::new(Semaphore::new(MAX_ADMIN_CONNECTIONS)),
+        })
+    }
+
+    /// Handle to give the node to the socket through, once it joined the network.
+    pub fn node_handle(&self) -> AdminNodeHandle {
+        self.node.clone()
+    }
+
+    /// Address the socket listens on.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serves the operator tools connecting, until accepting connections fails.
+    pub async fn serve(self) {
+        loop {
+            let (mut stream, peer_addr) = match self.listener.accept().await {
+                Ok(connection) => connection,
+                Err(error) => {
+                    error!("Admin socket stopped accepting connections: {:?}", error);
+                    return;
+                }
+            };
+
+            let permit = match self.connections.clone().try_acquire_owned() {
+                Ok(permit) => permit,
+                Err(_) => {
+                    warn!(
+                        "Turning away admin connection from {}, as {} are open already",
+                        peer_addr, MAX_ADMIN_CONNECTIONS
+                    );
+                    let _ = write_line(
+                        &mut stream,
+                        &AdminError {
+                            error: "too many admin connections".to_string(),
+                        },
+                    )
+                    .await;
+                    continue;
+                }
+            };
+
+            let log_stream = self.log_stream.clone();
+            let node = self.node.clone();
+            let _handle = tokio::spawn(async move {
+                debug!("Admin connection from {}", peer_addr);
+                if let Err(error) = handle_connection(stream, log_stream, node).await {
+                    debug!("Admin connection from {} closed: {:?}", peer_addr, error);
+                }
+                drop(permit);
+            });
+        }
+    }
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    log_stream: LogStream,
+    node: AdminNodeHandle,
+) -> Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    let mut request = String::new();
+    let _ = (&mut reader)
+        .take(MAX_REQUEST_SIZE)
+        .read_line(&mut request)
+        .await?;
+    let request = match serde_json::from_str(&request) {
+        Ok(request) => request,
+        Err(error) => {
+            let error = format!("invalid request: {}", error);
+            return write_line(&mut writer, &AdminError { error }).await;
+        }
+    };
+    let filter = match request {
+        AdminRequest::LogsSubscribe(filter) => filter,
+        AdminRequest::NodeAttestation => {
+            let attestation = match node.get() {
+                Some(node) => node.identity_attestation().await,
+                None => None,
+            };
+            return match attestation {
+                Some(attestation) => write_line(&mut writer, &attestation).await,
+                None => write_not_joined(&mut writer).await,
+            };
+        }
+        AdminRequest::NetworkSnapshot => {
+            return match node.get() {
+                Some(node) => write_line(&mut writer, &node.network_snapshot().await).await,
+                None => write_not_joined(&mut writer).await,
+            };
+        }
+        AdminRequest::RoutingTable => {
+            return match node.get() {
+                Some(node) => write_line(&mut writer, &node.routing_table().await).await,
+                None => write_not_joined(&mut writer).await,
+            };
+        }
+        AdminRequest::Status => {
+            return match node.get() {
+                Some(node) => write_line(&mut writer, &node.status().await).await,
+                None => {
+                    let status = JoiningStatus {
+                        error: NOT_JOINED_ERROR.to_string(),
+                        reachability: *node.reachability.read().await,
+                    };
+                    write_line(&mut writer, &status).await
+                }
+            };
+        }
+        AdminRequest::Blacklist => {
+            return match node.get() {
+                Some(node) => write_line(&mut writer, &node.blacklisted_peers().await).await,
+                None => write_not_joined(&mut writer).await,
+            };
+        }
+        AdminRequest::BlacklistClear => {
+            return match node.get() {
+                Some(node) => write_line(&mut writer, &node.clear_blacklist(None).await).await,
+                None => write_not_joined(&mut writer).await,
+            };
+        }
+    };
+    let mut subscription = match log_stream.subscribe(filter) {
+        Ok(subscription) => subscription,
+        Err(error) => return write_line(&mut writer, &AdminError { error }).await,
+    };
+
+    let mut byte = [0; 1];
+    loop {
+        tokio::select! {
+            item = subscription.next() => match item {
+                Some(item) => write_line(&mut writer, &item).await?,
+                None => return Ok(()),
+            },
+            // the tool isn't expected to send anything else, so any more input, or it
+            // disconnecting, ends the subscription
+            _ = reader.read(&mut byte) => return Ok(()),
+        }
+    }
+}
+
+async fn write_not_joined(writer: &mut (impl AsyncWrite + Unpin)) -> Result<()> {
+    let error = NOT_JOINED_ERROR.to_string();
+    write_line(writer, &AdminError { error }).await
+}
+
+async fn write_line(writer: &mut (impl AsyncWrite + Unpin), value: &impl Serialize) -> Result<()> {
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+    writer.write_all(&line).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AdminSocket;
+    use crate::node::{LogStream, LogStreamItem, Reachability};
+
+    use sn_interface::types::log_markers::LogMarker;
+
+    use eyre::{eyre, Result};
+    use std::{
+        net::{Ipv4Addr, SocketAddr},
+        time::Duration,
+    };
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpStream,
+        time::timeout,
+    };
+    use tracing::Dispatch;
+    use tracing_subscriber::prelude::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn log_events_are_streamed_to_subscribers() -> Result<()> {
+        let log_stream = LogStream::default();
+        let dispatch = Dispatch::new(tracing_subscriber::registry().with(log_stream.clone()));
+
+        assert!(AdminSocket::bind("0.0.0.0:0".parse()?, log_stream.clone())
+            .await
+            .is_err());
+        let socket =
+            AdminSocket::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)), log_stream).await?;
+        let addr = socket.local_addr()?;
+        let _handle = tokio::spawn(socket.serve());
+
+        let mut stream = TcpStream::connect(addr).await?;
+        stream
+            .write_all(b"{\"method\": \"logs subscribe\", \"params\": {\"markers\": [\"SendJoinRequest\"]}}\n")
+            .await?;
+        let mut lines = BufReader::new(stream).lines();
+
+        // the subscription only starts once the request was read, so events are logged until
+        // the subscriber gets one
+        let line = loop {
+            tracing::dispatcher::with_default(&dispatch, || {
+                tracing::info!("unrelated event");
+                tracing::info!(peer = "peer", "{:?} from peer", LogMarker::SendJoinRequest);
+            });
+            match timeout(Duration::from_millis(100), lines.next_line()).await {
+                Ok(line) => break line?.ok_or_else(|| eyre!("admin connection closed"))?,
+                Err(_) => continue,
+            }
+        };
+
+        match serde_json::from_str(&line)? {
+            LogStreamItem::Event(event) => {
+                assert_eq!(event.marker.as_deref(), Some("SendJoinRequest"));
+                assert_eq!(event.level, "INFO");
+                assert_eq!(event.fields.get("peer").map(String::as_str), Some("peer"));
+            }
+            other => return Err(eyre!("unexpected item: {:?}", other)),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn node_attestation_is_refused_until_the_node_joined() -> Result<()> {
+        let socket = AdminSocket::bind(
+            SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+            LogStream::default(),
+        )
+        .await?;
+        let addr = socket.local_addr()?;
+        let _handle = tokio::spawn(socket.serve());
+
+        let mut stream = TcpStream::connect(addr).await?;
+        stream
+            .write_all(b"{\"method\": \"node attestation\"}\n")
+            .await?;
+        let line = BufReader::new(stream)
+            .lines()
+            .next_line()
+            .await?
+            .ok_or_else(|| eyre!("admin connection closed"))?;
+
+        let reply: serde_json::Value = serde_json::from_str(&line)?;
+        assert_eq!(
+            reply["error"].as_str(),
+            Some("the node hasn't joined the network yet")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reachability_is_reported_until_the_node_joined() -> Result<()> {
+        let socket = AdminSocket::bind(
+            SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+            LogStream::default(),
+        )
+        .await?;
+        let addr = socket.local_addr()?;
+        let node_handle = socket.node_handle();
+        let _handle = tokio::spawn(socket.serve());
+
+        let status = || async move {
+            let mut stream = TcpStream::connect(addr).await?;
+            stream.write_all(b"{\"method\": \"status\"}\n").await?;
+            let line = BufReader::new(stream)
+                .lines()
+                .next_line()
+                .await?
+                .ok_or_else(|| eyre!("admin connection closed"))?;
+            Ok::<_, eyre::Report>(serde_json::from_str::<serde_json::Value>(&line)?)
+        };
+
+        let reply = status().await?;
+        assert_eq!(
+            reply["error"].as_str(),
+            Some("the node hasn't joined the network yet")
+        );
+        assert!(reply["reachability"].is_null());
+
+        let unreachable = Reachability::Unreachable(SocketAddr::from((Ipv4Addr::LOCALHOST, 1)));
+        node_handle.set_reachability(unreachable).await;
+        let reply = status().await?;
+        assert_eq!(reply["reachability"], serde_json::to_value(unreachable)?);
+
+        Ok(())
+    }
+}
@@ -17,6 +17,7 @@ use sn_interface::{
     types::ReplicatedDataAddress,
 };
 
+use bls::PublicKey as BlsPublicKey;
 use bytes::Bytes;
 use custom_debug::Debug;
 use sn_consensus::Generation;
@@ -68,6 +69,12 @@ pub(crate) enum Cmd {
         recipients: Vec<Peer>,
         wire_msg: WireMsg,
     },
+    /// Send a message to the given `recipients`, and only then process the `then` cmds.
+    SendMsgThen {
+        recipients: Vec<Peer>,
+        wire_msg: WireMsg,
+        then: Vec<Cmd>,
+    },
     /// Send the batch of data messages in a throttled/controlled fashion to the given `recipients`.
     /// chunks addresses are provided, so that we only retrieve the data right before we send it,
     /// hopefully reducing memory impact or data replication
@@ -80,6 +87,14 @@ pub(crate) enum Cmd {
     /// Performs serialisation and signing for sending of NodeMsg.
     /// This cmd only send this to other nodes
     SignOutgoingSystemMsg { msg: SystemMsg, dst: DstLocation },
+    /// Performs serialisation and signing of a NodeMsg from our node, with the given section
+    /// key, then sends it to the given `recipients`.
+    SignAndSendMsg {
+        msg: SystemMsg,
+        dst: DstLocation,
+        src_section_pk: BlsPublicKey,
+        recipients: Vec<Peer>,
+    },
     /// Send a message to `delivery_group_size` peers out of the given `recipients`.
     SendMsgDeliveryGroup {
         recipients: Vec<Peer>,
@@ -129,7 +144,16 @@ impl fmt::Display for Cmd {
                     wire_msg.payload_debug
                 )
             }
+            Cmd::SendMsgThen { wire_msg, then, .. } => {
+                write!(
+                    f,
+                    "SendMsgThen {:?}, then {} cmds",
+                    wire_msg.msg_id(),
+                    then.len()
+                )
+            }
             Cmd::SignOutgoingSystemMsg { .. } => write!(f, "SignOutgoingSystemMsg"),
+            Cmd::SignAndSendMsg { .. } => write!(f, "SignAndSendMsg"),
             Cmd::EnqueueDataForReplication { .. } => write!(f, "ThrottledSendBatchMsgs"),
             Cmd::SendMsgDeliveryGroup { wire_msg, .. } => {
                 write!(f, "SendMsgDeliveryGroup {:?}", wire_msg.msg_id())
@@ -7,102 +7,258 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::Cmd;
-use crate::node::{messages::WireMsgUtils, Result};
+use crate::node::{
+    core::{answer_lan_peers, bind_lan_beacon},
+    LivenessConfig, Result,
+};
+use sn_dysfunction::PolicyAction;
+use sn_interface::messaging::system::{NodeCmd, SystemMsg};
 #[cfg(feature = "back-pressure")]
 use sn_interface::messaging::DstLocation;
-use sn_interface::messaging::{
-    system::{NodeCmd, SystemMsg},
-    WireMsg,
-};
 use sn_interface::types::log_markers::LogMarker;
-use std::{collections::BTreeSet, sync::Arc, time::Duration};
-use tokio::task::JoinHandle;
+use std::{
+    collections::BTreeSet,
+    sync::{atomic::Ordering, Arc},
+    time::Duration,
+};
 use tokio::time::MissedTickBehavior;
 
-use super::Dispatcher;
+use super::{supervision::Subsystem, Dispatcher};
 
 const PROBE_INTERVAL: Duration = Duration::from_secs(30);
 #[cfg(feature = "back-pressure")]
 const BACKPRESSURE_INTERVAL: Duration = Duration::from_secs(60);
 const SECTION_PROBE_INTERVAL: Duration = Duration::from_secs(300);
 const LINK_CLEANUP_INTERVAL: Duration = Duration::from_secs(120);
+const PEER_EXCHANGE_INTERVAL: Duration = Duration::from_secs(300);
 const DATA_BATCH_INTERVAL: Duration = Duration::from_secs(1);
 const DYSFUNCTION_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+const CHUNK_SCRUB_INTERVAL: Duration = Duration::from_secs(10);
+// Kept small so scrubbing doesn't compete with serving data
+const CHUNK_SCRUB_BATCH_SIZE: usize = 10;
 
 impl Dispatcher {
     pub(crate) async fn start_network_probing(self: Arc<Self>) {
         info!("Starting to probe network");
-        let _handle = tokio::spawn(async move {
-            let dispatcher = self.clone();
-            let mut interval = tokio::time::interval(PROBE_INTERVAL);
-            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
-
-            loop {
-                let _instant = interval.tick().await;
-
-                // Send a probe message if we are an elder
-                let node = &dispatcher.node;
-                if node.is_elder().await && !node.network_knowledge().prefix().await.is_empty() {
-                    match node.generate_probe_msg().await {
-                        Ok(cmd) => {
-                            info!("Sending probe msg");
-                            if let Err(e) = dispatcher
-                                .clone()
-                                .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
-                                .await
-                            {
-                                error!("Error sending a probe msg to the network: {:?}", e);
+        self.supervise(
+            Subsystem::Comm,
+            "network probing",
+            |dispatcher| async move {
+                let mut interval = tokio::time::interval(PROBE_INTERVAL);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+                loop {
+                    let _instant = interval.tick().await;
+
+                    // Send a probe message if we are an elder
+                    let node = &dispatcher.node;
+                    if node.is_elder().await && !node.network_knowledge().prefix().await.is_empty()
+                    {
+                        match node.generate_probe_msg().await {
+                            Ok(cmd) => {
+                                info!("Sending probe msg");
+                                if let Err(e) = dispatcher
+                                    .clone()
+                                    .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
+                                    .await
+                                {
+                                    error!("Error sending a probe msg to the network: {:?}", e);
+                                }
                             }
+                            Err(error) => error!("Problem generating probe msg: {:?}", error),
                         }
-                        Err(error) => error!("Problem generating probe msg: {:?}", error),
                     }
                 }
-            }
-        });
+            },
+        );
     }
 
     pub(crate) async fn start_section_probing(self: Arc<Self>) {
         info!("Starting to probe section");
-        let _handle = tokio::spawn(async move {
-            let dispatcher = self.clone();
-            let mut interval = tokio::time::interval(SECTION_PROBE_INTERVAL);
-            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
-
-            loop {
-                let _instant = interval.tick().await;
-
-                // Send a probe message to an elder
-                let node = &dispatcher.node;
-                if !node.network_knowledge().prefix().await.is_empty() {
-                    match node.generate_section_probe_msg().await {
-                        Ok(cmd) => {
-                            info!("Sending section probe msg");
-                            if let Err(e) = dispatcher
-                                .clone()
-                                .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
-                                .await
-                            {
-                                error!("Error sending section probe msg: {:?}", e);
+        self.supervise(
+            Subsystem::Comm,
+            "section probing",
+            |dispatcher| async move {
+                let mut interval = tokio::time::interval(SECTION_PROBE_INTERVAL);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+                loop {
+                    let _instant = interval.tick().await;
+
+                    // Send a probe message to an elder
+                    let node = &dispatcher.node;
+                    if !node.network_knowledge().prefix().await.is_empty() {
+                        match node.generate_section_probe_msg().await {
+                            Ok(cmd) => {
+                                info!("Sending section probe msg");
+                                if let Err(e) = dispatcher
+                                    .clone()
+                                    .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
+                                    .await
+                                {
+                                    error!("Error sending section probe msg: {:?}", e);
+                                }
+                            }
+                            Err(error) => {
+                                error!("Problem generating section probe msg: {:?}", error)
                             }
                         }
-                        Err(error) => error!("Problem generating section probe msg: {:?}", error),
                     }
                 }
-            }
-        });
+            },
+        );
     }
 
     pub(crate) async fn start_cleaning_peer_links(self: Arc<Self>) {
         info!("Starting cleaning up network links");
-        let _handle = tokio::spawn(async move {
-            let dispatcher = self.clone();
-            let mut interval = tokio::time::interval(LINK_CLEANUP_INTERVAL);
-            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
-            let _ = interval.tick().await;
-
-            loop {
+        self.supervise(
+            Subsystem::Comm,
+            "peer links cleanup",
+            |dispatcher| async move {
+                let mut interval = tokio::time::interval(LINK_CLEANUP_INTERVAL);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
                 let _ = interval.tick().await;
-                let cmd = Cmd::CleanupPeerLinks;
+
+                loop {
+                    let _ = interval.tick().await;
+                    let cmd = Cmd::CleanupPeerLinks;
+                    if let Err(e) = dispatcher
+                        .clone()
+                        .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
+                        .await
+                    {
+                        error!(
+                            "Error requesting a cleaning up of unused PeerLinks: {:?}",
+                            e
+                        );
+                    }
+                }
+            },
+        );
+    }
+
+    /// Answers the nodes of our network looking for peers on the local network, with the address
+    /// we accept connections at. Only one node of each host can answer them, as the others
+    /// can't bind the port beacons are broadcast to.
+    pub(crate) async fn start_answering_lan_discovery(
+        self: Arc<Self>,
+        port: u16,
+        network_name: Option<String>,
+    ) {
+        info!("Starting answering LAN discovery beacons on port {}", port);
+        self.supervise(
+            Subsystem::Comm,
+            "LAN discovery answering",
+            move |dispatcher| {
+                let network_name = network_name.clone();
+                async move {
+                    let socket = match bind_lan_beacon(port).await {
+                        Ok(socket) => socket,
+                        Err(error) => {
+                            warn!(
+                                "Not answering LAN discovery beacons, as port {} can't be bound: {:?}",
+                                port, error
+                            );
+                            return;
+                        }
+                    };
+                    let our_addr = dispatcher.node.info.read().await.addr;
+                    if let Err(error) = answer_lan_peers(socket, network_name, our_addr).await {
+                        error!("Stopped answering LAN discovery beacons: {:?}", error);
+                    }
+                }
+            },
+        );
+    }
+
+    /// Periodically loop over any pending data batches and queue up send_msg for those
+    pub(crate) async fn start_sending_any_data_batches(self: Arc<Self>) {
+        info!("Starting sending any queued data for replication in batches");
+
+        self.supervise(
+            Subsystem::Storage,
+            "data batches replication",
+            |dispatcher| async move {
+                if let Err(error) = dispatcher.send_data_batches().await {
+                    error!("Stopped sending queued data in batches: {:?}", error);
+                }
+            },
+        );
+    }
+
+    async fn send_data_batches(self: Arc<Self>) -> Result<()> {
+        let dispatcher = self.clone();
+        let mut interval = tokio::time::interval(DATA_BATCH_INTERVAL);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let _ = interval.tick().await;
+
+        loop {
+            use rand::seq::IteratorRandom;
+            let mut rng = rand::rngs::OsRng;
+            let mut cmds = vec![];
+            let mut bytes_sent = 0;
+            let mut this_batch_address = None;
+
+            // choose a data to replicate at random
+            if let Some(data_queued) = self
+                .pending_data_to_replicate_to_peers
+                .iter()
+                .choose(&mut rng)
+            {
+                this_batch_address = Some(*data_queued.key());
+            }
+
+            if let Some(address) = this_batch_address {
+                if let Some((data_address, data_recipients)) =
+                    self.pending_data_to_replicate_to_peers.remove(&address)
+                {
+                    // get info for the WireMsg
+                    let src_section_pk = self.node.network_knowledge().section_key().await;
+                    let our_info = &*self.node.info.read().await;
+
+                    let mut recipients = vec![];
+
+                    for peer in data_recipients.read().await.iter() {
+                        recipients.push(*peer);
+                    }
+
+                    if recipients.is_empty() {
+                        continue;
+                    }
+
+                    let name = recipients[0].name();
+
+                    let dst = sn_interface::messaging::DstLocation::node(name, src_section_pk);
+
+                    let data_to_send = self
+                        .node
+                        .data_storage
+                        .get_from_local_store(&data_address)
+                        .await?;
+
+                    let system_msg = SystemMsg::NodeCmd(NodeCmd::ReplicateData(vec![data_to_send]));
+                    let wire_msg = self
+                        .node
+                        .signing_pool
+                        .single_src(our_info, dst, system_msg, src_section_pk)
+                        .await?;
+
+                    debug!(
+                        "{:?} to: {:?} w/ {:?} ",
+                        LogMarker::SendingMissingReplicatedData,
+                        recipients,
+                        wire_msg.msg_id()
+                    );
+
+                    bytes_sent = wire_msg.payload.len() * recipients.len();
+                    cmds.extend(
+                        self.send_msg(&recipients, recipients.len(), wire_msg)
+                            .await?,
+                    )
+                }
+            }
+
+            for cmd in cmds {
                 if let Err(e) = dispatcher
                     .clone()
                     .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
@@ -114,83 +270,116 @@ impl Dispatcher {
                     );
                 }
             }
-        });
-    }
 
-    /// Periodically loop over any pending data batches and queue up send_msg for those
-    pub(crate) async fn start_sending_any_data_batches(self: Arc<Self>) {
-        info!("Starting sending any queued data for replication in batches");
+            let bytes_per_sec = self.replication_bytes_per_sec.load(Ordering::Relaxed);
+            if bytes_per_sec > 0 && bytes_sent > 0 {
+                // hold the next batch back for as long as this one takes to send at the capped rate
+                let send_time = Duration::from_secs_f64(bytes_sent as f64 / bytes_per_sec as f64);
+                tokio::time::sleep(send_time).await;
+            }
 
-        let _handle: JoinHandle<Result<()>> = tokio::spawn(async move {
-            let dispatcher = self.clone();
-            let mut interval = tokio::time::interval(DATA_BATCH_INTERVAL);
-            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
             let _ = interval.tick().await;
+        }
+    }
 
-            loop {
-                use rand::seq::IteratorRandom;
-                let mut rng = rand::rngs::OsRng;
-                let mut cmds = vec![];
-                let mut this_batch_address = None;
-
-                // choose a data to replicate at random
-                if let Some(data_queued) = self
-                    .pending_data_to_replicate_to_peers
-                    .iter()
-                    .choose(&mut rng)
-                {
-                    this_batch_address = Some(*data_queued.key());
-                }
+    /// Periodically check the integrity of a few of the chunks we hold,
+    /// so all of them are eventually re-hashed on a rolling basis
+    pub(crate) async fn start_scrubbing_chunks(self: Arc<Self>) {
+        info!("Starting scrubbing stored chunks");
+        self.supervise(
+            Subsystem::Storage,
+            "chunk scrubbing",
+            |dispatcher| async move {
+                let mut interval = tokio::time::interval(CHUNK_SCRUB_INTERVAL);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+                let _ = interval.tick().await;
 
-                if let Some(address) = this_batch_address {
-                    if let Some((data_address, data_recipients)) =
-                        self.pending_data_to_replicate_to_peers.remove(&address)
-                    {
-                        // get info for the WireMsg
-                        let src_section_pk = self.node.network_knowledge().section_key().await;
-                        let our_info = &*self.node.info.read().await;
+                loop {
+                    let _ = interval.tick().await;
 
-                        let mut recipients = vec![];
+                    let cmds = match dispatcher
+                        .node
+                        .scrub_stored_chunks(CHUNK_SCRUB_BATCH_SIZE)
+                        .await
+                    {
+                        Ok(cmds) => cmds,
+                        Err(error) => {
+                            error!("Error scrubbing stored chunks: {:?}", error);
+                            continue;
+                        }
+                    };
 
-                        for peer in data_recipients.read().await.iter() {
-                            recipients.push(*peer);
+                    for cmd in cmds {
+                        if let Err(e) = dispatcher
+                            .clone()
+                            .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
+                            .await
+                        {
+                            error!("Error requesting corrupt chunks to be repaired: {:?}", e);
                         }
+                    }
+                }
+            },
+        );
+    }
 
-                        if recipients.is_empty() {
+    pub(crate) async fn start_checking_peer_liveness(self: Arc<Self>, config: LivenessConfig) {
+        if config.ping_interval.is_zero() {
+            info!("Peer liveness checking is disabled");
+            return;
+        }
+
+        info!("Starting to check peer liveness");
+        self.supervise(
+            Subsystem::Comm,
+            "peer liveness checks",
+            |dispatcher| async move {
+                let mut interval = tokio::time::interval(config.ping_interval);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+                loop {
+                    let _instant = interval.tick().await;
+
+                    let cmds = match dispatcher.node.check_peer_liveness(&config).await {
+                        Ok(cmds) => cmds,
+                        Err(error) => {
+                            error!("Error checking peer liveness: {:?}", error);
                             continue;
                         }
+                    };
 
-                        let name = recipients[0].name();
+                    for cmd in cmds {
+                        if let Err(e) = dispatcher
+                            .clone()
+                            .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
+                            .await
+                        {
+                            error!("Error sending keep-alive ping: {:?}", e);
+                        }
+                    }
+                }
+            },
+        );
+    }
 
-                        let dst = sn_interface::messaging::DstLocation::Node {
-                            name,
-                            section_pk: src_section_pk,
-                        };
+    pub(crate) async fn start_exchanging_peers(self: Arc<Self>) {
+        info!("Starting to exchange peers");
+        self.supervise(Subsystem::Comm, "peer exchange", |dispatcher| async move {
+            let mut interval = tokio::time::interval(PEER_EXCHANGE_INTERVAL);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            // give the liveness checks the time to hear from the peers first
+            let _ = interval.tick().await;
 
-                        let data_to_send = self
-                            .node
-                            .data_storage
-                            .get_from_local_store(&data_address)
-                            .await?;
-
-                        let system_msg =
-                            SystemMsg::NodeCmd(NodeCmd::ReplicateData(vec![data_to_send]));
-                        let wire_msg =
-                            WireMsg::single_src(our_info, dst, system_msg, src_section_pk)?;
-
-                        debug!(
-                            "{:?} to: {:?} w/ {:?} ",
-                            LogMarker::SendingMissingReplicatedData,
-                            recipients,
-                            wire_msg.msg_id()
-                        );
+            loop {
+                let _instant = interval.tick().await;
 
-                        cmds.extend(
-                            self.send_msg(&recipients, recipients.len(), wire_msg)
-                                .await?,
-                        )
+                let cmds = match dispatcher.node.exchange_peers().await {
+                    Ok(cmds) => cmds,
+                    Err(error) => {
+                        error!("Error exchanging peers: {:?}", error);
+                        continue;
                     }
-                }
+                };
 
                 for cmd in cmds {
                     if let Err(e) = dispatcher
@@ -198,50 +387,125 @@ impl Dispatcher {
                         .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
                         .await
                     {
-                        error!(
-                            "Error requesting a cleaning up of unused PeerLinks: {:?}",
-                            e
-                        );
+                        error!("Error sending peer exchange: {:?}", e);
                     }
                 }
-
-                let _ = interval.tick().await;
             }
         });
     }
 
+    pub(crate) async fn start_falling_back_delegated_queries(self: Arc<Self>) {
+        let config = *self.node.query_delegation.config();
+        if config.delegates == 0 {
+            info!("Query delegation is disabled");
+            return;
+        }
+
+        info!("Starting to fall back delegated queries which timed out");
+        self.supervise(
+            Subsystem::Storage,
+            "delegated queries fallback",
+            |dispatcher| async move {
+                let mut interval = tokio::time::interval(config.timeout);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+                loop {
+                    let _instant = interval.tick().await;
+
+                    let cmds = match dispatcher.node.fall_back_timed_out_queries().await {
+                        Ok(cmds) => cmds,
+                        Err(error) => {
+                            error!("Error falling back delegated queries: {:?}", error);
+                            continue;
+                        }
+                    };
+
+                    for cmd in cmds {
+                        if let Err(e) = dispatcher
+                            .clone()
+                            .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
+                            .await
+                        {
+                            error!("Error forwarding delegated query: {:?}", e);
+                        }
+                    }
+                }
+            },
+        );
+    }
+
     pub(crate) async fn check_for_dysfunction_periodically(self: Arc<Self>) {
         info!("Starting dysfunction checking");
-        let _handle = tokio::spawn(async move {
-            let dispatcher = self.clone();
-            let mut interval = tokio::time::interval(DYSFUNCTION_CHECK_INTERVAL);
-            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        self.supervise(
+            Subsystem::Membership,
+            "dysfunction checks",
+            |dispatcher| async move {
+                let mut interval = tokio::time::interval(DYSFUNCTION_CHECK_INTERVAL);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+                loop {
+                    let _instant = interval.tick().await;
+
+                    let mut unresponsive_nodes =
+                        match dispatcher.node.get_dysfunctional_node_names().await {
+                            Ok(nodes) => nodes,
+                            Err(error) => {
+                                error!("Error getting dysfunctional nodes: {error}");
+                                BTreeSet::default()
+                            }
+                        };
 
-            loop {
-                let _instant = interval.tick().await;
+                    let mut demoted = BTreeSet::new();
+                    for (name, action) in dispatcher.node.get_dysfunction_policy_actions().await {
+                        match action {
+                            PolicyAction::Offline => {
+                                let _ = unresponsive_nodes.insert(name);
+                            }
+                            PolicyAction::Demote => {
+                                let _ = demoted.insert(name);
+                            }
+                        }
+                    }
+                    demoted.retain(|name| !unresponsive_nodes.contains(name));
 
-                let unresponsive_nodes = match dispatcher.node.get_dysfunctional_node_names().await
-                {
-                    Ok(nodes) => nodes,
-                    Err(error) => {
-                        error!("Error getting dysfunctional nodes: {error}");
-                        BTreeSet::default()
+                    if !demoted.is_empty() {
+                        debug!("{:?} : {demoted:?}", LogMarker::ProposeDemotion);
+                        let cmds = match dispatcher
+                            .node
+                            .promote_and_demote_elders_except(&demoted)
+                            .await
+                        {
+                            Ok(cmds) => cmds,
+                            Err(error) => {
+                                error!("Error demoting dysfunctional elders: {error}");
+                                vec![]
+                            }
+                        };
+                        for cmd in cmds {
+                            if let Err(e) = dispatcher
+                                .clone()
+                                .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
+                                .await
+                            {
+                                error!("Error demoting dysfunctional elders: {e:?}");
+                            }
+                        }
                     }
-                };
 
-                if !unresponsive_nodes.is_empty() {
-                    debug!("{:?} : {unresponsive_nodes:?}", LogMarker::ProposeOffline);
-                    let cmd = Cmd::ProposeOffline(unresponsive_nodes);
-                    if let Err(e) = dispatcher
-                        .clone()
-                        .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
-                        .await
-                    {
-                        error!("Error sending Propose Offline for dysfunctional nodes: {e:?}");
+                    if !unresponsive_nodes.is_empty() {
+                        debug!("{:?} : {unresponsive_nodes:?}", LogMarker::ProposeOffline);
+                        let cmd = Cmd::ProposeOffline(unresponsive_nodes);
+                        if let Err(e) = dispatcher
+                            .clone()
+                            .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
+                            .await
+                        {
+                            error!("Error sending Propose Offline for dysfunctional nodes: {e:?}");
+                        }
                     }
                 }
-            }
-        });
+            },
+        );
     }
 
     #[cfg(feature = "back-pressure")]
@@ -254,68 +518,74 @@ impl Dispatcher {
     /// not be overloaded...
     pub(crate) async fn report_backpressure_to_our_section_periodically(self: Arc<Self>) {
         info!("Firing off backpressure reports");
-        let _handle = tokio::spawn(async move {
-            let dispatcher = self.clone();
-            let mut interval = tokio::time::interval(BACKPRESSURE_INTERVAL);
-            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
-            let _ = interval.tick().await;
-
-            loop {
+        self.supervise(
+            Subsystem::Comm,
+            "back-pressure reports",
+            |dispatcher| async move {
+                let mut interval = tokio::time::interval(BACKPRESSURE_INTERVAL);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
                 let _ = interval.tick().await;
 
-                let members = dispatcher.node.network_knowledge().section_members().await;
-                let section_pk = dispatcher.node.network_knowledge().section_key().await;
+                loop {
+                    let _ = interval.tick().await;
 
-                if let Some(load_report) = dispatcher.node.comm.tolerated_msgs_per_s().await {
-                    trace!("New BackPressure report to disseminate: {:?}", load_report);
+                    let members = dispatcher.node.network_knowledge().section_members().await;
+                    let section_pk = dispatcher.node.network_knowledge().section_key().await;
 
-                    // TODO: use comms to send report to anyone connected? (can we ID end users there?)
-                    for member in members {
-                        let our_name = dispatcher.node.info.read().await.name();
-                        let peer = member.peer();
+                    if let Some(load_report) = dispatcher.node.comm.tolerated_msgs_per_s().await {
+                        trace!("New BackPressure report to disseminate: {:?}", load_report);
 
-                        if peer.name() == our_name {
-                            continue;
-                        }
+                        // TODO: use comms to send report to anyone connected? (can we ID end users there?)
+                        for member in members {
+                            let our_name = dispatcher.node.info.read().await.name();
+                            let peer = member.peer();
 
-                        let wire_msg = match WireMsg::single_src(
-                            &*dispatcher.node.info.read().await,
-                            DstLocation::Node {
-                                name: peer.name(),
-                                section_pk,
-                            },
-                            SystemMsg::BackPressure(load_report),
-                            section_pk,
-                        ) {
-                            Ok(msg) => msg,
-                            Err(e) => {
-                                error!(
-                                    "Error forming backpressure message to section member {:?}",
-                                    e
-                                );
+                            if peer.name() == our_name {
                                 continue;
                             }
-                        };
 
-                        let cmd = Cmd::SendMsg {
-                            wire_msg,
-                            recipients: vec![*peer],
-                        };
+                            let our_info = dispatcher.node.info.read().await.clone();
+                            let wire_msg = match dispatcher
+                                .node
+                                .signing_pool
+                                .single_src(
+                                    &our_info,
+                                    DstLocation::node(peer.name(), section_pk),
+                                    SystemMsg::BackPressure(load_report),
+                                    section_pk,
+                                )
+                                .await
+                            {
+                                Ok(msg) => msg,
+                                Err(e) => {
+                                    error!(
+                                        "Error forming backpressure message to section member {:?}",
+                                        e
+                                    );
+                                    continue;
+                                }
+                            };
+
+                            let cmd = Cmd::SendMsg {
+                                wire_msg,
+                                recipients: vec![*peer],
+                            };
 
-                        if let Err(e) = dispatcher
-                            .clone()
-                            .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
-                            .await
-                        {
-                            error!(
+                            if let Err(e) = dispatcher
+                                .clone()
+                                .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
+                                .await
+                            {
+                                error!(
                                 "Error sending backpressure report to section member {:?}: {:?}",
                                 peer, e
                             );
+                            }
                         }
                     }
                 }
-            }
-        });
+            },
+        );
     }
 
     pub(crate) async fn write_prefixmap_to_disk(self: Arc<Self>) {
@@ -0,0 +1,91 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{supervision::Subsystem, Dispatcher};
+use crate::node::{api::event::Event, cfg::live_config::LiveConfig};
+
+use std::{
+    path::PathBuf,
+    sync::{atomic::Ordering, Arc},
+    time::{Duration, SystemTime},
+};
+use tokio::time::MissedTickBehavior;
+
+// How often the config file is checked for changes
+const CONFIG_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+impl Dispatcher {
+    /// Periodically check the given config file for changes, applying the settings it holds
+    /// whenever it changed, as well as when first found.
+    pub(crate) async fn start_watching_config_file(self: Arc<Self>, path: PathBuf) {
+        info!("Starting to watch config file {:?}", path);
+        self.supervise(
+            Subsystem::Config,
+            "config file watching",
+            move |dispatcher| {
+                let path = path.clone();
+                async move {
+                    let mut interval = tokio::time::interval(CONFIG_POLL_INTERVAL);
+                    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+                    let mut modified_at: Option<SystemTime> = None;
+
+                    loop {
+                        let _instant = interval.tick().await;
+
+                        let modified = match tokio::fs::metadata(&path)
+                            .await
+                            .and_then(|metadata| metadata.modified())
+                        {
+                            Ok(modified) => modified,
+                            Err(error) => {
+                                trace!("Config file {:?} not readable: {:?}", path, error);
+                                continue;
+                            }
+                        };
+                        if modified_at == Some(modified) {
+                            continue;
+                        }
+                        modified_at = Some(modified);
+
+                        let event = match LiveConfig::read_from_file(&path).await {
+                            Ok(config) => {
+                                dispatcher.apply_live_config(&config).await;
+                                Event::ConfigReloaded(config)
+                            }
+                            Err(error) => {
+                                error!("Config file change not applied: {}", error);
+                                Event::ConfigRejected {
+                                    reason: error.to_string(),
+                                }
+                            }
+                        };
+                        dispatcher.node.send_event(event).await;
+                    }
+                }
+            },
+        );
+    }
+
+    /// Applies the settings held by the node itself. The log level is left to whoever set up
+    /// the logging, as notified with an [`Event::ConfigReloaded`].
+    pub(crate) async fn apply_live_config(&self, config: &LiveConfig) {
+        let rate_limiter = &self.node.client_rate_limiter;
+        let limits = config.client_rate_limits(rate_limiter.limits().await);
+        rate_limiter.set_limits(limits).await;
+
+        if let Some(bytes_per_sec) = config.replication_bytes_per_sec {
+            self.replication_bytes_per_sec
+                .store(bytes_per_sec, Ordering::Relaxed);
+        }
+        if let Some(resource_logs) = config.resource_logs {
+            self.resource_logs.store(resource_logs, Ordering::Relaxed);
+        }
+
+        info!("Applied settings from config file: {:?}", config);
+    }
+}
@@ -7,20 +7,34 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 mod background_tasks;
+mod live_config;
+mod supervision;
+
 use super::Cmd;
 use crate::node::{
     core::{DeliveryStatus, Node, Proposal},
-    messages::WireMsgUtils,
     Result,
 };
 use dashmap::DashMap;
 use sn_interface::types::{log_markers::LogMarker, Peer};
 use sn_interface::{
-    messaging::{system::SystemMsg, AuthKind, WireMsg},
+    messaging::{system::SystemMsg, AuthKind, TraceId, WireMsg},
     types::ReplicatedDataAddress,
 };
-use std::{collections::BTreeSet, sync::Arc, time::Duration};
-use tokio::{sync::watch, sync::RwLock, time};
+use std::{
+    collections::BTreeSet,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+pub use supervision::{Incident, Subsystem};
+use tokio::{
+    sync::{watch, OwnedSemaphorePermit, RwLock},
+    task::JoinHandle,
+    time,
+};
 use tracing::Instrument;
 
 // A command/subcommand id e.g. "963111461", "963111461.0"
@@ -34,8 +48,19 @@ pub(crate) struct Dispatcher {
     // we need to pull data first before we form the WireMsg, we won't do that just now
     pub(crate) pending_data_to_replicate_to_peers:
         Arc<DashMap<ReplicatedDataAddress, Arc<RwLock<BTreeSet<Peer>>>>>,
+    /// cap on the bytes per second of data replicated to peers, 0 meaning no cap
+    pub(crate) replication_bytes_per_sec: AtomicU64,
+    /// whether the resource usage of the node is printed to stdout
+    pub(crate) resource_logs: AtomicBool,
+    // number of the cmds spawned and not yet processed
+    cmds_in_flight: AtomicUsize,
     cancel_timer_tx: watch::Sender<bool>,
     cancel_timer_rx: watch::Receiver<bool>,
+    // set once the node is shutting down, for its long-running tasks to stop
+    shutdown_tx: watch::Sender<bool>,
+    shutdown_rx: watch::Receiver<bool>,
+    // long-running tasks of the node, waited for when it shuts down
+    tasks: Mutex<Vec<JoinHandle<()>>>,
 }
 
 impl Drop for Dispatcher {
@@ -48,14 +73,26 @@ impl Drop for Dispatcher {
 impl Dispatcher {
     pub(super) fn new(node: Node) -> Self {
         let (cancel_timer_tx, cancel_timer_rx) = watch::channel(false);
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
         Self {
             node,
             cancel_timer_tx,
             cancel_timer_rx,
+            shutdown_tx,
+            shutdown_rx,
+            tasks: Mutex::new(vec![]),
             pending_data_to_replicate_to_peers: Arc::new(DashMap::new()),
+            replication_bytes_per_sec: AtomicU64::new(0),
+            resource_logs: AtomicBool::new(false),
+            cmds_in_flight: AtomicUsize::new(0),
         }
     }
 
+    /// Number of the cmds being handled, i.e. spawned and not yet processed.
+    pub(crate) fn cmds_in_flight(&self) -> usize {
+        self.cmds_in_flight.load(Ordering::Relaxed)
+    }
+
     /// Enqueues the given cmd and handles whatever cmd is in the next priority queue and triggers handling after any required waits for higher priority tasks
     pub(super) async fn enqueue_and_handle_next_cmd_and_offshoots(
         self: Arc<Self>,
@@ -65,7 +102,7 @@ impl Dispatcher {
         let _ = tokio::spawn(async {
             let cmd_id: CmdId = cmd_id.unwrap_or_else(|| rand::random::<u32>().to_string());
 
-            self.handle_cmd_and_offshoots(cmd, Some(cmd_id)).await
+            self.handle_cmd_and_offshoots(cmd, Some(cmd_id), None).await
         });
         Ok(())
     }
@@ -74,21 +111,46 @@ impl Dispatcher {
     /// produced during its handling. Trace logs will include the provided cmd id,
     /// and any sub-cmds produced will have it as a common root cmd id.
     /// If a cmd id string is not provided a random one will be generated.
+    /// Likewise, the trace id of the client operation the cmd is part of, taken from the msg
+    /// being handled or else inherited from the parent cmd, is passed on to the sub-cmds.
     pub(super) async fn handle_cmd_and_offshoots(
         self: Arc<Self>,
         cmd: Cmd,
         cmd_id: Option<CmdId>,
+        trace_id: Option<TraceId>,
+    ) -> Result<()> {
+        self.handle_cmd_and_offshoots_holding(cmd, cmd_id, trace_id, None)
+            .await
+    }
+
+    /// Handles cmd and its offshoots as `handle_cmd_and_offshoots` does, holding the given
+    /// permit until the cmd itself is processed, for the number of cmds processed at once to
+    /// be bounded by the number of permits.
+    pub(super) async fn handle_cmd_and_offshoots_holding(
+        self: Arc<Self>,
+        cmd: Cmd,
+        cmd_id: Option<CmdId>,
+        trace_id: Option<TraceId>,
+        permit: Option<OwnedSemaphorePermit>,
     ) -> Result<()> {
         let cmd_id = cmd_id.unwrap_or_else(|| rand::random::<u32>().to_string());
         let cmd_id_clone = cmd_id.clone();
         let cmd_display = cmd.to_string();
+        let trace_id = match &cmd {
+            Cmd::HandleMsg { wire_msg, .. } => wire_msg.trace_id().or(trace_id),
+            _ => trace_id,
+        };
+        let _ = self.cmds_in_flight.fetch_add(1, Ordering::Relaxed);
         let _task = tokio::spawn(async move {
-            match self.process_cmd(cmd, &cmd_id).await {
+            let result = self.process_cmd(cmd, &cmd_id, trace_id).await;
+            drop(permit);
+            let _ = self.cmds_in_flight.fetch_sub(1, Ordering::Relaxed);
+            match result {
                 Ok(cmds) => {
                     for (sub_cmd_count, cmd) in cmds.into_iter().enumerate() {
                         let sub_cmd_id = format!("{}.{}", &cmd_id, sub_cmd_count);
                         // Error here is only related to queueing, and so a dropped cmd will be logged
-                        let _result = self.clone().spawn_cmd_handling(cmd, sub_cmd_id);
+                        let _result = self.clone().spawn_cmd_handling(cmd, sub_cmd_id, trace_id);
                     }
                 }
                 Err(err) => {
@@ -108,13 +170,23 @@ impl Dispatcher {
 
     // Note: this indirecton is needed. Trying to call `spawn(self.handle_cmds(...))` directly
     // inside `handle_cmds` causes compile error about type check cycle.
-    fn spawn_cmd_handling(self: Arc<Self>, cmd: Cmd, cmd_id: String) -> Result<()> {
-        let _task = tokio::spawn(self.enqueue_and_handle_next_cmd_and_offshoots(cmd, Some(cmd_id)));
+    fn spawn_cmd_handling(
+        self: Arc<Self>,
+        cmd: Cmd,
+        cmd_id: String,
+        trace_id: Option<TraceId>,
+    ) -> Result<()> {
+        let _task = tokio::spawn(self.handle_cmd_and_offshoots(cmd, Some(cmd_id), trace_id));
         Ok(())
     }
 
-    /// Handles a single cmd.
-    pub(super) async fn process_cmd(&self, cmd: Cmd, cmd_id: &str) -> Result<Vec<Cmd>> {
+    /// Handles a single cmd, tagging the msgs it sends with the given trace id, if they have none.
+    pub(super) async fn process_cmd(
+        &self,
+        cmd: Cmd,
+        cmd_id: &str,
+        trace_id: Option<TraceId>,
+    ) -> Result<Vec<Cmd>> {
         // Create a tracing span containing info about the current node. This is very useful when
         // analyzing logs produced by running multiple nodes within the same process, for example
         // from integration tests.
@@ -132,6 +204,7 @@ impl Dispatcher {
                 age,
                 elder = is_elder,
                 cmd_id = %cmd_id,
+                trace_id = ?trace_id,
                 section_key = ?section_key,
                 %cmd,
             )
@@ -146,7 +219,7 @@ impl Dispatcher {
                 cmd_display
             );
 
-            let res = match self.try_processing_cmd(cmd).await {
+            let res = match self.try_processing_cmd(cmd, trace_id).await {
                 Ok(outcome) => {
                     trace!(
                         "{:?} {:?} - {}",
@@ -177,7 +250,7 @@ impl Dispatcher {
     }
 
     /// Actually process the cmd
-    async fn try_processing_cmd(&self, cmd: Cmd) -> Result<Vec<Cmd>> {
+    async fn try_processing_cmd(&self, cmd: Cmd, trace_id: Option<TraceId>) -> Result<Vec<Cmd>> {
         match cmd {
             Cmd::CleanupPeerLinks => {
                 self.node.cleanup_non_elder_peers().await;
@@ -185,14 +258,38 @@ impl Dispatcher {
             }
             Cmd::SignOutgoingSystemMsg { msg, dst } => {
                 let src_section_pk = self.node.network_knowledge().section_key().await;
-                let wire_msg =
-                    WireMsg::single_src(&*self.node.info.read().await, dst, msg, src_section_pk)?;
+                let our_info = self.node.info.read().await.clone();
+                let mut wire_msg = self
+                    .node
+                    .signing_pool
+                    .single_src(&our_info, dst, msg, src_section_pk)
+                    .await?;
+                wire_msg.set_trace_id(trace_id);
 
                 let mut cmds = vec![];
                 cmds.extend(self.node.send_msg_to_nodes(wire_msg).await?);
 
                 Ok(cmds)
             }
+            Cmd::SignAndSendMsg {
+                msg,
+                dst,
+                src_section_pk,
+                recipients,
+            } => {
+                let our_info = self.node.info.read().await.clone();
+                let mut wire_msg = self
+                    .node
+                    .signing_pool
+                    .single_src(&our_info, dst, msg, src_section_pk)
+                    .await?;
+                wire_msg.set_trace_id(trace_id);
+
+                Ok(vec![Cmd::SendMsg {
+                    recipients,
+                    wire_msg,
+                }])
+            }
             Cmd::HandleMsg {
                 sender,
                 wire_msg,
@@ -240,8 +337,27 @@ impl Dispatcher {
                 .map(|cmd| vec![cmd]),
             Cmd::SendMsg {
                 recipients,
-                wire_msg,
-            } => self.send_msg(&recipients, recipients.len(), wire_msg).await,
+                mut wire_msg,
+            } => {
+                if wire_msg.trace_id().is_none() {
+                    wire_msg.set_trace_id(trace_id);
+                }
+                self.send_msg(&recipients, recipients.len(), wire_msg).await
+            }
+            Cmd::SendMsgThen {
+                recipients,
+                mut wire_msg,
+                then,
+            } => {
+                if wire_msg.trace_id().is_none() {
+                    wire_msg.set_trace_id(trace_id);
+                }
+                let mut cmds = self
+                    .send_msg(&recipients, recipients.len(), wire_msg)
+                    .await?;
+                cmds.extend(then);
+                Ok(cmds)
+            }
             Cmd::EnqueueDataForReplication {
                 // throttle_duration,
                 recipient,
@@ -270,8 +386,11 @@ impl Dispatcher {
             Cmd::SendMsgDeliveryGroup {
                 recipients,
                 delivery_group_size,
-                wire_msg,
+                mut wire_msg,
             } => {
+                if wire_msg.trace_id().is_none() {
+                    wire_msg.set_trace_id(trace_id);
+                }
                 self.send_msg(&recipients, delivery_group_size, wire_msg)
                     .await
             }
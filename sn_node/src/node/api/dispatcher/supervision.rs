@@ -0,0 +1,331 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Dispatcher;
+use crate::node::api::event::Event;
+
+use serde::Serialize;
+use std::{
+    any::Any,
+    collections::VecDeque,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{sync::watch, task::JoinHandle};
+
+// Number of times a task can panic within `RESTART_WINDOW` and be restarted,
+// before we give up on it
+const MAX_RESTARTS: usize = 5;
+const RESTART_WINDOW: Duration = Duration::from_secs(600);
+// Delay before restarting a task, multiplied by the number of times it panicked in the window
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+/// Subsystem of the node a long-running task belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+pub enum Subsystem {
+    /// Tracking of the members and Elders of our section, and of their dysfunction
+    Membership,
+    /// Msgs exchanged with peers, and the connections they are sent over
+    Comm,
+    /// Data stored by the node, and its replication
+    Storage,
+    /// Settings of the node changed while it's running
+    Config,
+}
+
+/// A long-running task of the node panicked.
+///
+/// The state the task works on is behind async locks, which are released rather than poisoned
+/// when a task panics holding them, or on disk, so the task is restarted from it. A task
+/// panicking over and over is given up on though, leaving the node unable to carry out its
+/// duties, so the `sn_node` binary restarts the node on such an incident, resuming it from the
+/// state it persisted with [`NodeApi::resume`](crate::node::NodeApi::resume).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Incident {
+    /// Subsystem the task belongs to
+    pub subsystem: Subsystem,
+    /// Name of the task
+    pub task: String,
+    /// Message the task panicked with
+    pub panic: String,
+    /// Number of times the task panicked recently, including this one
+    pub panics: usize,
+    /// Whether the task is restarted, or was given up on
+    pub restarted: bool,
+}
+
+impl Dispatcher {
+    /// Spawns a long-running task, restarting it whenever it panics, and reporting each panic
+    /// with an [`Event::Incident`]. The task is stopped once the node is shutting down.
+    pub(crate) fn supervise<F, Fut>(
+        self: Arc<Self>,
+        subsystem: Subsystem,
+        task: &'static str,
+        start: F,
+    ) where
+        F: Fn(Arc<Dispatcher>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let shutdown_rx = self.shutdown_rx.clone();
+        let supervisor = self.clone();
+        let handle = tokio::spawn(async move {
+            let dispatcher = self.clone();
+            run_supervised(
+                subsystem,
+                task,
+                RESTART_DELAY,
+                shutdown_rx,
+                || start(self.clone()),
+                |incident| {
+                    let dispatcher = dispatcher.clone();
+                    async move { dispatcher.node.send_event(Event::Incident(incident)).await }
+                },
+            )
+            .await
+        });
+
+        supervisor
+            .tasks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(handle);
+    }
+
+    /// Signals the long-running tasks to stop, and cancels the scheduled timers, returning the
+    /// handles of the tasks, to wait for them to be done.
+    pub(crate) fn shutdown(&self) -> Vec<JoinHandle<()>> {
+        info!("Shutting down the long-running tasks of the node");
+        let _ = self.shutdown_tx.send(true);
+        let _ = self.cancel_timer_tx.send(true);
+        std::mem::take(
+            &mut *self
+                .tasks
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        )
+    }
+}
+
+// Runs the task until it finishes without panicking, restarting it each time it panics,
+// unless it did so too many times recently, or until the node is shutting down.
+async fn run_supervised<F, Fut, R, RFut>(
+    subsystem: Subsystem,
+    task: &str,
+    restart_delay: Duration,
+    shutdown_rx: watch::Receiver<bool>,
+    start: F,
+    report: R,
+) where
+    F: Fn() -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+    R: Fn(Incident) -> RFut,
+    RFut: Future<Output = ()>,
+{
+    let mut panics = VecDeque::new();
+    loop {
+        let mut handle = tokio::spawn(start());
+        let result = tokio::select! {
+            result = &mut handle => result,
+            () = shutting_down(shutdown_rx.clone()) => {
+                handle.abort();
+                let _ = handle.await;
+                debug!(
+                    "Task '{}' of {:?} subsystem stopped as the node is shutting down",
+                    task, subsystem
+                );
+                return;
+            }
+        };
+
+        let error = match result {
+            Ok(()) => {
+                debug!("Task '{}' of {:?} subsystem finished", task, subsystem);
+                return;
+            }
+            Err(error) if error.is_panic() => error,
+            Err(error) => {
+                debug!(
+                    "Task '{}' of {:?} subsystem cancelled: {:?}",
+                    task, subsystem, error
+                );
+                return;
+            }
+        };
+
+        let now = Instant::now();
+        while matches!(panics.front(), Some(at) if now.duration_since(*at) > RESTART_WINDOW) {
+            let _ = panics.pop_front();
+        }
+        panics.push_back(now);
+
+        let incident = Incident {
+            subsystem,
+            task: task.to_string(),
+            panic: panic_message(error.into_panic()),
+            panics: panics.len(),
+            restarted: panics.len() <= MAX_RESTARTS,
+        };
+        if incident.restarted {
+            warn!("Restarting panicked task: {:?}", incident);
+        } else {
+            error!("Giving up on task panicking repeatedly: {:?}", incident);
+        }
+
+        let restart = incident.restarted;
+        report(incident).await;
+        if !restart {
+            return;
+        }
+
+        tokio::select! {
+            () = tokio::time::sleep(restart_delay * panics.len() as u32) => {}
+            () = shutting_down(shutdown_rx.clone()) => return,
+        }
+    }
+}
+
+// Resolves once the node is shutting down
+async fn shutting_down(mut shutdown_rx: watch::Receiver<bool>) {
+    while !*shutdown_rx.borrow() {
+        if shutdown_rx.changed().await.is_err() {
+            // the node was dropped without shutting down, which can't happen while its tasks run
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_supervised, Subsystem, MAX_RESTARTS};
+
+    use eyre::Result;
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+    use tokio::sync::{mpsc, watch};
+
+    #[tokio::test]
+    async fn panicked_task_is_restarted_until_it_finishes() {
+        let runs = Arc::new(AtomicUsize::new(0));
+        let (incident_tx, mut incident_rx) = mpsc::unbounded_channel();
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+
+        run_supervised(
+            Subsystem::Storage,
+            "flaky",
+            Duration::ZERO,
+            shutdown_rx,
+            || {
+                let runs = runs.clone();
+                async move {
+                    if runs.fetch_add(1, Ordering::SeqCst) < 2 {
+                        panic!("flaky task panicked");
+                    }
+                }
+            },
+            |incident| {
+                let _ = incident_tx.send(incident);
+                async {}
+            },
+        )
+        .await;
+
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        for panics in 1..=2 {
+            let incident = incident_rx.try_recv().expect("incident reported");
+            assert_eq!(incident.subsystem, Subsystem::Storage);
+            assert_eq!(incident.panic, "flaky task panicked");
+            assert_eq!(incident.panics, panics);
+            assert!(incident.restarted);
+        }
+        assert!(incident_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn task_panicking_repeatedly_is_given_up_on() {
+        let (incident_tx, mut incident_rx) = mpsc::unbounded_channel();
+        let (_shutdown_tx, shutdown_rx) = watch::channel(false);
+
+        run_supervised(
+            Subsystem::Comm,
+            "broken",
+            Duration::ZERO,
+            shutdown_rx,
+            || async { panic!("broken task panicked") },
+            |incident| {
+                let _ = incident_tx.send(incident);
+                async {}
+            },
+        )
+        .await;
+
+        let mut incidents = vec![];
+        while let Ok(incident) = incident_rx.try_recv() {
+            incidents.push(incident);
+        }
+        assert_eq!(incidents.len(), MAX_RESTARTS + 1);
+        assert!(incidents[..MAX_RESTARTS].iter().all(|i| i.restarted));
+        assert!(!incidents[MAX_RESTARTS].restarted);
+    }
+
+    #[tokio::test]
+    async fn task_is_stopped_once_the_node_is_shutting_down() -> Result<()> {
+        let stopped = Arc::new(AtomicUsize::new(0));
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+        // dropped along with the task, once it's aborted
+        struct StopGuard(Arc<AtomicUsize>);
+        impl Drop for StopGuard {
+            fn drop(&mut self) {
+                let _ = self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        let handle = tokio::spawn(run_supervised(
+            Subsystem::Membership,
+            "endless",
+            Duration::ZERO,
+            shutdown_rx,
+            {
+                let stopped = stopped.clone();
+                move || {
+                    let guard = StopGuard(stopped.clone());
+                    async move {
+                        let _guard = guard;
+                        std::future::pending::<()>().await
+                    }
+                }
+            },
+            |_| async {},
+        ));
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(stopped.load(Ordering::SeqCst), 0);
+
+        shutdown_tx.send(true)?;
+        tokio::time::timeout(Duration::from_secs(5), handle).await??;
+        assert_eq!(stopped.load(Ordering::SeqCst), 1);
+
+        Ok(())
+    }
+}
@@ -8,10 +8,12 @@
 
 use sn_interface::messaging::{
     data::ServiceMsg,
-    system::{NodeCmd, NodeQuery, NodeQueryResponse},
+    system::{NodeCmd, NodeQuery, NodeQueryResponse, Reachability},
     AuthorityProof, DstLocation, EndUser, MsgId, ServiceAuth, SrcLocation,
 };
 
+use crate::node::{Incident, LiveConfig, RecoveryReport};
+
 use bls::PublicKey as BlsPublicKey;
 use ed25519_dalek::Keypair;
 use std::{collections::BTreeSet, sync::Arc};
@@ -57,6 +59,41 @@ pub enum Event {
     /// Join occured during section churn and new elders missed it,
     /// therefore the node is not a section member anymore, it needs to rejoin the network.
     ChurnJoinMissError,
+    /// The peer this node bootstrapped to dialled it back, or failed to, before it joined.
+    ReachabilityChecked(Reachability),
+    /// The node joined the network, or started it as its genesis node, and is taking on its
+    /// duties. The first event the node raises, besides the check of its reachability.
+    Joined {
+        /// Name of the node
+        name: XorName,
+        /// Prefix of the section the node is a member of
+        prefix: Prefix,
+        /// Age of the node
+        age: u8,
+    },
+    /// A peer this node watches, i.e. a member of its section as an Elder, or one of its Elders
+    /// as an Adult, was deemed down, not having been heard from for a while.
+    PeerLost {
+        /// Name of the peer
+        name: XorName,
+    },
+    /// The node was shut down, its tasks having stopped and its connections being closed.
+    /// The last event the node raises.
+    Terminated,
+    /// The node checked its stored state against its section's, as it started again after
+    /// going down uncleanly.
+    RecoveryChecked(RecoveryReport),
+    /// A long-running task of the node panicked, and was restarted or given up on.
+    Incident(Incident),
+    /// The config file of the node changed, and the settings it holds were applied.
+    /// The log level is applied by whoever set up the logging.
+    ConfigReloaded(LiveConfig),
+    /// The config file of the node changed, but was rejected, the node carrying on with the
+    /// settings it had.
+    ConfigRejected {
+        /// Why the file was rejected
+        reason: String,
+    },
     /// Received a message from another Node.
     MessageReceived {
         /// The message ID
@@ -104,6 +141,15 @@ pub enum Event {
         /// Previous name before relocation
         previous_name: XorName,
     },
+    /// This node gave up relocating to other section, not having been approved by the
+    /// destination section after several attempts. Having been relocated out of its previous
+    /// section, it's not a member of any section anymore, so it needs to rejoin the network.
+    RelocationTimedOut {
+        /// Name of the node, unchanged.
+        previous_name: XorName,
+        /// Name the destination section was picked by.
+        dst: XorName,
+    },
     /// This node has completed relocation to other section.
     Relocated {
         /// Old name before the relocation.
@@ -8,6 +8,7 @@
 
 use super::Event;
 use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
 
 /// Stream of routing node events
 #[allow(missing_debug_implementations)]
@@ -24,4 +25,53 @@ impl EventStream {
     pub async fn next(&mut self) -> Option<Event> {
         self.events_rx.recv().await
     }
+
+    /// Returns next event, or `None` once the given token is cancelled, even if events are
+    /// still pending, so apps can stop driving the node at any point.
+    pub async fn next_until_cancelled(
+        &mut self,
+        cancellation: &CancellationToken,
+    ) -> Option<Event> {
+        tokio::select! {
+            biased;
+            _ = cancellation.cancelled() => None,
+            event = self.events_rx.recv() => event,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Event, EventStream};
+
+    use eyre::{eyre, Result};
+    use tokio::sync::mpsc;
+    use tokio_util::sync::CancellationToken;
+
+    #[tokio::test]
+    async fn no_event_is_returned_once_cancelled() -> Result<()> {
+        let (events_tx, events_rx) = mpsc::channel(2);
+        let mut event_stream = EventStream::new(events_rx);
+        let cancellation = CancellationToken::new();
+
+        for _ in 0..2 {
+            events_tx
+                .send(Event::Terminated)
+                .await
+                .map_err(|_| eyre!("event stream closed"))?;
+        }
+        let event = event_stream
+            .next_until_cancelled(&cancellation)
+            .await
+            .ok_or_else(|| eyre!("expected an event"))?;
+        assert!(matches!(event, Event::Terminated));
+
+        cancellation.cancel();
+        assert!(event_stream
+            .next_until_cancelled(&cancellation)
+            .await
+            .is_none());
+
+        Ok(())
+    }
 }
@@ -14,39 +14,61 @@ pub(crate) mod cmds;
 pub(super) mod dispatcher;
 pub(super) mod event;
 pub(super) mod event_stream;
+mod msg_queue;
+pub(super) mod status;
 
 use self::{
     cmds::Cmd,
-    dispatcher::Dispatcher,
+    dispatcher::{Dispatcher, Subsystem},
     event::{Elders, Event, NodeElderChange},
     event_stream::EventStream,
+    msg_queue::MsgQueue,
+    status::{BootstrapState, NodeStatus},
 };
 
 use crate::node::{
-    cfg::keypair_storage::{get_reward_pk, store_network_keypair, store_new_reward_keypair},
-    core::{join_network, Comm, MsgEvent, Node},
+    cfg::{
+        bootstrap_cache::get_bootstrap_cache,
+        bootstrap_contacts::read_bootstrap_contacts,
+        keypair_storage::{
+            get_encrypted_network_keypair, get_network_keypair, get_reward_pk, read_passphrase,
+            store_network_keypair, store_new_reward_keypair,
+        },
+    },
+    core::{
+        check_reachability, discover_lan_peers, join_network, Comm, MsgEvent, Node, Proposal,
+        RunningMarker, SigningPool,
+    },
     error::{Error, Result},
     logging::{log_ctx::LogCtx, run_system_logger},
-    messages::WireMsgUtils,
-    Config, Peer,
+    AdultParticipation, Config, Peer, QueryCacheStats, RoutingTable, ScrubProgress, SigningStats,
+};
+use crate::{dbs::migrate_chunks, UsedSpace};
+use sn_interface::messaging::{
+    system::{Reachability, SystemMsg},
+    DstLocation, WireMsg,
+};
+use sn_interface::network_knowledge::{
+    NetworkSnapshot, NodeInfo, SectionAuthorityProvider, MIN_ADULT_AGE,
+};
+use sn_interface::types::{
+    keys::ed25519, log_markers::LogMarker, NodeAttestation, PublicKey as TypesPublicKey,
 };
-use crate::UsedSpace;
-use sn_interface::messaging::{system::SystemMsg, DstLocation, WireMsg};
-use sn_interface::network_knowledge::{NodeInfo, SectionAuthorityProvider, MIN_ADULT_AGE};
-use sn_interface::types::{keys::ed25519, log_markers::LogMarker, PublicKey as TypesPublicKey};
 
-use ed25519_dalek::PublicKey;
-use itertools::Itertools;
+use ed25519_dalek::{Keypair, PublicKey};
 use rand_07::rngs::OsRng;
 use secured_linked_list::SecuredLinkedList;
 use std::{
-    collections::BTreeSet,
-    net::{Ipv4Addr, SocketAddr},
+    collections::{BTreeMap, BTreeSet},
+    net::{IpAddr, SocketAddr},
     path::Path,
-    sync::Arc,
-    time::Duration,
+    sync::{atomic::Ordering, Arc},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    sync::{mpsc, Mutex, Semaphore},
+    task::JoinHandle,
 };
-use tokio::{sync::mpsc, task};
 use xor_name::{Prefix, XorName};
 
 /// Interface for sending and receiving messages to and from other nodes, in the role of a full
@@ -59,21 +81,94 @@ use xor_name::{Prefix, XorName};
 #[allow(missing_debug_implementations)]
 pub struct NodeApi {
     dispatcher: Arc<Dispatcher>,
+    // outcome of the connectivity self-test run before joining, if any was
+    reachability: Option<Reachability>,
+    // removed when the node shuts down cleanly, for it to know otherwise when starting again
+    running_marker: Option<RunningMarker>,
+    bootstrap: BootstrapState,
+    // name the node joined the network with, to tell whether it got relocated since
+    joined_as: XorName,
+    joined_at: Instant,
 }
 
 static EVENT_CHANNEL_SIZE: usize = 20;
 
+// Time the peers on the local network are given to answer a LAN discovery beacon
+const LAN_DISCOVERY_WAIT: Duration = Duration::from_secs(2);
+
+// Number of the msgs received which are processed at once. Beyond it, msgs wait in a queue
+// and the most urgent of them are processed first.
+const MAX_MSGS_PROCESSED_AT_ONCE: usize = 128;
+
+// Number of the msgs waiting to be processed beyond which no more are read from the network
+// until some are processed.
+const MAX_QUEUED_MSGS: usize = 10_000;
+
 impl NodeApi {
     ////////////////////////////////////////////////////////////////////////////
     // Public API
     ////////////////////////////////////////////////////////////////////////////
 
     /// Initialize a new node.
+    ///
+    /// When the config has a passphrase file, the network keypair the node had before restarting
+    /// is loaded back, for it to rejoin the network under the same name.
     pub async fn new(config: &Config, joining_timeout: Duration) -> Result<(Self, EventStream)> {
+        Self::start(config, None, joining_timeout).await
+    }
+
+    /// Initialize a new node which joins the network with the given network keypair, rather than
+    /// a newly generated one. The genesis node always starts with a new keypair.
+    ///
+    /// The Elders recognise a node joining with the keypair of one which left their section as
+    /// that node rejoining.
+    pub async fn with_keypair(
+        config: &Config,
+        keypair: Keypair,
+        joining_timeout: Duration,
+    ) -> Result<(Self, EventStream)> {
+        Self::start(config, Some(keypair), joining_timeout).await
+    }
+
+    /// Restart a node from the state it persisted, once it went down: it rejoins the network with
+    /// the network keypair it had, stored in plaintext unless the config has a passphrase file,
+    /// resuming from the chunks, network knowledge and bootstrap contacts kept in its root dir.
+    pub async fn resume(config: &Config, joining_timeout: Duration) -> Result<(Self, EventStream)> {
+        let keypair = match &config.identity_passphrase_file {
+            // loaded back along with the passphrase
+            Some(_) => None,
+            None => {
+                let keypair = get_network_keypair(&config.root_dir()?).await?;
+                if keypair.is_none() {
+                    warn!("No network keypair persisted, the node is resumed under a new name");
+                }
+                keypair
+            }
+        };
+
+        Self::start(config, keypair, joining_timeout).await
+    }
+
+    // Private helper to set up the storage of the node, start it, and persist its network keypair.
+    async fn start(
+        config: &Config,
+        keypair: Option<Keypair>,
+        joining_timeout: Duration,
+    ) -> Result<(Self, EventStream)> {
         let root_dir_buf = config.root_dir()?;
         let root_dir = root_dir_buf.as_path();
         tokio::fs::create_dir_all(root_dir).await?;
 
+        let passphrase = match &config.identity_passphrase_file {
+            Some(path) => Some(read_passphrase(path).await?),
+            None => None,
+        };
+        let keypair = match (keypair, &passphrase) {
+            (Some(keypair), _) => Some(keypair),
+            (None, Some(passphrase)) => get_encrypted_network_keypair(root_dir, passphrase).await?,
+            (None, None) => None,
+        };
+
         let _reward_key = match get_reward_pk(root_dir).await? {
             Some(public_key) => TypesPublicKey::Ed25519(public_key),
             None => {
@@ -84,18 +179,46 @@ impl NodeApi {
             }
         };
 
+        if let Some(migrate_chunks_from) = config.migrate_chunks_from {
+            let _migrated =
+                migrate_chunks(root_dir, migrate_chunks_from, config.chunk_store).await?;
+        }
+
         let used_space = UsedSpace::new(config.max_capacity());
+        let unclean_shutdown = RunningMarker::found_in(root_dir);
+        if unclean_shutdown {
+            warn!("Node went down uncleanly, it will check its state before resuming its duties");
+        }
+        // created ahead of joining, for a node going down while joining to be caught as well
+        let running_marker = RunningMarker::create(root_dir).await?;
 
-        let (api, network_events) = tokio::time::timeout(
+        let (mut api, network_events) = tokio::time::timeout(
             joining_timeout,
-            Self::start_node(config, used_space, root_dir),
+            Self::start_node(
+                config,
+                used_space,
+                root_dir,
+                unclean_shutdown,
+                keypair,
+                passphrase.clone(),
+            ),
         )
         .await
         .map_err(|_| Error::JoinTimeout)??;
+        api.running_marker = Some(running_marker);
 
         // Network keypair may have to be changed due to naming criteria or network requirements.
-        let keypair_as_bytes = api.dispatcher.node.info.read().await.keypair.to_bytes();
-        store_network_keypair(root_dir, keypair_as_bytes).await?;
+        // It's stored again whenever the node is relocated.
+        let keypair = api.dispatcher.node.info.read().await.keypair.clone();
+        store_network_keypair(root_dir, &keypair, passphrase.as_deref()).await?;
+
+        // The Elders of our section are the ones to contact first if we restart
+        if !config.is_first() {
+            let elders = api.dispatcher.node.network_knowledge().elders().await;
+            if let Err(err) = api.dispatcher.node.store_bootstrap_cache(&elders).await {
+                warn!("Failed to store the bootstrap cache: {:?}", err);
+            }
+        }
 
         let our_pid = std::process::id();
         let node_prefix = api.our_prefix().await;
@@ -108,12 +231,20 @@ impl NodeApi {
             "Node PID: {:?}, prefix: {:?}, name: {:?}, age: {}, connection info:\n{}",
             our_pid, node_prefix, node_name, node_age, our_conn_info_json,
         );
+        let listening_addrs = api.listening_addrs();
+        if listening_addrs.len() > 1 {
+            println!("Node listening at: {:?}", listening_addrs);
+            info!("Node listening at: {:?}", listening_addrs);
+        }
         info!(
             "Node PID: {:?}, prefix: {:?}, name: {:?}, age: {}, connection info: {}",
             our_pid, node_prefix, node_name, node_age, our_conn_info_json,
         );
 
-        run_system_logger(LogCtx::new(api.dispatcher.clone()), config.resource_logs).await;
+        api.dispatcher
+            .resource_logs
+            .store(config.resource_logs, Ordering::Relaxed);
+        run_system_logger(LogCtx::new(api.dispatcher.clone())).await;
 
         Ok((api, network_events))
     }
@@ -127,15 +258,16 @@ impl NodeApi {
         config: &Config,
         used_space: UsedSpace,
         root_storage_dir: &Path,
+        unclean_shutdown: bool,
+        keypair: Option<Keypair>,
+        identity_passphrase: Option<String>,
     ) -> Result<(Self, EventStream)> {
         let (event_tx, event_rx) = mpsc::channel(EVENT_CHANNEL_SIZE);
         let (connection_event_tx, mut connection_event_rx) = mpsc::channel(1);
 
-        let local_addr = config
-            .local_addr
-            .unwrap_or_else(|| SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)));
+        let local_addr = config.local_addr();
 
-        let node = if config.is_first() {
+        let (mut node, reachability, bootstrap) = if config.is_first() {
             // Genesis node having a fix age of 255.
             let keypair = ed25519::gen_keypair(&Prefix::default().range_inclusive(), 255);
             let node_name = ed25519::name(&keypair.public);
@@ -149,8 +281,11 @@ impl NodeApi {
             let comm = Comm::first_node(
                 local_addr,
                 config.network_config().clone(),
+                config.network_name(),
                 connection_event_tx,
             )
+            .await?
+            .listen_at(&config.listen_addrs, config.network_config().clone())
             .await?;
             let info = NodeInfo::new(keypair, comm.our_connection_info());
 
@@ -161,6 +296,9 @@ impl NodeApi {
                 event_tx,
                 used_space.clone(),
                 root_storage_dir.to_path_buf(),
+                config.chunk_store_config(),
+                config.client_rate_limits(),
+                config.query_delegation_config(),
                 genesis_sk_set,
             )
             .await?;
@@ -175,6 +313,13 @@ impl NodeApi {
                 removed: BTreeSet::new(),
             };
 
+            node.send_event(Event::Joined {
+                name: node_name,
+                prefix: network_knowledge.prefix().await,
+                age: node.info.read().await.age(),
+            })
+            .await;
+
             info!("{}", LogMarker::PromotedToElder);
             node.send_event(Event::EldersChanged {
                 elders,
@@ -190,7 +335,7 @@ impl NodeApi {
                 hex::encode(genesis_key.to_bytes())
             );
 
-            node
+            (node, None, BootstrapState::Genesis)
         } else {
             let genesis_key_str = config.genesis_key.as_ref().ok_or_else(|| {
                 Error::Configuration("Network's genesis key was not provided.".to_string())
@@ -203,22 +348,19 @@ impl NodeApi {
                     )
                 })?;
 
-            let keypair = ed25519::gen_keypair(&Prefix::default().range_inclusive(), MIN_ADULT_AGE);
+            let rejoining = keypair.is_some();
+            let keypair = keypair.unwrap_or_else(|| {
+                ed25519::gen_keypair(&Prefix::default().range_inclusive(), MIN_ADULT_AGE)
+            });
             let node_name = ed25519::name(&keypair.public);
-            info!("{} Bootstrapping as a new node.", node_name);
+            if rejoining {
+                info!("{} Bootstrapping with our previous keypair.", node_name);
+            } else {
+                info!("{} Bootstrapping as a new node.", node_name);
+            }
 
-            let (comm, bootstrap_addr) = Comm::bootstrap(
-                local_addr,
-                config
-                    .hard_coded_contacts
-                    .iter()
-                    .copied()
-                    .collect_vec()
-                    .as_slice(),
-                config.network_config().clone(),
-                connection_event_tx,
-            )
-            .await?;
+            let (comm, bootstrap_addr) =
+                Self::bootstrap(config, root_storage_dir, connection_event_tx).await?;
             info!(
                 "{} Joining as a new node (PID: {}) our socket: {}, bootstrapper was: {}, network's genesis key: {:?}",
                 node_name,
@@ -229,9 +371,39 @@ impl NodeApi {
             );
 
             let joining_node = NodeInfo::new(keypair, comm.our_connection_info());
-            let (info, network_knowledge) = join_network(
+            let signing_pool = SigningPool::for_node();
+
+            // Make sure our peers can dial us back before trying to join
+            let reachability = check_reachability(
+                &joining_node,
+                &comm,
+                &signing_pool,
+                &mut connection_event_rx,
+                bootstrap_addr,
+                genesis_key,
+            )
+            .await?;
+            match reachability {
+                Some(Reachability::Unreachable(addr)) => {
+                    error!(
+                        "{} cannot join the network since it is not externally reachable: {}",
+                        node_name, addr
+                    );
+                    return Err(Error::NodeNotReachable(addr));
+                }
+                Some(Reachability::Reachable(addr)) => {
+                    info!("{} is externally reachable at {}", node_name, addr);
+                }
+                None => warn!(
+                    "{} could not check whether it is externally reachable",
+                    node_name
+                ),
+            }
+
+            let (info, network_knowledge, activated_features) = join_network(
                 joining_node,
                 &comm,
+                &signing_pool,
                 &mut connection_event_rx,
                 bootstrap_addr,
                 genesis_key,
@@ -246,30 +418,104 @@ impl NodeApi {
                 event_tx,
                 used_space.clone(),
                 root_storage_dir.to_path_buf(),
+                config.chunk_store_config(),
+                config.client_rate_limits(),
+                config.query_delegation_config(),
             )
             .await?;
+            node.set_feature_activations(activated_features).await;
             info!("{} Joined the network!", node.info.read().await.name());
             info!("Our AGE: {}", node.info.read().await.age());
 
-            node
+            if let Some(reachability) = reachability {
+                node.send_event(Event::ReachabilityChecked(reachability))
+                    .await;
+            }
+            let (name, age) = {
+                let info = node.info.read().await;
+                (info.name(), info.age())
+            };
+            node.send_event(Event::Joined {
+                name,
+                prefix: node.network_knowledge().prefix().await,
+                age,
+            })
+            .await;
+
+            let bootstrap = if rejoining && name == node_name {
+                BootstrapState::Rejoined
+            } else {
+                BootstrapState::Joined
+            };
+
+            (node, reachability, bootstrap)
         };
 
+        node.comm
+            .set_inbound_rate_limits(config.inbound_rate_limits())
+            .await;
+        node.comm
+            .set_blacklist_threshold(config.peer_violation_threshold);
+        node.relocation_timeout = config.relocation_timeout();
+        node.require_chunk_payments = config.require_chunk_payments;
+        node.identity_passphrase = identity_passphrase;
+        // the members of our section are known from its SAP from the start, not only
+        // once it churns
+        node.tag_section_members().await;
         let dispatcher = Arc::new(Dispatcher::new(node));
         let event_stream = EventStream::new(event_rx);
 
+        // check our state before resuming our duties, rather than trusting it blindly,
+        // before anything overwrites the state stored on disk
+        let recovery = if unclean_shutdown {
+            Some(dispatcher.node.check_recovery().await?)
+        } else {
+            None
+        };
+
         // Start listening to incoming connections.
-        let _handle = task::spawn(handle_connection_events(
-            dispatcher.clone(),
-            connection_event_rx,
-        ));
+        let connection_event_rx = Arc::new(Mutex::new(connection_event_rx));
+        dispatcher.clone().supervise(
+            Subsystem::Comm,
+            "incoming msgs handling",
+            move |dispatcher| handle_connection_events(dispatcher, connection_event_rx.clone()),
+        );
+
+        if let Some((report, cmds)) = recovery {
+            for cmd in cmds {
+                if let Err(e) = dispatcher
+                    .clone()
+                    .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
+                    .await
+                {
+                    error!("Error recovering from unclean shutdown: {:?}", e);
+                }
+            }
+            // our Elders' AE response is handled by the incoming msgs handling started above
+            let report = dispatcher.node.await_recovery_probe(report).await;
+            dispatcher
+                .node
+                .send_event(Event::RecoveryChecked(report))
+                .await;
+        }
 
         dispatcher.clone().start_network_probing().await;
         dispatcher.clone().start_section_probing().await;
+        dispatcher
+            .clone()
+            .start_checking_peer_liveness(config.liveness_config())
+            .await;
+        dispatcher.clone().start_exchanging_peers().await;
+        dispatcher
+            .clone()
+            .start_falling_back_delegated_queries()
+            .await;
         dispatcher
             .clone()
             .check_for_dysfunction_periodically()
             .await;
         dispatcher.clone().start_sending_any_data_batches().await;
+        dispatcher.clone().start_scrubbing_chunks().await;
 
         #[cfg(feature = "back-pressure")]
         dispatcher
@@ -278,13 +524,178 @@ impl NodeApi {
             .await;
 
         dispatcher.clone().start_cleaning_peer_links().await;
+
+        if let Some(port) = config.lan_discovery_port() {
+            dispatcher
+                .clone()
+                .start_answering_lan_discovery(port, config.network_name.clone())
+                .await;
+        }
+
         dispatcher.clone().write_prefixmap_to_disk().await;
 
-        let api = Self { dispatcher };
+        if let Some(config_file) = &config.config_file {
+            dispatcher
+                .clone()
+                .start_watching_config_file(config_file.clone())
+                .await;
+        }
+
+        let joined_as = dispatcher.node.info.read().await.name();
+        let api = Self {
+            dispatcher,
+            reachability,
+            running_marker: None,
+            bootstrap,
+            joined_as,
+            joined_at: Instant::now(),
+        };
 
         Ok((api, event_stream))
     }
 
+    // Bootstraps through the peers found on the local network, if LAN discovery is enabled, then
+    // through the peers cached when the node last joined, if any, as they're likely still
+    // around, along with the contacts read from the file or seed list of the config, falling
+    // back to the hard-coded contacts only if none of them can be reached.
+    // Connections are then also accepted at the additional addresses of the config.
+    async fn bootstrap(
+        config: &Config,
+        root_storage_dir: &Path,
+        connection_event_tx: mpsc::Sender<MsgEvent>,
+    ) -> Result<(Comm, SocketAddr)> {
+        let (comm, bootstrap_addr) =
+            Self::bootstrap_comm(config, root_storage_dir, connection_event_tx).await?;
+        let comm = comm
+            .listen_at(&config.listen_addrs, config.network_config().clone())
+            .await?;
+
+        Ok((comm, bootstrap_addr))
+    }
+
+    async fn bootstrap_comm(
+        config: &Config,
+        root_storage_dir: &Path,
+        connection_event_tx: mpsc::Sender<MsgEvent>,
+    ) -> Result<(Comm, SocketAddr)> {
+        if let Some(port) = config.lan_discovery_port() {
+            let found =
+                match discover_lan_peers(port, config.network_name.clone(), LAN_DISCOVERY_WAIT)
+                    .await
+                {
+                    Ok(peers) => peers,
+                    Err(err) => {
+                        warn!("Failed to look for peers on the local network: {:?}", err);
+                        vec![]
+                    }
+                };
+
+            if !found.is_empty() {
+                debug!(
+                    "Bootstrapping through the {} peers found on the local network: {:?}",
+                    found.len(),
+                    found
+                );
+                match Comm::bootstrap(
+                    config.local_addr(),
+                    &found,
+                    config.network_config().clone(),
+                    config.network_name(),
+                    connection_event_tx.clone(),
+                )
+                .await
+                {
+                    Ok(bootstrapped) => return Ok(bootstrapped),
+                    Err(err) => warn!(
+                        "Failed to bootstrap through the peers found on the local network, trying the cached peers: {:?}",
+                        err
+                    ),
+                }
+            }
+        }
+
+        let cached = match get_bootstrap_cache(root_storage_dir).await {
+            Ok(peers) => peers,
+            Err(err) => {
+                warn!("Ignoring the bootstrap cache: {:?}", err);
+                vec![]
+            }
+        };
+
+        let mut contacts = config
+            .ip_preference
+            .sort_contacts(cached.iter().map(|peer| peer.addr()));
+        if let Some(location) = &config.bootstrap_contacts {
+            match read_bootstrap_contacts(location).await {
+                Ok(read) => {
+                    let read = config.ip_preference.sort_contacts(read);
+                    for contact in read {
+                        if !contacts.contains(&contact) {
+                            contacts.push(contact);
+                        }
+                    }
+                }
+                Err(err) => warn!(
+                    "Ignoring the bootstrap contacts read from {}: {:?}",
+                    location, err
+                ),
+            }
+        }
+
+        if !contacts.is_empty() {
+            debug!(
+                "Bootstrapping through the {} cached peers and contacts read: {:?}",
+                contacts.len(),
+                contacts
+            );
+            match Comm::bootstrap(
+                config.local_addr(),
+                &contacts,
+                config.network_config().clone(),
+                config.network_name(),
+                connection_event_tx.clone(),
+            )
+            .await
+            {
+                Ok(bootstrapped) => return Ok(bootstrapped),
+                Err(err) => warn!(
+                    "Failed to bootstrap through the cached peers and contacts read, trying the hard-coded contacts: {:?}",
+                    err
+                ),
+            }
+        }
+
+        Comm::bootstrap(
+            config.local_addr(),
+            config.sorted_contacts().as_slice(),
+            config.network_config().clone(),
+            config.network_name(),
+            connection_event_tx,
+        )
+        .await
+    }
+
+    /// Shuts the node down: its long-running tasks, e.g. the handling of incoming msgs and the
+    /// probing of the network, are signalled to stop, and its connections are closed once they
+    /// did. The handle returned completes once the node is torn down, for callers to wait for it.
+    ///
+    /// The node is left unable to carry out its duties, but it's only marked as shut down
+    /// cleanly once dropped.
+    pub fn shutdown(&self) -> JoinHandle<()> {
+        let tasks = self.dispatcher.shutdown();
+        let dispatcher = self.dispatcher.clone();
+        tokio::spawn(async move {
+            for task in tasks {
+                if let Err(error) = task.await {
+                    warn!("Task failed while the node was shutting down: {:?}", error);
+                }
+            }
+            dispatcher.node.comm.close();
+            dispatcher.node.try_send_event(Event::Terminated);
+            info!("Node shut down");
+        })
+    }
+
     /// Returns the current age of this node.
     pub async fn age(&self) -> u8 {
         self.dispatcher.node.info.read().await.age()
@@ -305,6 +716,13 @@ impl NodeApi {
         self.dispatcher.node.our_connection_info()
     }
 
+    /// Returns the addresses this node accepts connections at, its connection info first, then
+    /// the ones of `--listen-addr`, e.g. to advertise them. The ports bound are reported, also
+    /// where port 0 was asked for, while unspecified addresses such as `0.0.0.0` are left out.
+    pub fn listening_addrs(&self) -> Vec<SocketAddr> {
+        self.dispatcher.node.listening_addrs()
+    }
+
     /// Returns the Section Signed Chain
     pub async fn section_chain(&self) -> SecuredLinkedList {
         self.dispatcher.node.section_chain().await
@@ -320,11 +738,203 @@ impl NodeApi {
         self.dispatcher.node.network_knowledge().prefix().await
     }
 
+    /// Returns an attestation of the identity of this node, signed with its key, which the
+    /// operator can publish to prove they run it, or `None` if the node isn't a member of its
+    /// section as far as it knows, e.g. while being relocated.
+    pub async fn identity_attestation(&self) -> Option<NodeAttestation> {
+        let network_knowledge = self.dispatcher.node.network_knowledge();
+        let keypair = self.dispatcher.node.info.read().await.keypair.clone();
+        let node_state = network_knowledge
+            .get_section_signed_member(&ed25519::name(&keypair.public))
+            .await?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        Some(NodeAttestation::new(
+            &keypair,
+            network_knowledge.prefix().await,
+            network_knowledge.section_key().await,
+            network_knowledge.chain_len().await,
+            timestamp,
+            node_state,
+        ))
+    }
+
+    /// Returns a snapshot of what this node knows of the network, i.e. the sections it learnt
+    /// about and its connections to its peers, e.g. to attach to a bug report.
+    pub async fn network_snapshot(&self) -> NetworkSnapshot {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let connections = self.dispatcher.node.comm.peer_connections().await;
+
+        NetworkSnapshot::new(
+            self.dispatcher.node.network_knowledge().prefix_map(),
+            connections,
+            timestamp,
+        )
+    }
+
+    /// Returns the peers this node has sessions with, with their endpoints, whether they're
+    /// connected, and how close they are to this node, e.g. to debug its connectivity.
+    pub async fn routing_table(&self) -> RoutingTable {
+        let node = &self.dispatcher.node;
+        let (our_name, our_addr) = {
+            let info = node.info.read().await;
+            (info.name(), info.addr)
+        };
+        let elders: BTreeSet<_> = node
+            .network_knowledge()
+            .elders()
+            .await
+            .iter()
+            .map(Peer::name)
+            .collect();
+        RoutingTable::new(
+            our_name,
+            our_addr,
+            node.comm.peer_connections().await,
+            |name| elders.contains(name),
+        )
+    }
+
+    /// Returns the status of the node, e.g. for monitoring tools to poll.
+    pub async fn status(&self) -> NodeStatus {
+        let node = &self.dispatcher.node;
+        let (name, age) = {
+            let info = node.info.read().await;
+            (info.name(), info.age())
+        };
+        let connected_peers = node
+            .comm
+            .peer_connections()
+            .await
+            .iter()
+            .filter(|(_, connected)| *connected)
+            .count();
+
+        NodeStatus {
+            name,
+            age,
+            prefix: node.network_knowledge().prefix().await,
+            is_elder: node.is_elder().await,
+            bootstrap: self.bootstrap,
+            relocated: name != self.joined_as,
+            connected_peers,
+            cmds_in_flight: self.dispatcher.cmds_in_flight(),
+            rate_limited_msgs: node.comm.rate_limited_msgs_dropped(),
+            duplicate_msgs: node.comm.duplicate_msgs_dropped(),
+            blacklisted_peers: node.comm.blacklisted_peers().await.len(),
+            uptime_secs: self.joined_at.elapsed().as_secs(),
+        }
+    }
+
     /// Returns whether the node is Elder.
     pub async fn is_elder(&self) -> bool {
         self.dispatcher.node.is_elder().await
     }
 
+    /// Returns the progress of the integrity scrubbing of the chunks held by this node.
+    pub async fn chunk_scrub_progress(&self) -> ScrubProgress {
+        self.dispatcher.node.data_storage.scrub_progress().await
+    }
+
+    /// Returns the hit-rate metrics of the cache of query responses held by this node as an Elder.
+    pub async fn query_cache_stats(&self) -> QueryCacheStats {
+        self.dispatcher.node.query_cache.stats().await
+    }
+
+    /// Returns the metrics of the workers signing the msgs sent by this node.
+    pub fn signing_stats(&self) -> SigningStats {
+        self.dispatcher.node.signing_pool.stats()
+    }
+
+    /// Returns how each of the Adults took part in answering the data queries delegated to them
+    /// by this node as an Elder.
+    pub async fn adult_participation(&self) -> BTreeMap<XorName, AdultParticipation> {
+        self.dispatcher.node.query_delegation.participation().await
+    }
+
+    /// Reject the join requests of the peers at the given IP from now on, when this node is an Elder.
+    pub async fn deny_joins_from(&self, ip: IpAddr) {
+        let _ = self.dispatcher.node.join_denylist.write().await.insert(ip);
+    }
+
+    /// Stop rejecting the join requests of the peers at the given IP,
+    /// returning whether they were rejected until now.
+    pub async fn allow_joins_from(&self, ip: IpAddr) -> bool {
+        self.dispatcher.node.join_denylist.write().await.remove(&ip)
+    }
+
+    /// Propose to the other Elders of our section to store the given number of copies of each
+    /// data from now on. It's applied with the next SAP of the section, once they agree on it.
+    pub async fn propose_data_copy_count(&self, count: usize) -> Result<()> {
+        if count == 0 {
+            return Err(Error::InvalidDataCopyCount(count));
+        }
+
+        let cmds = self
+            .dispatcher
+            .node
+            .propose(Proposal::DataCopyCount(count))
+            .await?;
+        for cmd in cmds {
+            self.dispatcher
+                .clone()
+                .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of copies of each data our section stores.
+    pub async fn data_copy_count(&self) -> usize {
+        self.dispatcher
+            .node
+            .network_knowledge()
+            .authority_provider()
+            .await
+            .data_copy_count()
+    }
+
+    /// Returns the number of msgs received which were sent within another network, and dropped.
+    pub fn other_network_msgs_dropped(&self) -> u64 {
+        self.dispatcher.node.comm.other_network_msgs_dropped()
+    }
+
+    /// Returns the number of msgs received beyond the limits on the rate of msgs of their
+    /// endpoint, and dropped.
+    pub fn rate_limited_msgs_dropped(&self) -> u64 {
+        self.dispatcher.node.comm.rate_limited_msgs_dropped()
+    }
+
+    /// Returns the number of msgs received again, e.g. relayed by several peers, and dropped.
+    pub fn duplicate_msgs_dropped(&self) -> u64 {
+        self.dispatcher.node.comm.duplicate_msgs_dropped()
+    }
+
+    /// Returns the endpoints blacklisted for violating the protocol, e.g. sending mis-signed
+    /// msgs, with the number of violations of each.
+    pub async fn blacklisted_peers(&self) -> BTreeMap<SocketAddr, u32> {
+        self.dispatcher.node.comm.blacklisted_peers().await
+    }
+
+    /// Clears the given endpoint from the blacklist, or all of them if none is given, for
+    /// their connections to be accepted again. Returns whether any endpoint was blacklisted.
+    pub async fn clear_blacklist(&self, endpoint: Option<SocketAddr>) -> bool {
+        self.dispatcher.node.comm.clear_blacklist(endpoint).await
+    }
+
+    /// Returns whether the peer this node bootstrapped to could dial it back, before it joined.
+    /// This is `None` for the genesis node, or if the peer didn't answer.
+    pub fn reachability(&self) -> Option<Reachability> {
+        self.reachability
+    }
+
     /// Returns the information of all the current section elders.
     pub async fn our_elders(&self) -> Vec<Peer> {
         self.dispatcher.node.network_knowledge().elders().await
@@ -347,12 +957,16 @@ impl NodeApi {
         dst: DstLocation,
     ) -> Result<WireMsg> {
         let src_section_pk = *self.section_chain().await.last_key();
-        WireMsg::single_src(
-            &self.dispatcher.node.info.read().await.clone(),
-            dst,
-            node_msg,
-            src_section_pk,
-        )
+        self.dispatcher
+            .node
+            .signing_pool
+            .single_src(
+                &self.dispatcher.node.info.read().await.clone(),
+                dst,
+                node_msg,
+                src_section_pk,
+            )
+            .await
     }
 
     /// Send a message.
@@ -382,11 +996,41 @@ impl NodeApi {
 }
 
 // Listen for incoming connection events and handle them.
+// The receiver is shared so listening can be restarted if it panicked.
+// Msgs received are queued by priority, e.g. for churn to be dealt with before data is stored
+// when the node is loaded.
 async fn handle_connection_events(
     dispatcher: Arc<Dispatcher>,
-    mut incoming_conns: mpsc::Receiver<MsgEvent>,
+    incoming_conns: Arc<Mutex<mpsc::Receiver<MsgEvent>>>,
 ) {
-    while let Some(event) = incoming_conns.recv().await {
+    let mut incoming_conns = incoming_conns.lock().await;
+    let processing_permits = Arc::new(Semaphore::new(MAX_MSGS_PROCESSED_AT_ONCE));
+    let mut queue = MsgQueue::default();
+    let mut closed = false;
+
+    loop {
+        if closed && queue.is_empty() {
+            break;
+        }
+
+        let (event, permit) = tokio::select! {
+            biased;
+            permit = processing_permits.clone().acquire_owned(), if !queue.is_empty() => {
+                match (permit, queue.pop()) {
+                    (Ok(permit), Some(event)) => (event, permit),
+                    // the semaphore is never closed, nor the queue empty here
+                    _ => continue,
+                }
+            }
+            event = incoming_conns.recv(), if !closed && queue.len() < MAX_QUEUED_MSGS => {
+                match event {
+                    Some(event) => queue.push(event),
+                    None => closed = true,
+                }
+                continue;
+            }
+        };
+
         match event {
             MsgEvent::Received {
                 sender,
@@ -419,7 +1063,7 @@ async fn handle_connection_events(
 
                 let _handle = dispatcher
                     .clone()
-                    .enqueue_and_handle_next_cmd_and_offshoots(cmd, None)
+                    .handle_cmd_and_offshoots_holding(cmd, None, None, Some(permit))
                     .await;
             }
         }
@@ -0,0 +1,191 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::core::MsgEvent;
+
+use sn_interface::messaging::{AuthKind, WireMsg};
+
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+// Priority of the msgs of clients, which are queued after any msg of nodes. They aren't
+// deserialised to be classified, as they carry the bulk of the data being stored.
+const CLIENT_MSG_PRIORITY: i32 = i32::MIN;
+
+/// Msgs received from the network and not yet handled, handed out most urgent first, e.g. DKG,
+/// AE and join msgs before the data msgs of nodes, and these before the msgs of clients.
+/// Msgs of the same priority are handed out in the order they were received.
+#[derive(Default)]
+pub(super) struct MsgQueue {
+    msgs: BinaryHeap<QueuedMsg>,
+    // number of the msgs ever queued, for the msgs of the same priority to be kept in order
+    queued: u64,
+}
+
+impl MsgQueue {
+    /// Queue a msg received, classifying it by priority.
+    pub(super) fn push(&mut self, event: MsgEvent) {
+        let priority = match &event {
+            MsgEvent::Received { wire_msg, .. } => msg_priority(wire_msg),
+        };
+        self.msgs.push(QueuedMsg {
+            priority,
+            seq: self.queued,
+            event,
+        });
+        self.queued += 1;
+    }
+
+    /// Take the most urgent msg queued, if any.
+    pub(super) fn pop(&mut self) -> Option<MsgEvent> {
+        self.msgs.pop().map(|msg| msg.event)
+    }
+
+    /// Number of the msgs queued.
+    pub(super) fn len(&self) -> usize {
+        self.msgs.len()
+    }
+
+    /// Whether there are no msgs queued.
+    pub(super) fn is_empty(&self) -> bool {
+        self.msgs.is_empty()
+    }
+}
+
+// Priority of a msg, higher being more urgent. Msgs which fail to deserialise come last, and
+// will be dropped once handled.
+fn msg_priority(wire_msg: &WireMsg) -> i32 {
+    match wire_msg.msg_kind() {
+        #[cfg(any(feature = "chunks", feature = "registers"))]
+        AuthKind::Service(_) => CLIENT_MSG_PRIORITY,
+        _ => wire_msg
+            .into_msg()
+            .map(|msg| msg.priority())
+            .unwrap_or(CLIENT_MSG_PRIORITY),
+    }
+}
+
+struct QueuedMsg {
+    priority: i32,
+    seq: u64,
+    event: MsgEvent,
+}
+
+impl PartialEq for QueuedMsg {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for QueuedMsg {}
+
+impl PartialOrd for QueuedMsg {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueuedMsg {
+    // Higher priority first, then the earliest received
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MsgQueue;
+    use crate::node::{core::MsgEvent, messages::WireMsgUtils};
+
+    use sn_interface::{
+        messaging::{
+            data::{DataQuery, ServiceMsg},
+            system::SystemMsg,
+            AuthKind, DstLocation, MsgId, ServiceAuth, WireMsg,
+        },
+        network_knowledge::{test_utils::gen_addr, NodeInfo, MIN_ADULT_AGE},
+        types::{keys::ed25519, ChunkAddress, Keypair, Peer},
+    };
+
+    use bytes::Bytes;
+    use eyre::Result;
+    use xor_name::Prefix;
+
+    #[test]
+    fn msgs_are_handed_out_by_priority_then_in_order() -> Result<()> {
+        let mut queue = MsgQueue::default();
+
+        let client_query = client_msg()?;
+        let join_msg = node_msg(SystemMsg::StartConnectivityTest(xor_name::rand::random()))?;
+        let first_ae_msg = node_msg(SystemMsg::AntiEntropyProbe)?;
+        let second_ae_msg = node_msg(SystemMsg::AntiEntropyProbe)?;
+
+        for wire_msg in [&client_query, &join_msg, &first_ae_msg, &second_ae_msg] {
+            queue.push(received(wire_msg.clone()));
+        }
+        assert_eq!(queue.len(), 4);
+
+        let handed_out: Vec<MsgId> = std::iter::from_fn(|| queue.pop())
+            .map(|MsgEvent::Received { wire_msg, .. }| wire_msg.msg_id())
+            .collect();
+        assert_eq!(
+            handed_out,
+            vec![
+                first_ae_msg.msg_id(),
+                second_ae_msg.msg_id(),
+                join_msg.msg_id(),
+                client_query.msg_id(),
+            ]
+        );
+        assert!(queue.is_empty());
+
+        Ok(())
+    }
+
+    fn received(wire_msg: WireMsg) -> MsgEvent {
+        MsgEvent::Received {
+            sender: Peer::new(xor_name::rand::random(), gen_addr()),
+            wire_msg,
+            original_bytes: Bytes::new(),
+        }
+    }
+
+    fn node_msg(msg: SystemMsg) -> Result<WireMsg> {
+        let node = NodeInfo::new(
+            ed25519::gen_keypair(&Prefix::default().range_inclusive(), MIN_ADULT_AGE),
+            gen_addr(),
+        );
+        let section_pk = bls::SecretKey::random().public_key();
+        let dst = DstLocation::node(xor_name::rand::random(), section_pk);
+
+        Ok(WireMsg::single_src(&node, dst, msg, section_pk)?)
+    }
+
+    fn client_msg() -> Result<WireMsg> {
+        let keypair = Keypair::new_ed25519();
+        let payload = WireMsg::serialize_msg_payload(&ServiceMsg::Query(DataQuery::GetChunk(
+            ChunkAddress(xor_name::rand::random()),
+        )))?;
+        let auth = ServiceAuth {
+            public_key: keypair.public_key(),
+            signature: keypair.sign(&payload),
+        };
+        let dst = DstLocation::node(
+            xor_name::rand::random(),
+            bls::SecretKey::random().public_key(),
+        );
+
+        Ok(WireMsg::new_msg(
+            MsgId::new(),
+            payload,
+            AuthKind::Service(auth),
+            dst,
+        )?)
+    }
+}
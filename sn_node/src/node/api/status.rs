@@ -0,0 +1,52 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use serde::Serialize;
+use xor_name::{Prefix, XorName};
+
+/// How the node became part of the network.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BootstrapState {
+    /// The node started the network, as its genesis node
+    Genesis,
+    /// The node joined the network as a new node
+    Joined,
+    /// The node joined the network with the keypair it had before restarting
+    Rejoined,
+}
+
+/// Status of a running node, for monitoring tools to poll.
+#[derive(Clone, Debug, Serialize)]
+pub struct NodeStatus {
+    /// Current name of the node
+    pub name: XorName,
+    /// Current age of the node
+    pub age: u8,
+    /// Prefix of the section of the node
+    pub prefix: Prefix,
+    /// Whether the node is an Elder of its section
+    pub is_elder: bool,
+    /// How the node became part of the network
+    pub bootstrap: BootstrapState,
+    /// Whether the node was relocated since it joined, getting a new name
+    pub relocated: bool,
+    /// Number of the peers the node is connected to
+    pub connected_peers: usize,
+    /// Number of the cmds being handled by the node, e.g. for the msgs it received
+    pub cmds_in_flight: usize,
+    /// Number of the msgs dropped for being sent beyond the limits on the rate of msgs of their
+    /// endpoint
+    pub rate_limited_msgs: u64,
+    /// Number of the msgs received again, e.g. relayed by several peers, and dropped
+    pub duplicate_msgs: u64,
+    /// Number of the endpoints blacklisted for violating the protocol
+    pub blacklisted_peers: usize,
+    /// How long since the node joined the network, in seconds
+    pub uptime_secs: u64,
+}
@@ -8,34 +8,42 @@
 
 #![allow(dead_code, unused_imports)]
 
-use super::{Cmd, Comm, Dispatcher};
+use super::{Cmd, Comm, Dispatcher, NodeApi};
 
 use crate::dbs::UsedSpace;
 use crate::node::{
+    cfg::bootstrap_cache::get_bootstrap_cache,
     core::{
-        relocation_check, ChurnId, MsgEvent, Node, Proposal, RESOURCE_PROOF_DATA_SIZE,
-        RESOURCE_PROOF_DIFFICULTY,
+        LivenessConfig, MsgEvent, Node, Proposal, MAX_RELOCATION_ATTEMPTS,
+        RESOURCE_PROOF_DATA_SIZE, RESOURCE_PROOF_DIFFICULTY,
     },
     create_test_max_capacity_and_root_storage,
     messages::WireMsgUtils,
-    Error, Event, Result as RoutingResult,
+    node_age::{is_relocation_candidate, ChurnId},
+    ClientRateLimits, Config, Error, Event, QueryDelegationConfig, Result as RoutingResult,
 };
+use crate::ChunkStoreConfig;
 use sn_interface::messaging::{
+    data::{
+        CmdAckLevel, CmdError, DataCmd, Error as ErrorMsg, ReceiptDurability, ServiceMsg,
+        FAST_ACK_MAX_CHUNK_SIZE,
+    },
     system::{
-        JoinAsRelocatedRequest, JoinRequest, JoinResponse, KeyedSig, MembershipState,
-        NodeMsgAuthorityUtils, NodeState as NodeStateMsg, RelocateDetails, ResourceProofResponse,
-        SectionAuth, SystemMsg,
+        AddrAttestationFailure, Capabilities, JoinAsRelocatedRequest, JoinRejectionReason,
+        JoinRequest, JoinResponse, KeyedSig, MembershipState, NodeMsgAuthorityUtils,
+        NodeState as NodeStateMsg, Reachability, ReachabilityAttestation, RelocateDetails,
+        ResourceProofResponse, SectionAuth, SystemMsg, PROTOCOL_VERSION,
     },
     AuthKind, AuthorityProof, DstLocation, MsgId, MsgType, NodeAuth,
-    SectionAuth as MsgKindSectionAuth, WireMsg,
+    SectionAuth as MsgKindSectionAuth, ServiceAuth, WireMsg,
 };
 use sn_interface::network_knowledge::{
     recommended_section_size, supermajority, test_utils::*, NetworkKnowledge, NodeInfo, NodeState,
     SectionAuthorityProvider, SectionKeyShare, FIRST_SECTION_MAX_AGE, FIRST_SECTION_MIN_AGE,
     MIN_ADULT_AGE,
 };
-use sn_interface::types::{keyed_signed, SecretKeySet};
-use sn_interface::{elder_count, init_logger};
+use sn_interface::types::{keyed_signed, utils::random_bytes, Chunk, ReplicatedData, SecretKeySet};
+use sn_interface::{data_copy_count, elder_count, init_logger};
 
 use sn_interface::types::{keys::ed25519, Keypair, Peer, PublicKey};
 
@@ -53,6 +61,7 @@ use std::{
     net::Ipv4Addr,
     ops::Deref,
     path::Path,
+    sync::Arc,
 };
 use tempfile::tempdir;
 use tokio::{
@@ -82,6 +91,9 @@ async fn receive_join_request_without_resource_proof_response() -> Result<()> {
         mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
     )
     .await?;
     let dispatcher = Dispatcher::new(node);
@@ -94,13 +106,14 @@ async fn receive_join_request_without_resource_proof_response() -> Result<()> {
 
     let wire_msg = WireMsg::single_src(
         &new_node,
-        DstLocation::Section {
-            name: XorName::from(PublicKey::Bls(section_key)),
-            section_pk: section_key,
-        },
+        DstLocation::section(XorName::from(PublicKey::Bls(section_key)), section_key),
         SystemMsg::JoinRequest(Box::new(JoinRequest {
             section_key,
             resource_proof_response: None,
+            genesis_key: section_key,
+            version: None,
+            protocol_version: PROTOCOL_VERSION,
+            capabilities: Capabilities::ours(),
         })),
         section_key,
     )?;
@@ -113,6 +126,7 @@ async fn receive_join_request_without_resource_proof_response() -> Result<()> {
                 original_bytes: None,
             },
             "cmd-id",
+            None,
         )
         .await?
         .into_iter();
@@ -143,6 +157,229 @@ async fn receive_join_request_without_resource_proof_response() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn receive_join_request_from_other_network() -> Result<()> {
+    let prefix1 = Prefix::default().pushed(true);
+    let (section_auth, mut nodes, sk_set) = gen_section_authority_provider(prefix1, elder_count());
+    let section_key = sk_set.public_keys().public_key();
+
+    let (section, section_key_share) = create_section(&sk_set, &section_auth).await?;
+    let node = nodes.remove(0);
+    let (max_capacity, root_storage_dir) = create_test_max_capacity_and_root_storage()?;
+    let node = Node::new(
+        create_comm().await?,
+        node,
+        section,
+        Some(section_key_share),
+        mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
+        UsedSpace::new(max_capacity),
+        root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
+    )
+    .await?;
+    let dispatcher = Dispatcher::new(node);
+
+    let new_node = NodeInfo::new(
+        ed25519::gen_keypair(&prefix1.range_inclusive(), MIN_ADULT_AGE),
+        gen_addr(),
+    );
+    let other_genesis_key = bls::SecretKey::random().public_key();
+
+    let wire_msg = WireMsg::single_src(
+        &new_node,
+        DstLocation::section(XorName::from(PublicKey::Bls(section_key)), section_key),
+        SystemMsg::JoinRequest(Box::new(JoinRequest {
+            section_key,
+            resource_proof_response: None,
+            genesis_key: other_genesis_key,
+            version: None,
+            protocol_version: PROTOCOL_VERSION,
+            capabilities: Capabilities::ours(),
+        })),
+        section_key,
+    )?;
+
+    let cmds = dispatcher
+        .process_cmd(
+            Cmd::HandleMsg {
+                sender: new_node.peer(),
+                wire_msg,
+                original_bytes: None,
+            },
+            "cmd-id",
+            None,
+        )
+        .await?;
+
+    let response_wire_msg = cmds
+        .into_iter()
+        .find_map(|cmd| match cmd {
+            Cmd::SendMsg { wire_msg, .. } => Some(wire_msg),
+            _ => None,
+        })
+        .ok_or_else(|| eyre!("JoinResponse was not sent"))?;
+
+    assert_matches!(
+        response_wire_msg.into_msg(),
+        Ok(MsgType::System {
+            msg: SystemMsg::JoinResponse(response),
+            ..
+        }) => assert_matches!(
+            *response,
+            JoinResponse::Rejected(JoinRejectionReason::WrongNetwork(key)) if key == section_key
+        )
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn receive_join_request_of_incompatible_protocol() -> Result<()> {
+    let prefix1 = Prefix::default().pushed(true);
+    let (section_auth, mut nodes, sk_set) = gen_section_authority_provider(prefix1, elder_count());
+    let section_key = sk_set.public_keys().public_key();
+
+    let (section, section_key_share) = create_section(&sk_set, &section_auth).await?;
+    let node = nodes.remove(0);
+    let (max_capacity, root_storage_dir) = create_test_max_capacity_and_root_storage()?;
+    let node = Node::new(
+        create_comm().await?,
+        node,
+        section,
+        Some(section_key_share),
+        mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
+        UsedSpace::new(max_capacity),
+        root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
+    )
+    .await?;
+    let dispatcher = Dispatcher::new(node);
+
+    let new_node = NodeInfo::new(
+        ed25519::gen_keypair(&prefix1.range_inclusive(), MIN_ADULT_AGE),
+        gen_addr(),
+    );
+
+    // a node predating protocol versions and capabilities
+    let wire_msg = WireMsg::single_src(
+        &new_node,
+        DstLocation::section(XorName::from(PublicKey::Bls(section_key)), section_key),
+        SystemMsg::JoinRequest(Box::new(JoinRequest {
+            section_key,
+            resource_proof_response: None,
+            genesis_key: section_key,
+            version: None,
+            protocol_version: 0,
+            capabilities: Capabilities::empty(),
+        })),
+        section_key,
+    )?;
+
+    let cmds = dispatcher
+        .process_cmd(
+            Cmd::HandleMsg {
+                sender: new_node.peer(),
+                wire_msg,
+                original_bytes: None,
+            },
+            "cmd-id",
+            None,
+        )
+        .await?;
+
+    let response_wire_msg = cmds
+        .into_iter()
+        .find_map(|cmd| match cmd {
+            Cmd::SendMsg { wire_msg, .. } => Some(wire_msg),
+            _ => None,
+        })
+        .ok_or_else(|| eyre!("JoinResponse was not sent"))?;
+
+    assert_matches!(
+        response_wire_msg.into_msg(),
+        Ok(MsgType::System {
+            msg: SystemMsg::JoinResponse(response),
+            ..
+        }) => assert_matches!(
+            *response,
+            JoinResponse::Rejected(JoinRejectionReason::IncompatibleProtocol { found: 0, missing, .. })
+                if missing == Capabilities::REQUIRED
+        )
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn receive_reachability_check() -> Result<()> {
+    let prefix1 = Prefix::default().pushed(true);
+    let (section_auth, mut nodes, sk_set) = gen_section_authority_provider(prefix1, elder_count());
+    let section_key = sk_set.public_keys().public_key();
+
+    let (section, section_key_share) = create_section(&sk_set, &section_auth).await?;
+    let node = nodes.remove(0);
+    let (max_capacity, root_storage_dir) = create_test_max_capacity_and_root_storage()?;
+    let node = Node::new(
+        create_comm().await?,
+        node,
+        section,
+        Some(section_key_share),
+        mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
+        UsedSpace::new(max_capacity),
+        root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
+    )
+    .await?;
+    let dispatcher = Dispatcher::new(node);
+
+    let new_node_comm = create_comm().await?;
+    let new_node = NodeInfo::new(
+        ed25519::gen_keypair(&prefix1.range_inclusive(), MIN_ADULT_AGE),
+        new_node_comm.our_connection_info(),
+    );
+
+    let wire_msg = WireMsg::single_src(
+        &new_node,
+        DstLocation::section(new_node.name(), section_key),
+        SystemMsg::ReachabilityCheck,
+        section_key,
+    )?;
+
+    let response_wire_msg = dispatcher
+        .process_cmd(
+            Cmd::HandleMsg {
+                sender: new_node.peer(),
+                wire_msg,
+                original_bytes: None,
+            },
+            "cmd-id",
+            None,
+        )
+        .await?
+        .into_iter()
+        .find_map(|cmd| match cmd {
+            Cmd::SendMsg { wire_msg, .. } => Some(wire_msg),
+            _ => None,
+        })
+        .ok_or_else(|| eyre!("no response sent"))?;
+
+    assert_matches!(
+        response_wire_msg.into_msg(),
+        Ok(MsgType::System {
+            msg: SystemMsg::ReachabilityCheckResponse(reachability),
+            ..
+        }) => assert_eq!(reachability, Reachability::Reachable(new_node.addr))
+    );
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn membership_churn_starts_on_join_request_with_resource_proof() -> Result<()> {
     let prefix1 = Prefix::default().pushed(true);
@@ -162,6 +399,9 @@ async fn membership_churn_starts_on_join_request_with_resource_proof() -> Result
         mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
     )
     .await?;
     let dispatcher = Dispatcher::new(node);
@@ -179,33 +419,91 @@ async fn membership_churn_starts_on_join_request_with_resource_proof() -> Result
     let data = rp.create_proof_data(&nonce);
     let mut prover = rp.create_prover(data.clone());
     let solution = prover.solve();
-    let resource_proof_response = ResourceProofResponse {
+    let mut resource_proof_response = ResourceProofResponse {
         solution,
         data,
         nonce,
         nonce_signature,
+        reachability_attestation: None,
     };
-    let wire_msg = WireMsg::single_src(
-        &new_node,
-        DstLocation::Section {
-            name: XorName::from(PublicKey::Bls(section_key)),
-            section_pk: section_key,
-        },
-        SystemMsg::JoinRequest(Box::new(JoinRequest {
+    let join_request_msg = |resource_proof_response: &ResourceProofResponse| {
+        WireMsg::single_src(
+            &new_node,
+            DstLocation::section(XorName::from(PublicKey::Bls(section_key)), section_key),
+            SystemMsg::JoinRequest(Box::new(JoinRequest {
+                section_key,
+                resource_proof_response: Some(resource_proof_response.clone()),
+                genesis_key: section_key,
+                version: None,
+                protocol_version: PROTOCOL_VERSION,
+                capabilities: Capabilities::ours(),
+            })),
             section_key,
-            resource_proof_response: Some(resource_proof_response.clone()),
-        })),
-        section_key,
-    )?;
+        )
+    };
+
+    // without attesting its address, the node isn't proposed as a member
+    let cmds = dispatcher
+        .process_cmd(
+            Cmd::HandleMsg {
+                sender: new_node.peer(),
+                wire_msg: join_request_msg(&resource_proof_response)?,
+                original_bytes: None,
+            },
+            "cmd-id",
+            None,
+        )
+        .await?;
+    let response_wire_msg = cmds
+        .into_iter()
+        .find_map(|cmd| match cmd {
+            Cmd::SendMsg { wire_msg, .. } => Some(wire_msg),
+            _ => None,
+        })
+        .ok_or_else(|| eyre!("JoinResponse was not sent"))?;
+    assert_matches!(
+        response_wire_msg.into_msg(),
+        Ok(MsgType::System {
+            msg: SystemMsg::JoinResponse(response),
+            ..
+        }) => assert_matches!(
+            *response,
+            JoinResponse::Rejected(JoinRejectionReason::AddrNotAttested {
+                failure: AddrAttestationFailure::Missing,
+                ..
+            })
+        )
+    );
+    assert!(!dispatcher
+        .node
+        .membership
+        .read()
+        .await
+        .as_ref()
+        .unwrap()
+        .is_churn_in_progress());
+
+    // the challenge the node was dialled back with at its address
+    let addr_nonce: [u8; 32] = rand::random();
+    let serialized = bincode::serialize(&(new_node.name(), new_node.addr, addr_nonce))?;
+    let addr_nonce_signature =
+        ed25519::sign(&serialized, &dispatcher.node.info.read().await.keypair);
+    resource_proof_response.reachability_attestation = Some(ReachabilityAttestation::new(
+        &new_node.keypair,
+        new_node.addr,
+        addr_nonce,
+        addr_nonce_signature,
+    ));
 
     let _ = dispatcher
         .process_cmd(
             Cmd::HandleMsg {
                 sender: new_node.peer(),
-                wire_msg,
+                wire_msg: join_request_msg(&resource_proof_response)?,
                 original_bytes: None,
             },
             "cmd-id",
+            None,
         )
         .await?;
 
@@ -252,6 +550,9 @@ async fn membership_churn_starts_on_join_request_from_relocated_node() -> Result
         mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
     )
     .await?;
     let dispatcher = Dispatcher::new(node);
@@ -280,10 +581,7 @@ async fn membership_churn_starts_on_join_request_from_relocated_node() -> Result
 
     let wire_msg = WireMsg::single_src(
         &relocated_node,
-        DstLocation::Section {
-            name: XorName::from(PublicKey::Bls(section_key)),
-            section_pk: section_key,
-        },
+        DstLocation::section(XorName::from(PublicKey::Bls(section_key)), section_key),
         SystemMsg::JoinAsRelocatedRequest(Box::new(JoinAsRelocatedRequest {
             section_key,
             relocate_proof,
@@ -300,6 +598,7 @@ async fn membership_churn_starts_on_join_request_from_relocated_node() -> Result
                 original_bytes: None,
             },
             "cmd-id",
+            None,
         )
         .await?;
 
@@ -333,6 +632,9 @@ async fn handle_agreement_on_online() -> Result<()> {
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
     )
     .await?;
     let dispatcher = Dispatcher::new(node);
@@ -350,6 +652,86 @@ async fn handle_agreement_on_online() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn genesis_node_raises_joined_then_terminated_once_shut_down() -> Result<()> {
+    let root_dir = tempdir()?;
+    let mut config = Config::default();
+    config.first = true;
+    config.local_addr = Some((Ipv4Addr::LOCALHOST, 0).into());
+    config.set_root_dir(root_dir.path());
+
+    let (node, mut events) = NodeApi::new(&config, Duration::from_secs(30)).await?;
+    let name = node.name().await;
+    assert_matches!(events.next().await, Some(Event::Joined { name: joined, prefix, age }) => {
+        assert_eq!(joined, name);
+        assert_eq!(prefix, Prefix::default());
+        assert_eq!(age, 255);
+    });
+
+    node.shutdown().await?;
+
+    // the events raised in between, e.g. on the node being promoted, come first
+    loop {
+        match timeout(Duration::from_secs(10), events.next()).await? {
+            Some(Event::Terminated) => break,
+            Some(_) => continue,
+            None => bail!("The events were dropped without Terminated being raised"),
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn elder_raises_peer_lost_for_the_members_not_heard_from() -> Result<()> {
+    let (event_tx, mut event_rx) = mpsc::channel(TEST_EVENT_CHANNEL_SIZE);
+
+    let prefix = Prefix::default();
+    let (section_auth, mut nodes, sk_set) = gen_section_authority_provider(prefix, elder_count());
+    let (section, section_key_share) = create_section(&sk_set, &section_auth).await?;
+    let node = nodes.remove(0);
+    let (max_capacity, root_storage_dir) = create_test_max_capacity_and_root_storage()?;
+    let node = Node::new(
+        create_comm().await?,
+        node,
+        section,
+        Some(section_key_share),
+        event_tx,
+        UsedSpace::new(max_capacity),
+        root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
+    )
+    .await?;
+    let dispatcher = Arc::new(Dispatcher::new(node));
+
+    dispatcher
+        .clone()
+        .start_checking_peer_liveness(LivenessConfig {
+            ping_interval: Duration::from_millis(50),
+            suspect_after: Duration::from_millis(100),
+            dead_after: Duration::from_millis(200),
+        })
+        .await;
+
+    // none of the other members of the section is ever heard from
+    let others: BTreeSet<XorName> = nodes.iter().map(NodeInfo::name).collect();
+    let mut lost = BTreeSet::new();
+    while lost != others {
+        match timeout(Duration::from_secs(10), event_rx.recv()).await? {
+            Some(Event::PeerLost { name }) => {
+                assert!(others.contains(&name));
+                let _ = lost.insert(name);
+            }
+            Some(_) => continue,
+            None => bail!("The events were dropped without all the peers being lost"),
+        }
+    }
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn handle_agreement_on_online_of_elder_candidate() -> Result<()> {
     init_logger();
@@ -361,8 +743,14 @@ async fn handle_agreement_on_online_of_elder_candidate() -> Result<()> {
 
     let elders = nodes.iter().map(NodeInfo::peer);
     let members = nodes.iter().map(|n| NodeState::joined(n.peer(), None));
-    let section_auth =
-        SectionAuthorityProvider::new(elders, Prefix::default(), members, sk_set.public_keys(), 0);
+    let section_auth = SectionAuthorityProvider::new(
+        elders,
+        Prefix::default(),
+        members,
+        sk_set.public_keys(),
+        0,
+        data_copy_count(),
+    );
     let signed_sap = section_signed(sk_set.secret_key(), section_auth.clone())?;
 
     let section = NetworkKnowledge::new(*chain.root_key(), chain, signed_sap, None)?;
@@ -394,6 +782,9 @@ async fn handle_agreement_on_online_of_elder_candidate() -> Result<()> {
         mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
     )
     .await?;
     let dispatcher = Dispatcher::new(node);
@@ -416,7 +807,7 @@ async fn handle_agreement_on_online_of_elder_candidate() -> Result<()> {
         .force_bootstrap(node_state.to_msg());
 
     let cmds = dispatcher
-        .process_cmd(Cmd::HandleNewNodeOnline(auth), "cmd-id")
+        .process_cmd(Cmd::HandleNewNodeOnline(auth), "cmd-id", None)
         .await?;
 
     // Verify we sent a `DkgStart` message with the expected participants.
@@ -471,7 +862,7 @@ async fn handle_online_cmd(
     let auth = section_signed(sk_set.secret_key(), node_state.to_msg())?;
 
     let cmds = dispatcher
-        .process_cmd(Cmd::HandleNewNodeOnline(auth), "cmd-id")
+        .process_cmd(Cmd::HandleNewNodeOnline(auth), "cmd-id", None)
         .await?;
 
     let mut status = HandleOnlineStatus {
@@ -563,6 +954,9 @@ async fn handle_agreement_on_online_of_rejoined_node(phase: NetworkPhase, age: u
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
     )
     .await?;
     let dispatcher = Dispatcher::new(node);
@@ -632,6 +1026,9 @@ async fn handle_agreement_on_offline_of_non_elder() -> Result<()> {
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
     )
     .await?;
     let dispatcher = Dispatcher::new(node);
@@ -641,7 +1038,7 @@ async fn handle_agreement_on_offline_of_non_elder() -> Result<()> {
     let sig = keyed_signed(sk_set.secret_key(), &proposal.as_signable_bytes()?);
 
     let _cmds = dispatcher
-        .process_cmd(Cmd::HandleAgreement { proposal, sig }, "cmd-id")
+        .process_cmd(Cmd::HandleAgreement { proposal, sig }, "cmd-id", None)
         .await?;
 
     assert!(!dispatcher
@@ -687,6 +1084,9 @@ async fn handle_agreement_on_offline_of_elder() -> Result<()> {
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
     )
     .await?;
     let dispatcher = Dispatcher::new(node);
@@ -696,7 +1096,7 @@ async fn handle_agreement_on_offline_of_elder() -> Result<()> {
     let sig = keyed_signed(sk_set.secret_key(), &proposal.as_signable_bytes()?);
 
     let _ = dispatcher
-        .process_cmd(Cmd::HandleAgreement { proposal, sig }, "cmd-id")
+        .process_cmd(Cmd::HandleAgreement { proposal, sig }, "cmd-id", None)
         .await?;
 
     // Verify we initiated a membership churn
@@ -753,6 +1153,9 @@ async fn ae_msg_from_the_future_is_handled() -> Result<()> {
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
     )
     .await?;
 
@@ -780,6 +1183,7 @@ async fn ae_msg_from_the_future_is_handled() -> Result<()> {
         members,
         sk_set2.public_keys(),
         0,
+        data_copy_count(),
     );
     let new_section_elders: BTreeSet<_> = new_sap.names();
     let signed_new_sap = section_signed(sk2, new_sap.clone())?;
@@ -787,10 +1191,7 @@ async fn ae_msg_from_the_future_is_handled() -> Result<()> {
     // Create the `Sync` message containing the new `Section`.
     let wire_msg = WireMsg::single_src(
         &old_node,
-        DstLocation::Node {
-            name: XorName::from(PublicKey::Bls(pk1)),
-            section_pk: pk1,
-        },
+        DstLocation::node(XorName::from(PublicKey::Bls(pk1)), pk1),
         SystemMsg::AntiEntropyUpdate {
             section_auth: new_sap.to_msg(),
             members: BTreeSet::default(),
@@ -816,6 +1217,7 @@ async fn ae_msg_from_the_future_is_handled() -> Result<()> {
                 original_bytes: None,
             },
             "cmd-id",
+            None,
         )
         .await?;
 
@@ -871,6 +1273,9 @@ async fn untrusted_ae_msg_errors() -> Result<()> {
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
     )
     .await?;
 
@@ -879,10 +1284,10 @@ async fn untrusted_ae_msg_errors() -> Result<()> {
     let sender = gen_info(MIN_ADULT_AGE, None);
     let wire_msg = WireMsg::single_src(
         &sender,
-        DstLocation::Section {
-            name: XorName::from(PublicKey::Bls(bogus_section_pk)),
-            section_pk: bogus_section_pk,
-        },
+        DstLocation::section(
+            XorName::from(PublicKey::Bls(bogus_section_pk)),
+            bogus_section_pk,
+        ),
         node_msg.clone(),
         // we use the nonsense here
         bogus_section_pk,
@@ -896,6 +1301,7 @@ async fn untrusted_ae_msg_errors() -> Result<()> {
                 original_bytes: None,
             },
             "cmd-id",
+            None,
         )
         .await?;
 
@@ -913,6 +1319,84 @@ async fn relocation_of_non_elder() -> Result<()> {
     relocation(RelocatedPeerRole::NonElder).await
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn relocation_not_approved_in_time_is_retried_then_given_up() -> Result<()> {
+    init_logger();
+    let _span =
+        tracing::info_span!("relocation_not_approved_in_time_is_retried_then_given_up").entered();
+
+    let (section_auth, mut nodes, sk_set) = create_section_auth();
+    let (section, section_key_share) = create_section(&sk_set, &section_auth).await?;
+
+    let (event_tx, mut event_rx) = mpsc::channel(TEST_EVENT_CHANNEL_SIZE);
+    let info = nodes.remove(0);
+    let (max_capacity, root_storage_dir) = create_test_max_capacity_and_root_storage()?;
+    let node = Node::new(
+        create_comm().await?,
+        info.clone(),
+        section,
+        Some(section_key_share),
+        event_tx,
+        UsedSpace::new(max_capacity),
+        root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
+    )
+    .await?;
+
+    let relocate_details = RelocateDetails {
+        previous_name: info.name(),
+        dst: info.name(),
+        dst_section_key: sk_set.public_keys().public_key(),
+        age: info.age() + 1,
+    };
+    let node_state = NodeState::relocated(info.peer(), None, relocate_details);
+    let relocate_proof = section_signed(sk_set.secret_key(), node_state.to_msg())?;
+
+    let relocation_timeout = |cmds: Vec<Cmd>| {
+        cmds.into_iter().find_map(|cmd| match cmd {
+            Cmd::ScheduleTimeout { token, .. } => Some(token),
+            _ => None,
+        })
+    };
+
+    let cmds = node.handle_relocate(relocate_proof.clone()).await?;
+    let mut token =
+        relocation_timeout(cmds).ok_or_else(|| eyre!("relocation timeout not scheduled"))?;
+    assert_matches!(
+        event_rx.try_recv(),
+        Ok(Event::RelocationStarted { previous_name }) if previous_name == info.name()
+    );
+
+    // other timeouts leave the relocation in progress
+    assert!(node.handle_timeout(token + 1).await.is_ok());
+    assert!(node
+        .handle_relocate(relocate_proof.clone())
+        .await?
+        .is_empty());
+
+    // the relocation is started over, with a new timeout, a few times
+    for _ in 1..MAX_RELOCATION_ATTEMPTS {
+        let cmds = node.handle_timeout(token).await?;
+        let new_token =
+            relocation_timeout(cmds).ok_or_else(|| eyre!("relocation timeout not scheduled"))?;
+        assert_ne!(new_token, token);
+        token = new_token;
+        assert!(event_rx.try_recv().is_err());
+    }
+
+    // then given up on, for the node to rejoin the network
+    assert!(node.handle_timeout(token).await?.is_empty());
+    assert_matches!(
+        event_rx.try_recv(),
+        Ok(Event::RelocationTimedOut { previous_name, dst })
+            if previous_name == info.name() && dst == info.name()
+    );
+
+    Ok(())
+}
+
 fn threshold() -> usize {
     supermajority(elder_count()) - 1
 }
@@ -955,6 +1439,9 @@ async fn relocation(relocated_peer_role: RelocatedPeerRole) -> Result<()> {
         mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
     )
     .await?;
     let dispatcher = Dispatcher::new(node);
@@ -966,7 +1453,7 @@ async fn relocation(relocated_peer_role: RelocatedPeerRole) -> Result<()> {
 
     let auth = create_relocation_trigger(sk_set.secret_key(), relocated_peer.age())?;
     let cmds = dispatcher
-        .process_cmd(Cmd::HandleNewNodeOnline(auth), "cmd-id")
+        .process_cmd(Cmd::HandleNewNodeOnline(auth), "cmd-id", None)
         .await?;
 
     let mut offline_relocate_sent = false;
@@ -1018,8 +1505,13 @@ async fn message_to_self(dst: MessageDst) -> Result<()> {
     let info = gen_info(MIN_ADULT_AGE, None);
     let (event_tx, _) = mpsc::channel(TEST_EVENT_CHANNEL_SIZE);
     let (comm_tx, mut comm_rx) = mpsc::channel(TEST_EVENT_CHANNEL_SIZE);
-    let comm =
-        Comm::first_node((Ipv4Addr::LOCALHOST, 0).into(), Default::default(), comm_tx).await?;
+    let comm = Comm::first_node(
+        (Ipv4Addr::LOCALHOST, 0).into(),
+        Default::default(),
+        None,
+        comm_tx,
+    )
+    .await?;
     let (max_capacity, root_storage_dir) = create_test_max_capacity_and_root_storage()?;
 
     let genesis_sk_set = bls::SecretKeySet::random(0, &mut rand::thread_rng());
@@ -1029,6 +1521,9 @@ async fn message_to_self(dst: MessageDst) -> Result<()> {
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
         genesis_sk_set,
     )
     .await?;
@@ -1037,14 +1532,8 @@ async fn message_to_self(dst: MessageDst) -> Result<()> {
     let dispatcher = Dispatcher::new(node);
 
     let dst_location = match dst {
-        MessageDst::Node => DstLocation::Node {
-            name: info.name(),
-            section_pk,
-        },
-        MessageDst::Section => DstLocation::Section {
-            name: info.name(),
-            section_pk,
-        },
+        MessageDst::Node => DstLocation::node(info.name(), section_pk),
+        MessageDst::Section => DstLocation::section(info.name(), section_pk),
     };
 
     let node_msg = SystemMsg::NodeMsgError {
@@ -1060,6 +1549,7 @@ async fn message_to_self(dst: MessageDst) -> Result<()> {
                 wire_msg,
             },
             "cmd-id",
+            None,
         )
         .await?;
 
@@ -1109,6 +1599,7 @@ async fn handle_elders_update() -> Result<()> {
         members.clone(),
         sk_set0.public_keys(),
         0,
+        data_copy_count(),
     );
 
     let (section0, section_key_share) = create_section(&sk_set0, &sap0).await?;
@@ -1134,6 +1625,7 @@ async fn handle_elders_update() -> Result<()> {
         members,
         sk_set1.public_keys(),
         0,
+        data_copy_count(),
     );
     let elder_names1: BTreeSet<_> = sap1.names();
 
@@ -1155,6 +1647,9 @@ async fn handle_elders_update() -> Result<()> {
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
     )
     .await?;
 
@@ -1167,7 +1662,11 @@ async fn handle_elders_update() -> Result<()> {
     let dispatcher = Dispatcher::new(node);
 
     let cmds = dispatcher
-        .process_cmd(Cmd::HandleNewEldersAgreement { proposal, sig }, "cmd-id")
+        .process_cmd(
+            Cmd::HandleNewEldersAgreement { proposal, sig },
+            "cmd-id",
+            None,
+        )
         .await?;
 
     let mut update_actual_recipients = HashSet::new();
@@ -1267,6 +1766,7 @@ async fn handle_demote_during_split() -> Result<()> {
         members.clone(),
         sk_set_v0.public_keys(),
         0,
+        data_copy_count(),
     );
     let (section, section_key_share) = create_section(&sk_set_v0, &section_auth_v0).await?;
 
@@ -1288,6 +1788,9 @@ async fn handle_demote_during_split() -> Result<()> {
         event_tx,
         UsedSpace::new(max_capacity),
         root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
     )
     .await?;
 
@@ -1327,11 +1830,12 @@ async fn handle_demote_during_split() -> Result<()> {
         members.clone(),
         sk_set_v1_p0.public_keys(),
         0,
+        data_copy_count(),
     );
 
     let signed_sap = section_signed(sk_set_v1_p0.secret_key(), section_auth)?;
     let cmd = create_our_elders_cmd(signed_sap)?;
-    let mut cmds = dispatcher.process_cmd(cmd, "cmd-id-1").await?;
+    let mut cmds = dispatcher.process_cmd(cmd, "cmd-id-1", None).await?;
 
     // Handle agreement on `NewElders` for prefix-1.
     let section_auth = SectionAuthorityProvider::new(
@@ -1340,12 +1844,13 @@ async fn handle_demote_during_split() -> Result<()> {
         members,
         sk_set_v1_p1.public_keys(),
         0,
+        data_copy_count(),
     );
 
     let signed_sap = section_signed(sk_set_v1_p1.secret_key(), section_auth)?;
     let cmd = create_our_elders_cmd(signed_sap)?;
 
-    let new_cmds = dispatcher.process_cmd(cmd, "cmd-id-2").await?;
+    let new_cmds = dispatcher.process_cmd(cmd, "cmd-id-2", None).await?;
 
     cmds.extend(new_cmds);
 
@@ -1380,6 +1885,437 @@ async fn handle_demote_during_split() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn data_is_replicated_as_per_data_copy_count_of_section() -> Result<()> {
+    let prefix = Prefix::default();
+    let (section_auth, mut nodes, sk_set) = gen_section_authority_provider(prefix, elder_count());
+    let node = nodes.remove(0);
+    let adults: Vec<Peer> = (0..6).map(|_| create_peer(MIN_ADULT_AGE)).collect();
+
+    let chunk = Chunk::new(random_bytes(100));
+    let data = ReplicatedData::Chunk(chunk.clone());
+    // the 4th closest adult to the chunk only holds it when there are at least 4 copies of it
+    let fourth_holder = adults
+        .iter()
+        .sorted_by(|lhs, rhs| chunk.name().cmp_distance(&lhs.name(), &rhs.name()))
+        .nth(3)
+        .copied()
+        .ok_or_else(|| eyre!("Not enough adults"))?;
+
+    for data_copy_count in [2, 4] {
+        let sap = SectionAuthorityProvider::new(
+            section_auth.elders().cloned(),
+            prefix,
+            section_auth.members().cloned(),
+            sk_set.public_keys(),
+            0,
+            data_copy_count,
+        );
+        let (section, section_key_share) = create_section(&sk_set, &sap).await?;
+        for adult in &adults {
+            let node_state = section_signed(sk_set.secret_key(), NodeState::joined(*adult, None))?;
+            assert!(section.update_member(node_state).await);
+        }
+
+        let (max_capacity, root_storage_dir) = create_test_max_capacity_and_root_storage()?;
+        let node = Node::new(
+            create_comm().await?,
+            node.clone(),
+            section,
+            Some(section_key_share),
+            mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
+            UsedSpace::new(max_capacity),
+            root_storage_dir,
+            ChunkStoreConfig::default(),
+            ClientRateLimits::default(),
+            QueryDelegationConfig::default(),
+        )
+        .await?;
+        let _ = node.data_storage.store(&data).await?;
+
+        // the adult reports holding no data, so it's sent the chunk only if it's to hold it
+        let cmds = node
+            .get_missing_data_for_node(fourth_holder, vec![])
+            .await?;
+        if data_copy_count < 4 {
+            assert!(cmds.is_empty());
+        } else {
+            assert_matches!(&cmds[..], [Cmd::EnqueueDataForReplication { recipient, data_batch }] => {
+                assert_eq!(*recipient, fourth_holder);
+                assert_eq!(data_batch, &vec![data.address()]);
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn missing_chunk_is_repaired_by_adults_holding_it() -> Result<()> {
+    let prefix = Prefix::default();
+    let (section_auth, _, sk_set) = gen_section_authority_provider(prefix, elder_count());
+    let (section, _) = create_section(&sk_set, &section_auth).await?;
+    let info = gen_info(MIN_ADULT_AGE, None);
+    let adults: Vec<Peer> = (0..6)
+        .map(|_| create_peer(MIN_ADULT_AGE))
+        .chain([info.peer()])
+        .collect();
+    for adult in &adults {
+        let node_state = section_signed(sk_set.secret_key(), NodeState::joined(*adult, None))?;
+        assert!(section.update_member(node_state).await);
+    }
+
+    let (max_capacity, root_storage_dir) = create_test_max_capacity_and_root_storage()?;
+    let node = Node::new(
+        create_comm().await?,
+        info.clone(),
+        section,
+        None,
+        mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
+        UsedSpace::new(max_capacity),
+        root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
+    )
+    .await?;
+
+    // we still hold a copy of the chunk, which was lost by the other Adults meant to hold it
+    let chunk = Chunk::new(random_bytes(100));
+    let data = ReplicatedData::Chunk(chunk.clone());
+    let _ = node.data_storage.store(&data).await?;
+    let holders: BTreeSet<Peer> = adults
+        .iter()
+        .sorted_by(|lhs, rhs| chunk.name().cmp_distance(&lhs.name(), &rhs.name()))
+        .take(data_copy_count())
+        .filter(|adult| adult.name() != info.name())
+        .copied()
+        .collect();
+
+    let cmds = node.repair_data_held(vec![data.address()]).await?;
+    let mut recipients = BTreeSet::new();
+    for cmd in cmds {
+        assert_matches!(cmd, Cmd::EnqueueDataForReplication { recipient, data_batch } => {
+            assert_eq!(data_batch, vec![data.address()]);
+            assert!(recipients.insert(recipient));
+        });
+    }
+    assert_eq!(recipients, holders);
+
+    // nothing is sent for data we don't hold a copy of either
+    let other = ReplicatedData::Chunk(Chunk::new(random_bytes(100)));
+    assert!(node
+        .repair_data_held(vec![other.address()])
+        .await?
+        .is_empty());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unpaid_chunk_is_only_rejected_when_payments_are_required() -> Result<()> {
+    let prefix = Prefix::default();
+    let (section_auth, mut nodes, sk_set) = gen_section_authority_provider(prefix, elder_count());
+    let (section, section_key_share) = create_section(&sk_set, &section_auth).await?;
+    let (max_capacity, root_storage_dir) = create_test_max_capacity_and_root_storage()?;
+    let mut node = Node::new(
+        create_comm().await?,
+        nodes.remove(0),
+        section,
+        Some(section_key_share),
+        mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
+        UsedSpace::new(max_capacity),
+        root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
+    )
+    .await?;
+
+    let client = Keypair::new_ed25519();
+    let origin = Peer::new(xor_name::rand::random(), gen_addr());
+    let msg = ServiceMsg::Cmd {
+        cmd: DataCmd::StoreChunk(Chunk::new(random_bytes(100))),
+        ack_level: CmdAckLevel::default(),
+        seq: None,
+    };
+    let payload = WireMsg::serialize_msg_payload(&msg)?;
+    let auth = AuthorityProof::verify(
+        ServiceAuth {
+            public_key: client.public_key(),
+            signature: client.sign(&payload),
+        },
+        &payload,
+    )?;
+    let msg_id = MsgId::new();
+
+    // chunks are stored whether paid for or not by default, this node being only short of
+    // Adults to store the chunk with
+    let cmds = node
+        .handle_service_msg_received(msg_id, msg.clone(), auth.clone(), origin)
+        .await?;
+    assert_matches!(&cmds[..], [Cmd::SendMsg { wire_msg, .. }] => {
+        assert_matches!(wire_msg.clone().into_msg()?, MsgType::Service {
+            msg: ServiceMsg::CmdError {
+                error: CmdError::Data(ErrorMsg::InsufficientAdults { .. }),
+                ..
+            },
+            ..
+        });
+    });
+
+    // but they're rejected once the node is told to require payments
+    node.require_chunk_payments = true;
+    let cmds = node
+        .handle_service_msg_received(msg_id, msg, auth, origin)
+        .await?;
+    assert_matches!(&cmds[..], [Cmd::SendMsg { recipients, wire_msg }] => {
+        assert_eq!(recipients, &vec![origin]);
+        assert_matches!(wire_msg.clone().into_msg()?, MsgType::Service {
+            msg: ServiceMsg::CmdError { error, correlation_id },
+            ..
+        } => {
+            assert_eq!(error, CmdError::Data(ErrorMsg::PaymentRequired));
+            assert_eq!(correlation_id, msg_id);
+        });
+    });
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejected_ordered_cmd_does_not_advance_its_stream() -> Result<()> {
+    let prefix = Prefix::default();
+    let (section_auth, mut nodes, sk_set) = gen_section_authority_provider(prefix, elder_count());
+    let (section, section_key_share) = create_section(&sk_set, &section_auth).await?;
+    let (max_capacity, root_storage_dir) = create_test_max_capacity_and_root_storage()?;
+    let mut node = Node::new(
+        create_comm().await?,
+        nodes.remove(0),
+        section,
+        Some(section_key_share),
+        mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
+        UsedSpace::new(max_capacity),
+        root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
+    )
+    .await?;
+    node.require_chunk_payments = true;
+
+    let client = Keypair::new_ed25519();
+    let origin = Peer::new(xor_name::rand::random(), gen_addr());
+    let chunk = Chunk::new(random_bytes(100));
+    let store_chunk = |seq| -> Result<_> {
+        let msg = ServiceMsg::Cmd {
+            cmd: DataCmd::StoreChunk(chunk.clone()),
+            ack_level: CmdAckLevel::default(),
+            seq: Some(seq),
+        };
+        let payload = WireMsg::serialize_msg_payload(&msg)?;
+        let auth = AuthorityProof::verify(
+            ServiceAuth {
+                public_key: client.public_key(),
+                signature: client.sign(&payload),
+            },
+            &payload,
+        )?;
+        Ok((msg, auth))
+    };
+    let cmd_error = |cmds: Vec<Cmd>| -> Result<CmdError> {
+        match &cmds[..] {
+            [Cmd::SendMsg { wire_msg, .. }] => match wire_msg.clone().into_msg()? {
+                MsgType::Service {
+                    msg: ServiceMsg::CmdError { error, .. },
+                    ..
+                } => Ok(error),
+                other => bail!("Unexpected msg sent to the client: {:?}", other),
+            },
+            other => bail!("Unexpected cmds: {:?}", other),
+        }
+    };
+
+    // the cmd is rejected for not being paid for
+    let (msg, auth) = store_chunk(5)?;
+    let cmds = node
+        .handle_service_msg_received(MsgId::new(), msg, auth, origin)
+        .await?;
+    assert_eq!(cmd_error(cmds)?, CmdError::Data(ErrorMsg::PaymentRequired));
+
+    // so an earlier cmd of the stream isn't out of order, only rejected for the lack of Adults
+    node.require_chunk_payments = false;
+    let (msg, auth) = store_chunk(3)?;
+    let cmds = node
+        .handle_service_msg_received(MsgId::new(), msg, auth, origin)
+        .await?;
+    assert_matches!(
+        cmd_error(cmds)?,
+        CmdError::Data(ErrorMsg::InsufficientAdults { .. })
+    );
+
+    // neither of them was applied
+    assert!(node.ordered_cmds.matching(&prefix).await.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn small_chunk_is_acked_ahead_of_its_replication_when_asked() -> Result<()> {
+    let prefix = Prefix::default();
+    let (section_auth, mut nodes, sk_set) = gen_section_authority_provider(prefix, elder_count());
+    let (section, section_key_share) = create_section(&sk_set, &section_auth).await?;
+    let adults: Vec<Peer> = (0..data_copy_count())
+        .map(|_| create_peer(MIN_ADULT_AGE))
+        .collect();
+    for adult in &adults {
+        let node_state = section_signed(sk_set.secret_key(), NodeState::joined(*adult, None))?;
+        assert!(section.update_member(node_state).await);
+    }
+    let (max_capacity, root_storage_dir) = create_test_max_capacity_and_root_storage()?;
+    let node = Node::new(
+        create_comm().await?,
+        nodes.remove(0),
+        section,
+        Some(section_key_share),
+        mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
+        UsedSpace::new(max_capacity),
+        root_storage_dir,
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
+    )
+    .await?;
+
+    let client = Keypair::new_ed25519();
+    let origin = Peer::new(xor_name::rand::random(), gen_addr());
+    let store_chunk = |ack_level, len| -> Result<_> {
+        let msg = ServiceMsg::Cmd {
+            cmd: DataCmd::StoreChunk(Chunk::new(random_bytes(len))),
+            ack_level,
+            seq: None,
+        };
+        let payload = WireMsg::serialize_msg_payload(&msg)?;
+        let auth = AuthorityProof::verify(
+            ServiceAuth {
+                public_key: client.public_key(),
+                signature: client.sign(&payload),
+            },
+            &payload,
+        )?;
+        Ok((msg, auth))
+    };
+    let ack_durability = |wire_msg: &WireMsg| -> Result<ReceiptDurability> {
+        match wire_msg.clone().into_msg()? {
+            MsgType::Service {
+                msg:
+                    ServiceMsg::CmdAck {
+                        receipt_share: Some(share),
+                        ..
+                    },
+                ..
+            } => Ok(share.content.durability),
+            other => bail!("Unexpected msg sent to the client: {:?}", other),
+        }
+    };
+    let is_replication_to_adult = |cmd: &Cmd| match cmd {
+        Cmd::SendMsg { recipients, .. } => recipients.iter().all(|peer| adults.contains(peer)),
+        _ => false,
+    };
+
+    // the client is acked first, the Adults are only sent the chunk once the ack is sent
+    let (msg, auth) = store_chunk(CmdAckLevel::FastAck, 100)?;
+    let cmds = node
+        .handle_service_msg_received(MsgId::new(), msg, auth, origin)
+        .await?;
+    assert_matches!(&cmds[..], [Cmd::SendMsgThen { recipients, wire_msg, then }] => {
+        assert_eq!(recipients, &vec![origin]);
+        assert_eq!(ack_durability(wire_msg)?, ReceiptDurability::ElderValidated);
+        assert_eq!(then.len(), data_copy_count());
+        assert!(then.iter().all(is_replication_to_adult));
+    });
+
+    // larger chunks are only acked alongside their replication, as with the default level
+    for (ack_level, len) in [
+        (CmdAckLevel::FastAck, FAST_ACK_MAX_CHUNK_SIZE + 1),
+        (CmdAckLevel::Processed, 100),
+    ] {
+        let (msg, auth) = store_chunk(ack_level, len)?;
+        let cmds = node
+            .handle_service_msg_received(MsgId::new(), msg, auth, origin)
+            .await?;
+        let (acks, replication): (Vec<_>, Vec<_>) = cmds.iter().partition(
+            |cmd| matches!(cmd, Cmd::SendMsg { recipients, .. } if recipients == &vec![origin]),
+        );
+        assert_matches!(&acks[..], [Cmd::SendMsg { wire_msg, .. }] => {
+            assert_eq!(ack_durability(wire_msg)?, ReceiptDurability::Replicated);
+        });
+        assert_eq!(replication.len(), data_copy_count());
+        assert!(replication.into_iter().all(is_replication_to_adult));
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn peers_shared_by_a_member_take_a_bounded_share_of_the_bootstrap_cache() -> Result<()> {
+    let prefix = Prefix::default();
+    let (section_auth, mut nodes, sk_set) = gen_section_authority_provider(prefix, elder_count());
+    let (section, section_key_share) = create_section(&sk_set, &section_auth).await?;
+    let (max_capacity, root_storage_dir) = create_test_max_capacity_and_root_storage()?;
+    let node = Node::new(
+        create_comm().await?,
+        nodes.remove(0),
+        section,
+        Some(section_key_share),
+        mpsc::channel(TEST_EVENT_CHANNEL_SIZE).0,
+        UsedSpace::new(max_capacity),
+        root_storage_dir.clone(),
+        ChunkStoreConfig::default(),
+        ClientRateLimits::default(),
+        QueryDelegationConfig::default(),
+    )
+    .await?;
+    let (member, other_member) = (nodes[0].peer(), nodes[1].peer());
+    let random_peers = |count: usize| -> Vec<Peer> {
+        (0..count)
+            .map(|_| Peer::new(xor_name::rand::random(), gen_addr()))
+            .collect()
+    };
+
+    // exchanges handled concurrently don't lose each other's peers
+    let shared = random_peers(8);
+    let other_shared = random_peers(2);
+    let (result, other_result) = futures::join!(
+        node.handle_peer_exchange(member, shared.clone()),
+        node.handle_peer_exchange(other_member, other_shared.clone())
+    );
+    let _cmds = result?;
+    let _cmds = other_result?;
+    let cached: BTreeSet<_> = get_bootstrap_cache(&root_storage_dir)
+        .await?
+        .iter()
+        .map(Peer::name)
+        .collect();
+    assert_eq!(cached.len(), 6);
+    assert!(other_shared
+        .iter()
+        .all(|peer| cached.contains(&peer.name())));
+
+    // a member sharing more peers doesn't flush out the ones shared by others
+    let _cmds = node.handle_peer_exchange(member, random_peers(8)).await?;
+    let cached = get_bootstrap_cache(&root_storage_dir).await?;
+    assert_eq!(cached.len(), 6);
+    assert!(other_shared.iter().all(|peer| cached.contains(peer)));
+
+    // while the peers it shared before can still be updated
+    let moved = Peer::new(shared[0].name(), gen_addr());
+    let _cmds = node.handle_peer_exchange(member, vec![moved]).await?;
+    assert_eq!(get_bootstrap_cache(&root_storage_dir).await?[0], moved);
+
+    Ok(())
+}
+
 fn create_peer(age: u8) -> Peer {
     let name = ed25519::gen_name_with_age(age);
     Peer::new(name, gen_addr())
@@ -1399,7 +2335,13 @@ fn gen_info(age: u8, prefix: Option<Prefix>) -> NodeInfo {
 
 pub(crate) async fn create_comm() -> Result<Comm> {
     let (tx, _rx) = mpsc::channel(TEST_EVENT_CHANNEL_SIZE);
-    Ok(Comm::first_node((Ipv4Addr::LOCALHOST, 0).into(), Default::default(), tx).await?)
+    Ok(Comm::first_node(
+        (Ipv4Addr::LOCALHOST, 0).into(),
+        Default::default(),
+        None,
+        tx,
+    )
+    .await?)
 }
 
 // Generate random SectionAuthorityProvider and the corresponding Nodes.
@@ -1450,7 +2392,7 @@ fn create_relocation_trigger(sk: &bls::SecretKey, age: u8) -> Result<SectionAuth
         let auth = section_signed(sk, node_state.to_msg())?;
 
         let churn_id = ChurnId(auth.sig.signature.to_bytes().to_vec());
-        if relocation_check(age, &churn_id) && !relocation_check(age + 1, &churn_id) {
+        if is_relocation_candidate(age, &churn_id) && !is_relocation_candidate(age + 1, &churn_id) {
             return Ok(auth);
         }
     }
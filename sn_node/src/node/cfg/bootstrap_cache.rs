@@ -0,0 +1,117 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::{Error, Result};
+use sn_interface::types::Peer;
+use std::path::Path;
+use tokio::fs;
+
+// Filename for storing the peers (names and addresses) the node last bootstrapped through
+const BOOTSTRAP_CACHE_FILENAME: &str = "bootstrap_cache";
+// Max number of peers cached, the ones added last being kept
+const BOOTSTRAP_CACHE_MAX_SIZE: usize = 32;
+
+/// Writes the peers the node joined through to disk, for it to contact them first when it
+/// restarts, rather than its hard-coded contacts.
+pub(crate) async fn store_bootstrap_cache(root_dir: &Path, peers: &[Peer]) -> Result<()> {
+    let path = root_dir.join(BOOTSTRAP_CACHE_FILENAME);
+    let serialised = serde_json::to_vec(peers).map_err(|err| {
+        Error::Configuration(format!(
+            "couldn't serialise bootstrap cache to write to {}: {}",
+            path.display(),
+            err
+        ))
+    })?;
+
+    // write to a temporary file first, so a node stopped in between doesn't leave a truncated
+    // cache behind
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, serialised).await?;
+    fs::rename(tmp_path, path).await?;
+
+    Ok(())
+}
+
+/// Adds the given peers, e.g. shared by other nodes in a peer exchange, to the cache, ahead of
+/// the peers cached already. The peers cached the longest are dropped once the cache is full.
+pub(crate) async fn add_to_bootstrap_cache(root_dir: &Path, peers: &[Peer]) -> Result<()> {
+    let cached = get_bootstrap_cache(root_dir).await?;
+    let mut updated = peers.to_vec();
+    updated.extend(
+        cached
+            .into_iter()
+            .filter(|cached| peers.iter().all(|peer| peer.name() != cached.name())),
+    );
+    updated.truncate(BOOTSTRAP_CACHE_MAX_SIZE);
+    store_bootstrap_cache(root_dir, &updated).await
+}
+
+/// Returns the peers cached, or an empty list if the file doesn't exist.
+pub(crate) async fn get_bootstrap_cache(root_dir: &Path) -> Result<Vec<Peer>> {
+    let path = root_dir.join(BOOTSTRAP_CACHE_FILENAME);
+    if !path.is_file() {
+        return Ok(vec![]);
+    }
+
+    let serialised = fs::read(&path).await?;
+    serde_json::from_slice(&serialised).map_err(|err| {
+        Error::Configuration(format!(
+            "couldn't deserialise bootstrap cache read from {}: {}",
+            path.display(),
+            err
+        ))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        add_to_bootstrap_cache, get_bootstrap_cache, store_bootstrap_cache,
+        BOOTSTRAP_CACHE_FILENAME, BOOTSTRAP_CACHE_MAX_SIZE,
+    };
+    use eyre::{eyre, Result};
+    use sn_interface::types::Peer;
+    use tempfile::tempdir;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn bootstrap_cache_to_and_from_file() -> Result<()> {
+        let root = tempdir().map_err(|e| eyre!("Failed to create temp dir: {}", e))?;
+        let root_dir = root.path();
+
+        assert!(get_bootstrap_cache(root_dir).await?.is_empty());
+
+        let peers = vec![
+            Peer::new(xor_name::rand::random(), ([10, 0, 0, 1], 12000).into()),
+            Peer::new(xor_name::rand::random(), ([10, 0, 0, 2], 12000).into()),
+        ];
+        store_bootstrap_cache(root_dir, &peers).await?;
+        let cached = get_bootstrap_cache(root_dir).await?;
+        assert_eq!(cached, peers);
+
+        // peers shared later are tried first, and replace the stale entries of the same nodes
+        let moved = Peer::new(peers[1].name(), ([10, 0, 0, 3], 12000).into());
+        add_to_bootstrap_cache(root_dir, &[moved]).await?;
+        assert_eq!(get_bootstrap_cache(root_dir).await?, vec![moved, peers[0]]);
+
+        let shared: Vec<_> = (0..BOOTSTRAP_CACHE_MAX_SIZE)
+            .map(|port| {
+                Peer::new(
+                    xor_name::rand::random(),
+                    ([10, 0, 1, 1], port as u16).into(),
+                )
+            })
+            .collect();
+        add_to_bootstrap_cache(root_dir, &shared).await?;
+        assert_eq!(get_bootstrap_cache(root_dir).await?, shared);
+
+        std::fs::write(root_dir.join(BOOTSTRAP_CACHE_FILENAME), "not a cache")?;
+        assert!(get_bootstrap_cache(root_dir).await.is_err());
+
+        Ok(())
+    }
+}
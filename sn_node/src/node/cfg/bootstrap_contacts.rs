@@ -0,0 +1,113 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::{Error, Result};
+use sn_interface::network_knowledge::NodeConfig;
+use std::{net::SocketAddr, time::Duration};
+use tokio::fs;
+
+// Time a seed list has to be fetched in
+const SEED_LIST_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Reads the contacts to bootstrap through from the given location: either the `https` URL of a
+/// seed list, or the path of a JSON file. The contacts are either a JSON list of addresses, or
+/// connection info as written by the nodes, e.g. `node_connection_info.config`.
+pub(crate) async fn read_bootstrap_contacts(location: &str) -> Result<Vec<SocketAddr>> {
+    let bytes = if location.starts_with("https://") {
+        fetch_seed_list(location).await?
+    } else if location.starts_with("http://") {
+        return Err(Error::Configuration(format!(
+            "the seed list at {} is to be fetched over https",
+            location
+        )));
+    } else {
+        fs::read(location).await?
+    };
+
+    parse_contacts(&bytes).map_err(|err| {
+        Error::Configuration(format!(
+            "couldn't parse the bootstrap contacts read from {}: {}",
+            location, err
+        ))
+    })
+}
+
+async fn fetch_seed_list(url: &str) -> Result<Vec<u8>> {
+    let fetch_error = |err: reqwest::Error| {
+        Error::Configuration(format!("couldn't fetch the seed list at {}: {}", url, err))
+    };
+    let client = reqwest::Client::builder()
+        .timeout(SEED_LIST_FETCH_TIMEOUT)
+        .https_only(true)
+        .build()
+        .map_err(fetch_error)?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(fetch_error)?;
+    let bytes = response.bytes().await.map_err(fetch_error)?;
+
+    Ok(bytes.to_vec())
+}
+
+fn parse_contacts(bytes: &[u8]) -> std::result::Result<Vec<SocketAddr>, String> {
+    if let Ok(contacts) = serde_json::from_slice::<Vec<SocketAddr>>(bytes) {
+        return Ok(contacts);
+    }
+
+    NodeConfig::from_json(bytes)
+        .map(|config| config.contacts.into_iter().collect())
+        .map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::read_bootstrap_contacts;
+    use eyre::{eyre, Result};
+    use sn_interface::network_knowledge::NodeConfig;
+    use std::{collections::BTreeSet, net::SocketAddr};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn contacts_are_read_from_a_file_in_either_format() -> Result<()> {
+        let dir = tempdir()?;
+        let contacts: Vec<SocketAddr> = vec!["10.0.0.1:12000".parse()?, "10.0.0.2:12000".parse()?];
+
+        let list_path = dir.path().join("contacts.json");
+        std::fs::write(&list_path, serde_json::to_vec(&contacts)?)?;
+        let path = list_path.to_str().ok_or_else(|| eyre!("invalid path"))?;
+        assert_eq!(read_bootstrap_contacts(path).await?, contacts);
+
+        let node_config = NodeConfig::new(
+            bls::SecretKey::random().public_key(),
+            contacts.iter().copied().collect::<BTreeSet<_>>(),
+        );
+        let conn_info_path = dir.path().join("node_connection_info.config");
+        std::fs::write(&conn_info_path, node_config.to_json()?)?;
+        let path = conn_info_path
+            .to_str()
+            .ok_or_else(|| eyre!("invalid path"))?;
+        assert_eq!(read_bootstrap_contacts(path).await?, contacts);
+
+        let invalid_path = dir.path().join("invalid.json");
+        std::fs::write(&invalid_path, b"{\"contacts\": 42}")?;
+        let path = invalid_path.to_str().ok_or_else(|| eyre!("invalid path"))?;
+        assert!(read_bootstrap_contacts(path).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn seed_lists_are_only_fetched_over_https() {
+        assert!(read_bootstrap_contacts("http://example.com/contacts.json")
+            .await
+            .is_err());
+    }
+}
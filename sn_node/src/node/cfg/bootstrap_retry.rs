@@ -0,0 +1,92 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use backoff::{backoff::Backoff, ExponentialBackoff};
+use std::time::Duration;
+
+// Randomisation of the delays, so nodes which failed to bootstrap together don't all retry at once
+const JITTER: f64 = 0.3;
+
+/// Delays to wait for before trying again to join the network, after failing to bootstrap or to
+/// be accepted, e.g. as the network is unreachable for a while.
+///
+/// Each delay is about twice the previous one, up to a maximum, with some jitter. Attempts go on
+/// until one succeeds, unless a maximum number of them is set.
+#[derive(Debug)]
+pub struct BootstrapRetries {
+    backoff: ExponentialBackoff,
+    max_attempts: Option<u32>,
+    attempts: u32,
+}
+
+impl BootstrapRetries {
+    /// Retries starting with the given delay, which grows up to the given maximum, and giving up
+    /// after the given number of attempts failed, if any.
+    pub fn new(initial_delay: Duration, max_delay: Duration, max_attempts: Option<u32>) -> Self {
+        let mut backoff = ExponentialBackoff {
+            initial_interval: initial_delay,
+            max_interval: max_delay.max(initial_delay),
+            randomization_factor: JITTER,
+            multiplier: 2.0,
+            max_elapsed_time: None,
+            ..Default::default()
+        };
+        // this seems needed for custom settings to take effect
+        backoff.reset();
+
+        Self {
+            backoff,
+            max_attempts,
+            attempts: 0,
+        }
+    }
+
+    /// Records an attempt failed, returning how long to wait for before the next one, or `None`
+    /// if no attempt is left.
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        self.attempts += 1;
+        if matches!(self.max_attempts, Some(max_attempts) if self.attempts >= max_attempts) {
+            return None;
+        }
+        self.backoff.next_backoff()
+    }
+
+    /// Number of attempts which failed so far.
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BootstrapRetries, JITTER};
+    use std::time::Duration;
+
+    #[test]
+    fn delays_grow_exponentially_up_to_the_max() {
+        let initial = Duration::from_secs(10);
+        let max = Duration::from_secs(60);
+        let mut retries = BootstrapRetries::new(initial, max, Some(6));
+
+        let mut expected = initial;
+        for _ in 0..5 {
+            let delay = retries.next_delay().expect("attempts are left");
+            assert!(delay >= expected.mul_f64(1.0 - JITTER));
+            assert!(delay <= expected.mul_f64(1.0 + JITTER));
+            expected = (expected * 2).min(max);
+        }
+
+        // the last attempt failed
+        assert_eq!(retries.next_delay(), None);
+        assert_eq!(retries.attempts(), 6);
+
+        // without a maximum number of attempts, retries go on
+        let mut retries = BootstrapRetries::new(initial, max, None);
+        assert!((0..100).all(|_| retries.next_delay().is_some()));
+    }
+}
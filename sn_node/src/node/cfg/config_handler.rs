@@ -6,7 +6,13 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::node::{Error, NetworkConfig, Result};
+use crate::node::{
+    BootstrapRetries, ClientRateLimits, Error, InboundRateLimits, LivenessConfig, NetworkConfig,
+    NotifierTarget, QueryDelegationConfig, Result, DEFAULT_LAN_DISCOVERY_PORT,
+};
+use crate::{ChunkDurability, ChunkStoreBackend, ChunkStoreConfig};
+use sn_interface::{messaging::NetworkName, network_knowledge::NodeConfig, types::IpPreference};
+
 use serde::{Deserialize, Serialize};
 use std::{
     collections::BTreeSet,
@@ -25,6 +31,8 @@ use tracing::{debug, warn, Level};
 const CONFIG_FILE: &str = "node.config";
 const CONNECTION_INFO_FILE: &str = "node_connection_info.config";
 const DEFAULT_ROOT_DIR_NAME: &str = "root_dir";
+// Env var which, when set, has the node store chunks sent without a payment
+const REQUIRE_CHUNK_PAYMENTS_ENV: &str = "SN_REQUIRE_CHUNK_PAYMENTS";
 #[cfg(not(target_arch = "arm"))]
 const DEFAULT_MAX_CAPACITY: usize = 10 * 1024 * 1024 * 1024; // 10GB
 #[cfg(any(target_arch = "arm", target_arch = "armv7"))]
@@ -91,12 +99,22 @@ pub struct Config {
     pub first: bool,
     /// Local address to be used for the node.
     ///
-    /// When unspecified, the node will listen on `0.0.0.0` with a random unused port. If you're
-    /// running a local-only network, you should set this to `127.0.0.1:0` to prevent any external
-    /// traffic from reaching the node (but note that the node will also be unable to connect to
-    /// non-local nodes).
+    /// When unspecified, the node will listen on `0.0.0.0`, or on `[::]` when preferring IPv6 (see
+    /// `--ip-preference`), with a random unused port. If you're running a local-only network, you
+    /// should set this to `127.0.0.1:0` or `[::1]:0` to prevent any external traffic from reaching
+    /// the node (but note that the node will also be unable to connect to non-local nodes).
     #[structopt(long)]
     pub local_addr: Option<SocketAddr>,
+    /// Additional local address for the node to accept connections at, e.g. an IPv6 one when
+    /// `--local-addr` is an IPv4 one. Can be given several times. A port of 0 binds a random
+    /// unused one; the ports bound are reported once the node joined.
+    #[structopt(long = "listen-addr", number_of_values = 1)]
+    pub listen_addrs: Vec<SocketAddr>,
+    /// IP version to prefer: "v4", or "v6" to listen on both IPv6 and IPv4 where the host supports
+    /// dual-stack sockets. Hard coded contacts of the preferred version are tried first, with the
+    /// others as fallback.
+    #[structopt(long, default_value = "v4")]
+    pub ip_preference: IpPreference,
     /// External address of the node, to use when writing connection info.
     ///
     /// If unspecified, it will be queried from a peer; if there are no peers, the `local-addr` will
@@ -108,6 +126,39 @@ pub struct Config {
     /// e.g. Digital Ocean droplets.
     #[structopt(long)]
     pub skip_auto_port_forwarding: bool,
+    /// Look for peers on the local network with a UDP broadcast before bootstrapping through the
+    /// cached peers and the hard coded contacts, and answer the nodes looking for peers once
+    /// joined, e.g. for local testnets not to need their contacts passed around.
+    #[structopt(long)]
+    pub lan_discovery: bool,
+    /// UDP port the nodes looking for peers on the local network broadcast to, which the ones
+    /// found answer at. If none supplied we'll default to the documented constant.
+    #[structopt(long)]
+    pub lan_discovery_port: Option<u16>,
+    /// Only store chunks sent with a payment when this node is an Elder, rejecting the unpaid
+    /// ones. Otherwise chunks are stored whether they're paid for or not. Also enabled by setting
+    /// the `SN_REQUIRE_CHUNK_PAYMENTS` env var.
+    #[structopt(long)]
+    pub require_chunk_payments: bool,
+    /// Local address of the admin socket, which operator tools connect to, e.g. to stream the
+    /// log events of the node. Only loopback addresses are allowed, as the tools aren't
+    /// authenticated. The admin socket is disabled when unspecified.
+    #[structopt(long)]
+    pub admin_addr: Option<SocketAddr>,
+    /// URL of a webhook each membership decision and Elder change of the section of the node is
+    /// POSTed to, as JSON, e.g. for dashboards and alerting. Only plain `http` URLs are
+    /// supported. Failed deliveries are retried with an exponential backoff.
+    #[structopt(long)]
+    pub notify_url: Option<String>,
+    /// Unix socket each membership decision and Elder change of the section of the node is
+    /// written to, as a line of JSON, instead of a webhook set with `--notify-url`.
+    #[structopt(long, parse(from_os_str))]
+    pub notify_socket: Option<PathBuf>,
+    /// File holding the passphrase the network keypair of the node is encrypted with on disk.
+    /// When set, the keypair is loaded back when the node restarts, for it to rejoin the network
+    /// under the same name, rather than as a new node.
+    #[structopt(long, parse(from_os_str))]
+    pub identity_passphrase_file: Option<PathBuf>,
     /// Hard Coded contacts
     #[structopt(
         short,
@@ -116,9 +167,20 @@ pub struct Config {
         parse(try_from_str = serde_json::from_str)
     )]
     pub hard_coded_contacts: BTreeSet<SocketAddr>,
+    /// Path of a JSON file, or `https` URL of a seed list, the contacts to bootstrap through are
+    /// read from when the node starts, e.g. for public testnets whose contacts change often.
+    /// They're tried along with the peers cached when the node last joined, before the hard
+    /// coded contacts. The contacts are either a JSON list of addresses, or connection info as
+    /// written by the nodes.
+    #[structopt(long)]
+    pub bootstrap_contacts: Option<String>,
     /// Genesis key of the network in hex format.
     #[structopt(long)]
     pub genesis_key: Option<String>,
+    /// Name of the network, conventionally its genesis key in hex format. When set, it is carried
+    /// in all the messages we send, and messages sent within any other network are dropped.
+    #[structopt(long)]
+    pub network_name: Option<String>,
     /// This is the maximum message size we'll allow the peer to send to us. Any bigger message and
     /// we'll error out probably shutting down the connection to the peer. If none supplied we'll
     /// default to the documented constant.
@@ -136,9 +198,90 @@ pub struct Config {
     /// The interval is in milliseconds. A value of 0 disables this feature.
     #[structopt(long)]
     pub keep_alive_interval_msec: Option<u32>,
+    /// Interval to check the liveness of the peers this node watches at, i.e. the members of its
+    /// section as an Elder, or its Elders as an Adult, pinging those it hasn't heard from since.
+    ///
+    /// The interval is in milliseconds. A value of 0 disables liveness checking.
+    #[structopt(long, default_value = "5000")]
+    pub liveness_ping_interval_msec: u64,
+    /// How long without hearing from a watched peer before it's suspected to be down, in
+    /// milliseconds.
+    #[structopt(long, default_value = "15000")]
+    pub liveness_suspect_after_msec: u64,
+    /// How long without hearing from a watched peer before it's deemed down, in milliseconds.
+    /// As an Elder, peers deemed down are reported as dysfunctional, to be voted offline.
+    #[structopt(long, default_value = "45000")]
+    pub liveness_dead_after_msec: u64,
     /// Duration of a UPnP port mapping.
     #[structopt(long)]
     pub upnp_lease_duration: Option<u32>,
+    /// How long to wait for before trying to join the network again, after failing to, in
+    /// seconds. The delay doubles with each failed attempt, up to `--bootstrap-retry-max-delay-sec`.
+    #[structopt(long, default_value = "10")]
+    pub bootstrap_retry_initial_delay_sec: u64,
+    /// Maximum delay between two attempts to join the network, in seconds.
+    #[structopt(long, default_value = "300")]
+    pub bootstrap_retry_max_delay_sec: u64,
+    /// Number of failed attempts to join the network after which the node gives up. If none is
+    /// supplied the node keeps trying, e.g. to ride out network outages.
+    #[structopt(long)]
+    pub bootstrap_retry_max_attempts: Option<u32>,
+    /// Backend to store chunks with: "file" to hold each chunk in its own file, or "sled" to hold
+    /// them in a sled db (only available when built with the `sled-chunk-store` feature).
+    #[structopt(long, default_value = "file")]
+    pub chunk_store: ChunkStoreBackend,
+    /// Move the chunks held by the given backend into the one set with `--chunk-store`
+    /// before joining the network.
+    #[structopt(long)]
+    pub migrate_chunks_from: Option<ChunkStoreBackend>,
+    /// How durable chunk writes are by the time they're acknowledged: "sync" to flush each of
+    /// them to disk, "batched" to log the writes made within a short window and flush them at
+    /// once, or "relaxed" to leave it to the OS to flush them.
+    #[structopt(long, default_value = "batched")]
+    pub chunk_durability: ChunkDurability,
+    /// Number of write cmds per second each client can sustain, when this node is an Elder.
+    /// Cmds sent beyond it are rejected, telling the client how long to back off for.
+    /// A value of 0 disables rate limiting. When not set, the node's default rate applies.
+    #[structopt(long)]
+    pub client_write_rate: Option<f64>,
+    /// Number of write cmds each client can send at once, before being held to the sustained rate.
+    /// When not set, the node's default burst applies.
+    #[structopt(long)]
+    pub client_write_burst: Option<u32>,
+    /// Number of msgs per second each endpoint connected to the node can sustain, whichever peer
+    /// is behind it. Msgs sent beyond it are dropped, before being handled.
+    /// A value of 0 disables rate limiting.
+    #[structopt(long, default_value = "0")]
+    pub peer_msg_rate: f64,
+    /// Number of msgs each endpoint connected to the node can send at once, before being held to
+    /// the sustained rate
+    #[structopt(long, default_value = "1000")]
+    pub peer_msg_burst: u32,
+    /// Number of protocol violations, e.g. mis-signed msgs, after which an endpoint is
+    /// blacklisted: its connections are dropped and refused until it's cleared from the
+    /// blacklist. A value of 0 disables the blacklist.
+    #[structopt(long, default_value = "10")]
+    pub peer_violation_threshold: u32,
+    /// How long to wait for the destination section to approve this node when it's relocated, in
+    /// seconds, after which the relocation is started over. It's given up on after a few
+    /// attempts, for the node to rejoin the network. A value of 0 disables the timeout.
+    #[structopt(long, default_value = "120")]
+    pub relocation_timeout_sec: u64,
+    /// TOML file of settings which are applied while the node is running, whenever the file
+    /// changes: "log_level", "client_write_rate", "client_write_burst",
+    /// "replication_bytes_per_sec" and "resource_logs". Files with any other setting, or invalid
+    /// values, are rejected.
+    #[structopt(long, parse(from_os_str))]
+    pub config_file: Option<PathBuf>,
+    /// Number of the Adults holding the data a query is delegated to at once, when this node is
+    /// an Elder, the closest ones first. The next holders are queried when they time out or don't
+    /// have the data. A value of 0 disables delegation, queries then go to all the holders at once.
+    #[structopt(long, default_value = "0")]
+    pub query_delegates: usize,
+    /// How long the Adults a query is delegated to are waited on before querying the next
+    /// holders, in milliseconds.
+    #[structopt(long, default_value = "3000")]
+    pub query_delegation_timeout_msec: u64,
     #[structopt(skip)]
     #[allow(missing_docs)]
     pub network_config: NetworkConfig,
@@ -161,18 +304,25 @@ impl Config {
 
         if cmd_line_args.hard_coded_contacts.is_empty() {
             debug!("Using node connection config file as no hard coded contacts were passed in");
-            if let Ok((_, info)) = read_conn_info_from_file().await {
-                cmd_line_args.hard_coded_contacts = info;
+            if let Ok(node_config) = read_conn_info_from_file().await {
+                cmd_line_args.hard_coded_contacts = node_config.contacts;
             }
         }
 
         if cmd_line_args.genesis_key.is_none() {
             debug!("Using node connection config file as no genesis key was passed in");
-            if let Ok((genesis_key, _)) = read_conn_info_from_file().await {
-                cmd_line_args.genesis_key = Some(genesis_key);
+            if let Ok(node_config) = read_conn_info_from_file().await {
+                cmd_line_args.genesis_key = Some(hex::encode(node_config.genesis_key.to_bytes()));
+                if cmd_line_args.network_name.is_none() {
+                    cmd_line_args.network_name = node_config.network_name;
+                }
             }
         }
 
+        if std::env::var_os(REQUIRE_CHUNK_PAYMENTS_ENV).is_some() {
+            cmd_line_args.require_chunk_payments = true;
+        }
+
         config.merge(cmd_line_args);
 
         config.clear_data_from_disk().await.unwrap_or_else(|_| {
@@ -211,6 +361,18 @@ impl Config {
                 .to_string());
         }
 
+        if let Some(admin_addr) = self.admin_addr {
+            if !admin_addr.ip().is_loopback() {
+                return Err(
+                    "--admin-addr must use a loopback IP, as operator tools connecting to the \
+                    admin socket aren't authenticated."
+                        .to_string(),
+                );
+            }
+        }
+
+        let _target = self.notifier_target()?;
+
         Ok(())
     }
 
@@ -253,6 +415,12 @@ impl Config {
             self.local_addr = Some(local_addr);
         }
 
+        if !config.listen_addrs.is_empty() {
+            self.listen_addrs = config.listen_addrs;
+        }
+
+        self.ip_preference = config.ip_preference;
+
         if let Some(public_addr) = config.public_addr {
             self.public_addr = config.public_addr;
             self.network_config.external_port = Some(public_addr.port());
@@ -260,31 +428,99 @@ impl Config {
         }
 
         self.network_config.forward_port = !config.skip_auto_port_forwarding;
+        self.lan_discovery = config.lan_discovery || self.lan_discovery;
+        self.require_chunk_payments = config.require_chunk_payments || self.require_chunk_payments;
+
+        if let Some(port) = config.lan_discovery_port {
+            self.lan_discovery_port = Some(port);
+        }
+
+        if let Some(admin_addr) = config.admin_addr {
+            self.admin_addr = Some(admin_addr);
+        }
+
+        if let Some(notify_url) = &config.notify_url {
+            self.notify_url = Some(notify_url.clone());
+        }
+
+        if let Some(notify_socket) = &config.notify_socket {
+            self.notify_socket = Some(notify_socket.clone());
+        }
+
+        if let Some(passphrase_file) = &config.identity_passphrase_file {
+            self.identity_passphrase_file = Some(passphrase_file.clone());
+        }
 
         if !config.hard_coded_contacts.is_empty() {
             self.hard_coded_contacts = config.hard_coded_contacts;
         }
 
+        if let Some(location) = &config.bootstrap_contacts {
+            self.bootstrap_contacts = Some(location.clone());
+        }
+
         if config.genesis_key.is_some() {
             self.genesis_key = config.genesis_key;
         }
 
+        if config.network_name.is_some() {
+            self.network_name = config.network_name;
+        }
+
         if let Some(max_msg_size) = config.max_msg_size_allowed {
             self.max_msg_size_allowed = Some(max_msg_size);
         }
 
         if let Some(idle_timeout) = config.idle_timeout_msec {
             self.idle_timeout_msec = Some(idle_timeout);
+            self.network_config.idle_timeout = Some(Duration::from_millis(idle_timeout));
         }
 
         if let Some(keep_alive) = config.keep_alive_interval_msec {
             self.keep_alive_interval_msec = Some(keep_alive);
+            self.network_config.keep_alive_interval = if keep_alive == 0 {
+                None
+            } else {
+                Some(Duration::from_millis(keep_alive as u64))
+            };
         }
 
+        self.liveness_ping_interval_msec = config.liveness_ping_interval_msec;
+        self.liveness_suspect_after_msec = config.liveness_suspect_after_msec;
+        self.liveness_dead_after_msec = config.liveness_dead_after_msec;
+
         if let Some(upnp_lease_duration) = config.upnp_lease_duration {
             self.network_config.upnp_lease_duration =
                 Some(Duration::from_millis(upnp_lease_duration as u64));
         }
+
+        self.bootstrap_retry_initial_delay_sec = config.bootstrap_retry_initial_delay_sec;
+        self.bootstrap_retry_max_delay_sec = config.bootstrap_retry_max_delay_sec;
+        if let Some(max_attempts) = config.bootstrap_retry_max_attempts {
+            self.bootstrap_retry_max_attempts = Some(max_attempts);
+        }
+
+        self.chunk_store = config.chunk_store;
+        self.chunk_durability = config.chunk_durability;
+        if let Some(rate) = config.client_write_rate {
+            self.client_write_rate = Some(rate);
+        }
+        if let Some(burst) = config.client_write_burst {
+            self.client_write_burst = Some(burst);
+        }
+        self.peer_msg_rate = config.peer_msg_rate;
+        self.peer_msg_burst = config.peer_msg_burst;
+        self.peer_violation_threshold = config.peer_violation_threshold;
+        self.relocation_timeout_sec = config.relocation_timeout_sec;
+        if let Some(config_file) = &config.config_file {
+            self.config_file = Some(config_file.clone());
+        }
+        self.query_delegates = config.query_delegates;
+        self.query_delegation_timeout_msec = config.query_delegation_timeout_msec;
+
+        if let Some(migrate_chunks_from) = config.migrate_chunks_from {
+            self.migrate_chunks_from = Some(migrate_chunks_from);
+        }
     }
 
     /// The address to be credited when this node farms SafeCoin.
@@ -302,6 +538,107 @@ impl Config {
         DEFAULT_MAX_CAPACITY
     }
 
+    /// Address to listen on, defaulting to the unspecified address of the preferred IP version.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+            .unwrap_or_else(|| self.ip_preference.unspecified_addr())
+    }
+
+    /// Hard coded contacts, the ones of the preferred IP version first.
+    pub fn sorted_contacts(&self) -> Vec<SocketAddr> {
+        self.ip_preference
+            .sort_contacts(self.hard_coded_contacts.iter().copied())
+    }
+
+    /// UDP port to look for peers on the local network at, if LAN discovery is enabled.
+    pub fn lan_discovery_port(&self) -> Option<u16> {
+        self.lan_discovery.then(|| {
+            self.lan_discovery_port
+                .unwrap_or(DEFAULT_LAN_DISCOVERY_PORT)
+        })
+    }
+
+    /// Name of the network this node only exchanges messages within, if any.
+    pub fn network_name(&self) -> Option<NetworkName> {
+        self.network_name.as_deref().map(NetworkName::new)
+    }
+
+    /// How chunks are to be stored on this node.
+    pub fn chunk_store_config(&self) -> ChunkStoreConfig {
+        ChunkStoreConfig {
+            backend: self.chunk_store,
+            durability: self.chunk_durability,
+        }
+    }
+
+    /// Limits on the rate of write cmds accepted from each client, when this node is an Elder.
+    pub fn client_rate_limits(&self) -> ClientRateLimits {
+        let defaults = ClientRateLimits::default();
+        ClientRateLimits {
+            ops_per_sec: self.client_write_rate.unwrap_or(defaults.ops_per_sec),
+            burst: self.client_write_burst.unwrap_or(defaults.burst),
+        }
+    }
+
+    /// Limits on the rate of msgs accepted from each endpoint connected to the node.
+    pub fn inbound_rate_limits(&self) -> InboundRateLimits {
+        InboundRateLimits {
+            msgs_per_sec: self.peer_msg_rate,
+            burst: self.peer_msg_burst,
+        }
+    }
+
+    /// Tuning of the delegation of data queries to the Adults, when this node is an Elder.
+    pub fn query_delegation_config(&self) -> QueryDelegationConfig {
+        QueryDelegationConfig {
+            delegates: self.query_delegates,
+            timeout: Duration::from_millis(self.query_delegation_timeout_msec),
+        }
+    }
+
+    /// Where the membership decisions and Elder changes of the section are notified to, if
+    /// anywhere, returning why the target set isn't supported otherwise.
+    pub fn notifier_target(&self) -> Result<Option<NotifierTarget>, String> {
+        match (&self.notify_url, &self.notify_socket) {
+            (None, None) => Ok(None),
+            (Some(url), None) => NotifierTarget::http(url).map(Some),
+            #[cfg(unix)]
+            (None, Some(path)) => Ok(Some(NotifierTarget::UnixSocket(path.clone()))),
+            #[cfg(not(unix))]
+            (None, Some(_)) => Err("--notify-socket is only supported on Unix".to_string()),
+            (Some(_), Some(_)) => {
+                Err("Cannot specify both --notify-url and --notify-socket".to_string())
+            }
+        }
+    }
+
+    /// Tuning of the keep-alive pings and liveness detection of the peers this node watches.
+    pub fn liveness_config(&self) -> LivenessConfig {
+        LivenessConfig {
+            ping_interval: Duration::from_millis(self.liveness_ping_interval_msec),
+            suspect_after: Duration::from_millis(self.liveness_suspect_after_msec),
+            dead_after: Duration::from_millis(self.liveness_dead_after_msec),
+        }
+    }
+
+    /// Retries of the attempts to join the network, as configured.
+    pub fn bootstrap_retries(&self) -> BootstrapRetries {
+        BootstrapRetries::new(
+            Duration::from_secs(self.bootstrap_retry_initial_delay_sec),
+            Duration::from_secs(self.bootstrap_retry_max_delay_sec),
+            self.bootstrap_retry_max_attempts,
+        )
+    }
+
+    /// How long a relocation may take before it's abandoned, if at all.
+    pub fn relocation_timeout(&self) -> Option<Duration> {
+        if self.relocation_timeout_sec == 0 {
+            None
+        } else {
+            Some(Duration::from_secs(self.relocation_timeout_sec))
+        }
+    }
+
     /// Root directory for dbs and cached state. If not set, it defaults to
     /// `DEFAULT_ROOT_DIR_NAME` within the project's data directory (see `Config::root_dir` for the
     /// directories on each platform).
@@ -453,29 +790,34 @@ fn parse_public_addr(public_addr: &str) -> Result<SocketAddr, String> {
 /// Overwrites connection info at file.
 ///
 /// The file is written to the `current_bin_dir()` with the appropriate file name.
-pub async fn set_connection_info(genesis_key: bls::PublicKey, contact: SocketAddr) -> Result<()> {
-    let genesis_key_hex = hex::encode(genesis_key.to_bytes());
-    write_file(CONNECTION_INFO_FILE, &(genesis_key_hex, vec![contact])).await
+pub async fn set_connection_info(
+    genesis_key: bls::PublicKey,
+    contact: SocketAddr,
+    network_name: Option<String>,
+) -> Result<()> {
+    let mut node_config = NodeConfig::new(genesis_key, BTreeSet::from([contact]));
+    node_config.network_name = network_name;
+    write_conn_info_to_file(&node_config).await
 }
 
 /// Writes connection info to file for use by clients (and joining nodes when local network).
 ///
 /// The file is written to the `current_bin_dir()` with the appropriate file name.
 pub async fn add_connection_info(contact: SocketAddr) -> Result<()> {
-    let (genesis_key_hex, mut bootstrap_nodes) = read_conn_info_from_file().await?;
-    let _prev = bootstrap_nodes.insert(contact);
-    write_file(CONNECTION_INFO_FILE, &(genesis_key_hex, bootstrap_nodes)).await
+    let mut node_config = read_conn_info_from_file().await?;
+    let _prev = node_config.contacts.insert(contact);
+    write_conn_info_to_file(&node_config).await
 }
 
 /// Reads the default node config file.
-async fn read_conn_info_from_file() -> Result<(String, BTreeSet<SocketAddr>)> {
+async fn read_conn_info_from_file() -> Result<NodeConfig> {
     let path = project_dirs()?.join(CONNECTION_INFO_FILE);
 
     match fs::read(&path).await {
         Ok(content) => {
             debug!("Reading connection info from {}", path.display());
-            let config = serde_json::from_slice(&content)?;
-            Ok(config)
+            let node_config = NodeConfig::from_json(&content)?;
+            Ok(node_config)
         }
         Err(error) => {
             if error.kind() == std::io::ErrorKind::NotFound {
@@ -486,6 +828,18 @@ async fn read_conn_info_from_file() -> Result<(String, BTreeSet<SocketAddr>)> {
     }
 }
 
+// Writes the node config file, always in the latest format.
+async fn write_conn_info_to_file(node_config: &NodeConfig) -> Result<()> {
+    let project_dirs = project_dirs()?;
+    fs::create_dir_all(project_dirs.clone()).await?;
+
+    let path = project_dirs.join(CONNECTION_INFO_FILE);
+    let mut file = File::create(&path).await?;
+    file.write_all(node_config.to_json()?.as_bytes()).await?;
+    file.sync_all().await?;
+    Ok(())
+}
+
 async fn write_file<T: ?Sized>(file: &str, config: &T) -> Result<()>
 where
     T: Serialize,
@@ -516,7 +870,62 @@ fn smoke() {
     // NOTE: IF this value is being changed due to a change in the config,
     // the change in config also be handled in Config::merge()
     // and in examples/config_handling.rs
-    let expected_size = 456;
+    let expected_size = 728;
 
     assert_eq!(std::mem::size_of::<Config>(), expected_size);
 }
+
+#[test]
+fn ipv6_contacts() -> Result<(), String> {
+    let contacts: BTreeSet<SocketAddr> =
+        serde_json::from_str(r#"["127.0.0.1:12000", "[2001:db8::1]:12000"]"#)
+            .map_err(|err| err.to_string())?;
+    let config = Config {
+        hard_coded_contacts: contacts.clone(),
+        ip_preference: IpPreference::V6,
+        ..Default::default()
+    };
+    assert_eq!(
+        config.sorted_contacts(),
+        vec![
+            "[2001:db8::1]:12000".parse().map_err(|_| "invalid addr")?,
+            "127.0.0.1:12000".parse().map_err(|_| "invalid addr")?,
+        ]
+    );
+    assert!(config.local_addr().is_ipv6());
+
+    // connection info written to disk is read back the same
+    let conn_info = NodeConfig::new(bls::SecretKey::random().public_key(), contacts);
+    let serialized = conn_info.to_json().map_err(|err| err.to_string())?;
+    assert_eq!(
+        NodeConfig::from_json(serialized.as_bytes()).map_err(|err| err.to_string())?,
+        conn_info
+    );
+
+    assert!(parse_public_addr("[2001:db8::1]:12000").is_ok());
+    assert!(parse_public_addr("[::1]:12000").is_err());
+    assert!(parse_public_addr("[::]:12000").is_err());
+
+    Ok(())
+}
+
+#[test]
+fn notifier_targets() -> Result<(), String> {
+    let mut config = Config {
+        notify_url: Some("http://127.0.0.1:8080/membership".to_string()),
+        ..Default::default()
+    };
+    assert!(matches!(
+        config.notifier_target()?,
+        Some(NotifierTarget::Http(_))
+    ));
+
+    config.notify_url = Some("https://dashboard.example.com/membership".to_string());
+    assert!(config.notifier_target().is_err());
+
+    config.notify_url = Some("http://127.0.0.1:8080".to_string());
+    config.notify_socket = Some(PathBuf::from("/run/sn_node/notify.sock"));
+    assert!(config.validate().is_err());
+
+    Ok(())
+}
@@ -9,6 +9,9 @@
 use crate::node::{Error, Result};
 use ed25519_dalek::{Keypair, PublicKey, KEYPAIR_LENGTH};
 use hex::{decode, encode};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sn_interface::types::utils::{passphrase_mac, passphrase_secret_key, verify_passphrase_mac};
 use std::path::Path;
 use tokio::fs;
 
@@ -18,9 +21,41 @@ const REWARD_PUBLIC_KEY_FILENAME: &str = "reward_public_key";
 const REWARD_SECRET_KEY_FILENAME: &str = "reward_secret_key";
 
 const NETWORK_KEYPAIR_FILENAME: &str = "network_keypair";
+// Filename for storing the node's network keypair encrypted with a passphrase
+const ENCRYPTED_NETWORK_KEYPAIR_FILENAME: &str = "network_keypair.enc";
 
-/// Writes the network keypair to disk.
+#[derive(Serialize, Deserialize)]
+struct EncryptedKeypair {
+    salt: [u8; 32],
+    // Serialised `bls::Ciphertext` of the keypair
+    ciphertext: Vec<u8>,
+    // MAC of the ciphertext, telling a wrong passphrase apart
+    mac: [u8; 32],
+}
+
+/// Writes the network keypair to disk, encrypted with a key derived from the passphrase if one
+/// is given. Once encrypted, no plaintext copy of the keypair is left behind.
 pub(crate) async fn store_network_keypair(
+    root_dir: &Path,
+    keypair: &Keypair,
+    passphrase: Option<&str>,
+) -> Result<()> {
+    match passphrase {
+        Some(passphrase) => {
+            store_encrypted_network_keypair(root_dir, keypair, passphrase).await?;
+            let keypair_path = root_dir.join(NETWORK_KEYPAIR_FILENAME);
+            if keypair_path.is_file() {
+                fs::remove_file(keypair_path).await?;
+            }
+        }
+        None => store_plaintext_network_keypair(root_dir, keypair.to_bytes()).await?,
+    }
+
+    Ok(())
+}
+
+// Writes the network keypair to disk, hex-encoded
+async fn store_plaintext_network_keypair(
     root_dir: &Path,
     keypair_as_bytes: [u8; KEYPAIR_LENGTH],
 ) -> Result<()> {
@@ -58,6 +93,128 @@ pub(crate) async fn get_network_keypair(root_dir: &Path) -> Result<Option<Keypai
     Ok(Some(keypair))
 }
 
+// Writes the network keypair to disk, encrypted with a key derived from the passphrase. It's
+// written to a temporary file first, for a node stopped in between not to lose its keypair.
+async fn store_encrypted_network_keypair(
+    root_dir: &Path,
+    keypair: &Keypair,
+    passphrase: &str,
+) -> Result<()> {
+    let salt: [u8; 32] = rand::thread_rng().gen();
+    let secret_key = passphrase_key(passphrase, &salt)?;
+    let serialisation_error = |err: bincode::Error| {
+        Error::Configuration(format!(
+            "couldn't serialise encrypted network keypair: {}",
+            err
+        ))
+    };
+    let ciphertext = bincode::serialize(&secret_key.public_key().encrypt(keypair.to_bytes()))
+        .map_err(serialisation_error)?;
+    let encrypted = EncryptedKeypair {
+        salt,
+        mac: passphrase_mac(&secret_key, &ciphertext).map_err(|err| {
+            Error::Configuration(format!("couldn't authenticate network keypair: {}", err))
+        })?,
+        ciphertext,
+    };
+    let serialised = bincode::serialize(&encrypted).map_err(serialisation_error)?;
+
+    let keypair_path = root_dir.join(ENCRYPTED_NETWORK_KEYPAIR_FILENAME);
+    let tmp_path = keypair_path.with_extension("tmp");
+    fs::write(&tmp_path, encode(serialised)).await?;
+    fs::rename(tmp_path, keypair_path).await?;
+
+    Ok(())
+}
+
+/// Returns Some(KeyPair) decrypted with the passphrase, or None if file doesn't exist.
+pub(crate) async fn get_encrypted_network_keypair(
+    root_dir: &Path,
+    passphrase: &str,
+) -> Result<Option<Keypair>> {
+    let path = root_dir.join(ENCRYPTED_NETWORK_KEYPAIR_FILENAME);
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let encrypted_hex_bytes = fs::read(&path).await?;
+    let encrypted: EncryptedKeypair = decode(encrypted_hex_bytes)
+        .map_err(|err| err.to_string())
+        .and_then(|bytes| bincode::deserialize(&bytes).map_err(|err| err.to_string()))
+        .map_err(|err| {
+            Error::Configuration(format!(
+                "invalid encrypted network keypair read from {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+
+    let secret_key = passphrase_key(passphrase, &encrypted.salt)?;
+    if !verify_passphrase_mac(&secret_key, &encrypted.ciphertext, &encrypted.mac) {
+        return Err(Error::Configuration(format!(
+            "couldn't decrypt network keypair read from {}, the passphrase is wrong",
+            path.display()
+        )));
+    }
+    let keypair_bytes = bincode::deserialize::<bls::Ciphertext>(&encrypted.ciphertext)
+        .ok()
+        .and_then(|ciphertext| secret_key.decrypt(&ciphertext))
+        .ok_or_else(|| {
+            Error::Configuration(format!(
+                "couldn't decrypt network keypair read from {}",
+                path.display()
+            ))
+        })?;
+
+    let keypair = Keypair::from_bytes(&keypair_bytes).map_err(|err| {
+        Error::Configuration(format!(
+            "invalid network keypair bytes read from {}: {}",
+            path.display(),
+            err
+        ))
+    })?;
+    // the public half isn't checked against the secret one when reading a keypair
+    if PublicKey::from(&keypair.secret) != keypair.public {
+        return Err(Error::Configuration(format!(
+            "mismatched network keypair read from {}",
+            path.display()
+        )));
+    }
+
+    Ok(Some(keypair))
+}
+
+/// Reads the passphrase the network keypair is encrypted with from the given file, ignoring
+/// trailing line breaks.
+pub(crate) async fn read_passphrase(path: &Path) -> Result<String> {
+    let passphrase = fs::read_to_string(path).await.map_err(|err| {
+        Error::Configuration(format!(
+            "couldn't read passphrase from {}: {}",
+            path.display(),
+            err
+        ))
+    })?;
+    let passphrase = passphrase.trim_end_matches(&['\r', '\n'][..]);
+    if passphrase.is_empty() {
+        return Err(Error::Configuration(format!(
+            "empty passphrase read from {}",
+            path.display()
+        )));
+    }
+
+    Ok(passphrase.to_string())
+}
+
+// Derive the BLS key a network keypair is encrypted with from the passphrase and salt
+fn passphrase_key(passphrase: &str, salt: &[u8; 32]) -> Result<bls::SecretKey> {
+    passphrase_secret_key(passphrase, salt).map_err(|err| {
+        Error::Configuration(format!(
+            "couldn't derive the network keypair key from the passphrase: {}",
+            err
+        ))
+    })
+}
+
 /// Writes the public and secret key (hex-encoded) to different locations at disk.
 pub(crate) async fn store_new_reward_keypair(root_dir: &Path, keypair: &Keypair) -> Result<()> {
     let secret_key_path = root_dir.join(REWARD_SECRET_KEY_FILENAME);
@@ -98,7 +255,8 @@ pub(crate) async fn get_reward_pk(root_dir: &Path) -> Result<Option<PublicKey>>
 #[cfg(test)]
 mod test {
     use super::{
-        get_network_keypair, get_reward_pk, store_network_keypair, store_new_reward_keypair,
+        get_encrypted_network_keypair, get_network_keypair, get_reward_pk, store_network_keypair,
+        store_new_reward_keypair,
     };
     use eyre::{eyre, Result};
     use rand_07::rngs::OsRng;
@@ -129,7 +287,7 @@ mod test {
         let keypair_result = get_network_keypair(root_dir).await?;
         assert!(keypair_result.is_none());
 
-        store_network_keypair(root_dir, keypair.to_bytes()).await?;
+        store_network_keypair(root_dir, &keypair, None).await?;
         let keypair_result = get_network_keypair(root_dir).await?;
         if let Some(kp) = keypair_result {
             assert_eq!(kp.public, keypair.public);
@@ -139,6 +297,34 @@ mod test {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn encrypted_keypair_is_only_read_with_its_passphrase() -> Result<()> {
+        let mut rng = OsRng;
+        let keypair = ed25519_dalek::Keypair::generate(&mut rng);
+
+        let root = create_temp_root()?;
+        let root_dir = root.path();
+
+        assert!(get_encrypted_network_keypair(root_dir, "passphrase")
+            .await?
+            .is_none());
+
+        store_network_keypair(root_dir, &keypair, None).await?;
+        store_network_keypair(root_dir, &keypair, Some("passphrase")).await?;
+        // the plaintext copy is gone once the keypair is encrypted
+        assert!(get_network_keypair(root_dir).await?.is_none());
+        assert!(get_encrypted_network_keypair(root_dir, "other passphrase")
+            .await
+            .is_err());
+
+        let keypair_result = get_encrypted_network_keypair(root_dir, "passphrase").await?;
+        let kp = keypair_result.ok_or_else(|| eyre!("Network keypair was not read from file"))?;
+        assert_eq!(kp.public, keypair.public);
+        assert_eq!(kp.secret.to_bytes(), keypair.secret.to_bytes());
+
+        Ok(())
+    }
+
     // creates a temp dir
     fn create_temp_root() -> Result<TempDir> {
         tempdir().map_err(|e| eyre!("Failed to create temp dir: {}", e))
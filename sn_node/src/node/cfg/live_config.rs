@@ -0,0 +1,134 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::{ClientRateLimits, Error, Result};
+
+use serde::{Deserialize, Serialize};
+use std::{path::Path, str::FromStr};
+use tracing::Level;
+
+/// Settings of a running node, read from the TOML file passed with `--config-file`, which are
+/// applied without restarting the node whenever the file changes.
+///
+/// Only the settings below can be changed this way; a file with any other key is rejected as a
+/// whole, as are invalid values, the node then carrying on with the settings it had. Settings
+/// left out keep their current value.
+///
+/// ```toml
+/// log_level = "debug"
+/// client_write_rate = 50.0
+/// client_write_burst = 500
+/// replication_bytes_per_sec = 10485760
+/// resource_logs = true
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LiveConfig {
+    /// Level of the logs of the node: "error", "warn", "info", "debug" or "trace"
+    pub log_level: Option<String>,
+    /// Number of write cmds per second each client can sustain, when this node is an Elder.
+    /// A value of 0 disables rate limiting.
+    pub client_write_rate: Option<f64>,
+    /// Number of write cmds each client can send at once, before being held to the sustained rate
+    pub client_write_burst: Option<u32>,
+    /// Cap on the bytes per second of data the node replicates to its peers.
+    /// A value of 0 removes the cap.
+    pub replication_bytes_per_sec: Option<u64>,
+    /// Whether the resource usage of the node is printed to stdout
+    pub resource_logs: Option<bool>,
+}
+
+impl LiveConfig {
+    /// Reads and validates the settings from the given file.
+    pub async fn read_from_file(path: &Path) -> Result<Self> {
+        let contents = tokio::fs::read_to_string(path).await?;
+        Self::from_str(&contents).map_err(|reason| Error::InvalidConfigFile {
+            path: path.to_path_buf(),
+            reason,
+        })
+    }
+
+    /// Level of the logs of the node, if set.
+    pub fn log_level(&self) -> Option<Level> {
+        // validated when parsed
+        self.log_level
+            .as_deref()
+            .and_then(|level| Level::from_str(level).ok())
+    }
+
+    /// Limits on the rate of write cmds accepted from each client, updated from the given ones
+    /// with the settings which are set.
+    pub fn client_rate_limits(&self, current: ClientRateLimits) -> ClientRateLimits {
+        ClientRateLimits {
+            ops_per_sec: self.client_write_rate.unwrap_or(current.ops_per_sec),
+            burst: self.client_write_burst.unwrap_or(current.burst),
+        }
+    }
+
+    fn validate(&self) -> std::result::Result<(), String> {
+        if let Some(level) = &self.log_level {
+            let _ =
+                Level::from_str(level).map_err(|_| format!("invalid log_level \"{}\"", level))?;
+        }
+        if let Some(rate) = self.client_write_rate {
+            if !rate.is_finite() || rate < 0.0 {
+                return Err(format!("invalid client_write_rate {}", rate));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for LiveConfig {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let config: Self = toml::from_str(s).map_err(|error| error.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LiveConfig;
+    use crate::node::ClientRateLimits;
+
+    use std::str::FromStr;
+    use tracing::Level;
+
+    #[test]
+    fn only_valid_hot_reloadable_settings_are_accepted() {
+        let config = LiveConfig::from_str(
+            "log_level = \"debug\"\nclient_write_rate = 50.0\nresource_logs = true\n",
+        )
+        .expect("valid config");
+        assert_eq!(config.log_level(), Some(Level::DEBUG));
+        assert_eq!(config.resource_logs, Some(true));
+        assert_eq!(config.replication_bytes_per_sec, None);
+
+        // settings left out keep their current value
+        let current = ClientRateLimits {
+            ops_per_sec: 20.0,
+            burst: 200,
+        };
+        assert_eq!(
+            config.client_rate_limits(current),
+            ClientRateLimits {
+                ops_per_sec: 50.0,
+                burst: 200,
+            }
+        );
+
+        // settings needing a restart are rejected, as are invalid values
+        assert!(LiveConfig::from_str("local_addr = \"127.0.0.1:0\"").is_err());
+        assert!(LiveConfig::from_str("log_level = \"loud\"").is_err());
+        assert!(LiveConfig::from_str("client_write_rate = -1.0").is_err());
+        assert!(LiveConfig::from_str("client_write_burst = \"many\"").is_err());
+    }
+}
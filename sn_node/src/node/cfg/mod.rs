@@ -9,5 +9,17 @@
 /// Configuration
 pub mod config_handler;
 
+/// Settings changed while the node is running
+pub mod live_config;
+
+/// Retries of the attempts to join the network
+pub mod bootstrap_retry;
+
 /// File storage for keypairs
 pub(crate) mod keypair_storage;
+
+/// File storage for the peers bootstrapped through
+pub(crate) mod bootstrap_cache;
+
+/// Contacts to bootstrap through read from a file or a seed list
+pub(crate) mod bootstrap_contacts;
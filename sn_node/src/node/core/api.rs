@@ -6,14 +6,15 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{delivery_group, Comm, Node};
+use super::{delivery_group, ClientRateLimits, Comm, Node, QueryDelegationConfig};
 
 use crate::node::{
     api::cmds::Cmd,
+    cfg::keypair_storage::store_network_keypair,
     error::{Error, Result},
     Event,
 };
-use crate::UsedSpace;
+use crate::{ChunkStoreConfig, UsedSpace};
 use sn_interface::messaging::WireMsg;
 use sn_interface::network_knowledge::{
     NetworkKnowledge, NodeInfo, SectionAuthorityProvider, SectionKeyShare,
@@ -33,6 +34,9 @@ impl Node {
         event_tx: mpsc::Sender<Event>,
         used_space: UsedSpace,
         root_storage_dir: PathBuf,
+        chunk_store_config: ChunkStoreConfig,
+        client_rate_limits: ClientRateLimits,
+        query_delegation: QueryDelegationConfig,
         genesis_sk_set: bls::SecretKeySet,
     ) -> Result<Self> {
         // make sure the Node has the correct local addr as Comm
@@ -48,6 +52,9 @@ impl Node {
             event_tx,
             used_space,
             root_storage_dir,
+            chunk_store_config,
+            client_rate_limits,
+            query_delegation,
         )
         .await
     }
@@ -63,8 +70,19 @@ impl Node {
         // make sure the new Node has the correct local addr as Comm
         new_node.addr = self.comm.our_connection_info();
 
-        let mut our_node = self.info.write().await;
-        *our_node = new_node;
+        let new_keypair = new_node.keypair.clone();
+        *self.info.write().await = new_node;
+
+        // the node is to rejoin with its relocated name if it restarts
+        if let Err(err) = store_network_keypair(
+            &self.root_storage_dir,
+            &new_keypair,
+            self.identity_passphrase.as_deref(),
+        )
+        .await
+        {
+            error!("Failed to store our relocated network keypair: {:?}", err);
+        }
 
         Ok(())
     }
@@ -93,6 +111,11 @@ impl Node {
         self.comm.our_connection_info()
     }
 
+    /// The local addresses we accept connections at
+    pub(crate) fn listening_addrs(&self) -> Vec<SocketAddr> {
+        self.comm.listening_addrs()
+    }
+
     /// Returns the current BLS public key set
     pub(crate) async fn public_key_set(&self) -> Result<bls::PublicKeySet> {
         Ok(self.key_share().await?.public_key_set)
@@ -125,11 +148,23 @@ impl Node {
         }
     }
 
+    // Sends the event without waiting, for when the events may not be read anymore, e.g. once
+    // the node is shutting down
+    pub(crate) fn try_send_event(&self, event: Event) {
+        if let Err(error) = self.event_tx.try_send(event) {
+            warn!("Dropping event: {:?}", error);
+        }
+    }
+
     // ----------------------------------------------------------------------------------------
     //   ---------------------------------- Mut ------------------------------------------
     // ----------------------------------------------------------------------------------------
 
     pub(crate) async fn handle_timeout(&self, token: u64) -> Result<Vec<Cmd>> {
+        if let Some(cmds) = self.handle_relocation_timeout(token).await? {
+            return Ok(cmds);
+        }
+
         self.dkg_voter.handle_timeout(
             &self.info.read().await.clone(),
             token,
@@ -184,7 +219,8 @@ impl Node {
 
     // Generate a new section info based on the current set of members, but
     // excluding the ones in the provided list. And if the outcome list of candidates
-    // differs from the current elders, trigger a DKG.
+    // differs from the current elders, or the number of copies of data is to change,
+    // trigger a DKG.
     pub(crate) async fn promote_and_demote_elders_except(
         &self,
         excluded_names: &BTreeSet<XorName>,
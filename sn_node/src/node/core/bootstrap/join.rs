@@ -8,31 +8,36 @@
 
 use super::{read_prefix_map_from_disk, UsedRecipientSaps};
 use crate::node::{
-    core::{Comm, DeliveryStatus, MsgEvent},
-    messages::WireMsgUtils,
+    core::{our_version, Comm, DeliveryStatus, MsgEvent, SigningPool},
     Error, Result,
 };
 use sn_interface::messaging::{
     system::{
-        JoinRejectionReason, JoinRequest, JoinResponse, ResourceProofResponse, SectionAuth,
-        SystemMsg,
+        handshake_msgs, Capabilities, FeatureActivation, JoinRejectionReason, JoinRequest,
+        JoinResponse, ReachabilityAttestation, ResourceProofResponse, SectionAuth, SystemMsg,
+        MAX_HANDSHAKE_FRAME_SIZE, PROTOCOL_VERSION,
     },
     AuthKind, DstLocation, MsgType, NodeAuth, WireMsg,
 };
 use sn_interface::network_knowledge::{
-    prefix_map::NetworkPrefixMap, NetworkKnowledge, NodeInfo, SectionAuthUtils, MIN_ADULT_AGE,
+    prefix_map::NetworkPrefixMap, supermajority, NetworkKnowledge, NodeInfo, SectionAuthUtils,
+    MIN_ADULT_AGE,
 };
 
 use sn_interface::types::{keys::ed25519, log_markers::LogMarker, Peer};
 
 use backoff::{backoff::Backoff, ExponentialBackoff};
 use bls::PublicKey as BlsPublicKey;
+use ed25519_dalek::Signature;
 use futures::future;
 use resource_proof::ResourceProof;
-use std::net::SocketAddr;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::SocketAddr,
+};
 use tokio::{sync::mpsc, time::sleep, time::Duration};
 use tracing::Instrument;
-use xor_name::Prefix;
+use xor_name::{Prefix, XorName};
 
 /// Join the network as new node.
 ///
@@ -42,10 +47,15 @@ use xor_name::Prefix;
 pub(crate) async fn join_network(
     node: NodeInfo,
     comm: &Comm,
+    signing_pool: &SigningPool,
     incoming_msgs: &mut mpsc::Receiver<MsgEvent>,
     bootstrap_addr: SocketAddr,
     genesis_key: BlsPublicKey,
-) -> Result<(NodeInfo, NetworkKnowledge)> {
+) -> Result<(
+    NodeInfo,
+    NetworkKnowledge,
+    Vec<SectionAuth<FeatureActivation>>,
+)> {
     let (outgoing_msgs_sender, outgoing_msgs_receiver) = mpsc::channel(1);
 
     let span = trace_span!("bootstrap");
@@ -53,7 +63,13 @@ pub(crate) async fn join_network(
     // Read prefix map from cache if available
     let prefix_map = read_prefix_map_from_disk(genesis_key).await?;
 
-    let state = Join::new(node, outgoing_msgs_sender, incoming_msgs, prefix_map);
+    let state = Join::new(
+        node,
+        outgoing_msgs_sender,
+        incoming_msgs,
+        prefix_map,
+        signing_pool.clone(),
+    );
 
     future::join(
         state.run(bootstrap_addr),
@@ -74,6 +90,19 @@ struct Join<'a> {
     prefix_map: NetworkPrefixMap,
     backoff: ExponentialBackoff,
     aggregated: bool,
+    // Attestations of the challenges Elders dialled us back with, by Elder, to send along with
+    // the resource proofs they challenge us for
+    attestations: BTreeMap<XorName, ReachabilityAttestation>,
+    // Resource challenges received ahead of the challenge their Elder dials us back with
+    resource_challenges: BTreeMap<XorName, (Peer, ResourceChallenge)>,
+    signing_pool: SigningPool,
+}
+
+struct ResourceChallenge {
+    data_size: usize,
+    difficulty: u8,
+    nonce: [u8; 32],
+    nonce_signature: Signature,
 }
 
 impl<'a> Join<'a> {
@@ -82,6 +111,7 @@ impl<'a> Join<'a> {
         outgoing_msgs: mpsc::Sender<(WireMsg, Vec<Peer>)>,
         incoming_msgs: &'a mut mpsc::Receiver<MsgEvent>,
         prefix_map: NetworkPrefixMap,
+        signing_pool: SigningPool,
     ) -> Self {
         let mut backoff = ExponentialBackoff {
             initial_interval: Duration::from_millis(50),
@@ -101,6 +131,9 @@ impl<'a> Join<'a> {
             prefix_map,
             backoff,
             aggregated: false,
+            attestations: BTreeMap::new(),
+            resource_challenges: BTreeMap::new(),
+            signing_pool,
         }
     }
 
@@ -110,7 +143,14 @@ impl<'a> Join<'a> {
     // - `ResourceChallenge`: carry out resource proof calculation.
     // - `Approval`: returns the initial `Section` value to use by this node,
     //    completing the bootstrap.
-    async fn run(self, bootstrap_addr: SocketAddr) -> Result<(NodeInfo, NetworkKnowledge)> {
+    async fn run(
+        self,
+        bootstrap_addr: SocketAddr,
+    ) -> Result<(
+        NodeInfo,
+        NetworkKnowledge,
+        Vec<SectionAuth<FeatureActivation>>,
+    )> {
         // Use our XorName as we do not know their name or section key yet.
         let bootstrap_peer = Peer::new(self.node.name(), bootstrap_addr);
         let genesis_key = self.prefix_map.genesis_key();
@@ -131,7 +171,11 @@ impl<'a> Join<'a> {
         network_genesis_key: BlsPublicKey,
         target_section_key: BlsPublicKey,
         recipients: Vec<Peer>,
-    ) -> Result<(NodeInfo, NetworkKnowledge)> {
+    ) -> Result<(
+        NodeInfo,
+        NetworkKnowledge,
+        Vec<SectionAuth<FeatureActivation>>,
+    )> {
         // We first use genesis key as the target section key, we'll be getting
         // a response with the latest section key for us to retry with.
         // Once we are approved to join, we will make sure the SAP we receive can
@@ -144,6 +188,10 @@ impl<'a> Join<'a> {
         let join_request = JoinRequest {
             section_key,
             resource_proof_response: None,
+            genesis_key: network_genesis_key,
+            version: Some(our_version()),
+            protocol_version: PROTOCOL_VERSION,
+            capabilities: Capabilities::ours(),
         };
 
         self.send_join_requests(join_request.clone(), &recipients, section_key, false)
@@ -151,6 +199,8 @@ impl<'a> Join<'a> {
 
         // Avoid sending more than one duplicated request (with same SectionKey) to the same peer.
         let mut used_recipient_saps = UsedRecipientSaps::new();
+        // Elders which rejected us as they denylisted us
+        let mut denylisted_by = BTreeSet::new();
 
         loop {
             let (response, sender) = self.receive_join_response().await?;
@@ -166,11 +216,40 @@ impl<'a> Join<'a> {
                     error!("Network is set to not taking any new joining node, try join later.");
                     return Err(Error::TryJoinLater);
                 }
+                JoinResponse::Rejected(JoinRejectionReason::Denylisted) => {
+                    // each Elder keeps its own denylist, so we're only kept out once a
+                    // supermajority of them denylisted us
+                    let _ = denylisted_by.insert(sender.name());
+                    let elders = self
+                        .prefix_map
+                        .section_by_name(&self.node.name())
+                        .map(|sap| sap.elder_count())
+                        .unwrap_or(recipients.len());
+                    if denylisted_by.len() < supermajority(elders) {
+                        warn!(
+                            "Join request rejected by {}, which denylisted us, {}/{} Elders did",
+                            sender,
+                            denylisted_by.len(),
+                            elders
+                        );
+                        continue;
+                    }
+                    error!(
+                        "Join request rejected by {} Elders which denylisted us",
+                        denylisted_by.len()
+                    );
+                    return Err(Error::DenylistedByElders(denylisted_by.len()));
+                }
+                JoinResponse::Rejected(reason) => {
+                    error!("Join request rejected by {}: {}", sender, reason);
+                    return Err(Error::JoinRejected(reason));
+                }
                 JoinResponse::Approval {
                     section_auth,
                     genesis_key,
                     section_chain,
                     node_state,
+                    activated_features,
                 } => {
                     info!("{}", LogMarker::ReceivedJoinApproval);
                     if node_state.name != self.node.name() {
@@ -201,7 +280,7 @@ impl<'a> Join<'a> {
                         Some(self.prefix_map),
                     )?;
 
-                    return Ok((self.node, network_knowledge));
+                    return Ok((self.node, network_knowledge, activated_features));
                 }
                 JoinResponse::Retry {
                     section_auth,
@@ -293,6 +372,10 @@ impl<'a> Join<'a> {
                     let join_request = JoinRequest {
                         section_key,
                         resource_proof_response: None,
+                        genesis_key: network_genesis_key,
+                        version: Some(our_version()),
+                        protocol_version: PROTOCOL_VERSION,
+                        capabilities: Capabilities::ours(),
                     };
 
                     let new_recipients = section_auth.elders_vec();
@@ -348,11 +431,42 @@ impl<'a> Join<'a> {
                     let join_request = JoinRequest {
                         section_key,
                         resource_proof_response: None,
+                        genesis_key: network_genesis_key,
+                        version: Some(our_version()),
+                        protocol_version: PROTOCOL_VERSION,
+                        capabilities: Capabilities::ours(),
                     };
 
                     self.send_join_requests(join_request, &new_recipients, section_key, true)
                         .await?;
                 }
+                JoinResponse::AddrChallenge {
+                    addr,
+                    nonce,
+                    nonce_signature,
+                } => {
+                    // sent over a connection of the Elder's own, whose address is not the one
+                    // it listens at, so the proof is sent once its resource challenge is in
+                    trace!("Received an AddrChallenge from {}", sender);
+                    let attestation = ReachabilityAttestation::new(
+                        &self.node.keypair,
+                        addr,
+                        nonce,
+                        nonce_signature,
+                    );
+                    let _ = self.attestations.insert(sender.name(), attestation);
+                    if let Some((elder, challenge)) =
+                        self.resource_challenges.remove(&sender.name())
+                    {
+                        self.send_resource_proof(
+                            elder,
+                            challenge,
+                            section_key,
+                            network_genesis_key,
+                        )
+                        .await?;
+                    }
+                }
                 JoinResponse::ResourceChallenge {
                     data_size,
                     difficulty,
@@ -360,28 +474,62 @@ impl<'a> Join<'a> {
                     nonce_signature,
                 } => {
                     trace!("Received a ResourceChallenge from {}", sender);
-                    let rp = ResourceProof::new(data_size, difficulty);
-                    let data = rp.create_proof_data(&nonce);
-                    let mut prover = rp.create_prover(data.clone());
-                    let solution = prover.solve();
-
-                    let join_request = JoinRequest {
-                        section_key,
-                        resource_proof_response: Some(ResourceProofResponse {
-                            solution,
-                            data,
-                            nonce,
-                            nonce_signature,
-                        }),
+                    let challenge = ResourceChallenge {
+                        data_size,
+                        difficulty,
+                        nonce,
+                        nonce_signature,
                     };
-                    let recipients = &[sender];
-                    self.send_join_requests(join_request, recipients, section_key, false)
+                    if self.attestations.contains_key(&sender.name()) {
+                        self.send_resource_proof(
+                            sender,
+                            challenge,
+                            section_key,
+                            network_genesis_key,
+                        )
                         .await?;
+                    } else {
+                        let _ = self
+                            .resource_challenges
+                            .insert(sender.name(), (sender, challenge));
+                    }
                 }
             }
         }
     }
 
+    // Solves the resource challenge of the Elder, sending it the proof along with the
+    // attestation of the challenge it dialled us back with.
+    async fn send_resource_proof(
+        &mut self,
+        elder: Peer,
+        challenge: ResourceChallenge,
+        section_key: BlsPublicKey,
+        network_genesis_key: BlsPublicKey,
+    ) -> Result<()> {
+        let rp = ResourceProof::new(challenge.data_size, challenge.difficulty);
+        let data = rp.create_proof_data(&challenge.nonce);
+        let mut prover = rp.create_prover(data.clone());
+        let solution = prover.solve();
+
+        let join_request = JoinRequest {
+            section_key,
+            resource_proof_response: Some(ResourceProofResponse {
+                solution,
+                data,
+                nonce: challenge.nonce,
+                nonce_signature: challenge.nonce_signature,
+                reachability_attestation: self.attestations.remove(&elder.name()),
+            }),
+            genesis_key: network_genesis_key,
+            version: Some(our_version()),
+            protocol_version: PROTOCOL_VERSION,
+            capabilities: Capabilities::ours(),
+        };
+        self.send_join_requests(join_request, &[elder], section_key, false)
+            .await
+    }
+
     #[tracing::instrument(skip(self))]
     async fn send_join_requests(
         &mut self,
@@ -407,20 +555,23 @@ impl<'a> Join<'a> {
         info!("Sending {:?} to {:?}", join_request, recipients);
 
         let node_msg = SystemMsg::JoinRequest(Box::new(join_request));
-        let wire_msg = WireMsg::single_src(
-            &self.node,
-            DstLocation::Section {
-                name: self.node.name(),
-                section_pk: section_key,
-            },
-            node_msg,
-            section_key,
-        )?;
+        // the request is split into frames if it's too large to be sent whole
+        for node_msg in handshake_msgs(node_msg, MAX_HANDSHAKE_FRAME_SIZE)? {
+            let wire_msg = self
+                .signing_pool
+                .single_src(
+                    &self.node,
+                    DstLocation::section(self.node.name(), section_key),
+                    node_msg,
+                    section_key,
+                )
+                .await?;
 
-        let _res = self
-            .outgoing_msgs
-            .send((wire_msg, recipients.to_vec()))
-            .await;
+            let _res = self
+                .outgoing_msgs
+                .send((wire_msg, recipients.to_vec()))
+                .await;
+        }
 
         Ok(())
     }
@@ -549,6 +700,7 @@ mod tests {
             send_tx,
             &mut recv_rx,
             NetworkPrefixMap::new(section_key),
+            SigningPool::new(1, 1),
         );
 
         // Create the bootstrap task, but don't run it yet.
@@ -616,6 +768,7 @@ mod tests {
                     section_auth: section_auth.clone().into_authed_msg(),
                     node_state: node_state.into_authed_msg(),
                     section_chain: proof_chain,
+                    activated_features: vec![],
                 })),
                 &bootstrap_node,
                 section_auth.section_key(),
@@ -625,7 +778,7 @@ mod tests {
         };
 
         // Drive both tasks to completion concurrently (but on the same thread).
-        let ((node, section), _) = future::try_join(bootstrap, others).await?;
+        let ((node, section, _), _) = future::try_join(bootstrap, others).await?;
 
         assert_eq!(section.authority_provider().await, section_auth);
         assert_eq!(section.section_key().await, section_key);
@@ -653,6 +806,7 @@ mod tests {
             send_tx,
             &mut recv_rx,
             NetworkPrefixMap::new(genesis_key),
+            SigningPool::new(1, 1),
         );
 
         let bootstrap_task = state.run(bootstrap_node.addr);
@@ -692,6 +846,7 @@ mod tests {
                         elders: new_bootstrap_addrs.clone(),
                         members: BTreeMap::new(),
                         membership_gen: 0,
+                        data_copy_count: new_section_auth.data_copy_count(),
                     },
                 ))),
                 &bootstrap_node,
@@ -758,6 +913,7 @@ mod tests {
             send_tx,
             &mut recv_rx,
             NetworkPrefixMap::new(section_key),
+            SigningPool::new(1, 1),
         );
 
         let bootstrap_task = state.run(bootstrap_node.addr);
@@ -783,6 +939,7 @@ mod tests {
                         elders: BTreeMap::new(),
                         members: BTreeMap::new(),
                         membership_gen: 0,
+                        data_copy_count: new_section_auth.data_copy_count(),
                     },
                 ))),
                 &bootstrap_node,
@@ -803,6 +960,7 @@ mod tests {
                         elders: addrs.clone(),
                         members: BTreeMap::new(),
                         membership_gen: 0,
+                        data_copy_count: new_section_auth.data_copy_count(),
                     },
                 ))),
                 &bootstrap_node,
@@ -850,6 +1008,7 @@ mod tests {
             send_tx,
             &mut recv_rx,
             NetworkPrefixMap::new(section_key),
+            SigningPool::new(1, 1),
         );
 
         let bootstrap_task = state.run(bootstrap_node.addr);
@@ -921,6 +1080,7 @@ mod tests {
             send_tx,
             &mut recv_rx,
             NetworkPrefixMap::new(section_key),
+            SigningPool::new(1, 1),
         );
 
         let elders = (0..elder_count())
@@ -1006,10 +1166,7 @@ mod tests {
     ) -> Result<()> {
         let wire_msg = WireMsg::single_src(
             bootstrap_node,
-            DstLocation::Section {
-                name: XorName::from(PublicKey::Bls(section_pk)),
-                section_pk,
-            },
+            DstLocation::section(XorName::from(PublicKey::Bls(section_pk)), section_pk),
             node_msg,
             section_pk,
         )?;
@@ -7,9 +7,11 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 mod join;
+mod reachability;
 mod relocate;
 
 pub(crate) use join::join_network;
+pub(crate) use reachability::check_reachability;
 pub(crate) use relocate::JoiningAsRelocated;
 
 #[cfg(not(test))]
@@ -0,0 +1,120 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::{
+    core::{Comm, DeliveryStatus, MsgEvent, SigningPool},
+    Error, Result,
+};
+use sn_interface::messaging::{
+    system::{Reachability, SystemMsg},
+    AuthKind, DstLocation, MsgType,
+};
+use sn_interface::network_knowledge::NodeInfo;
+use sn_interface::types::{log_markers::LogMarker, Peer};
+
+use bls::PublicKey as BlsPublicKey;
+use std::net::SocketAddr;
+use tokio::{sync::mpsc, time::Duration};
+
+// How long the contact is given to dial us back and report on it. Contacts which don't support
+// the check won't answer at all, so this has to leave enough of the joining time to join.
+const REACHABILITY_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Ask the contact we bootstrapped to to dial us back, to find out whether our peers can reach us.
+///
+/// Returns `None` if the contact couldn't be asked, or didn't answer in time.
+pub(crate) async fn check_reachability(
+    node: &NodeInfo,
+    comm: &Comm,
+    signing_pool: &SigningPool,
+    incoming_msgs: &mut mpsc::Receiver<MsgEvent>,
+    contact: SocketAddr,
+    genesis_key: BlsPublicKey,
+) -> Result<Option<Reachability>> {
+    let wire_msg = signing_pool
+        .single_src(
+            node,
+            DstLocation::section(node.name(), genesis_key),
+            SystemMsg::ReachabilityCheck,
+            genesis_key,
+        )
+        .await?;
+
+    // Use our XorName as we do not know the contact's name yet.
+    let recipient = Peer::new(node.name(), contact);
+    match comm.send(&[recipient], 1, wire_msg).await {
+        Ok(DeliveryStatus::AllRecipients) => {}
+        Ok(status) => {
+            warn!(
+                "Failed to send ReachabilityCheck to {}: {:?}",
+                contact, status
+            );
+            return Ok(None);
+        }
+        Err(error) => {
+            warn!(
+                "Failed to send ReachabilityCheck to {}: {:?}",
+                contact, error
+            );
+            return Ok(None);
+        }
+    }
+
+    let reachability = match tokio::time::timeout(
+        REACHABILITY_CHECK_TIMEOUT,
+        receive_reachability(incoming_msgs),
+    )
+    .await
+    {
+        Ok(result) => result?,
+        Err(_) => {
+            warn!("{} did not answer our ReachabilityCheck in time", contact);
+            return Ok(None);
+        }
+    };
+
+    info!(
+        "{:?} by {}: {:?}",
+        LogMarker::ReachabilityChecked,
+        contact,
+        reachability
+    );
+
+    Ok(Some(reachability))
+}
+
+async fn receive_reachability(
+    incoming_msgs: &mut mpsc::Receiver<MsgEvent>,
+) -> Result<Reachability> {
+    while let Some(MsgEvent::Received {
+        sender, wire_msg, ..
+    }) = incoming_msgs.recv().await
+    {
+        if !matches!(wire_msg.msg_kind(), AuthKind::Node(_)) {
+            continue;
+        }
+
+        match wire_msg.into_msg() {
+            Ok(MsgType::System {
+                msg: SystemMsg::ReachabilityCheckResponse(reachability),
+                ..
+            }) => return Ok(reachability),
+            Ok(MsgType::Service { msg_id, .. } | MsgType::System { msg_id, .. }) => {
+                trace!(
+                    "Bootstrap message discarded: sender: {:?} msg_id: {:?}",
+                    sender,
+                    msg_id
+                );
+            }
+            Err(err) => debug!("Failed to deserialize message payload: {:?}", err),
+        }
+    }
+
+    error!("NodeMsg sender unexpectedly closed");
+    Err(Error::InvalidState)
+}
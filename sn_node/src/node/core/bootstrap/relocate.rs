@@ -8,10 +8,17 @@
 
 use super::UsedRecipientSaps;
 
-use crate::node::{api::cmds::Cmd, messages::WireMsgUtils, Error, Result};
+use crate::node::{
+    api::cmds::{next_timer_token, Cmd},
+    core::SigningPool,
+    Error, Result,
+};
 use sn_interface::messaging::{
-    system::{JoinAsRelocatedRequest, JoinAsRelocatedResponse, NodeState, SectionAuth, SystemMsg},
-    DstLocation, WireMsg,
+    system::{
+        handshake_msgs, JoinAsRelocatedRequest, JoinAsRelocatedResponse, MembershipState,
+        NodeState, SectionAuth, SystemMsg, MAX_HANDSHAKE_FRAME_SIZE,
+    },
+    DstLocation,
 };
 use sn_interface::network_knowledge::{NodeInfo, SectionAuthorityProvider};
 use sn_interface::types::{keys::ed25519, Peer, PublicKey};
@@ -32,12 +39,18 @@ pub(crate) struct JoiningAsRelocated {
     dst_section_key: BlsPublicKey,
     new_age: u8,
     old_keypair: Arc<Keypair>,
+    // Token of the timeout the relocation is started over at, if not approved by then
+    timeout_token: u64,
+    // Number of times the relocation was started, including the first one
+    attempts: u32,
+    signing_pool: SigningPool,
 }
 
 impl JoiningAsRelocated {
-    // Generates the first cmd to send a `JoinAsRelocatedRequest`, responses
+    // Generates the first cmds to send a `JoinAsRelocatedRequest`, responses
     // shall be fed back with `handle_join_response` function.
-    pub(crate) fn start(
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn start(
         node: NodeInfo,
         genesis_key: BlsPublicKey,
         relocate_proof: SectionAuth<NodeState>,
@@ -45,7 +58,8 @@ impl JoiningAsRelocated {
         dst_xorname: XorName,
         dst_section_key: BlsPublicKey,
         new_age: u8,
-    ) -> Result<(Self, Cmd)> {
+        signing_pool: SigningPool,
+    ) -> Result<(Self, Vec<Cmd>)> {
         let recipients: Vec<_> = bootstrap_addrs
             .iter()
             .map(|addr| Peer::new(dst_xorname, *addr))
@@ -72,10 +86,53 @@ impl JoiningAsRelocated {
             dst_section_key,
             new_age,
             old_keypair,
+            timeout_token: next_timer_token(),
+            attempts: 1,
+            signing_pool,
         };
-        let cmd = relocating.build_join_request_cmd(&recipients, dst_xorname, dummy_signature)?;
+        let cmds = relocating
+            .build_join_request_cmds(&recipients, dst_xorname, dummy_signature)
+            .await?;
+
+        Ok((relocating, cmds))
+    }
+
+    /// Name the destination section was picked by.
+    pub(crate) fn dst_xorname(&self) -> XorName {
+        self.dst_xorname
+    }
+
+    /// Token of the timeout the relocation is started over at, if not approved by then.
+    pub(crate) fn timeout_token(&self) -> u64 {
+        self.timeout_token
+    }
+
+    /// Number of times the relocation was started, including the first one.
+    pub(crate) fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Starts the relocation over, e.g. as it wasn't approved in time, sending the first
+    /// `JoinAsRelocatedRequest` again to the given addresses. The relocation is given a new
+    /// timeout token.
+    pub(crate) async fn retry(&mut self, bootstrap_addrs: Vec<SocketAddr>) -> Result<Vec<Cmd>> {
+        if let MembershipState::Relocated(ref relocate_details) = self.relocate_proof.value.state {
+            self.dst_section_key = relocate_details.dst_section_key;
+        }
+        self.used_recipient_saps = bootstrap_addrs
+            .iter()
+            .map(|addr| (*addr, self.dst_section_key))
+            .collect();
+        self.timeout_token = next_timer_token();
+        self.attempts += 1;
 
-        Ok((relocating, cmd))
+        let recipients: Vec<_> = bootstrap_addrs
+            .iter()
+            .map(|addr| Peer::new(self.dst_xorname, *addr))
+            .collect();
+        let dummy_signature = ed25519::sign(&self.node.name().0, &self.old_keypair);
+        self.build_join_request_cmds(&recipients, self.dst_xorname, dummy_signature)
+            .await
     }
 
     // Handles a `JoinAsRelocatedResponse`, if it's a:
@@ -87,19 +144,19 @@ impl JoiningAsRelocated {
         &mut self,
         join_response: JoinAsRelocatedResponse,
         sender: SocketAddr,
-    ) -> Result<Option<Cmd>> {
+    ) -> Result<Vec<Cmd>> {
         trace!("Hanlde JoinResponse {:?}", join_response);
         match join_response {
             JoinAsRelocatedResponse::Retry(section_auth) => {
                 let section_auth = section_auth.into_state();
                 if !self.check_autority_provider(&section_auth, &self.dst_xorname) {
                     trace!("failed to check authority");
-                    return Ok(None);
+                    return Ok(vec![]);
                 }
 
                 if section_auth.section_key() == self.dst_section_key {
                     trace!("equal destination section key");
-                    return Ok(None);
+                    return Ok(vec![]);
                 }
 
                 let new_section_key = section_auth.section_key();
@@ -117,7 +174,7 @@ impl JoiningAsRelocated {
                         "Ignore JoinAsRelocatedResponse::Retry with old SAP that has been sent to: {:?}",
                         section_auth
                     );
-                    return Ok(None);
+                    return Ok(vec![]);
                 }
 
                 info!(
@@ -127,23 +184,25 @@ impl JoiningAsRelocated {
                 self.dst_section_key = section_auth.section_key();
 
                 let new_name_sig = self.build_relocation_name(&section_auth.prefix());
-                let cmd = self.build_join_request_cmd(
-                    &new_recipients,
-                    section_auth.prefix().name(),
-                    new_name_sig,
-                )?;
-
-                Ok(Some(cmd))
+                let cmds = self
+                    .build_join_request_cmds(
+                        &new_recipients,
+                        section_auth.prefix().name(),
+                        new_name_sig,
+                    )
+                    .await?;
+
+                Ok(cmds)
             }
             JoinAsRelocatedResponse::Redirect(section_auth) => {
                 let section_auth = section_auth.into_state();
 
                 if !self.check_autority_provider(&section_auth, &self.dst_xorname) {
-                    return Ok(None);
+                    return Ok(vec![]);
                 }
 
                 if section_auth.section_key() == self.dst_section_key {
-                    return Ok(None);
+                    return Ok(vec![]);
                 }
 
                 let new_section_key = section_auth.section_key();
@@ -161,7 +220,7 @@ impl JoiningAsRelocated {
                         "Ignore JoinAsRelocatedResponse::Redirect with old SAP that has been sent to: {:?}",
                         section_auth
                     );
-                    return Ok(None);
+                    return Ok(vec![]);
                 }
 
                 info!(
@@ -171,13 +230,15 @@ impl JoiningAsRelocated {
                 self.dst_section_key = section_auth.section_key();
 
                 let new_name_sig = self.build_relocation_name(&section_auth.prefix());
-                let cmd = self.build_join_request_cmd(
-                    &new_recipients,
-                    section_auth.prefix().name(),
-                    new_name_sig,
-                )?;
-
-                Ok(Some(cmd))
+                let cmds = self
+                    .build_join_request_cmds(
+                        &new_recipients,
+                        section_auth.prefix().name(),
+                        new_name_sig,
+                    )
+                    .await?;
+
+                Ok(cmds)
             }
             JoinAsRelocatedResponse::NodeNotReachable(addr) => {
                 error!(
@@ -208,12 +269,12 @@ impl JoiningAsRelocated {
         signature_over_new_name
     }
 
-    fn build_join_request_cmd(
+    async fn build_join_request_cmds(
         &self,
         recipients: &[Peer],
         dst_name: XorName,
         new_name_sig: Signature,
-    ) -> Result<Cmd> {
+    ) -> Result<Vec<Cmd>> {
         let join_request = JoinAsRelocatedRequest {
             section_key: self.dst_section_key,
             relocate_proof: self.relocate_proof.clone(),
@@ -223,22 +284,26 @@ impl JoiningAsRelocated {
         info!("Sending {:?} to {:?}", join_request, recipients);
 
         let node_msg = SystemMsg::JoinAsRelocatedRequest(Box::new(join_request));
-        let wire_msg = WireMsg::single_src(
-            &self.node,
-            DstLocation::Section {
-                name: dst_name,
-                section_pk: self.dst_section_key,
-            },
-            node_msg,
-            self.genesis_key,
-        )?;
-
-        let cmd = Cmd::SendMsg {
-            recipients: recipients.to_vec(),
-            wire_msg,
-        };
+        // the request is split into frames if it's too large to be sent whole
+        let mut cmds = vec![];
+        for node_msg in handshake_msgs(node_msg, MAX_HANDSHAKE_FRAME_SIZE)? {
+            let wire_msg = self
+                .signing_pool
+                .single_src(
+                    &self.node,
+                    DstLocation::section(dst_name, self.dst_section_key),
+                    node_msg,
+                    self.genesis_key,
+                )
+                .await?;
+
+            cmds.push(Cmd::SendMsg {
+                recipients: recipients.to_vec(),
+                wire_msg,
+            });
+        }
 
-        Ok(cmd)
+        Ok(cmds)
     }
 
     fn check_autority_provider(
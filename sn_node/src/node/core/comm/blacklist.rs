@@ -0,0 +1,140 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::RwLock;
+
+// Number of endpoints tracked at once after which the ones which are not blacklisted are dropped
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Protocol violations of the endpoints sending msgs to us, e.g. mis-signed msgs, shared by all
+/// our connections. Endpoints reaching the threshold of violations are
+/// blacklisted: their connections are dropped, and the ones they make refused, until they're
+/// cleared from the blacklist.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PeerBlacklist {
+    // number of violations an endpoint is blacklisted at, `0` disabling the blacklist
+    threshold: Arc<AtomicU32>,
+    violations: Arc<RwLock<HashMap<SocketAddr, u32>>>,
+}
+
+impl PeerBlacklist {
+    /// Replace the number of violations endpoints are blacklisted at, `0` disabling the
+    /// blacklist. The violations recorded so far are kept.
+    pub(crate) fn set_threshold(&self, threshold: u32) {
+        self.threshold.store(threshold, Ordering::Relaxed)
+    }
+
+    /// Record a violation of the given endpoint, returning whether it's blacklisted.
+    pub(crate) async fn record_violation(&self, endpoint: SocketAddr) -> bool {
+        let threshold = self.threshold.load(Ordering::Relaxed);
+        let mut violations = self.violations.write().await;
+        if violations.len() >= PRUNE_THRESHOLD {
+            violations.retain(|_, count| Self::exceeds(*count, threshold));
+        }
+
+        let count = violations.entry(endpoint).or_default();
+        *count = count.saturating_add(1);
+        Self::exceeds(*count, threshold)
+    }
+
+    /// Whether the given endpoint is blacklisted.
+    pub(crate) async fn is_blacklisted(&self, endpoint: &SocketAddr) -> bool {
+        let threshold = self.threshold.load(Ordering::Relaxed);
+        self.violations
+            .read()
+            .await
+            .get(endpoint)
+            .map_or(false, |count| Self::exceeds(*count, threshold))
+    }
+
+    /// The endpoints blacklisted, with the number of violations of each.
+    pub(crate) async fn blacklisted(&self) -> BTreeMap<SocketAddr, u32> {
+        let threshold = self.threshold.load(Ordering::Relaxed);
+        self.violations
+            .read()
+            .await
+            .iter()
+            .filter(|(_, count)| Self::exceeds(**count, threshold))
+            .map(|(endpoint, count)| (*endpoint, *count))
+            .collect()
+    }
+
+    /// Forget the violations of the given endpoint, returning whether it was blacklisted.
+    pub(crate) async fn remove(&self, endpoint: &SocketAddr) -> bool {
+        let threshold = self.threshold.load(Ordering::Relaxed);
+        self.violations
+            .write()
+            .await
+            .remove(endpoint)
+            .map_or(false, |count| Self::exceeds(count, threshold))
+    }
+
+    /// Forget the violations of all the endpoints.
+    pub(crate) async fn clear(&self) {
+        self.violations.write().await.clear()
+    }
+
+    fn exceeds(count: u32, threshold: u32) -> bool {
+        threshold > 0 && count >= threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PeerBlacklist;
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    #[tokio::test]
+    async fn endpoints_are_blacklisted_at_the_threshold_until_cleared() {
+        let blacklist = PeerBlacklist::default();
+        blacklist.set_threshold(3);
+        let offender = SocketAddr::from((Ipv4Addr::LOCALHOST, 12000));
+        let other = SocketAddr::from((Ipv4Addr::LOCALHOST, 12001));
+
+        assert!(!blacklist.record_violation(offender).await);
+        assert!(!blacklist.record_violation(offender).await);
+        assert!(!blacklist.record_violation(other).await);
+        assert!(!blacklist.is_blacklisted(&offender).await);
+
+        assert!(blacklist.record_violation(offender).await);
+        assert!(blacklist.is_blacklisted(&offender).await);
+        assert!(!blacklist.is_blacklisted(&other).await);
+        assert_eq!(
+            blacklist
+                .blacklisted()
+                .await
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![(offender, 3)]
+        );
+
+        assert!(blacklist.remove(&offender).await);
+        assert!(!blacklist.is_blacklisted(&offender).await);
+        assert!(!blacklist.record_violation(offender).await);
+
+        // a threshold of 0 disables the blacklist
+        blacklist.set_threshold(0);
+        for _ in 0..10 {
+            assert!(!blacklist.record_violation(other).await);
+        }
+        assert!(blacklist.blacklisted().await.is_empty());
+
+        blacklist.set_threshold(3);
+        assert!(blacklist.is_blacklisted(&other).await);
+        blacklist.clear().await;
+        assert!(!blacklist.is_blacklisted(&other).await);
+    }
+}
@@ -0,0 +1,254 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap},
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::{Mutex, RwLock};
+
+// Number of endpoints tracked at once after which the ones which are not limited anymore are dropped
+const PRUNE_THRESHOLD: usize = 10_000;
+// Minimum time between two prunings, for them not to be done on every msg once many endpoints
+// are still limited
+const PRUNE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Limits on the rate of msgs a node accepts from each endpoint it's connected to, whichever
+/// peer is behind it, for a single noisy one not to flood the handling of incoming msgs.
+/// The members of our section are not limited, for their DKG, AE and membership msgs never to
+/// be dropped.
+///
+/// Each endpoint can send up to `burst` msgs at once, and `msgs_per_sec` msgs per second over
+/// time. The msgs sent beyond it are dropped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InboundRateLimits {
+    /// Number of msgs per second an endpoint can sustain. `0` disables rate limiting.
+    pub msgs_per_sec: f64,
+    /// Number of msgs an endpoint can send at once
+    pub burst: u32,
+}
+
+impl InboundRateLimits {
+    fn is_enabled(&self) -> bool {
+        self.msgs_per_sec > 0.0
+    }
+
+    // Capacity of the bucket of each endpoint, which can send at least one msg
+    fn capacity(&self) -> f64 {
+        f64::from(self.burst.max(1))
+    }
+}
+
+// Msgs an endpoint can still send, as of the last time it sent one
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct Buckets {
+    by_endpoint: HashMap<SocketAddr, TokenBucket>,
+    pruned_at: Option<Instant>,
+}
+
+/// Token buckets of the endpoints sending msgs to us, shared by all our connections.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct InboundRateLimiter {
+    limits: Arc<RwLock<InboundRateLimits>>,
+    buckets: Arc<Mutex<Buckets>>,
+    // endpoints of the members of our section, which are not limited
+    exempt: Arc<RwLock<BTreeSet<SocketAddr>>>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl InboundRateLimiter {
+    /// Replace the limits, the tokens endpoints have left being kept as they are.
+    pub(crate) async fn set_limits(&self, limits: InboundRateLimits) {
+        *self.limits.write().await = limits;
+    }
+
+    /// Replace the endpoints which are not limited, i.e. the ones of our section's members.
+    pub(crate) async fn set_exempt(&self, endpoints: BTreeSet<SocketAddr>) {
+        *self.exempt.write().await = endpoints;
+    }
+
+    /// Number of msgs dropped for being sent beyond the limits.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Take a token from the bucket of the given endpoint for a msg it sent, returning whether
+    /// the msg is to be handled. Msgs which are not are counted as dropped.
+    pub(crate) async fn allow(&self, endpoint: SocketAddr) -> bool {
+        self.allow_at(endpoint, Instant::now()).await
+    }
+
+    async fn allow_at(&self, endpoint: SocketAddr, now: Instant) -> bool {
+        let limits = *self.limits.read().await;
+        if !limits.is_enabled() || self.exempt.read().await.contains(&endpoint) {
+            return true;
+        }
+
+        let mut buckets = self.buckets.lock().await;
+        let prune_due = buckets.pruned_at.map_or(true, |pruned_at| {
+            now.saturating_duration_since(pruned_at) >= PRUNE_INTERVAL
+        });
+        if buckets.by_endpoint.len() >= PRUNE_THRESHOLD && prune_due {
+            Self::prune(&limits, &mut buckets.by_endpoint, now);
+            buckets.pruned_at = Some(now);
+        }
+
+        let capacity = limits.capacity();
+        let bucket = buckets.by_endpoint.entry(endpoint).or_insert(TokenBucket {
+            tokens: capacity,
+            refilled_at: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * limits.msgs_per_sec).min(capacity);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            let _ = self.dropped.fetch_add(1, Ordering::Relaxed);
+            false
+        }
+    }
+
+    // Drop the buckets which are full again, their endpoints being treated as new ones
+    fn prune(
+        limits: &InboundRateLimits,
+        buckets: &mut HashMap<SocketAddr, TokenBucket>,
+        now: Instant,
+    ) {
+        let capacity = limits.capacity();
+        let msgs_per_sec = limits.msgs_per_sec;
+        buckets.retain(|_, bucket| {
+            let elapsed = now.saturating_duration_since(bucket.refilled_at);
+            bucket.tokens + elapsed.as_secs_f64() * msgs_per_sec < capacity
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{net::Ipv4Addr, time::Duration};
+
+    #[tokio::test]
+    async fn msgs_beyond_the_burst_are_dropped_until_refilled() {
+        let limiter = InboundRateLimiter::default();
+        limiter
+            .set_limits(InboundRateLimits {
+                msgs_per_sec: 10.0,
+                burst: 5,
+            })
+            .await;
+        let noisy = SocketAddr::from((Ipv4Addr::LOCALHOST, 12000));
+        let quiet = SocketAddr::from((Ipv4Addr::LOCALHOST, 12001));
+        let start = Instant::now();
+
+        for _ in 0..5 {
+            assert!(limiter.allow_at(noisy, start).await);
+        }
+        assert!(!limiter.allow_at(noisy, start).await);
+        assert!(!limiter.allow_at(noisy, start).await);
+        assert_eq!(limiter.dropped(), 2);
+
+        // other endpoints are not affected
+        assert!(limiter.allow_at(quiet, start).await);
+
+        // tokens are refilled over time
+        let later = start + Duration::from_millis(100);
+        assert!(limiter.allow_at(noisy, later).await);
+        assert!(!limiter.allow_at(noisy, later).await);
+        assert_eq!(limiter.dropped(), 3);
+    }
+
+    #[tokio::test]
+    async fn section_members_are_not_limited() {
+        let limiter = InboundRateLimiter::default();
+        limiter
+            .set_limits(InboundRateLimits {
+                msgs_per_sec: 1.0,
+                burst: 1,
+            })
+            .await;
+        let elder = SocketAddr::from((Ipv4Addr::LOCALHOST, 12000));
+        let client = SocketAddr::from((Ipv4Addr::LOCALHOST, 12001));
+        limiter.set_exempt(BTreeSet::from([elder])).await;
+        let now = Instant::now();
+
+        for _ in 0..10 {
+            assert!(limiter.allow_at(elder, now).await);
+        }
+        assert!(limiter.allow_at(client, now).await);
+        assert!(!limiter.allow_at(client, now).await);
+        assert_eq!(limiter.dropped(), 1);
+
+        // once it left our section, the member is limited as any other endpoint
+        limiter.set_exempt(BTreeSet::new()).await;
+        assert!(limiter.allow_at(elder, now).await);
+        assert!(!limiter.allow_at(elder, now).await);
+    }
+
+    #[tokio::test]
+    async fn endpoints_are_pruned_at_most_once_per_interval() {
+        let limiter = InboundRateLimiter::default();
+        limiter
+            .set_limits(InboundRateLimits {
+                msgs_per_sec: 1.0,
+                burst: 1,
+            })
+            .await;
+        let start = Instant::now();
+        for port in 0..PRUNE_THRESHOLD as u16 {
+            let endpoint = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+            assert!(limiter.allow_at(endpoint, start).await);
+        }
+
+        // the endpoints are still limited, so none of them is pruned
+        let other = SocketAddr::from((Ipv4Addr::LOCALHOST, u16::MAX));
+        assert!(limiter.allow_at(other, start).await);
+        let pruned_at = limiter.buckets.lock().await.pruned_at;
+        assert_eq!(pruned_at, Some(start));
+
+        // and no pruning is attempted again until the interval elapsed
+        let soon = start + PRUNE_INTERVAL / 2;
+        let another = SocketAddr::from((Ipv4Addr::LOCALHOST, u16::MAX - 1));
+        assert!(limiter.allow_at(another, soon).await);
+        assert_eq!(limiter.buckets.lock().await.pruned_at, Some(start));
+
+        // by when the endpoints' buckets are full again, and they're dropped
+        let later = start + PRUNE_INTERVAL * 2;
+        assert!(limiter.allow_at(other, later).await);
+        let buckets = limiter.buckets.lock().await;
+        assert_eq!(buckets.pruned_at, Some(later));
+        assert_eq!(buckets.by_endpoint.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn disabled_limits_allow_everything() {
+        let limiter = InboundRateLimiter::default();
+        let endpoint = SocketAddr::from((Ipv4Addr::LOCALHOST, 12000));
+        let now = Instant::now();
+
+        for _ in 0..100 {
+            assert!(limiter.allow_at(endpoint, now).await);
+        }
+        assert_eq!(limiter.dropped(), 0);
+    }
+}
@@ -0,0 +1,200 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::Result;
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeSet,
+    net::{Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+use tokio::{
+    net::UdpSocket,
+    time::{self, Instant},
+};
+
+/// Default UDP port the nodes looking for peers on the local network broadcast to.
+pub const DEFAULT_LAN_DISCOVERY_PORT: u16 = 12_100;
+
+// Prefix of the beacons, for any other datagram sent to the port to be ignored
+const BEACON_MAGIC: &[u8] = b"sn-lan-discovery/1";
+
+// Max size of a beacon, generous as they only carry a network name and an address
+const MAX_BEACON_SIZE: usize = 1024;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Beacon {
+    // Broadcast by a node looking for peers of its network
+    Seek {
+        network_name: Option<String>,
+    },
+    // Sent back by a node which joined, with the address it accepts connections at
+    Found {
+        network_name: Option<String>,
+        addr: SocketAddr,
+    },
+}
+
+impl Beacon {
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut bytes = BEACON_MAGIC.to_vec();
+        bytes.extend(bincode::serialize(self)?);
+        Ok(bytes)
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        bytes
+            .strip_prefix(BEACON_MAGIC)
+            .and_then(|beacon| bincode::deserialize(beacon).ok())
+    }
+}
+
+/// Broadcast a beacon to the given port of the local network, returning the addresses of the
+/// nodes of the same network which answered it within the given time.
+pub(crate) async fn discover_lan_peers(
+    port: u16,
+    network_name: Option<String>,
+    wait: Duration,
+) -> Result<Vec<SocketAddr>> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).await?;
+    socket.set_broadcast(true)?;
+    seek_peers(
+        &socket,
+        SocketAddr::from((Ipv4Addr::BROADCAST, port)),
+        network_name,
+        wait,
+    )
+    .await
+}
+
+/// Bind the socket the beacons broadcast to the given port are received at. It can only be
+/// bound by a single node of each host.
+pub(crate) async fn bind_lan_beacon(port: u16) -> Result<UdpSocket> {
+    Ok(UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port)).await?)
+}
+
+/// Answer the beacons of the nodes of the same network looking for peers, with the address we
+/// accept connections at. Runs until receiving from the socket fails.
+pub(crate) async fn answer_lan_peers(
+    socket: UdpSocket,
+    network_name: Option<String>,
+    our_addr: SocketAddr,
+) -> Result<()> {
+    let found = Beacon::Found {
+        network_name: network_name.clone(),
+        addr: our_addr,
+    }
+    .encode()?;
+    let mut buf = [0; MAX_BEACON_SIZE];
+
+    loop {
+        let (len, sender) = socket.recv_from(&mut buf).await?;
+        match Beacon::decode(&buf[..len]) {
+            Some(Beacon::Seek {
+                network_name: their_network,
+            }) if their_network == network_name => {
+                trace!("Answering the LAN discovery beacon of {}", sender);
+                if let Err(error) = socket.send_to(&found, sender).await {
+                    warn!(
+                        "Failed to answer the LAN discovery beacon of {}: {:?}",
+                        sender, error
+                    );
+                }
+            }
+            _ => trace!("Ignoring a datagram of {} bytes from {}", len, sender),
+        }
+    }
+}
+
+// Send a beacon to the given address, collecting the answers received within the given time
+async fn seek_peers(
+    socket: &UdpSocket,
+    target: SocketAddr,
+    network_name: Option<String>,
+    wait: Duration,
+) -> Result<Vec<SocketAddr>> {
+    let seek = Beacon::Seek {
+        network_name: network_name.clone(),
+    }
+    .encode()?;
+    let _ = socket.send_to(&seek, target).await?;
+
+    let deadline = Instant::now() + wait;
+    let mut found = BTreeSet::new();
+    let mut buf = [0; MAX_BEACON_SIZE];
+    while let Ok(received) = time::timeout_at(deadline, socket.recv_from(&mut buf)).await {
+        let (len, sender) = received?;
+        match Beacon::decode(&buf[..len]) {
+            Some(Beacon::Found {
+                network_name: their_network,
+                addr,
+            }) if their_network == network_name => {
+                // a node listening at an unspecified address is reached at the one it answered from
+                let addr = if addr.ip().is_unspecified() {
+                    SocketAddr::new(sender.ip(), addr.port())
+                } else {
+                    addr
+                };
+                let _ = found.insert(addr);
+            }
+            _ => trace!("Ignoring a datagram of {} bytes from {}", len, sender),
+        }
+    }
+
+    Ok(found.into_iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{answer_lan_peers, seek_peers};
+
+    use eyre::Result;
+    use std::{
+        net::{Ipv4Addr, SocketAddr},
+        time::Duration,
+    };
+    use tokio::net::UdpSocket;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn peers_of_the_same_network_answer_beacons() -> Result<()> {
+        let responder = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+        let beacon_addr = responder.local_addr()?;
+        let node_addr = SocketAddr::from((Ipv4Addr::LOCALHOST, 12000));
+        let unspecified_addr = SocketAddr::from((Ipv4Addr::UNSPECIFIED, 12001));
+        let _answering = tokio::spawn(answer_lan_peers(
+            responder,
+            Some("testnet".to_string()),
+            node_addr,
+        ));
+
+        let seeker = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+        let wait = Duration::from_millis(500);
+        let found = seek_peers(&seeker, beacon_addr, Some("testnet".to_string()), wait).await?;
+        assert_eq!(found, vec![node_addr]);
+
+        // nodes of other networks are not answered
+        let found = seek_peers(&seeker, beacon_addr, None, wait).await?;
+        assert!(found.is_empty());
+
+        // a node listening at an unspecified address is found at the one it answered from
+        let responder = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+        let beacon_addr = responder.local_addr()?;
+        let _answering = tokio::spawn(answer_lan_peers(responder, None, unspecified_addr));
+        let found = seek_peers(&seeker, beacon_addr, None, wait).await?;
+        assert_eq!(
+            found,
+            vec![SocketAddr::from((
+                Ipv4Addr::LOCALHOST,
+                unspecified_addr.port()
+            ))]
+        );
+
+        Ok(())
+    }
+}
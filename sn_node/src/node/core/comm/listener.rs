@@ -6,12 +6,16 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::MsgEvent;
+use super::{InboundRateLimiter, MsgEvent, PeerBlacklist, PeerTag, PeerTags};
 
-use sn_interface::messaging::WireMsg;
-use sn_interface::types::{log_markers::LogMarker, Peer};
+use sn_interface::messaging::{NetworkName, WireMsg};
+use sn_interface::types::{canonical_addr, log_markers::LogMarker, Peer};
 
 use qp2p::ConnectionIncoming;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
 use tokio::sync::mpsc;
 use tokio::task;
 use tracing::Instrument;
@@ -29,49 +33,137 @@ pub(crate) struct MsgListener {
     add_connection: mpsc::Sender<ListenerEvent>,
     receive_msg: mpsc::Sender<MsgEvent>,
     count_msg: mpsc::Sender<()>,
+    network_name: Option<NetworkName>,
+    other_network_msgs: Arc<AtomicU64>,
+    peer_tags: PeerTags,
+    inbound_limiter: InboundRateLimiter,
+    blacklist: PeerBlacklist,
 }
 
 impl MsgListener {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         add_connection: mpsc::Sender<ListenerEvent>,
         receive_msg: mpsc::Sender<MsgEvent>,
         count_msg: mpsc::Sender<()>,
+        network_name: Option<NetworkName>,
+        peer_tags: PeerTags,
+        inbound_limiter: InboundRateLimiter,
+        blacklist: PeerBlacklist,
     ) -> Self {
         Self {
             add_connection,
             count_msg,
             receive_msg,
+            network_name,
+            other_network_msgs: Arc::new(AtomicU64::new(0)),
+            peer_tags,
+            inbound_limiter,
+            blacklist,
         }
     }
 
+    pub(crate) fn other_network_msgs_dropped(&self) -> u64 {
+        self.other_network_msgs.load(Ordering::Relaxed)
+    }
+
     #[tracing::instrument(skip_all)]
     pub(crate) fn listen(&self, conn: qp2p::Connection, incoming_msgs: ConnectionIncoming) {
         let clone = self.clone();
-        let _ = task::spawn(clone.listen_internal(conn, incoming_msgs).in_current_span());
+        let _ = task::spawn(
+            clone
+                .listen_internal(conn, incoming_msgs, None)
+                .in_current_span(),
+        );
+    }
+
+    /// Listens to the msgs of the connection, tagging the peer at the other end of it.
+    #[tracing::instrument(skip_all)]
+    pub(crate) fn listen_tagged(
+        &self,
+        conn: qp2p::Connection,
+        incoming_msgs: ConnectionIncoming,
+        tag: PeerTag,
+    ) {
+        let clone = self.clone();
+        let _ = task::spawn(
+            clone
+                .listen_internal(conn, incoming_msgs, Some(tag))
+                .in_current_span(),
+        );
     }
 
     #[tracing::instrument(skip_all)]
-    async fn listen_internal(self, conn: qp2p::Connection, mut incoming_msgs: ConnectionIncoming) {
+    async fn listen_internal(
+        self,
+        conn: qp2p::Connection,
+        mut incoming_msgs: ConnectionIncoming,
+        tag: Option<PeerTag>,
+    ) {
         let conn_id = conn.id();
-        let remote_address = conn.remote_address();
+        // IPv4 peers reaching our dual-stack socket are reported with IPv4-mapped addresses
+        let remote_address = canonical_addr(conn.remote_address());
         let mut first = true;
 
+        if self.blacklist.is_blacklisted(&remote_address).await {
+            debug!("{:?} {}", LogMarker::BlacklistedPeerRefused, remote_address);
+            conn.close(Some("Blacklisted.".to_string()));
+            return;
+        }
+
         while let Some(result) = incoming_msgs.next().await.transpose() {
             match result {
                 Ok(msg_bytes) => {
+                    // the peer may have been blacklisted since the connection was opened, e.g.
+                    // for a mis-signed msg it sent over it
+                    if self.blacklist.is_blacklisted(&remote_address).await {
+                        debug!("{:?} {}", LogMarker::BlacklistedPeerRefused, remote_address);
+                        conn.close(Some("Blacklisted.".to_string()));
+                        break;
+                    }
+
+                    // checked before decoding, for msgs beyond the limits to cost little
+                    if !self.inbound_limiter.allow(remote_address).await {
+                        trace!(
+                            "{:?} from {}",
+                            LogMarker::InboundMsgRateLimited,
+                            remote_address
+                        );
+                        continue;
+                    }
+
                     let wire_msg = match WireMsg::from(msg_bytes.clone()) {
                         Ok(wire_msg) => wire_msg,
                         Err(error) => {
-                            // TODO: should perhaps rather drop this connection.. as it is a spam vector
-                            debug!("Failed to deserialize message: {:?}", error);
+                            // not held against the sender, as nothing in it is authenticated,
+                            // and it may just be from a newer version
+                            debug!(
+                                "Failed to deserialize message from {}: {:?}",
+                                remote_address, error
+                            );
                             continue;
                         }
                     };
 
+                    if !wire_msg.is_from_network(self.network_name) {
+                        let _ = self.other_network_msgs.fetch_add(1, Ordering::Relaxed);
+                        debug!(
+                            "{:?} {:?} from {}, sent within network {:?}",
+                            LogMarker::OtherNetworkMsgDropped,
+                            wire_msg.msg_id(),
+                            remote_address,
+                            wire_msg.network_name()
+                        );
+                        continue;
+                    }
+
                     let src_name = wire_msg.msg_kind().src().name();
 
                     if first {
                         first = false;
+                        if let Some(tag) = tag {
+                            self.peer_tags.tag(src_name, tag).await;
+                        }
                         let _ = self
                             .add_connection
                             .send(ListenerEvent::Connected {
@@ -92,6 +184,7 @@ impl MsgListener {
 
                     // count incoming msgs..
                     let _ = self.count_msg.send(());
+                    self.peer_tags.count_msg_from(&src_name).await;
                 }
                 Err(error) => {
                     // TODO: should we propagate this?
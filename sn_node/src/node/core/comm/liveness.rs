@@ -0,0 +1,222 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use sn_interface::types::Peer;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::RwLock;
+use xor_name::XorName;
+
+/// Liveness of a watched peer, as told by how long ago we last heard from it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Liveness {
+    /// We heard from the peer recently
+    Healthy,
+    /// We haven't heard from the peer for a while, despite pinging it
+    Suspect,
+    /// We haven't heard from the peer for so long it's deemed to be down
+    Dead,
+}
+
+/// Tuning of the keep-alive pings and of the liveness detection of the peers a node watches,
+/// i.e. the members of its section as an Elder, or its Elders as an Adult.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LivenessConfig {
+    /// Interval the watched peers are checked at, pinging those we haven't heard from since the
+    /// previous check. A zero interval disables the pings and liveness detection.
+    pub ping_interval: Duration,
+    /// How long without hearing from a peer before it's suspected to be down
+    pub suspect_after: Duration,
+    /// How long without hearing from a peer before it's deemed down, and reported as dysfunctional
+    pub dead_after: Duration,
+}
+
+impl Default for LivenessConfig {
+    fn default() -> Self {
+        Self {
+            ping_interval: Duration::from_secs(5),
+            suspect_after: Duration::from_secs(15),
+            dead_after: Duration::from_secs(45),
+        }
+    }
+}
+
+impl LivenessConfig {
+    fn liveness(&self, quiet_for: Duration) -> Liveness {
+        if quiet_for >= self.dead_after {
+            Liveness::Dead
+        } else if quiet_for >= self.suspect_after {
+            Liveness::Suspect
+        } else {
+            Liveness::Healthy
+        }
+    }
+}
+
+#[derive(Debug)]
+struct WatchedPeer {
+    last_heard: Instant,
+    liveness: Liveness,
+    // whether we pinged the peer since we last heard from it
+    pinged: bool,
+}
+
+/// Outcome of a check of the watched peers
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct LivenessCheck {
+    /// Peers we haven't heard from within the ping interval, to be pinged
+    pub(crate) to_ping: Vec<Peer>,
+    /// Peers whose liveness changed since the previous check
+    pub(crate) changed: Vec<(XorName, Liveness)>,
+    /// Peers currently deemed down
+    pub(crate) dead: Vec<XorName>,
+    /// Peers which didn't reply to the ping we sent them on the previous check
+    pub(crate) unanswered: Vec<XorName>,
+}
+
+/// Tracks when we last heard from each of the peers we watch, i.e. received any msg from them,
+/// rather than waiting for the transport to notice their connection was lost.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PeerLiveness {
+    watched: Arc<RwLock<BTreeMap<XorName, WatchedPeer>>>,
+}
+
+impl PeerLiveness {
+    /// Record we just received a msg from the peer, if it's watched.
+    pub(crate) async fn heard_from(&self, name: &XorName) {
+        self.heard_from_at(name, Instant::now()).await
+    }
+
+    /// Checks the liveness of the given peers, which are now the ones watched. Newly watched
+    /// peers are given the time to be heard from, as if we just heard from them.
+    pub(crate) async fn check(
+        &self,
+        watched: &BTreeSet<Peer>,
+        config: &LivenessConfig,
+    ) -> LivenessCheck {
+        self.check_at(watched, config, Instant::now()).await
+    }
+
+    /// Names of the watched peers deemed healthy as of the last check.
+    pub(crate) async fn healthy(&self) -> BTreeSet<XorName> {
+        self.watched
+            .read()
+            .await
+            .iter()
+            .filter(|(_, peer)| peer.liveness == Liveness::Healthy)
+            .map(|(name, _)| *name)
+            .collect()
+    }
+
+    async fn heard_from_at(&self, name: &XorName, now: Instant) {
+        if let Some(peer) = self.watched.write().await.get_mut(name) {
+            peer.last_heard = now;
+            peer.pinged = false;
+        }
+    }
+
+    async fn check_at(
+        &self,
+        watched: &BTreeSet<Peer>,
+        config: &LivenessConfig,
+        now: Instant,
+    ) -> LivenessCheck {
+        let mut peers = self.watched.write().await;
+        let names: BTreeSet<_> = watched.iter().map(Peer::name).collect();
+        peers.retain(|name, _| names.contains(name));
+
+        let mut check = LivenessCheck::default();
+        for peer in watched {
+            let state = peers.entry(peer.name()).or_insert(WatchedPeer {
+                last_heard: now,
+                liveness: Liveness::Healthy,
+                pinged: false,
+            });
+
+            let quiet_for = now.saturating_duration_since(state.last_heard);
+            if quiet_for >= config.ping_interval {
+                if state.pinged {
+                    check.unanswered.push(peer.name());
+                }
+                state.pinged = true;
+                check.to_ping.push(*peer);
+            }
+
+            let liveness = config.liveness(quiet_for);
+            if liveness != state.liveness {
+                state.liveness = liveness;
+                check.changed.push((peer.name(), liveness));
+            }
+            if liveness == Liveness::Dead {
+                check.dead.push(peer.name());
+            }
+        }
+
+        check
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn quiet_peers_are_pinged_then_suspected_then_deemed_dead() {
+        let liveness = PeerLiveness::default();
+        let config = LivenessConfig {
+            ping_interval: Duration::from_secs(1),
+            suspect_after: Duration::from_secs(3),
+            dead_after: Duration::from_secs(6),
+        };
+        let quiet = Peer::new(xor_name::rand::random(), ([127, 0, 0, 1], 12000).into());
+        let chatty = Peer::new(xor_name::rand::random(), ([127, 0, 0, 1], 12001).into());
+        let watched = BTreeSet::from([quiet, chatty]);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        let check = liveness.check_at(&watched, &config, start).await;
+        assert_eq!(check, LivenessCheck::default());
+
+        liveness.heard_from_at(&chatty.name(), at(1)).await;
+        let check = liveness.check_at(&watched, &config, at(1)).await;
+        assert_eq!(check.to_ping, vec![quiet]);
+        assert!(check.changed.is_empty());
+        assert!(check.unanswered.is_empty());
+
+        liveness.heard_from_at(&chatty.name(), at(3)).await;
+        let check = liveness.check_at(&watched, &config, at(3)).await;
+        assert_eq!(check.changed, vec![(quiet.name(), Liveness::Suspect)]);
+        assert!(check.dead.is_empty());
+        assert_eq!(check.unanswered, vec![quiet.name()]);
+
+        liveness.heard_from_at(&chatty.name(), at(6)).await;
+        let check = liveness.check_at(&watched, &config, at(6)).await;
+        assert_eq!(check.changed, vec![(quiet.name(), Liveness::Dead)]);
+        assert_eq!(check.dead, vec![quiet.name()]);
+        assert_eq!(liveness.healthy().await, BTreeSet::from([chatty.name()]));
+
+        // hearing from a dead peer brings it back to life
+        liveness.heard_from_at(&quiet.name(), at(7)).await;
+        let check = liveness.check_at(&watched, &config, at(7)).await;
+        assert_eq!(check.changed, vec![(quiet.name(), Liveness::Healthy)]);
+        assert!(check.dead.is_empty());
+        assert!(check.unanswered.is_empty());
+
+        // peers not watched anymore are forgotten, and given time again once watched again
+        let _ = liveness
+            .check_at(&BTreeSet::from([chatty]), &config, at(7))
+            .await;
+        let check = liveness.check_at(&watched, &config, at(14)).await;
+        assert_eq!(check.to_ping, vec![chatty]);
+        assert_eq!(check.dead, vec![chatty.name()]);
+    }
+}
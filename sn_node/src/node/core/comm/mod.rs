@@ -9,40 +9,71 @@
 #[cfg(feature = "back-pressure")]
 mod back_pressure;
 
+mod blacklist;
+mod inbound_limiter;
+mod lan_discovery;
 mod link;
 mod listener;
+mod liveness;
+mod msg_filter;
 mod peer_session;
+mod peer_tags;
+mod routing_table;
 
 #[cfg(feature = "back-pressure")]
 use self::back_pressure::BackPressure;
 
+use self::blacklist::PeerBlacklist;
+use self::inbound_limiter::InboundRateLimiter;
+pub use self::inbound_limiter::InboundRateLimits;
+pub use self::lan_discovery::DEFAULT_LAN_DISCOVERY_PORT;
+pub(crate) use self::lan_discovery::{answer_lan_peers, bind_lan_beacon, discover_lan_peers};
 use self::link::Link;
 use self::listener::{ListenerEvent, MsgListener};
+pub use self::liveness::LivenessConfig;
+pub(crate) use self::liveness::{Liveness, PeerLiveness};
+use self::msg_filter::MsgFilter;
 use self::peer_session::{PeerSession, SendWatcher};
+pub(crate) use self::peer_tags::{PeerTag, PeerTags};
+pub use self::routing_table::{RoutingTable, RoutingTableEntry};
 
 use crate::node::core::comm::peer_session::SendStatus;
 use crate::node::error::{Error, Result};
-use sn_interface::messaging::WireMsg;
-use sn_interface::types::Peer;
+use sn_interface::messaging::{MsgId, NetworkName, WireMsg};
+use sn_interface::types::{log_markers::LogMarker, Peer};
 
+use bls::PublicKey as BlsPublicKey;
 use bytes::Bytes;
 use futures::stream::{FuturesUnordered, StreamExt};
 use qp2p::{Endpoint, IncomingConnections};
 use std::time::Duration;
-use std::{collections::BTreeMap, net::SocketAddr, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::SocketAddr,
+    sync::Arc,
+};
 use tokio::{
     sync::{mpsc, RwLock},
     task,
 };
+use xor_name::XorName;
 
 // Communication component of the node to interact with other nodes.
 #[derive(Clone)]
 pub(crate) struct Comm {
     our_endpoint: Endpoint,
+    // endpoints only accepting connections, at the additional addresses we listen at
+    extra_endpoints: Vec<Endpoint>,
     msg_listener: MsgListener,
     #[cfg(feature = "back-pressure")]
     back_pressure: BackPressure,
     sessions: Arc<RwLock<BTreeMap<Peer, PeerSession>>>,
+    network_name: Option<NetworkName>,
+    liveness: PeerLiveness,
+    peer_tags: PeerTags,
+    inbound_limiter: InboundRateLimiter,
+    msg_filter: MsgFilter,
+    blacklist: PeerBlacklist,
 }
 
 impl Comm {
@@ -50,6 +81,7 @@ impl Comm {
     pub(crate) async fn first_node(
         local_addr: SocketAddr,
         config: qp2p::Config,
+        network_name: Option<NetworkName>,
         receive_msg: mpsc::Sender<MsgEvent>,
     ) -> Result<Self> {
         // Doesn't bootstrap, just creates an endpoint to listen to
@@ -57,7 +89,12 @@ impl Comm {
         let (our_endpoint, incoming_connections, _) =
             Endpoint::new_peer(local_addr, Default::default(), config).await?;
 
-        let (comm, _) = setup_comms(our_endpoint, incoming_connections, receive_msg);
+        let (comm, _) = setup_comms(
+            our_endpoint,
+            incoming_connections,
+            network_name,
+            receive_msg,
+        );
 
         Ok(comm)
     }
@@ -67,6 +104,7 @@ impl Comm {
         local_addr: SocketAddr,
         bootstrap_nodes: &[SocketAddr],
         config: qp2p::Config,
+        network_name: Option<NetworkName>,
         receive_msg: mpsc::Sender<MsgEvent>,
     ) -> Result<(Self, SocketAddr)> {
         debug!("Starting bootstrap process");
@@ -74,20 +112,183 @@ impl Comm {
         let (our_endpoint, incoming_connections, bootstrap_node) =
             Endpoint::new_peer(local_addr, bootstrap_nodes, config).await?;
 
-        let (comm, msg_listener) = setup_comms(our_endpoint, incoming_connections, receive_msg);
+        let (comm, msg_listener) = setup_comms(
+            our_endpoint,
+            incoming_connections,
+            network_name,
+            receive_msg,
+        );
 
         let (connection, incoming_msgs) = bootstrap_node.ok_or(Error::BootstrapFailed)?;
         let remote_address = connection.remote_address();
 
-        msg_listener.listen(connection, incoming_msgs);
+        msg_listener.listen_tagged(connection, incoming_msgs, PeerTag::Bootstrap);
 
         Ok((comm, remote_address))
     }
 
+    /// Also accepts connections at each of the given local addresses, e.g. an IPv6 one next to
+    /// our IPv4 endpoint. A port of 0 binds a random unused one.
+    ///
+    /// Msgs are only sent from our endpoint, or back over the connections accepted.
+    pub(crate) async fn listen_at(
+        mut self,
+        local_addrs: &[SocketAddr],
+        config: qp2p::Config,
+    ) -> Result<Self> {
+        // The external address and port forwarding only apply to our endpoint
+        let config = qp2p::Config {
+            external_ip: None,
+            external_port: None,
+            forward_port: false,
+            ..config
+        };
+        for local_addr in local_addrs {
+            let (endpoint, incoming_connections, _) =
+                Endpoint::new_peer(*local_addr, &[], config.clone()).await?;
+            info!("Also listening at {}", endpoint.local_addr());
+
+            listen(self.msg_listener.clone(), incoming_connections);
+            self.extra_endpoints.push(endpoint);
+        }
+
+        Ok(self)
+    }
+
     pub(crate) fn our_connection_info(&self) -> SocketAddr {
         self.our_endpoint.public_addr()
     }
 
+    /// The addresses we accept connections at, our connection info first. Extra endpoints bound
+    /// to an unspecified address, e.g. `0.0.0.0`, aren't reachable at it, so they're left out.
+    pub(crate) fn listening_addrs(&self) -> Vec<SocketAddr> {
+        std::iter::once(self.our_connection_info())
+            .chain(
+                self.extra_endpoints
+                    .iter()
+                    .map(Endpoint::local_addr)
+                    .filter(|addr| !addr.ip().is_unspecified()),
+            )
+            .collect()
+    }
+
+    /// Closes all the connections, and stops accepting new ones, for the node to shut down
+    pub(crate) fn close(&self) {
+        self.our_endpoint.close();
+        for endpoint in &self.extra_endpoints {
+            endpoint.close();
+        }
+    }
+
+    /// Liveness of the peers we watch, as told by the msgs we receive from them
+    pub(crate) fn liveness(&self) -> &PeerLiveness {
+        &self.liveness
+    }
+
+    /// Tags of the peers we communicate with, telling what they are to us
+    pub(crate) fn peer_tags(&self) -> &PeerTags {
+        &self.peer_tags
+    }
+
+    /// Peers we have sessions with, and whether they're connected
+    pub(crate) async fn peer_connections(&self) -> Vec<(Peer, bool)> {
+        let sessions = self.sessions.read().await;
+        let mut connections = vec![];
+        for (peer, session) in sessions.iter() {
+            connections.push((*peer, session.is_connected().await));
+        }
+        connections
+    }
+
+    /// Number of msgs received which were sent within another network, and dropped
+    pub(crate) fn other_network_msgs_dropped(&self) -> u64 {
+        self.msg_listener.other_network_msgs_dropped()
+    }
+
+    /// Replace the limits on the rate of msgs we accept from each endpoint.
+    pub(crate) async fn set_inbound_rate_limits(&self, limits: InboundRateLimits) {
+        self.inbound_limiter.set_limits(limits).await
+    }
+
+    /// Replace the endpoints of our section's members, which the limits on the rate of msgs
+    /// don't apply to.
+    pub(crate) async fn set_inbound_rate_exempt(&self, endpoints: BTreeSet<SocketAddr>) {
+        self.inbound_limiter.set_exempt(endpoints).await
+    }
+
+    /// Number of msgs received beyond the limits on the rate of msgs of their endpoint, and
+    /// dropped
+    pub(crate) fn rate_limited_msgs_dropped(&self) -> u64 {
+        self.inbound_limiter.dropped()
+    }
+
+    /// Records the msg, whose authority was verified, as received, returning whether it's the
+    /// first time it is, e.g. rather than relayed to us again by another peer.
+    pub(crate) async fn is_new_msg(
+        &self,
+        msg_id: MsgId,
+        src: XorName,
+        dst_section_pk: Option<BlsPublicKey>,
+    ) -> bool {
+        self.msg_filter.is_new(msg_id, src, dst_section_pk).await
+    }
+
+    /// Number of msgs received again, e.g. relayed by several peers, and dropped
+    pub(crate) fn duplicate_msgs_dropped(&self) -> u64 {
+        self.msg_filter.duplicates_dropped()
+    }
+
+    /// Replace the number of protocol violations endpoints are blacklisted at, `0` disabling
+    /// the blacklist.
+    pub(crate) fn set_blacklist_threshold(&self, threshold: u32) {
+        self.blacklist.set_threshold(threshold)
+    }
+
+    /// Record a protocol violation of the given endpoint, e.g. a msg it sent which is mis-signed.
+    /// Once the endpoint is blacklisted, our sessions with it are dropped, and the connections
+    /// it makes refused.
+    pub(crate) async fn report_violation(&self, endpoint: SocketAddr) {
+        if !self.blacklist.record_violation(endpoint).await {
+            return;
+        }
+        warn!("{:?} {}", LogMarker::PeerBlacklisted, endpoint);
+
+        let mut sessions = self.sessions.write().await;
+        let peers: Vec<Peer> = sessions
+            .keys()
+            .filter(|peer| peer.addr() == endpoint)
+            .copied()
+            .collect();
+        let dropped: Vec<PeerSession> = peers
+            .iter()
+            .filter_map(|peer| sessions.remove(peer))
+            .collect();
+        drop(sessions);
+
+        for session in dropped {
+            session.disconnect().await;
+        }
+    }
+
+    /// The endpoints blacklisted for violating the protocol, with the number of violations of
+    /// each.
+    pub(crate) async fn blacklisted_peers(&self) -> BTreeMap<SocketAddr, u32> {
+        self.blacklist.blacklisted().await
+    }
+
+    /// Clear the given endpoint from the blacklist, or all of them, for their connections to be
+    /// accepted again. Returns whether any endpoint was blacklisted.
+    pub(crate) async fn clear_blacklist(&self, endpoint: Option<SocketAddr>) -> bool {
+        match endpoint {
+            Some(endpoint) => self.blacklist.remove(&endpoint).await,
+            None => {
+                let any = !self.blacklist.blacklisted().await.is_empty();
+                self.blacklist.clear().await;
+                any
+            }
+        }
+    }
+
     pub(crate) async fn cleanup_peers(&self, retain_peers: Vec<Peer>) {
         let sessions = self.sessions.read().await;
 
@@ -121,6 +322,8 @@ impl Comm {
                 if let Some(session) = perhaps_peer {
                     session.disconnect().await
                 };
+                // clients reconnecting will be tagged again by their msgs
+                self.peer_tags.untag(&peer.name(), PeerTag::Client).await;
             }
         }
 
@@ -161,6 +364,44 @@ impl Comm {
         result
     }
 
+    /// Fake function used as replacement for testing only.
+    #[cfg(test)]
+    pub(crate) async fn dial_back(&self, _peer: &Peer, _wire_msg: WireMsg) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Sends the msg to the peer on a connection of its own, dialling it at its address rather
+    /// than using any connection it opened to us, so it only gets the msg if it listens there.
+    #[cfg(not(test))]
+    pub(crate) async fn dial_back(&self, peer: &Peer, mut wire_msg: WireMsg) -> Result<(), Error> {
+        wire_msg.set_network_name(self.network_name);
+        wire_msg.set_dst_xorname(peer.name());
+        let msg_bytes = wire_msg.serialize()?;
+
+        let qp2p_config = qp2p::Config {
+            forward_port: false,
+            ..Default::default()
+        };
+        let dial_back_endpoint =
+            Endpoint::new_client((self.our_endpoint.local_addr().ip(), 0), qp2p_config)?;
+
+        let result: Result<(), Error> = async {
+            let (connection, _) = dial_back_endpoint
+                .connect_to(&peer.addr())
+                .await
+                .map_err(qp2p::RpcError::from)?;
+            connection.send(msg_bytes).await?;
+            Ok(())
+        }
+        .await;
+        match &result {
+            Ok(()) => info!("Peer {} dialled back.", peer),
+            Err(error) => info!("Peer {} could NOT be dialled back: {:?}", peer, error),
+        }
+        dial_back_endpoint.close();
+        result
+    }
+
     #[cfg(feature = "back-pressure")]
     /// Returns our caller-specific tolerated msgs per s, if the value has changed significantly.
     pub(crate) async fn tolerated_msgs_per_s(&self) -> Option<f64> {
@@ -509,10 +750,11 @@ impl Comm {
     async fn send_to_one(
         &self,
         recipient: Peer,
-        wire_msg: WireMsg,
+        mut wire_msg: WireMsg,
         msg_priority: i32,
     ) -> (Peer, Result<SendWatcher>) {
         let msg_id = wire_msg.msg_id();
+        wire_msg.set_network_name(self.network_name);
         let msg_bytes = match wire_msg.serialize() {
             Ok(bytes) => bytes,
             Err(error) => {
@@ -539,9 +781,10 @@ impl Comm {
 fn setup_comms(
     our_endpoint: Endpoint,
     incoming_connections: IncomingConnections,
+    network_name: Option<NetworkName>,
     receive_msg: mpsc::Sender<MsgEvent>,
 ) -> (Comm, MsgListener) {
-    let (comm, msg_listener) = setup(our_endpoint, receive_msg);
+    let (comm, msg_listener) = setup(our_endpoint, network_name, receive_msg);
 
     listen(msg_listener.clone(), incoming_connections);
 
@@ -549,7 +792,11 @@ fn setup_comms(
 }
 
 #[tracing::instrument(skip_all)]
-fn setup(our_endpoint: Endpoint, receive_msg: mpsc::Sender<MsgEvent>) -> (Comm, MsgListener) {
+fn setup(
+    our_endpoint: Endpoint,
+    network_name: Option<NetworkName>,
+    receive_msg: mpsc::Sender<MsgEvent>,
+) -> (Comm, MsgListener) {
     #[cfg(feature = "back-pressure")]
     let back_pressure = BackPressure::new();
 
@@ -559,14 +806,34 @@ fn setup(our_endpoint: Endpoint, receive_msg: mpsc::Sender<MsgEvent>) -> (Comm,
     #[cfg(not(feature = "back-pressure"))]
     let (count_msg, _msg_counter) = mpsc::channel(1000);
 
-    let msg_listener = MsgListener::new(add_connection, receive_msg, count_msg);
+    let liveness = PeerLiveness::default();
+    let peer_tags = PeerTags::default();
+    let inbound_limiter = InboundRateLimiter::default();
+    let msg_filter = MsgFilter::default();
+    let blacklist = PeerBlacklist::default();
+    let msg_listener = MsgListener::new(
+        add_connection,
+        receive_msg,
+        count_msg,
+        network_name,
+        peer_tags.clone(),
+        inbound_limiter.clone(),
+        blacklist.clone(),
+    );
 
     let comm = Comm {
         our_endpoint,
+        extra_endpoints: Vec::new(),
         msg_listener: msg_listener.clone(),
         #[cfg(feature = "back-pressure")]
         back_pressure: back_pressure.clone(),
         sessions: Arc::new(RwLock::new(BTreeMap::new())),
+        network_name,
+        liveness,
+        peer_tags,
+        inbound_limiter,
+        msg_filter,
+        blacklist,
     };
 
     #[cfg(feature = "back-pressure")]
@@ -613,7 +880,7 @@ impl Drop for Comm {
     fn drop(&mut self) {
         // Close all existing connections and stop accepting new ones.
         // FIXME: this may be broken – `Comm` is clone, so this will break any clones?
-        self.our_endpoint.close();
+        self.close();
     }
 }
 
@@ -642,9 +909,12 @@ mod tests {
     use futures::future;
     use qp2p::Config;
     use sn_interface::messaging::data::{DataQuery, ServiceMsg};
-    use sn_interface::messaging::{AuthKind, DstLocation, MsgId, ServiceAuth};
+    use sn_interface::messaging::{AuthKind, DstLocation, MsgId, ServiceAuth, SigningDomain};
     use sn_interface::types::{ChunkAddress, Keypair, Peer};
-    use std::{net::Ipv4Addr, time::Duration};
+    use std::{
+        net::{Ipv4Addr, Ipv6Addr},
+        time::Duration,
+    };
     use tokio::{net::UdpSocket, sync::mpsc, time};
 
     const TIMEOUT: Duration = Duration::from_secs(1);
@@ -652,7 +922,7 @@ mod tests {
     #[tokio::test(flavor = "multi_thread")]
     async fn successful_send() -> Result<()> {
         let (tx, _rx) = mpsc::channel(1);
-        let comm = Comm::first_node(local_addr(), Config::default(), tx).await?;
+        let comm = Comm::first_node(local_addr(), Config::default(), None, tx).await?;
 
         let (peer0, mut rx0) = new_peer().await?;
         let (peer1, mut rx1) = new_peer().await?;
@@ -687,7 +957,7 @@ mod tests {
     #[tokio::test(flavor = "multi_thread")]
     async fn successful_send_to_subset() -> Result<()> {
         let (tx, _rx) = mpsc::channel(1);
-        let comm = Comm::first_node(local_addr(), Config::default(), tx).await?;
+        let comm = Comm::first_node(local_addr(), Config::default(), None, tx).await?;
 
         let (peer0, mut rx0) = new_peer().await?;
         let (peer1, mut rx1) = new_peer().await?;
@@ -726,6 +996,7 @@ mod tests {
                 idle_timeout: Some(Duration::from_millis(1)),
                 ..Config::default()
             },
+            None,
             tx,
         )
         .await?;
@@ -751,6 +1022,7 @@ mod tests {
                 idle_timeout: Some(Duration::from_millis(1)),
                 ..Config::default()
             },
+            None,
             tx,
         )
         .await?;
@@ -783,6 +1055,7 @@ mod tests {
                 idle_timeout: Some(Duration::from_millis(1)),
                 ..Config::default()
             },
+            None,
             tx,
         )
         .await?;
@@ -811,7 +1084,7 @@ mod tests {
     #[tokio::test(flavor = "multi_thread")]
     async fn send_after_reconnect() -> Result<()> {
         let (tx, _rx) = mpsc::channel(1);
-        let send_comm = Comm::first_node(local_addr(), Config::default(), tx).await?;
+        let send_comm = Comm::first_node(local_addr(), Config::default(), None, tx).await?;
 
         let (recv_endpoint, mut incoming_connections, _) =
             Endpoint::new_peer(local_addr(), &[], Config::default()).await?;
@@ -871,11 +1144,11 @@ mod tests {
     #[tokio::test(flavor = "multi_thread")]
     async fn incoming_connection_lost() -> Result<()> {
         let (tx, mut rx0) = mpsc::channel(1);
-        let comm0 = Comm::first_node(local_addr(), Config::default(), tx).await?;
+        let comm0 = Comm::first_node(local_addr(), Config::default(), None, tx).await?;
         let addr0 = comm0.our_connection_info();
 
         let (tx, _rx) = mpsc::channel(1);
-        let comm1 = Comm::first_node(local_addr(), Config::default(), tx).await?;
+        let comm1 = Comm::first_node(local_addr(), Config::default(), None, tx).await?;
 
         // Send a message to establish the connection
         let status = comm1
@@ -896,11 +1169,196 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn msgs_from_other_networks_are_dropped() -> Result<()> {
+        let (tx, mut rx0) = mpsc::channel(1);
+        let comm0 = Comm::first_node(
+            local_addr(),
+            Config::default(),
+            Some(NetworkName::new("comnet")),
+            tx,
+        )
+        .await?;
+        let addr0 = comm0.our_connection_info();
+
+        let (tx, _rx) = mpsc::channel(1);
+        let comm1 = Comm::first_node(
+            local_addr(),
+            Config::default(),
+            Some(NetworkName::new("main")),
+            tx,
+        )
+        .await?;
+
+        let status = comm1
+            .send(
+                &[Peer::new(xor_name::rand::random(), addr0)],
+                1,
+                new_test_msg()?,
+            )
+            .await?;
+        assert_matches!(status, DeliveryStatus::AllRecipients);
+
+        assert_matches!(time::timeout(TIMEOUT, rx0.recv()).await, Err(_));
+        assert_eq!(comm0.other_network_msgs_dropped(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn successful_send_over_ipv6() -> Result<()> {
+        let v6_loopback = SocketAddr::from((Ipv6Addr::LOCALHOST, 0));
+
+        let (tx, mut rx0) = mpsc::channel(1);
+        let comm0 = Comm::first_node(v6_loopback, Config::default(), None, tx).await?;
+        let addr0 = comm0.our_connection_info();
+        assert!(addr0.is_ipv6());
+
+        let (tx, _rx) = mpsc::channel(1);
+        let comm1 = Comm::first_node(v6_loopback, Config::default(), None, tx).await?;
+        let addr1 = comm1.our_connection_info();
+
+        let name0 = xor_name::rand::random();
+        let original_message = new_test_msg()?;
+        let status = comm1
+            .send(&[Peer::new(name0, addr0)], 1, original_message.clone())
+            .await?;
+        assert_matches!(status, DeliveryStatus::AllRecipients);
+
+        let mut check_msg = original_message;
+        check_msg.set_dst_xorname(name0);
+        assert_matches!(
+            time::timeout(TIMEOUT, rx0.recv()).await?,
+            Some(MsgEvent::Received { sender, wire_msg, .. }) => {
+                assert_eq!(sender.addr(), addr1);
+                assert_eq!(wire_msg, check_msg);
+            }
+        );
+
+        Ok(())
+    }
+
+    // IPv6 sockets only accept IPv4 peers where IPV6_V6ONLY is off by default, which it isn't on
+    // Windows
+    #[cfg(unix)]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn dual_stack_node_receives_from_ipv4_peers() -> Result<()> {
+        let (tx, mut rx0) = mpsc::channel(1);
+        let comm0 = Comm::first_node(
+            sn_interface::types::IpPreference::V6.unspecified_addr(),
+            Config::default(),
+            None,
+            tx,
+        )
+        .await?;
+        let addr0 = SocketAddr::from((Ipv4Addr::LOCALHOST, comm0.our_connection_info().port()));
+
+        let (tx, _rx) = mpsc::channel(1);
+        let comm1 = Comm::first_node(local_addr(), Config::default(), None, tx).await?;
+        let addr1 = comm1.our_connection_info();
+
+        let status = comm1
+            .send(
+                &[Peer::new(xor_name::rand::random(), addr0)],
+                1,
+                new_test_msg()?,
+            )
+            .await?;
+        assert_matches!(status, DeliveryStatus::AllRecipients);
+
+        // the IPv4-mapped address of the sender is reported as a plain IPv4 one
+        assert_matches!(
+            time::timeout(TIMEOUT, rx0.recv()).await?,
+            Some(MsgEvent::Received { sender, .. }) => assert_eq!(sender.addr(), addr1)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn msgs_are_received_at_extra_listening_addrs() -> Result<()> {
+        let (tx, mut rx0) = mpsc::channel(1);
+        let comm0 = Comm::first_node(local_addr(), Config::default(), None, tx)
+            .await?
+            .listen_at(&[local_addr()], Config::default())
+            .await?;
+
+        let listening_addrs = comm0.listening_addrs();
+        assert_eq!(listening_addrs.len(), 2);
+        assert_eq!(listening_addrs[0], comm0.our_connection_info());
+        assert_ne!(listening_addrs[1].port(), 0);
+        assert_ne!(listening_addrs[1], listening_addrs[0]);
+
+        let (tx, _rx) = mpsc::channel(1);
+        let comm1 = Comm::first_node(local_addr(), Config::default(), None, tx).await?;
+        let status = comm1
+            .send(
+                &[Peer::new(xor_name::rand::random(), listening_addrs[1])],
+                1,
+                new_test_msg()?,
+            )
+            .await?;
+        assert_matches!(status, DeliveryStatus::AllRecipients);
+
+        assert_matches!(
+            time::timeout(TIMEOUT, rx0.recv()).await?,
+            Some(MsgEvent::Received { sender, .. }) => {
+                assert_eq!(sender.addr(), comm1.our_connection_info())
+            }
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn unspecified_listening_addrs_are_not_reported() -> Result<()> {
+        let (tx, _rx) = mpsc::channel(1);
+        let unspecified = SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0));
+        let comm = Comm::first_node(local_addr(), Config::default(), None, tx)
+            .await?
+            .listen_at(&[unspecified], Config::default())
+            .await?;
+
+        assert_eq!(comm.listening_addrs(), vec![comm.our_connection_info()]);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn msgs_beyond_the_inbound_rate_limits_are_dropped() -> Result<()> {
+        let (tx, mut rx0) = mpsc::channel(10);
+        let comm0 = Comm::first_node(local_addr(), Config::default(), None, tx).await?;
+        comm0
+            .set_inbound_rate_limits(InboundRateLimits {
+                msgs_per_sec: 0.1,
+                burst: 1,
+            })
+            .await;
+        let addr0 = comm0.our_connection_info();
+
+        let (tx, _rx) = mpsc::channel(1);
+        let comm1 = Comm::first_node(local_addr(), Config::default(), None, tx).await?;
+        let peer0 = Peer::new(xor_name::rand::random(), addr0);
+        for _ in 0..2 {
+            let status = comm1.send(&[peer0], 1, new_test_msg()?).await?;
+            assert_matches!(status, DeliveryStatus::AllRecipients);
+        }
+
+        assert_matches!(
+            time::timeout(TIMEOUT, rx0.recv()).await?,
+            Some(MsgEvent::Received { .. })
+        );
+        assert_matches!(time::timeout(TIMEOUT, rx0.recv()).await, Err(_));
+        assert_eq!(comm0.rate_limited_msgs_dropped(), 1);
+
+        Ok(())
+    }
+
     fn new_test_msg() -> Result<WireMsg> {
-        let dst_location = DstLocation::Node {
-            name: xor_name::rand::random(),
-            section_pk: bls::SecretKey::random().public_key(),
-        };
+        let dst_location = DstLocation::node(
+            xor_name::rand::random(),
+            bls::SecretKey::random().public_key(),
+        );
 
         let src_keypair = Keypair::new_ed25519();
 
@@ -909,7 +1367,7 @@ mod tests {
         )))?;
         let auth = ServiceAuth {
             public_key: src_keypair.public_key(),
-            signature: src_keypair.sign(&payload),
+            signature: src_keypair.sign(&SigningDomain::Service.signable_bytes(&payload)),
         };
 
         let wire_msg =
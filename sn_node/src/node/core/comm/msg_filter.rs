@@ -0,0 +1,111 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use sn_interface::messaging::MsgId;
+
+use bls::PublicKey as BlsPublicKey;
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::Mutex;
+use xor_name::XorName;
+
+// Number of the msgs most recently received which are remembered, to tell their duplicates
+const SEEN_MSGS_LIMIT: usize = 1024;
+
+// Id, source, and key of the destination section of a msg. A msg bounced by anti-entropy is
+// resent with the same id to the section key it was told about, so it's another msg then.
+type SeenMsg = (MsgId, XorName, Option<BlsPublicKey>);
+
+// The msgs remembered, along with the order they were received in to forget the oldest first
+#[derive(Default)]
+struct SeenMsgs {
+    set: HashSet<SeenMsg>,
+    order: VecDeque<SeenMsg>,
+}
+
+/// Msgs recently received, by id, source and destination, for the duplicates of a msg, e.g.
+/// relayed by several peers, to be dropped before being handled.
+#[derive(Clone, Default)]
+pub(crate) struct MsgFilter {
+    seen: Arc<Mutex<SeenMsgs>>,
+    duplicates: Arc<AtomicU64>,
+}
+
+impl MsgFilter {
+    /// Records the msg as received, returning whether it's the first time it is.
+    /// Duplicates are counted.
+    pub(crate) async fn is_new(
+        &self,
+        msg_id: MsgId,
+        src: XorName,
+        dst_section_pk: Option<BlsPublicKey>,
+    ) -> bool {
+        let msg = (msg_id, src, dst_section_pk);
+        let mut seen = self.seen.lock().await;
+        if seen.set.contains(&msg) {
+            let _ = self.duplicates.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+
+        if seen.order.len() >= SEEN_MSGS_LIMIT {
+            if let Some(oldest) = seen.order.pop_front() {
+                let _ = seen.set.remove(&oldest);
+            }
+        }
+        let _ = seen.set.insert(msg);
+        seen.order.push_back(msg);
+        true
+    }
+
+    /// Number of the duplicate msgs dropped.
+    pub(crate) fn duplicates_dropped(&self) -> u64 {
+        self.duplicates.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MsgFilter, SEEN_MSGS_LIMIT};
+    use sn_interface::messaging::MsgId;
+
+    #[tokio::test]
+    async fn duplicates_are_only_told_while_remembered() {
+        let filter = MsgFilter::default();
+        let msg_id = MsgId::new();
+        let src = xor_name::rand::random();
+
+        let dst_section_pk = Some(bls::SecretKey::random().public_key());
+
+        assert!(filter.is_new(msg_id, src, dst_section_pk).await);
+        assert!(!filter.is_new(msg_id, src, dst_section_pk).await);
+        assert_eq!(filter.duplicates_dropped(), 1);
+
+        // the same id from another source is another msg
+        assert!(
+            filter
+                .is_new(msg_id, xor_name::rand::random(), dst_section_pk)
+                .await
+        );
+
+        // as is the msg resent to another section key, after being bounced by anti-entropy
+        let updated_section_pk = Some(bls::SecretKey::random().public_key());
+        assert!(filter.is_new(msg_id, src, updated_section_pk).await);
+
+        // once enough other msgs were received, the msg is forgotten
+        for _ in 0..SEEN_MSGS_LIMIT {
+            assert!(filter.is_new(MsgId::new(), src, dst_section_pk).await);
+        }
+        assert!(filter.is_new(msg_id, src, dst_section_pk).await);
+        assert_eq!(filter.duplicates_dropped(), 1);
+    }
+}
@@ -0,0 +1,255 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::RwLock;
+use xor_name::XorName;
+
+// Number of clients tagged at most. Their tags are removed as their connections are cleaned
+// up, and beyond it more clients are left untagged until then.
+const MAX_TAGGED_CLIENTS: usize = 10_000;
+
+/// What a peer is to us, so msgs can be handled according to their sender
+/// without looking it up in our network knowledge every time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum PeerTag {
+    /// An Elder of our section
+    Elder,
+    /// An Adult of our section
+    Adult,
+    /// A client, which sent us service msgs
+    Client,
+    /// A node which redirected us to the Elders of a section
+    Relay,
+    /// A node we bootstrapped through
+    Bootstrap,
+}
+
+impl fmt::Display for PeerTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Elder => write!(f, "elder"),
+            Self::Adult => write!(f, "adult"),
+            Self::Client => write!(f, "client"),
+            Self::Relay => write!(f, "relay"),
+            Self::Bootstrap => write!(f, "bootstrap"),
+        }
+    }
+}
+
+/// Tags of the peers we communicate with, and the number of msgs received from peers
+/// of each tag.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PeerTags {
+    tags: Arc<RwLock<BTreeMap<XorName, BTreeSet<PeerTag>>>>,
+    // number of peers with the `Client` tag, updated under the lock of `tags`
+    tagged_clients: Arc<AtomicUsize>,
+    msgs_received: Arc<RwLock<BTreeMap<PeerTag, u64>>>,
+}
+
+impl PeerTags {
+    /// Tags the peer, keeping any tag it has already. A client isn't tagged when
+    /// `MAX_TAGGED_CLIENTS` are already.
+    pub(crate) async fn tag(&self, name: XorName, tag: PeerTag) {
+        let mut tags = self.tags.write().await;
+        if tag == PeerTag::Client {
+            if tags
+                .get(&name)
+                .map_or(false, |peer_tags| peer_tags.contains(&tag))
+            {
+                return;
+            }
+            if self.tagged_clients.load(Ordering::Relaxed) >= MAX_TAGGED_CLIENTS {
+                trace!("Not tagging client {}, too many are already", name);
+                return;
+            }
+            let _ = self.tagged_clients.fetch_add(1, Ordering::Relaxed);
+        }
+        let _ = tags.entry(name).or_default().insert(tag);
+    }
+
+    /// Removes the tag from the peer, if it has it.
+    pub(crate) async fn untag(&self, name: &XorName, tag: PeerTag) {
+        let mut tags = self.tags.write().await;
+        if let Some(peer_tags) = tags.get_mut(name) {
+            if peer_tags.remove(&tag) && tag == PeerTag::Client {
+                let _ = self.tagged_clients.fetch_sub(1, Ordering::Relaxed);
+            }
+            if peer_tags.is_empty() {
+                let _ = tags.remove(name);
+            }
+        }
+    }
+
+    /// Tags of the peer, empty if we know nothing of it.
+    pub(crate) async fn tags(&self, name: &XorName) -> BTreeSet<PeerTag> {
+        self.tags
+            .read()
+            .await
+            .get(name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub(crate) async fn has_tag(&self, name: &XorName, tag: PeerTag) -> bool {
+        self.tags
+            .read()
+            .await
+            .get(name)
+            .map(|peer_tags| peer_tags.contains(&tag))
+            .unwrap_or(false)
+    }
+
+    /// Retags the members of our section, the peers which are not anymore losing
+    /// their `Elder` or `Adult` tag.
+    pub(crate) async fn set_section_members(
+        &self,
+        elders: &BTreeSet<XorName>,
+        adults: &BTreeSet<XorName>,
+    ) {
+        let mut tags = self.tags.write().await;
+        for peer_tags in tags.values_mut() {
+            let _ = peer_tags.remove(&PeerTag::Elder);
+            let _ = peer_tags.remove(&PeerTag::Adult);
+        }
+        for elder in elders {
+            let _ = tags.entry(*elder).or_default().insert(PeerTag::Elder);
+        }
+        for adult in adults {
+            let _ = tags.entry(*adult).or_default().insert(PeerTag::Adult);
+        }
+        tags.retain(|_, peer_tags| !peer_tags.is_empty());
+    }
+
+    /// Counts a msg received from the peer against each of its tags.
+    pub(crate) async fn count_msg_from(&self, name: &XorName) {
+        let peer_tags = self.tags(name).await;
+        if peer_tags.is_empty() {
+            return;
+        }
+        let mut msgs_received = self.msgs_received.write().await;
+        for tag in peer_tags {
+            *msgs_received.entry(tag).or_insert(0) += 1;
+        }
+    }
+
+    /// Number of peers with each tag.
+    pub(crate) async fn peers_per_tag(&self) -> BTreeMap<PeerTag, usize> {
+        let mut counts = BTreeMap::new();
+        for tag in self.tags.read().await.values().flatten() {
+            *counts.entry(*tag).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Number of msgs received from peers with each tag.
+    pub(crate) async fn msgs_received(&self) -> BTreeMap<PeerTag, u64> {
+        self.msgs_received.read().await.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use xor_name::rand::random as random_xorname;
+
+    #[tokio::test]
+    async fn section_members_are_retagged() {
+        let peer_tags = PeerTags::default();
+        let (elder, adult, client) = (random_xorname(), random_xorname(), random_xorname());
+
+        peer_tags.tag(elder, PeerTag::Bootstrap).await;
+        peer_tags.tag(client, PeerTag::Client).await;
+        peer_tags
+            .set_section_members(&BTreeSet::from([elder]), &BTreeSet::from([adult]))
+            .await;
+        assert_eq!(
+            peer_tags.tags(&elder).await,
+            BTreeSet::from([PeerTag::Elder, PeerTag::Bootstrap])
+        );
+        assert!(peer_tags.has_tag(&adult, PeerTag::Adult).await);
+
+        // the elder is demoted, and the adult leaves
+        peer_tags
+            .set_section_members(&BTreeSet::new(), &BTreeSet::from([elder]))
+            .await;
+        assert_eq!(
+            peer_tags.tags(&elder).await,
+            BTreeSet::from([PeerTag::Adult, PeerTag::Bootstrap])
+        );
+        assert!(peer_tags.tags(&adult).await.is_empty());
+        assert!(peer_tags.has_tag(&client, PeerTag::Client).await);
+        assert_eq!(
+            peer_tags.peers_per_tag().await,
+            BTreeMap::from([
+                (PeerTag::Adult, 1),
+                (PeerTag::Client, 1),
+                (PeerTag::Bootstrap, 1)
+            ])
+        );
+
+        peer_tags.untag(&client, PeerTag::Client).await;
+        assert!(!peer_tags.tags.read().await.contains_key(&client));
+    }
+
+    #[tokio::test]
+    async fn tagged_clients_are_bounded() {
+        let peer_tags = PeerTags::default();
+        let clients: Vec<_> = std::iter::repeat_with(random_xorname)
+            .take(MAX_TAGGED_CLIENTS)
+            .collect();
+        for client in &clients {
+            peer_tags.tag(*client, PeerTag::Client).await;
+        }
+        // tagging a client again doesn't count it twice
+        peer_tags.tag(clients[0], PeerTag::Client).await;
+
+        let extra = random_xorname();
+        peer_tags.tag(extra, PeerTag::Client).await;
+        assert!(!peer_tags.has_tag(&extra, PeerTag::Client).await);
+
+        // once a client's tag is removed, there's room for another
+        peer_tags.untag(&clients[0], PeerTag::Client).await;
+        peer_tags.tag(extra, PeerTag::Client).await;
+        assert!(peer_tags.has_tag(&extra, PeerTag::Client).await);
+        assert_eq!(
+            peer_tags.peers_per_tag().await,
+            BTreeMap::from([(PeerTag::Client, MAX_TAGGED_CLIENTS)])
+        );
+    }
+
+    #[tokio::test]
+    async fn msgs_are_counted_per_tag() {
+        let peer_tags = PeerTags::default();
+        let (elder, client, unknown) = (random_xorname(), random_xorname(), random_xorname());
+
+        peer_tags.tag(elder, PeerTag::Elder).await;
+        peer_tags.tag(elder, PeerTag::Bootstrap).await;
+        peer_tags.tag(client, PeerTag::Client).await;
+
+        for name in [elder, client, client, unknown] {
+            peer_tags.count_msg_from(&name).await;
+        }
+
+        assert_eq!(
+            peer_tags.msgs_received().await,
+            BTreeMap::from([
+                (PeerTag::Elder, 1),
+                (PeerTag::Client, 2),
+                (PeerTag::Bootstrap, 1)
+            ])
+        );
+    }
+}
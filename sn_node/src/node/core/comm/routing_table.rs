@@ -0,0 +1,124 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use sn_interface::types::Peer;
+
+use serde::Serialize;
+use std::{collections::BTreeMap, net::SocketAddr};
+use xor_name::XorName;
+
+/// The peers a node has sessions with, as seen from the node, for operators to debug its
+/// connectivity.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RoutingTable {
+    /// Name of the node
+    pub our_name: XorName,
+    /// Address of the node
+    pub our_addr: SocketAddr,
+    /// Peers the node has sessions with, closest to it first
+    pub peers: Vec<RoutingTableEntry>,
+}
+
+/// A peer in the [`RoutingTable`] of a node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct RoutingTableEntry {
+    /// Name of the peer
+    pub name: XorName,
+    /// Endpoint the peer is reached at
+    pub addr: SocketAddr,
+    /// Whether the session with the peer has an open connection
+    pub connected: bool,
+    /// Bucket of the peer, i.e. the number of leading bits its name has in common with ours,
+    /// the higher the closer
+    pub bucket: usize,
+    /// Whether the peer is an Elder of our section
+    pub elder: bool,
+}
+
+impl RoutingTable {
+    pub(crate) fn new(
+        our_name: XorName,
+        our_addr: SocketAddr,
+        connections: impl IntoIterator<Item = (Peer, bool)>,
+        is_elder: impl Fn(&XorName) -> bool,
+    ) -> Self {
+        let mut peers: Vec<_> = connections
+            .into_iter()
+            .map(|(peer, connected)| RoutingTableEntry {
+                name: peer.name(),
+                addr: peer.addr(),
+                connected,
+                bucket: our_name.common_prefix(&peer.name()),
+                elder: is_elder(&peer.name()),
+            })
+            .collect();
+        peers.sort_by(|lhs, rhs| our_name.cmp_distance(&lhs.name, &rhs.name));
+
+        Self {
+            our_name,
+            our_addr,
+            peers,
+        }
+    }
+
+    /// Number of peers in each bucket.
+    pub fn bucket_sizes(&self) -> BTreeMap<usize, usize> {
+        let mut sizes = BTreeMap::new();
+        for peer in &self.peers {
+            *sizes.entry(peer.bucket).or_default() += 1;
+        }
+        sizes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RoutingTable;
+    use sn_interface::types::Peer;
+
+    use std::collections::BTreeMap;
+    use xor_name::XorName;
+
+    #[test]
+    fn peers_are_bucketed_by_the_bits_their_names_share_with_ours() {
+        let our_name = XorName([0; 32]);
+        let name = |first_byte| {
+            let mut name: XorName = xor_name::rand::random();
+            name.0[0] = first_byte;
+            name
+        };
+        let far = Peer::new(name(0b1000_0000), ([127, 0, 0, 1], 12001).into());
+        let close = Peer::new(name(0b0001_0000), ([127, 0, 0, 1], 12002).into());
+        let closer = Peer::new(name(0b0000_0100), ([127, 0, 0, 1], 12003).into());
+
+        let table = RoutingTable::new(
+            our_name,
+            ([127, 0, 0, 1], 12000).into(),
+            [(far, true), (closer, false), (close, true)],
+            |name| *name == close.name(),
+        );
+
+        let peers: Vec<_> = table
+            .peers
+            .iter()
+            .map(|peer| (peer.name, peer.bucket, peer.connected, peer.elder))
+            .collect();
+        assert_eq!(
+            peers,
+            vec![
+                (closer.name(), 5, false, false),
+                (close.name(), 3, true, true),
+                (far.name(), 0, true, false),
+            ]
+        );
+        assert_eq!(
+            table.bucket_sizes(),
+            BTreeMap::from([(0, 1), (3, 1), (5, 1)])
+        );
+    }
+}
@@ -7,13 +7,28 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::node::{
-    api::cmds::Cmd,
-    core::{Node, Proposal},
+    api::{cmds::Cmd, event::Event},
+    cfg::bootstrap_cache::{add_to_bootstrap_cache, get_bootstrap_cache, store_bootstrap_cache},
+    core::{Liveness, LivenessConfig, Node, Proposal},
     Result,
 };
+use rand::seq::IteratorRandom;
+use sn_dysfunction::Signal;
+use sn_interface::{
+    messaging::system::SystemMsg,
+    types::{log_markers::LogMarker, Peer},
+};
 use std::{collections::BTreeSet, net::SocketAddr};
 use xor_name::XorName;
 
+// Max number of peers shared in a peer exchange
+const PEER_EXCHANGE_SAMPLE_SIZE: usize = 8;
+// Number of peers each sample is sent to
+const PEER_EXCHANGE_FANOUT: usize = 3;
+// Max number of the peers in our bootstrap cache a single member of our section can have shared,
+// for none of them to flush out the others
+const MAX_CACHED_PEERS_PER_MEMBER: usize = 4;
+
 impl Node {
     pub(crate) async fn handle_peer_lost(&self, addr: &SocketAddr) -> Result<Vec<Cmd>> {
         let name = if let Some(peer) = self.network_knowledge.find_member_by_addr(addr).await {
@@ -34,6 +49,181 @@ impl Node {
         Ok(cmds)
     }
 
+    /// Checks the liveness of the peers we watch, i.e. the members of our section as an Elder, or
+    /// its Elders as an Adult, pinging those we haven't heard from lately.
+    /// As an Elder, the peers deemed down are reported as dysfunctional, to be voted offline.
+    pub(crate) async fn check_peer_liveness(&self, config: &LivenessConfig) -> Result<Vec<Cmd>> {
+        let is_elder = self.is_elder().await;
+        let watched = self.watched_peers().await;
+
+        let check = self.comm.liveness().check(&watched, config).await;
+        for (name, liveness) in &check.changed {
+            match liveness {
+                Liveness::Healthy => info!("{:?} {} is healthy", LogMarker::PeerLiveness, name),
+                Liveness::Suspect => warn!("{:?} {} is suspect", LogMarker::PeerLiveness, name),
+                Liveness::Dead => {
+                    warn!("{:?} {} is dead", LogMarker::PeerLiveness, name);
+                    self.send_event(Event::PeerLost { name: *name }).await;
+                }
+            }
+        }
+
+        if is_elder {
+            // keep reporting them, for their dysfunction to be weighed against the others'
+            for name in check.dead {
+                self.log_comm_issue(name).await?;
+            }
+            for name in check.unanswered {
+                self.log_signal(name, Signal::MissedAeReply).await;
+            }
+        }
+
+        let section_key = self.network_knowledge.section_key().await;
+        let mut cmds = vec![];
+        for peer in check.to_ping {
+            cmds.push(
+                self.send_direct_msg(peer, SystemMsg::KeepAlive, section_key)
+                    .await?,
+            );
+        }
+        Ok(cmds)
+    }
+
+    /// Shares a sample of the watched peers we heard from lately with a few of them, for them to
+    /// have more nodes to bootstrap through than their contacts, should those churn away.
+    pub(crate) async fn exchange_peers(&self) -> Result<Vec<Cmd>> {
+        let healthy_names = self.comm.liveness().healthy().await;
+        let healthy: Vec<Peer> = self
+            .watched_peers()
+            .await
+            .into_iter()
+            .filter(|peer| healthy_names.contains(&peer.name()))
+            .collect();
+
+        let (sample, recipients) = {
+            let mut rng = rand::thread_rng();
+            (
+                healthy
+                    .iter()
+                    .copied()
+                    .choose_multiple(&mut rng, PEER_EXCHANGE_SAMPLE_SIZE),
+                healthy
+                    .iter()
+                    .copied()
+                    .choose_multiple(&mut rng, PEER_EXCHANGE_FANOUT),
+            )
+        };
+
+        let section_key = self.network_knowledge.section_key().await;
+        let mut cmds = vec![];
+        for recipient in recipients {
+            // no need to tell a peer about itself
+            let peers: Vec<_> = sample
+                .iter()
+                .filter(|peer| peer.name() != recipient.name())
+                .copied()
+                .collect();
+            if peers.is_empty() {
+                continue;
+            }
+            cmds.push(
+                self.send_direct_msg(recipient, SystemMsg::PeerExchange(peers), section_key)
+                    .await?,
+            );
+        }
+        Ok(cmds)
+    }
+
+    /// Adds the peers shared by a member of our section to our bootstrap cache.
+    pub(crate) async fn handle_peer_exchange(
+        &self,
+        sender: Peer,
+        peers: Vec<Peer>,
+    ) -> Result<Vec<Cmd>> {
+        // the sender is authenticated by the signature of the msg, but only the members of our
+        // section are trusted to share peers worth bootstrapping through
+        if self
+            .network_knowledge
+            .get_section_member(&sender.name())
+            .await
+            .is_none()
+        {
+            debug!(
+                "Ignoring peers shared by {}, not a member of our section",
+                sender
+            );
+            return Ok(vec![]);
+        }
+
+        // held while the cache is read and written, for concurrent exchanges not to lose updates
+        let mut shared_by = self.bootstrap_peers_shared.lock().await;
+        let cached: BTreeSet<_> = get_bootstrap_cache(&self.root_storage_dir)
+            .await?
+            .iter()
+            .map(Peer::name)
+            .collect();
+        shared_by.retain(|_, shared| {
+            shared.retain(|name| cached.contains(name));
+            !shared.is_empty()
+        });
+
+        // the peers the sender shared before can be updated, while new ones only take the room
+        // left to the sender
+        let our_name = self.info.read().await.name();
+        let shared = shared_by.entry(sender.name()).or_default();
+        let mut room = MAX_CACHED_PEERS_PER_MEMBER.saturating_sub(shared.len());
+        let peers: Vec<_> = peers
+            .into_iter()
+            .filter(|peer| peer.name() != our_name)
+            .take(PEER_EXCHANGE_SAMPLE_SIZE)
+            .filter(|peer| {
+                if shared.contains(&peer.name()) {
+                    true
+                } else if room > 0 {
+                    room -= 1;
+                    true
+                } else {
+                    false
+                }
+            })
+            .collect();
+        if !peers.is_empty() {
+            add_to_bootstrap_cache(&self.root_storage_dir, &peers).await?;
+            shared.extend(peers.iter().map(Peer::name));
+        }
+        Ok(vec![])
+    }
+
+    /// Replaces our bootstrap cache with the given peers, e.g. the Elders of our section.
+    pub(crate) async fn store_bootstrap_cache(&self, peers: &[Peer]) -> Result<()> {
+        let mut shared_by = self.bootstrap_peers_shared.lock().await;
+        store_bootstrap_cache(&self.root_storage_dir, peers).await?;
+        shared_by.clear();
+        Ok(())
+    }
+
+    // The peers we watch the liveness of, i.e. the members of our section as an Elder, or its
+    // Elders as an Adult
+    async fn watched_peers(&self) -> BTreeSet<Peer> {
+        let our_name = self.info.read().await.name();
+        if self.is_elder().await {
+            self.network_knowledge
+                .section_members()
+                .await
+                .iter()
+                .map(|node_state| *node_state.peer())
+                .filter(|peer| peer.name() != our_name)
+                .collect()
+        } else {
+            self.network_knowledge
+                .elders()
+                .await
+                .into_iter()
+                .filter(|peer| peer.name() != our_name)
+                .collect()
+        }
+    }
+
     pub(crate) async fn cast_offline_proposals(
         &self,
         names: &BTreeSet<XorName>,
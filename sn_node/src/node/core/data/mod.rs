@@ -6,8 +6,23 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+mod ordered_cmds;
+mod pending_replications;
+mod query_cache;
+mod query_delegation;
+mod rate_limiter;
 mod records;
+mod spent_payments;
 mod storage;
 
+pub(crate) use self::ordered_cmds::{CmdOrder, OrderedCmds};
+pub(crate) use self::pending_replications::PendingReplications;
+pub(crate) use self::query_cache::QueryCache;
+pub use self::query_cache::QueryCacheStats;
+pub use self::query_delegation::{AdultParticipation, QueryDelegationConfig};
+pub(crate) use self::query_delegation::{DelegationOutcome, QueryDelegation};
+pub(crate) use self::rate_limiter::ClientRateLimiter;
+pub use self::rate_limiter::ClientRateLimits;
 pub(crate) use self::records::{Capacity, MIN_LEVEL_WHEN_FULL};
-pub use self::storage::DataStorage;
+pub(crate) use self::spent_payments::{PaymentClaim, SpentPayments};
+pub use self::storage::{DataStorage, ScrubProgress};
@@ -0,0 +1,219 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use sn_interface::types::PublicKey;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use xor_name::{Prefix, XorName};
+
+// Number of streams tracked at once after which the idle ones are dropped
+const PRUNE_THRESHOLD: usize = 10_000;
+// How long a stream is kept track of after its last cmd, once there are too many of them.
+// Clients number their cmds from the time they're sent, so a cmd sent after its stream was
+// dropped still comes after the ones applied before.
+const STREAM_IDLE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Where an ordered cmd stands in the stream of cmds a client sends to the same data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CmdOrder {
+    /// The cmd comes after all the ones applied, so it's to be applied.
+    Next,
+    /// The cmd is the last one applied, sent again as the client didn't hear back.
+    Applied,
+    /// A later cmd was applied already, so the cmd is to be rejected.
+    Stale {
+        /// Sequence number of the latest cmd applied
+        applied: u64,
+    },
+}
+
+// Latest cmd applied of a stream
+#[derive(Debug)]
+struct StreamState {
+    applied: u64,
+    applied_at: Instant,
+}
+
+/// Sequence numbers of the latest ordered cmds an Elder applied, by client and data written to.
+///
+/// They're handed over to the Elders joining us along with the rest of the metadata, for the
+/// cmds applied before not to be applied again once they're Elders.
+#[derive(Debug, Default)]
+pub(crate) struct OrderedCmds {
+    streams: Mutex<HashMap<(PublicKey, XorName), StreamState>>,
+}
+
+impl OrderedCmds {
+    /// Where the cmd with the given sequence number stands in its stream, without recording
+    /// anything, for a stale cmd to be rejected before it's validated.
+    pub(crate) async fn check(&self, client: PublicKey, dst: XorName, seq: u64) -> CmdOrder {
+        match self.streams.lock().await.get(&(client, dst)) {
+            Some(state) if seq < state.applied => CmdOrder::Stale {
+                applied: state.applied,
+            },
+            Some(state) if seq == state.applied => CmdOrder::Applied,
+            _ => CmdOrder::Next,
+        }
+    }
+
+    /// Claims the place of the cmd with the given sequence number in its stream, once it was
+    /// accepted, returning where it stands. A cmd coming [`CmdOrder::Next`] is recorded as
+    /// applied right away, under the same lock, so an earlier cmd accepted concurrently is found
+    /// stale rather than applied too. A cmd rejected is never claimed, so it doesn't advance its
+    /// stream.
+    pub(crate) async fn claim(&self, client: PublicKey, dst: XorName, seq: u64) -> CmdOrder {
+        self.claim_at(client, dst, seq, Instant::now()).await
+    }
+
+    /// Records the cmds applied by other Elders, keeping the latest of each stream.
+    pub(crate) async fn merge(&self, applied: BTreeMap<(PublicKey, XorName), u64>) {
+        let now = Instant::now();
+        let mut streams = self.streams.lock().await;
+        for ((client, dst), seq) in applied {
+            let state = streams.entry((client, dst)).or_insert(StreamState {
+                applied: seq,
+                applied_at: now,
+            });
+            state.applied = state.applied.max(seq);
+        }
+        Self::prune(&mut streams, now);
+    }
+
+    /// The latest cmds applied to data matching the given prefix, by client and data.
+    pub(crate) async fn matching(&self, prefix: &Prefix) -> BTreeMap<(PublicKey, XorName), u64> {
+        self.streams
+            .lock()
+            .await
+            .iter()
+            .filter(|((_, dst), _)| prefix.matches(dst))
+            .map(|(key, state)| (*key, state.applied))
+            .collect()
+    }
+
+    async fn claim_at(&self, client: PublicKey, dst: XorName, seq: u64, now: Instant) -> CmdOrder {
+        let mut streams = self.streams.lock().await;
+        Self::prune(&mut streams, now);
+
+        match streams.get_mut(&(client, dst)) {
+            Some(state) if seq < state.applied => CmdOrder::Stale {
+                applied: state.applied,
+            },
+            Some(state) if seq == state.applied => {
+                state.applied_at = now;
+                CmdOrder::Applied
+            }
+            Some(state) => {
+                state.applied = seq;
+                state.applied_at = now;
+                CmdOrder::Next
+            }
+            None => {
+                let _ = streams.insert(
+                    (client, dst),
+                    StreamState {
+                        applied: seq,
+                        applied_at: now,
+                    },
+                );
+                CmdOrder::Next
+            }
+        }
+    }
+
+    // Drops the idle streams, once there are too many of them
+    fn prune(streams: &mut HashMap<(PublicKey, XorName), StreamState>, now: Instant) {
+        if streams.len() >= PRUNE_THRESHOLD {
+            streams.retain(|_, state| {
+                now.saturating_duration_since(state.applied_at) < STREAM_IDLE_TIMEOUT
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sn_interface::types::Keypair;
+
+    #[tokio::test]
+    async fn cmds_applied_out_of_order_are_stale() {
+        let ordered_cmds = OrderedCmds::default();
+        let client = Keypair::new_ed25519().public_key();
+        let dst = xor_name::rand::random();
+        let other_dst = xor_name::rand::random();
+
+        assert_eq!(ordered_cmds.claim(client, dst, 5).await, CmdOrder::Next);
+        // the same cmd sent again, e.g. as the client didn't hear back
+        assert_eq!(ordered_cmds.claim(client, dst, 5).await, CmdOrder::Applied);
+        assert_eq!(
+            ordered_cmds.claim(client, dst, 4).await,
+            CmdOrder::Stale { applied: 5 }
+        );
+
+        // gaps are allowed, as the client may have given up on some cmds
+        assert_eq!(ordered_cmds.claim(client, dst, 9).await, CmdOrder::Next);
+        assert_eq!(
+            ordered_cmds.claim(client, dst, 5).await,
+            CmdOrder::Stale { applied: 9 }
+        );
+
+        // streams to other data, or of other clients, are not affected
+        assert_eq!(
+            ordered_cmds.claim(client, other_dst, 1).await,
+            CmdOrder::Next
+        );
+        let other_client = Keypair::new_ed25519().public_key();
+        assert_eq!(
+            ordered_cmds.claim(other_client, dst, 1).await,
+            CmdOrder::Next
+        );
+    }
+
+    #[tokio::test]
+    async fn checking_a_cmd_does_not_advance_its_stream() {
+        let ordered_cmds = OrderedCmds::default();
+        let client = Keypair::new_ed25519().public_key();
+        let dst = xor_name::rand::random();
+
+        assert_eq!(ordered_cmds.check(client, dst, 5).await, CmdOrder::Next);
+        assert_eq!(ordered_cmds.check(client, dst, 4).await, CmdOrder::Next);
+        assert_eq!(ordered_cmds.claim(client, dst, 4).await, CmdOrder::Next);
+        assert_eq!(ordered_cmds.check(client, dst, 4).await, CmdOrder::Applied);
+        assert_eq!(
+            ordered_cmds.check(client, dst, 3).await,
+            CmdOrder::Stale { applied: 4 }
+        );
+    }
+
+    #[tokio::test]
+    async fn cmds_applied_by_other_elders_are_merged() {
+        let ordered_cmds = OrderedCmds::default();
+        let client = Keypair::new_ed25519().public_key();
+        let (dst, other_dst) = (xor_name::rand::random(), xor_name::rand::random());
+        assert_eq!(ordered_cmds.claim(client, dst, 5).await, CmdOrder::Next);
+
+        ordered_cmds
+            .merge(BTreeMap::from([
+                ((client, dst), 3),
+                ((client, other_dst), 7),
+            ]))
+            .await;
+        assert_eq!(
+            ordered_cmds.matching(&Prefix::default()).await,
+            BTreeMap::from([((client, dst), 5), ((client, other_dst), 7)])
+        );
+        assert_eq!(
+            ordered_cmds.claim(client, other_dst, 6).await,
+            CmdOrder::Stale { applied: 7 }
+        );
+    }
+}
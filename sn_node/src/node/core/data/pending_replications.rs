@@ -0,0 +1,209 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use sn_interface::{messaging::MsgId, types::Peer};
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use xor_name::XorName;
+
+// How long the Adults' confirmations are waited on, after which the client is not told anymore.
+// This is longer than the default time clients wait for them.
+const REPLICATION_CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(60);
+
+// A client cmd whose data is being stored by Adults
+#[derive(Debug)]
+struct PendingReplication {
+    client: Peer,
+    // Adults which have yet to confirm storing the data
+    awaited: BTreeSet<XorName>,
+    started: Instant,
+}
+
+/// Client cmds sent with the `Replicated` ack level, tracked by the Elder which told the Adults
+/// to store their data, until all of them confirmed they did.
+#[derive(Debug, Default)]
+pub(crate) struct PendingReplications {
+    pending: Mutex<HashMap<MsgId, PendingReplication>>,
+}
+
+impl PendingReplications {
+    /// Start waiting for the given Adults to confirm storing the data of the client's cmd,
+    /// unless there are none to wait for.
+    /// Returns the Adults which failed to confirm storing the data of earlier cmds in time.
+    pub(crate) async fn track(
+        &self,
+        correlation_id: MsgId,
+        client: Peer,
+        adults: BTreeSet<XorName>,
+    ) -> Vec<XorName> {
+        self.track_at(correlation_id, client, adults, Instant::now())
+            .await
+    }
+
+    /// Record the Adult confirmed storing the data of the cmd.
+    /// Returns the client to tell, once all the Adults did.
+    pub(crate) async fn confirm(&self, correlation_id: MsgId, adult: XorName) -> Option<Peer> {
+        self.confirm_at(correlation_id, adult, Instant::now()).await
+    }
+
+    async fn track_at(
+        &self,
+        correlation_id: MsgId,
+        client: Peer,
+        adults: BTreeSet<XorName>,
+        now: Instant,
+    ) -> Vec<XorName> {
+        let mut pending = self.pending.lock().await;
+        let mut failed = vec![];
+        pending.retain(|_, replication| {
+            let timed_out = now.saturating_duration_since(replication.started)
+                >= REPLICATION_CONFIRMATION_TIMEOUT;
+            if timed_out {
+                failed.extend(replication.awaited.iter().copied());
+            }
+            !timed_out
+        });
+        // no confirmation would ever complete it
+        if adults.is_empty() {
+            return failed;
+        }
+        let _prev = pending.insert(
+            correlation_id,
+            PendingReplication {
+                client,
+                awaited: adults,
+                started: now,
+            },
+        );
+        failed
+    }
+
+    async fn confirm_at(
+        &self,
+        correlation_id: MsgId,
+        adult: XorName,
+        now: Instant,
+    ) -> Option<Peer> {
+        let mut pending = self.pending.lock().await;
+        let replication = pending.get_mut(&correlation_id)?;
+        if now.saturating_duration_since(replication.started) >= REPLICATION_CONFIRMATION_TIMEOUT {
+            let _expired = pending.remove(&correlation_id);
+            return None;
+        }
+
+        let _ = replication.awaited.remove(&adult);
+        if replication.awaited.is_empty() {
+            pending
+                .remove(&correlation_id)
+                .map(|replication| replication.client)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn client_is_told_once_all_adults_confirmed() {
+        let pending = PendingReplications::default();
+        let client = Peer::new(xor_name::rand::random(), ([127, 0, 0, 1], 12000).into());
+        let adults: [XorName; 3] = [
+            xor_name::rand::random(),
+            xor_name::rand::random(),
+            xor_name::rand::random(),
+        ];
+        let correlation_id = MsgId::new();
+        let now = Instant::now();
+
+        let failed = pending
+            .track_at(correlation_id, client, BTreeSet::from(adults), now)
+            .await;
+        assert!(failed.is_empty());
+        // confirmations of other cmds or from unexpected nodes don't count
+        assert_eq!(pending.confirm_at(MsgId::new(), adults[0], now).await, None);
+        assert_eq!(
+            pending
+                .confirm_at(correlation_id, xor_name::rand::random(), now)
+                .await,
+            None
+        );
+
+        assert_eq!(
+            pending.confirm_at(correlation_id, adults[0], now).await,
+            None
+        );
+        assert_eq!(
+            pending.confirm_at(correlation_id, adults[1], now).await,
+            None
+        );
+        assert_eq!(
+            pending.confirm_at(correlation_id, adults[2], now).await,
+            Some(client)
+        );
+        // the client is told only once
+        assert_eq!(
+            pending.confirm_at(correlation_id, adults[2], now).await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn cmd_without_adults_is_not_tracked() {
+        let pending = PendingReplications::default();
+        let client = Peer::new(xor_name::rand::random(), ([127, 0, 0, 1], 12000).into());
+
+        let failed = pending
+            .track_at(MsgId::new(), client, BTreeSet::new(), Instant::now())
+            .await;
+        assert!(failed.is_empty());
+        assert!(pending.pending.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn late_confirmations_are_dropped() {
+        let pending = PendingReplications::default();
+        let client = Peer::new(xor_name::rand::random(), ([127, 0, 0, 1], 12000).into());
+        let adult = xor_name::rand::random();
+        let correlation_id = MsgId::new();
+        let now = Instant::now();
+
+        pending
+            .track_at(correlation_id, client, BTreeSet::from([adult]), now)
+            .await;
+        let later = now + REPLICATION_CONFIRMATION_TIMEOUT;
+        assert_eq!(pending.confirm_at(correlation_id, adult, later).await, None);
+    }
+
+    #[tokio::test]
+    async fn adults_not_confirming_in_time_are_reported() {
+        let pending = PendingReplications::default();
+        let client = Peer::new(xor_name::rand::random(), ([127, 0, 0, 1], 12000).into());
+        let (prompt, slow): (XorName, XorName) =
+            (xor_name::rand::random(), xor_name::rand::random());
+        let correlation_id = MsgId::new();
+        let now = Instant::now();
+
+        let _ = pending
+            .track_at(correlation_id, client, BTreeSet::from([prompt, slow]), now)
+            .await;
+        assert_eq!(pending.confirm_at(correlation_id, prompt, now).await, None);
+
+        let later = now + REPLICATION_CONFIRMATION_TIMEOUT;
+        let failed = pending
+            .track_at(MsgId::new(), client, BTreeSet::from([prompt]), later)
+            .await;
+        assert_eq!(failed, vec![slow]);
+    }
+}
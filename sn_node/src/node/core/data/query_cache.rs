@@ -0,0 +1,293 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use sn_interface::messaging::data::{OperationId, QueryResponse};
+use sn_interface::types::{Cache, PublicKey, ReplicatedDataAddress};
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use xor_name::XorName;
+
+// Max number of query responses held by an Elder at once
+const QUERY_CACHE_CAPACITY: usize = 256;
+// How long a query response is served from the cache before asking the Adults again.
+// This must be longer than the time a query is waited on, see `QueryCache::versions`.
+const QUERY_CACHE_TTL: Duration = Duration::from_secs(60);
+// How long a query forwarded to the Adults is waited on
+const IN_FLIGHT_QUERY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Hit-rate metrics of the query response cache of an Elder.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct QueryCacheStats {
+    /// Number of queries answered from the cache
+    pub hits: u64,
+    /// Number of queries which had to be forwarded to the Adults
+    pub misses: u64,
+    /// Number of responses currently cached
+    pub entries: usize,
+}
+
+impl QueryCacheStats {
+    /// Share of the queries answered from the cache, between 0 and 1
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+/// Responses to data queries, cached by Elders so hot content isn't fetched
+/// from the Adults on every read.
+///
+/// Responses are keyed by the query and the version of the data they were read at, and served
+/// to any client making the same query. Responses to reads of private registers are keyed by
+/// the client which made the query as well: the Adults answered it with the permissions of that
+/// client, so its response is never served to other clients, which may not be allowed to read
+/// the data. The version of a piece of data is bumped with each write to it the Elder handles,
+/// after which the responses cached for the previous versions are never served again.
+#[derive(Debug)]
+pub(crate) struct QueryCache {
+    responses: Cache<(Option<PublicKey>, OperationId, u64), QueryResponse>,
+    // Versions of the data written to recently. Data never written to is at version 0.
+    // A version entry only expires once all the responses, and in-flight queries, for
+    // earlier versions have, so going back to version 0 can't revive stale responses.
+    versions: Cache<XorName, u64>,
+    // Queries forwarded to the Adults, with the client which made them if the response is
+    // only theirs, and the name and version of the data they read
+    in_flight: Cache<OperationId, (Option<PublicKey>, XorName, u64)>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl Default for QueryCache {
+    fn default() -> Self {
+        Self {
+            responses: Cache::with_expiry_duration_and_capacity(
+                QUERY_CACHE_TTL,
+                QUERY_CACHE_CAPACITY,
+            ),
+            versions: Cache::with_expiry_duration(QUERY_CACHE_TTL),
+            in_flight: Cache::with_expiry_duration(IN_FLIGHT_QUERY_TIMEOUT),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+}
+
+impl QueryCache {
+    /// Look up the response to a query of the given client for the data at `address`, at its
+    /// current version
+    pub(crate) async fn get(
+        &self,
+        requester: PublicKey,
+        operation_id: OperationId,
+        address: &ReplicatedDataAddress,
+    ) -> Option<QueryResponse> {
+        let version = self.version(address.name()).await;
+        let response = self
+            .responses
+            .get(&(reader(requester, address), operation_id, version))
+            .await;
+        let counter = if response.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        let _ = counter.fetch_add(1, Ordering::Relaxed);
+        response
+    }
+
+    /// Record a query of the given client for the data at `address` as forwarded to the Adults,
+    /// so its response can be cached once it comes back
+    pub(crate) async fn track_forwarded(
+        &self,
+        requester: PublicKey,
+        operation_id: OperationId,
+        address: &ReplicatedDataAddress,
+    ) {
+        let name = *address.name();
+        let version = self.version(&name).await;
+        let _ = self
+            .in_flight
+            .set(
+                operation_id,
+                (reader(requester, address), name, version),
+                None,
+            )
+            .await;
+    }
+
+    /// Cache the response to an in-flight query, unless the data it read
+    /// was written to since the query was forwarded to the Adults.
+    pub(crate) async fn insert(&self, operation_id: OperationId, response: QueryResponse) {
+        if !response.is_success() {
+            return;
+        }
+
+        let (requester, name, version) = match self.in_flight.remove(&operation_id).await {
+            Some(in_flight) => in_flight,
+            None => return,
+        };
+
+        if self.version(&name).await != version {
+            trace!(
+                "Not caching response to {:?}, the data was written to meanwhile",
+                operation_id
+            );
+            return;
+        }
+
+        if version > 0 {
+            // keep the version around for as long as the response is
+            let _ = self.versions.set(name, version, None).await;
+        }
+        let _ = self
+            .responses
+            .set((requester, operation_id, version), response, None)
+            .await;
+    }
+
+    /// Bump the version of the data at `name`, following a write to it
+    pub(crate) async fn invalidate(&self, name: XorName) {
+        let version = self.version(&name).await + 1;
+        let _ = self.versions.set(name, version, None).await;
+    }
+
+    pub(crate) async fn stats(&self) -> QueryCacheStats {
+        QueryCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.responses.len().await,
+        }
+    }
+
+    async fn version(&self, name: &XorName) -> u64 {
+        self.versions.get(name).await.unwrap_or_default()
+    }
+}
+
+// The client a response to a query of the data at the given address is only served to, if any.
+// Anyone can read chunks, public registers and spentbooks, while the Adults check the permissions
+// of the client reading a private register.
+fn reader(requester: PublicKey, address: &ReplicatedDataAddress) -> Option<PublicKey> {
+    match address {
+        ReplicatedDataAddress::Register(address) if address.is_private() => Some(requester),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sn_interface::{
+        messaging::data::Error,
+        types::{register::User, ChunkAddress, Keypair, RegisterAddress, Scope},
+    };
+
+    fn found(operation_id: OperationId) -> QueryResponse {
+        QueryResponse::GetRegisterOwner((Ok(User::Anyone), operation_id))
+    }
+
+    fn failed(operation_id: OperationId) -> QueryResponse {
+        QueryResponse::GetRegisterOwner((Err(Error::NoSuchEntry), operation_id))
+    }
+
+    fn chunk_address() -> ReplicatedDataAddress {
+        ReplicatedDataAddress::Chunk(ChunkAddress(xor_name::rand::random()))
+    }
+
+    fn register_address(scope: Scope) -> ReplicatedDataAddress {
+        ReplicatedDataAddress::Register(RegisterAddress::new(
+            xor_name::rand::random(),
+            scope,
+            rand::random(),
+        ))
+    }
+
+    #[tokio::test]
+    async fn responses_are_served_until_the_data_is_written_to() {
+        let cache = QueryCache::default();
+        let address = register_address(Scope::Public);
+        let operation_id = OperationId(rand::random());
+        let client = Keypair::new_ed25519().public_key();
+
+        assert!(cache.get(client, operation_id, &address).await.is_none());
+        cache.track_forwarded(client, operation_id, &address).await;
+        cache.insert(operation_id, found(operation_id)).await;
+        assert!(cache.get(client, operation_id, &address).await.is_some());
+
+        cache.invalidate(*address.name()).await;
+        assert!(cache.get(client, operation_id, &address).await.is_none());
+
+        let stats = cache.stats().await;
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 2);
+        assert!((stats.hit_rate() - 1.0 / 3.0).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn stale_and_failed_responses_are_not_cached() {
+        let cache = QueryCache::default();
+        let address = register_address(Scope::Public);
+        let operation_id = OperationId(rand::random());
+        let client = Keypair::new_ed25519().public_key();
+
+        // the data is written to while the query is in flight
+        cache.track_forwarded(client, operation_id, &address).await;
+        cache.invalidate(*address.name()).await;
+        cache.insert(operation_id, found(operation_id)).await;
+        assert!(cache.get(client, operation_id, &address).await.is_none());
+
+        cache.track_forwarded(client, operation_id, &address).await;
+        cache.insert(operation_id, failed(operation_id)).await;
+        assert!(cache.get(client, operation_id, &address).await.is_none());
+        assert_eq!(cache.stats().await.entries, 0);
+    }
+
+    #[tokio::test]
+    async fn responses_to_reads_of_public_data_are_served_to_any_client() {
+        let cache = QueryCache::default();
+        let client = Keypair::new_ed25519().public_key();
+        let other_client = Keypair::new_ed25519().public_key();
+
+        for address in [chunk_address(), register_address(Scope::Public)] {
+            let operation_id = OperationId(rand::random());
+            cache.track_forwarded(client, operation_id, &address).await;
+            cache.insert(operation_id, found(operation_id)).await;
+            assert!(cache
+                .get(other_client, operation_id, &address)
+                .await
+                .is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn responses_to_reads_of_private_data_are_only_served_to_their_client() {
+        let cache = QueryCache::default();
+        let address = register_address(Scope::Private);
+        let operation_id = OperationId(rand::random());
+        let client = Keypair::new_ed25519().public_key();
+
+        cache.track_forwarded(client, operation_id, &address).await;
+        cache.insert(operation_id, found(operation_id)).await;
+        assert!(cache.get(client, operation_id, &address).await.is_some());
+
+        // another client, maybe not allowed to read the data, has its query forwarded to the
+        // Adults, for them to check its permissions
+        let other_client = Keypair::new_ed25519().public_key();
+        assert!(cache
+            .get(other_client, operation_id, &address)
+            .await
+            .is_none());
+    }
+}
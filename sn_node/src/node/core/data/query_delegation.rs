@@ -0,0 +1,382 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use sn_interface::messaging::{data::OperationId, system::SystemMsg};
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+use xor_name::XorName;
+
+/// Tuning of the delegation of data queries by Elders to the Adults holding the data.
+///
+/// Rather than forwarding a query to all the holders at once, an Elder delegates it to the
+/// closest ones only, falling back to the next ones when the delegates time out or don't have
+/// the data, until one of them answers with it or all of them answered without it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueryDelegationConfig {
+    /// Number of holders a query is delegated to at once. A value of 0 disables delegation,
+    /// queries are then forwarded to all the holders at once.
+    pub delegates: usize,
+    /// How long the delegates are waited on before the query falls back to the next holders
+    pub timeout: Duration,
+}
+
+impl Default for QueryDelegationConfig {
+    fn default() -> Self {
+        Self {
+            delegates: 0,
+            timeout: Duration::from_secs(3),
+        }
+    }
+}
+
+/// Participation of an Adult in answering the data queries delegated to it by an Elder.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AdultParticipation {
+    /// Number of queries delegated to the Adult
+    pub delegated: u64,
+    /// Number of queries the Adult answered with the data
+    pub answered: u64,
+    /// Number of queries the Adult answered without the data
+    pub failed: u64,
+    /// Number of queries the Adult didn't answer in time
+    pub timed_out: u64,
+}
+
+impl AdultParticipation {
+    /// Share of the queries delegated to the Adult it answered with the data, between 0 and 1
+    pub fn answer_rate(&self) -> f64 {
+        if self.delegated == 0 {
+            0.0
+        } else {
+            self.answered as f64 / self.delegated as f64
+        }
+    }
+}
+
+/// What's next for a delegated query, once one of its delegates answered without the data.
+#[derive(Debug, PartialEq)]
+pub(crate) enum DelegationOutcome {
+    /// Other delegates are still awaited, or the query wasn't delegated
+    Awaiting,
+    /// The query is to be forwarded to the next holders
+    FallBack(SystemMsg, BTreeSet<XorName>),
+    /// All the holders answered without the data, which is to be relayed to the client
+    Exhausted,
+}
+
+// A query delegated to some of the holders of its data
+#[derive(Debug)]
+struct Delegation {
+    msg: SystemMsg,
+    awaited: BTreeSet<XorName>,
+    // holders not asked yet, the closest to the data first
+    fallback: VecDeque<XorName>,
+    asked_at: Instant,
+}
+
+/// Data queries an Elder delegated to Adults, and how each Adult took part in answering them.
+#[derive(Debug)]
+pub(crate) struct QueryDelegation {
+    config: QueryDelegationConfig,
+    delegations: Mutex<HashMap<OperationId, Delegation>>,
+    participation: Mutex<BTreeMap<XorName, AdultParticipation>>,
+}
+
+impl QueryDelegation {
+    pub(crate) fn new(config: QueryDelegationConfig) -> Self {
+        Self {
+            config,
+            delegations: Mutex::new(HashMap::new()),
+            participation: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub(crate) fn config(&self) -> &QueryDelegationConfig {
+        &self.config
+    }
+
+    /// Delegates the query to the closest of the holders of its data, given the closest first.
+    /// Returns the holders to forward the query to, i.e. all of them if delegation is disabled.
+    pub(crate) async fn delegate(
+        &self,
+        operation_id: OperationId,
+        msg: SystemMsg,
+        holders: Vec<XorName>,
+    ) -> BTreeSet<XorName> {
+        self.delegate_at(operation_id, msg, holders, Instant::now())
+            .await
+    }
+
+    /// Records the delegate answered the query with the data, which is then not delegated anymore.
+    pub(crate) async fn answered(&self, operation_id: OperationId, adult: XorName) {
+        let mut delegations = self.delegations.lock().await;
+        if let Some(delegation) = delegations.get(&operation_id) {
+            if delegation.awaited.contains(&adult) {
+                let _ = delegations.remove(&operation_id);
+                self.participation
+                    .lock()
+                    .await
+                    .entry(adult)
+                    .or_default()
+                    .answered += 1;
+            }
+        }
+    }
+
+    /// Records the delegate answered the query without the data, returning what's next for it.
+    pub(crate) async fn failed(
+        &self,
+        operation_id: OperationId,
+        adult: XorName,
+    ) -> DelegationOutcome {
+        self.failed_at(operation_id, adult, Instant::now()).await
+    }
+
+    /// Falls the queries whose delegates didn't answer in time back to the next holders.
+    /// Returns the queries to forward, along with the holders to forward them to.
+    pub(crate) async fn fall_back_timed_out(
+        &self,
+    ) -> Vec<(OperationId, SystemMsg, BTreeSet<XorName>)> {
+        self.fall_back_timed_out_at(Instant::now()).await
+    }
+
+    /// Participation of each of the Adults queries were delegated to.
+    pub(crate) async fn participation(&self) -> BTreeMap<XorName, AdultParticipation> {
+        self.participation.lock().await.clone()
+    }
+
+    /// Forgets the participation of the Adults which are not members anymore.
+    pub(crate) async fn retain_members_only(&self, members: &BTreeSet<XorName>) {
+        self.participation
+            .lock()
+            .await
+            .retain(|adult, _| members.contains(adult));
+    }
+
+    async fn delegate_at(
+        &self,
+        operation_id: OperationId,
+        msg: SystemMsg,
+        holders: Vec<XorName>,
+        now: Instant,
+    ) -> BTreeSet<XorName> {
+        if self.config.delegates == 0 {
+            return holders.into_iter().collect();
+        }
+
+        let mut fallback = VecDeque::from(holders);
+        let delegates = take_delegates(
+            self.config.delegates,
+            &mut fallback,
+            &mut *self.participation.lock().await,
+        );
+        let _prev = self.delegations.lock().await.insert(
+            operation_id,
+            Delegation {
+                msg,
+                awaited: delegates.clone(),
+                fallback,
+                asked_at: now,
+            },
+        );
+        delegates
+    }
+
+    async fn failed_at(
+        &self,
+        operation_id: OperationId,
+        adult: XorName,
+        now: Instant,
+    ) -> DelegationOutcome {
+        let mut delegations = self.delegations.lock().await;
+        let delegation = match delegations.get_mut(&operation_id) {
+            Some(delegation) => delegation,
+            None => return DelegationOutcome::Awaiting,
+        };
+        if !delegation.awaited.remove(&adult) {
+            return DelegationOutcome::Awaiting;
+        }
+        let mut participation = self.participation.lock().await;
+        participation.entry(adult).or_default().failed += 1;
+
+        if !delegation.awaited.is_empty() {
+            DelegationOutcome::Awaiting
+        } else if delegation.fallback.is_empty() {
+            let _ = delegations.remove(&operation_id);
+            DelegationOutcome::Exhausted
+        } else {
+            let delegates = take_delegates(
+                self.config.delegates,
+                &mut delegation.fallback,
+                &mut participation,
+            );
+            delegation.awaited = delegates.clone();
+            delegation.asked_at = now;
+            DelegationOutcome::FallBack(delegation.msg.clone(), delegates)
+        }
+    }
+
+    async fn fall_back_timed_out_at(
+        &self,
+        now: Instant,
+    ) -> Vec<(OperationId, SystemMsg, BTreeSet<XorName>)> {
+        let mut delegations = self.delegations.lock().await;
+        let mut participation = self.participation.lock().await;
+        let mut fall_backs = vec![];
+
+        delegations.retain(|operation_id, delegation| {
+            if now.saturating_duration_since(delegation.asked_at) < self.config.timeout {
+                return true;
+            }
+            for adult in &delegation.awaited {
+                participation.entry(*adult).or_default().timed_out += 1;
+            }
+            if delegation.fallback.is_empty() {
+                debug!(
+                    "No holder left to delegate query {:?} to, after its delegates timed out",
+                    operation_id
+                );
+                return false;
+            }
+
+            let delegates = take_delegates(
+                self.config.delegates,
+                &mut delegation.fallback,
+                &mut participation,
+            );
+            delegation.awaited = delegates.clone();
+            delegation.asked_at = now;
+            fall_backs.push((*operation_id, delegation.msg.clone(), delegates));
+            true
+        });
+
+        fall_backs
+    }
+}
+
+// Takes the next delegates off the holders not asked yet, recording the delegation to them
+fn take_delegates(
+    count: usize,
+    fallback: &mut VecDeque<XorName>,
+    participation: &mut BTreeMap<XorName, AdultParticipation>,
+) -> BTreeSet<XorName> {
+    let count = count.min(fallback.len());
+    let delegates: BTreeSet<_> = fallback.drain(..count).collect();
+    for adult in &delegates {
+        participation.entry(*adult).or_default().delegated += 1;
+    }
+    delegates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delegation(delegates: usize) -> QueryDelegation {
+        QueryDelegation::new(QueryDelegationConfig {
+            delegates,
+            timeout: Duration::from_secs(3),
+        })
+    }
+
+    fn query_msg() -> SystemMsg {
+        SystemMsg::AntiEntropyProbe
+    }
+
+    fn holders(count: usize) -> Vec<XorName> {
+        (0..count).map(|_| xor_name::rand::random()).collect()
+    }
+
+    #[tokio::test]
+    async fn queries_fall_back_to_the_next_holders_until_exhausted() {
+        let delegation = delegation(1);
+        let holders = holders(3);
+        let operation_id = OperationId([1; 32]);
+        let now = Instant::now();
+
+        let delegates = delegation
+            .delegate_at(operation_id, query_msg(), holders.clone(), now)
+            .await;
+        assert_eq!(delegates, BTreeSet::from([holders[0]]));
+
+        // answers from nodes the query wasn't delegated to don't count
+        assert_eq!(
+            delegation.failed_at(operation_id, holders[2], now).await,
+            DelegationOutcome::Awaiting
+        );
+        match delegation.failed_at(operation_id, holders[0], now).await {
+            DelegationOutcome::FallBack(_, delegates) => {
+                assert_eq!(delegates, BTreeSet::from([holders[1]]))
+            }
+            other => panic!("query didn't fall back, but {:?}", other),
+        }
+
+        let later = now + Duration::from_secs(3);
+        let fall_backs = delegation.fall_back_timed_out_at(later).await;
+        assert_eq!(fall_backs.len(), 1);
+        assert_eq!(fall_backs[0].0, operation_id);
+        assert_eq!(fall_backs[0].2, BTreeSet::from([holders[2]]));
+
+        assert_eq!(
+            delegation.failed_at(operation_id, holders[2], later).await,
+            DelegationOutcome::Exhausted
+        );
+
+        let participation = delegation.participation().await;
+        assert_eq!(participation[&holders[0]].failed, 1);
+        assert_eq!(participation[&holders[1]].timed_out, 1);
+        assert_eq!(participation[&holders[2]].failed, 1);
+        assert!(participation.values().all(|adult| adult.delegated == 1));
+    }
+
+    #[tokio::test]
+    async fn answered_queries_are_not_delegated_anymore() {
+        let delegation = delegation(2);
+        let holders = holders(4);
+        let operation_id = OperationId([2; 32]);
+        let now = Instant::now();
+
+        let delegates = delegation
+            .delegate_at(operation_id, query_msg(), holders.clone(), now)
+            .await;
+        assert_eq!(delegates, BTreeSet::from([holders[0], holders[1]]));
+        assert_eq!(
+            delegation.failed_at(operation_id, holders[1], now).await,
+            DelegationOutcome::Awaiting
+        );
+        delegation.answered(operation_id, holders[0]).await;
+
+        let later = now + Duration::from_secs(60);
+        assert!(delegation.fall_back_timed_out_at(later).await.is_empty());
+
+        let participation = delegation.participation().await;
+        assert_eq!(participation.len(), 2);
+        assert_eq!(participation[&holders[0]].answer_rate(), 1.0);
+        assert_eq!(participation[&holders[1]].answer_rate(), 0.0);
+    }
+
+    #[tokio::test]
+    async fn queries_go_to_all_holders_when_delegation_is_disabled() {
+        let delegation = delegation(0);
+        let holders = holders(3);
+        let delegates = delegation
+            .delegate_at(
+                OperationId([3; 32]),
+                query_msg(),
+                holders.clone(),
+                Instant::now(),
+            )
+            .await;
+        assert_eq!(delegates, holders.into_iter().collect());
+        assert!(delegation.participation().await.is_empty());
+    }
+}
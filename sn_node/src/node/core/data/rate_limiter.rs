@@ -0,0 +1,274 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use sn_interface::types::PublicKey;
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+use tokio::sync::{Mutex, RwLock};
+
+// Limits applied to each client unless set otherwise
+const DEFAULT_CLIENT_WRITE_RATE: f64 = 20.0;
+const DEFAULT_CLIENT_WRITE_BURST: u32 = 200;
+
+// Max number of clients tracked at once, the least recently seen ones being dropped beyond it
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Limits on the rate of write cmds an Elder accepts from each client.
+///
+/// Each client can send up to `burst` cmds at once, and `ops_per_sec` cmds per second over time.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ClientRateLimits {
+    /// Number of cmds per second a client can sustain. `0` disables rate limiting.
+    pub ops_per_sec: f64,
+    /// Number of cmds a client can send at once
+    pub burst: u32,
+}
+
+impl Default for ClientRateLimits {
+    fn default() -> Self {
+        Self {
+            ops_per_sec: DEFAULT_CLIENT_WRITE_RATE,
+            burst: DEFAULT_CLIENT_WRITE_BURST,
+        }
+    }
+}
+
+impl ClientRateLimits {
+    /// Limits which accept every cmd.
+    pub fn disabled() -> Self {
+        Self {
+            ops_per_sec: 0.0,
+            burst: 0,
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.ops_per_sec > 0.0
+    }
+
+    // Capacity of the bucket of each client, which can send at least one cmd
+    fn capacity(&self) -> f64 {
+        f64::from(self.burst.max(1))
+    }
+}
+
+// Cmds a client can still send, as of the last time it sent one
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    refilled_at: Instant,
+    // position of the client in `Buckets::recency`
+    seen: u64,
+}
+
+// Buckets of the clients, along with the order they were last seen in
+#[derive(Debug, Default)]
+struct Buckets {
+    by_client: HashMap<PublicKey, TokenBucket>,
+    recency: BTreeMap<u64, PublicKey>,
+    next_seen: u64,
+}
+
+/// Token buckets of the clients sending write cmds to an Elder.
+#[derive(Debug)]
+pub(crate) struct ClientRateLimiter {
+    limits: RwLock<ClientRateLimits>,
+    buckets: Mutex<Buckets>,
+}
+
+impl ClientRateLimiter {
+    pub(crate) fn new(limits: ClientRateLimits) -> Self {
+        Self {
+            limits: RwLock::new(limits),
+            buckets: Mutex::new(Buckets::default()),
+        }
+    }
+
+    /// Current limits on the rate of write cmds.
+    pub(crate) async fn limits(&self) -> ClientRateLimits {
+        *self.limits.read().await
+    }
+
+    /// Replace the limits, the tokens clients have left being kept as they are.
+    pub(crate) async fn set_limits(&self, limits: ClientRateLimits) {
+        *self.limits.write().await = limits;
+    }
+
+    /// Take a token from the bucket of the given client for a cmd it sent.
+    /// Returns how long the client has to back off for if it's out of tokens.
+    pub(crate) async fn try_acquire(&self, client: PublicKey) -> Result<(), Duration> {
+        self.try_acquire_at(client, Instant::now()).await
+    }
+
+    async fn try_acquire_at(&self, client: PublicKey, now: Instant) -> Result<(), Duration> {
+        let limits = *self.limits.read().await;
+        if !limits.is_enabled() {
+            return Ok(());
+        }
+
+        let mut buckets = self.buckets.lock().await;
+        let Buckets {
+            by_client,
+            recency,
+            next_seen,
+        } = &mut *buckets;
+
+        let seen = *next_seen;
+        *next_seen += 1;
+
+        let capacity = limits.capacity();
+        let bucket = by_client.entry(client).or_insert(TokenBucket {
+            tokens: capacity,
+            refilled_at: now,
+            seen,
+        });
+        let _ = recency.remove(&bucket.seen);
+        let _ = recency.insert(seen, client);
+        bucket.seen = seen;
+
+        let elapsed = now.saturating_duration_since(bucket.refilled_at);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * limits.ops_per_sec).min(capacity);
+        bucket.refilled_at = now;
+        let acquired = if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            let missing = 1.0 - bucket.tokens;
+            Err(Duration::from_secs_f64(missing / limits.ops_per_sec))
+        };
+
+        Self::prune(&limits, by_client, recency, now);
+
+        acquired
+    }
+
+    // Drop the least recently seen clients whose buckets are full again, as they'd be treated the
+    // same as new ones, then the least recently seen ones beyond `MAX_TRACKED_CLIENTS` whatever
+    // their tokens. Clients are dropped in the order they were seen, only looking at the oldest ones.
+    fn prune(
+        limits: &ClientRateLimits,
+        by_client: &mut HashMap<PublicKey, TokenBucket>,
+        recency: &mut BTreeMap<u64, PublicKey>,
+        now: Instant,
+    ) {
+        let capacity = limits.capacity();
+        while let Some((&seen, client)) = recency.iter().next() {
+            let refilled = by_client.get(client).map_or(true, |bucket| {
+                let elapsed = now.saturating_duration_since(bucket.refilled_at);
+                bucket.tokens + elapsed.as_secs_f64() * limits.ops_per_sec >= capacity
+            });
+            if !refilled && recency.len() <= MAX_TRACKED_CLIENTS {
+                break;
+            }
+            if let Some(client) = recency.remove(&seen) {
+                let _ = by_client.remove(&client);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sn_interface::types::Keypair;
+
+    #[tokio::test]
+    async fn burst_then_backoff() {
+        let limiter = ClientRateLimiter::new(ClientRateLimits {
+            ops_per_sec: 2.0,
+            burst: 3,
+        });
+        let client = Keypair::new_ed25519().public_key();
+        let other_client = Keypair::new_ed25519().public_key();
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert_eq!(limiter.try_acquire_at(client, start).await, Ok(()));
+        }
+        assert_eq!(
+            limiter.try_acquire_at(client, start).await,
+            Err(Duration::from_millis(500))
+        );
+        // other clients are not affected
+        assert_eq!(limiter.try_acquire_at(other_client, start).await, Ok(()));
+
+        // tokens are refilled over time
+        let later = start + Duration::from_millis(500);
+        assert_eq!(limiter.try_acquire_at(client, later).await, Ok(()));
+        assert!(limiter.try_acquire_at(client, later).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn disabled_limits_accept_everything() {
+        let limiter = ClientRateLimiter::new(ClientRateLimits::disabled());
+        let client = Keypair::new_ed25519().public_key();
+        let now = Instant::now();
+
+        for _ in 0..100 {
+            assert_eq!(limiter.try_acquire_at(client, now).await, Ok(()));
+        }
+
+        // limits can be enabled while running
+        limiter
+            .set_limits(ClientRateLimits {
+                ops_per_sec: 1.0,
+                burst: 1,
+            })
+            .await;
+        assert_eq!(limiter.try_acquire_at(client, now).await, Ok(()));
+        assert!(limiter.try_acquire_at(client, now).await.is_err());
+    }
+    #[tokio::test]
+    async fn tracked_clients_are_bounded() {
+        let limiter = ClientRateLimiter::new(ClientRateLimits {
+            ops_per_sec: 1.0,
+            burst: 2,
+        });
+        let first_client = Keypair::new_ed25519().public_key();
+        let now = Instant::now();
+
+        assert_eq!(limiter.try_acquire_at(first_client, now).await, Ok(()));
+        for _ in 0..MAX_TRACKED_CLIENTS {
+            let client = Keypair::new_ed25519().public_key();
+            assert_eq!(limiter.try_acquire_at(client, now).await, Ok(()));
+        }
+
+        let buckets = limiter.buckets.lock().await;
+        assert_eq!(buckets.by_client.len(), MAX_TRACKED_CLIENTS);
+        assert_eq!(buckets.recency.len(), MAX_TRACKED_CLIENTS);
+        // the least recently seen client was dropped
+        assert!(!buckets.by_client.contains_key(&first_client));
+    }
+
+    #[tokio::test]
+    async fn refilled_clients_are_dropped() {
+        let limiter = ClientRateLimiter::new(ClientRateLimits {
+            ops_per_sec: 1.0,
+            burst: 2,
+        });
+        let idle_client = Keypair::new_ed25519().public_key();
+        let busy_client = Keypair::new_ed25519().public_key();
+        let start = Instant::now();
+
+        assert_eq!(limiter.try_acquire_at(idle_client, start).await, Ok(()));
+        assert_eq!(limiter.try_acquire_at(busy_client, start).await, Ok(()));
+
+        // the idle client's bucket is full again a sec later, when the busy client is seen
+        let later = start + Duration::from_secs(1);
+        assert_eq!(limiter.try_acquire_at(busy_client, later).await, Ok(()));
+        assert_eq!(limiter.try_acquire_at(busy_client, later).await, Ok(()));
+
+        let buckets = limiter.buckets.lock().await;
+        assert!(!buckets.by_client.contains_key(&idle_client));
+        assert!(buckets.by_client.contains_key(&busy_client));
+    }
+}
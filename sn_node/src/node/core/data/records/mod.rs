@@ -11,22 +11,32 @@ mod capacity;
 pub(crate) use self::capacity::{Capacity, MIN_LEVEL_WHEN_FULL};
 
 use crate::node::{
-    core::{Cmd, Node, Prefix, MAX_WAITING_PEERS_PER_QUERY},
+    core::{Cmd, Node, PeerTag, Prefix, MAX_WAITING_PEERS_PER_QUERY},
     error::convert_to_error_msg,
-    messages::WireMsgUtils,
     Error, Result,
 };
 use dashmap::DashSet;
 use itertools::Itertools;
-use sn_dysfunction::IssueType;
-use sn_interface::data_copy_count;
+use sn_dysfunction::{IssueType, Signal};
 use sn_interface::messaging::{
-    data::{CmdError, DataQuery, MetadataExchange, StorageLevel},
-    system::{NodeCmd, NodeQuery, SystemMsg},
+    data::{
+        missing_chunk_report_operation_id, section_key_history_operation_id,
+        section_members_operation_id, section_stats_operation_id, CmdAckLevel, CmdError, DataQuery,
+        Error as ErrorMsg, MembersContinuation, MetadataExchange, OperationId, QueryResponse,
+        SectionMember, SectionMembersPage, SectionStats, ServiceMsg, StorageLevel,
+        MAX_SECTION_MEMBERS_PAGE_SIZE,
+    },
+    system::{Feature, NodeCmd, NodeQuery, SystemMsg},
     AuthorityProof, DstLocation, EndUser, MsgId, ServiceAuth, WireMsg,
 };
-use sn_interface::types::{log_markers::LogMarker, Peer, PublicKey, ReplicatedData};
-use std::{cmp::Ordering, collections::BTreeSet, sync::Arc};
+use sn_interface::types::{
+    log_markers::LogMarker, ChunkAddress, Peer, PublicKey, ReplicatedData, ReplicatedDataAddress,
+};
+use std::{
+    cmp::Ordering,
+    collections::BTreeSet,
+    sync::{atomic, Arc},
+};
 use tracing::info;
 use xor_name::XorName;
 
@@ -50,6 +60,65 @@ impl Node {
         }
     }
 
+    // Replicates the data written by a client's cmd, instructing the holders to confirm they
+    // stored it when the client asked to be told
+    pub(crate) async fn replicate_client_data(
+        &self,
+        data: ReplicatedData,
+        ack_level: CmdAckLevel,
+        msg_id: MsgId,
+        origin: Peer,
+    ) -> Result<Vec<Cmd>> {
+        if ack_level != CmdAckLevel::Replicated {
+            return self.replicate_data(data).await;
+        }
+        // Adults of older versions don't confirm storing data, the client would wait in vain
+        if !self
+            .is_feature_active(Feature::ReplicationConfirmation)
+            .await
+        {
+            warn!(
+                "Not awaiting confirmation of replication of cmd {:?}, too few of our members support it yet",
+                msg_id
+            );
+            return self.replicate_data(data).await;
+        }
+        trace!("{:?}: {:?}", LogMarker::DataStoreReceivedAtElder, data);
+        if !self.is_elder().await {
+            return Err(Error::InvalidState);
+        }
+
+        let targets = self.get_adults_who_should_store_data(data.name()).await;
+        if targets.is_empty() {
+            warn!(
+                "No holders to replicate data {:?} of cmd {:?} to",
+                data.name(),
+                msg_id
+            );
+            return Ok(vec![]);
+        }
+        info!(
+            "Replicating data {:?} of cmd {:?} to holders {:?}, awaiting their confirmation",
+            data.name(),
+            msg_id,
+            &targets,
+        );
+        let failed = self
+            .pending_replications
+            .track(msg_id, origin, targets.clone())
+            .await;
+        for adult in failed {
+            warn!("Adult {} failed to confirm storing data in time", adult);
+            self.log_signal(adult, Signal::FailedStorageProbe).await;
+        }
+
+        let msg = SystemMsg::NodeCmd(NodeCmd::ReplicateDataAndConfirm {
+            data,
+            correlation_id: msg_id,
+        });
+        self.send_node_msg_to_nodes(msg, targets).await
+    }
+
     pub(crate) async fn read_data_from_adults(
         &self,
         query: DataQuery,
@@ -57,7 +126,12 @@ impl Node {
         auth: AuthorityProof<ServiceAuth>,
         origin: Peer,
     ) -> Result<Vec<Cmd>> {
-        let address = query.address();
+        let address = if let Some(address) = query.address() {
+            address
+        } else {
+            warn!("Ignoring query not about any data: {:?}", query);
+            return Ok(vec![]);
+        };
         let operation_id = query.operation_id()?;
         trace!(
             "{:?} preparing to query adults for data at {:?} with op_id: {:?}",
@@ -66,6 +140,33 @@ impl Node {
             operation_id
         );
 
+        // responses to reads of private data are only served from the cache to the client they
+        // were read for, as the Adults checked its permissions to read the data
+        if let Some(response) = self
+            .query_cache
+            .get(auth.public_key, operation_id, &address)
+            .await
+        {
+            trace!(
+                "{:?} {:?}, op_id: {:?}",
+                LogMarker::DataQueryAnsweredFromCache,
+                address,
+                operation_id
+            );
+            let msg = ServiceMsg::QueryResponse {
+                response,
+                correlation_id: MsgId::from_xor_name(*address.name()),
+            };
+            let (msg_kind, payload) = self.ed_sign_client_msg(&msg).await?;
+            let dst = DstLocation::end_user(origin.name());
+            let wire_msg = WireMsg::new_msg(MsgId::new(), payload, msg_kind, dst)?;
+
+            return Ok(vec![Cmd::SendMsg {
+                recipients: vec![origin],
+                wire_msg,
+            }]);
+        }
+
         let targets = self
             .get_adults_holding_data_including_full(address.name())
             .await;
@@ -102,17 +203,6 @@ impl Node {
         // otherwise we've appended to the Peers above
         // we rely on the data query cache timeout to decide as/when we'll be re-sending a query to adults
         if !op_was_already_underway {
-            // ensure we only add a pending request when we're actually sending out requests.
-            for target in &targets {
-                trace!("adding pending req for {target:?} in dysfunction tracking");
-                self.dysfunction_tracking
-                    .track_issue(
-                        *target,
-                        IssueType::PendingRequestOperation(Some(operation_id)),
-                    )
-                    .await?;
-            }
-
             trace!(
                 "Adding to pending data queries for op id: {:?}",
                 operation_id
@@ -122,6 +212,9 @@ impl Node {
                 .pending_data_queries
                 .set(operation_id, waiting_peers, None)
                 .await;
+            self.query_cache
+                .track_forwarded(auth.public_key, operation_id, &address)
+                .await;
 
             let msg = SystemMsg::NodeQuery(NodeQuery::Data {
                 query,
@@ -130,22 +223,302 @@ impl Node {
                 correlation_id: MsgId::from_xor_name(*address.name()),
             });
 
-            self.send_node_msg_to_nodes(msg, targets).await
+            // the closest holders are delegated the query first
+            let holders = targets
+                .into_iter()
+                .sorted_by(|lhs, rhs| address.name().cmp_distance(lhs, rhs))
+                .collect();
+            let delegates = self
+                .query_delegation
+                .delegate(operation_id, msg.clone(), holders)
+                .await;
+
+            self.forward_query_to_adults(operation_id, msg, delegates)
+                .await
         } else {
             // we don't do anything as we're still within data query timeout
             Ok(vec![])
         }
     }
 
+    /// Forwards the query to the given Adults, tracking it as pending with each of them.
+    pub(crate) async fn forward_query_to_adults(
+        &self,
+        operation_id: OperationId,
+        msg: SystemMsg,
+        adults: BTreeSet<XorName>,
+    ) -> Result<Vec<Cmd>> {
+        // ensure we only add a pending request when we're actually sending out requests.
+        for adult in &adults {
+            trace!("adding pending req for {adult:?} in dysfunction tracking");
+            self.dysfunction_tracking
+                .track_issue(
+                    *adult,
+                    IssueType::PendingRequestOperation(Some(operation_id)),
+                )
+                .await?;
+        }
+        if self.query_delegation.config().delegates > 0 {
+            debug!(
+                "{:?} {:?} to {:?}",
+                LogMarker::DataQueryDelegated,
+                operation_id,
+                adults
+            );
+        }
+
+        self.send_node_msg_to_nodes(msg, adults).await
+    }
+
+    /// Forwards the queries whose delegates didn't answer in time to the next holders of the data.
+    pub(crate) async fn fall_back_timed_out_queries(&self) -> Result<Vec<Cmd>> {
+        let mut cmds = vec![];
+        for (operation_id, msg, adults) in self.query_delegation.fall_back_timed_out().await {
+            cmds.extend(
+                self.forward_query_to_adults(operation_id, msg, adults)
+                    .await?,
+            );
+        }
+        Ok(cmds)
+    }
+
+    /// A client reported a chunk it couldn't retrieve from our section. We ask all our
+    /// Adults to send it over to the ones meant to hold it, should they hold a copy,
+    /// and let the client know the repair was triggered so it can retry its read.
+    pub(crate) async fn repair_missing_chunk(
+        &self,
+        address: ChunkAddress,
+        msg_id: MsgId,
+        origin: Peer,
+    ) -> Result<Vec<Cmd>> {
+        let operation_id = missing_chunk_report_operation_id(&address)?;
+        debug!(
+            "{:?} {:?} by {:?}, op_id: {:?}",
+            LogMarker::MissingChunkReported,
+            address,
+            origin,
+            operation_id
+        );
+
+        let adults = self.network_knowledge().adults().await;
+        let targets: BTreeSet<_> = adults.iter().map(|adult| adult.name()).collect();
+
+        let (mut cmds, result) = if targets.is_empty() {
+            let error =
+                convert_to_error_msg(Error::NoAdults(self.network_knowledge().prefix().await));
+            (vec![], Err(error))
+        } else {
+            let msg = SystemMsg::NodeCmd(NodeCmd::RepairData(vec![ReplicatedDataAddress::Chunk(
+                address,
+            )]));
+            (self.send_node_msg_to_nodes(msg, targets).await?, Ok(()))
+        };
+
+        let msg = ServiceMsg::QueryResponse {
+            response: QueryResponse::ReportMissingChunk((result, operation_id)),
+            correlation_id: msg_id,
+        };
+        let (msg_kind, payload) = self.ed_sign_client_msg(&msg).await?;
+        let dst = DstLocation::end_user(origin.name());
+        let wire_msg = WireMsg::new_msg(MsgId::new(), payload, msg_kind, dst)?;
+
+        cmds.push(Cmd::SendMsg {
+            recipients: vec![origin],
+            wire_msg,
+        });
+
+        Ok(cmds)
+    }
+
+    /// Respond to a client with the stats of our section. The stats of our current section are
+    /// sent even if the prefix queried for is outdated, e.g. the client didn't know of a split yet.
+    pub(crate) async fn send_section_stats(
+        &self,
+        prefix: Prefix,
+        msg_id: MsgId,
+        origin: Peer,
+    ) -> Result<Vec<Cmd>> {
+        let operation_id = section_stats_operation_id(&prefix)?;
+        let sap = self.network_knowledge().authority_provider().await;
+        let stats = SectionStats {
+            prefix: sap.prefix(),
+            section_key: sap.section_key(),
+            section_key_generation: self.network_knowledge().chain_len().await,
+            elder_count: sap.elder_count(),
+            adult_count: self.network_knowledge().adults().await.len(),
+            approx_stored_chunks: self.approx_stored_chunks.load(atomic::Ordering::Relaxed),
+            data_copy_count: sap.data_copy_count(),
+            joins_allowed: *self.joins_allowed.read().await,
+        };
+        debug!(
+            "Sending stats of section {:?} to {:?}, op_id: {:?}",
+            stats.prefix, origin, operation_id
+        );
+
+        let msg = ServiceMsg::QueryResponse {
+            response: QueryResponse::GetSectionStats((Ok(stats), operation_id)),
+            correlation_id: msg_id,
+        };
+        let (msg_kind, payload) = self.ed_sign_client_msg(&msg).await?;
+        let dst = DstLocation::end_user(origin.name());
+        let wire_msg = WireMsg::new_msg(MsgId::new(), payload, msg_kind, dst)?;
+
+        Ok(vec![Cmd::SendMsg {
+            recipients: vec![origin],
+            wire_msg,
+        }])
+    }
+
+    /// Respond to a client with the chain of keys of our section, from the given key or from
+    /// the genesis key, to our current key. As with our stats, our current section chain is sent
+    /// even if the prefix queried for is outdated.
+    pub(crate) async fn send_section_key_history(
+        &self,
+        prefix: Prefix,
+        since: Option<bls::PublicKey>,
+        msg_id: MsgId,
+        origin: Peer,
+    ) -> Result<Vec<Cmd>> {
+        let operation_id = section_key_history_operation_id(&prefix, since.as_ref())?;
+        let from_key = since.unwrap_or(*self.network_knowledge().genesis_key());
+        let history = self
+            .network_knowledge()
+            .get_proof_chain_to_current(&from_key)
+            .await
+            .map_err(|error| {
+                debug!(
+                    "No history of our section keys from {:?}: {:?}",
+                    from_key, error
+                );
+                ErrorMsg::UnknownSectionKey(from_key)
+            });
+        debug!(
+            "Sending history of our section keys from {:?} to {:?}, op_id: {:?}",
+            from_key, origin, operation_id
+        );
+
+        let msg = ServiceMsg::QueryResponse {
+            response: QueryResponse::GetSectionKeyHistory((history, operation_id)),
+            correlation_id: msg_id,
+        };
+        let (msg_kind, payload) = self.ed_sign_client_msg(&msg).await?;
+        let dst = DstLocation::end_user(origin.name());
+        let wire_msg = WireMsg::new_msg(MsgId::new(), payload, msg_kind, dst)?;
+
+        Ok(vec![Cmd::SendMsg {
+            recipients: vec![origin],
+            wire_msg,
+        }])
+    }
+
+    /// Respond to a client with the page of our section members following the given
+    /// continuation, kept within the max size of such a page. As with our stats, our own members
+    /// are sent even if the prefix queried for is outdated.
+    pub(crate) async fn send_section_members(
+        &self,
+        prefix: Prefix,
+        continuation: Option<MembersContinuation>,
+        msg_id: MsgId,
+        origin: Peer,
+    ) -> Result<Vec<Cmd>> {
+        let operation_id = section_members_operation_id(&prefix, continuation.as_ref())?;
+        let sap = self.network_knowledge().authority_provider().await;
+        let members = self
+            .network_knowledge()
+            .section_members()
+            .await
+            .into_iter()
+            .map(|node_state| SectionMember {
+                name: node_state.name(),
+                addr: node_state.addr(),
+                is_elder: sap.contains_elder(&node_state.name()),
+            })
+            .sorted_by_key(|member| member.name);
+        let page = SectionMembersPage::new(
+            sap.prefix(),
+            sap.section_key(),
+            members,
+            continuation.as_ref(),
+            MAX_SECTION_MEMBERS_PAGE_SIZE,
+        );
+        debug!(
+            "Sending page of {} members of section {:?} to {:?}, op_id: {:?}",
+            page.members.len(),
+            page.prefix,
+            origin,
+            operation_id
+        );
+
+        let msg = ServiceMsg::QueryResponse {
+            response: QueryResponse::GetSectionMembers((Ok(page), operation_id)),
+            correlation_id: msg_id,
+        };
+        let (msg_kind, payload) = self.ed_sign_client_msg(&msg).await?;
+        let dst = DstLocation::end_user(origin.name());
+        let wire_msg = WireMsg::new_msg(MsgId::new(), payload, msg_kind, dst)?;
+
+        Ok(vec![Cmd::SendMsg {
+            recipients: vec![origin],
+            wire_msg,
+        }])
+    }
+
+    /// Check the integrity of the next batch of chunks we hold. Corrupt chunks are quarantined,
+    /// and the other Adults of our section are asked to send us back any copy they hold.
+    pub(crate) async fn scrub_stored_chunks(&self, batch_size: usize) -> Result<Vec<Cmd>> {
+        let corrupt = self.data_storage.scrub_next_chunks(batch_size).await?;
+        if corrupt.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let our_name = self.info.read().await.name();
+        let targets: BTreeSet<_> = self
+            .network_knowledge()
+            .adults()
+            .await
+            .iter()
+            .map(|adult| adult.name())
+            .filter(|name| *name != our_name)
+            .collect();
+
+        if targets.is_empty() {
+            warn!(
+                "No other Adults to recover {} corrupt chunks from",
+                corrupt.len()
+            );
+            return Ok(vec![]);
+        }
+
+        let msg = SystemMsg::NodeCmd(NodeCmd::RepairData(
+            corrupt
+                .into_iter()
+                .map(ReplicatedDataAddress::Chunk)
+                .collect(),
+        ));
+        self.send_node_msg_to_nodes(msg, targets).await
+    }
+
     pub(crate) async fn get_metadata_of(&self, prefix: &Prefix) -> MetadataExchange {
         // Load tracked adult_levels
         let adult_levels = self.capacity.levels_matching(*prefix).await;
-        MetadataExchange { adult_levels }
+        let spent_payments = self.spent_payments.matching(prefix).await;
+        let applied_cmds = self.ordered_cmds.matching(prefix).await;
+        MetadataExchange {
+            adult_levels,
+            spent_payments,
+            applied_cmds,
+        }
     }
 
-    pub(crate) async fn set_adult_levels(&self, adult_levels: MetadataExchange) {
-        let MetadataExchange { adult_levels } = adult_levels;
-        self.capacity.set_adult_levels(adult_levels).await
+    pub(crate) async fn set_metadata(&self, metadata: MetadataExchange) -> Result<()> {
+        let MetadataExchange {
+            adult_levels,
+            spent_payments,
+            applied_cmds,
+        } = metadata;
+        self.capacity.set_adult_levels(adult_levels).await;
+        self.ordered_cmds.merge(applied_cmds).await;
+        self.spent_payments.merge(spent_payments).await
     }
 
     /// Registered holders not present in provided list of members
@@ -153,10 +526,13 @@ impl Node {
     pub(crate) async fn liveness_retain_only(&self, members: BTreeSet<XorName>) -> Result<()> {
         // full adults
         self.capacity.retain_members_only(&members).await;
+        self.query_delegation.retain_members_only(&members).await;
 
         // stop tracking liveness of absent holders
         let _ = self.dysfunction_tracking.retain_members_only(members).await;
 
+        self.tag_section_members().await;
+
         Ok(())
     }
 
@@ -164,6 +540,7 @@ impl Node {
     pub(crate) async fn add_new_adult_to_trackers(&self, adult: XorName) {
         info!("Adding new Adult: {adult} to trackers");
         self.capacity.add_new_adult(adult).await;
+        self.comm.peer_tags().tag(adult, PeerTag::Adult).await;
 
         let _ = self.dysfunction_tracking.add_new_node(adult).await;
     }
@@ -196,12 +573,17 @@ impl Node {
         let adults = self.network_knowledge().adults().await;
 
         let adults_names = adults.iter().map(|p2p_node| p2p_node.name());
+        let data_copy_count = self
+            .network_knowledge()
+            .authority_provider()
+            .await
+            .data_copy_count();
 
         let mut candidates = adults_names
             .into_iter()
             .sorted_by(|lhs, rhs| target.cmp_distance(lhs, rhs))
             .filter(|peer| !full_adults.contains(peer))
-            .take(data_copy_count())
+            .take(data_copy_count)
             .collect::<BTreeSet<_>>();
 
         trace!(
@@ -242,12 +624,17 @@ impl Node {
         trace!("Total adults known about: {:?}", adults.len());
 
         let adults_names = adults.iter().map(|p2p_node| p2p_node.name());
+        let data_copy_count = self
+            .network_knowledge()
+            .authority_provider()
+            .await
+            .data_copy_count();
 
         let candidates = adults_names
             .into_iter()
             .sorted_by(|lhs, rhs| target.cmp_distance(lhs, rhs))
             .filter(|peer| !full_adults.contains(peer))
-            .take(data_copy_count())
+            .take(data_copy_count)
             .collect::<BTreeSet<_>>();
 
         trace!(
@@ -272,18 +659,18 @@ impl Node {
         // we will set it correctly for each msg and target
         let section_pk = self.network_knowledge().section_key().await;
         let our_name = self.info.read().await.name();
-        let dummy_dst_location = DstLocation::Node {
-            name: our_name,
-            section_pk,
-        };
+        let dummy_dst_location = DstLocation::node(our_name, section_pk);
 
         // separate this into form_wire_msg based on agg
-        let wire_msg = WireMsg::single_src(
-            &self.info.read().await.clone(),
-            dummy_dst_location,
-            msg,
-            section_pk,
-        )?;
+        let wire_msg = self
+            .signing_pool
+            .single_src(
+                &self.info.read().await.clone(),
+                dummy_dst_location,
+                msg,
+                section_pk,
+            )
+            .await?;
 
         let mut cmds = vec![];
 
@@ -0,0 +1,306 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::Result;
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+use tokio::{
+    fs::{self, OpenOptions},
+    io::AsyncWriteExt,
+    sync::Mutex,
+};
+use xor_name::{Prefix, XorName, XOR_NAME_LEN};
+
+// Filename of the log the payments accepted by our section are appended to
+const SPENT_PAYMENTS_FILENAME: &str = "spent_payments.log";
+
+// Length of a record of the log: the name of the key image of the payment DBC, followed by the
+// name of the data it paid for
+const RECORD_LEN: usize = 2 * XOR_NAME_LEN;
+
+/// Whether a payment can be accepted for the data it's presented for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PaymentClaim {
+    /// The payment wasn't used before, and now pays for the data.
+    Claimed,
+    /// The payment already paid for the same data, e.g. as the client sent its cmd to several
+    /// Elders, or resent it.
+    AlreadyClaimed,
+    /// The payment already paid for other data, so it's to be rejected.
+    UsedForOtherData,
+}
+
+/// Payments accepted by our section, by the name of the key image of their DBC, along with the
+/// name of the data each of them paid for.
+///
+/// They're appended to a log on disk as they're accepted, so they're not forgotten on restart,
+/// and shared with the other Elders and with the ones joining them. The payments for data which
+/// isn't ours anymore once our section split are pruned.
+#[derive(Debug)]
+pub(crate) struct SpentPayments {
+    path: PathBuf,
+    spent: Mutex<BTreeMap<XorName, XorName>>,
+}
+
+impl SpentPayments {
+    /// Loads the payments accepted before the node restarted, if any.
+    pub(crate) async fn new(root_dir: &Path) -> Result<Self> {
+        let path = root_dir.join(SPENT_PAYMENTS_FILENAME);
+        let bytes = if path.is_file() {
+            fs::read(&path).await?
+        } else {
+            vec![]
+        };
+
+        let mut spent = BTreeMap::new();
+        let records = bytes.chunks_exact(RECORD_LEN);
+        let truncated = !records.remainder().is_empty();
+        for record in records {
+            let (key_image, data) = record.split_at(XOR_NAME_LEN);
+            // the first payment recorded for a key image is the one which stands
+            let _ = spent.entry(name(key_image)).or_insert_with(|| name(data));
+        }
+
+        let spent_payments = Self {
+            path,
+            spent: Mutex::new(spent),
+        };
+        // a record partly appended as the node stopped is dropped, for the next ones to be
+        // appended after the last whole one
+        if truncated {
+            warn!("Dropping the truncated last record of the spent payments log");
+            let spent = spent_payments.spent.lock().await;
+            spent_payments.rewrite(&spent).await?;
+        }
+
+        Ok(spent_payments)
+    }
+
+    /// Claims the payment with the given key image for the data with the given name.
+    /// The check and the update are done under the same lock, so a payment presented for
+    /// different data concurrently is only accepted for one of them.
+    pub(crate) async fn claim(&self, key_image: XorName, data: XorName) -> Result<PaymentClaim> {
+        let mut spent = self.spent.lock().await;
+        match spent.get(&key_image) {
+            Some(paid_for) if *paid_for == data => Ok(PaymentClaim::AlreadyClaimed),
+            Some(_) => Ok(PaymentClaim::UsedForOtherData),
+            None => {
+                self.append(&[(key_image, data)]).await?;
+                let _ = spent.insert(key_image, data);
+                Ok(PaymentClaim::Claimed)
+            }
+        }
+    }
+
+    /// Records the payments accepted by other Elders. A payment we accepted for other data is
+    /// kept as is.
+    pub(crate) async fn merge(&self, payments: BTreeMap<XorName, XorName>) -> Result<()> {
+        let mut spent = self.spent.lock().await;
+        let new: Vec<_> = payments
+            .into_iter()
+            .filter(|(key_image, _)| !spent.contains_key(key_image))
+            .collect();
+        if new.is_empty() {
+            return Ok(());
+        }
+
+        self.append(&new).await?;
+        spent.extend(new);
+
+        Ok(())
+    }
+
+    /// Forgets the payments for data not matching the given prefix, e.g. once our section split
+    /// and the other half of the data is the sibling section's.
+    pub(crate) async fn retain_matching(&self, prefix: &Prefix) -> Result<()> {
+        let mut spent = self.spent.lock().await;
+        let count = spent.len();
+        spent.retain(|_, data| prefix.matches(data));
+
+        if spent.len() < count {
+            debug!(
+                "Pruned {} spent payments not matching {:?}",
+                count - spent.len(),
+                prefix
+            );
+            self.rewrite(&spent).await?;
+        }
+
+        Ok(())
+    }
+
+    /// The payments accepted for data matching the given prefix.
+    pub(crate) async fn matching(&self, prefix: &Prefix) -> BTreeMap<XorName, XorName> {
+        self.spent
+            .lock()
+            .await
+            .iter()
+            .filter(|(_, data)| prefix.matches(data))
+            .map(|(key_image, data)| (*key_image, *data))
+            .collect()
+    }
+
+    // Appends the records of the payments to the log
+    async fn append(&self, payments: &[(XorName, XorName)]) -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(&records(
+            payments.iter().map(|(key_image, data)| (key_image, data)),
+        ))
+        .await?;
+        file.sync_data().await?;
+        Ok(())
+    }
+
+    // Rewrites the log with only the given payments, to a temporary file first, so a node
+    // stopped in between doesn't leave a truncated log behind
+    async fn rewrite(&self, spent: &BTreeMap<XorName, XorName>) -> Result<()> {
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, records(spent)).await?;
+        fs::rename(tmp_path, &self.path).await?;
+        Ok(())
+    }
+}
+
+fn records<'a>(payments: impl IntoIterator<Item = (&'a XorName, &'a XorName)>) -> Vec<u8> {
+    payments
+        .into_iter()
+        .flat_map(|(key_image, data)| key_image.0.iter().chain(data.0.iter()).copied())
+        .collect()
+}
+
+fn name(bytes: &[u8]) -> XorName {
+    let mut name = [0; XOR_NAME_LEN];
+    name.copy_from_slice(bytes);
+    XorName(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::{eyre, Result};
+    use tempfile::tempdir;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn payment_is_only_accepted_for_the_data_it_was_first_presented_for() -> Result<()> {
+        let root = tempdir().map_err(|e| eyre!("Failed to create temp dir: {}", e))?;
+        let spent_payments = SpentPayments::new(root.path()).await?;
+        let key_image = xor_name::rand::random();
+        let data = xor_name::rand::random();
+
+        assert_eq!(
+            spent_payments.claim(key_image, data).await?,
+            PaymentClaim::Claimed
+        );
+        // the same cmd reaching another Elder, or sent again, is fine
+        assert_eq!(
+            spent_payments.claim(key_image, data).await?,
+            PaymentClaim::AlreadyClaimed
+        );
+        // but the payment can't be replayed for other data
+        assert_eq!(
+            spent_payments
+                .claim(key_image, xor_name::rand::random())
+                .await?,
+            PaymentClaim::UsedForOtherData
+        );
+
+        // nor after a restart
+        let spent_payments = SpentPayments::new(root.path()).await?;
+        assert_eq!(
+            spent_payments
+                .claim(key_image, xor_name::rand::random())
+                .await?,
+            PaymentClaim::UsedForOtherData
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn payments_accepted_by_other_elders_are_merged() -> Result<()> {
+        let root = tempdir().map_err(|e| eyre!("Failed to create temp dir: {}", e))?;
+        let spent_payments = SpentPayments::new(root.path()).await?;
+        let (key_image, data) = (xor_name::rand::random(), xor_name::rand::random());
+        let (other_key_image, other_data) = (xor_name::rand::random(), xor_name::rand::random());
+        assert_eq!(
+            spent_payments.claim(key_image, data).await?,
+            PaymentClaim::Claimed
+        );
+
+        spent_payments
+            .merge(BTreeMap::from([
+                (key_image, xor_name::rand::random()),
+                (other_key_image, other_data),
+            ]))
+            .await?;
+        assert_eq!(
+            spent_payments.claim(key_image, data).await?,
+            PaymentClaim::AlreadyClaimed
+        );
+        assert_eq!(
+            spent_payments
+                .claim(other_key_image, xor_name::rand::random())
+                .await?,
+            PaymentClaim::UsedForOtherData
+        );
+
+        let all = spent_payments.matching(&Prefix::default()).await;
+        assert_eq!(
+            all,
+            BTreeMap::from([(key_image, data), (other_key_image, other_data)])
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn payments_for_data_of_our_sibling_are_pruned() -> Result<()> {
+        let root = tempdir().map_err(|e| eyre!("Failed to create temp dir: {}", e))?;
+        let spent_payments = SpentPayments::new(root.path()).await?;
+        let ours = Prefix::default().pushed(false);
+        let payments: BTreeMap<_, _> = (0..10)
+            .map(|_| (xor_name::rand::random(), xor_name::rand::random()))
+            .collect();
+        spent_payments.merge(payments.clone()).await?;
+
+        spent_payments.retain_matching(&ours).await?;
+        let expected: BTreeMap<_, _> = payments
+            .into_iter()
+            .filter(|(_, data)| ours.matches(data))
+            .collect();
+        assert_eq!(spent_payments.matching(&Prefix::default()).await, expected);
+
+        // the pruned log is read back as is after a restart, a record partly appended before
+        // stopping being dropped
+        let mut log = fs::read(root.path().join(SPENT_PAYMENTS_FILENAME)).await?;
+        log.extend_from_slice(&[0; XOR_NAME_LEN]);
+        fs::write(root.path().join(SPENT_PAYMENTS_FILENAME), log).await?;
+        let spent_payments = SpentPayments::new(root.path()).await?;
+        assert_eq!(spent_payments.matching(&Prefix::default()).await, expected);
+
+        let (key_image, data) = (xor_name::rand::random(), xor_name::rand::random());
+        assert_eq!(
+            spent_payments.claim(key_image, data).await?,
+            PaymentClaim::Claimed
+        );
+        let spent_payments = SpentPayments::new(root.path()).await?;
+        assert_eq!(
+            spent_payments.claim(key_image, data).await?,
+            PaymentClaim::AlreadyClaimed
+        );
+
+        Ok(())
+    }
+}
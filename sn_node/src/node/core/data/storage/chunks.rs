@@ -6,52 +6,80 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::dbs::{convert_to_error_msg, ChunkStore, Error, Result};
-use crate::UsedSpace;
+use crate::dbs::{convert_to_error_msg, open_chunk_store, ChunkStore, Error, Result};
+use crate::{ChunkStoreConfig, UsedSpace};
 use sn_interface::messaging::system::NodeQueryResponse;
 use sn_interface::types::{log_markers::LogMarker, Chunk, ChunkAddress};
 
 use std::{
     fmt::{self, Display, Formatter},
-    io::ErrorKind,
     path::Path,
+    sync::Arc,
+    time::SystemTime,
 };
 use tracing::info;
+use xor_name::Prefix;
 
 /// Operations on data chunks.
 #[derive(Clone, Debug)]
 pub(crate) struct ChunkStorage {
-    db: ChunkStore,
+    db: Arc<dyn ChunkStore>,
 }
 
 impl ChunkStorage {
-    pub(crate) fn new(path: &Path, used_space: UsedSpace) -> Result<Self> {
+    pub(crate) fn new(
+        path: &Path,
+        used_space: UsedSpace,
+        config: ChunkStoreConfig,
+    ) -> Result<Self> {
         Ok(Self {
-            db: ChunkStore::new(path, used_space)?,
+            db: open_chunk_store(path, config, used_space)?,
         })
     }
 
+    /// Recover the chunk writes which were not completed when the node went down
+    pub(crate) async fn recover(&self) -> Result<usize> {
+        self.db.recover().await
+    }
+
     pub(crate) fn keys(&self) -> Result<Vec<ChunkAddress>> {
-        self.db.list_all_chunk_addresses()
+        self.db.list_range(Prefix::default())
     }
 
     #[allow(dead_code)]
     pub(crate) async fn remove_chunk(&self, address: &ChunkAddress) -> Result<()> {
         trace!("Removing chunk, {:?}", address);
-        self.db.delete_chunk(address).await
+        self.db.delete(address).await
     }
 
+    /// Read a stored chunk. Temporary chunks which are expired are reported as not found,
+    /// they're left for the scrubber to remove.
     pub(crate) async fn get_chunk(&self, address: &ChunkAddress) -> Result<Chunk> {
         debug!("Getting chunk {:?}", address);
+        let chunk = self.db.get(address).await?;
+        if chunk.is_expired_at(SystemTime::now()) {
+            debug!("Chunk {:?} is expired", address);
+            return Err(Error::ChunkNotFound(*address.name()));
+        }
+        Ok(chunk)
+    }
 
-        match self.db.read_chunk(address).await {
-            Ok(res) => Ok(res),
-            Err(error) => match error {
-                Error::Io(io_error) if io_error.kind() == ErrorKind::NotFound => {
-                    Err(Error::ChunkNotFound(*address.name()))
-                }
-                something_else => Err(something_else),
-            },
+    /// Re-hash a stored chunk against its name, quarantining it if they don't match,
+    /// and remove it if it's a temporary chunk which is expired.
+    pub(crate) async fn scrub_chunk(&self, address: &ChunkAddress) -> Result<ScrubOutcome> {
+        match self.db.get(address).await {
+            Ok(chunk) if chunk.address() != address => {
+                self.db.quarantine(address).await?;
+                Ok(ScrubOutcome::Corrupt)
+            }
+            Ok(chunk) if chunk.is_expired_at(SystemTime::now()) => {
+                self.db.delete(address).await?;
+                Ok(ScrubOutcome::Expired)
+            }
+            Ok(_) => Ok(ScrubOutcome::Intact),
+            // it was removed since it was listed for scrubbing
+            Err(Error::ChunkNotFound(_)) => Ok(ScrubOutcome::Intact),
+            Err(error) => Err(error),
         }
     }
 
@@ -65,7 +93,7 @@ impl ChunkStorage {
     /// If that chunk was already in the local store, just overwrites it
     #[instrument(skip_all)]
     pub(super) async fn store(&self, data: &Chunk) -> Result<()> {
-        if self.db.chunk_file_exists(data.address())? {
+        if self.db.contains(data.address()).await? {
             info!(
                 "{}: Chunk already exists, not storing: {:?}",
                 self,
@@ -75,22 +103,42 @@ impl ChunkStorage {
             return Ok(());
         }
 
+        if data.is_expired_at(SystemTime::now()) {
+            info!(
+                "{}: Chunk is expired, not storing: {:?}",
+                self,
+                data.address()
+            );
+            return Ok(());
+        }
+
         // cheap extra security check for space (prone to race conditions)
         // just so we don't go too much overboard
         // should not be triggered as chunks should not be sent to full adults
-        if !self.db.can_add(data.value().len()) {
+        if !self.db.can_add(data.serialised_size()) {
             return Err(Error::NotEnoughSpace);
         }
 
         // store the data
         trace!("{:?}", LogMarker::StoringChunk);
-        let _addr = self.db.write_chunk(data).await?;
+        let _addr = self.db.put(data).await?;
         trace!("{:?}", LogMarker::StoredNewChunk);
 
         Ok(())
     }
 }
 
+/// What scrubbing a stored chunk found.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum ScrubOutcome {
+    /// The chunk matches its name, or was already removed
+    Intact,
+    /// The chunk doesn't match its name, and was quarantined
+    Corrupt,
+    /// The chunk is temporary and expired, and was removed
+    Expired,
+}
+
 impl Display for ChunkStorage {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         write!(formatter, "ChunkStorage")
@@ -8,20 +8,24 @@
 
 mod chunks;
 mod registers;
+mod scrubber;
 
-use crate::{dbs::Result, UsedSpace};
+use crate::{dbs::Result, ChunkStoreConfig, UsedSpace};
 
 use sn_interface::messaging::{
     data::{DataQuery, Error, RegisterQuery, RegisterStoreExport, StorageLevel},
     system::NodeQueryResponse,
 };
 use sn_interface::types::{
-    register::User, RegisterAddress, ReplicatedData, ReplicatedDataAddress, SPENTBOOK_TYPE_SCOPE,
-    SPENTBOOK_TYPE_TAG,
+    register::User, ChunkAddress, RegisterAddress, ReplicatedData, ReplicatedDataAddress,
+    SPENTBOOK_TYPE_SCOPE, SPENTBOOK_TYPE_TAG,
 };
 
 pub(crate) use chunks::ChunkStorage;
 pub(crate) use registers::RegisterStorage;
+pub use scrubber::ScrubProgress;
+
+use scrubber::ChunkScrubber;
 
 use sn_dbc::SpentProofShare;
 use std::{path::Path, sync::Arc};
@@ -35,16 +39,27 @@ pub struct DataStorage {
     registers: RegisterStorage,
     used_space: UsedSpace,
     last_recorded_level: Arc<RwLock<StorageLevel>>,
+    scrubber: ChunkScrubber,
 }
 
 impl DataStorage {
-    /// Set up a new DataStorage instance
+    /// Set up a new DataStorage instance, storing chunks with the default config
     pub fn new(path: &Path, used_space: UsedSpace) -> Result<Self> {
+        Self::with_chunk_store_config(path, used_space, ChunkStoreConfig::default())
+    }
+
+    /// Set up a new DataStorage instance, storing chunks with the given config
+    pub fn with_chunk_store_config(
+        path: &Path,
+        used_space: UsedSpace,
+        config: ChunkStoreConfig,
+    ) -> Result<Self> {
         Ok(Self {
-            chunks: ChunkStorage::new(path, used_space.clone())?,
+            chunks: ChunkStorage::new(path, used_space.clone(), config)?,
             registers: RegisterStorage::new(path, used_space.clone())?,
             used_space,
             last_recorded_level: Arc::new(RwLock::new(StorageLevel::zero())),
+            scrubber: ChunkScrubber::default(),
         })
     }
 
@@ -99,6 +114,38 @@ impl DataStorage {
     pub(crate) async fn query(&self, query: &DataQuery, requester: User) -> NodeQueryResponse {
         match query {
             DataQuery::GetChunk(addr) => self.chunks.get(addr).await,
+            DataQuery::ReportMissingChunk(addr) => {
+                // Reports are handled by Elders, which never forward them to us
+                warn!(
+                    "Unexpected missing chunk report received at Adult: {:?}",
+                    addr
+                );
+                NodeQueryResponse::FailedToCreateOperationId
+            }
+            DataQuery::GetSectionStats(prefix) => {
+                // Section stats are answered by Elders, which never forward the query to us
+                warn!(
+                    "Unexpected section stats query received at Adult: {:?}",
+                    prefix
+                );
+                NodeQueryResponse::FailedToCreateOperationId
+            }
+            DataQuery::GetSectionKeyHistory { prefix, .. } => {
+                // As are section key histories
+                warn!(
+                    "Unexpected section key history query received at Adult: {:?}",
+                    prefix
+                );
+                NodeQueryResponse::FailedToCreateOperationId
+            }
+            DataQuery::GetSectionMembers { prefix, .. } => {
+                // And section members
+                warn!(
+                    "Unexpected section members query received at Adult: {:?}",
+                    prefix
+                );
+                NodeQueryResponse::FailedToCreateOperationId
+            }
             DataQuery::Register(read) => self.registers.read(read, requester).await,
             DataQuery::Spentbook(read) => {
                 // TODO: this is temporary till spentbook native data type is implemented,
@@ -192,6 +239,23 @@ impl DataStorage {
         }
     }
 
+    /// Recover the chunk writes which were not completed when the node went down,
+    /// returning how many there were
+    pub(crate) async fn recover_chunk_writes(&self) -> Result<usize> {
+        self.chunks.recover().await
+    }
+
+    /// Check the integrity of the next `batch_size` stored chunks, over rolling passes.
+    /// Returns the addresses of the corrupt chunks found, which were quarantined.
+    pub(crate) async fn scrub_next_chunks(&self, batch_size: usize) -> Result<Vec<ChunkAddress>> {
+        self.scrubber.scrub_next(&self.chunks, batch_size).await
+    }
+
+    /// Progress of the integrity scrubbing of the stored chunks
+    pub async fn scrub_progress(&self) -> ScrubProgress {
+        self.scrubber.progress().await
+    }
+
     /// Retrieve all keys/ReplicatedDataAddresses of stored data
     pub async fn keys(&self) -> Result<Vec<ReplicatedDataAddress>> {
         let chunk_keys = self
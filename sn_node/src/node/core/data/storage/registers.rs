@@ -11,9 +11,10 @@ use crate::dbs::{
 };
 use sn_interface::messaging::{
     data::{
-        CreateRegister, DeleteRegister, EditRegister, ExtendRegister, OperationId, RegisterCmd,
-        RegisterQuery, RegisterStoreExport, ReplicatedRegisterLog, SignedRegisterCreate,
-        SignedRegisterDelete, SignedRegisterEdit, SignedRegisterExtend,
+        CreateRegister, DeleteRegister, EditRegister, Error as ErrorMsg, ExtendRegister,
+        OperationId, RegisterCmd, RegisterQuery, RegisterStoreExport, ReplicatedRegisterLog,
+        SignedRegisterCreate, SignedRegisterDelete, SignedRegisterEdit, SignedRegisterEditBatch,
+        SignedRegisterExtend,
     },
     system::NodeQueryResponse,
     SectionAuth, ServiceAuth, VerifyAuthority,
@@ -182,6 +183,15 @@ impl RegisterStorage {
                             return None;
                         }
                     }
+                    RegisterCmd::EditBatch(batch) => {
+                        if batch.validate().is_err() {
+                            error!(
+                                "Invalid batch of edits found for a cmd stored in db: {:?}",
+                                stored_cmd
+                            );
+                            return None;
+                        }
+                    }
                     RegisterCmd::Delete(SignedRegisterDelete { op, auth }) => {
                         let verification = auth.verify_authority(serialize(&op).ok()?);
                         if verification.is_err() {
@@ -355,7 +365,7 @@ impl RegisterStorage {
                     .state
                     .read()
                     .await
-                    .check_permissions(Action::Write, Some(User::Key(public_key)))?;
+                    .check_write_op(&edit, User::Key(public_key))?;
                 let result = entry
                     .state
                     .write()
@@ -376,6 +386,39 @@ impl RegisterStorage {
                     }
                 }
             }
+            EditBatch(batch) => {
+                batch.validate().map_err(Error::InvalidRegisterBatch)?;
+
+                let entry = self.try_load_cache_entry(&key).await?;
+
+                info!(
+                    "Editing Register with a batch of {} edits",
+                    batch.edits.len()
+                );
+                // the edits are applied to a copy of the register first,
+                // for none of them to be applied if any fails
+                let mut state = entry.state.write().await;
+                let mut edited = state.clone();
+                for (index, SignedRegisterEdit { op, auth }) in batch.edits.into_iter().enumerate()
+                {
+                    let result = edited
+                        .check_write_op(&op.edit, User::Key(auth.public_key))
+                        .and_then(|()| edited.apply_op(op.edit));
+                    if let Err(err) = result {
+                        trace!("Editing Register with a batch failed at edit #{index}: {err:?}");
+                        let rejected = BTreeMap::from([(index, err.to_string())]);
+                        return Err(Error::InvalidRegisterBatch(
+                            ErrorMsg::RegisterBatchRejected(rejected),
+                        ));
+                    }
+                }
+
+                entry.store.append(cmd)?;
+                *state = edited;
+                self.used_space.increase(required_space);
+                trace!("Editing Register with a batch success!");
+                Ok(())
+            }
             Delete(SignedRegisterDelete { op, auth }) => {
                 let DeleteRegister(address) = &op;
                 if address.is_public() {
@@ -687,6 +730,13 @@ impl RegisterStorage {
                         reg.apply_op(edit).map_err(Error::NetworkData)?
                     }
                 }
+                EditBatch(SignedRegisterEditBatch { edits, .. }) => {
+                    if let Some((reg, _)) = &mut hydrated_register {
+                        for SignedRegisterEdit { op, .. } in edits {
+                            reg.apply_op(op.edit).map_err(Error::NetworkData)?
+                        }
+                    }
+                }
                 Delete(SignedRegisterDelete { .. }) => {
                     // should not be reachable, since we don't append these ops
                     return Err(Error::KeyNotFound(key.to_string()));
@@ -776,17 +826,23 @@ fn section_auth() -> SectionAuth {
 mod test {
     use super::{create_reg_w_policy, RegisterStorage};
 
+    use crate::dbs::Error as DbError;
     use crate::node::{Error, Result};
     use crate::UsedSpace;
     use sn_interface::messaging::{
-        data::{RegisterCmd, RegisterQuery},
+        data::{
+            EditRegister, Error as ErrorMsg, RegisterCmd, RegisterQuery, SignedRegisterEdit,
+            SignedRegisterEditBatch,
+        },
         system::NodeQueryResponse,
+        ServiceAuth,
     };
-    use sn_interface::types::register::{EntryHash, Policy, PrivatePolicy, PublicPolicy};
+    use sn_interface::types::register::{EntryHash, Policy, PrivatePolicy, PublicPolicy, Register};
     use sn_interface::types::DataAddress;
     use sn_interface::types::{register::User, Keypair};
 
     use rand::Rng;
+    use std::collections::BTreeSet;
     use tempfile::tempdir;
     use xor_name::Prefix;
 
@@ -830,6 +886,47 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_register_write_batch_is_atomic() -> Result<()> {
+        let store = new_store()?;
+        let (authority, keypair) = random_user();
+        let policy = Policy::Private(PrivatePolicy {
+            owner: authority,
+            permissions: Default::default(),
+        });
+        let name = xor_name::rand::random();
+        store
+            .write(create_reg_w_policy(
+                name,
+                0,
+                policy.clone(),
+                keypair.clone(),
+            )?)
+            .await?;
+
+        let mut replica = Register::new(name, 0, policy, u16::MAX);
+        let address = *replica.address();
+        let batch = |edits| RegisterCmd::EditBatch(SignedRegisterEditBatch { address, edits });
+
+        // an edit by someone not allowed to write fails the whole batch
+        let (_, other_keypair) = random_user();
+        let first = edit_register(&mut replica, b"first", &keypair)?;
+        let unallowed = edit_register(&mut replica, b"unallowed", &other_keypair)?;
+        match store.write(batch(vec![first.clone(), unallowed])).await {
+            Err(DbError::InvalidRegisterBatch(ErrorMsg::RegisterBatchRejected(rejected))) => {
+                assert_eq!(rejected.keys().copied().collect::<Vec<_>>(), vec![1]);
+            }
+            other => panic!("Unexpected result of an invalid batch: {:?}", other),
+        }
+        assert_eq!(read_entries(&store, address, authority).await, 0);
+
+        let second = edit_register(&mut replica, b"second", &keypair)?;
+        store.write(batch(vec![first, second])).await?;
+        assert_eq!(read_entries(&store, address, authority).await, 2);
+
+        Ok(())
+    }
+
     async fn register_write<F>(create_register: F) -> Result<()>
     where
         F: Fn() -> Result<(RegisterCmd, User)>,
@@ -1021,6 +1118,37 @@ mod test {
         Ok(store)
     }
 
+    fn edit_register(
+        replica: &mut Register,
+        entry: &[u8],
+        keypair: &Keypair,
+    ) -> Result<SignedRegisterEdit> {
+        let (_, edit) = replica.write(entry.to_vec(), BTreeSet::default())?;
+        let op = EditRegister {
+            address: *replica.address(),
+            edit,
+        };
+        let signature = keypair.sign(&bincode::serialize(&op)?);
+        Ok(SignedRegisterEdit {
+            op,
+            auth: ServiceAuth {
+                public_key: keypair.public_key(),
+                signature,
+            },
+        })
+    }
+
+    async fn read_entries(
+        store: &RegisterStorage,
+        address: sn_interface::types::RegisterAddress,
+        requester: User,
+    ) -> usize {
+        match store.read(&RegisterQuery::Read(address), requester).await {
+            NodeQueryResponse::ReadRegister((Ok(entries), _)) => entries.len(),
+            other => panic!("Could not read! {:?}", other),
+        }
+    }
+
     fn random_user() -> (User, Keypair) {
         let keypair = Keypair::new_ed25519();
         let authority = User::Key(keypair.public_key());
@@ -0,0 +1,186 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{chunks::ScrubOutcome, ChunkStorage};
+
+use crate::dbs::Result;
+use sn_interface::types::{log_markers::LogMarker, ChunkAddress};
+
+use std::{collections::VecDeque, sync::Arc};
+use tokio::sync::RwLock;
+
+/// Progress of the background integrity scrubbing of the stored chunks.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ScrubProgress {
+    /// Number of complete passes over all the stored chunks
+    pub passes_completed: u64,
+    /// Number of chunks checked so far in the current pass
+    pub scrubbed: usize,
+    /// Number of chunks to be checked in the current pass
+    pub total: usize,
+    /// Number of corrupt chunks found, and quarantined, since the node started
+    pub corrupt_chunks: u64,
+    /// Number of expired temporary chunks removed since the node started
+    pub expired_chunks: u64,
+}
+
+#[derive(Debug, Default)]
+struct ScrubState {
+    // chunks still to be checked in the current pass
+    pending: VecDeque<ChunkAddress>,
+    progress: ScrubProgress,
+}
+
+/// Checks the stored chunks, a batch at a time, over rolling passes.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ChunkScrubber {
+    state: Arc<RwLock<ScrubState>>,
+}
+
+impl ChunkScrubber {
+    pub(crate) async fn progress(&self) -> ScrubProgress {
+        self.state.read().await.progress
+    }
+
+    /// Check the next `batch_size` chunks of the current pass, starting a new pass over
+    /// all the stored chunks if the previous one was completed. Expired temporary chunks are removed.
+    /// Returns the addresses of the corrupt chunks found, which were quarantined.
+    pub(crate) async fn scrub_next(
+        &self,
+        chunks: &ChunkStorage,
+        batch_size: usize,
+    ) -> Result<Vec<ChunkAddress>> {
+        let mut state = self.state.write().await;
+
+        if state.pending.is_empty() {
+            let addresses = chunks.keys()?;
+            state.progress.total = addresses.len();
+            state.progress.scrubbed = 0;
+            state.pending = addresses.into();
+        }
+
+        let mut corrupt = vec![];
+        for _ in 0..batch_size {
+            let address = match state.pending.pop_front() {
+                Some(address) => address,
+                None => break,
+            };
+
+            match chunks.scrub_chunk(&address).await? {
+                ScrubOutcome::Intact => {}
+                ScrubOutcome::Corrupt => {
+                    warn!("{:?} {:?}", LogMarker::CorruptChunkQuarantined, address);
+                    corrupt.push(address);
+                    state.progress.corrupt_chunks += 1;
+                }
+                ScrubOutcome::Expired => {
+                    debug!("{:?} {:?}", LogMarker::ExpiredChunkRemoved, address);
+                    state.progress.expired_chunks += 1;
+                }
+            }
+            state.progress.scrubbed += 1;
+        }
+
+        if state.pending.is_empty() && state.progress.total > 0 {
+            state.progress.passes_completed += 1;
+            info!(
+                "{:?} {:?}",
+                LogMarker::ChunkScrubPassCompleted,
+                state.progress
+            );
+        }
+
+        Ok(corrupt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChunkStoreConfig, UsedSpace};
+    use sn_interface::types::{utils::random_bytes, Chunk};
+    use std::time::{Duration, SystemTime};
+    use tempfile::tempdir;
+    use walkdir::WalkDir;
+
+    #[tokio::test]
+    async fn corrupt_chunk_is_quarantined() -> Result<()> {
+        let root = tempdir()?;
+        let chunks = ChunkStorage::new(
+            root.path(),
+            UsedSpace::new(usize::MAX),
+            ChunkStoreConfig::default(),
+        )?;
+        for _ in 0..5 {
+            chunks.store(&Chunk::new(random_bytes(100))).await?;
+        }
+
+        // flip the content of one of the chunk files on disk, rather than of the write-ahead log
+        // kept along them
+        let corrupted_file = WalkDir::new(root.path().join("chunkdb"))
+            .into_iter()
+            .flatten()
+            .find(|entry| entry.file_type().is_file())
+            .map(|entry| entry.into_path())
+            .expect("no chunk file found");
+        std::fs::write(&corrupted_file, random_bytes(100))?;
+
+        let scrubber = ChunkScrubber::default();
+        let corrupt = scrubber.scrub_next(&chunks, 3).await?;
+        let progress = scrubber.progress().await;
+        assert_eq!(progress.scrubbed, 3);
+        assert_eq!(progress.total, 5);
+        assert_eq!(progress.passes_completed, 0);
+
+        let mut all_corrupt = corrupt;
+        all_corrupt.extend(scrubber.scrub_next(&chunks, 3).await?);
+        let progress = scrubber.progress().await;
+        assert_eq!(progress.scrubbed, 5);
+        assert_eq!(progress.passes_completed, 1);
+        assert_eq!(progress.corrupt_chunks, 1);
+
+        assert_eq!(all_corrupt.len(), 1);
+        assert!(!chunks.keys()?.contains(&all_corrupt[0]));
+        assert_eq!(chunks.keys()?.len(), 4);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn expired_chunk_is_removed() -> Result<()> {
+        let root = tempdir()?;
+        let chunks = ChunkStorage::new(
+            root.path(),
+            UsedSpace::new(usize::MAX),
+            ChunkStoreConfig::default(),
+        )?;
+        let permanent = Chunk::new(random_bytes(100));
+        let expiring = Chunk::new_temporary(
+            &random_bytes(100),
+            SystemTime::now() + Duration::from_secs(2),
+        );
+        chunks.store(&permanent).await?;
+        chunks.store(&expiring).await?;
+        assert!(chunks.get_chunk(expiring.address()).await.is_ok());
+
+        tokio::time::sleep(Duration::from_secs(3)).await;
+        assert!(chunks.get_chunk(expiring.address()).await.is_err());
+
+        let scrubber = ChunkScrubber::default();
+        let corrupt = scrubber.scrub_next(&chunks, 2).await?;
+        assert!(corrupt.is_empty());
+        assert_eq!(scrubber.progress().await.expired_chunks, 1);
+        assert_eq!(chunks.keys()?, vec![*permanent.address()]);
+
+        // expired chunks are not stored again, e.g. when replicated
+        chunks.store(&expiring).await?;
+        assert_eq!(chunks.keys()?.len(), 1);
+
+        Ok(())
+    }
+}
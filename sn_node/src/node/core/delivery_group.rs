@@ -196,10 +196,7 @@ mod tests {
             .context("too few elders")?;
 
         let section_pk = network_knowledge.authority_provider().await.section_key();
-        let dst = DstLocation::Node {
-            name: dst_name,
-            section_pk,
-        };
+        let dst = DstLocation::node(dst_name, section_pk);
         let (recipients, dg_size) = delivery_targets(&dst, &our_name, &network_knowledge).await?;
 
         // Send only to the dst node.
@@ -221,10 +218,7 @@ mod tests {
         assert!(network_knowledge.update_member(node_state).await);
 
         let section_pk = network_knowledge.authority_provider().await.section_key();
-        let dst = DstLocation::Node {
-            name: dst_name,
-            section_pk,
-        };
+        let dst = DstLocation::node(dst_name, section_pk);
         let (recipients, dg_size) = delivery_targets(&dst, &our_name, &network_knowledge).await?;
 
         // Send only to the dst node.
@@ -243,10 +237,7 @@ mod tests {
             .await
             .substituted_in(xor_name::rand::random());
         let section_pk = network_knowledge.authority_provider().await.section_key();
-        let dst = DstLocation::Section {
-            name: dst_name,
-            section_pk,
-        };
+        let dst = DstLocation::section(dst_name, section_pk);
         let (recipients, dg_size) = delivery_targets(&dst, &our_name, &network_knowledge).await?;
 
         // Send to all our elders except us.
@@ -275,10 +266,7 @@ mod tests {
 
         let dst_name = choose_elder_name(&section_auth1)?;
         let section_pk = network_knowledge.authority_provider().await.section_key();
-        let dst = DstLocation::Node {
-            name: dst_name,
-            section_pk,
-        };
+        let dst = DstLocation::node(dst_name, section_pk);
         let (recipients, dg_size) = delivery_targets(&dst, &our_name, &network_knowledge).await?;
 
         // Send only to the dst node.
@@ -301,10 +289,7 @@ mod tests {
             .prefix()
             .substituted_in(xor_name::rand::random());
         let section_pk = network_knowledge.authority_provider().await.section_key();
-        let dst = DstLocation::Node {
-            name: dst_name,
-            section_pk,
-        };
+        let dst = DstLocation::node(dst_name, section_pk);
         let (recipients, dg_size) = delivery_targets(&dst, &our_name, &network_knowledge).await?;
 
         // Send to all elders in the dst section
@@ -332,10 +317,7 @@ mod tests {
             .pushed(false)
             .substituted_in(xor_name::rand::random());
         let section_pk = network_knowledge.authority_provider().await.section_key();
-        let dst = DstLocation::Node {
-            name: dst_name,
-            section_pk,
-        };
+        let dst = DstLocation::node(dst_name, section_pk);
         let (recipients, dg_size) = delivery_targets(&dst, &our_name, &network_knowledge).await?;
 
         // Send to all elders in the dst section
@@ -363,10 +345,7 @@ mod tests {
             .prefix()
             .substituted_in(xor_name::rand::random());
         let section_pk = network_knowledge.authority_provider().await.section_key();
-        let dst = DstLocation::Section {
-            name: dst_name,
-            section_pk,
-        };
+        let dst = DstLocation::section(dst_name, section_pk);
         let (recipients, dg_size) = delivery_targets(&dst, &our_name, &network_knowledge).await?;
 
         // Send to all elders in the final dst section
@@ -394,10 +373,7 @@ mod tests {
             .pushed(false)
             .substituted_in(xor_name::rand::random());
         let section_pk = network_knowledge.authority_provider().await.section_key();
-        let dst = DstLocation::Section {
-            name: dst_name,
-            section_pk,
-        };
+        let dst = DstLocation::section(dst_name, section_pk);
         let (recipients, dg_size) = delivery_targets(&dst, &our_name, &network_knowledge).await?;
 
         // Send to a subset of elders in the intermediary dst section
@@ -420,10 +396,7 @@ mod tests {
 
         let dst_name = choose_elder_name(&network_knowledge.authority_provider().await)?;
         let section_pk = network_knowledge.authority_provider().await.section_key();
-        let dst = DstLocation::Node {
-            name: dst_name,
-            section_pk,
-        };
+        let dst = DstLocation::node(dst_name, section_pk);
         let (recipients, dg_size) = delivery_targets(&dst, &our_name, &network_knowledge).await?;
 
         // Send to chosen elder
@@ -448,10 +421,7 @@ mod tests {
             .await
             .substituted_in(xor_name::rand::random());
         let section_pk = network_knowledge.authority_provider().await.section_key();
-        let dst = DstLocation::Node {
-            name: dst_name,
-            section_pk,
-        };
+        let dst = DstLocation::node(dst_name, section_pk);
         let (recipients, dg_size) = delivery_targets(&dst, &our_name, &network_knowledge).await?;
 
         // Send to all elders
@@ -476,10 +446,7 @@ mod tests {
             .await
             .substituted_in(xor_name::rand::random());
         let section_pk = network_knowledge.authority_provider().await.section_key();
-        let dst = DstLocation::Section {
-            name: dst_name,
-            section_pk,
-        };
+        let dst = DstLocation::section(dst_name, section_pk);
         let (recipients, dg_size) = delivery_targets(&dst, &our_name, &network_knowledge).await?;
 
         // Send to all elders
@@ -503,10 +470,7 @@ mod tests {
             .pushed(true)
             .substituted_in(xor_name::rand::random());
         let section_pk = network_knowledge.authority_provider().await.section_key();
-        let dst = DstLocation::Node {
-            name: dst_name,
-            section_pk,
-        };
+        let dst = DstLocation::node(dst_name, section_pk);
         let (recipients, dg_size) = delivery_targets(&dst, &our_name, &network_knowledge).await?;
 
         // Send to all elders
@@ -530,10 +494,7 @@ mod tests {
             .pushed(true)
             .substituted_in(xor_name::rand::random());
         let section_pk = network_knowledge.authority_provider().await.section_key();
-        let dst = DstLocation::Section {
-            name: dst_name,
-            section_pk,
-        };
+        let dst = DstLocation::section(dst_name, section_pk);
         let (recipients, dg_size) = delivery_targets(&dst, &our_name, &network_knowledge).await?;
 
         // Send to all elders
@@ -0,0 +1,211 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::{
+    api::cmds::Cmd,
+    core::{Node, Proposal},
+    Result,
+};
+use sn_interface::messaging::system::{
+    Feature, FeatureActivation, KeyedSig, NodeVersion, SectionAuth, SystemMsg,
+};
+use sn_interface::network_knowledge::{supermajority, SectionAuthUtils};
+use sn_interface::types::Peer;
+
+/// Version of our binary, advertised when joining a section.
+pub(crate) fn our_version() -> NodeVersion {
+    NodeVersion::parse(env!("CARGO_PKG_VERSION"))
+        .expect("the crate version is a valid node version")
+}
+
+// Features supported by a supermajority of the members of a section, given their versions.
+fn supported_features(versions: &[Option<NodeVersion>]) -> Vec<Feature> {
+    Feature::ALL
+        .iter()
+        .copied()
+        .filter(|feature| {
+            let supporting = versions
+                .iter()
+                .filter(|version| feature.is_supported_by(**version))
+                .count();
+            !versions.is_empty() && supporting >= supermajority(versions.len())
+        })
+        .collect()
+}
+
+impl Node {
+    /// Whether the feature was activated in our section, and it reached the generation it
+    /// takes effect at.
+    pub(crate) async fn is_feature_active(&self, feature: Feature) -> bool {
+        match self.activated_features.read().await.get(&feature) {
+            Some(activation) => {
+                self.network_knowledge.chain_len().await >= activation.value.generation
+            }
+            None => false,
+        }
+    }
+
+    /// The activations agreed in our section, for the nodes joining it to learn of them.
+    pub(crate) async fn feature_activations(&self) -> Vec<SectionAuth<FeatureActivation>> {
+        self.activated_features
+            .read()
+            .await
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Replace the activations we know of with the ones of the section we just joined, or were
+    /// relocated to, keeping those signed by that section only.
+    pub(crate) async fn set_feature_activations(
+        &self,
+        activations: Vec<SectionAuth<FeatureActivation>>,
+    ) {
+        let section_chain = self.network_knowledge.section_chain().await;
+        let mut activated = self.activated_features.write().await;
+        activated.clear();
+        for activation in activations {
+            if !activation.verify(&section_chain) {
+                warn!(
+                    "Ignoring activation of {:?} not signed by our section",
+                    activation.value.feature
+                );
+                continue;
+            }
+            let _prev = activated.insert(activation.value.feature, activation);
+        }
+    }
+
+    /// Propose activating the features enough of our members now support, to take effect with
+    /// our next section key.
+    pub(crate) async fn propose_supported_features(&self) -> Result<Vec<Cmd>> {
+        if self.is_not_elder().await {
+            return Ok(vec![]);
+        }
+
+        let versions: Vec<_> = self
+            .network_knowledge
+            .section_members()
+            .await
+            .iter()
+            .map(|node_state| node_state.version())
+            .collect();
+        let generation = self.network_knowledge.chain_len().await + 1;
+
+        let mut cmds = vec![];
+        for feature in supported_features(&versions) {
+            if self.activated_features.read().await.contains_key(&feature) {
+                continue;
+            }
+            info!(
+                "Proposing to activate {:?} at generation {}",
+                feature, generation
+            );
+            let activation = FeatureActivation {
+                feature,
+                generation,
+            };
+            cmds.extend(self.propose(Proposal::ActivateFeature(activation)).await?);
+        }
+
+        Ok(cmds)
+    }
+
+    /// Record the activation our Elders agreed on, and let the rest of our section know of it.
+    pub(crate) async fn handle_feature_activation_agreement(
+        &self,
+        activation: FeatureActivation,
+        sig: KeyedSig,
+    ) -> Result<Vec<Cmd>> {
+        let activation = SectionAuth {
+            value: activation,
+            sig,
+        };
+        if !self.record_feature_activation(activation.clone()).await {
+            return Ok(vec![]);
+        }
+
+        let our_name = self.info.read().await.name();
+        let recipients: Vec<Peer> = self
+            .network_knowledge
+            .section_members()
+            .await
+            .iter()
+            .map(|node_state| *node_state.peer())
+            .filter(|peer| peer.name() != our_name)
+            .collect();
+        if recipients.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let section_name = self.network_knowledge.prefix().await.name();
+        let section_key = self.network_knowledge.section_key().await;
+        let node_msg = SystemMsg::FeatureActivation(activation);
+        let cmd = self
+            .send_direct_msg_to_nodes(recipients, node_msg, section_name, section_key)
+            .await?;
+
+        Ok(vec![cmd])
+    }
+
+    /// Record an activation agreed by the Elders of our section, once verified it was.
+    pub(crate) async fn handle_feature_activation(
+        &self,
+        activation: SectionAuth<FeatureActivation>,
+    ) -> Result<Vec<Cmd>> {
+        let section_chain = self.network_knowledge.section_chain().await;
+        if !activation.verify(&section_chain) {
+            warn!(
+                "Ignoring activation of {:?} not signed by our section",
+                activation.value.feature
+            );
+            return Ok(vec![]);
+        }
+
+        let _ = self.record_feature_activation(activation).await;
+        Ok(vec![])
+    }
+
+    // Returns whether the activation wasn't known yet. The first one agreed stands.
+    async fn record_feature_activation(&self, activation: SectionAuth<FeatureActivation>) -> bool {
+        let mut activated = self.activated_features.write().await;
+        if activated.contains_key(&activation.value.feature) {
+            return false;
+        }
+
+        info!(
+            "Activating {:?} at generation {}",
+            activation.value.feature, activation.value.generation
+        );
+        let _prev = activated.insert(activation.value.feature, activation);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn features_are_supported_by_a_supermajority() {
+        let supported = Some(Feature::ReplicationConfirmation.min_version());
+        let older = Some(NodeVersion::new(0, 1, 0));
+
+        assert_eq!(
+            supported_features(&[supported, supported, supported, older]),
+            vec![Feature::ReplicationConfirmation]
+        );
+        assert!(supported_features(&[supported, supported, older, None]).is_empty());
+        assert!(supported_features(&[]).is_empty());
+    }
+
+    #[test]
+    fn our_version_is_the_crate_version() {
+        assert_eq!(our_version().to_string(), env!("CARGO_PKG_VERSION"));
+    }
+}
@@ -8,7 +8,8 @@
 
 use crate::node::{
     api::cmds::Cmd,
-    core::{relocation::ChurnId, Node, Proposal},
+    core::{Node, Proposal},
+    node_age::ChurnId,
     Event, Result,
 };
 use sn_consensus::Generation;
@@ -43,9 +44,30 @@ impl Node {
                 *self.joins_allowed.write().await = joins_allowed;
                 Ok(vec![])
             }
+            Proposal::DataCopyCount(count) => self.handle_data_copy_count_agreement(count).await,
+            Proposal::ActivateFeature(activation) => {
+                self.handle_feature_activation_agreement(activation, sig)
+                    .await
+            }
         }
     }
 
+    // The number of copies is carried in our SAP, so a new one is generated for it to apply,
+    // even if the elders stay the same. Adults then reorganise their data upon the AE update
+    // of the new SAP, replicating it as per the new count.
+    async fn handle_data_copy_count_agreement(&self, count: usize) -> Result<Vec<Cmd>> {
+        if count == 0 {
+            error!("Ignoring agreement on storing no copy of data");
+            return Ok(vec![]);
+        }
+
+        info!("Agreed on storing {} copies of each data", count);
+        *self.agreed_data_copy_count.write().await = Some(count);
+
+        self.promote_and_demote_elders_except(&BTreeSet::default())
+            .await
+    }
+
     pub(crate) async fn handle_online_agreement(
         &self,
         new_info: NodeState,
@@ -135,6 +157,7 @@ impl Node {
 
         cmds.extend(result);
         cmds.extend(self.send_node_approval(new_info).await);
+        cmds.extend(self.propose_supported_features().await?);
 
         info!("cmds in queue for Accepting node {:?}", cmds);
 
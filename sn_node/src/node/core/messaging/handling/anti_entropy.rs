@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::node::{api::cmds::Cmd, core::Node, messages::WireMsgUtils, Error, Event, Result};
+use crate::node::{api::cmds::Cmd, core::Node, Error, Event, Result};
 use sn_interface::messaging::{
     system::{KeyedSig, SectionAuth, SectionPeers, SystemMsg},
     MsgId, MsgType, SrcLocation, WireMsg,
@@ -322,12 +322,15 @@ impl Node {
                         section_chain,
                         bounced_msg,
                     };
-                    let wire_msg = WireMsg::single_src(
-                        &self.info.read().await.clone(),
-                        src_location.to_dst(),
-                        ae_msg,
-                        self.network_knowledge.section_key().await,
-                    )?;
+                    let wire_msg = self
+                        .signing_pool
+                        .single_src(
+                            &self.info.read().await.clone(),
+                            src_location.to_dst(),
+                            ae_msg,
+                            self.network_knowledge.section_key().await,
+                        )
+                        .await?;
                     trace!("{}", LogMarker::AeSendRedirect);
 
                     return Ok(Some(Cmd::SendMsg {
@@ -415,12 +418,15 @@ impl Node {
             }
         };
 
-        let wire_msg = WireMsg::single_src(
-            &self.info.read().await.clone(),
-            src_location.to_dst(),
-            ae_msg,
-            self.network_knowledge.section_key().await,
-        )?;
+        let wire_msg = self
+            .signing_pool
+            .single_src(
+                &self.info.read().await.clone(),
+                src_location.to_dst(),
+                ae_msg,
+                self.network_knowledge.section_key().await,
+            )
+            .await?;
 
         Ok(Some(Cmd::SendMsg {
             recipients: vec![*sender],
@@ -447,12 +453,15 @@ impl Node {
             bounced_msg: original_wire_msg.serialize()?,
         };
 
-        let wire_msg = WireMsg::single_src(
-            &self.info.read().await.clone(),
-            src_location.to_dst(),
-            ae_msg,
-            self.network_knowledge.section_key().await,
-        )?;
+        let wire_msg = self
+            .signing_pool
+            .single_src(
+                &self.info.read().await.clone(),
+                src_location.to_dst(),
+                ae_msg,
+                self.network_knowledge.section_key().await,
+            )
+            .await?;
 
         trace!("{} in ae_redirect", LogMarker::AeSendRedirect);
 
@@ -467,9 +476,10 @@ impl Node {
 mod tests {
     use super::*;
     use crate::node::{
-        api::tests::create_comm, create_test_max_capacity_and_root_storage, MIN_ADULT_AGE,
+        api::tests::create_comm, create_test_max_capacity_and_root_storage, ClientRateLimits,
+        QueryDelegationConfig, MIN_ADULT_AGE,
     };
-    use crate::UsedSpace;
+    use crate::{ChunkStoreConfig, UsedSpace};
     use sn_interface::elder_count;
     use sn_interface::messaging::{
         AuthKind, AuthorityProof, DstLocation, MsgId, MsgType, NodeAuth, NodeMsgAuthority,
@@ -746,6 +756,9 @@ mod tests {
                 mpsc::channel(1).0,
                 UsedSpace::new(max_capacity),
                 root_storage_dir,
+                ChunkStoreConfig::default(),
+                ClientRateLimits::default(),
+                QueryDelegationConfig::default(),
                 genesis_sk_set.clone(),
             )
             .await?;
@@ -806,10 +819,7 @@ mod tests {
 
             let dst_name = xor_name::rand::random();
             let dst_section_key = SecretKey::random().public_key();
-            let dst_location = DstLocation::Node {
-                name: dst_name,
-                section_pk: dst_section_key,
-            };
+            let dst_location = DstLocation::node(dst_name, dst_section_key);
 
             let msg_id = MsgId::new();
 
@@ -819,10 +829,7 @@ mod tests {
 
             let wire_msg = WireMsg::new_msg(msg_id, payload, msg_kind, dst_location)?;
 
-            let src_location = SrcLocation::Node {
-                name: sender_name,
-                section_pk: src_section_pk,
-            };
+            let src_location = SrcLocation::node(sender_name, src_section_pk);
 
             Ok((wire_msg, src_location))
         }
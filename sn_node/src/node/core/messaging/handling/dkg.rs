@@ -10,12 +10,11 @@ use crate::node::{
     api::cmds::Cmd,
     core::{Node, Proposal},
     dkg::DkgFailureSigSetUtils,
-    messages::WireMsgUtils,
     Error, Result,
 };
 use sn_interface::messaging::{
     system::{DkgFailureSig, DkgFailureSigSet, DkgSessionId, SystemMsg},
-    DstLocation, WireMsg,
+    DstLocation,
 };
 use sn_interface::network_knowledge::{SectionAuthorityProvider, SectionKeyShare};
 use sn_interface::types::{log_markers::LogMarker, Peer};
@@ -115,15 +114,15 @@ impl Node {
             message,
             session_id,
         };
-        let wire_msg = WireMsg::single_src(
-            &self.info.read().await.clone(),
-            DstLocation::Node {
-                name: sender.name(),
+        let wire_msg = self
+            .signing_pool
+            .single_src(
+                &self.info.read().await.clone(),
+                DstLocation::node(sender.name(), section_pk),
+                node_msg,
                 section_pk,
-            },
-            node_msg,
-            section_pk,
-        )?;
+            )
+            .await?;
 
         Ok(vec![Cmd::SendMsg {
             recipients: vec![sender],
@@ -119,6 +119,20 @@ impl Node {
             debug!("InvalidElderCandidates: received SAP at gen {} with candidates {:#?}, expected candidates {:#?}", sap.membership_gen(), received_candidates, expected_candidates);
             return Err(Error::InvalidElderCandidates);
         }
+        self.check_data_copy_count(sap).await
+    }
+
+    async fn check_data_copy_count(&self, sap: &SectionAuthorityProvider) -> Result<()> {
+        let expected = self.next_data_copy_count().await;
+        if sap.data_copy_count() != expected {
+            debug!(
+                "InvalidDataCopyCount: received SAP at gen {} with data copy count {}, expected {}",
+                sap.membership_gen(),
+                sap.data_copy_count(),
+                expected
+            );
+            return Err(Error::InvalidDataCopyCount(sap.data_copy_count()));
+        }
         Ok(())
     }
 
@@ -135,13 +149,18 @@ impl Node {
         let members = self.get_members_at_gen(sap1.membership_gen()).await?;
         let dummy_chain_len = 0;
         let dummy_gen = 0;
+        let data_copy_count = self.next_data_copy_count().await;
 
         let received_candidates1: BTreeSet<&Peer> = sap1.elders().collect();
         let received_candidates2: BTreeSet<&Peer> = sap2.elders().collect();
 
-        if let Some((dkg1, dkg2)) =
-            try_split_dkg(&members, &previous_gen_sap, dummy_chain_len, dummy_gen)
-        {
+        if let Some((dkg1, dkg2)) = try_split_dkg(
+            &members,
+            &previous_gen_sap,
+            dummy_chain_len,
+            dummy_gen,
+            data_copy_count,
+        ) {
             let expected_peers1: BTreeSet<Peer> =
                 dkg1.elders.iter().map(|(n, a)| Peer::new(*n, *a)).collect();
             let expected_peers2: BTreeSet<Peer> =
@@ -159,7 +178,8 @@ impl Node {
                 debug!("InvalidElderCandidates: received SAP2 at gen {} with candidates {:#?}, expected candidates {:#?}", sap2.membership_gen(), received_candidates2, expected_candidates2);
                 return Err(Error::InvalidElderCandidates);
             }
-            Ok(())
+            self.check_data_copy_count(sap1).await?;
+            self.check_data_copy_count(sap2).await
         } else {
             Err(Error::InvalidSplitCandidates)
         }
@@ -13,8 +13,9 @@ use crate::node::{
 };
 use sn_interface::elder_count;
 use sn_interface::messaging::system::{
-    JoinAsRelocatedRequest, JoinAsRelocatedResponse, JoinRejectionReason, JoinRequest,
-    JoinResponse, MembershipState, NodeState, SystemMsg,
+    HandshakeFrame, JoinAsRelocatedRequest, JoinAsRelocatedResponse, JoinRejectionReason,
+    JoinRequest, JoinResponse, MembershipState, NodeState, Reachability, SystemMsg,
+    PROTOCOL_VERSION,
 };
 use sn_interface::network_knowledge::{SectionAuthUtils, FIRST_SECTION_MAX_AGE, MIN_ADULT_AGE};
 use sn_interface::types::{log_markers::LogMarker, Peer};
@@ -33,14 +34,75 @@ impl Node {
     ) -> Result<Vec<Cmd>> {
         debug!("Received {:?} from {}", join_request, peer);
 
+        if self.join_denylist.read().await.contains(&peer.addr().ip()) {
+            debug!("Rejecting JoinRequest from {peer} - denylisted.");
+            return self
+                .reject_join_request(peer, JoinRejectionReason::Denylisted)
+                .await;
+        }
+
+        let genesis_key = *self.network_knowledge.genesis_key();
+        if join_request.genesis_key != genesis_key {
+            debug!(
+                "Rejecting JoinRequest from {peer} - trying to join network {:?}.",
+                join_request.genesis_key
+            );
+            return self
+                .reject_join_request(peer, JoinRejectionReason::WrongNetwork(genesis_key))
+                .await;
+        }
+
+        // Peers we can't make sense of are told so now, rather than misbehaving once joined
+        match join_request.negotiate_protocol() {
+            Ok(capabilities) => trace!(
+                "JoinRequest from {peer} speaks protocol version {}, sharing capabilities {:?}",
+                join_request.protocol_version.min(PROTOCOL_VERSION),
+                capabilities
+            ),
+            Err(reason) => {
+                debug!("Rejecting JoinRequest from {peer} - {reason}.");
+                return self.reject_join_request(peer, reason).await;
+            }
+        }
+
         // Require resource signed if joining as a new node.
         if let Some(response) = join_request.resource_proof_response {
+            let reachability_attestation = response.reachability_attestation.clone();
             if !self
                 .validate_resource_proof_response(&peer.name(), response)
                 .await
             {
-                debug!("Ignoring JoinRequest from {peer} - invalid resource signed response");
-                return Ok(vec![]);
+                debug!("Rejecting JoinRequest from {peer} - invalid resource signed response");
+                return self
+                    .reject_join_request(peer, JoinRejectionReason::ResourceProofFailed)
+                    .await;
+            }
+
+            // The peer is only proposed as a member once it proved it listens at its address
+            if let Err(failure) = self
+                .validate_reachability_attestation(&peer, reachability_attestation.as_ref())
+                .await
+            {
+                debug!("Rejecting JoinRequest from {peer} - address not attested: {failure}");
+                let reason = JoinRejectionReason::AddrNotAttested {
+                    addr: peer.addr(),
+                    failure,
+                };
+                return self.reject_join_request(peer, reason).await;
+            }
+
+            // The age expected in the first section depends on its size, which may have
+            // changed since the challenge was sent, so it's only checked afterwards.
+            if !self.network_knowledge.prefix().await.is_empty() {
+                let (is_age_invalid, expected) = self.verify_joining_node_age(&peer).await;
+                if is_age_invalid {
+                    debug!("Rejecting JoinRequest from {peer} - invalid age.");
+                    let reason = JoinRejectionReason::BadAge {
+                        expected,
+                        found: peer.age(),
+                    };
+                    return self.reject_join_request(peer, reason).await;
+                }
             }
 
             let node_state = NodeState {
@@ -48,6 +110,7 @@ impl Node {
                 addr: peer.addr(),
                 state: MembershipState::Joined,
                 previous_name: None,
+                version: join_request.version,
             };
             return self.propose_membership_change(node_state).await;
         }
@@ -89,17 +152,10 @@ impl Node {
                 "Rejecting JoinRequest from {} - joins currently not allowed.",
                 peer,
             );
-            let node_msg = SystemMsg::JoinResponse(Box::new(JoinResponse::Rejected(
-                JoinRejectionReason::JoinsDisallowed,
-            )));
-
             trace!("{}", LogMarker::SendJoinsDisallowed);
-
-            trace!("Sending {:?} to {}", node_msg, peer);
-            return Ok(vec![
-                self.send_direct_msg(peer, node_msg, our_section_key)
-                    .await?,
-            ]);
+            return self
+                .reject_join_request(peer, JoinRejectionReason::JoinsDisallowed)
+                .await;
         }
 
         let (is_age_invalid, expected_age) = self.verify_joining_node_age(&peer).await;
@@ -150,26 +206,71 @@ impl Node {
             ]);
         }
 
-        // Do reachability check only for the initial join request
-        let cmd = if self.comm.is_reachable(&peer.addr()).await.is_err() {
-            let node_msg = SystemMsg::JoinResponse(Box::new(JoinResponse::Rejected(
-                JoinRejectionReason::NodeNotReachable(peer.addr()),
-            )));
+        // Dial the peer back at its address, only for the initial join request, with the
+        // challenge it's to attest along with its resource proof
+        let addr_challenge = self.addr_challenge(&peer).await?;
+        if self.comm.dial_back(&peer, addr_challenge).await.is_err() {
+            return self
+                .reject_join_request(peer, JoinRejectionReason::NodeNotReachable(peer.addr()))
+                .await;
+        }
 
-            trace!("{}", LogMarker::SendJoinRejected);
+        // It's reachable, let's then send the proof challenge
+        Ok(vec![self.send_resource_proof_challenge(peer).await?])
+    }
 
-            trace!("Sending {:?} to {}", node_msg, peer);
-            self.send_direct_msg(peer, node_msg, our_section_key)
-                .await?
+    // Tell the peer why its join request is rejected, rather than just dropping it
+    async fn reject_join_request(
+        &self,
+        peer: Peer,
+        reason: JoinRejectionReason,
+    ) -> Result<Vec<Cmd>> {
+        let node_msg = SystemMsg::JoinResponse(Box::new(JoinResponse::Rejected(reason)));
+
+        trace!("{}", LogMarker::SendJoinRejected);
+
+        trace!("Sending {:?} to {}", node_msg, peer);
+        let section_key = self.network_knowledge.section_key().await;
+        Ok(vec![
+            self.send_direct_msg(peer, node_msg, section_key).await?,
+        ])
+    }
+
+    // Dial back the peer which asked us to, and tell it whether we could reach it
+    pub(crate) async fn handle_reachability_check(&self, peer: Peer) -> Result<Vec<Cmd>> {
+        let reachability = if self.comm.is_reachable(&peer.addr()).await.is_ok() {
+            Reachability::Reachable(peer.addr())
         } else {
-            // It's reachable, let's then send the proof challenge
-            self.send_resource_proof_challenge(peer).await?
+            Reachability::Unreachable(peer.addr())
         };
 
-        Ok(vec![cmd])
+        trace!(
+            "{:?} {:?} to {}",
+            LogMarker::SendReachabilityCheckResponse,
+            reachability,
+            peer
+        );
+        let node_msg = SystemMsg::ReachabilityCheckResponse(reachability);
+        let section_key = self.network_knowledge.section_key().await;
+
+        Ok(vec![
+            self.send_direct_msg(peer, node_msg, section_key).await?,
+        ])
     }
 
     pub(crate) async fn verify_joining_node_age(&self, peer: &Peer) -> (bool, u8) {
+        // A node which left our section may rejoin with its previous name, and age, for it to be
+        // relocated as a rejoining node once agreed Online.
+        if let Some(old_info) = self
+            .network_knowledge
+            .is_either_member_or_archived(&peer.name())
+            .await
+        {
+            if old_info.state() == MembershipState::Left && old_info.age() == peer.age() {
+                return (false, peer.age());
+            }
+        }
+
         // During the first section, nodes shall use ranged age to avoid too many nodes getting
         // relocated at the same time. After the first section splits, nodes shall only
         // start with an age of MIN_ADULT_AGE
@@ -204,6 +305,36 @@ impl Node {
         }
     }
 
+    /// Adds a frame of a join request too large to be sent whole, returning the request once
+    /// all its frames are in, whatever their order. Frames past the last one or over the max
+    /// size get the request dropped.
+    pub(crate) async fn assemble_handshake(
+        &self,
+        peer: &Peer,
+        frame: HandshakeFrame,
+    ) -> Option<SystemMsg> {
+        let handshake = match self
+            .handshake_assembler
+            .write()
+            .await
+            .add_frame(peer.name(), frame)
+        {
+            Ok(handshake) => handshake?,
+            Err(error) => {
+                debug!("Dropping handshake from {peer}: {error}");
+                return None;
+            }
+        };
+
+        match rmp_serde::from_slice(&handshake) {
+            Ok(msg) => Some(msg),
+            Err(error) => {
+                debug!("Dropping handshake from {peer} which failed to deserialise: {error}");
+                None
+            }
+        }
+    }
+
     pub(crate) async fn handle_join_as_relocated_request(
         &self,
         peer: Peer,
@@ -212,6 +343,12 @@ impl Node {
     ) -> Result<Vec<Cmd>> {
         debug!("Received JoinAsRelocatedRequest {join_request:?} from {peer}",);
 
+        if self.is_not_elder().await
+            && join_request.section_key == self.network_knowledge.section_key().await
+        {
+            return Ok(vec![]);
+        }
+
         let our_prefix = self.network_knowledge.prefix().await;
         if !our_prefix.matches(&peer.name())
             || join_request.section_key != self.network_knowledge.section_key().await
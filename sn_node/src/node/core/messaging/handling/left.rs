@@ -8,7 +8,9 @@ use sn_interface::{
 
 use crate::node::{
     api::cmds::Cmd,
-    core::{relocation::ChurnId, Node, Result},
+    core::{Node, Result},
+    node_age::ChurnId,
+    Event,
 };
 
 impl Node {
@@ -47,6 +49,12 @@ impl Node {
             node_state.addr()
         );
 
+        self.send_event(Event::MemberLeft {
+            name: node_state.name(),
+            age: node_state.age(),
+        })
+        .await;
+
         // If this is an Offline agreement where the new node state is Relocated,
         // we then need to send the Relocate msg to the peer attaching the signed NodeState
         // containing the relocation details.
@@ -24,8 +24,7 @@ pub(crate) use proposals::handle_proposal;
 use crate::dbs::Error as DbError;
 use crate::node::{
     api::cmds::Cmd,
-    core::{DkgSessionInfo, Node, Proposal as CoreProposal, DATA_QUERY_LIMIT},
-    messages::WireMsgUtils,
+    core::{DkgSessionInfo, Node, PeerTag, Proposal as CoreProposal, DATA_QUERY_LIMIT},
     Error, Event, MessageReceived, Result, MIN_LEVEL_WHEN_FULL,
 };
 use sn_interface::messaging::{
@@ -35,15 +34,15 @@ use sn_interface::messaging::{
         JoinResponse, NodeCmd, NodeEvent, NodeMsgAuthorityUtils, NodeQuery,
         Proposal as ProposalMsg, SystemMsg,
     },
-    AuthorityProof, DstLocation, MsgId, MsgType, NodeMsgAuthority, SectionAuth, WireMsg,
+    AuthorityProof, DstLocation, Error as MessagingError, MsgId, MsgType, NodeMsgAuthority,
+    SectionAuth, SigningDomain, WireMsg,
 };
 use sn_interface::network_knowledge::NetworkKnowledge;
-use sn_interface::types::{log_markers::LogMarker, Peer, PublicKey};
+use sn_interface::types::{log_markers::LogMarker, Peer, PublicKey, ReplicatedData};
 
 use bls::PublicKey as BlsPublicKey;
 use bytes::Bytes;
-use itertools::Itertools;
-use sn_dysfunction::IssueType;
+use sn_dysfunction::{IssueType, Signal};
 use xor_name::XorName;
 
 // Message handling
@@ -61,6 +60,7 @@ impl Node {
         let msg_id = wire_msg.msg_id();
         // payload needed for aggregation
         let payload = wire_msg.payload.clone();
+        let src_name = wire_msg.msg_kind().src().name();
 
         let message_type = match wire_msg.into_msg() {
             Ok(message_type) => message_type,
@@ -69,10 +69,18 @@ impl Node {
                     "Failed to deserialize message payload ({:?}): {:?}",
                     msg_id, error
                 );
+                // only a signature not matching the authority the msg claims is held against
+                // the sender, as a payload we fail to decode may just be from a newer version
+                if matches!(error, MessagingError::InvalidSignature) {
+                    self.comm.report_violation(sender.addr()).await;
+                }
                 return Ok(cmds);
             }
         };
 
+        // any msg tells the peer is alive, whichever it is, once its signature is verified
+        self.comm.liveness().heard_from(&sender.name()).await;
+
         match message_type {
             MsgType::System {
                 msg_id,
@@ -104,6 +112,25 @@ impl Node {
                     return Ok(cmds);
                 }
 
+                // the same msg may reach us from several peers relaying it, which is only told
+                // once its authority is verified, for a forged copy not to shadow the genuine one.
+                // Clients connect to us directly, and resend a msg with the same id when it
+                // wasn't answered, so only system msgs are filtered.
+                if !self
+                    .comm
+                    .is_new_msg(msg_id, src_name, dst_location.section_pk())
+                    .await
+                {
+                    trace!(
+                        "{:?} {:?} from {}, via {}",
+                        LogMarker::DuplicateMsgDropped,
+                        msg_id,
+                        src_name,
+                        sender.addr()
+                    );
+                    return Ok(cmds);
+                }
+
                 // Let's check for entropy before we proceed further
                 // Adult nodes don't need to carry out entropy checking,
                 // however the message shall always be handled.
@@ -119,7 +146,9 @@ impl Node {
                         | SystemMsg::AntiEntropyUpdate { .. }
                         | SystemMsg::AntiEntropyRedirect { .. }
                         | SystemMsg::JoinRequest(_)
-                        | SystemMsg::JoinAsRelocatedRequest(_) => {
+                        | SystemMsg::ReachabilityCheck
+                        | SystemMsg::JoinAsRelocatedRequest(_)
+                        | SystemMsg::HandshakeFrame(_) => {
                             trace!(
                                 "Entropy check skipped for {:?}, handling message directly",
                                 msg_id
@@ -142,10 +171,12 @@ impl Node {
                                     .await?
                                 {
                                     // we want to log issues with an elder who is out of sync here...
-                                    let knowledge = self.network_knowledge.elders().await;
-                                    let mut known_elders = knowledge.iter().map(|peer| peer.name());
-
-                                    if known_elders.contains(&sender.name()) {
+                                    if self
+                                        .comm
+                                        .peer_tags()
+                                        .has_tag(&sender.name(), PeerTag::Elder)
+                                        .await
+                                    {
                                         // we track a dysfunction against our elder here
                                         self.dysfunction_tracking
                                             .track_issue(sender.name(), IssueType::Knowledge)
@@ -186,6 +217,13 @@ impl Node {
                 dst_location,
                 auth,
             } => {
+                // tagged only now its signature was verified, so the tag can't be claimed
+                // by anyone for any name
+                let peer_tags = self.comm.peer_tags();
+                if !peer_tags.has_tag(&sender.name(), PeerTag::Client).await {
+                    peer_tags.tag(sender.name(), PeerTag::Client).await;
+                }
+
                 let dst_name = match msg.dst_address() {
                     Some(name) => name,
                     None => {
@@ -284,6 +322,8 @@ impl Node {
                     "Invalid signature on received system message, dropping the message: {:?}",
                     msg_id
                 );
+                self.log_signal(sender.name(), Signal::InvalidSignature)
+                    .await;
                 Ok(vec![])
             }
             Ok(true) => Ok(vec![]),
@@ -324,11 +364,7 @@ impl Node {
             }
             SystemMsg::Relocate(node_state) => {
                 trace!("Handling msg: Relocate from {}: {:?}", sender, msg_id);
-                Ok(self
-                    .handle_relocate(node_state)
-                    .await?
-                    .into_iter()
-                    .collect())
+                self.handle_relocate(node_state).await
             }
             SystemMsg::StartConnectivityTest(name) => {
                 trace!(
@@ -342,23 +378,27 @@ impl Node {
 
                 Ok(vec![Cmd::TestConnectivity(name)])
             }
+            SystemMsg::FeatureActivation(activation) => {
+                trace!(
+                    "Handling msg: FeatureActivation from {}: {:?}",
+                    sender,
+                    msg_id
+                );
+                self.handle_feature_activation(activation).await
+            }
             SystemMsg::JoinAsRelocatedResponse(join_response) => {
                 trace!("Handling msg: JoinAsRelocatedResponse from {}", sender);
                 if let Some(ref mut joining_as_relocated) = *self.relocate_state.write().await {
-                    if let Some(cmd) = joining_as_relocated
+                    joining_as_relocated
                         .handle_join_response(*join_response, sender.addr())
-                        .await?
-                    {
-                        return Ok(vec![cmd]);
-                    }
+                        .await
                 } else {
                     error!(
                         "No relocation in progress upon receiving {:?}",
                         join_response
                     );
+                    Ok(vec![])
                 }
-
-                Ok(vec![])
             }
             SystemMsg::NodeMsgError {
                 error,
@@ -396,6 +436,10 @@ impl Node {
                 bounced_msg,
             } => {
                 trace!("Handling msg: AE-Redirect from {}: {:?}", sender, msg_id);
+                self.comm
+                    .peer_tags()
+                    .tag(sender.name(), PeerTag::Relay)
+                    .await;
                 self.handle_anti_entropy_redirect_msg(
                     section_auth.into_state(),
                     section_signed,
@@ -409,6 +453,23 @@ impl Node {
                 trace!("Received Probe message from {}: {:?}", sender, msg_id);
                 Ok(vec![])
             }
+            SystemMsg::KeepAlive => {
+                trace!("Handling msg: KeepAlive from {}: {:?}", sender, msg_id);
+                let section_key = self.network_knowledge.section_key().await;
+                Ok(vec![
+                    self.send_direct_msg(sender, SystemMsg::KeepAliveResponse, section_key)
+                        .await?,
+                ])
+            }
+            SystemMsg::KeepAliveResponse => {
+                // we heard from the peer, which is all we needed
+                trace!("Received KeepAliveResponse from {}: {:?}", sender, msg_id);
+                Ok(vec![])
+            }
+            SystemMsg::PeerExchange(peers) => {
+                trace!("Handling msg: PeerExchange from {}: {:?}", sender, msg_id);
+                self.handle_peer_exchange(sender, peers).await
+            }
             #[cfg(feature = "back-pressure")]
             SystemMsg::BackPressure(msgs_per_s) => {
                 trace!(
@@ -427,6 +488,7 @@ impl Node {
                     JoinResponse::Approval {
                         section_auth,
                         section_chain,
+                        activated_features,
                         ..
                     } => {
                         info!(
@@ -435,10 +497,10 @@ impl Node {
                         );
                         info!("Relocation: Successfully aggregated ApprovalShares for joining the network");
 
-                        if let Some(ref mut joining_as_relocated) =
-                            *self.relocate_state.write().await
-                        {
-                            let new_node = joining_as_relocated.node.clone();
+                        // taking the relocation state out, for its timeout not to abandon it
+                        let relocate_state = self.relocate_state.write().await.take();
+                        if let Some(joining_as_relocated) = relocate_state {
+                            let new_node = joining_as_relocated.node;
                             let new_name = new_node.name();
                             let previous_name = self.info.read().await.name();
                             let new_keypair = new_node.keypair.clone();
@@ -466,6 +528,8 @@ impl Node {
                             //       may require the `node` to be switched to new already.
 
                             self.relocate(new_node, new_network_knowledge).await?;
+                            self.set_feature_activations(activated_features).await;
+                            self.tag_section_members().await;
 
                             trace!(
                                 "Relocation: Sending aggregated JoinRequest to {:?}",
@@ -502,17 +566,44 @@ impl Node {
                 trace!("Handling msg: JoinRequest from {}", sender);
                 self.handle_join_request(sender, *join_request).await
             }
+            SystemMsg::ReachabilityCheck => {
+                trace!("Handling msg: ReachabilityCheck from {}", sender);
+                self.handle_reachability_check(sender).await
+            }
+            SystemMsg::ReachabilityCheckResponse(reachability) => {
+                // only expected while bootstrapping, before we handle msgs here
+                trace!(
+                    "Ignoring unexpected ReachabilityCheckResponse from {}: {:?}",
+                    sender,
+                    reachability
+                );
+                Ok(vec![])
+            }
             SystemMsg::JoinAsRelocatedRequest(join_request) => {
                 trace!("Handling msg: JoinAsRelocatedRequest from {}", sender);
-                if self.is_not_elder().await
-                    && join_request.section_key == self.network_knowledge.section_key().await
-                {
-                    return Ok(vec![]);
-                }
-
                 self.handle_join_as_relocated_request(sender, *join_request, known_keys)
                     .await
             }
+            SystemMsg::HandshakeFrame(frame) => {
+                trace!("Handling msg: HandshakeFrame from {}", sender);
+                match self.assemble_handshake(&sender, frame).await {
+                    Some(SystemMsg::JoinRequest(join_request)) => {
+                        self.handle_join_request(sender, *join_request).await
+                    }
+                    Some(SystemMsg::JoinAsRelocatedRequest(join_request)) => {
+                        self.handle_join_as_relocated_request(sender, *join_request, known_keys)
+                            .await
+                    }
+                    Some(msg) => {
+                        warn!(
+                            "Dropping unexpected handshake msg from {}: {:?}",
+                            sender, msg
+                        );
+                        Ok(vec![])
+                    }
+                    None => Ok(vec![]),
+                }
+            }
             SystemMsg::MembershipVotes(votes) => {
                 let mut cmds = vec![];
                 cmds.extend(self.handle_membership_votes(sender, votes).await?);
@@ -523,6 +614,7 @@ impl Node {
             SystemMsg::Propose {
                 proposal,
                 sig_share,
+                key_bundle,
             } => {
                 if self.is_not_elder().await {
                     trace!("Adult handling a Propose msg from {}: {:?}", sender, msg_id);
@@ -541,12 +633,17 @@ impl Node {
                     },
                     ProposalMsg::NewElders(sap) => CoreProposal::NewElders(sap.into_authed_state()),
                     ProposalMsg::JoinsAllowed(allowed) => CoreProposal::JoinsAllowed(allowed),
+                    ProposalMsg::DataCopyCount(count) => CoreProposal::DataCopyCount(count),
+                    ProposalMsg::ActivateFeature(activation) => {
+                        CoreProposal::ActivateFeature(activation)
+                    }
                 };
 
                 handle_proposal(
                     msg_id,
                     core_proposal,
                     sig_share,
+                    key_bundle,
                     sender,
                     &self.network_knowledge,
                     &self.proposal_aggregator,
@@ -620,7 +717,19 @@ impl Node {
             }
             SystemMsg::NodeCmd(NodeCmd::ReceiveMetadata { metadata }) => {
                 info!("Processing received MetadataExchange packet: {:?}", msg_id);
-                self.set_adult_levels(metadata).await;
+                self.set_metadata(metadata).await?;
+                Ok(vec![])
+            }
+            SystemMsg::NodeCmd(NodeCmd::RecordSpentPayments { payments }) => {
+                // only our Elders accept payments on behalf of our section
+                if !self.network_knowledge.is_elder(&sender.name()).await {
+                    warn!(
+                        "Ignoring payments spent reported by non-Elder {:?}: {:?}",
+                        sender, msg_id
+                    );
+                    return Ok(vec![]);
+                }
+                self.spent_payments.merge(payments).await?;
                 Ok(vec![])
             }
             SystemMsg::NodeEvent(NodeEvent::CouldNotStoreData {
@@ -658,38 +767,57 @@ impl Node {
                     let mut cmds = vec![];
 
                     for data in data_collection {
-                        // We are an adult here, so just store away!
-                        // This may return a DatabaseFull error... but we should have reported storage increase
-                        // well before this
-                        match self.data_storage.store(&data).await {
-                            Ok(level_report) => {
-                                info!("Storage level report: {:?}", level_report);
-                                cmds.extend(self.record_storage_level_if_any(level_report).await);
-                            }
-                            Err(DbError::NotEnoughSpace) => {
-                                // db full
-                                error!("Not enough space to store more data");
-
-                                let node_id =
-                                    PublicKey::from(self.info.read().await.keypair.public);
-                                let msg = SystemMsg::NodeEvent(NodeEvent::CouldNotStoreData {
-                                    node_id,
-                                    data,
-                                    full: true,
-                                });
-
-                                cmds.push(self.send_msg_to_our_elders(msg).await?)
-                            }
-                            Err(error) => {
-                                // the rest seem to be non-problematic errors.. (?)
-                                error!("Problem storing data, but it was ignored: {error}");
-                            }
-                        }
+                        let _stored = self.store_replica(data, &mut cmds).await?;
                     }
 
                     Ok(cmds)
                 };
             }
+            SystemMsg::NodeCmd(NodeCmd::ReplicateDataAndConfirm {
+                data,
+                correlation_id,
+            }) => {
+                info!(
+                    "ReplicateDataAndConfirm MsgId: {:?}, for cmd {:?}",
+                    msg_id, correlation_id
+                );
+                if self.is_elder().await {
+                    error!("Received unexpected message while Elder");
+                    return Ok(vec![]);
+                }
+
+                let mut cmds = vec![];
+                if self.store_replica(data, &mut cmds).await? {
+                    // confirm back to the Elder which is to tell the client
+                    let msg = SystemMsg::NodeEvent(NodeEvent::DataReplicated { correlation_id });
+                    let dst = DstLocation::node(
+                        sender.name(),
+                        self.network_knowledge.section_key().await,
+                    );
+                    cmds.push(Cmd::SignOutgoingSystemMsg { msg, dst });
+                }
+
+                Ok(cmds)
+            }
+            SystemMsg::NodeEvent(NodeEvent::DataReplicated { correlation_id }) => {
+                if !self.is_elder().await {
+                    error!("Received unexpected message while Adult");
+                    return Ok(vec![]);
+                }
+                trace!(
+                    "Adult {} confirmed storing the data of cmd {:?}",
+                    sender,
+                    correlation_id
+                );
+                match self
+                    .pending_replications
+                    .confirm(correlation_id, sender.name())
+                    .await
+                {
+                    Some(client) => self.send_cmd_replicated(client, correlation_id).await,
+                    None => Ok(vec![]),
+                }
+            }
             SystemMsg::NodeCmd(NodeCmd::SendAnyMissingRelevantData(known_data_addresses)) => {
                 info!(
                     "{:?} MsgId: {:?}",
@@ -700,6 +828,11 @@ impl Node {
                 self.get_missing_data_for_node(sender, known_data_addresses)
                     .await
             }
+            SystemMsg::NodeCmd(NodeCmd::RepairData(data_addresses)) => {
+                info!("{:?} MsgId: {:?}", LogMarker::RepairingReportedData, msg_id);
+
+                self.repair_data_held(data_addresses).await
+            }
             SystemMsg::NodeCmd(node_cmd) => {
                 self.send_event(Event::MessageReceived {
                     msg_id,
@@ -792,15 +925,15 @@ impl Node {
                         message,
                     };
                     let section_pk = self.network_knowledge.section_key().await;
-                    let wire_msg = WireMsg::single_src(
-                        &self.info.read().await.clone(),
-                        DstLocation::Node {
-                            name: sender.name(),
+                    let wire_msg = self
+                        .signing_pool
+                        .single_src(
+                            &self.info.read().await.clone(),
+                            DstLocation::node(sender.name(), section_pk),
+                            node_msg,
                             section_pk,
-                        },
-                        node_msg,
-                        section_pk,
-                    )?;
+                        )
+                        .await?;
 
                     Ok(vec![Cmd::SendMsg {
                         recipients: vec![sender],
@@ -823,7 +956,9 @@ impl Node {
                     WireMsg::serialize_msg_payload(&SystemMsg::DkgStart(session_id.clone()))?;
                 let auth = section_auth.clone().into_inner();
                 if self.network_knowledge.section_key().await == auth.sig.public_key {
-                    if let Err(err) = AuthorityProof::verify(auth, payload) {
+                    if let Err(err) =
+                        AuthorityProof::verify_in_domain(auth, SigningDomain::Section, payload)
+                    {
                         error!("Error verifying signature for DkgSessionInfo: {:?}", err);
                         return Ok(cmds);
                     } else {
@@ -856,6 +991,38 @@ impl Node {
         }
     }
 
+    // Stores a replica of the data as an Adult, returning whether it got stored
+    async fn store_replica(&self, data: ReplicatedData, cmds: &mut Vec<Cmd>) -> Result<bool> {
+        // This may return a DatabaseFull error... but we should have reported storage increase
+        // well before this
+        match self.data_storage.store(&data).await {
+            Ok(level_report) => {
+                info!("Storage level report: {:?}", level_report);
+                cmds.extend(self.record_storage_level_if_any(level_report).await);
+                Ok(true)
+            }
+            Err(DbError::NotEnoughSpace) => {
+                // db full
+                error!("Not enough space to store more data");
+
+                let node_id = PublicKey::from(self.info.read().await.keypair.public);
+                let msg = SystemMsg::NodeEvent(NodeEvent::CouldNotStoreData {
+                    node_id,
+                    data,
+                    full: true,
+                });
+
+                cmds.push(self.send_msg_to_our_elders(msg).await?);
+                Ok(false)
+            }
+            Err(error) => {
+                // the rest seem to be non-problematic errors.. (?)
+                error!("Problem storing data, but it was ignored: {error}");
+                Ok(false)
+            }
+        }
+    }
+
     async fn record_storage_level_if_any(&self, level: Option<StorageLevel>) -> Vec<Cmd> {
         let mut cmds = vec![];
         if let Some(level) = level {
@@ -870,10 +1037,8 @@ impl Node {
                 level,
             });
 
-            let dst = DstLocation::Section {
-                name: node_xorname,
-                section_pk: self.network_knowledge.section_key().await,
-            };
+            let dst =
+                DstLocation::section(node_xorname, self.network_knowledge.section_key().await);
 
             cmds.push(Cmd::SignOutgoingSystemMsg { msg, dst });
         }
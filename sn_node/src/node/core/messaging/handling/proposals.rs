@@ -12,13 +12,14 @@ use sn_interface::messaging::{
     MsgId,
 };
 use sn_interface::network_knowledge::NetworkKnowledge;
-use sn_interface::types::Peer;
+use sn_interface::types::{KeyBundle, Peer};
 
 // Insert the proposal into the proposal aggregator and handle it if aggregated.
 pub(crate) async fn handle_proposal(
     msg_id: MsgId,
     proposal: Proposal,
     sig_share: SigShare,
+    key_bundle: Option<KeyBundle>,
     sender: Peer,
     network_knowledge: &NetworkKnowledge,
     proposal_aggregator: &SignatureAggregator,
@@ -46,6 +47,28 @@ pub(crate) async fn handle_proposal(
                 );
                 return Ok(vec![]);
             }
+
+            // The DKG participant has to prove the key share it signed with is its own,
+            // i.e. the one of its index in the new key set.
+            let index = section_auth
+                .names()
+                .into_iter()
+                .position(|name| name == sender.name());
+            let key_share_proven = match (key_bundle, index) {
+                (Some(key_bundle), Some(index)) => {
+                    sig_share.index == index
+                        && sig_share.public_key_set == section_auth.public_key_set()
+                        && key_bundle.verify_for(&sender.name(), &sig_share.public_key_set, index)
+                }
+                _ => false,
+            };
+            if !key_share_proven {
+                warn!(
+                    "Dropped SectionInfo proposal ({:?}) from {} not proving its key share: {:?}",
+                    msg_id, sender, proposal
+                );
+                return Ok(vec![]);
+            }
         }
     } else {
         // Proposal from other section shall be ignored.
@@ -10,9 +10,10 @@ use crate::node::{
     api::cmds::Cmd,
     core::{
         bootstrap::JoiningAsRelocated,
-        relocation::{find_nodes_to_relocate, ChurnId, RelocateDetailsUtils},
-        Node, Proposal,
+        relocation::{find_nodes_to_relocate, RelocateDetailsUtils},
+        Node, Proposal, MAX_RELOCATION_ATTEMPTS,
     },
+    node_age::ChurnId,
     Event, Result,
 };
 use sn_interface::elder_count;
@@ -22,7 +23,7 @@ use sn_interface::messaging::system::{
 use sn_interface::network_knowledge::NodeState;
 use sn_interface::types::log_markers::LogMarker;
 
-use std::collections::BTreeSet;
+use std::{collections::BTreeSet, net::SocketAddr};
 use xor_name::XorName;
 
 // Relocation
@@ -92,7 +93,7 @@ impl Node {
     pub(crate) async fn handle_relocate(
         &self,
         relocate_proof: SectionAuth<NodeStateMsg>,
-    ) -> Result<Option<Cmd>> {
+    ) -> Result<Vec<Cmd>> {
         let (dst_xorname, dst_section_key, new_age) =
             if let MembershipState::Relocated(ref relocate_details) = relocate_proof.value.state {
                 (
@@ -105,14 +106,14 @@ impl Node {
                     "Ignoring Relocate msg containing invalid NodeState: {:?}",
                     relocate_proof.state
                 );
-                return Ok(None);
+                return Ok(vec![]);
             };
 
         let node = self.info.read().await.clone();
         if dst_xorname != node.name() {
             // This `Relocate` message is not for us - it's most likely a duplicate of a previous
             // message that we already handled.
-            return Ok(None);
+            return Ok(vec![]);
         }
 
         debug!(
@@ -123,7 +124,7 @@ impl Node {
         match *self.relocate_state.read().await {
             Some(_) => {
                 trace!("Ignore Relocate - relocation already in progress");
-                return Ok(None);
+                return Ok(vec![]);
             }
             None => {
                 trace!("{}", LogMarker::RelocateStart);
@@ -138,16 +139,8 @@ impl Node {
         // flow. This same instance will handle responses till relocation is complete.
         let genesis_key = *self.network_knowledge.genesis_key();
 
-        let bootstrap_addrs = if let Ok(sap) = self.network_knowledge.section_by_name(&dst_xorname)
-        {
-            sap.addresses()
-        } else {
-            self.network_knowledge
-                .authority_provider()
-                .await
-                .addresses()
-        };
-        let (joining_as_relocated, cmd) = JoiningAsRelocated::start(
+        let bootstrap_addrs = self.relocation_bootstrap_addrs(&dst_xorname).await;
+        let (joining_as_relocated, mut cmds) = JoiningAsRelocated::start(
             node,
             genesis_key,
             relocate_proof,
@@ -155,10 +148,77 @@ impl Node {
             dst_xorname,
             dst_section_key,
             new_age,
-        )?;
+            self.signing_pool.clone(),
+        )
+        .await?;
+
+        if let Some(duration) = self.relocation_timeout {
+            cmds.push(Cmd::ScheduleTimeout {
+                duration,
+                token: joining_as_relocated.timeout_token(),
+            });
+        }
 
         *self.relocate_state.write().await = Some(Box::new(joining_as_relocated));
 
-        Ok(Some(cmd))
+        Ok(cmds)
+    }
+
+    /// Starts the relocation in progress over if the timeout is the one it was given, unless it
+    /// was attempted [`MAX_RELOCATION_ATTEMPTS`] times already. It's then given up on, raising
+    /// `Event::RelocationTimedOut` for the node to rejoin the network, as it's not a member of
+    /// its previous section anymore. Returns `None` if the timeout isn't the relocation's.
+    pub(crate) async fn handle_relocation_timeout(&self, token: u64) -> Result<Option<Vec<Cmd>>> {
+        let mut relocate_state = self.relocate_state.write().await;
+        let joining_as_relocated = match relocate_state.as_mut() {
+            Some(joining_as_relocated) if joining_as_relocated.timeout_token() == token => {
+                joining_as_relocated
+            }
+            _ => return Ok(None),
+        };
+        let previous_name = self.info.read().await.name();
+        let dst = joining_as_relocated.dst_xorname();
+
+        if joining_as_relocated.attempts() < MAX_RELOCATION_ATTEMPTS {
+            warn!(
+                "Relocation of {} to {} not approved in time, starting it over",
+                previous_name, dst
+            );
+            let bootstrap_addrs = self.relocation_bootstrap_addrs(&dst).await;
+            let mut cmds = joining_as_relocated.retry(bootstrap_addrs).await?;
+            if let Some(duration) = self.relocation_timeout {
+                cmds.push(Cmd::ScheduleTimeout {
+                    duration,
+                    token: joining_as_relocated.timeout_token(),
+                });
+            }
+            return Ok(Some(cmds));
+        }
+
+        *relocate_state = None;
+        drop(relocate_state);
+        warn!(
+            "{}: relocation of {} to {} not approved after {} attempts, giving it up",
+            LogMarker::RelocateTimedOut,
+            previous_name,
+            dst,
+            MAX_RELOCATION_ATTEMPTS
+        );
+        self.send_event(Event::RelocationTimedOut { previous_name, dst })
+            .await;
+
+        Ok(Some(vec![]))
+    }
+
+    // Addresses of the Elders of the section to relocate to, as far as we know, or of ours
+    async fn relocation_bootstrap_addrs(&self, dst: &XorName) -> Vec<SocketAddr> {
+        if let Ok(sap) = self.network_knowledge.section_by_name(dst) {
+            sap.addresses()
+        } else {
+            self.network_knowledge
+                .authority_provider()
+                .await
+                .addresses()
+        }
     }
 }
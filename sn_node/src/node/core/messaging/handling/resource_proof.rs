@@ -11,7 +11,13 @@ use crate::node::{
     core::{Node, RESOURCE_PROOF_DATA_SIZE, RESOURCE_PROOF_DIFFICULTY},
     Error, Result,
 };
-use sn_interface::messaging::system::{JoinResponse, ResourceProofResponse, SystemMsg};
+use sn_interface::messaging::{
+    system::{
+        AddrAttestationFailure, JoinResponse, ReachabilityAttestation, ResourceProofResponse,
+        SystemMsg,
+    },
+    DstLocation, WireMsg,
+};
 use sn_interface::types::{keys::ed25519, log_markers::LogMarker, Peer};
 
 use ed25519_dalek::Verifier;
@@ -61,4 +67,50 @@ impl Node {
         self.send_direct_msg(peer, response, self.network_knowledge.section_key().await)
             .await
     }
+
+    // Checks the peer attested it listens at the address it joins from, with the challenge we
+    // dialled it back with there.
+    pub(crate) async fn validate_reachability_attestation(
+        &self,
+        peer: &Peer,
+        attestation: Option<&ReachabilityAttestation>,
+    ) -> std::result::Result<(), AddrAttestationFailure> {
+        let attestation = attestation.ok_or(AddrAttestationFailure::Missing)?;
+        attestation.verify(&peer.name(), peer.addr())?;
+
+        let serialized = bincode::serialize(&(peer.name(), attestation.addr, &attestation.nonce))
+            .map_err(|_| AddrAttestationFailure::InvalidChallenge)?;
+        self.info
+            .read()
+            .await
+            .keypair
+            .public
+            .verify(&serialized, &attestation.nonce_signature)
+            .map_err(|_| AddrAttestationFailure::InvalidChallenge)
+    }
+
+    // The challenge to dial the peer back with at the address it joins from, for it to attest
+    // it listens there.
+    pub(crate) async fn addr_challenge(&self, peer: &Peer) -> Result<WireMsg> {
+        let nonce: [u8; 32] = rand::random();
+        let serialized = bincode::serialize(&(peer.name(), peer.addr(), &nonce))
+            .map_err(|_| Error::InvalidMessage)?;
+        let our_node = self.info.read().await.clone();
+        let node_msg = SystemMsg::JoinResponse(Box::new(JoinResponse::AddrChallenge {
+            addr: peer.addr(),
+            nonce,
+            nonce_signature: ed25519::sign(&serialized, &our_node.keypair),
+        }));
+        let section_key = self.network_knowledge.section_key().await;
+
+        trace!("{}", LogMarker::SendAddrChallenge);
+        self.signing_pool
+            .single_src(
+                &our_node,
+                DstLocation::section(peer.name(), section_key),
+                node_msg,
+                section_key,
+            )
+            .await
+    }
 }
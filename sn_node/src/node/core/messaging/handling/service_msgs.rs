@@ -6,27 +6,34 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::node::{api::cmds::Cmd, core::Node, Error, Result};
-use sn_interface::data_copy_count;
+use crate::node::{
+    api::cmds::Cmd,
+    core::{CmdOrder, DelegationOutcome, Node, PaymentClaim},
+    Error, Result,
+};
 use sn_interface::messaging::{
     data::{
-        CmdError, DataCmd, DataQuery, EditRegister, Error as ErrorMsg, ServiceMsg,
-        SignedRegisterEdit, SpentbookCmd,
+        store_cost, CmdAckLevel, CmdError, DataCmd, DataPayment, DataQuery, EditRegister,
+        Error as ErrorMsg, ServiceMsg, SignedRegisterEdit, SpentbookCmd, StorageReceiptContent,
+        FAST_ACK_MAX_CHUNK_SIZE,
     },
-    system::{NodeQueryResponse, SystemMsg},
+    system::{NodeCmd, NodeQueryResponse, SystemMsg},
     AuthorityProof, DstLocation, EndUser, MsgId, ServiceAuth, WireMsg,
 };
 use sn_interface::types::{
     log_markers::LogMarker,
     register::{PublicPermissions, PublicPolicy, Register, User},
-    Keypair, Peer, PublicKey, RegisterCmd, ReplicatedData, SPENTBOOK_TYPE_TAG,
+    Keypair, Peer, PublicKey, RegisterCmd, ReplicatedData, Token, SPENTBOOK_TYPE_TAG,
 };
 
 use bytes::Bytes;
 use sn_dbc::{
     Hash, IndexedSignatureShare, KeyImage, RingCtTransaction, SpentProofContent, SpentProofShare,
 };
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::atomic::Ordering,
+};
 use xor_name::XorName;
 
 impl Node {
@@ -55,10 +62,7 @@ impl Node {
 
         // Setup node authority on this response and send this back to our elders
         let section_pk = self.network_knowledge().section_key().await;
-        let dst = DstLocation::Node {
-            name: requesting_elder,
-            section_pk,
-        };
+        let dst = DstLocation::node(requesting_elder, section_pk);
 
         cmds.push(Cmd::SignOutgoingSystemMsg { msg, dst });
 
@@ -122,22 +126,36 @@ impl Node {
                     .await
                     .unwrap_or(false))
         {
-            // lets requeue waiting peers in case another adult has the data...
-            // if no more responses come in this query should eventually time out
-            // TODO: What happens if we keep getting queries / client for some data that's always not found?
-            // We need to handle that
-            let _prev = self
-                .pending_data_queries
-                .set(op_id, waiting_peers.clone(), None)
-                .await;
             trace!(
                 "Node {:?}, reported data not found {:?}",
                 sending_node_pk,
                 op_id
             );
-            return Ok(cmds);
+            match self.query_delegation.failed(op_id, node_id).await {
+                // all the holders the query was delegated to answered without the data,
+                // so we relay the response rather than have the client wait in vain
+                DelegationOutcome::Exhausted => {}
+                outcome => {
+                    // lets requeue waiting peers in case another adult has the data...
+                    // if no more responses come in this query should eventually time out
+                    // TODO: What happens if we keep getting queries / client for some data that's always not found?
+                    // We need to handle that
+                    let _prev = self
+                        .pending_data_queries
+                        .set(op_id, waiting_peers.clone(), None)
+                        .await;
+                    if let DelegationOutcome::FallBack(msg, adults) = outcome {
+                        cmds.extend(self.forward_query_to_adults(op_id, msg, adults).await?);
+                    }
+                    return Ok(cmds);
+                }
+            }
+        } else {
+            self.query_delegation.answered(op_id, node_id).await;
         }
 
+        self.query_cache.insert(op_id, query_response.clone()).await;
+
         let msg = ServiceMsg::QueryResponse {
             response: query_response,
             correlation_id,
@@ -145,7 +163,7 @@ impl Node {
         let (msg_kind, payload) = self.ed_sign_client_msg(&msg).await?;
 
         for peer in waiting_peers.iter() {
-            let dst = DstLocation::EndUser(EndUser(peer.name()));
+            let dst = DstLocation::end_user(peer.name());
             let wire_msg = WireMsg::new_msg(msg_id, payload.clone(), msg_kind.clone(), dst)?;
 
             debug!("Responding with the first query response to {:?}", dst);
@@ -167,11 +185,68 @@ impl Node {
         auth: AuthorityProof<ServiceAuth>,
         origin: Peer,
     ) -> Result<Vec<Cmd>> {
+        let mut ack_level = CmdAckLevel::default();
+        // the place of an ordered cmd in its stream, only claimed once the cmd is accepted
+        let mut ordered = None;
+        if let ServiceMsg::Cmd {
+            cmd,
+            ack_level: level,
+            seq,
+        } = &msg
+        {
+            ack_level = *level;
+            if let Err(backoff) = self.client_rate_limiter.try_acquire(auth.public_key).await {
+                warn!(
+                    "Rate limiting cmd {:?} from {:?}, backing off for {:?}",
+                    msg_id, auth.public_key, backoff
+                );
+                let error = CmdError::Data(ErrorMsg::TryAgainLater { backoff });
+                return self.send_cmd_error_response(error, origin, msg_id).await;
+            }
+
+            if let Some(seq) = *seq {
+                let dst = cmd.dst_name();
+                let order = self.ordered_cmds.check(auth.public_key, dst, seq).await;
+                if let Some(error) = out_of_order(order, seq) {
+                    warn!(
+                        "Rejecting cmd {:?} #{} from {:?}: {:?}",
+                        msg_id, seq, auth.public_key, error
+                    );
+                    return self.send_cmd_error_response(error, origin, msg_id).await;
+                }
+                ordered = Some((dst, seq));
+            }
+        }
+
+        // the other Elders are told about the payments we accept, for them not to accept them
+        // for other data
+        let mut payment_cmds = vec![];
         // extract the data from the request
         let data = match msg {
             // These reads/writes are for adult nodes...
-            ServiceMsg::Cmd(DataCmd::Register(cmd)) => ReplicatedData::RegisterWrite(cmd),
-            ServiceMsg::Cmd(DataCmd::Spentbook(SpentbookCmd::Spend { key_image, tx })) => {
+            ServiceMsg::Cmd {
+                cmd: DataCmd::Register(RegisterCmd::EditBatch(batch)),
+                ..
+            } => {
+                // the whole batch is rejected if any of its edits is invalid
+                if let Err(error) = batch.validate() {
+                    warn!(
+                        "Rejecting batch of edits of {:?}: {:?}",
+                        batch.address, error
+                    );
+                    let error = CmdError::Data(error);
+                    return self.send_cmd_error_response(error, origin, msg_id).await;
+                }
+                ReplicatedData::RegisterWrite(RegisterCmd::EditBatch(batch))
+            }
+            ServiceMsg::Cmd {
+                cmd: DataCmd::Register(cmd),
+                ..
+            } => ReplicatedData::RegisterWrite(cmd),
+            ServiceMsg::Cmd {
+                cmd: DataCmd::Spentbook(SpentbookCmd::Spend { key_image, tx }),
+                ..
+            } => {
                 // generate and sign spent proof share
                 let spent_proof_share = self.gen_spent_proof_share(&key_image, &tx).await?;
 
@@ -179,7 +254,69 @@ impl Node {
                 let reg_cmd = gen_register_cmd(&key_image, &spent_proof_share)?;
                 ReplicatedData::SpentbookWrite(reg_cmd)
             }
-            ServiceMsg::Cmd(DataCmd::StoreChunk(chunk)) => ReplicatedData::Chunk(chunk),
+            ServiceMsg::Cmd {
+                cmd: DataCmd::StoreChunk(chunk),
+                ..
+            } => {
+                if self.require_chunk_payments {
+                    warn!("Rejecting unpaid store of {:?}", chunk.address());
+                    let error = CmdError::Data(ErrorMsg::PaymentRequired);
+                    return self.send_cmd_error_response(error, origin, msg_id).await;
+                }
+                ReplicatedData::Chunk(chunk)
+            }
+            ServiceMsg::Cmd {
+                cmd: DataCmd::StorePaidChunk { chunk, payment },
+                ..
+            } => {
+                let key_image = match self
+                    .validate_payment(&payment, chunk.name(), chunk.value().len())
+                    .await
+                {
+                    Ok(key_image) => key_image,
+                    Err(error) => {
+                        warn!("Rejecting paid store of {:?}: {:?}", chunk.address(), error);
+                        let error = CmdError::Data(error);
+                        return self.send_cmd_error_response(error, origin, msg_id).await;
+                    }
+                };
+                match self.spent_payments.claim(key_image, *chunk.name()).await? {
+                    PaymentClaim::Claimed => {
+                        let payments = BTreeMap::from([(key_image, *chunk.name())]);
+                        let msg = SystemMsg::NodeCmd(NodeCmd::RecordSpentPayments { payments });
+                        payment_cmds.push(self.send_msg_to_our_elders(msg).await?);
+                    }
+                    PaymentClaim::AlreadyClaimed => {}
+                    PaymentClaim::UsedForOtherData => {
+                        warn!(
+                            "Rejecting paid store of {:?}, paid with a DBC already used",
+                            chunk.address()
+                        );
+                        let error = CmdError::Data(ErrorMsg::PaymentAlreadyUsed);
+                        return self.send_cmd_error_response(error, origin, msg_id).await;
+                    }
+                }
+                ReplicatedData::Chunk(chunk)
+            }
+            ServiceMsg::Query(DataQuery::ReportMissingChunk(address)) => {
+                return self.repair_missing_chunk(address, msg_id, origin).await;
+            }
+            ServiceMsg::Query(DataQuery::GetSectionStats(prefix)) => {
+                return self.send_section_stats(prefix, msg_id, origin).await;
+            }
+            ServiceMsg::Query(DataQuery::GetSectionKeyHistory { prefix, since }) => {
+                return self
+                    .send_section_key_history(prefix, since, msg_id, origin)
+                    .await;
+            }
+            ServiceMsg::Query(DataQuery::GetSectionMembers {
+                prefix,
+                continuation,
+            }) => {
+                return self
+                    .send_section_members(prefix, continuation, msg_id, origin)
+                    .await;
+            }
             ServiceMsg::Query(query) => {
                 return self
                     .read_data_from_adults(query, msg_id, auth, origin)
@@ -193,19 +330,68 @@ impl Node {
                 return Ok(vec![]);
             }
         };
+        // small chunks can be acknowledged ahead of the Adults being instructed to store them
+        let fast_ack = match &data {
+            ReplicatedData::Chunk(chunk) => {
+                ack_level == CmdAckLevel::FastAck && chunk.value().len() <= FAST_ACK_MAX_CHUNK_SIZE
+            }
+            _ => false,
+        };
+        // chunks accepted are acknowledged with a share of their storage receipt,
+        // while the responses cached for the mutable data written to are now stale
+        let receipt = match &data {
+            ReplicatedData::Chunk(chunk) if fast_ack => {
+                Some(StorageReceiptContent::elder_validated(*chunk.name()))
+            }
+            ReplicatedData::Chunk(chunk) => Some(StorageReceiptContent::new(*chunk.name())),
+            _ => {
+                self.query_cache.invalidate(data.name()).await;
+                None
+            }
+        };
         // build the replication cmds
-        let mut cmds = self.replicate_data(data).await?;
+        let mut cmds = self
+            .replicate_client_data(data, ack_level, msg_id, origin)
+            .await?;
         // make sure the expected replication factor is achieved
-        if data_copy_count() > cmds.len() {
+        let data_copy_count = self
+            .network_knowledge()
+            .authority_provider()
+            .await
+            .data_copy_count();
+        if data_copy_count > cmds.len() {
             error!("InsufficientAdults for storing data reliably");
             let error = CmdError::Data(ErrorMsg::InsufficientAdults {
                 prefix: self.network_knowledge().prefix().await,
-                expected: data_copy_count() as u8,
+                expected: data_copy_count as u8,
                 found: cmds.len() as u8,
             });
             return self.send_cmd_error_response(error, origin, msg_id).await;
         }
-        cmds.extend(self.send_cmd_ack(origin, msg_id).await?);
+        // the cmd is accepted, unless an ordered cmd coming after it was accepted meanwhile
+        if let Some((dst, seq)) = ordered {
+            let order = self.ordered_cmds.claim(auth.public_key, dst, seq).await;
+            if let Some(error) = out_of_order(order, seq) {
+                warn!(
+                    "Rejecting cmd {:?} #{} from {:?}: {:?}",
+                    msg_id, seq, auth.public_key, error
+                );
+                return self.send_cmd_error_response(error, origin, msg_id).await;
+            }
+        }
+        cmds.extend(payment_cmds);
+        if receipt.is_some() {
+            let _ = self.approx_stored_chunks.fetch_add(1, Ordering::Relaxed);
+        }
+        if fast_ack {
+            // the replication carries on in the background once the client is acknowledged
+            return self
+                .send_cmd_ack_ahead_of(origin, msg_id, receipt, cmds)
+                .await;
+        }
+        if ack_level != CmdAckLevel::None {
+            cmds.extend(self.send_cmd_ack(origin, msg_id, receipt).await?);
+        }
         Ok(cmds)
     }
 
@@ -236,6 +422,80 @@ impl Node {
             .await
     }
 
+    // Private helper to validate the payment attached to a cmd storing `num_bytes` of data at
+    // the given name, returning the name of the key image of the payment DBC.
+    // The payment must have been made for that data, the inputs of the payment DBC must have been
+    // logged as spent in the spentbook of a section we know of, and its amount must cover the cost
+    // of storing the data. Whether it already paid for other data is up to the caller to check.
+    async fn validate_payment(
+        &self,
+        payment: &DataPayment,
+        name: &XorName,
+        num_bytes: usize,
+    ) -> std::result::Result<XorName, ErrorMsg> {
+        if !payment.is_bound_to(name, num_bytes) {
+            return Err(ErrorMsg::InvalidPayment(
+                "The payment wasn't made for storing this data".to_string(),
+            ));
+        }
+
+        let key_image = payment.dbc.key_image_bearer().map_err(|err| {
+            ErrorMsg::InvalidPayment(format!(
+                "Couldn't get the key image of the payment DBC: {:?}",
+                err
+            ))
+        })?;
+
+        let paid = payment
+            .dbc
+            .amount_secrets_bearer()
+            .map(|amount_secrets| Token::from_nano(amount_secrets.amount()))
+            .map_err(|err| {
+                ErrorMsg::InvalidPayment(format!(
+                    "Couldn't reveal the amount of the payment DBC: {:?}",
+                    err
+                ))
+            })?;
+        let required = store_cost(num_bytes);
+        if paid < required {
+            return Err(ErrorMsg::InsufficientPayment { paid, required });
+        }
+
+        if payment.dbc.spent_proofs.is_empty() {
+            return Err(ErrorMsg::InvalidPayment(
+                "The payment DBC carries no spent proofs for its inputs".to_string(),
+            ));
+        }
+
+        let tx_hash = Hash::from(payment.dbc.transaction.hash());
+        let known_keys = self.network_knowledge.prefix_map().section_keys();
+        for spent_proof in &payment.dbc.spent_proofs {
+            if spent_proof.content.transaction_hash != tx_hash {
+                return Err(ErrorMsg::InvalidPayment(
+                    "A spent proof of the payment DBC is for another transaction".to_string(),
+                ));
+            }
+
+            let spentbook_pk = &spent_proof.spentbook_pub_key;
+            if !known_keys.contains(spentbook_pk)
+                && !self.network_knowledge.has_chain_key(spentbook_pk).await
+            {
+                return Err(ErrorMsg::InvalidPayment(format!(
+                    "A spent proof of the payment DBC was signed by an unknown spentbook: {:?}",
+                    spentbook_pk
+                )));
+            }
+
+            if !spentbook_pk.verify(&spent_proof.spentbook_sig, spent_proof.content.hash()) {
+                return Err(ErrorMsg::InvalidPayment(
+                    "A spent proof of the payment DBC has an invalid signature".to_string(),
+                ));
+            }
+        }
+
+        Ok(XorName::from_content(&key_image.to_bytes()))
+    }
+
     // Private helper to generate spent proof share
     async fn gen_spent_proof_share(
         &self,
@@ -301,7 +561,9 @@ fn gen_register_cmd(
         ))
     })?);
 
-    let (_, op) = register.write(entry.to_vec(), BTreeSet::default())?;
+    let (_, mut op) = register.write(entry.to_vec(), BTreeSet::default())?;
+    // anyone can write to the spentbook, so its entries are to be signed by their writer
+    op.signature = Some(keypair.sign(&bincode::serialize(&op.crdt_op)?));
     let op = EditRegister {
         address: *register.address(),
         edit: op,
@@ -318,3 +580,14 @@ fn gen_register_cmd(
 
     Ok(RegisterCmd::Edit(signed_edit))
 }
+
+// The error an ordered cmd is rejected with, if a later cmd of its stream was applied already.
+// The last cmd applied, sent again, is applied again, which is harmless.
+fn out_of_order(order: CmdOrder, seq: u64) -> Option<CmdError> {
+    match order {
+        CmdOrder::Next | CmdOrder::Applied => None,
+        CmdOrder::Stale { applied } => {
+            Some(CmdError::Data(ErrorMsg::CmdOutOfOrder { seq, applied }))
+        }
+    }
+}
@@ -8,7 +8,6 @@
 
 use crate::node::{api::cmds::Cmd, core::Node, Result};
 use itertools::Itertools;
-use sn_interface::data_copy_count;
 use sn_interface::types::{log_markers::LogMarker, Peer};
 use sn_interface::{
     messaging::{
@@ -17,7 +16,7 @@ use sn_interface::{
     },
     types::ReplicatedDataAddress,
 };
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 impl Node {
     /// Given what data the peer has, we shall calculate what data the peer is missing that
@@ -42,6 +41,11 @@ impl Node {
 
         let adults = self.network_knowledge.adults().await;
         let adults_names = adults.iter().map(|p2p_node| p2p_node.name());
+        let data_copy_count = self
+            .network_knowledge
+            .authority_provider()
+            .await
+            .data_copy_count();
 
         let mut data_for_sender = vec![];
         for data in data_i_have {
@@ -52,7 +56,7 @@ impl Node {
             let holder_adult_list: BTreeSet<_> = adults_names
                 .clone()
                 .sorted_by(|lhs, rhs| data.name().cmp_distance(lhs, rhs))
-                .take(data_copy_count())
+                .take(data_copy_count)
                 .collect();
 
             if holder_adult_list.contains(&sender.name()) {
@@ -80,6 +84,68 @@ impl Node {
         Ok(vec![cmd])
     }
 
+    /// Send any of the given data we hold to the other Adults meant to hold it,
+    /// so data reported as missing is brought back to its replication factor.
+    #[instrument(skip(self, data_addresses))]
+    pub(crate) async fn repair_data_held(
+        &self,
+        data_addresses: Vec<ReplicatedDataAddress>,
+    ) -> Result<Vec<Cmd>> {
+        if self.is_elder().await {
+            trace!("Ignoring data repair request while Elder");
+            return Ok(vec![]);
+        }
+
+        let data_i_have = self.data_storage.keys().await?;
+        let data_to_repair = data_addresses
+            .into_iter()
+            .filter(|data| data_i_have.contains(data))
+            .collect_vec();
+
+        if data_to_repair.is_empty() {
+            trace!("We hold none of the data to repair");
+            return Ok(vec![]);
+        }
+
+        let adults = self.network_knowledge.adults().await;
+        let my_name = self.info.read().await.name();
+        let data_copy_count = self
+            .network_knowledge
+            .authority_provider()
+            .await
+            .data_copy_count();
+
+        let mut batches: BTreeMap<Peer, Vec<ReplicatedDataAddress>> = BTreeMap::new();
+        for data in data_to_repair {
+            let holders = adults
+                .iter()
+                .sorted_by(|lhs, rhs| data.name().cmp_distance(&lhs.name(), &rhs.name()))
+                .take(data_copy_count)
+                .filter(|peer| peer.name() != my_name);
+
+            for holder in holders {
+                batches.entry(*holder).or_default().push(data);
+            }
+        }
+
+        let cmds = batches
+            .into_iter()
+            .map(|(recipient, data_batch)| {
+                debug!(
+                    "{:?} batch to: {:?} ",
+                    LogMarker::QueuingMissingReplicatedData,
+                    recipient
+                );
+                Cmd::EnqueueDataForReplication {
+                    recipient,
+                    data_batch,
+                }
+            })
+            .collect();
+
+        Ok(cmds)
+    }
+
     /// Will send a list of currently known/owned data to relevant nodes.
     /// These nodes should send back anything missing (in batches).
     /// Relevant nodes should be all _prior_ neighbours + _new_ elders.
@@ -94,13 +160,18 @@ impl Node {
 
         let elders = self.network_knowledge.elders().await;
         let my_name = self.info.read().await.name();
+        let data_copy_count = self
+            .network_knowledge
+            .authority_provider()
+            .await
+            .data_copy_count();
 
         // find data targets that are not us.
         let mut target_member_names = adults_names
             .into_iter()
             .sorted_by(|lhs, rhs| my_name.cmp_distance(lhs, rhs))
             .filter(|peer| peer != &my_name)
-            .take(data_copy_count())
+            .take(data_copy_count)
             .collect::<BTreeSet<_>>();
 
         trace!(
@@ -122,7 +193,7 @@ impl Node {
                 msg: SystemMsg::NodeCmd(
                     NodeCmd::SendAnyMissingRelevantData(data_i_have.clone()).clone(),
                 ),
-                dst: DstLocation::Node { name, section_pk },
+                dst: DstLocation::node(name, section_pk),
             })
         }
 
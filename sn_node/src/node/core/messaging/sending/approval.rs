@@ -27,6 +27,7 @@ impl Node {
                 .into_authed_msg(),
             node_state: node_state.into_authed_msg(),
             section_chain: self.network_knowledge.section_chain().await,
+            activated_features: self.feature_activations().await,
         }));
 
         let dst_section_pk = self.network_knowledge.section_key().await;
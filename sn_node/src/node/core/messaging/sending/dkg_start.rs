@@ -35,10 +35,7 @@ impl Node {
         let section_pk = self.network_knowledge.section_key().await;
         self.send_msg_for_dst_accumulation(
             prefix.name(),
-            DstLocation::Section {
-                name: prefix.name(),
-                section_pk,
-            },
+            DstLocation::section(prefix.name(), section_pk),
             node_msg,
             recipients,
         )
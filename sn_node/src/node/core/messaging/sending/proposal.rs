@@ -9,12 +9,11 @@
 use crate::node::{
     api::cmds::Cmd,
     core::{Node, Proposal},
-    messages::WireMsgUtils,
     Result,
 };
-use sn_interface::messaging::{system::SystemMsg, DstLocation, WireMsg};
+use sn_interface::messaging::{system::SystemMsg, DstLocation};
 use sn_interface::network_knowledge::SectionKeyShare;
-use sn_interface::types::Peer;
+use sn_interface::types::{KeyBundle, Peer};
 
 impl Node {
     /// Send proposal to all our elders.
@@ -67,24 +66,35 @@ impl Node {
             key_share.index,
             &key_share.secret_key_share,
         )?;
+        // proposing our new SAP, we prove the key share we signed it with is ours
+        let key_bundle = if let Proposal::SectionInfo { .. } = proposal {
+            Some(KeyBundle::new(
+                &self.info.read().await.keypair,
+                key_share.index,
+                &key_share.secret_key_share,
+            ))
+        } else {
+            None
+        };
 
         // Broadcast the proposal to the rest of the section elders.
         let node_msg = SystemMsg::Propose {
             proposal: proposal.clone().into_msg(),
             sig_share: sig_share.clone(),
+            key_bundle: key_bundle.clone(),
         };
         // Name of the section_pk may not matches the section prefix.
         // Carry out a substitution to prevent the dst_location becomes other section.
         let section_key = self.network_knowledge.section_key().await;
-        let wire_msg = WireMsg::single_src(
-            &self.info.read().await.clone(),
-            DstLocation::Section {
-                name: self.network_knowledge.prefix().await.name(),
-                section_pk: section_key,
-            },
-            node_msg,
-            section_key,
-        )?;
+        let wire_msg = self
+            .signing_pool
+            .single_src(
+                &self.info.read().await.clone(),
+                DstLocation::section(self.network_knowledge.prefix().await.name(), section_key),
+                node_msg,
+                section_key,
+            )
+            .await?;
 
         let msg_id = wire_msg.msg_id();
 
@@ -98,6 +108,7 @@ impl Node {
                         msg_id,
                         proposal.clone(),
                         sig_share.clone(),
+                        key_bundle.clone(),
                         peer,
                         &self.network_knowledge,
                         &self.proposal_aggregator,
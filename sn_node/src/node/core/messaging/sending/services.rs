@@ -8,8 +8,9 @@
 
 use crate::node::{api::cmds::Cmd, core::Node, Result};
 use sn_interface::messaging::{
-    data::{CmdError, ServiceMsg},
-    AuthKind, DstLocation, EndUser, MsgId, ServiceAuth, WireMsg,
+    data::{CmdError, ServiceMsg, StorageReceiptContent, StorageReceiptShare},
+    system::SigShare,
+    AuthKind, DstLocation, MsgId, ServiceAuth, SigningDomain, WireMsg,
 };
 use sn_interface::types::{Peer, PublicKey, Signature};
 
@@ -31,20 +32,97 @@ impl Node {
         self.send_cmd_response(target, the_error_msg).await
     }
 
-    /// Forms a CmdAck msg to send back to the client
-    pub(crate) async fn send_cmd_ack(&self, target: Peer, msg_id: MsgId) -> Result<Vec<Cmd>> {
-        let the_ack_msg = ServiceMsg::CmdAck {
+    /// Forms a CmdAck msg to send back to the client.
+    /// When a chunk was stored, our share of its storage receipt is attached to it.
+    pub(crate) async fn send_cmd_ack(
+        &self,
+        target: Peer,
+        msg_id: MsgId,
+        receipt: Option<StorageReceiptContent>,
+    ) -> Result<Vec<Cmd>> {
+        let the_ack_msg = self.cmd_ack_msg(msg_id, receipt).await?;
+        self.send_cmd_response(target, the_ack_msg).await
+    }
+
+    /// Forms a CmdAck msg to send back to the client, as `send_cmd_ack` does, but ahead of
+    /// the given cmds, which are only processed once it's sent.
+    pub(crate) async fn send_cmd_ack_ahead_of(
+        &self,
+        target: Peer,
+        msg_id: MsgId,
+        receipt: Option<StorageReceiptContent>,
+        then: Vec<Cmd>,
+    ) -> Result<Vec<Cmd>> {
+        let the_ack_msg = self.cmd_ack_msg(msg_id, receipt).await?;
+        let wire_msg = self.cmd_response_wire_msg(target, the_ack_msg).await?;
+
+        Ok(vec![Cmd::SendMsgThen {
+            recipients: vec![target],
+            wire_msg,
+            then,
+        }])
+    }
+
+    async fn cmd_ack_msg(
+        &self,
+        msg_id: MsgId,
+        receipt: Option<StorageReceiptContent>,
+    ) -> Result<ServiceMsg> {
+        let receipt_share = match receipt {
+            Some(content) => Some(self.sign_storage_receipt(content).await?),
+            None => None,
+        };
+        Ok(ServiceMsg::CmdAck {
+            correlation_id: msg_id,
+            receipt_share,
+        })
+    }
+
+    /// Forms a CmdReplicated msg to send back to the client, once the Adults confirmed storing
+    /// the data written by its cmd.
+    pub(crate) async fn send_cmd_replicated(
+        &self,
+        target: Peer,
+        msg_id: MsgId,
+    ) -> Result<Vec<Cmd>> {
+        let msg = ServiceMsg::CmdReplicated {
             correlation_id: msg_id,
         };
-        self.send_cmd_response(target, the_ack_msg).await
+        self.send_cmd_response(target, msg).await
+    }
+
+    // Signs our share of the receipt for a chunk accepted by our section, over the current
+    // window and the one before
+    async fn sign_storage_receipt(
+        &self,
+        content: StorageReceiptContent,
+    ) -> Result<StorageReceiptShare> {
+        Ok(StorageReceiptShare {
+            content,
+            sig_share: self.sign_receipt_content(&content).await?,
+            previous_window_sig_share: self
+                .sign_receipt_content(&content.previous_window())
+                .await?,
+        })
+    }
+
+    async fn sign_receipt_content(&self, content: &StorageReceiptContent) -> Result<SigShare> {
+        let sap = self.network_knowledge.authority_provider().await;
+        let (index, signature_share) = self
+            .section_keys_provider
+            .sign_with(&content.signable_bytes(), &sap.section_key())
+            .await?;
+
+        Ok(SigShare {
+            public_key_set: sap.public_key_set(),
+            index,
+            signature_share,
+        })
     }
 
     /// Forms a cmd to send a cmd response error/ack to the client
     async fn send_cmd_response(&self, target: Peer, msg: ServiceMsg) -> Result<Vec<Cmd>> {
-        let dst = DstLocation::EndUser(EndUser(target.name()));
-
-        let (msg_kind, payload) = self.ed_sign_client_msg(&msg).await?;
-        let wire_msg = WireMsg::new_msg(MsgId::new(), payload, msg_kind, dst)?;
+        let wire_msg = self.cmd_response_wire_msg(target, msg).await?;
 
         let cmd = Cmd::SendMsg {
             recipients: vec![target],
@@ -54,6 +132,13 @@ impl Node {
         Ok(vec![cmd])
     }
 
+    async fn cmd_response_wire_msg(&self, target: Peer, msg: ServiceMsg) -> Result<WireMsg> {
+        let dst = DstLocation::end_user(target.name());
+
+        let (msg_kind, payload) = self.ed_sign_client_msg(&msg).await?;
+        Ok(WireMsg::new_msg(MsgId::new(), payload, msg_kind, dst)?)
+    }
+
     /// Currently using node's Ed key. May need to use bls key share for concensus purpose.
     pub(crate) async fn ed_sign_client_msg(
         &self,
@@ -61,7 +146,7 @@ impl Node {
     ) -> Result<(AuthKind, Bytes)> {
         let keypair = self.info.read().await.keypair.clone();
         let payload = WireMsg::serialize_msg_payload(client_msg)?;
-        let signature = keypair.sign(&payload);
+        let signature = keypair.sign(&SigningDomain::Service.signable_bytes(&payload));
 
         let msg = AuthKind::Service(ServiceAuth {
             public_key: PublicKey::Ed25519(keypair.public),
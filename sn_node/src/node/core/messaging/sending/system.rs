@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::node::{api::cmds::Cmd, core::Node, messages::WireMsgUtils, Error, Result};
+use crate::node::{api::cmds::Cmd, core::Node, Error, Result};
 use sn_interface::messaging::{
     system::{SectionAuth, SystemMsg},
     AuthKind, DstLocation, WireMsg,
@@ -42,15 +42,15 @@ impl Node {
         let our_node = self.info.read().await.clone();
         let our_section_key = self.network_knowledge.section_key().await;
 
-        let wire_msg = WireMsg::single_src(
-            &our_node,
-            DstLocation::Section {
-                name: section_name,
-                section_pk,
-            },
-            node_msg,
-            our_section_key,
-        )?;
+        let wire_msg = self
+            .signing_pool
+            .single_src(
+                &our_node,
+                DstLocation::section(section_name, section_pk),
+                node_msg,
+                our_section_key,
+            )
+            .await?;
 
         Ok(Cmd::SendMsg {
             recipients,
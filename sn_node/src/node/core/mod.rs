@@ -12,20 +12,35 @@ mod comm;
 mod connectivity;
 mod data;
 mod delivery_group;
+mod features;
 mod messaging;
 mod proposal;
+mod recovery;
 mod relocation;
+mod signing;
 mod split_barrier;
 
 /// DataStorage apis.
-pub use self::data::DataStorage;
+pub use self::data::{
+    AdultParticipation, ClientRateLimits, DataStorage, QueryCacheStats, QueryDelegationConfig,
+    ScrubProgress,
+};
 use self::split_barrier::SplitBarrier;
-pub(crate) use bootstrap::{join_network, JoiningAsRelocated};
-pub(crate) use comm::{Comm, DeliveryStatus, MsgEvent};
-pub(crate) use data::MIN_LEVEL_WHEN_FULL;
+pub(crate) use bootstrap::{check_reachability, join_network, JoiningAsRelocated};
+pub(crate) use comm::{
+    answer_lan_peers, bind_lan_beacon, discover_lan_peers, Comm, DeliveryStatus, Liveness,
+    MsgEvent, PeerTag,
+};
+pub use comm::{
+    InboundRateLimits, LivenessConfig, RoutingTable, RoutingTableEntry, DEFAULT_LAN_DISCOVERY_PORT,
+};
+pub(crate) use data::{CmdOrder, DelegationOutcome, PaymentClaim, MIN_LEVEL_WHEN_FULL};
+pub(crate) use features::our_version;
 pub(crate) use proposal::Proposal;
-#[cfg(test)]
-pub(crate) use relocation::{check as relocation_check, ChurnId};
+pub use recovery::RecoveryReport;
+pub(crate) use recovery::RunningMarker;
+pub(crate) use signing::SigningPool;
+pub use signing::SigningStats;
 use sn_interface::{
     network_knowledge::{
         supermajority, NetworkKnowledge, NodeInfo, SectionKeyShare, SectionKeysProvider,
@@ -46,27 +61,34 @@ use crate::node::{
 use sn_interface::messaging::{
     data::OperationId,
     signature_aggregator::SignatureAggregator,
-    system::{DkgSessionId, NodeState, SystemMsg},
+    system::{
+        DkgSessionId, Feature, FeatureActivation, HandshakeAssembler, NodeState,
+        SectionAuth as SystemSectionAuth, SystemMsg,
+    },
     AuthorityProof, SectionAuth, SectionAuthorityProvider,
 };
 use sn_interface::types::{log_markers::LogMarker, Cache, Peer};
 
-use crate::UsedSpace;
+use crate::{ChunkStoreConfig, UsedSpace};
 use sn_interface::network_knowledge::utils::compare_and_write_prefix_map_to_disk;
 
 use backoff::ExponentialBackoff;
 use dashmap::DashSet;
-use data::Capacity;
+use data::{
+    Capacity, ClientRateLimiter, OrderedCmds, PendingReplications, QueryCache, QueryDelegation,
+    SpentPayments,
+};
 use itertools::Itertools;
 use resource_proof::ResourceProof;
-use sn_dysfunction::{DysfunctionDetection, DysfunctionSeverity, IssueType};
+use sn_dysfunction::{DysfunctionDetection, DysfunctionSeverity, IssueType, PolicyAction, Signal};
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
+    net::IpAddr,
     path::PathBuf,
-    sync::Arc,
+    sync::{atomic::AtomicU64, Arc},
     time::Duration,
 };
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::{mpsc, Mutex, RwLock};
 use uluru::LRUCache;
 use xor_name::{Prefix, XorName};
 
@@ -85,6 +107,13 @@ const BACKOFF_CACHE_LIMIT: usize = 100;
 // the section).
 const DATA_QUERY_TIMEOUT: Duration = Duration::from_secs(15);
 
+// How long a relocation may take before it's started over, unless configured otherwise
+const DEFAULT_RELOCATION_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Number of times a relocation is started before it's given up on, for the node to rejoin the
+/// network.
+pub(crate) const MAX_RELOCATION_ATTEMPTS: u32 = 3;
+
 // This prevents pending query limit unbound growth
 pub(crate) const DATA_QUERY_LIMIT: usize = 100;
 // per query we can have this many peers, so the total peers waiting can be QUERY_LIMIT * MAX_WAITING_PEERS_PER_QUERY
@@ -108,6 +137,11 @@ pub(crate) struct Node {
     pub(crate) comm: Comm,
 
     pub(super) data_storage: DataStorage, // Adult only before cache
+    // Where the node keeps its files, e.g. its bootstrap cache
+    root_storage_dir: PathBuf,
+    // Names of the peers in our bootstrap cache, by the member of our section which shared them.
+    // Locked while the cache is written.
+    bootstrap_peers_shared: Arc<Mutex<BTreeMap<XorName, BTreeSet<XorName>>>>,
 
     resource_proof: ResourceProof,
     // Network resources
@@ -121,17 +155,45 @@ pub(crate) struct Node {
     dkg_sessions: Arc<RwLock<HashMap<Digest256, DkgSessionInfo>>>,
     dkg_voter: DkgVoter,
     relocate_state: Arc<RwLock<Option<Box<JoiningAsRelocated>>>>,
+    // How long a relocation may take before it's abandoned, if at all
+    pub(crate) relocation_timeout: Option<Duration>,
     // ======================== Elder only ========================
     pub(crate) membership: Arc<RwLock<Option<Membership>>>,
     // Section handover consensus state (Some for Elders, None for others)
     pub(crate) handover_voting: Arc<RwLock<Option<Handover>>>,
     joins_allowed: Arc<RwLock<bool>>,
+    // IPs of the peers whose join requests are rejected
+    pub(crate) join_denylist: Arc<RwLock<BTreeSet<IpAddr>>>,
+    // Join requests from peers being received over several frames
+    handshake_assembler: Arc<RwLock<HandshakeAssembler>>,
+    // Number of copies of each data agreed by the Elders, to be applied with the next SAP
+    agreed_data_copy_count: Arc<RwLock<Option<usize>>>,
+    // Features activated in our section, with the generation they take effect at
+    activated_features: Arc<RwLock<BTreeMap<Feature, SystemSectionAuth<FeatureActivation>>>>,
     // Trackers
     capacity: Capacity,
     dysfunction_tracking: DysfunctionDetection,
+    pub(crate) client_rate_limiter: ClientRateLimiter,
+    // Latest ordered cmds applied, by client and data written to
+    pub(crate) ordered_cmds: OrderedCmds,
+    // Payments accepted for storing data, so none of them pays for other data
+    pub(crate) spent_payments: SpentPayments,
+    // Whether chunks are only stored when sent with a payment
+    pub(crate) require_chunk_payments: bool,
+    // Passphrase our network keypair is stored encrypted with, if any
+    pub(crate) identity_passphrase: Option<String>,
     pending_data_queries: Arc<Cache<OperationId, Arc<DashSet<Peer>>>>,
+    // Client cmds awaiting the Adults to confirm storing their data
+    pub(crate) pending_replications: PendingReplications,
+    // Data queries delegated to our Adults, and how each of them took part in answering them
+    pub(crate) query_delegation: QueryDelegation,
+    // Workers signing the msgs we send
+    pub(crate) signing_pool: SigningPool,
     // Caches
     ae_backoff_cache: AeBackoffCache,
+    pub(crate) query_cache: QueryCache,
+    // Number of chunks we had our Adults store while Elder, as reported in our section stats
+    pub(crate) approx_stored_chunks: AtomicU64,
 }
 
 impl Node {
@@ -145,6 +207,9 @@ impl Node {
         event_tx: mpsc::Sender<Event>,
         used_space: UsedSpace,
         root_storage_dir: PathBuf,
+        chunk_store_config: ChunkStoreConfig,
+        client_rate_limits: ClientRateLimits,
+        query_delegation: QueryDelegationConfig,
     ) -> Result<Self> {
         let membership = if let Some(key) = section_key_share.clone() {
             let n_elders = network_knowledge
@@ -176,7 +241,14 @@ impl Node {
         // make sure the Node has the correct local addr as Comm
         info.addr = comm.our_connection_info();
 
-        let data_storage = DataStorage::new(&root_storage_dir, used_space.clone())?;
+        let data_storage = DataStorage::with_chunk_store_config(
+            &root_storage_dir,
+            used_space.clone(),
+            chunk_store_config,
+        )?;
+        // chunk writes acknowledged before the node went down must not be lost
+        let _recovered = data_storage.recover_chunk_writes().await?;
+        let spent_payments = SpentPayments::new(&root_storage_dir).await?;
 
         info!("Creating DysfunctionDetection checks");
         let node_dysfunction_detector = DysfunctionDetection::new(
@@ -215,15 +287,32 @@ impl Node {
             message_aggregator: SignatureAggregator::default(),
             dkg_voter: DkgVoter::default(),
             relocate_state: Arc::new(RwLock::new(None)),
+            relocation_timeout: Some(DEFAULT_RELOCATION_TIMEOUT),
             event_tx,
             handover_voting: Arc::new(RwLock::new(handover)),
             joins_allowed: Arc::new(RwLock::new(true)),
+            join_denylist: Arc::new(RwLock::new(BTreeSet::new())),
+            handshake_assembler: Arc::new(RwLock::new(HandshakeAssembler::default())),
+            agreed_data_copy_count: Arc::new(RwLock::new(None)),
+            activated_features: Arc::new(RwLock::new(BTreeMap::new())),
             resource_proof: ResourceProof::new(RESOURCE_PROOF_DATA_SIZE, RESOURCE_PROOF_DIFFICULTY),
             data_storage,
+            root_storage_dir,
+            bootstrap_peers_shared: Arc::new(Mutex::new(BTreeMap::new())),
             capacity: Capacity::default(),
             dysfunction_tracking: node_dysfunction_detector,
+            client_rate_limiter: ClientRateLimiter::new(client_rate_limits),
+            ordered_cmds: OrderedCmds::default(),
+            spent_payments,
+            require_chunk_payments: false,
+            identity_passphrase: None,
             pending_data_queries: Arc::new(Cache::with_expiry_duration(DATA_QUERY_TIMEOUT)),
+            pending_replications: PendingReplications::default(),
+            query_delegation: QueryDelegation::new(query_delegation),
+            signing_pool: SigningPool::for_node(),
             ae_backoff_cache: AeBackoffCache::default(),
+            query_cache: QueryCache::default(),
+            approx_stored_chunks: AtomicU64::new(0),
             membership: Arc::new(RwLock::new(membership)),
         })
     }
@@ -276,6 +365,25 @@ impl Node {
             .await
     }
 
+    /// Retags our section's Elders and Adults in the comm layer, after a membership change,
+    /// exempting them from the limits on the rate of inbound msgs
+    pub(crate) async fn tag_section_members(&self) {
+        let sap = self.network_knowledge.authority_provider().await;
+        let adults = self.network_knowledge.adults().await;
+        let endpoints = sap
+            .elders()
+            .chain(adults.iter())
+            .map(|peer| peer.addr())
+            .collect();
+        let elders = sap.names();
+        let adults = adults.iter().map(|peer| peer.name()).collect();
+        self.comm
+            .peer_tags()
+            .set_section_members(&elders, &adults)
+            .await;
+        self.comm.set_inbound_rate_exempt(endpoints).await;
+    }
+
     /// Removes any PeerLinks not from our section elders
     pub(crate) async fn cleanup_non_elder_peers(&self) {
         let elders = self.network_knowledge.elders().await;
@@ -290,6 +398,16 @@ impl Node {
             .map_err(Error::from)
     }
 
+    /// returns the action the signal policy calls for against each node whose score crossed one
+    /// of its thresholds. Only Elders act on them.
+    pub(crate) async fn get_dysfunction_policy_actions(&self) -> BTreeMap<XorName, PolicyAction> {
+        if self.is_not_elder().await {
+            return BTreeMap::new();
+        }
+        let elders = self.network_knowledge.authority_provider().await.names();
+        self.dysfunction_tracking.get_policy_actions(&elders).await
+    }
+
     /// Log a communication problem
     pub(crate) async fn log_comm_issue(&self, name: XorName) -> Result<()> {
         self.dysfunction_tracking
@@ -298,6 +416,11 @@ impl Node {
             .map_err(Error::from)
     }
 
+    /// Log a signal of misbehaviour against a node, adding to its dysfunction score
+    pub(crate) async fn log_signal(&self, name: XorName, signal: Signal) {
+        self.dysfunction_tracking.track_signal(name, signal).await
+    }
+
     pub(crate) async fn write_prefix_map(&self) {
         info!("Writing our latest PrefixMap to disk");
         // TODO: Make this serialization human readable
@@ -310,6 +433,8 @@ impl Node {
                 error!("Error writing PrefixMap to `~/.safe` dir: {:?}", e);
             }
         });
+
+        self.write_section_state().await;
     }
 
     pub(super) async fn state_snapshot(&self) -> StateSnapshot {
@@ -348,10 +473,12 @@ impl Node {
                 return vec![];
             };
 
+        let data_copy_count = self.next_data_copy_count().await;
+
         // Try splitting
         trace!("{}", LogMarker::SplitAttempt);
         if let Some((zero_dkg_id, one_dkg_id)) =
-            try_split_dkg(&members, &sap, chain_len, current_gen)
+            try_split_dkg(&members, &sap, chain_len, current_gen, data_copy_count)
         {
             debug!(
                 "Upon section split attempt, section size: zero {:?}, one {:?}",
@@ -385,6 +512,7 @@ impl Node {
             .iter()
             .map(NodeState::peer)
             .eq(current_elders.iter())
+            && data_copy_count == sap.data_copy_count()
         {
             vec![]
         } else if elder_candidates.len() < supermajority(current_elders.len()) {
@@ -412,11 +540,25 @@ impl Node {
                 section_chain_len: chain_len,
                 bootstrap_members: BTreeSet::from_iter(members.into_values()),
                 membership_gen: current_gen,
+                data_copy_count,
             };
             vec![session_id]
         }
     }
 
+    /// Number of copies of each data the next SAP of our section is to have, i.e. the last one
+    /// agreed by the Elders, if any, or else the one of our current SAP.
+    pub(super) async fn next_data_copy_count(&self) -> usize {
+        if let Some(count) = *self.agreed_data_copy_count.read().await {
+            count
+        } else {
+            self.network_knowledge
+                .authority_provider()
+                .await
+                .data_copy_count()
+        }
+    }
+
     async fn initialize_membership(&self, sap: SectionAuthorityProvider) -> Result<()> {
         let key = self
             .section_keys_provider
@@ -479,6 +621,7 @@ impl Node {
     ) -> Result<Vec<Cmd>> {
         let mut cmds = vec![];
         let new = self.state_snapshot().await;
+        self.tag_section_members().await;
 
         if new.section_key != old.section_key {
             if new.is_elder {
@@ -571,6 +714,8 @@ impl Node {
                 }
 
                 cmds.extend(self.send_updates_to_sibling_section(&old).await?);
+                // the payments for the data of our sibling are its to keep track of from now on
+                self.spent_payments.retain_matching(&new.prefix).await?;
                 self.liveness_retain_only(
                     self.network_knowledge
                         .adults()
@@ -669,6 +814,14 @@ impl Node {
             let prefix = self.network_knowledge.prefix().await;
 
             debug!("{prefix:?}: {elders} Elders, {adults}~{membership_adults} Adults.");
+
+            let peer_tags = self.comm.peer_tags();
+            let peers_per_tag = peer_tags.peers_per_tag().await;
+            let msgs_received = peer_tags.msgs_received().await;
+            for (tag, peers) in peers_per_tag {
+                let msgs = msgs_received.get(&tag).copied().unwrap_or(0);
+                debug!("{prefix:?}: {peers} {tag} peers, {msgs} msgs received from them.");
+            }
         } else {
             debug!("log_section_stats: No membership instance");
         };
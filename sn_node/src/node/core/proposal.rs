@@ -8,7 +8,7 @@
 
 use crate::node::{dkg::SigShare, Result};
 use sn_consensus::Generation;
-use sn_interface::messaging::system::{Proposal as ProposalMsg, SectionAuth};
+use sn_interface::messaging::system::{FeatureActivation, Proposal as ProposalMsg, SectionAuth};
 use sn_interface::network_knowledge::{NodeState, SectionAuthorityProvider};
 
 #[allow(clippy::large_enum_variant)]
@@ -21,6 +21,8 @@ pub(crate) enum Proposal {
     },
     NewElders(SectionAuth<SectionAuthorityProvider>),
     JoinsAllowed(bool),
+    DataCopyCount(usize),
+    ActivateFeature(FeatureActivation),
 }
 
 impl Proposal {
@@ -45,6 +47,8 @@ impl Proposal {
             Self::SectionInfo { sap, generation: _ } => bincode::serialize(sap),
             Self::NewElders(info) => bincode::serialize(&info.sig.public_key),
             Self::JoinsAllowed(joins_allowed) => bincode::serialize(&joins_allowed),
+            Self::DataCopyCount(count) => bincode::serialize(&count),
+            Self::ActivateFeature(activation) => bincode::serialize(activation),
         }?)
     }
 
@@ -59,6 +63,8 @@ impl Proposal {
             },
             Self::NewElders(sap) => ProposalMsg::NewElders(sap.into_authed_msg()),
             Self::JoinsAllowed(allowed) => ProposalMsg::JoinsAllowed(allowed),
+            Self::DataCopyCount(count) => ProposalMsg::DataCopyCount(count),
+            Self::ActivateFeature(activation) => ProposalMsg::ActivateFeature(activation),
         }
     }
 }
@@ -93,6 +99,18 @@ mod tests {
         let proposal = Proposal::NewElders(section_signed_auth);
         verify_serialize_for_signing(&proposal, &new_pk)?;
 
+        // Proposal::DataCopyCount
+        let proposal = Proposal::DataCopyCount(6);
+        verify_serialize_for_signing(&proposal, &6usize)?;
+
+        // Proposal::ActivateFeature
+        let activation = FeatureActivation {
+            feature: sn_interface::messaging::system::Feature::ReplicationConfirmation,
+            generation: 3,
+        };
+        let proposal = Proposal::ActivateFeature(activation);
+        verify_serialize_for_signing(&proposal, &activation)?;
+
         Ok(())
     }
 
@@ -0,0 +1,297 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::Node;
+
+use crate::node::{api::cmds::Cmd, Result};
+use sn_interface::{
+    messaging::system::{NodeState, SectionAuth},
+    types::log_markers::LogMarker,
+};
+
+use serde::{Deserialize, Serialize};
+use sn_consensus::Generation;
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+use tokio::time::{sleep, Duration, Instant};
+
+// Name of the file marking the node as running, in its root dir
+const RUNNING_MARKER_FILENAME: &str = "node_running";
+// Name of the file the state of our section is written to, in the root dir of the node
+const SECTION_STATE_FILENAME: &str = "section_state";
+// How long to wait for our Elders to update us through AE, when probed with our section key.
+// They only answer a probe made with a key they're ahead of.
+const RECOVERY_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+const RECOVERY_PROBE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Marks the node as running in its root dir, until it's dropped as the node shuts down cleanly.
+/// Finding the marker on startup means the node last running from the dir went down uncleanly,
+/// e.g. it crashed or was killed.
+#[derive(Debug)]
+pub(crate) struct RunningMarker {
+    path: PathBuf,
+}
+
+impl RunningMarker {
+    /// Whether the node last running from the root dir went down without shutting down cleanly.
+    pub(crate) fn found_in(root_dir: &Path) -> bool {
+        root_dir.join(RUNNING_MARKER_FILENAME).exists()
+    }
+
+    /// Marks the node as running from the root dir.
+    pub(crate) async fn create(root_dir: &Path) -> Result<Self> {
+        let path = root_dir.join(RUNNING_MARKER_FILENAME);
+        tokio::fs::write(&path, std::process::id().to_string()).await?;
+        Ok(Self { path })
+    }
+}
+
+impl Drop for RunningMarker {
+    fn drop(&mut self) {
+        if let Err(error) = std::fs::remove_file(&self.path) {
+            warn!(
+                "Could not remove the running marker at {:?}: {:?}",
+                self.path, error
+            );
+        }
+    }
+}
+
+// State of our section written to the root dir of the node as it changes, to be reloaded
+// when the node starts again after going down uncleanly
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct SectionState {
+    section_key: bls::PublicKey,
+    members: BTreeSet<SectionAuth<NodeState>>,
+    membership_gen: Option<Generation>,
+    handover_gen: Option<Generation>,
+}
+
+impl SectionState {
+    async fn read(root_dir: &Path) -> Option<Self> {
+        let bytes = tokio::fs::read(root_dir.join(SECTION_STATE_FILENAME))
+            .await
+            .ok()?;
+        bincode::deserialize(&bytes).ok()
+    }
+
+    // Writes to a temporary file first, so a node going down in between doesn't leave a
+    // truncated file behind
+    async fn write(&self, root_dir: &Path) -> Result<()> {
+        let path = root_dir.join(SECTION_STATE_FILENAME);
+        let tmp_path = path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, bincode::serialize(self)?).await?;
+        tokio::fs::rename(tmp_path, path).await?;
+        Ok(())
+    }
+}
+
+/// Outcome of the self-check a node runs when it starts again after going down uncleanly,
+/// before it resumes its duties.
+///
+/// The section members written to the root dir of the node before it went down are reloaded
+/// when our section still has the same key. Membership and handover votes can't be resumed
+/// without the key share the node had, so the generations they had reached are only reported.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct RecoveryReport {
+    /// Number of chunks found in the chunk store
+    pub stored_chunks: usize,
+    /// Number of corrupt chunks found, which were quarantined and requested from other Adults
+    pub corrupt_chunks: u64,
+    /// Key of our section in the state stored on disk before going down, if any was
+    pub stored_section_key: Option<bls::PublicKey>,
+    /// Number of section members in the state stored on disk before going down
+    pub stored_members: usize,
+    /// Number of the stored section members which were reloaded, as our Elders didn't provide
+    /// them or provided an older state of them
+    pub reloaded_members: usize,
+    /// Generation of the membership votes reached before going down, if we were an Elder
+    pub stored_membership_gen: Option<Generation>,
+    /// Generation of the handover votes reached before going down, if we were an Elder
+    pub stored_handover_gen: Option<Generation>,
+    /// Key of our section, as provided by its Elders and updated through AE
+    pub section_key: bls::PublicKey,
+    /// Whether our Elders updated our knowledge when probed through AE
+    pub updated_by_probe: bool,
+    /// Number of members of our section, as provided by its Elders
+    pub section_members: usize,
+    /// Whether the node is an Elder of its section
+    pub is_elder: bool,
+}
+
+impl RecoveryReport {
+    /// Whether the state stored on disk was behind, or ahead of, the one of our section.
+    pub fn knowledge_diverged(&self) -> bool {
+        self.stored_section_key != Some(self.section_key)
+    }
+}
+
+impl Node {
+    /// Writes the state of our section to our root dir, for it to be reloaded if we go down
+    /// uncleanly.
+    pub(crate) async fn write_section_state(&self) {
+        let state = SectionState {
+            section_key: self.network_knowledge.section_key().await,
+            members: self
+                .network_knowledge
+                .section_signed_members()
+                .await
+                .into_iter()
+                .map(|member| member.into_authed_msg())
+                .collect(),
+            membership_gen: self
+                .membership
+                .read()
+                .await
+                .as_ref()
+                .map(|membership| membership.generation()),
+            handover_gen: self
+                .handover_voting
+                .read()
+                .await
+                .as_ref()
+                .map(|handover| handover.generation()),
+        };
+        if let Err(error) = state.write(&self.root_storage_dir).await {
+            error!("Failed to write the state of our section: {:?}", error);
+        }
+    }
+
+    /// Checks the integrity of the whole chunk store, and reloads the state of our section
+    /// stored on disk, comparing it against the one our Elders provided.
+    /// Returns the report along with the cmds repairing the corrupt chunks and probing the
+    /// Elders through AE, for `await_recovery_probe` to wait on.
+    pub(crate) async fn check_recovery(&self) -> Result<(RecoveryReport, Vec<Cmd>)> {
+        let corrupt_before = self.data_storage.scrub_progress().await.corrupt_chunks;
+        // the scrubber hasn't started yet, so this is a complete pass over all the chunks
+        let mut cmds = self.scrub_stored_chunks(usize::MAX).await?;
+        let progress = self.data_storage.scrub_progress().await;
+
+        let section_key = self.network_knowledge.section_key().await;
+        let stored = SectionState::read(&self.root_storage_dir).await;
+        let mut reloaded_members = 0;
+        if let Some(stored) = stored
+            .as_ref()
+            .filter(|stored| stored.section_key == section_key)
+        {
+            let known = self.network_knowledge.section_signed_members().await;
+            for member in stored.members.iter().cloned() {
+                let member = member.into_authed_state();
+                if !known.contains(&member) && self.network_knowledge.update_member(member).await {
+                    reloaded_members += 1;
+                }
+            }
+        }
+
+        let report = RecoveryReport {
+            stored_chunks: progress.total,
+            corrupt_chunks: progress.corrupt_chunks - corrupt_before,
+            stored_section_key: stored.as_ref().map(|stored| stored.section_key),
+            stored_members: stored.as_ref().map_or(0, |stored| stored.members.len()),
+            reloaded_members,
+            stored_membership_gen: stored.as_ref().and_then(|stored| stored.membership_gen),
+            stored_handover_gen: stored.as_ref().and_then(|stored| stored.handover_gen),
+            section_key,
+            updated_by_probe: false,
+            section_members: self.network_knowledge.section_members().await.len(),
+            is_elder: self.is_elder().await,
+        };
+
+        cmds.push(self.generate_section_probe_msg().await?);
+
+        Ok((report, cmds))
+    }
+
+    /// Waits for our Elders to update our knowledge in response to the AE probe sent by
+    /// `check_recovery`, for a few seconds at most, completing the report with what we know
+    /// afterwards. Incoming msgs must be handled meanwhile.
+    pub(crate) async fn await_recovery_probe(&self, mut report: RecoveryReport) -> RecoveryReport {
+        let deadline = Instant::now() + RECOVERY_PROBE_TIMEOUT;
+        while Instant::now() < deadline {
+            if self.network_knowledge.section_key().await != report.section_key {
+                report.updated_by_probe = true;
+                break;
+            }
+            sleep(RECOVERY_PROBE_POLL_INTERVAL).await;
+        }
+
+        report.section_key = self.network_knowledge.section_key().await;
+        report.section_members = self.network_knowledge.section_members().await.len();
+        report.is_elder = self.is_elder().await;
+
+        if report.knowledge_diverged() {
+            warn!(
+                "State stored before going down diverged from our section's, with key {:?} instead of {:?}",
+                report.stored_section_key, report.section_key
+            );
+        }
+
+        info!(
+            "{:?} {}",
+            LogMarker::RecoveryChecked,
+            serde_json::to_string(&report).unwrap_or_else(|_| format!("{:?}", report))
+        );
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::Result;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn unclean_shutdown_is_detected_until_clean_one() -> Result<()> {
+        let root = tempdir()?;
+        assert!(!RunningMarker::found_in(root.path()));
+
+        let marker = RunningMarker::create(root.path()).await?;
+        assert!(RunningMarker::found_in(root.path()));
+        // the node went down without dropping its marker
+        std::mem::forget(marker);
+        assert!(RunningMarker::found_in(root.path()));
+
+        let marker = RunningMarker::create(root.path()).await?;
+        drop(marker);
+        assert!(!RunningMarker::found_in(root.path()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn section_state_is_read_back() -> Result<()> {
+        let root = tempdir()?;
+        assert!(SectionState::read(root.path()).await.is_none());
+
+        let section_key = bls::SecretKey::random().public_key();
+        let state = SectionState {
+            section_key,
+            members: BTreeSet::new(),
+            membership_gen: Some(3),
+            handover_gen: None,
+        };
+        state.write(root.path()).await?;
+
+        let read = SectionState::read(root.path())
+            .await
+            .expect("section state not read back");
+        assert_eq!(read.section_key, section_key);
+        assert_eq!(read.membership_gen, Some(3));
+        assert_eq!(read.handover_gen, None);
+
+        // a truncated state is ignored rather than failing the node's startup
+        tokio::fs::write(root.path().join(SECTION_STATE_FILENAME), b"trunc").await?;
+        assert!(SectionState::read(root.path()).await.is_none());
+
+        Ok(())
+    }
+}
@@ -8,32 +8,19 @@
 
 //! Relocation related types and utilities.
 
+use crate::node::node_age::{
+    age_after_relocation, is_relocation_candidate, relocation_dst, ChurnId,
+};
+
 use sn_interface::elder_count;
 use sn_interface::messaging::system::RelocateDetails;
 use sn_interface::network_knowledge::{recommended_section_size, NetworkKnowledge, NodeState};
 use sn_interface::types::{keys::ed25519, Peer};
 
 use ed25519_dalek::{Signature, Verifier};
-use std::{
-    cmp::min,
-    collections::BTreeSet,
-    fmt::{self, Display, Formatter},
-};
+use std::{cmp::min, collections::BTreeSet};
 use xor_name::XorName;
 
-// Unique identifier for a churn event, which is used to select nodes to relocate.
-pub(crate) struct ChurnId(pub(crate) Vec<u8>);
-
-impl Display for ChurnId {
-    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
-        write!(
-            fmt,
-            "Churn-{:02x}{:02x}{:02x}..",
-            self.0[0], self.0[1], self.0[2]
-        )
-    }
-}
-
 /// Find all nodes to relocate after a churn event and generate the relocation details for them.
 pub(super) async fn find_nodes_to_relocate(
     network_knowledge: &NetworkKnowledge,
@@ -58,7 +45,7 @@ pub(super) async fn find_nodes_to_relocate(
     // Find the peers that pass the relocation check
     let mut candidates: Vec<_> = joined_nodes
             .into_iter()
-            .filter(|info| check(info.age(), churn_id))
+            .filter(|info| is_relocation_candidate(info.age(), churn_id))
             // the newly joined node shall not be relocated immediately
             .filter(|info| !excluded.contains(&info.name()))
             .collect();
@@ -75,9 +62,11 @@ pub(super) async fn find_nodes_to_relocate(
 
     let mut relocating_nodes = vec![];
     for node_state in candidates {
-        if node_state.age() == max_age {
-            let dst = dst(&node_state.name(), churn_id);
-            let age = node_state.age().saturating_add(1);
+        if node_state.age() != max_age {
+            continue;
+        }
+        if let Some(age) = age_after_relocation(node_state.age(), churn_id) {
+            let dst = relocation_dst(&node_state.name(), churn_id);
             let relocate_details =
                 RelocateDetails::with_age(network_knowledge, node_state.peer(), dst, age);
             relocating_nodes.push((node_state, relocate_details));
@@ -135,42 +124,13 @@ impl RelocateDetailsUtils for RelocateDetails {
     }
 }
 
-// Relocation check - returns whether a member with the given age is a candidate for relocation on
-// a churn event with the given churn id.
-pub(crate) fn check(age: u8, churn_id: &ChurnId) -> bool {
-    // Evaluate the formula: `signature % 2^age == 0` Which is the same as checking the signature
-    // has at least `age` trailing zero bits.
-    trailing_zeros(&churn_id.0) >= age as u32
-}
-
-// Compute the destination for the node with `relocating_name` to be relocated to.
-fn dst(relocating_name: &XorName, churn_id: &ChurnId) -> XorName {
-    XorName::from_content_parts(&[&relocating_name.0, &churn_id.0])
-}
-
-// Returns the number of trailing zero bits of the bytes slice.
-fn trailing_zeros(bytes: &[u8]) -> u32 {
-    let mut output = 0;
-
-    for &byte in bytes.iter().rev() {
-        if byte == 0 {
-            output += 8;
-        } else {
-            output += byte.trailing_zeros();
-            break;
-        }
-    }
-
-    output
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use sn_interface::elder_count;
     use sn_interface::network_knowledge::test_utils::section_signed;
     use sn_interface::types::SecretKeySet;
+    use sn_interface::{data_copy_count, elder_count};
 
     use eyre::Result;
     use itertools::Itertools;
@@ -181,18 +141,6 @@ mod tests {
     use std::net::SocketAddr;
     use xor_name::{Prefix, XOR_NAME_LEN};
 
-    #[test]
-    fn byte_slice_trailing_zeros() {
-        assert_eq!(trailing_zeros(&[0]), 8);
-        assert_eq!(trailing_zeros(&[1]), 0);
-        assert_eq!(trailing_zeros(&[2]), 1);
-        assert_eq!(trailing_zeros(&[4]), 2);
-        assert_eq!(trailing_zeros(&[8]), 3);
-        assert_eq!(trailing_zeros(&[0, 0]), 16);
-        assert_eq!(trailing_zeros(&[1, 0]), 8);
-        assert_eq!(trailing_zeros(&[2, 0]), 9);
-    }
-
     const MAX_AGE: u8 = MIN_ADULT_AGE + 3;
 
     proptest! {
@@ -223,6 +171,7 @@ mod tests {
             peers.iter().map(|p| NodeState::joined(*p, None)),
             sk_set.public_keys(),
             0,
+            data_copy_count(),
         );
         let section_auth = section_signed(sk, section_auth)?;
 
@@ -324,7 +273,10 @@ mod tests {
             let data: u64 = rng.gen();
             let signature = sk.sign(&data.to_be_bytes());
 
-            if trailing_zeros(&signature.to_bytes()) == trailing_zeros_count {
+            let churn_id = ChurnId(signature.to_bytes().to_vec());
+            if is_relocation_candidate(trailing_zeros_count as u8, &churn_id)
+                && !is_relocation_candidate(trailing_zeros_count as u8 + 1, &churn_id)
+            {
                 return signature;
             }
         }
@@ -0,0 +1,328 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::{Error, Result};
+use sn_interface::messaging::{system::SystemMsg, AuthKind, DstLocation, MsgId, NodeAuth, WireMsg};
+use sn_interface::network_knowledge::NodeInfo;
+
+use bls::PublicKey as BlsPublicKey;
+use bytes::Bytes;
+use ed25519_dalek::Keypair;
+use rayon::prelude::*;
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::{
+    mpsc::{self, error::TrySendError},
+    oneshot, Mutex,
+};
+
+// Max number of msgs waiting to be signed, beyond which senders wait for room in the queue
+const SIGNING_QUEUE_CAPACITY: usize = 1024;
+// Max number of workers signing msgs at once
+const MAX_SIGNING_WORKERS: usize = 4;
+// Max number of queued msgs a worker signs in one go
+const MAX_SIGNING_BATCH: usize = 32;
+
+/// Metrics of the workers signing the msgs sent by the node.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SigningStats {
+    /// Number of msgs signed
+    pub signed: u64,
+    /// Number of batches the msgs were signed in
+    pub batches: u64,
+    /// Number of msgs which had to wait for room in the queue, as it was full
+    pub backpressured: u64,
+    /// Number of msgs currently queued or being signed
+    pub pending: u64,
+    /// Total time the msgs signed spent between being queued and signed
+    pub total_latency: Duration,
+    /// Longest time a msg spent between being queued and signed
+    pub max_latency: Duration,
+}
+
+impl SigningStats {
+    /// Mean time a msg spent between being queued and signed
+    pub fn mean_latency(&self) -> Duration {
+        if self.signed == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_nanos((self.total_latency.as_nanos() / self.signed as u128) as u64)
+        }
+    }
+}
+
+/// Workers signing the msgs sent by the node, off the tasks handling cmds.
+///
+/// Msgs to sign are queued, each worker signing as many of them as are waiting, up to a batch,
+/// in parallel, in a blocking task. When the queue is full, senders wait for room in it, rather than piling
+/// up more signing work than the workers get through.
+#[derive(Clone, Debug)]
+pub(crate) struct SigningPool {
+    job_tx: mpsc::Sender<SigningJob>,
+    metrics: Arc<SigningMetrics>,
+}
+
+struct SigningJob {
+    input: SigningInput,
+    queued_at: Instant,
+    signed_tx: oneshot::Sender<Result<NodeAuth>>,
+}
+
+struct SigningInput {
+    keypair: Arc<Keypair>,
+    section_pk: BlsPublicKey,
+    payload: Bytes,
+}
+
+#[derive(Debug, Default)]
+struct SigningMetrics {
+    queued: AtomicU64,
+    signed: AtomicU64,
+    batches: AtomicU64,
+    backpressured: AtomicU64,
+    total_latency_micros: AtomicU64,
+    max_latency_micros: AtomicU64,
+}
+
+impl SigningPool {
+    /// Starts the given number of workers, sharing a queue of msgs to sign of the given capacity.
+    /// The workers stop once the pool, and all its clones, are dropped.
+    pub(crate) fn new(workers: usize, queue_capacity: usize) -> Self {
+        let (job_tx, job_rx) = mpsc::channel(queue_capacity.max(1));
+        let job_rx = Arc::new(Mutex::new(job_rx));
+        let metrics = Arc::new(SigningMetrics::default());
+
+        for _ in 0..workers.max(1) {
+            let _worker = tokio::spawn(run_worker(job_rx.clone(), metrics.clone()));
+        }
+
+        Self { job_tx, metrics }
+    }
+
+    /// Starts a worker per CPU, up to `MAX_SIGNING_WORKERS`, sharing a queue of
+    /// `SIGNING_QUEUE_CAPACITY` msgs to sign.
+    pub(crate) fn for_node() -> Self {
+        Self::new(
+            num_cpus::get().min(MAX_SIGNING_WORKERS),
+            SIGNING_QUEUE_CAPACITY,
+        )
+    }
+
+    /// Creates a msg signed by the given node, as `WireMsg::single_src` does, the signing being
+    /// done by the workers.
+    pub(crate) async fn single_src(
+        &self,
+        node: &NodeInfo,
+        dst: DstLocation,
+        node_msg: SystemMsg,
+        src_section_pk: BlsPublicKey,
+    ) -> Result<WireMsg> {
+        let msg_payload =
+            WireMsg::serialize_msg_payload(&node_msg).map_err(|_| Error::InvalidMessage)?;
+
+        let auth = self
+            .sign(node.keypair.clone(), src_section_pk, msg_payload.clone())
+            .await?;
+
+        let wire_msg = WireMsg::new_system_msg(
+            MsgId::new(),
+            &node_msg,
+            msg_payload,
+            AuthKind::Node(auth),
+            dst,
+        )?;
+
+        #[cfg(test)]
+        let wire_msg = wire_msg.set_payload_debug(node_msg);
+
+        Ok(wire_msg)
+    }
+
+    /// Returns the metrics of the signing done so far.
+    pub(crate) fn stats(&self) -> SigningStats {
+        let signed = self.metrics.signed.load(Ordering::Relaxed);
+        SigningStats {
+            signed,
+            batches: self.metrics.batches.load(Ordering::Relaxed),
+            backpressured: self.metrics.backpressured.load(Ordering::Relaxed),
+            pending: self
+                .metrics
+                .queued
+                .load(Ordering::Relaxed)
+                .saturating_sub(signed),
+            total_latency: Duration::from_micros(
+                self.metrics.total_latency_micros.load(Ordering::Relaxed),
+            ),
+            max_latency: Duration::from_micros(
+                self.metrics.max_latency_micros.load(Ordering::Relaxed),
+            ),
+        }
+    }
+
+    async fn sign(
+        &self,
+        keypair: Arc<Keypair>,
+        section_pk: BlsPublicKey,
+        payload: Bytes,
+    ) -> Result<NodeAuth> {
+        let (signed_tx, signed_rx) = oneshot::channel();
+        let job = SigningJob {
+            input: SigningInput {
+                keypair,
+                section_pk,
+                payload,
+            },
+            queued_at: Instant::now(),
+            signed_tx,
+        };
+
+        match self.job_tx.try_send(job) {
+            Ok(()) => {}
+            Err(TrySendError::Full(job)) => {
+                let _ = self.metrics.backpressured.fetch_add(1, Ordering::Relaxed);
+                trace!("Signing queue is full, waiting for room in it");
+                self.job_tx
+                    .send(job)
+                    .await
+                    .map_err(|_| Error::SigningPoolClosed)?;
+            }
+            Err(TrySendError::Closed(_)) => return Err(Error::SigningPoolClosed),
+        }
+        let _ = self.metrics.queued.fetch_add(1, Ordering::Relaxed);
+
+        signed_rx.await.map_err(|_| Error::SigningPoolClosed)?
+    }
+}
+
+// Signs the queued msgs in batches, until the queue is closed.
+async fn run_worker(job_rx: Arc<Mutex<mpsc::Receiver<SigningJob>>>, metrics: Arc<SigningMetrics>) {
+    loop {
+        let batch = {
+            let mut job_rx = job_rx.lock().await;
+            let job = match job_rx.recv().await {
+                Some(job) => job,
+                None => break,
+            };
+            let mut batch = vec![job];
+            while batch.len() < MAX_SIGNING_BATCH {
+                match job_rx.try_recv() {
+                    Ok(job) => batch.push(job),
+                    Err(_) => break,
+                }
+            }
+            batch
+        };
+
+        let (replies, inputs): (Vec<_>, Vec<_>) = batch
+            .into_iter()
+            .map(|job| ((job.queued_at, job.signed_tx), job.input))
+            .unzip();
+
+        // Signing is CPU bound, so it's kept off the threads the cmds are handled on
+        let signed = match tokio::task::spawn_blocking(move || sign_batch(inputs)).await {
+            Ok(signed) => signed,
+            Err(error) => {
+                error!("Signing a batch of msgs panicked: {:?}", error);
+                for (_, signed_tx) in replies {
+                    let _ = signed_tx.send(Err(Error::SigningPanicked));
+                }
+                continue;
+            }
+        };
+
+        let _ = metrics.batches.fetch_add(1, Ordering::Relaxed);
+        for ((queued_at, signed_tx), auth) in replies.into_iter().zip(signed) {
+            let latency = queued_at.elapsed().as_micros() as u64;
+            let _ = metrics
+                .total_latency_micros
+                .fetch_add(latency, Ordering::Relaxed);
+            let _ = metrics
+                .max_latency_micros
+                .fetch_max(latency, Ordering::Relaxed);
+            let _ = metrics.signed.fetch_add(1, Ordering::Relaxed);
+            // the sender may have given up on the msg meanwhile
+            let _ = signed_tx.send(Ok(auth));
+        }
+    }
+
+    trace!("Signing queue closed, worker stopping");
+}
+
+fn sign_batch(batch: Vec<SigningInput>) -> Vec<NodeAuth> {
+    batch
+        .par_iter()
+        .map(|input| {
+            NodeAuth::authorize(input.section_pk, &input.keypair, &input.payload).into_inner()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SigningPool;
+    use crate::node::messages::WireMsgUtils;
+    use sn_interface::{
+        messaging::{system::SystemMsg, DstLocation, MsgType, NodeMsgAuthority, WireMsg},
+        network_knowledge::{NodeInfo, MIN_ADULT_AGE},
+        types::keys::ed25519,
+    };
+
+    use eyre::{eyre, Result};
+    use futures::future::try_join_all;
+    use std::net::{Ipv4Addr, SocketAddr};
+    use xor_name::Prefix;
+
+    fn dst() -> DstLocation {
+        DstLocation::section(
+            xor_name::rand::random(),
+            bls::SecretKey::random().public_key(),
+        )
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn msgs_signed_by_the_pool_match_the_ones_signed_in_place() -> Result<()> {
+        let pool = SigningPool::new(2, 4);
+        let node = NodeInfo::new(
+            ed25519::gen_keypair(&Prefix::default().range_inclusive(), MIN_ADULT_AGE),
+            SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+        );
+        let section_pk = bls::SecretKey::random().public_key();
+
+        // more msgs than the queue holds, so senders wait for room in it
+        let wire_msgs = try_join_all(
+            (0..64).map(|_| pool.single_src(&node, dst(), SystemMsg::AntiEntropyProbe, section_pk)),
+        )
+        .await?;
+
+        let expected = WireMsg::single_src(&node, dst(), SystemMsg::AntiEntropyProbe, section_pk)?;
+        for wire_msg in &wire_msgs {
+            // ed25519 signatures are deterministic, so they're the same for the same payload
+            assert_eq!(wire_msg.msg_kind(), expected.msg_kind());
+            match WireMsg::from(wire_msg.serialize()?)?.into_msg()? {
+                MsgType::System {
+                    msg_authority: NodeMsgAuthority::Node(auth),
+                    ..
+                } => assert_eq!(auth.node_ed_pk, node.keypair.public),
+                other => return Err(eyre!("unexpected msg: {:?}", other)),
+            }
+        }
+
+        let stats = pool.stats();
+        assert_eq!(stats.signed, 64);
+        assert_eq!(stats.pending, 0);
+        assert!(stats.batches > 0 && stats.batches <= 64);
+        assert!(stats.max_latency >= stats.mean_latency());
+
+        Ok(())
+    }
+}
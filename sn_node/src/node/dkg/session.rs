@@ -9,12 +9,11 @@
 use crate::node::{
     api::cmds::{next_timer_token, Cmd},
     dkg::dkg_msgs_utils::{DkgFailureSigSetUtils, DkgFailureSigUtils},
-    messages::WireMsgUtils,
     Result,
 };
 use sn_interface::messaging::{
     system::{DkgFailureSig, DkgFailureSigSet, DkgSessionId, SystemMsg},
-    DstLocation, WireMsg,
+    DstLocation,
 };
 use sn_interface::network_knowledge::{NodeInfo, SectionAuthorityProvider, SectionKeyShare};
 use sn_interface::types::{keys::ed25519, log_markers::LogMarker, Peer, PublicKey};
@@ -70,7 +69,6 @@ impl Session {
 
     fn send_dkg_not_ready(
         &mut self,
-        node: &NodeInfo,
         message: DkgMessage,
         sender: XorName,
         section_pk: BlsPublicKey,
@@ -100,19 +98,11 @@ impl Session {
                 session_id: self.session_id.clone(),
                 message,
             };
-            let wire_msg = WireMsg::single_src(
-                node,
-                DstLocation::Node {
-                    name: target,
-                    section_pk,
-                },
-                node_msg,
-                section_pk,
-            )?;
-
-            cmds.push(Cmd::SendMsg {
+            cmds.push(Cmd::SignAndSendMsg {
+                msg: node_msg,
+                dst: DstLocation::node(target, section_pk),
+                src_section_pk: section_pk,
                 recipients: vec![*peer],
-                wire_msg,
             });
         } else {
             warn!(
@@ -150,10 +140,10 @@ impl Session {
             Err(DkgError::UnexpectedPhase { expected, actual })
                 if is_dkg_behind(expected, actual) =>
             {
-                cmds.extend(self.send_dkg_not_ready(node, message, sender, section_pk)?);
+                cmds.extend(self.send_dkg_not_ready(message, sender, section_pk)?);
             }
             Err(DkgError::MissingPart) => {
-                cmds.extend(self.send_dkg_not_ready(node, message, sender, section_pk)?);
+                cmds.extend(self.send_dkg_not_ready(message, sender, section_pk)?);
             }
             Err(error) => {
                 error!("Error processing DKG message: {:?}", error);
@@ -213,25 +203,11 @@ impl Session {
                     session_id: self.session_id.clone(),
                     message: message.clone(),
                 };
-                let wire_msg = WireMsg::single_src(
-                    node,
-                    DstLocation::Node {
-                        name: target,
-                        section_pk,
-                    },
-                    node_msg,
-                    section_pk,
-                )?;
-
-                trace!(
-                    "DKG sending {:?} with msg_id {:?}",
-                    message,
-                    wire_msg.msg_id()
-                );
-
-                cmds.push(Cmd::SendMsg {
+                cmds.push(Cmd::SignAndSendMsg {
+                    msg: node_msg,
+                    dst: DstLocation::node(target, section_pk),
+                    src_section_pk: section_pk,
                     recipients: vec![*peer],
-                    wire_msg,
                 });
             } else {
                 error!("Failed to find target {:?} among peers {:?}", target, peers);
@@ -399,19 +375,15 @@ impl Session {
                     sig,
                     failed_participants,
                 };
-                let wire_msg = WireMsg::single_src(
-                    node,
-                    DstLocation::Section {
-                        name: XorName::from(PublicKey::Bls(section_pk)),
-                        section_pk,
-                    },
-                    node_msg,
-                    section_pk,
-                )?;
                 trace!("{}", LogMarker::DkgSendFailureObservation);
-                Cmd::SendMsg {
+                Cmd::SignAndSendMsg {
+                    msg: node_msg,
+                    dst: DstLocation::section(
+                        XorName::from(PublicKey::Bls(section_pk)),
+                        section_pk,
+                    ),
+                    src_section_pk: section_pk,
                     recipients: self.recipients(),
-                    wire_msg,
                 }
             }))
             .collect();
@@ -500,11 +472,10 @@ impl Session {
 mod tests {
     use super::*;
     use crate::node::dkg::voter::DkgVoter;
-    use sn_interface::elder_count;
     use sn_interface::messaging::system::{MembershipState, NodeState};
-    use sn_interface::messaging::MsgType;
     use sn_interface::network_knowledge::{test_utils::gen_addr, NodeInfo, MIN_ADULT_AGE};
     use sn_interface::types::keys::ed25519::{self, proptesting::arbitrary_keypair};
+    use sn_interface::{data_copy_count, elder_count};
 
     use assert_matches::assert_matches;
     use eyre::{bail, ContextCompat, Result};
@@ -532,6 +503,7 @@ mod tests {
             addr: node.peer().addr(),
             state: MembershipState::Joined,
             previous_name: None,
+            version: None,
         }]);
         let session_id = DkgSessionId {
             prefix,
@@ -539,6 +511,7 @@ mod tests {
             section_chain_len: 0,
             bootstrap_members,
             membership_gen: 0,
+            data_copy_count: data_copy_count(),
         };
 
         let cmds = voter.start(&node, session_id, section_pk).await?;
@@ -576,6 +549,7 @@ mod tests {
                     .map(|n| NodeState::joined(n.name(), n.addr, None)),
             ),
             membership_gen: dummy_gen,
+            data_copy_count: data_copy_count(),
         };
 
         let mut actors: HashMap<_, _> = nodes
@@ -653,17 +627,12 @@ mod tests {
             expected_dkg_key: &DkgSessionId,
         ) -> Result<Vec<(SocketAddr, DkgMessage)>> {
             match cmd {
-                Cmd::SendMsg {
-                    recipients,
-                    wire_msg,
-                } => match wire_msg.into_msg()? {
-                    MsgType::System {
-                        msg:
-                            SystemMsg::DkgMessage {
-                                session_id,
-                                message,
-                            },
-                        ..
+                Cmd::SignAndSendMsg {
+                    msg, recipients, ..
+                } => match msg {
+                    SystemMsg::DkgMessage {
+                        session_id,
+                        message,
                     } => {
                         assert_eq!(session_id.hash(), expected_dkg_key.hash());
                         Ok(recipients
@@ -671,10 +640,7 @@ mod tests {
                             .map(|peer| (peer.addr(), message.clone()))
                             .collect())
                     }
-                    MsgType::System {
-                        msg: SystemMsg::DkgNotReady { message, .. },
-                        ..
-                    } => Ok(vec![(self.node.addr, message)]),
+                    SystemMsg::DkgNotReady { message, .. } => Ok(vec![(self.node.addr, message)]),
                     other_msg => bail!("Unexpected msg: {:?}", other_msg),
                 },
                 Cmd::HandleDkgOutcome { outcome, .. } => {
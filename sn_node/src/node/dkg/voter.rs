@@ -6,11 +6,11 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::node::{api::cmds::Cmd, dkg::session::Session, messages::WireMsgUtils, Result};
+use crate::node::{api::cmds::Cmd, dkg::session::Session, Result};
 use sn_interface::{
     messaging::{
         system::{DkgFailureSig, DkgFailureSigSet, DkgSessionId, SystemMsg},
-        DstLocation, WireMsg,
+        DstLocation,
     },
     types::keys::ed25519::Digest256,
 };
@@ -181,19 +181,11 @@ impl DkgVoter {
                 session_id: session_id.clone(),
                 message,
             };
-            let wire_msg = WireMsg::single_src(
-                node,
-                DstLocation::Node {
-                    name: sender.name(),
-                    section_pk,
-                },
-                node_msg,
-                section_pk,
-            )?;
-
-            cmds.push(Cmd::SendMsg {
+            cmds.push(Cmd::SignAndSendMsg {
+                msg: node_msg,
+                dst: DstLocation::node(sender.name(), section_pk),
+                src_section_pk: section_pk,
                 recipients: vec![sender],
-                wire_msg,
             });
         }
         Ok(cmds)
@@ -9,7 +9,7 @@
 use super::Prefix;
 
 use crate::node::handover::Error as HandoverError;
-use sn_interface::messaging::data::Error as ErrorMsg;
+use sn_interface::messaging::{data::Error as ErrorMsg, system::JoinRejectionReason};
 use sn_interface::types::{convert_dt_error_to_error_msg, DataAddress, Peer, PublicKey};
 
 use secured_linked_list::error::Error as SecuredLinkedListError;
@@ -37,6 +37,10 @@ pub enum Error {
     CouldNotGetPermitInTime,
     #[error("Node prioritisation semaphore was closed early.")]
     SemaphoreClosed,
+    #[error("The workers signing outgoing msgs were shut down.")]
+    SigningPoolClosed,
+    #[error("Signing outgoing msgs panicked.")]
+    SigningPanicked,
     #[error("Only messages requiring auth accumulation should be sent via \"send_messages_to_all_nodes_or_directly_handle_for_accumulation\"")]
     SendOrHandlingNormalMsg,
     #[error("There was a problem during acquisition of a tokio::sync::semaphore permit.")]
@@ -91,12 +95,44 @@ pub enum Error {
     NoMatchingElder,
     #[error("Node cannot join the network since it is not externally reachable: {0}")]
     NodeNotReachable(SocketAddr),
+    /// The section refused our join request
+    #[error("Invalid number of copies of each data for a section to store: {0}")]
+    InvalidDataCopyCount(usize),
+    #[error("Join request rejected by the section: {0}")]
+    JoinRejected(JoinRejectionReason),
+    /// A supermajority of the Elders of the section denylisted us
+    #[error("Join request rejected by {0} Elders of the section, which denylisted the node")]
+    DenylistedByElders(usize),
     /// Timeout when trying to join the network
     #[error("Timeout when trying to join the network")]
     JoinTimeout,
+    /// Timeout when relocating to another section, the relocation being given up on, need to
+    /// re-join the network
+    #[error("Timeout when relocating to the section at {0}")]
+    RelocationTimeout(XorName),
     /// Join occured during section churn and new elders missed it, need to re-join the network
     #[error("Join occured during section churn and new elders missed it")]
     ChurnJoinMiss,
+    /// A task of the node kept panicking, and was given up on, need to restart the node
+    #[error("Task '{task}' of the {subsystem:?} subsystem kept panicking and was given up on")]
+    SubsystemFailed {
+        /// Subsystem the task belongs to
+        subsystem: crate::node::Subsystem,
+        /// Name of the task
+        task: String,
+    },
+    /// The config file of the node holds settings which can't be changed while it's running,
+    /// or invalid values
+    #[error("Invalid config file {path:?}: {reason}")]
+    InvalidConfigFile {
+        /// Path of the config file
+        path: std::path::PathBuf,
+        /// What's wrong with it
+        reason: String,
+    },
+    /// An event of the node couldn't be delivered to the endpoint it's notified to
+    #[error("Failed to notify event: {0}")]
+    NotificationFailed(String),
     /// Database error.
     #[error("Database error:: {0}")]
     Database(#[from] crate::dbs::Error),
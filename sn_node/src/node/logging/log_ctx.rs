@@ -8,7 +8,7 @@
 
 use crate::node::api::dispatcher::Dispatcher;
 
-use std::sync::Arc;
+use std::sync::{atomic::Ordering, Arc};
 use xor_name::Prefix;
 
 pub(crate) struct LogCtx {
@@ -23,4 +23,8 @@ impl LogCtx {
     pub(crate) async fn prefix(&self) -> Prefix {
         self.cmds_dispatcher.node.network_knowledge().prefix().await
     }
+
+    pub(crate) fn print_resources_usage(&self) -> bool {
+        self.cmds_dispatcher.resource_logs.load(Ordering::Relaxed)
+    }
 }
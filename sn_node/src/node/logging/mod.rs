@@ -7,6 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 pub(super) mod log_ctx;
+pub(super) mod stream;
 mod system;
 
 use self::log_ctx::LogCtx;
@@ -19,7 +20,7 @@ use tracing::trace;
 
 const LOG_INTERVAL: Duration = std::time::Duration::from_secs(10);
 
-pub(super) async fn run_system_logger(ctx: LogCtx, print_resources_usage: bool) {
+pub(super) async fn run_system_logger(ctx: LogCtx) {
     let mut system = System::new_all();
     initial_log(&mut system, &ctx).await;
 
@@ -33,7 +34,7 @@ pub(super) async fn run_system_logger(ctx: LogCtx, print_resources_usage: bool)
             if system.used_memory() > HIGH_MEM_LOAD {
                 warn!("========================>>> HIGH MEM LOAD");
             }
-            log(&mut system, &ctx, print_resources_usage).await;
+            log(&mut system, &ctx).await;
         }
     });
 }
@@ -52,7 +53,7 @@ fn fmt(string: Option<String>) -> String {
 }
 
 #[tracing::instrument(skip(ctx))]
-async fn log(system: &mut System, ctx: &LogCtx, print_resources_usage: bool) {
+async fn log(system: &mut System, ctx: &LogCtx) {
     let prefix: &str = &format!("({:?})", ctx.prefix().await);
 
     let processors = system.processors();
@@ -65,7 +66,7 @@ async fn log(system: &mut System, ctx: &LogCtx, print_resources_usage: bool) {
             continue;
         }
 
-        if print_resources_usage {
+        if ctx.print_resources_usage() {
             println!(
                 "{}: Node resource usage: {:?}",
                 prefix,
@@ -0,0 +1,375 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use sn_interface::types::log_markers::LogMarker;
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{layer::Context, Layer};
+
+// Number of log events held for subscribers which haven't received them yet, beyond which
+// the oldest are dropped for the subscribers lagging behind
+const LOG_STREAM_CAPACITY: usize = 1024;
+
+/// Default max number of log events a subscriber is sent per second
+pub const DEFAULT_LOG_EVENTS_PER_SEC: u32 = 100;
+
+/// Max number of log events a subscriber can be sent per second
+pub const MAX_LOG_EVENTS_PER_SEC: u32 = 1000;
+
+/// A log event of the node, as streamed to subscribers.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEvent {
+    /// When the event was logged, in milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    /// Level of the event, e.g. "INFO"
+    pub level: String,
+    /// Module the event was logged from
+    pub module: String,
+    /// Log marker the message of the event starts with, if any
+    pub marker: Option<String>,
+    /// Message of the event
+    pub message: String,
+    /// Fields of the event other than its message
+    pub fields: BTreeMap<String, String>,
+}
+
+/// Which log events a subscriber is sent.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogFilter {
+    /// Most verbose level of the events sent, e.g. "debug". Events are only logged, and so
+    /// can only be sent, at up to the log level of the node.
+    pub level: String,
+    /// Log markers of the events sent, all events being sent if there are none
+    pub markers: BTreeSet<String>,
+    /// Modules, along with their submodules, the events sent are logged from, all events
+    /// being sent if there are none
+    pub modules: Vec<String>,
+    /// Max number of events sent per second, beyond which events are dropped
+    pub max_events_per_sec: u32,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            level: Level::INFO.to_string(),
+            markers: BTreeSet::new(),
+            modules: vec![],
+            max_events_per_sec: DEFAULT_LOG_EVENTS_PER_SEC,
+        }
+    }
+}
+
+impl LogFilter {
+    /// Checks the filter can be applied, returning why not otherwise.
+    pub fn validate(&self) -> Result<(), String> {
+        let _level = self.parse_level()?;
+        if let Some(marker) = self
+            .markers
+            .iter()
+            .find(|marker| marker.parse::<LogMarker>().is_err())
+        {
+            return Err(format!("unknown log marker: {}", marker));
+        }
+        if self.max_events_per_sec == 0 || self.max_events_per_sec > MAX_LOG_EVENTS_PER_SEC {
+            return Err(format!(
+                "max events per second must be between 1 and {}",
+                MAX_LOG_EVENTS_PER_SEC
+            ));
+        }
+        Ok(())
+    }
+
+    fn parse_level(&self) -> Result<Level, String> {
+        self.level
+            .parse()
+            .map_err(|_| format!("invalid log level: {}", self.level))
+    }
+
+    // Whether the event, at a level already checked, matches the markers and modules
+    fn matches(&self, event: &LogEvent) -> bool {
+        let marker_matches = self.markers.is_empty()
+            || matches!(&event.marker, Some(marker) if self.markers.contains(marker));
+        let module_matches = self.modules.is_empty()
+            || self.modules.iter().any(|module| {
+                event.module == *module || event.module.starts_with(&format!("{}::", module))
+            });
+
+        marker_matches && module_matches
+    }
+}
+
+/// What a subscriber to the log events of the node is sent.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogStreamItem {
+    /// A log event matching the filter of the subscriber
+    Event(LogEvent),
+    /// Number of events matching the filter of the subscriber which were dropped since the
+    /// last one sent, as they were over its rate limit, or it fell behind
+    Dropped(u64),
+}
+
+/// Streams the log events of the node to subscribers, such as operator tools connected to the
+/// admin socket, as a tracing layer.
+///
+/// Events are only captured while there are subscribers.
+#[derive(Clone)]
+pub struct LogStream {
+    event_tx: broadcast::Sender<Arc<(Level, LogEvent)>>,
+}
+
+impl fmt::Debug for LogStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogStream")
+            .field("subscribers", &self.event_tx.receiver_count())
+            .finish()
+    }
+}
+
+impl Default for LogStream {
+    fn default() -> Self {
+        let (event_tx, _) = broadcast::channel(LOG_STREAM_CAPACITY);
+        Self { event_tx }
+    }
+}
+
+impl LogStream {
+    /// Subscribes to the log events matching the given filter, from now on.
+    pub fn subscribe(&self, filter: LogFilter) -> Result<LogSubscription, String> {
+        filter.validate()?;
+        Ok(LogSubscription {
+            event_rx: self.event_tx.subscribe(),
+            max_level: filter.parse_level()?,
+            filter,
+            window_start: Instant::now(),
+            sent_in_window: 0,
+            dropped: 0,
+            pending: None,
+        })
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogStream {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if self.event_tx.receiver_count() == 0 {
+            return;
+        }
+
+        let metadata = event.metadata();
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let marker = visitor
+            .message
+            .split_whitespace()
+            .next()
+            .and_then(|word| word.parse::<LogMarker>().ok())
+            .map(|marker| marker.to_string());
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        let log_event = LogEvent {
+            timestamp_ms,
+            level: metadata.level().to_string(),
+            module: metadata
+                .module_path()
+                .unwrap_or_else(|| metadata.target())
+                .to_string(),
+            marker,
+            message: visitor.message,
+            fields: visitor.fields,
+        };
+        // there may be no subscribers left meanwhile
+        let _ = self.event_tx.send(Arc::new((*metadata.level(), log_event)));
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: BTreeMap<String, String>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            let _ = self
+                .fields
+                .insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            let _ = self
+                .fields
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+}
+
+/// A subscription to the log events of the node, sending those matching its filter, within its
+/// rate limit.
+#[derive(Debug)]
+pub struct LogSubscription {
+    event_rx: broadcast::Receiver<Arc<(Level, LogEvent)>>,
+    max_level: Level,
+    filter: LogFilter,
+    window_start: Instant,
+    sent_in_window: u32,
+    dropped: u64,
+    // Event to send once the subscriber was told about the ones dropped before it
+    pending: Option<LogEvent>,
+}
+
+impl LogSubscription {
+    /// Waits for the next item to send to the subscriber. Returns `None` once the node stops
+    /// streaming its log events.
+    pub async fn next(&mut self) -> Option<LogStreamItem> {
+        if let Some(log_event) = self.pending.take() {
+            return Some(LogStreamItem::Event(log_event));
+        }
+
+        loop {
+            let event = match self.event_rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(count)) => {
+                    // we can't tell how many of the events missed matched the filter
+                    self.dropped += count;
+                    continue;
+                }
+                Err(RecvError::Closed) if self.dropped > 0 => {
+                    return Some(LogStreamItem::Dropped(std::mem::take(&mut self.dropped)))
+                }
+                Err(RecvError::Closed) => return None,
+            };
+            let (level, log_event) = &*event;
+            if *level > self.max_level || !self.filter.matches(log_event) {
+                continue;
+            }
+
+            if self.window_start.elapsed() >= Duration::from_secs(1) {
+                self.window_start = Instant::now();
+                self.sent_in_window = 0;
+            }
+            if self.sent_in_window >= self.filter.max_events_per_sec {
+                self.dropped += 1;
+                continue;
+            }
+            self.sent_in_window += 1;
+
+            if self.dropped > 0 {
+                // the subscriber learns about the events dropped ahead of the next one sent
+                let dropped = std::mem::take(&mut self.dropped);
+                self.pending = Some(log_event.clone());
+                return Some(LogStreamItem::Dropped(dropped));
+            }
+            return Some(LogStreamItem::Event(log_event.clone()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LogFilter, LogStream, LogStreamItem, MAX_LOG_EVENTS_PER_SEC};
+    use sn_interface::types::log_markers::LogMarker;
+
+    use eyre::{eyre, Result};
+    use std::collections::BTreeSet;
+    use tracing::Dispatch;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn invalid_filters_are_rejected() {
+        let stream = LogStream::default();
+        assert!(stream.subscribe(LogFilter::default()).is_ok());
+
+        let filters = [
+            LogFilter {
+                level: "loud".to_string(),
+                ..Default::default()
+            },
+            LogFilter {
+                markers: BTreeSet::from(["NotAMarker".to_string()]),
+                ..Default::default()
+            },
+            LogFilter {
+                max_events_per_sec: 0,
+                ..Default::default()
+            },
+            LogFilter {
+                max_events_per_sec: MAX_LOG_EVENTS_PER_SEC + 1,
+                ..Default::default()
+            },
+        ];
+        for filter in filters {
+            assert!(stream.subscribe(filter).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn events_over_the_rate_limit_are_dropped_and_counted() -> Result<()> {
+        let stream = LogStream::default();
+        let dispatch = Dispatch::new(tracing_subscriber::registry().with(stream.clone()));
+        let mut subscription = stream.subscribe(LogFilter {
+            level: "debug".to_string(),
+            markers: BTreeSet::from([LogMarker::SendJoinRequest.to_string()]),
+            modules: vec!["sn_node::node".to_string()],
+            max_events_per_sec: 2,
+        })?;
+
+        tracing::dispatcher::with_default(&dispatch, || {
+            tracing::trace!("{:?} too verbose", LogMarker::SendJoinRequest);
+            tracing::info!("{:?} other marker", LogMarker::DkgBroadcastMsg);
+            for attempt in 0..5 {
+                tracing::debug!(attempt, "{:?} to section", LogMarker::SendJoinRequest);
+            }
+            tracing::info!("{:?} last", LogMarker::SendJoinRequest);
+        });
+        // the stream ends once there are no more events
+        drop(dispatch);
+        drop(stream);
+
+        let mut items = vec![];
+        while let Some(item) = subscription.next().await {
+            items.push(item);
+        }
+
+        // all the events are logged within the first second, so all but two of the matching
+        // ones are dropped
+        let attempts = items
+            .iter()
+            .map(|item| match item {
+                LogStreamItem::Event(event) => Ok(event.fields.get("attempt").cloned()),
+                LogStreamItem::Dropped(count) => Err(*count),
+            })
+            .collect::<Vec<_>>();
+        if attempts != vec![Ok(Some("0".to_string())), Ok(Some("1".to_string())), Err(4)] {
+            return Err(eyre!("unexpected items: {:?}", items));
+        }
+
+        Ok(())
+    }
+}
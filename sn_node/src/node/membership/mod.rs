@@ -11,6 +11,8 @@ use sn_interface::{
 use thiserror::Error;
 use xor_name::{Prefix, XorName};
 
+use super::node_age::is_eligible_for_promotion;
+
 use sn_consensus::{
     Ballot, Consensus, Decision, Generation, NodeId, SignedVote, Vote, VoteResponse,
 };
@@ -54,6 +56,7 @@ pub(crate) fn try_split_dkg(
     sap: &SectionAuthorityProvider,
     section_chain_len: u64,
     membership_gen: Generation,
+    data_copy_count: usize,
 ) -> Option<(DkgSessionId, DkgSessionId)> {
     let prefix = sap.prefix();
 
@@ -74,6 +77,7 @@ pub(crate) fn try_split_dkg(
         section_chain_len,
         bootstrap_members: zero,
         membership_gen,
+        data_copy_count,
     };
     let one_id = DkgSessionId {
         prefix: one_prefix,
@@ -81,12 +85,14 @@ pub(crate) fn try_split_dkg(
         section_chain_len,
         bootstrap_members: one,
         membership_gen,
+        data_copy_count,
     };
 
     Some((zero_id, one_id))
 }
 
 /// Returns the nodes that should be candidates to become the next elders, sorted by names.
+/// Only the nodes eligible for promotion given their age are considered.
 pub(crate) fn elder_candidates(
     candidates: impl IntoIterator<Item = NodeState>,
     current_elders: &SectionAuthorityProvider,
@@ -122,6 +128,7 @@ pub(crate) fn elder_candidates(
 
     candidates
         .into_iter()
+        .filter(|node| is_eligible_for_promotion(node.age()))
         .sorted_by(|lhs, rhs| cmp_elder_candidates(lhs, rhs, current_elders))
         .take(sn_interface::elder_count())
         .collect()
@@ -9,7 +9,7 @@
 use crate::node::{Error, Result};
 use sn_interface::messaging::{
     system::{SigShare, SystemMsg},
-    AuthKind, AuthorityProof, BlsShareAuth, DstLocation, MsgId, NodeAuth, WireMsg,
+    AuthKind, AuthorityProof, BlsShareAuth, DstLocation, MsgId, NodeAuth, SigningDomain, WireMsg,
 };
 
 use bls::PublicKey as BlsPublicKey;
@@ -52,7 +52,8 @@ impl WireMsgUtils for WireMsg {
             bls_share_authorize(src_section_pk, src_name, key_share, &msg_payload).into_inner(),
         );
 
-        let wire_msg = WireMsg::new_msg(MsgId::new(), msg_payload, msg_kind, dst)?;
+        let wire_msg =
+            WireMsg::new_system_msg(MsgId::new(), &node_msg, msg_payload, msg_kind, dst)?;
 
         #[cfg(test)]
         let wire_msg = wire_msg.set_payload_debug(node_msg);
@@ -74,7 +75,8 @@ impl WireMsgUtils for WireMsg {
             NodeAuth::authorize(src_section_pk, &node.keypair, &msg_payload).into_inner(),
         );
 
-        let wire_msg = WireMsg::new_msg(MsgId::new(), msg_payload, msg_kind, dst)?;
+        let wire_msg =
+            WireMsg::new_system_msg(MsgId::new(), &node_msg, msg_payload, msg_kind, dst)?;
 
         #[cfg(test)]
         let wire_msg = wire_msg.set_payload_debug(node_msg);
@@ -96,7 +98,9 @@ fn bls_share_authorize(
         sig_share: SigShare {
             public_key_set: key_share.public_key_set.clone(),
             index: key_share.index,
-            signature_share: key_share.secret_key_share.sign(payload),
+            signature_share: key_share
+                .secret_key_share
+                .sign(SigningDomain::Section.signable_bytes(payload)),
         },
     })
 }
@@ -8,6 +8,7 @@
 
 //! Implementation of the "Node" node for the SAFE Network.
 
+mod admin;
 /// Node Configuration
 pub mod cfg;
 // Node public API
@@ -15,7 +16,11 @@ mod api;
 
 mod core;
 
-pub use self::core::DataStorage;
+pub use self::core::{
+    AdultParticipation, ClientRateLimits, DataStorage, InboundRateLimits, LivenessConfig,
+    QueryCacheStats, QueryDelegationConfig, RecoveryReport, RoutingTable, RoutingTableEntry,
+    ScrubProgress, SigningStats, DEFAULT_LAN_DISCOVERY_PORT,
+};
 
 mod dkg;
 // mod ed25519;
@@ -24,19 +29,34 @@ pub(crate) mod handover;
 mod logging;
 pub(crate) mod membership;
 mod messages;
+pub(crate) mod node_age;
+mod notifier;
 
 use sn_interface::types::Peer;
 
 pub use self::{
+    admin::{AdminNodeHandle, AdminSocket},
     api::{
+        dispatcher::{Incident, Subsystem},
         event::{Elders, Event, MessageReceived, NodeElderChange},
         event_stream::EventStream,
+        status::{BootstrapState, NodeStatus},
         NodeApi,
     },
-    cfg::config_handler::{add_connection_info, set_connection_info, Config},
+    cfg::{
+        bootstrap_retry::BootstrapRetries,
+        config_handler::{add_connection_info, set_connection_info, Config},
+        live_config::LiveConfig,
+    },
     error::{Error, Result},
+    logging::stream::{
+        LogEvent, LogFilter, LogStream, LogStreamItem, LogSubscription, DEFAULT_LOG_EVENTS_PER_SEC,
+        MAX_LOG_EVENTS_PER_SEC,
+    },
+    notifier::{EldersChange, Notifier, NotifierEvent, NotifierEventKind, NotifierTarget},
 };
 pub use qp2p::{Config as NetworkConfig, SendStream};
+pub use sn_interface::messaging::system::Reachability;
 pub use sn_interface::network_knowledge::{
     FIRST_SECTION_MAX_AGE, FIRST_SECTION_MIN_AGE, MIN_ADULT_AGE,
 };
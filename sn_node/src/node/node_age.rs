@@ -0,0 +1,164 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Node aging: how the age of a node grows with the churn events of its section, and what it
+//! makes the node eligible for.
+//!
+//! A node joins with the age it's given, held in the last byte of its name, and only ages by
+//! being relocated. It's a candidate for relocation on the churn events whose decision signature
+//! has at least as many trailing zero bits as its age, so the older a node, the less likely each
+//! churn event is to relocate it, and the more churn it has seen. Being derived from signatures,
+//! which nodes can't predict, these decisions can't be gamed, yet are the same for all Elders.
+
+use sn_interface::network_knowledge::MIN_ADULT_AGE;
+
+use std::fmt::{self, Display, Formatter};
+use xor_name::XorName;
+
+// Unique identifier for a churn event, which is used to select nodes to relocate.
+pub(crate) struct ChurnId(pub(crate) Vec<u8>);
+
+impl Display for ChurnId {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "Churn-{:02x}{:02x}{:02x}..",
+            self.0[0], self.0[1], self.0[2]
+        )
+    }
+}
+
+/// Whether a member with the given age is a candidate for relocation on a churn event with the
+/// given churn id.
+pub(crate) fn is_relocation_candidate(age: u8, churn_id: &ChurnId) -> bool {
+    // Evaluate the formula: `signature % 2^age == 0` Which is the same as checking the signature
+    // has at least `age` trailing zero bits.
+    trailing_zeros(&churn_id.0) >= age as u32
+}
+
+/// Age a member with the given age gets once relocated on a churn event with the given churn id,
+/// if that's an event it's a candidate for relocation on. This is the only way a node ages.
+pub(crate) fn age_after_relocation(age: u8, churn_id: &ChurnId) -> Option<u8> {
+    is_relocation_candidate(age, churn_id).then(|| age.saturating_add(1))
+}
+
+/// Destination of the node with `relocating_name` relocated on a churn event with the given
+/// churn id.
+pub(crate) fn relocation_dst(relocating_name: &XorName, churn_id: &ChurnId) -> XorName {
+    XorName::from_content_parts(&[&relocating_name.0, &churn_id.0])
+}
+
+/// Whether a member with the given age can be promoted to Elder. Only adults can.
+pub(crate) fn is_eligible_for_promotion(age: u8) -> bool {
+    age >= MIN_ADULT_AGE
+}
+
+// Returns the number of trailing zero bits of the bytes slice.
+fn trailing_zeros(bytes: &[u8]) -> u32 {
+    let mut output = 0;
+
+    for &byte in bytes.iter().rev() {
+        if byte == 0 {
+            output += 8;
+        } else {
+            output += byte.trailing_zeros();
+            break;
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    #[test]
+    fn byte_slice_trailing_zeros() {
+        assert_eq!(trailing_zeros(&[0]), 8);
+        assert_eq!(trailing_zeros(&[1]), 0);
+        assert_eq!(trailing_zeros(&[2]), 1);
+        assert_eq!(trailing_zeros(&[4]), 2);
+        assert_eq!(trailing_zeros(&[8]), 3);
+        assert_eq!(trailing_zeros(&[0, 0]), 16);
+        assert_eq!(trailing_zeros(&[1, 0]), 8);
+        assert_eq!(trailing_zeros(&[2, 0]), 9);
+    }
+
+    // A churn event whose id has the given number of trailing zero bits
+    fn churn_with_trailing_zeros(mut bytes: Vec<u8>, count: u8) -> ChurnId {
+        let count = count as usize;
+        for (index, byte) in bytes.iter_mut().rev().enumerate() {
+            if index < count / 8 {
+                *byte = 0;
+            } else if index == count / 8 {
+                *byte = (*byte | 1) << (count % 8);
+            }
+        }
+        ChurnId(bytes)
+    }
+
+    proptest! {
+        #[test]
+        fn age_only_grows_by_relocation_on_valid_churn_events(
+            initial_age in MIN_ADULT_AGE..16,
+            events in prop::collection::vec(
+                (prop::collection::vec(any::<u8>(), 96), 0..24u8, any::<bool>()),
+                1..100,
+            )
+        ) {
+            let mut age = initial_age;
+            for (bytes, trailing_zeros_count, relocated) in events {
+                let churn_id = churn_with_trailing_zeros(bytes, trailing_zeros_count);
+                prop_assert_eq!(trailing_zeros(&churn_id.0), trailing_zeros_count as u32);
+
+                // a node is a candidate when the churn id has at least as many trailing zero
+                // bits as its age, and gets one year older when relocated then
+                let is_candidate = trailing_zeros_count >= age;
+                prop_assert_eq!(is_relocation_candidate(age, &churn_id), is_candidate);
+                let expected_age = if relocated && is_candidate { age + 1 } else { age };
+
+                if relocated {
+                    match age_after_relocation(age, &churn_id) {
+                        Some(new_age) => {
+                            prop_assert!(is_candidate);
+                            age = new_age;
+                        }
+                        None => prop_assert!(!is_candidate),
+                    }
+                }
+
+                prop_assert_eq!(age, expected_age);
+                prop_assert!(is_eligible_for_promotion(age));
+            }
+        }
+    }
+
+    #[test]
+    fn relocation_candidates_at_age_boundaries() {
+        let churn_id = churn_with_trailing_zeros(vec![0xff; 96], 10);
+        assert!(is_relocation_candidate(9, &churn_id));
+        assert!(is_relocation_candidate(10, &churn_id));
+        assert!(!is_relocation_candidate(11, &churn_id));
+        assert_eq!(age_after_relocation(10, &churn_id), Some(11));
+        assert_eq!(age_after_relocation(11, &churn_id), None);
+
+        // the oldest nodes stay as old as they can be
+        let all_zeros = ChurnId(vec![0; 96]);
+        assert_eq!(age_after_relocation(u8::MAX, &all_zeros), Some(u8::MAX));
+    }
+
+    #[test]
+    fn only_adults_are_eligible_for_promotion() {
+        assert!(!is_eligible_for_promotion(MIN_ADULT_AGE - 1));
+        assert!(is_eligible_for_promotion(MIN_ADULT_AGE));
+        assert!(is_eligible_for_promotion(u8::MAX));
+    }
+}
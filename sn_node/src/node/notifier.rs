@@ -0,0 +1,397 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::{Elders, Error, Event, NodeElderChange, Result};
+
+use backoff::{backoff::Backoff, ExponentialBackoff};
+use serde::{Deserialize, Serialize};
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::{
+    collections::BTreeSet,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+#[cfg(unix)]
+use tokio::net::UnixStream;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    sync::mpsc,
+    time::{sleep, timeout},
+};
+use url::Url;
+use xor_name::XorName;
+
+// Number of events held while the ones before them are being delivered, beyond which new
+// events are dropped
+const NOTIFIER_QUEUE_CAPACITY: usize = 256;
+// How long a single delivery attempt may take, from connecting to being replied to
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+// How long an event is retried for before it's given up on
+const MAX_DELIVERY_TIME: Duration = Duration::from_secs(5 * 60);
+
+/// Where the [`Notifier`] delivers the events of the node to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NotifierTarget {
+    /// URL each event is POSTed to, as JSON. Only plain `http` URLs are supported, so the
+    /// endpoint is expected to be local, or reached through a proxy.
+    Http(Url),
+    /// Unix socket each event is written to, as a line of JSON, over a new connection.
+    #[cfg(unix)]
+    UnixSocket(PathBuf),
+}
+
+impl NotifierTarget {
+    /// Parses the URL of a webhook, returning why it isn't supported otherwise.
+    pub fn http(url: &str) -> Result<Self, String> {
+        let url = Url::parse(url).map_err(|err| format!("invalid URL {}: {}", url, err))?;
+        if url.scheme() != "http" || url.host_str().is_none() {
+            return Err(format!(
+                "only http URLs with a host are supported, not {}",
+                url
+            ));
+        }
+        Ok(Self::Http(url))
+    }
+}
+
+/// A membership decision or Elder change of the section of the node, as delivered by the
+/// [`Notifier`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NotifierEvent {
+    /// When the event was raised, in milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    /// What happened
+    #[serde(flatten)]
+    pub kind: NotifierEventKind,
+}
+
+/// What a [`NotifierEvent`] is about. Names and keys are hex encoded.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum NotifierEventKind {
+    /// The section decided a node joined it.
+    MemberJoined {
+        /// Name of the node
+        name: String,
+        /// Name of the node before it was relocated, if it was
+        previous_name: Option<String>,
+        /// Age of the node
+        age: u8,
+    },
+    /// The section decided a node left it, or was relocated away.
+    MemberLeft {
+        /// Name of the node
+        name: String,
+        /// Age of the node
+        age: u8,
+    },
+    /// The Elders of the section changed.
+    EldersChanged(EldersChange),
+    /// The section split, the Elders being those of the half the node is in.
+    SectionSplit(EldersChange),
+}
+
+/// Elders of the section after a change, as carried by a [`NotifierEvent`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EldersChange {
+    /// Prefix of the section, as a string of bits
+    pub prefix: String,
+    /// Key of the section
+    pub section_key: String,
+    /// Elders which were already Elders before the change
+    pub remaining: BTreeSet<String>,
+    /// Elders which weren't before the change
+    pub added: BTreeSet<String>,
+    /// Elders which aren't anymore
+    pub removed: BTreeSet<String>,
+    /// Whether this node was "promoted", "demoted", or stayed as it was, "none"
+    pub self_status_change: String,
+}
+
+impl EldersChange {
+    fn new(elders: &Elders, self_status_change: &NodeElderChange) -> Self {
+        let names =
+            |names: &BTreeSet<XorName>| names.iter().map(|name| hex::encode(name.0)).collect();
+        let self_status_change = match self_status_change {
+            NodeElderChange::Promoted => "promoted",
+            NodeElderChange::Demoted => "demoted",
+            NodeElderChange::None => "none",
+        };
+
+        Self {
+            prefix: (0..elders.prefix.bit_count())
+                .map(|bit| {
+                    if elders.prefix.name().bit(bit as u8) {
+                        '1'
+                    } else {
+                        '0'
+                    }
+                })
+                .collect(),
+            section_key: hex::encode(elders.key.to_bytes()),
+            remaining: names(&elders.remaining),
+            added: names(&elders.added),
+            removed: names(&elders.removed),
+            self_status_change: self_status_change.to_string(),
+        }
+    }
+}
+
+impl NotifierEventKind {
+    /// The kind of the given event of the node, if it's one the notifier delivers.
+    pub fn of(event: &Event) -> Option<Self> {
+        let kind = match event {
+            Event::MemberJoined {
+                name,
+                previous_name,
+                age,
+            } => Self::MemberJoined {
+                name: hex::encode(name.0),
+                previous_name: previous_name.map(|name| hex::encode(name.0)),
+                age: *age,
+            },
+            Event::MemberLeft { name, age } => Self::MemberLeft {
+                name: hex::encode(name.0),
+                age: *age,
+            },
+            Event::EldersChanged {
+                elders,
+                self_status_change,
+            } => Self::EldersChanged(EldersChange::new(elders, self_status_change)),
+            Event::SectionSplit {
+                elders,
+                self_status_change,
+            } => Self::SectionSplit(EldersChange::new(elders, self_status_change)),
+            _ => return None,
+        };
+        Some(kind)
+    }
+}
+
+/// Delivers the membership decisions and Elder changes of the section of the node to an
+/// external endpoint, e.g. a dashboard or alerting system, as they're raised as [`Event`]s.
+///
+/// Events are delivered in order, each being retried with an exponential backoff until it's
+/// delivered or has been retried for a few minutes. Events raised while the queue of those
+/// waiting to be delivered is full are dropped, so a slow endpoint never holds the node up.
+#[derive(Debug, Clone)]
+pub struct Notifier {
+    event_tx: mpsc::Sender<NotifierEvent>,
+}
+
+impl Notifier {
+    /// Starts delivering the events notified to the given target.
+    pub fn start(target: NotifierTarget) -> Self {
+        let (event_tx, mut event_rx) = mpsc::channel::<NotifierEvent>(NOTIFIER_QUEUE_CAPACITY);
+        let _handle = tokio::spawn(async move {
+            while let Some(event) = event_rx.recv().await {
+                deliver_with_retries(&target, &event).await;
+            }
+        });
+        Self { event_tx }
+    }
+
+    /// Queues the given event of the node for delivery, if it's one the notifier delivers.
+    pub fn notify(&self, event: &Event) {
+        let kind = match NotifierEventKind::of(event) {
+            Some(kind) => kind,
+            None => return,
+        };
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        if let Err(error) = self.event_tx.try_send(NotifierEvent { timestamp_ms, kind }) {
+            warn!("Dropping event to notify: {:?}", error);
+        }
+    }
+}
+
+async fn deliver_with_retries(target: &NotifierTarget, event: &NotifierEvent) {
+    let mut backoff = ExponentialBackoff {
+        initial_interval: Duration::from_millis(500),
+        max_interval: Duration::from_secs(30),
+        max_elapsed_time: Some(MAX_DELIVERY_TIME),
+        ..Default::default()
+    };
+    // this seems needed for custom settings to take effect
+    backoff.reset();
+
+    loop {
+        let result = match timeout(DELIVERY_TIMEOUT, deliver(target, event)).await {
+            Ok(result) => result,
+            Err(_) => Err(Error::NotificationFailed("delivery timed out".to_string())),
+        };
+        let error = match result {
+            Ok(()) => {
+                trace!("Notified {:?} to {:?}", event, target);
+                return;
+            }
+            Err(error) => error,
+        };
+
+        match backoff.next_backoff() {
+            Some(wait) => {
+                debug!(
+                    "Failed to notify {:?}, retrying in {:?}: {:?}",
+                    target, wait, error
+                );
+                sleep(wait).await;
+            }
+            None => {
+                error!(
+                    "Giving up notifying {:?} to {:?}: {:?}",
+                    event, target, error
+                );
+                return;
+            }
+        }
+    }
+}
+
+async fn deliver(target: &NotifierTarget, event: &NotifierEvent) -> Result<()> {
+    let body = serde_json::to_vec(event)?;
+    match target {
+        NotifierTarget::Http(url) => post(url, &body).await,
+        #[cfg(unix)]
+        NotifierTarget::UnixSocket(path) => {
+            let mut stream = UnixStream::connect(path).await?;
+            stream.write_all(&body).await?;
+            stream.write_all(b"\n").await?;
+            stream.shutdown().await?;
+            Ok(())
+        }
+    }
+}
+
+// POSTs the body to the URL over plain HTTP/1.1, succeeding when replied to with a 2xx status
+async fn post(url: &Url, body: &[u8]) -> Result<()> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| Error::NotificationFailed(format!("no host in {}", url)))?;
+    let port = url.port_or_known_default().unwrap_or(80);
+    let path = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+
+    // IPv6 hosts are bracketed in URLs
+    let ip_or_domain = host.trim_start_matches('[').trim_end_matches(']');
+    let mut stream = TcpStream::connect((ip_or_domain, port)).await?;
+    let head = format!(
+        "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\n\
+        Content-Length: {}\r\nConnection: close\r\n\r\n",
+        path,
+        host,
+        port,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body).await?;
+
+    let mut status_line = String::new();
+    let _ = BufReader::new(stream).read_line(&mut status_line).await?;
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(()),
+        _ => Err(Error::NotificationFailed(format!(
+            "webhook replied with {:?}",
+            status_line.trim_end()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Notifier, NotifierEvent, NotifierEventKind, NotifierTarget};
+    use crate::node::{Elders, Event, NodeElderChange};
+
+    use eyre::{eyre, Result};
+    use std::collections::BTreeSet;
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+        time::{timeout, Duration},
+    };
+    use xor_name::Prefix;
+
+    #[test]
+    fn only_membership_and_elder_events_are_notified() -> Result<()> {
+        let name = xor_name::rand::random();
+        let elders = Elders {
+            prefix: Prefix::default().pushed(true),
+            key: bls::SecretKey::random().public_key(),
+            remaining: BTreeSet::new(),
+            added: BTreeSet::from([name]),
+            removed: BTreeSet::new(),
+        };
+
+        match NotifierEventKind::of(&Event::SectionSplit {
+            elders,
+            self_status_change: NodeElderChange::Promoted,
+        }) {
+            Some(NotifierEventKind::SectionSplit(change)) => {
+                assert_eq!(change.prefix, "1");
+                assert_eq!(change.added, BTreeSet::from([hex::encode(name.0)]));
+                assert_eq!(change.self_status_change, "promoted");
+            }
+            other => return Err(eyre!("unexpected kind: {:?}", other)),
+        }
+        assert!(NotifierEventKind::of(&Event::ChurnJoinMissError).is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn failed_deliveries_are_retried() -> Result<()> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let url = format!("http://{}/membership", listener.local_addr()?);
+        let notifier = Notifier::start(NotifierTarget::http(&url).map_err(|err| eyre!(err))?);
+
+        let name = xor_name::rand::random();
+        notifier.notify(&Event::MemberLeft { name, age: 7 });
+
+        // the first delivery is refused, the event then being delivered again
+        let mut bodies = vec![];
+        for status in ["503 Service Unavailable", "200 OK"] {
+            let (stream, _) = timeout(Duration::from_secs(10), listener.accept()).await??;
+            let mut reader = BufReader::new(stream);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                let _ = reader.read_line(&mut line).await?;
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some(length) = line.strip_prefix("Content-Length: ") {
+                    content_length = length.trim().parse()?;
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).await?;
+            bodies.push(serde_json::from_slice::<NotifierEvent>(&body)?);
+            reader
+                .into_inner()
+                .write_all(format!("HTTP/1.1 {}\r\n\r\n", status).as_bytes())
+                .await?;
+        }
+
+        assert_eq!(bodies.len(), 2);
+        assert_eq!(bodies[0], bodies[1]);
+        assert_eq!(
+            bodies[0].kind,
+            NotifierEventKind::MemberLeft {
+                name: hex::encode(name.0),
+                age: 7
+            }
+        );
+
+        Ok(())
+    }
+}